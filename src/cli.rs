@@ -1,25 +1,37 @@
-use crate::config::{ResolvedConfig, ResolvedConfigFile};
-use crate::downloader::{download_files, fetch_all_links, filter_periods_by_range};
+use crate::config::{PipelineConfigFile, ResolvedConfig, ResolvedConfigFile};
+use crate::converter::{convert_parquet, ConvertFormat};
+use crate::downloader::{current_month, recent_periods};
 use crate::errors::{AppError, AppResult};
-use crate::extractor::extract_all_zips;
-use crate::models::ProcurementType;
-use crate::parser::{cleanup_files, parse_xmls};
-use clap::{Arg, ArgAction, Command};
-use std::collections::BTreeMap;
-use std::path::PathBuf;
-use tracing::info;
+use crate::models::{ProcurementType, RunSummary};
+use crate::parser::{check_parquet_dir, output_schema, parquet_diff, parquet_stats, period_output};
+use crate::pipeline::{
+    self, available_links, plan_run, run_download_only, run_parse_only, watch_new_periods,
+    PipelineOptions,
+};
+use crate::state::{resume_point, ResumePoint};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::level_filters::LevelFilter;
+use tracing::{info, info_span, warn, Instrument};
 
 // CLI metadata constants
-const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
+pub(crate) const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
 const APP_AUTHOR: &str = env!("CARGO_PKG_AUTHORS");
 const APP_ABOUT: &str = env!("CARGO_PKG_DESCRIPTION");
 /// Parses command-line arguments and executes the download command.
 ///
-/// This function handles two subcommands:
+/// This function handles these subcommands:
 /// - `cli`: Manual CLI with default configuration (cleanup always enabled)
 /// - `toml`: Run using a TOML configuration file (cleanup configurable)
+/// - `parse`: Parse already-extracted XML/Atom files offline (no network access)
+/// - `check`: Verify that existing Parquet output files are readable and complete
+/// - `stats`: Summarize one Parquet output
+/// - `query`: Run SQL against the Parquet output (`duckdb` feature)
 ///
-/// Both subcommands fetch available download links (if running the workflow), then execute:
+/// The `cli` and `toml` subcommands fetch available download links (unless `cli --parse-only`
+/// is given, which behaves like `parse`), then execute:
 /// 1. Parses CLI arguments (procurement type, period range, cleanup options)
 /// 2. Filters available links by the specified period range
 /// 3. Downloads ZIP files from the filtered URLs
@@ -36,10 +48,110 @@ const APP_ABOUT: &str = env!("CARGO_PKG_DESCRIPTION");
 /// - XML parsing fails
 ///
 pub async fn cli() -> AppResult<()> {
-    let cmd = Command::new("sppd-cli")
+    let cmd = command();
+    let mut cmd_for_help = cmd.clone();
+    let matches = cmd.get_matches();
+
+    let _tracing_guard = crate::telemetry::init_tracing(
+        matches
+            .get_one::<String>("otel_endpoint")
+            .map(|s| s.as_str()),
+        matches
+            .get_one::<String>("log_format")
+            .expect("log_format has default_value")
+            .parse()?,
+        matches.get_one::<PathBuf>("log_file").map(PathBuf::as_path),
+        default_log_level(&matches),
+    )?;
+
+    start_metrics_server(
+        matches
+            .get_one::<std::net::SocketAddr>("metrics_addr")
+            .copied(),
+    )
+    .await?;
+
+    run_subcommand(&matches, &mut cmd_for_help)
+        .instrument(info_span!("main"))
+        .await
+}
+
+/// Builds the `sppd-cli` command with its `cli`, `parse`, `toml`, `check`, `stats`, and `query` subcommands.
+fn command() -> Command<'static> {
+    Command::new("sppd-cli")
         .version(APP_VERSION)
         .author(APP_AUTHOR)
         .about(APP_ABOUT)
+        .arg(
+            Arg::new("otel_endpoint")
+                .long("otel-endpoint")
+                .help("Export tracing spans to this OTLP/HTTP endpoint (requires the `otel` feature)")
+                .global(true)
+                .action(ArgAction::Set),
+        )
+        .arg(
+            Arg::new("log_format")
+                .long("log-format")
+                .value_name("FORMAT")
+                .help("Log line format: text (default) or json (one object per line)")
+                .global(true)
+                .value_parser(["text", "json"])
+                .default_value("text")
+                .action(ArgAction::Set),
+        )
+        .arg(
+            Arg::new("log_file")
+                .long("log-file")
+                .value_name("PATH")
+                .help("Append logs to this file instead of stdout (requires the `file-logging` feature)")
+                .global(true)
+                .value_parser(clap::value_parser!(PathBuf))
+                .action(ArgAction::Set),
+        )
+        .arg(
+            Arg::new("data_dir")
+                .long("data-dir")
+                .value_name("PATH")
+                .help("Root directory for downloads, extracted files, Parquet output, and run state (default: data); relative paths from --config-file or the TOML config resolve against it")
+                .global(true)
+                .value_parser(clap::value_parser!(PathBuf))
+                .action(ArgAction::Set),
+        )
+        .arg(
+            Arg::new("output_dir")
+                .long("output-dir")
+                .value_name("PATH")
+                .help("Directory for Parquet output (default: <data-dir>/parquet); a relative path resolves against --data-dir when given")
+                .global(true)
+                .value_parser(clap::value_parser!(PathBuf))
+                .action(ArgAction::Set),
+        )
+        .arg(
+            Arg::new("metrics_addr")
+                .long("metrics-addr")
+                .value_name("ADDR")
+                .help("Serve Prometheus metrics on /metrics at this address, e.g. 0.0.0.0:9090 (requires the `metrics` feature)")
+                .global(true)
+                .value_parser(clap::value_parser!(std::net::SocketAddr))
+                .action(ArgAction::Set),
+        )
+        .arg(
+            Arg::new("quiet")
+                .short('q')
+                .long("quiet")
+                .help("Hide progress bars and only log warnings and errors (unless RUST_LOG is set); progress bars are also hidden automatically when output is not a terminal")
+                .global(true)
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("verbose")
+                .short('v')
+                .long("verbose")
+                .help("Log debug messages too (unless RUST_LOG is set)")
+                .global(true)
+                .conflicts_with("quiet")
+                .action(ArgAction::SetTrue),
+        )
         .subcommand(
             Command::new("cli")
                 .about("Download, extract, parse, and clean a period range")
@@ -48,7 +160,7 @@ pub async fn cli() -> AppResult<()> {
                     Arg::new("type")
                         .short('t')
                         .long("type")
-                        .help("Procurement type: 'minor-contracts' (mc, min) or 'public-tenders' (pt, pub)")
+                        .help("Procurement type: 'minor-contracts' (mc, min), 'public-tenders' (pt, pub), or 'aggregated-platforms' (agg, agregadas)")
                         .default_value("public-tenders")
                         .action(ArgAction::Set),
                 )
@@ -56,61 +168,843 @@ pub async fn cli() -> AppResult<()> {
                     Arg::new("start")
                         .short('s')
                         .long("start")
-                        .help("First period to download and parse (YYYY or YYYYMM)")
+                        .help("First period to download and parse (YYYY, YYYYMM, or YYYYQn)")
                         .action(ArgAction::Set),
                 )
                 .arg(
                     Arg::new("end")
                         .short('e')
                         .long("end")
-                        .help("Last period to download and parse (YYYY or YYYYMM)")
+                        .help("Last period to download and parse (YYYY, YYYYMM, or YYYYQn)")
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("read_concurrency")
+                        .short('r')
+                        .long("read-concurrency")
+                        .alias("rc")
+                        .help("Files read in parallel while parsing XML (default: 16)")
+                        .value_parser(clap::value_parser!(usize))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("batch_size")
+                        .short('b')
+                        .long("batch-size")
+                        .alias("bs")
+                        .help("Number of XML files to parse per batch (default 500, minimum 1); smaller batches lower peak memory but write more Parquet files")
+                        .value_parser(clap::value_parser!(usize))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("parser_threads")
+                        .long("parser-threads")
+                        .alias("pt")
+                        .help("Number of threads for XML parsing rayon pool (0 = auto-detect, useful in Docker)")
+                        .value_parser(clap::value_parser!(usize))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("parallel_periods")
+                        .long("parallel-periods")
+                        .value_name("N")
+                        .help("Number of periods parsed concurrently, sharing the parser threads (default: 1)")
+                        .value_parser(clap::value_parser!(usize))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("workers")
+                        .long("workers")
+                        .value_name("N")
+                        .help("XML parsing worker threads (0 = auto-detect; capped at the number of logical CPUs)")
+                        .conflicts_with("parser_threads")
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("parse_only")
+                        .long("parse-only")
+                        .help("Skip download and extraction; parse the periods already extracted locally")
+                        .conflicts_with("since_last_run")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("download_only")
+                        .long("download-only")
+                        .help("Stop after downloading the ZIP archives (no extraction, parsing, or cleanup)")
+                        .conflicts_with("parse_only")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("periods_in_flight")
+                        .long("periods-in-flight")
+                        .value_name("N")
+                        .help("Take each period through download, extraction, parsing, and cleanup on its own, N periods at a time, instead of finishing each stage for all periods first")
+                        .value_parser(clap::value_parser!(usize))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("dry_run")
+                        .long("dry-run")
+                        .help("Print what would be downloaded, extracted, and overwritten for each period, without downloading or writing anything")
+                        .conflicts_with("parse_only")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("watch")
+                        .long("watch")
+                        .help("After the run, keep polling for new periods and process them as they appear; stop on SIGINT or SIGTERM once the current run finishes")
+                        .conflicts_with_all(&["parse_only", "download_only", "dry_run", "no_parse"])
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("watch_interval")
+                        .long("watch-interval")
+                        .value_name("SECONDS")
+                        .help("Seconds between polls with --watch")
+                        .default_value("3600")
+                        .requires("watch")
+                        .value_parser(clap::value_parser!(u64).range(1..))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("no_download")
+                        .long("no-download")
+                        .help("Skip downloading; use the ZIPs in the download directory (or, with --no-extract, the directories already extracted)")
+                        .conflicts_with_all(&["parse_only", "download_only", "source_dir"])
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("no_extract")
+                        .long("no-extract")
+                        .help("Skip ZIP extraction; parse the directories already extracted")
+                        .conflicts_with_all(&["parse_only", "download_only"])
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("no_parse")
+                        .long("no-parse")
+                        .help("Stop before parsing; downloaded and extracted files are kept (no cleanup)")
+                        .conflicts_with_all(&["parse_only", "download_only"])
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("since_last_run")
+                        .long("since-last-run")
+                        .help("Start after the last period completed by a previous successful run")
+                        .conflicts_with("start")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("last_months")
+                        .long("last-months")
+                        .value_name("N")
+                        .help("Process the N most recent monthly periods up to the current month (fewer if not that many are available)")
+                        .conflicts_with_all(&["start", "end", "since_last_run", "parse_only"])
+                        .value_parser(clap::value_parser!(u32).range(1..))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("source_dir")
+                        .long("source-dir")
+                        .value_name("PATH")
+                        .help("Read period ZIPs from a local directory instead of downloading them")
+                        .conflicts_with("parse_only")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("proxy")
+                        .long("proxy")
+                        .value_name("URL")
+                        .help("Send every request through this proxy (default: HTTPS_PROXY/HTTP_PROXY from the environment); an empty URL disables proxies")
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("max_rps")
+                        .long("max-rps")
+                        .value_name("RATE")
+                        .help("Start at most RATE download requests per second across all concurrent downloads (e.g. 0.5)")
+                        .value_parser(clap::value_parser!(f64))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("max_bytes_per_sec")
+                        .long("max-bytes-per-sec")
+                        .value_name("BYTES")
+                        .help("Cap the combined download bandwidth of all concurrent downloads at BYTES per second")
+                        .value_parser(clap::value_parser!(u64))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("fail_fast")
+                        .long("fail-fast")
+                        .help("Cancel the remaining downloads as soon as one file fails (after its retries)")
+                        .conflicts_with("keep_going")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("continue_on_error")
+                        .long("continue-on-error")
+                        .help("Finish every download even when some fail, then exit non-zero if any did (default)")
+                        .conflicts_with("fail_fast")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("verify_remote_checksum")
+                        .long("verify-remote-checksum")
+                        .help("Check each downloaded ZIP against the .sha256 or .md5 file published next to it, when there is one")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("links_cache_ttl")
+                        .long("links-cache-ttl")
+                        .value_name("SECONDS")
+                        .help("Reuse the landing page links cached in <data-dir>/cache for this many seconds (default: 21600, i.e. 6 hours); 0 disables the cache")
+                        .value_parser(clap::value_parser!(u64))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("refresh_links")
+                        .long("refresh-links")
+                        .help("Fetch the landing pages even when the links cache is fresh, and rewrite it")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("ca_cert")
+                        .long("ca-cert")
+                        .value_name("PATH")
+                        .help("PEM bundle of extra CA certificates to trust for HTTPS")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("user_agent")
+                        .long("user-agent")
+                        .value_name("UA")
+                        .help("User agent sent with every request (default: sppd-cli/<version>)")
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("no_cleanup")
+                        .long("no-cleanup")
+                        .help("Skip cleanup of downloaded ZIP and extracted files")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("concat_batches")
+                        .short('c')
+                        .long("concat-batches")
+                        .alias("cb")
+                        .help("Merge the per-batch parquet files after parsing (caution: high memory for large periods)")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("config_file")
+                        .long("config-file")
+                        .alias("config")
+                        .value_name("PATH")
+                        .help("Load pipeline settings from a TOML file (default: ./sppd-cli.toml if present); flags given on the command line take precedence")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .help("Output file format: parquet (default) or jsonl (newline-delimited JSON)")
+                        .value_parser(["parquet", "jsonl"])
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("compression")
+                        .long("compression")
+                        .alias("parquet-compression")
+                        .help("Parquet compression codec: zstd (default), snappy, lz4, or uncompressed (also: none)")
+                        .value_parser(["zstd", "snappy", "lz4", "uncompressed", "none"])
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("checksums")
+                        .long("checksums")
+                        .help("Write a {period}.sha256 file (sha256sum format) next to each period's output: yes or no (default)")
+                        .value_parser(["yes", "no"])
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("to_duckdb")
+                        .long("to-duckdb")
+                        .value_name("PATH")
+                        .help("Also load each parsed period into the DuckDB database at PATH, as entries, project_lots, tender_results, and awarding_criteria tables (requires the `duckdb` feature)")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("dedup")
+                        .long("dedup")
+                        .help("Deduplicate the concatenated period file: latest (keep the most recently updated row per contract) or none (default)")
+                        .value_parser(["latest", "none"])
+                        .requires("concat_batches")
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("dedupe_by_id")
+                        .long("dedupe-by-id")
+                        .alias("dedupe")
+                        .help("Drop entries whose id already appeared in the same period, keeping the last occurrence (entries without an id are kept)")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("join_lot_results")
+                        .long("join-lot-results")
+                        .help("Also write {period}_lots.parquet with one row per lot joined with its tender result (Parquet output only)")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("single_file_stream")
+                        .long("single-file-stream")
+                        .help("Stream each period into one Parquet file, appending a row group per batch (no batch files or concat pass; --concat-batches and --dedup are ignored)")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("max_entries_per_file")
+                        .long("max-entries-per-file")
+                        .value_name("N")
+                        .help("Split Parquet batch files over N rows into batch_{i}_{j}.parquet shards of at most N rows")
+                        .value_parser(clap::value_parser!(usize))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("incremental")
+                        .long("incremental")
+                        .help("Keep each period's batch directory and only parse the XML files not listed in its .processed_files.json manifest, appending them as new batches")
+                        .conflicts_with_all(&["single_file_stream", "partitioned"])
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("cache_dir")
+                        .long("cache-dir")
+                        .value_name("PATH")
+                        .help("Cache parsed XML documents in this directory by content hash and reuse them on later runs")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("strict_period_dirs")
+                        .long("strict-period-dirs")
+                        .help("Only parse extracted directories named exactly after their period (no case-insensitive or fuzzy matching)")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("keep_going")
+                        .long("keep-going")
+                        .help("Continue with the remaining periods when one fails; exits with code 2 and lists the failed periods")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("retention")
+                        .long("retention")
+                        .value_name("N")
+                        .help("After parsing, delete the output of every period older than the N most recent ones in the Parquet directory")
+                        .value_parser(clap::value_parser!(usize))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("retention_dry_run")
+                        .long("retention-dry-run")
+                        .help("With --retention, only print the paths that would be deleted")
+                        .requires("retention")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("manifest")
+                        .long("manifest")
+                        .help("Write manifest.json to the Parquet directory with per-period source URL, ZIP size, XML and entry counts, output paths, and parse time")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("exclude")
+                        .long("exclude")
+                        .value_name("PERIODS")
+                        .help("Comma-separated periods to drop from the selected range (YYYY, YYYYMM, or YYYYQn)")
+                        .value_delimiter(',')
+                        .action(ArgAction::Append),
+                )
+                .arg(
+                    Arg::new("enrich_cpv")
+                        .long("enrich-cpv")
+                        .help("Add a project_cpv_description column with the English description of project.cpv_code (Parquet output only)")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("partitioned")
+                        .long("partitioned")
+                        .help("Write each period to a Hive-style year=YYYY/month=MM/ directory (month=00 for year-only periods)")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("multiple_cfs")
+                        .long("multiple-cfs")
+                        .help("Entries with several ContractFolderStatus elements: latest (keep the last one, default) or merge (later values override earlier ones)")
+                        .value_parser(["latest", "merge"])
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("keep_cfs_raw_xml")
+                        .long("keep-cfs-raw-xml")
+                        .alias("keep-raw-xml")
+                        .help("Include the raw ContractFolderStatus XML in parquet output")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("raw_xml_compression")
+                        .long("raw-xml-compression")
+                        .help("Compression of the cfs_raw_xml column only: lz4, zstd, or none (default: same as --compression)")
+                        .value_parser(["lz4", "zstd", "none"])
+                        .requires("keep_cfs_raw_xml")
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("lenient")
+                        .long("lenient")
+                        .help("Log and skip malformed entries instead of failing the whole XML file")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("normalize_amounts")
+                        .long("normalize-amounts")
+                        .help("Rewrite amounts written with a decimal comma or thousands separators (1.234,56) as 1234.56")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("filter_min_amount")
+                        .long("filter-min-amount")
+                        .value_name("N")
+                        .help("Drop entries whose project total amount is below N (entries without a numeric amount are kept)")
+                        .value_parser(clap::value_parser!(f64))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("null_empty_strings")
+                        .long("null-empty-strings")
+                        .help("Write empty text fields as null instead of empty strings")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("report_unknown_elements")
+                        .long("report-unknown-elements")
+                        .help("Count ContractFolderStatus elements the parser does not map and write them to {period}.unknown_elements.json next to the output")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("summary_json")
+                        .long("summary-json")
+                        .help("Also write the end-of-run summary as JSON to this path")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .action(ArgAction::Set),
+                ),
+        )
+        .subcommand(
+            Command::new("parse")
+                .about("Parse already-extracted XML/Atom files without downloading")
+                .after_help("Scans the extraction directory for period subdirectories instead of fetching links.\nExample:\n  sppd-cli parse -t pt -s 202403 -e 202403")
+                .arg(
+                    Arg::new("type")
+                        .short('t')
+                        .long("type")
+                        .help("Procurement type: 'minor-contracts' (mc, min), 'public-tenders' (pt, pub), or 'aggregated-platforms' (agg, agregadas)")
+                        .default_value("public-tenders")
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("start")
+                        .short('s')
+                        .long("start")
+                        .help("First extracted period to parse (YYYY, YYYYMM, or YYYYQn)")
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("end")
+                        .short('e')
+                        .long("end")
+                        .help("Last extracted period to parse (YYYY, YYYYMM, or YYYYQn)")
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("read_concurrency")
+                        .short('r')
+                        .long("read-concurrency")
+                        .alias("rc")
+                        .help("Files read in parallel while parsing XML (default: 16)")
+                        .value_parser(clap::value_parser!(usize))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("batch_size")
+                        .short('b')
+                        .long("batch-size")
+                        .alias("bs")
+                        .help("Number of XML files to parse per batch (default 500, minimum 1); smaller batches lower peak memory but write more Parquet files")
+                        .value_parser(clap::value_parser!(usize))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("parser_threads")
+                        .long("parser-threads")
+                        .alias("pt")
+                        .help("Number of threads for XML parsing rayon pool (0 = auto-detect, useful in Docker)")
+                        .value_parser(clap::value_parser!(usize))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("parallel_periods")
+                        .long("parallel-periods")
+                        .value_name("N")
+                        .help("Number of periods parsed concurrently, sharing the parser threads (default: 1)")
+                        .value_parser(clap::value_parser!(usize))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("workers")
+                        .long("workers")
+                        .value_name("N")
+                        .help("XML parsing worker threads (0 = auto-detect; capped at the number of logical CPUs)")
+                        .conflicts_with("parser_threads")
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("concat_batches")
+                        .short('c')
+                        .long("concat-batches")
+                        .alias("cb")
+                        .help("Merge the per-batch parquet files after parsing (caution: high memory for large periods)")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("config_file")
+                        .long("config-file")
+                        .alias("config")
+                        .value_name("PATH")
+                        .help("Load pipeline settings from a TOML file (default: ./sppd-cli.toml if present); flags given on the command line take precedence")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .help("Output file format: parquet (default) or jsonl (newline-delimited JSON)")
+                        .value_parser(["parquet", "jsonl"])
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("compression")
+                        .long("compression")
+                        .alias("parquet-compression")
+                        .help("Parquet compression codec: zstd (default), snappy, lz4, or uncompressed (also: none)")
+                        .value_parser(["zstd", "snappy", "lz4", "uncompressed", "none"])
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("checksums")
+                        .long("checksums")
+                        .help("Write a {period}.sha256 file (sha256sum format) next to each period's output: yes or no (default)")
+                        .value_parser(["yes", "no"])
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("to_duckdb")
+                        .long("to-duckdb")
+                        .value_name("PATH")
+                        .help("Also load each parsed period into the DuckDB database at PATH, as entries, project_lots, tender_results, and awarding_criteria tables (requires the `duckdb` feature)")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("dedup")
+                        .long("dedup")
+                        .help("Deduplicate the concatenated period file: latest (keep the most recently updated row per contract) or none (default)")
+                        .value_parser(["latest", "none"])
+                        .requires("concat_batches")
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("dedupe_by_id")
+                        .long("dedupe-by-id")
+                        .alias("dedupe")
+                        .help("Drop entries whose id already appeared in the same period, keeping the last occurrence (entries without an id are kept)")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("join_lot_results")
+                        .long("join-lot-results")
+                        .help("Also write {period}_lots.parquet with one row per lot joined with its tender result (Parquet output only)")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("single_file_stream")
+                        .long("single-file-stream")
+                        .help("Stream each period into one Parquet file, appending a row group per batch (no batch files or concat pass; --concat-batches and --dedup are ignored)")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("max_entries_per_file")
+                        .long("max-entries-per-file")
+                        .value_name("N")
+                        .help("Split Parquet batch files over N rows into batch_{i}_{j}.parquet shards of at most N rows")
+                        .value_parser(clap::value_parser!(usize))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("incremental")
+                        .long("incremental")
+                        .help("Keep each period's batch directory and only parse the XML files not listed in its .processed_files.json manifest, appending them as new batches")
+                        .conflicts_with_all(&["single_file_stream", "partitioned"])
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("cache_dir")
+                        .long("cache-dir")
+                        .value_name("PATH")
+                        .help("Cache parsed XML documents in this directory by content hash and reuse them on later runs")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("strict_period_dirs")
+                        .long("strict-period-dirs")
+                        .help("Only parse extracted directories named exactly after their period (no case-insensitive or fuzzy matching)")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("keep_going")
+                        .long("keep-going")
+                        .help("Continue with the remaining periods when one fails; exits with code 2 and lists the failed periods")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("retention")
+                        .long("retention")
+                        .value_name("N")
+                        .help("After parsing, delete the output of every period older than the N most recent ones in the Parquet directory")
+                        .value_parser(clap::value_parser!(usize))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("retention_dry_run")
+                        .long("retention-dry-run")
+                        .help("With --retention, only print the paths that would be deleted")
+                        .requires("retention")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("manifest")
+                        .long("manifest")
+                        .help("Write manifest.json to the Parquet directory with per-period source URL, ZIP size, XML and entry counts, output paths, and parse time")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("exclude")
+                        .long("exclude")
+                        .value_name("PERIODS")
+                        .help("Comma-separated periods to drop from the selected range (YYYY, YYYYMM, or YYYYQn)")
+                        .value_delimiter(',')
+                        .action(ArgAction::Append),
+                )
+                .arg(
+                    Arg::new("enrich_cpv")
+                        .long("enrich-cpv")
+                        .help("Add a project_cpv_description column with the English description of project.cpv_code (Parquet output only)")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("partitioned")
+                        .long("partitioned")
+                        .help("Write each period to a Hive-style year=YYYY/month=MM/ directory (month=00 for year-only periods)")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("multiple_cfs")
+                        .long("multiple-cfs")
+                        .help("Entries with several ContractFolderStatus elements: latest (keep the last one, default) or merge (later values override earlier ones)")
+                        .value_parser(["latest", "merge"])
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("keep_cfs_raw_xml")
+                        .long("keep-cfs-raw-xml")
+                        .alias("keep-raw-xml")
+                        .help("Include the raw ContractFolderStatus XML in parquet output")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("raw_xml_compression")
+                        .long("raw-xml-compression")
+                        .help("Compression of the cfs_raw_xml column only: lz4, zstd, or none (default: same as --compression)")
+                        .value_parser(["lz4", "zstd", "none"])
+                        .requires("keep_cfs_raw_xml")
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("lenient")
+                        .long("lenient")
+                        .help("Log and skip malformed entries instead of failing the whole XML file")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("normalize_amounts")
+                        .long("normalize-amounts")
+                        .help("Rewrite amounts written with a decimal comma or thousands separators (1.234,56) as 1234.56")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("filter_min_amount")
+                        .long("filter-min-amount")
+                        .value_name("N")
+                        .help("Drop entries whose project total amount is below N (entries without a numeric amount are kept)")
+                        .value_parser(clap::value_parser!(f64))
+                        .action(ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("null_empty_strings")
+                        .long("null-empty-strings")
+                        .help("Write empty text fields as null instead of empty strings")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("report_unknown_elements")
+                        .long("report-unknown-elements")
+                        .help("Count ContractFolderStatus elements the parser does not map and write them to {period}.unknown_elements.json next to the output")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("summary_json")
+                        .long("summary-json")
+                        .help("Also write the end-of-run summary as JSON to this path")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .action(ArgAction::Set),
+                ),
+        )
+        .subcommand(
+            Command::new("check")
+                .about("Verify that Parquet output files are readable, non-empty, and have the expected columns")
+                .after_help("Only reads file metadata; no rows are decoded. Exits with an error if any file fails.\nExample:\n  sppd-cli check -t pt --data-dir /data")
+                .arg(
+                    Arg::new("type")
+                        .short('t')
+                        .long("type")
+                        .help("Procurement type: 'minor-contracts' (mc, min), 'public-tenders' (pt, pub), or 'aggregated-platforms' (agg, agregadas)")
+                        .default_value("public-tenders")
+                        .action(ArgAction::Set),
+                ),
+        )
+        .subcommand(
+            Command::new("stats")
+                .visible_alias("info")
+                .about("Summarize a Parquet output: rows, distinct contract ids, total project amount, and entries per status")
+                .after_help("Accepts a period file or a directory of batch files.\nExample:\n  sppd-cli stats data/parquet/pt/202301.parquet")
+                .arg(
+                    Arg::new("path")
+                        .help("Parquet file or period directory produced by sppd-cli")
+                        .required(true)
+                        .value_parser(clap::value_parser!(PathBuf)),
+                ),
+        )
+        .subcommand(
+            Command::new("schema")
+                .about("Print the columns and nested struct fields of the Parquet output")
+                .after_help("Read from the DataFrame the parser builds, so it always matches the written files.\nExample:\n  sppd-cli schema --json")
+                .arg(
+                    Arg::new("json")
+                        .long("json")
+                        .help("Print the schema as JSON instead of an indented tree")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("keep_cfs_raw_xml")
+                        .long("keep-cfs-raw-xml")
+                        .help("Include the cfs_raw_xml column written with --keep-cfs-raw-xml")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("diff")
+                .about("Compare two periods of Parquet output and count new, removed, and changed contracts")
+                .after_help("Contracts are matched on contract_id; changed means a different project.total_amount.\nExample:\n  sppd-cli diff -t pt --from 202401 --to 202402 --output-path diff.parquet")
+                .arg(
+                    Arg::new("type")
+                        .short('t')
+                        .long("type")
+                        .help("Procurement type: 'minor-contracts' (mc, min), 'public-tenders' (pt, pub), or 'aggregated-platforms' (agg, agregadas)")
+                        .default_value("public-tenders")
                         .action(ArgAction::Set),
                 )
                 .arg(
-                    Arg::new("read_concurrency")
-                        .short('r')
-                        .long("read-concurrency")
-                        .alias("rc")
-                        .help("Files read in parallel while parsing XML (default: 16)")
-                        .value_parser(clap::value_parser!(usize))
+                    Arg::new("from")
+                        .long("from")
+                        .value_name("PERIOD")
+                        .help("Period to compare from (YYYY or YYYYMM)")
+                        .required(true)
                         .action(ArgAction::Set),
                 )
                 .arg(
-                    Arg::new("batch_size")
-                        .short('b')
-                        .long("batch-size")
-                        .alias("bs")
-                        .help("Number of XML files to parse per batch (affects peak memory usage)")
-                        .value_parser(clap::value_parser!(usize))
+                    Arg::new("to")
+                        .long("to")
+                        .value_name("PERIOD")
+                        .help("Period to compare to (YYYY or YYYYMM)")
+                        .required(true)
                         .action(ArgAction::Set),
                 )
                 .arg(
-                    Arg::new("parser_threads")
-                        .long("parser-threads")
-                        .alias("pt")
-                        .help("Number of threads for XML parsing rayon pool (0 = auto-detect, useful in Docker)")
-                        .value_parser(clap::value_parser!(usize))
+                    Arg::new("output_path")
+                        .long("output-path")
+                        .value_name("FILE")
+                        .help("Also write one row per differing contract, with a diff_type column, to this Parquet file")
+                        .value_parser(clap::value_parser!(PathBuf))
                         .action(ArgAction::Set),
+                ),
+        )
+        .subcommand(
+            Command::new("convert")
+                .about("Convert Parquet output to CSV or JSON Lines, flattening nested columns")
+                .after_help("Accepts a period file (written to OUTPUT) or a directory of period outputs (one file per period in the OUTPUT directory).\nExample:\n  sppd-cli convert data/parquet/pt csv/pt --format csv")
+                .arg(
+                    Arg::new("input")
+                        .help("Parquet file or directory of period outputs produced by sppd-cli")
+                        .required(true)
+                        .value_parser(clap::value_parser!(PathBuf)),
                 )
                 .arg(
-                    Arg::new("no_cleanup")
-                        .long("no-cleanup")
-                        .help("Skip cleanup of downloaded ZIP and extracted files")
-                        .action(ArgAction::SetTrue),
+                    Arg::new("output")
+                        .help("Output file, or output directory when INPUT is a directory")
+                        .required(true)
+                        .value_parser(clap::value_parser!(PathBuf)),
                 )
                 .arg(
-                    Arg::new("concat_batches")
-                        .short('c')
-                        .long("concat-batches")
-                        .alias("cb")
-                        .help("Merge the per-batch parquet files after parsing (caution: high memory for large periods)")
-                        .action(ArgAction::SetTrue),
+                    Arg::new("format")
+                        .long("format")
+                        .help("Output file format: csv (default) or jsonl (newline-delimited JSON)")
+                        .value_parser(["csv", "jsonl", "ndjson"])
+                        .default_value("csv")
+                        .action(ArgAction::Set),
+                ),
+        )
+        .subcommand(
+            Command::new("query")
+                .about("Run a SQL query against the Parquet output with DuckDB (requires the `duckdb` feature)")
+                .after_help("Each period is a view named after it (quote it: \"202301\"); all_periods combines them.\nExample:\n  sppd-cli query -t pt --sql 'SELECT count(*) FROM all_periods'")
+                .arg(
+                    Arg::new("type")
+                        .short('t')
+                        .long("type")
+                        .help("Procurement type: 'minor-contracts' (mc, min), 'public-tenders' (pt, pub), or 'aggregated-platforms' (agg, agregadas)")
+                        .default_value("public-tenders")
+                        .action(ArgAction::Set),
                 )
                 .arg(
-                    Arg::new("keep_cfs_raw_xml")
-                        .long("keep-cfs-raw-xml")
-                        .help("Include the raw ContractFolderStatus XML in parquet output")
-                        .action(ArgAction::SetTrue),
+                    Arg::new("sql")
+                        .long("sql")
+                        .value_name("QUERY")
+                        .help("SQL query to run")
+                        .required(true)
+                        .action(ArgAction::Set),
                 ),
         )
         .subcommand(
@@ -121,165 +1015,630 @@ pub async fn cli() -> AppResult<()> {
                         .help("Path to the TOML config file")
                         .required(true)
                         .value_parser(clap::value_parser!(PathBuf)),
+                )
+                .arg(
+                    Arg::new("summary_json")
+                        .long("summary-json")
+                        .help("Also write the end-of-run summary as JSON to this path")
+                        .value_parser(clap::value_parser!(PathBuf))
+                        .action(ArgAction::Set),
                 ),
-        );
-
-    let mut cmd_for_help = cmd.clone();
-    let matches = cmd.get_matches();
+        )
+}
 
-    match matches.subcommand() {
-        Some(("cli", sub)) => {
-            let (minor_contracts_links, public_tenders_links) = fetch_all_links().await?;
+/// Log level used when `RUST_LOG` is not set: `warn` with `--quiet`, `debug` with
+/// `--verbose`, and `info` otherwise.
+fn default_log_level(matches: &ArgMatches) -> LevelFilter {
+    if matches.get_flag("quiet") {
+        LevelFilter::WARN
+    } else if matches.get_flag("verbose") {
+        LevelFilter::DEBUG
+    } else {
+        LevelFilter::INFO
+    }
+}
 
-            info!(
-                minor_contracts_periods = minor_contracts_links.len(),
-                public_tenders_periods = public_tenders_links.len(),
-                "Link fetching completed"
-            );
+/// Starts the Prometheus `/metrics` server when `--metrics-addr` is given.
+///
+/// # Errors
+///
+/// Returns `InvalidInput` if an address is given but the binary was built without the
+/// `metrics` feature, or `IoError` if the address cannot be bound.
+async fn start_metrics_server(addr: Option<std::net::SocketAddr>) -> AppResult<()> {
+    let Some(addr) = addr else {
+        return Ok(());
+    };
+    #[cfg(feature = "metrics")]
+    {
+        crate::metrics::serve(addr).await?;
+        Ok(())
+    }
+    #[cfg(not(feature = "metrics"))]
+    {
+        let _ = addr;
+        Err(AppError::InvalidInput(
+            "--metrics-addr requires sppd-cli to be built with the `metrics` feature".into(),
+        ))
+    }
+}
 
-            let type_arg = sub
-                .get_one::<String>("type")
-                .expect("type has default_value")
-                .as_str();
-
-            // Warn if procurement type is unknown (will default to public-tenders)
-            if !ProcurementType::is_known_type(type_arg) {
-                tracing::warn!(
-                    type_arg = %type_arg,
-                    "Unknown procurement type, defaulting to public-tenders"
-                );
+async fn run_subcommand(matches: &ArgMatches, cmd_for_help: &mut Command<'_>) -> AppResult<()> {
+    let (summary, summary_json) = match matches.subcommand() {
+        Some(("cli", sub)) if sub.get_flag("parse_only") => {
+            let (proc_type, start_period, end_period) = period_args_from_matches(sub)?;
+            let resolved_config = resolved_config_from_matches(sub)?;
+            let summary =
+                run_parse_only(proc_type, start_period, end_period, &resolved_config).await?;
+            (summary, sub.get_one::<PathBuf>("summary_json"))
+        }
+        Some(("cli", sub)) => {
+            // Parse every argument before touching the network, then fetch only the
+            // landing page for the requested procurement type.
+            let (proc_type, start_period, end_period) = period_args_from_matches(sub)?;
+            let mut start_period = start_period.map(str::to_string);
+            let mut end_period = end_period.map(str::to_string);
+            let mut resolved_config = resolved_config_from_matches(sub)?;
+            if let Some(source_dir) = sub.get_one::<PathBuf>("source_dir") {
+                resolved_config.source_dir = Some(source_dir.clone());
             }
-
-            let proc_type = ProcurementType::from(type_arg);
-            let start_period = sub.get_one::<String>("start").map(|s| s.as_str());
-            let end_period = sub.get_one::<String>("end").map(|s| s.as_str());
-            let mut resolved_config = ResolvedConfig::default();
-            if let Some(&batch_size) = sub.get_one::<usize>("batch_size") {
-                resolved_config.batch_size = batch_size;
+            if let Some(proxy) = sub.get_one::<String>("proxy") {
+                resolved_config.proxy = Some(proxy.clone());
+            }
+            if let Some(&max_rps) = sub.get_one::<f64>("max_rps") {
+                resolved_config.max_rps = Some(max_rps);
+            }
+            if let Some(&max_bytes_per_sec) = sub.get_one::<u64>("max_bytes_per_sec") {
+                resolved_config.max_bytes_per_sec = Some(max_bytes_per_sec);
             }
-            if let Some(&concurrency) = sub.get_one::<usize>("read_concurrency") {
-                resolved_config.read_concurrency = concurrency;
+            resolved_config.verify_remote_checksum |= sub.get_flag("verify_remote_checksum");
+            if let Some(&ttl) = sub.get_one::<u64>("links_cache_ttl") {
+                resolved_config.links_cache_ttl_secs = ttl;
             }
-            if let Some(&threads) = sub.get_one::<usize>("parser_threads") {
-                resolved_config.parser_threads = threads;
+            resolved_config.refresh_links |= sub.get_flag("refresh_links");
+            if sub.get_flag("fail_fast") {
+                resolved_config.fail_fast = true;
+            } else if sub.get_flag("continue_on_error") {
+                resolved_config.fail_fast = false;
             }
-            if sub.get_flag("concat_batches") {
-                resolved_config.concat_batches = true;
+            if let Some(&periods) = sub.get_one::<usize>("periods_in_flight") {
+                resolved_config.periods_in_flight = Some(periods);
             }
-            if sub.get_flag("keep_cfs_raw_xml") {
-                resolved_config.keep_cfs_raw_xml = true;
+            if let Some(ca_cert) = sub.get_one::<PathBuf>("ca_cert") {
+                resolved_config.ca_cert = Some(ca_cert.clone());
             }
+            if let Some(user_agent) = sub.get_one::<String>("user_agent") {
+                resolved_config.user_agent = Some(user_agent.clone());
+            }
+            resolved_config.skip_download |= sub.get_flag("no_download");
+            resolved_config.skip_extraction |= sub.get_flag("no_extract");
+            resolved_config.skip_parse |= sub.get_flag("no_parse");
+            resolved_config.validate()?;
 
-            let should_cleanup = !sub.get_flag("no_cleanup");
-
-            run_workflow(
-                &minor_contracts_links,
-                &public_tenders_links,
-                proc_type,
-                start_period,
-                end_period,
-                should_cleanup,
-                &resolved_config,
-            )
-            .await?;
-        }
-        Some(("toml", sub)) => {
-            let (minor_contracts_links, public_tenders_links) = fetch_all_links().await?;
+            let links = available_links(&proc_type, &resolved_config).await?;
+            info!(periods = links.len(), "Link fetching completed");
 
-            info!(
-                minor_contracts_periods = minor_contracts_links.len(),
-                public_tenders_periods = public_tenders_links.len(),
-                "Link fetching completed"
-            );
+            if sub.get_flag("since_last_run") {
+                match resume_point(&proc_type, &links, &resolved_config.state_file)? {
+                    ResumePoint::FromBeginning => {
+                        info!("No previous run recorded, processing all periods");
+                    }
+                    ResumePoint::From(period) => {
+                        info!(start_period = %period, "Resuming after last successful run");
+                        start_period = Some(period);
+                    }
+                    ResumePoint::UpToDate => {
+                        info!("No new periods since last successful run");
+                        return if sub.get_flag("watch") {
+                            watch(sub, proc_type, resolved_config).await
+                        } else {
+                            Ok(())
+                        };
+                    }
+                }
+            }
+            if let Some(&months) = sub.get_one::<u32>("last_months") {
+                let today = current_month();
+                let recent = recent_periods(&links, months as usize, &today);
+                let (Some(first), Some(last)) = (recent.keys().next(), recent.keys().last()) else {
+                    return Err(AppError::InvalidInput(format!(
+                        "--last-months {months}: no monthly periods up to {today} are available"
+                    )));
+                };
+                if recent.len() < months as usize {
+                    warn!(
+                        requested = months,
+                        available = recent.len(),
+                        "Fewer monthly periods available than requested by --last-months"
+                    );
+                }
+                info!(start_period = %first, end_period = %last, "Selected periods from --last-months");
+                start_period = Some(first.clone());
+                end_period = Some(last.clone());
+            }
+
+            if sub.get_flag("dry_run") {
+                let mut plan_config = resolved_config;
+                if sub.get_flag("download_only") {
+                    plan_config.skip_extraction = true;
+                    plan_config.skip_parse = true;
+                }
+                let plan = plan_run(
+                    &links,
+                    proc_type,
+                    start_period.as_deref(),
+                    end_period.as_deref(),
+                    &plan_config,
+                )
+                .await?;
+                print!("{}", plan.to_table());
+                return Ok(());
+            }
 
+            let summary = if sub.get_flag("download_only") {
+                run_download_only(
+                    &links,
+                    proc_type,
+                    start_period.as_deref(),
+                    end_period.as_deref(),
+                    &resolved_config,
+                )
+                .await?
+            } else {
+                let mut options = PipelineOptions::new(proc_type);
+                options.start_period = start_period;
+                options.end_period = end_period;
+                options.cleanup = !sub.get_flag("no_cleanup");
+                options.config = resolved_config.clone();
+                pipeline::run_with_links(&links, options).await?
+            };
+            if sub.get_flag("watch") {
+                report_watch_run(&summary, sub.get_one::<PathBuf>("summary_json"));
+                return watch(sub, proc_type, resolved_config).await;
+            }
+            (summary, sub.get_one::<PathBuf>("summary_json"))
+        }
+        Some(("toml", sub)) => {
             let config_path = sub
                 .get_one::<PathBuf>("config")
                 .expect("config is required");
 
             let file_config = ResolvedConfigFile::from_toml_file(config_path)?;
-            let proc_type = ProcurementType::from(file_config.procurement_type.as_str());
-            let start_period = Some(file_config.start.as_str());
-            let end_period = Some(file_config.end.as_str());
-
-            run_workflow(
-                &minor_contracts_links,
-                &public_tenders_links,
-                proc_type,
-                start_period,
-                end_period,
-                file_config.cleanup,
-                &file_config.resolved,
-            )
-            .await?;
+            let mut resolved_config = file_config.merge(
+                sub.get_one::<PathBuf>("data_dir").map(PathBuf::as_path),
+                sub.get_one::<PathBuf>("output_dir").map(PathBuf::as_path),
+            );
+            if sub.get_flag("quiet") {
+                resolved_config.progress = false;
+            }
+            let proc_type: ProcurementType = file_config.procurement_type.parse()?;
+
+            let mut options = PipelineOptions::new(proc_type);
+            options.start_period = Some(file_config.start);
+            options.end_period = Some(file_config.end);
+            options.cleanup = file_config.cleanup;
+            options.config = resolved_config;
+            let summary = pipeline::run(options).await?;
+            (summary, sub.get_one::<PathBuf>("summary_json"))
+        }
+        Some(("parse", sub)) => {
+            let (proc_type, start_period, end_period) = period_args_from_matches(sub)?;
+            let resolved_config = resolved_config_from_matches(sub)?;
+
+            let summary =
+                run_parse_only(proc_type, start_period, end_period, &resolved_config).await?;
+            (summary, sub.get_one::<PathBuf>("summary_json"))
+        }
+        Some(("check", sub)) => return run_check(sub),
+        Some(("stats", sub)) => {
+            let path = sub.get_one::<PathBuf>("path").expect("path is required");
+            print!("{}", parquet_stats(path)?.to_table());
+            return Ok(());
+        }
+        Some(("schema", sub)) => {
+            let schema = output_schema(sub.get_flag("keep_cfs_raw_xml"))?;
+            if sub.get_flag("json") {
+                println!("{}", schema.to_json()?);
+            } else {
+                print!("{}", schema.to_tree());
+            }
+            return Ok(());
+        }
+        Some(("diff", sub)) => return run_diff(sub),
+        Some(("convert", sub)) => {
+            let input = sub.get_one::<PathBuf>("input").expect("input is required");
+            let output = sub
+                .get_one::<PathBuf>("output")
+                .expect("output is required");
+            let format: ConvertFormat = sub
+                .get_one::<String>("format")
+                .expect("format has default_value")
+                .parse()?;
+            for path in convert_parquet(input, output, format)? {
+                println!("{}", path.display());
+            }
+            return Ok(());
         }
+        Some(("query", sub)) => return run_query(sub),
         _ => {
             cmd_for_help
                 .print_help()
                 .map_err(|e| AppError::IoError(format!("Failed to print help: {e}")))?;
+            return Ok(());
         }
+    };
+
+    print_summary(&summary, summary_json)?;
+    match summary.partial_failure() {
+        Some(err) => Err(err),
+        None => Ok(()),
     }
+}
 
+/// Prints the summary table and writes the summary to `summary_json` when set.
+fn print_summary(summary: &RunSummary, summary_json: Option<&PathBuf>) -> AppResult<()> {
+    print!("{}", summary.to_table());
+    if let Some(path) = summary_json {
+        write_summary_json(summary, path)?;
+        info!(path = %path.display(), "Run summary written");
+    }
     Ok(())
 }
 
-async fn run_workflow(
-    minor_contracts_links: &BTreeMap<String, String>,
-    public_tenders_links: &BTreeMap<String, String>,
+/// Reports a run made with `--watch`, which keeps going whatever its outcome.
+fn report_watch_run(summary: &RunSummary, summary_json: Option<&PathBuf>) {
+    if let Err(e) = print_summary(summary, summary_json) {
+        warn!(error = %e, "Failed to write run summary");
+    }
+    if let Some(err) = summary.partial_failure() {
+        warn!(error = %err, "Some periods failed; they are retried at the next poll");
+    }
+}
+
+/// Polls for new periods every `--watch-interval` seconds until SIGINT or SIGTERM.
+///
+/// The signal handlers are only installed here, after the initial run, so that a signal
+/// received during that run still stops the process at once.
+async fn watch(
+    sub: &ArgMatches,
     proc_type: ProcurementType,
-    start_period: Option<&str>,
-    end_period: Option<&str>,
-    should_cleanup: bool,
-    resolved_config: &ResolvedConfig,
+    resolved_config: ResolvedConfig,
 ) -> AppResult<()> {
-    let links = match proc_type {
-        ProcurementType::MinorContracts => minor_contracts_links,
-        ProcurementType::PublicTenders => public_tenders_links,
-    };
+    let shutdown = shutdown_signal()?;
+    let interval = Duration::from_secs(
+        *sub.get_one::<u64>("watch_interval")
+            .expect("watch_interval has default_value"),
+    );
+    let summary_json = sub.get_one::<PathBuf>("summary_json");
+    let mut options = PipelineOptions::new(proc_type);
+    options.cleanup = !sub.get_flag("no_cleanup");
+    options.config = resolved_config;
+    // Every poll must see the current landing page, not the links cache.
+    options.config.refresh_links = true;
+
+    watch_new_periods(
+        &options,
+        interval,
+        || available_links(&proc_type, &options.config),
+        shutdown,
+        |summary| report_watch_run(summary, summary_json),
+    )
+    .await;
+    Ok(())
+}
 
-    let target_links = filter_periods_by_range(links, start_period, end_period)?;
+/// Completes on the first SIGINT or SIGTERM (Ctrl-C outside Unix).
+///
+/// The Unix handlers are installed before returning, so a signal received before the
+/// future is first polled still completes it.
+fn shutdown_signal() -> AppResult<impl Future<Output = ()>> {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
 
-    print_download_info(&proc_type, start_period, end_period, target_links.len());
+        let install = |kind| {
+            signal(kind)
+                .map_err(|e| AppError::IoError(format!("Failed to install signal handler: {e}")))
+        };
+        let mut interrupt = install(SignalKind::interrupt())?;
+        let mut terminate = install(SignalKind::terminate())?;
+        Ok(async move {
+            tokio::select! {
+                _ = interrupt.recv() => {}
+                _ = terminate.recv() => {}
+            }
+        })
+    }
+    #[cfg(not(unix))]
+    {
+        Ok(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+    }
+}
 
-    let client = reqwest::Client::new();
-    download_files(&client, &target_links, &proc_type, resolved_config).await?;
+/// Checks the Parquet output of the selected procurement type and prints a per-file table.
+///
+/// # Errors
+///
+/// Returns `InvalidInput` if any file cannot be opened, has zero rows, or lacks an expected
+/// column, so the process exits with a non-zero status.
+fn run_check(sub: &ArgMatches) -> AppResult<()> {
+    let type_arg = sub
+        .get_one::<String>("type")
+        .expect("type has default_value");
+    let proc_type: ProcurementType = type_arg.parse()?;
+    let mut resolved_config = ResolvedConfig::default();
+    apply_data_dir_args(&mut resolved_config, sub);
 
-    info!("Starting extraction phase");
-    extract_all_zips(&target_links, &proc_type, resolved_config).await?;
+    let report = check_parquet_dir(&proc_type.parquet_dir(&resolved_config))?;
+    print!("{}", report.to_table());
 
-    parse_xmls(
-        &target_links,
-        &proc_type,
-        resolved_config.batch_size,
-        resolved_config,
-    )
-    .await?;
+    match report.error_count() {
+        0 => Ok(()),
+        errors => Err(AppError::InvalidInput(format!(
+            "{errors} of {} Parquet file(s) failed the check",
+            report.files.len()
+        ))),
+    }
+}
 
-    cleanup_files(&target_links, &proc_type, should_cleanup, resolved_config).await?;
+/// Compares the `--from` and `--to` periods of the selected procurement type, prints the
+/// counts, and writes the differing contracts to `--output-path` when given.
+///
+/// # Errors
+///
+/// Returns `InvalidInput` for a malformed period, `IoError` if a period has no output, and
+/// `ParseError` if the files cannot be compared or the diff file cannot be written.
+fn run_diff(sub: &ArgMatches) -> AppResult<()> {
+    let type_arg = sub
+        .get_one::<String>("type")
+        .expect("type has default_value");
+    let proc_type: ProcurementType = type_arg.parse()?;
+    let mut resolved_config = ResolvedConfig::default();
+    apply_data_dir_args(&mut resolved_config, sub);
 
-    info!(
-        procurement_type = proc_type.display_name(),
-        periods_processed = target_links.len(),
-        "All operations completed successfully"
-    );
+    let parquet_dir = proc_type.parquet_dir(&resolved_config);
+    let mut outputs = Vec::with_capacity(2);
+    for name in ["from", "to"] {
+        let period = sub.get_one::<String>(name).expect("period is required");
+        crate::downloader::validate_period_format(period)?;
+        if period.contains('Q') {
+            return Err(AppError::InvalidInput(format!(
+                "diff compares single periods (YYYY or YYYYMM), got: {period}"
+            )));
+        }
+        outputs.push(period_output(&parquet_dir, period)?);
+    }
+
+    let diff = parquet_diff(&outputs[0], &outputs[1])?;
+    print!("{}", diff.to_table());
+    if let Some(path) = sub.get_one::<PathBuf>("output_path") {
+        diff.write_parquet(path)?;
+        info!(path = %path.display(), rows = diff.rows.height(), "Diff written");
+    }
+    Ok(())
+}
+
+/// Runs the `query` subcommand's SQL against the Parquet output of the selected procurement
+/// type and prints the result table.
+///
+/// # Errors
+///
+/// Returns `InvalidInput` if the binary was built without the `duckdb` feature or DuckDB
+/// rejects the query, and `IoError` if there is no Parquet output to query.
+fn run_query(sub: &ArgMatches) -> AppResult<()> {
+    let type_arg = sub
+        .get_one::<String>("type")
+        .expect("type has default_value");
+    let proc_type: ProcurementType = type_arg.parse()?;
+    let mut resolved_config = ResolvedConfig::default();
+    apply_data_dir_args(&mut resolved_config, sub);
+    let sql = sub.get_one::<String>("sql").expect("sql is required");
+
+    #[cfg(feature = "duckdb")]
+    {
+        let table = crate::query::run_query(&proc_type.parquet_dir(&resolved_config), sql)?;
+        println!("{table}");
+        Ok(())
+    }
+    #[cfg(not(feature = "duckdb"))]
+    {
+        let _ = (proc_type, sql);
+        Err(AppError::InvalidInput(
+            "query requires sppd-cli to be built with the `duckdb` feature".to_string(),
+        ))
+    }
+}
 
+/// Writes the run summary as pretty-printed JSON, creating parent directories.
+fn write_summary_json(summary: &RunSummary, path: &Path) -> AppResult<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let json = serde_json::to_string_pretty(summary)
+        .map_err(|e| AppError::ParseError(format!("Failed to serialize run summary: {e}")))?;
+    std::fs::write(path, json)?;
     Ok(())
 }
 
-fn print_download_info(
-    proc_type: &ProcurementType,
-    start_period: Option<&str>,
-    end_period: Option<&str>,
-    periods_count: usize,
-) {
-    let start_text = start_period.unwrap_or("first available");
-    let end_text = end_period.unwrap_or("last available");
-    info!(
-        procurement_type = proc_type.display_name(),
-        periods = periods_count,
-        start_period = start_text,
-        end_period = end_text,
-        "Starting download"
+/// Reads the `--type`, `--start`, and `--end` arguments.
+///
+/// # Errors
+///
+/// Returns `InvalidInput` if `--type` is not a known alias (see [`ProcurementType::from_str`]).
+fn period_args_from_matches(
+    sub: &ArgMatches,
+) -> AppResult<(ProcurementType, Option<&str>, Option<&str>)> {
+    let proc_type = sub
+        .get_one::<String>("type")
+        .expect("type has default_value")
+        .parse()?;
+    let start_period = sub.get_one::<String>("start").map(|s| s.as_str());
+    let end_period = sub.get_one::<String>("end").map(|s| s.as_str());
+    Ok((proc_type, start_period, end_period))
+}
+
+/// Applies the global `--data-dir` and `--output-dir` flags to `resolved_config`.
+///
+/// Uses the same path rules as the TOML files; see [`ResolvedConfig::set_dirs`].
+fn apply_data_dir_args(resolved_config: &mut ResolvedConfig, matches: &ArgMatches) {
+    resolved_config.set_dirs(
+        matches.get_one::<PathBuf>("data_dir").map(PathBuf::as_path),
+        matches
+            .get_one::<PathBuf>("output_dir")
+            .map(PathBuf::as_path),
+    );
+}
+
+/// Builds a `ResolvedConfig` from the defaults, overridden by the `--config-file` TOML (or
+/// `./sppd-cli.toml`), overridden in turn by the options shared by the `cli` and `parse`
+/// subcommands.
+fn resolved_config_from_matches(sub: &ArgMatches) -> AppResult<ResolvedConfig> {
+    let config_file = sub.get_one::<PathBuf>("config_file");
+    let mut resolved_config = PipelineConfigFile::load(config_file.map(PathBuf::as_path))?.merge(
+        sub.get_one::<PathBuf>("data_dir").map(PathBuf::as_path),
+        sub.get_one::<PathBuf>("output_dir").map(PathBuf::as_path),
     );
+    if let Some(&batch_size) = sub.get_one::<usize>("batch_size") {
+        if batch_size == 0 {
+            return Err(AppError::InvalidInput(
+                "Batch size must be greater than 0".into(),
+            ));
+        }
+        resolved_config.batch_size = batch_size;
+    }
+    if let Some(&concurrency) = sub.get_one::<usize>("read_concurrency") {
+        resolved_config.read_concurrency = concurrency;
+    }
+    if let Some(&threads) = sub.get_one::<usize>("parser_threads") {
+        resolved_config.parser_threads = threads;
+    }
+    if let Some(&periods) = sub.get_one::<usize>("parallel_periods") {
+        resolved_config.parallel_periods = periods;
+    }
+    if let Some(workers) = sub.get_one::<String>("workers") {
+        resolved_config.parser_threads = parse_worker_count(workers)?;
+    }
+    if sub.get_flag("concat_batches") {
+        resolved_config.concat_batches = true;
+    }
+    if let Some(strategy) = sub.get_one::<String>("dedup") {
+        resolved_config.dedup = strategy.parse()?;
+    }
+    if sub.get_flag("dedupe_by_id") {
+        resolved_config.dedupe_by_id = true;
+    }
+    if sub.get_flag("join_lot_results") {
+        resolved_config.join_lot_results = true;
+    }
+    if sub.get_flag("partitioned") {
+        resolved_config.partitioned = true;
+    }
+    if sub.get_flag("single_file_stream") {
+        resolved_config.single_file_stream = true;
+    }
+    if sub.get_flag("incremental") {
+        resolved_config.incremental = true;
+    }
+    if let Some(&max_entries) = sub.get_one::<usize>("max_entries_per_file") {
+        resolved_config.max_entries_per_file = Some(max_entries);
+    }
+    if let Some(cache_dir) = sub.get_one::<PathBuf>("cache_dir") {
+        resolved_config.cache_dir = Some(cache_dir.clone());
+    }
+    if sub.get_flag("strict_period_dirs") {
+        resolved_config.strict_period_dirs = true;
+    }
+    if sub.get_flag("manifest") {
+        resolved_config.manifest = true;
+    }
+    if let Some(&keep) = sub.get_one::<usize>("retention") {
+        resolved_config.retention = Some(keep);
+    }
+    if sub.get_flag("retention_dry_run") {
+        resolved_config.retention_dry_run = true;
+    }
+    if sub.get_flag("keep_going") {
+        resolved_config.keep_going = true;
+    }
+    if let Some(periods) = sub.get_many::<String>("exclude") {
+        resolved_config.exclude_periods = periods.cloned().collect();
+    }
+    if sub.get_flag("enrich_cpv") {
+        resolved_config.enrich_cpv = true;
+    }
+    if sub.get_flag("keep_cfs_raw_xml") {
+        resolved_config.keep_cfs_raw_xml = true;
+    }
+    if let Some(codec) = sub.get_one::<String>("raw_xml_compression") {
+        resolved_config.raw_xml_compression = Some(codec.parse()?);
+    }
+    if sub.get_flag("lenient") {
+        resolved_config.lenient = true;
+    }
+    if sub.get_flag("normalize_amounts") {
+        resolved_config.normalize_amounts = true;
+    }
+    if let Some(&threshold) = sub.get_one::<f64>("filter_min_amount") {
+        resolved_config.filter_min_amount = Some(threshold);
+    }
+    if sub.get_flag("null_empty_strings") {
+        resolved_config.null_empty_strings = true;
+    }
+    if sub.get_flag("report_unknown_elements") {
+        resolved_config.report_unknown_elements = true;
+    }
+    if let Some(strategy) = sub.get_one::<String>("multiple_cfs") {
+        resolved_config.multiple_cfs = strategy.parse()?;
+    }
+    if let Some(format) = sub.get_one::<String>("format") {
+        resolved_config.output_format = format.parse()?;
+    }
+    if let Some(codec) = sub.get_one::<String>("compression") {
+        resolved_config.parquet_compression = codec.parse()?;
+    }
+    if let Some(checksums) = sub.get_one::<String>("checksums") {
+        resolved_config.checksums = checksums == "yes";
+    }
+    if let Some(path) = sub.get_one::<PathBuf>("to_duckdb") {
+        resolved_config.duckdb_path = Some(path.clone());
+    }
+    if sub.get_flag("quiet") {
+        resolved_config.progress = false;
+    }
+    Ok(resolved_config)
+}
+
+/// Parses a `--workers` value into a parser thread count.
+///
+/// `0` keeps auto-detection. Positive values above the number of logical CPUs are clamped
+/// to that number with a warning, since extra rayon threads only add contention.
+///
+/// # Errors
+///
+/// Returns `InvalidInput` if the value is not a non-negative integer.
+fn parse_worker_count(s: &str) -> AppResult<usize> {
+    let requested: usize = s.trim().parse().map_err(|_| {
+        AppError::InvalidInput(format!(
+            "Invalid worker count '{s}': expected a non-negative integer"
+        ))
+    })?;
+    if requested == 0 {
+        return Ok(0);
+    }
+
+    let available = std::thread::available_parallelism()
+        .map(|p| p.get())
+        .unwrap_or(1);
+    if requested > available {
+        tracing::warn!(
+            requested,
+            available,
+            "Worker count exceeds available CPUs, clamping"
+        );
+        return Ok(available);
+    }
+    Ok(requested)
 }
 
 #[cfg(test)]
@@ -287,6 +1646,261 @@ mod tests {
     use super::*;
     use clap::Command;
 
+    #[test]
+    fn parse_worker_count_validates_and_clamps() {
+        let available = std::thread::available_parallelism()
+            .map(|p| p.get())
+            .unwrap_or(1);
+        assert_eq!(parse_worker_count("0").unwrap(), 0);
+        assert_eq!(parse_worker_count("1").unwrap(), 1);
+        assert_eq!(
+            parse_worker_count(&available.to_string()).unwrap(),
+            available
+        );
+        assert_eq!(
+            parse_worker_count(&(available + 8).to_string()).unwrap(),
+            available
+        );
+        assert!(parse_worker_count("-1").is_err());
+        assert!(parse_worker_count("many").is_err());
+    }
+
+    #[test]
+    fn batch_size_flag_sets_resolved_config() {
+        for subcommand in ["cli", "parse"] {
+            let matches = command()
+                .try_get_matches_from(["sppd-cli", subcommand, "--batch-size", "25"])
+                .unwrap();
+            let sub = matches.subcommand_matches(subcommand).unwrap();
+            assert_eq!(resolved_config_from_matches(sub).unwrap().batch_size, 25);
+        }
+
+        let matches = command()
+            .try_get_matches_from(["sppd-cli", "cli", "-b", "0"])
+            .unwrap();
+        let sub = matches.subcommand_matches("cli").unwrap();
+        assert!(matches!(
+            resolved_config_from_matches(sub),
+            Err(AppError::InvalidInput(msg)) if msg.contains("Batch size")
+        ));
+    }
+
+    #[test]
+    fn dedupe_by_id_flag_accepts_the_dedupe_alias() {
+        for subcommand in ["cli", "parse"] {
+            for flag in ["--dedupe-by-id", "--dedupe"] {
+                let matches = command()
+                    .try_get_matches_from(["sppd-cli", subcommand, flag])
+                    .unwrap();
+                let sub = matches.subcommand_matches(subcommand).unwrap();
+                let config = resolved_config_from_matches(sub).unwrap();
+                assert!(config.dedupe_by_id, "{subcommand} {flag}");
+                assert_eq!(config.dedup, crate::config::DedupStrategy::None);
+            }
+        }
+    }
+
+    #[test]
+    fn transformer_flags_set_resolved_config() {
+        for subcommand in ["cli", "parse"] {
+            let matches = command()
+                .try_get_matches_from([
+                    "sppd-cli",
+                    subcommand,
+                    "--filter-min-amount",
+                    "1500.5",
+                    "--null-empty-strings",
+                ])
+                .unwrap();
+            let sub = matches.subcommand_matches(subcommand).unwrap();
+            let config = resolved_config_from_matches(sub).unwrap();
+            assert_eq!(config.filter_min_amount, Some(1500.5));
+            assert!(config.null_empty_strings);
+        }
+
+        assert!(command()
+            .try_get_matches_from(["sppd-cli", "parse", "--filter-min-amount", "lots"])
+            .is_err());
+    }
+
+    #[test]
+    fn retention_flags_set_resolved_config() {
+        for subcommand in ["cli", "parse"] {
+            let matches = command()
+                .try_get_matches_from([
+                    "sppd-cli",
+                    subcommand,
+                    "--retention",
+                    "24",
+                    "--retention-dry-run",
+                ])
+                .unwrap();
+            let sub = matches.subcommand_matches(subcommand).unwrap();
+            let config = resolved_config_from_matches(sub).unwrap();
+            assert_eq!(config.retention, Some(24));
+            assert!(config.retention_dry_run);
+        }
+
+        assert!(command()
+            .try_get_matches_from(["sppd-cli", "parse", "--retention-dry-run"])
+            .is_err());
+        let matches = command()
+            .try_get_matches_from(["sppd-cli", "parse", "--retention", "0"])
+            .unwrap();
+        let sub = matches.subcommand_matches("parse").unwrap();
+        assert!(matches!(
+            resolved_config_from_matches(sub).unwrap().validate(),
+            Err(AppError::InvalidInput(msg)) if msg.contains("Retention")
+        ));
+    }
+
+    #[test]
+    fn config_file_is_loaded_and_flags_take_precedence() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("pipeline.toml");
+        std::fs::write(
+            &path,
+            "parser_threads = 2\nbatch_size = 40\nmax_retries = 7\n",
+        )
+        .unwrap();
+
+        for subcommand in ["cli", "parse"] {
+            let matches = command()
+                .try_get_matches_from([
+                    "sppd-cli",
+                    subcommand,
+                    "--config-file",
+                    path.to_str().unwrap(),
+                    "--batch-size",
+                    "25",
+                ])
+                .unwrap();
+            let sub = matches.subcommand_matches(subcommand).unwrap();
+            let config = resolved_config_from_matches(sub).unwrap();
+            assert_eq!(config.parser_threads, 2);
+            assert_eq!(config.max_retries, 7);
+            assert_eq!(config.batch_size, 25);
+        }
+    }
+
+    #[test]
+    fn config_alias_output_dir_yields_to_flag() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("pipeline.toml");
+        std::fs::write(&path, "output_dir = \"/srv/out\"\nbatch_size = 40\n").unwrap();
+
+        let matches = command()
+            .try_get_matches_from(["sppd-cli", "cli", "--config", path.to_str().unwrap()])
+            .unwrap();
+        let sub = matches.subcommand_matches("cli").unwrap();
+        let config = resolved_config_from_matches(sub).unwrap();
+        assert_eq!(config.parquet_dir_pt, PathBuf::from("/srv/out/pt"));
+        assert_eq!(config.batch_size, 40);
+
+        let matches = command()
+            .try_get_matches_from([
+                "sppd-cli",
+                "--output-dir",
+                "/cli/out",
+                "cli",
+                "--config",
+                path.to_str().unwrap(),
+            ])
+            .unwrap();
+        let sub = matches.subcommand_matches("cli").unwrap();
+        let config = resolved_config_from_matches(sub).unwrap();
+        assert_eq!(config.parquet_dir_pt, PathBuf::from("/cli/out/pt"));
+    }
+
+    #[test]
+    fn quiet_flag_disables_progress_bar() {
+        let matches = command()
+            .try_get_matches_from(["sppd-cli", "parse", "--quiet"])
+            .unwrap();
+        let sub = matches.subcommand_matches("parse").unwrap();
+        assert!(!resolved_config_from_matches(sub).unwrap().progress);
+
+        let matches = command()
+            .try_get_matches_from(["sppd-cli", "-q", "cli"])
+            .unwrap();
+        let sub = matches.subcommand_matches("cli").unwrap();
+        assert!(!resolved_config_from_matches(sub).unwrap().progress);
+
+        let matches = command().try_get_matches_from(["sppd-cli", "cli"]).unwrap();
+        let sub = matches.subcommand_matches("cli").unwrap();
+        assert!(resolved_config_from_matches(sub).unwrap().progress);
+    }
+
+    #[test]
+    fn quiet_and_verbose_set_default_log_level() {
+        let level =
+            |args: &[&str]| default_log_level(&command().try_get_matches_from(args).unwrap());
+        assert_eq!(level(&["sppd-cli", "cli"]), LevelFilter::INFO);
+        assert_eq!(level(&["sppd-cli", "-q", "cli"]), LevelFilter::WARN);
+        assert_eq!(level(&["sppd-cli", "parse", "--quiet"]), LevelFilter::WARN);
+        assert_eq!(level(&["sppd-cli", "cli", "--verbose"]), LevelFilter::DEBUG);
+        assert_eq!(level(&["sppd-cli", "-v", "parse"]), LevelFilter::DEBUG);
+        assert!(command()
+            .try_get_matches_from(["sppd-cli", "cli", "-q", "-v"])
+            .is_err());
+    }
+
+    #[test]
+    fn global_data_dir_rebases_every_output_directory() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("pipeline.toml");
+        std::fs::write(&path, "download_dir_pt = \"zips/pt\"\n").unwrap();
+
+        for args in [
+            [
+                "sppd-cli",
+                "--data-dir",
+                "/scratch",
+                "parse",
+                "--output-dir",
+                "out",
+            ],
+            [
+                "sppd-cli",
+                "parse",
+                "--output-dir",
+                "out",
+                "--data-dir",
+                "/scratch",
+            ],
+        ] {
+            let matches = command()
+                .try_get_matches_from(
+                    args.into_iter()
+                        .chain(["--config-file", path.to_str().unwrap()]),
+                )
+                .unwrap();
+            let sub = matches.subcommand_matches("parse").unwrap();
+            let config = resolved_config_from_matches(sub).unwrap();
+            assert_eq!(config.download_dir_pt, PathBuf::from("/scratch/zips/pt"));
+            assert_eq!(config.download_dir_mc, PathBuf::from("/scratch/tmp/mc"));
+            assert_eq!(config.parquet_dir_pt, PathBuf::from("/scratch/out/pt"));
+            assert_eq!(
+                config.state_file,
+                PathBuf::from("/scratch/.sppd_state.json")
+            );
+        }
+
+        let matches = command()
+            .try_get_matches_from([
+                "sppd-cli",
+                "--data-dir",
+                "/scratch",
+                "--output-dir",
+                "/out",
+                "cli",
+            ])
+            .unwrap();
+        let sub = matches.subcommand_matches("cli").unwrap();
+        let config = resolved_config_from_matches(sub).unwrap();
+        assert_eq!(config.parquet_dir_mc, PathBuf::from("/out/mc"));
+    }
+
     #[test]
     fn cli_command_parses_defaults() {
         let cmd = Command::new("sppd-cli").subcommand(
@@ -315,15 +1929,4 @@ mod tests {
         let err = cmd.try_get_matches_from(vec!["sppd-cli", "toml"]);
         assert!(err.is_err());
     }
-
-    #[test]
-    fn test_print_download_info_runs() {
-        print_download_info(
-            &ProcurementType::MinorContracts,
-            Some("202301"),
-            Some("202302"),
-            3,
-        );
-        print_download_info(&ProcurementType::PublicTenders, None, None, 5);
-    }
 }
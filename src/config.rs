@@ -2,6 +2,147 @@ use crate::errors::{AppError, AppResult};
 use serde::Deserialize;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Compression codec applied to the generated Parquet files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParquetCompressionCodec {
+    /// Zstandard (default): smallest files, good for archival
+    #[default]
+    Zstd,
+    /// Snappy: faster to write and read, larger files
+    Snappy,
+    /// LZ4 (`LZ4_RAW`): fastest to write and read, files close to Snappy's size
+    Lz4,
+    /// No compression
+    #[serde(alias = "none")]
+    Uncompressed,
+}
+
+impl FromStr for ParquetCompressionCodec {
+    type Err = AppError;
+
+    /// Parses a codec name (`zstd`, `snappy`, `lz4`, `uncompressed` or `none`),
+    /// case-insensitively.
+    fn from_str(value: &str) -> AppResult<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "zstd" => Ok(Self::Zstd),
+            "snappy" => Ok(Self::Snappy),
+            "lz4" => Ok(Self::Lz4),
+            "uncompressed" | "none" => Ok(Self::Uncompressed),
+            other => Err(AppError::InvalidInput(format!(
+                "Unknown Parquet compression '{other}' (expected zstd, snappy, lz4, or uncompressed)"
+            ))),
+        }
+    }
+}
+
+/// Compression codec applied to the `cfs_raw_xml` column only (`--raw-xml-compression`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RawXmlCompressionCodec {
+    /// LZ4: fast to write and read
+    Lz4,
+    /// Zstandard: smallest column
+    Zstd,
+    /// No compression
+    None,
+}
+
+impl FromStr for RawXmlCompressionCodec {
+    type Err = AppError;
+
+    /// Parses a codec name (`lz4`, `zstd`, `none`), case-insensitively.
+    fn from_str(value: &str) -> AppResult<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "lz4" => Ok(Self::Lz4),
+            "zstd" => Ok(Self::Zstd),
+            "none" | "uncompressed" => Ok(Self::None),
+            other => Err(AppError::InvalidInput(format!(
+                "Unknown raw XML compression '{other}' (expected lz4, zstd, or none)"
+            ))),
+        }
+    }
+}
+
+/// File format written by the parsing stage.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Apache Parquet (default)
+    #[default]
+    Parquet,
+    /// Newline-delimited JSON with the same field names as the Parquet columns
+    Jsonl,
+}
+
+impl FromStr for OutputFormat {
+    type Err = AppError;
+
+    /// Parses a format name (`parquet`, `jsonl`), case-insensitively.
+    fn from_str(value: &str) -> AppResult<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "parquet" => Ok(Self::Parquet),
+            "jsonl" | "ndjson" => Ok(Self::Jsonl),
+            other => Err(AppError::InvalidInput(format!(
+                "Unknown output format '{other}' (expected parquet or jsonl)"
+            ))),
+        }
+    }
+}
+
+/// Deduplication applied to the concatenated period file when `concat_batches` is enabled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DedupStrategy {
+    /// Keep every row (default)
+    #[default]
+    None,
+    /// Keep only the most recently `updated` row per `contract_id` (or `id` when it is null)
+    Latest,
+}
+
+impl FromStr for DedupStrategy {
+    type Err = AppError;
+
+    /// Parses a strategy name (`latest`, `none`), case-insensitively.
+    fn from_str(value: &str) -> AppResult<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "none" => Ok(Self::None),
+            "latest" => Ok(Self::Latest),
+            other => Err(AppError::InvalidInput(format!(
+                "Unknown dedup strategy '{other}' (expected latest or none)"
+            ))),
+        }
+    }
+}
+
+/// How entries containing more than one `<ContractFolderStatus>` subtree are resolved.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MultipleCfsStrategy {
+    /// Keep only the last subtree in document order (default)
+    #[default]
+    Latest,
+    /// Merge all subtrees in document order, later values overriding earlier ones
+    Merge,
+}
+
+impl FromStr for MultipleCfsStrategy {
+    type Err = AppError;
+
+    /// Parses a strategy name (`latest`, `merge`), case-insensitively.
+    fn from_str(value: &str) -> AppResult<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "latest" => Ok(Self::Latest),
+            "merge" => Ok(Self::Merge),
+            other => Err(AppError::InvalidInput(format!(
+                "Unknown multiple ContractFolderStatus strategy '{other}' (expected latest or merge)"
+            ))),
+        }
+    }
+}
 
 /// Resolved configuration with all values filled in (no Options).
 ///
@@ -18,6 +159,28 @@ pub struct ResolvedConfig {
     pub parquet_dir_mc: PathBuf,
     /// Directory for processed public tenders Parquet files
     pub parquet_dir_pt: PathBuf,
+    /// Directory for downloaded aggregated platforms ZIP files
+    pub download_dir_agg: PathBuf,
+    /// Directory for processed aggregated platforms Parquet files
+    pub parquet_dir_agg: PathBuf,
+    /// State file recording the last completed period per procurement type
+    pub state_file: PathBuf,
+    /// Directory for the landing page links cache (`links_{mc,pt,agg}.json`)
+    pub links_cache_dir: PathBuf,
+
+    // Pipeline steps
+    /// Whether to skip downloading and work from the ZIPs (or extracted directories) on disk
+    pub skip_download: bool,
+    /// Whether to skip ZIP extraction and parse the directories already extracted
+    pub skip_extraction: bool,
+    /// Whether to stop before parsing, keeping the downloaded and extracted files
+    pub skip_parse: bool,
+    /// Periods (`YYYY`, `YYYYMM`, or `YYYYQn`) dropped from the selected range
+    pub exclude_periods: Vec<String>,
+    /// When set, each period goes through download, extraction, parsing, and cleanup on its
+    /// own, with at most this many periods in flight; otherwise every stage finishes for all
+    /// periods before the next one starts.
+    pub periods_in_flight: Option<usize>,
 
     // Processing
     /// Number of XML files processed per chunk during parsing.
@@ -29,20 +192,131 @@ pub struct ResolvedConfig {
     /// When set to 0 (default), automatically uses available_parallelism().
     /// In Docker/constrained environments, set to the container's CPU limit.
     pub parser_threads: usize,
+    /// Number of periods parsed concurrently. They share the parser thread pool.
+    pub parallel_periods: usize,
+    /// Directory caching parsed XML documents by content hash (no cache when `None`).
+    pub cache_dir: Option<PathBuf>,
+    /// Whether extracted directories must be named exactly after their period
+    /// (no case-insensitive or fuzzy matching).
+    pub strict_period_dirs: bool,
     /// Whether to concatenate per-batch parquet files into a single period file.
     pub concat_batches: bool,
+    /// Whether to stream each period into a single Parquet file, one row group per batch,
+    /// instead of writing batch files (`concat_batches` and `dedup` are then ignored).
+    pub single_file_stream: bool,
+    /// Whether to keep each period's batch directory and only parse the XML files not yet
+    /// listed in its `.processed_files.json` manifest, appending them as new batches.
+    pub incremental: bool,
+    /// Maximum rows per Parquet batch file; larger batches are split into
+    /// `batch_{i}_{j}.parquet` shards (no limit when `None`)
+    pub max_entries_per_file: Option<usize>,
+    /// Deduplication applied to the concatenated period file (ignored unless `concat_batches`).
+    pub dedup: DedupStrategy,
+    /// Whether to drop repeated entry `id`s within each period, keeping the last occurrence.
+    pub dedupe_by_id: bool,
+    /// Whether to also write `{period}_lots.parquet`, one row per lot joined with its tender result.
+    pub join_lot_results: bool,
+    /// Whether to write each period to a Hive-style `year=YYYY/month=MM/` partition
+    /// (`month=00` for year-only periods) instead of `{period}.parquet`/`{period}/`.
+    pub partitioned: bool,
+    /// Whether to add a `project_cpv_description` column with the description of `project.cpv_code`.
+    pub enrich_cpv: bool,
     /// Whether to include the raw ContractFolderStatus XML in the parquet output.
     pub keep_cfs_raw_xml: bool,
-    /// Maximum number of retry attempts for failed downloads
+    /// Compression codec of the `cfs_raw_xml` column when `keep_cfs_raw_xml` is set
+    /// (`None` uses `parquet_compression`, like every other column).
+    pub raw_xml_compression: Option<RawXmlCompressionCodec>,
+    /// Whether a malformed entry is logged and skipped instead of failing its whole file.
+    pub lenient: bool,
+    /// Whether to collapse whitespace runs (newlines, tabs, indentation) in extracted text fields.
+    pub normalize_whitespace: bool,
+    /// Whether amount fields are rewritten to dot-decimal form without thousands separators
+    /// (`1.234,56` becomes `1234.56`); values that cannot be normalized are kept as read.
+    pub normalize_amounts: bool,
+    /// Entries whose project total amount is below this are dropped before writing
+    /// (see [`crate::transformer::FilterByMinAmount`]).
+    pub filter_min_amount: Option<f64>,
+    /// Whether empty text fields are written as null instead of `""` and empty `categories`
+    /// terms are dropped.
+    pub null_empty_strings: bool,
+    /// Whether to count ContractFolderStatus elements the parser does not map and write them
+    /// to a `{period}.unknown_elements.json` report next to each period's output.
+    pub report_unknown_elements: bool,
+    /// How entries with several ContractFolderStatus subtrees are resolved.
+    pub multiple_cfs: MultipleCfsStrategy,
+    /// Output file format (`parquet` or `jsonl`).
+    pub output_format: OutputFormat,
+    /// Compression codec used when writing Parquet files (batch and concatenated output).
+    pub parquet_compression: ParquetCompressionCodec,
+    /// Whether to write a `{period}.sha256` checksum file next to each period's output.
+    pub checksums: bool,
+    /// Whether to write a per-period `manifest.json` to the Parquet directory at the end of a run.
+    pub manifest: bool,
+    /// Number of most recent periods whose output is kept in the Parquet directory after a
+    /// run; the output of older periods is deleted (everything is kept when `None`).
+    pub retention: Option<usize>,
+    /// Whether `retention` only prints the paths it would delete.
+    pub retention_dry_run: bool,
+    /// DuckDB database each parsed period is also loaded into, as normalized `entries`,
+    /// `project_lots`, `tender_results`, `awarding_criteria`, and `categories` tables (none
+    /// when `None`; requires the `duckdb` feature and Parquet output).
+    pub duckdb_path: Option<PathBuf>,
+    /// Whether a failed period lets the remaining periods continue (partial failure).
+    pub keep_going: bool,
+    /// Whether the first failed download cancels the remaining downloads instead of letting
+    /// them finish before the stage fails.
+    pub fail_fast: bool,
+    /// Whether to draw the download and parsing progress bars (they are always hidden when not on a terminal).
+    pub progress: bool,
+    /// Maximum number of retry attempts for failed downloads and landing-page fetches
     pub max_retries: u32,
     /// Initial delay in milliseconds before the first retry
     pub retry_initial_delay_ms: u64,
     /// Maximum delay in milliseconds between retries
     pub retry_max_delay_ms: u64,
+    /// Whether to randomize retry delays by ±50% to avoid synchronized retries
+    pub retry_jitter: bool,
 
     // Downloads
+    /// Local directory of period ZIPs read instead of the procurement portal, if set
+    pub source_dir: Option<PathBuf>,
     /// Number of concurrent download tasks
     pub concurrent_downloads: usize,
+    /// Maximum download request starts per second across all tasks (no limit when `None`)
+    pub max_rps: Option<f64>,
+    /// Maximum combined download bandwidth in bytes per second across all tasks (no limit
+    /// when `None`)
+    pub max_bytes_per_sec: Option<u64>,
+    /// Whether to check each downloaded ZIP against a `{zip_url}.sha256` or `{zip_url}.md5`
+    /// file published next to it, when the server has one
+    pub verify_remote_checksum: bool,
+    /// Connect timeout in seconds when fetching the landing pages that list the ZIP links
+    pub fetch_connect_timeout_secs: u64,
+    /// Overall request timeout in seconds when fetching the landing pages
+    pub fetch_timeout_secs: u64,
+    /// How long, in seconds, the cached landing page links are reused before the pages are
+    /// fetched again (0 disables the cache)
+    pub links_cache_ttl_secs: u64,
+    /// Whether to fetch the landing pages even when the links cache is fresh
+    pub refresh_links: bool,
+    /// Proxy URL used for every request, if set; otherwise the `HTTPS_PROXY`/`HTTP_PROXY`
+    /// environment variables apply
+    pub proxy: Option<String>,
+    /// PEM bundle of extra CA certificates trusted for HTTPS, if set
+    pub ca_cert: Option<PathBuf>,
+    /// User agent sent with every request; `sppd-cli/<version>` when unset
+    pub user_agent: Option<String>,
+
+    // Extraction
+    /// Number of ZIP archives extracted concurrently.
+    /// When set to 0 (default), uses twice available_parallelism().
+    pub concurrent_extractions: usize,
+    /// Maximum total uncompressed bytes extracted from a single ZIP archive (zip-bomb guard)
+    pub max_extracted_bytes: u64,
+    /// Maximum uncompressed bytes extracted from a single ZIP member
+    pub max_extracted_member_bytes: u64,
+    /// Maximum number of members accepted in a single ZIP archive
+    pub max_zip_entries: usize,
 }
 
 impl Default for ResolvedConfig {
@@ -52,19 +326,365 @@ impl Default for ResolvedConfig {
             download_dir_pt: PathBuf::from("data/tmp/pt"),
             parquet_dir_mc: PathBuf::from("data/parquet/mc"),
             parquet_dir_pt: PathBuf::from("data/parquet/pt"),
-            batch_size: 150,
+            download_dir_agg: PathBuf::from("data/tmp/agg"),
+            parquet_dir_agg: PathBuf::from("data/parquet/agg"),
+            state_file: PathBuf::from("data/.sppd_state.json"),
+            links_cache_dir: PathBuf::from("data/cache"),
+            skip_download: false,
+            skip_extraction: false,
+            skip_parse: false,
+            exclude_periods: Vec::new(),
+            periods_in_flight: None,
+            batch_size: 500,
             read_concurrency: 16,
             parser_threads: 0, // 0 means auto-detect via available_parallelism()
+            parallel_periods: 1,
+            cache_dir: None,
+            strict_period_dirs: false,
             concat_batches: false,
+            single_file_stream: false,
+            incremental: false,
+            max_entries_per_file: None,
+            dedup: DedupStrategy::None,
+            dedupe_by_id: false,
+            join_lot_results: false,
+            partitioned: false,
+            enrich_cpv: false,
             keep_cfs_raw_xml: false,
+            raw_xml_compression: None,
+            lenient: false,
+            normalize_whitespace: true,
+            normalize_amounts: false,
+            filter_min_amount: None,
+            null_empty_strings: false,
+            report_unknown_elements: false,
+            multiple_cfs: MultipleCfsStrategy::Latest,
+            output_format: OutputFormat::Parquet,
+            parquet_compression: ParquetCompressionCodec::Zstd,
+            checksums: false,
+            manifest: false,
+            retention: None,
+            retention_dry_run: false,
+            duckdb_path: None,
+            keep_going: false,
+            fail_fast: false,
+            progress: true,
             max_retries: 3,
             retry_initial_delay_ms: 1000,
             retry_max_delay_ms: 10000,
+            retry_jitter: true,
+            source_dir: None,
             concurrent_downloads: 4,
+            max_rps: None,
+            max_bytes_per_sec: None,
+            verify_remote_checksum: false,
+            fetch_connect_timeout_secs: 10,
+            fetch_timeout_secs: 60,
+            links_cache_ttl_secs: 6 * 60 * 60,
+            refresh_links: false,
+            proxy: None,
+            ca_cert: None,
+            user_agent: None,
+            concurrent_extractions: 0, // 0 means 2 x available_parallelism()
+            max_extracted_bytes: 32 * 1024 * 1024 * 1024,
+            max_extracted_member_bytes: 8 * 1024 * 1024 * 1024,
+            max_zip_entries: 100_000,
+        }
+    }
+}
+
+impl ResolvedConfig {
+    /// Relocates the whole data layout under `data_dir`.
+    ///
+    /// Paths left at their defaults move to the layout under `data_dir`: downloads go to
+    /// `{data_dir}/tmp/{mc,pt}`, Parquet output to `{data_dir}/parquet/{mc,pt}`, the state
+    /// file to `{data_dir}/.sppd_state.json`, and the links cache to `{data_dir}/cache`. The default layout uses `data`. Other relative
+    /// paths (e.g. set in a config file) resolve against `data_dir`; absolute ones are kept.
+    pub fn set_data_dir(&mut self, data_dir: &Path) {
+        let defaults = Self::default();
+        let layout = [
+            (
+                &mut self.download_dir_mc,
+                defaults.download_dir_mc,
+                Path::new("tmp/mc"),
+            ),
+            (
+                &mut self.download_dir_pt,
+                defaults.download_dir_pt,
+                Path::new("tmp/pt"),
+            ),
+            (
+                &mut self.parquet_dir_mc,
+                defaults.parquet_dir_mc,
+                Path::new("parquet/mc"),
+            ),
+            (
+                &mut self.parquet_dir_pt,
+                defaults.parquet_dir_pt,
+                Path::new("parquet/pt"),
+            ),
+            (
+                &mut self.download_dir_agg,
+                defaults.download_dir_agg,
+                Path::new("tmp/agg"),
+            ),
+            (
+                &mut self.parquet_dir_agg,
+                defaults.parquet_dir_agg,
+                Path::new("parquet/agg"),
+            ),
+            (
+                &mut self.state_file,
+                defaults.state_file,
+                Path::new(".sppd_state.json"),
+            ),
+            (
+                &mut self.links_cache_dir,
+                defaults.links_cache_dir,
+                Path::new("cache"),
+            ),
+        ];
+        for (path, default, relative) in layout {
+            // `join` keeps absolute paths as they are.
+            *path = if *path == default {
+                data_dir.join(relative)
+            } else {
+                data_dir.join(&*path)
+            };
+        }
+    }
+
+    /// Writes Parquet output to `{output_dir}/{mc,pt,agg}`.
+    pub fn set_output_dir(&mut self, output_dir: &Path) {
+        self.parquet_dir_mc = output_dir.join("mc");
+        self.parquet_dir_pt = output_dir.join("pt");
+        self.parquet_dir_agg = output_dir.join("agg");
+    }
+
+    /// Applies a data directory with [`Self::set_data_dir`] and an output directory with
+    /// [`Self::set_output_dir`], whether they come from the command line or a config file.
+    ///
+    /// A relative output directory resolves against the data directory when both are set.
+    pub fn set_dirs(&mut self, data_dir: Option<&Path>, output_dir: Option<&Path>) {
+        if let Some(data_dir) = data_dir {
+            self.set_data_dir(data_dir);
+        }
+        if let Some(output_dir) = output_dir {
+            let output_dir = match data_dir {
+                Some(data_dir) => data_dir.join(output_dir),
+                None => output_dir.to_path_buf(),
+            };
+            self.set_output_dir(&output_dir);
+        }
+    }
+
+    /// Checks that batch_size, read_concurrency, parallel_periods, the fetch timeouts, and
+    /// max_rps, max_bytes_per_sec, and periods_in_flight (when set) are greater than 0, that
+    /// at least one pipeline step is enabled, that every excluded period is well formed, that
+    /// filter_min_amount (when set) is finite, and that `duckdb_path` is only set for Parquet output in a `duckdb` build.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidInput` naming the first invalid setting.
+    pub fn validate(&self) -> AppResult<()> {
+        if self.batch_size == 0 {
+            return Err(AppError::InvalidInput(
+                "Batch size must be greater than 0".into(),
+            ));
+        }
+        if self.read_concurrency == 0 {
+            return Err(AppError::InvalidInput(
+                "Read concurrency must be greater than 0".into(),
+            ));
+        }
+        if self.parallel_periods == 0 {
+            return Err(AppError::InvalidInput(
+                "Parallel periods must be greater than 0".into(),
+            ));
+        }
+        if self.fetch_connect_timeout_secs == 0 || self.fetch_timeout_secs == 0 {
+            return Err(AppError::InvalidInput(
+                "Fetch timeouts must be greater than 0".into(),
+            ));
+        }
+        if let Some(max_rps) = self.max_rps {
+            if !(max_rps.is_finite() && max_rps > 0.0) {
+                return Err(AppError::InvalidInput(format!(
+                    "Max requests per second must be a positive number, got {max_rps}"
+                )));
+            }
+        }
+        if let Some(threshold) = self.filter_min_amount {
+            if !threshold.is_finite() {
+                return Err(AppError::InvalidInput(format!(
+                    "Minimum amount must be a finite number, got {threshold}"
+                )));
+            }
+        }
+        // `dedupe_by_id` only sees the entries parsed in one run, so it cannot drop the
+        // duplicates of entries written to earlier incremental batches.
+        if self.incremental
+            && (self.single_file_stream
+                || self.partitioned
+                || self.dedupe_by_id
+                || self.output_format != OutputFormat::Parquet)
+        {
+            return Err(AppError::InvalidInput(
+                "incremental requires Parquet batch output and cannot be combined with single_file_stream, partitioned or dedupe_by_id".into(),
+            ));
+        }
+        if self.fail_fast && self.keep_going {
+            return Err(AppError::InvalidInput(
+                "fail_fast and keep_going cannot be combined".into(),
+            ));
+        }
+        if self.max_bytes_per_sec == Some(0) {
+            return Err(AppError::InvalidInput(
+                "Max bytes per second must be greater than 0".into(),
+            ));
+        }
+        if self.max_entries_per_file == Some(0) {
+            return Err(AppError::InvalidInput(
+                "Max entries per file must be greater than 0".into(),
+            ));
+        }
+        if self.retention == Some(0) {
+            return Err(AppError::InvalidInput(
+                "Retention must keep at least 1 period".into(),
+            ));
+        }
+        if self.periods_in_flight == Some(0) {
+            return Err(AppError::InvalidInput(
+                "Periods in flight must be greater than 0".into(),
+            ));
         }
+        if self.skip_download && self.skip_extraction && self.skip_parse {
+            return Err(AppError::InvalidInput(
+                "Download, extraction, and parsing are all skipped; nothing to do".into(),
+            ));
+        }
+        for period in &self.exclude_periods {
+            crate::downloader::validate_period_format(period)?;
+        }
+        if self.duckdb_path.is_some() {
+            if !cfg!(feature = "duckdb") {
+                return Err(AppError::InvalidInput(
+                    "duckdb_path requires sppd-cli to be built with the `duckdb` feature".into(),
+                ));
+            }
+            if self.output_format != OutputFormat::Parquet {
+                return Err(AppError::InvalidInput(
+                    "duckdb_path requires Parquet output".into(),
+                ));
+            }
+        }
+        Ok(())
     }
 }
 
+/// Config file looked up in the current directory when `--config-file` is not given.
+pub const DEFAULT_CONFIG_FILE: &str = "sppd-cli.toml";
+
+/// Pipeline settings read from a `--config-file` TOML (or `sppd-cli.toml`).
+///
+/// Holds any `ResolvedConfig` key plus `data_dir` and `output_dir`. Missing keys keep their
+/// built-in defaults; [`Self::merge`] then applies the directories, letting the command-line
+/// ones win over the file's, and the remaining flags are applied on top by the caller.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PipelineConfigFile {
+    /// Root data directory, like `--data-dir`
+    #[serde(default)]
+    pub data_dir: Option<PathBuf>,
+    /// Parquet output directory, like `--output-dir`
+    #[serde(default)]
+    pub output_dir: Option<PathBuf>,
+    /// Flattened pipeline settings
+    #[serde(flatten)]
+    pub resolved: ResolvedConfig,
+}
+
+impl PipelineConfigFile {
+    /// Loads pipeline settings from a TOML file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IoError` if the file cannot be read, and `InvalidInput` with the line and
+    /// column if the TOML is malformed, has unknown keys, or fails [`ResolvedConfig::validate`].
+    pub fn from_toml_file(path: &Path) -> AppResult<Self> {
+        let config: PipelineConfigFile = read_toml_file(path)?;
+        config.resolved.validate()?;
+        Ok(config)
+    }
+
+    /// Loads `path` when given, or [`DEFAULT_CONFIG_FILE`] from the current directory when
+    /// it exists; returns the built-in defaults otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Self::from_toml_file`]; an explicit `path` that does not exist is an error.
+    pub fn load(path: Option<&Path>) -> AppResult<Self> {
+        Self::load_or_default(path, Path::new(DEFAULT_CONFIG_FILE))
+    }
+
+    fn load_or_default(path: Option<&Path>, default_path: &Path) -> AppResult<Self> {
+        match path {
+            Some(path) => Self::from_toml_file(path),
+            None if default_path.is_file() => {
+                tracing::info!(path = %default_path.display(), "Loading default config file");
+                Self::from_toml_file(default_path)
+            }
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Builds the final `ResolvedConfig`, with `data_dir` and `output_dir` (from the command
+    /// line) taking precedence over the directories of the file; both are applied with
+    /// [`ResolvedConfig::set_dirs`].
+    pub fn merge(self, data_dir: Option<&Path>, output_dir: Option<&Path>) -> ResolvedConfig {
+        let mut resolved = self.resolved;
+        resolved.set_dirs(
+            data_dir.or(self.data_dir.as_deref()),
+            output_dir.or(self.output_dir.as_deref()),
+        );
+        resolved
+    }
+}
+
+/// Reads and deserializes a TOML config file.
+///
+/// # Errors
+///
+/// Returns `IoError` if the file cannot be read, and `InvalidInput` with the line and column
+/// if the TOML is malformed or has unknown keys.
+fn read_toml_file<T: serde::de::DeserializeOwned>(path: &Path) -> AppResult<T> {
+    let contents = fs::read_to_string(path).map_err(|e| {
+        AppError::IoError(format!(
+            "Failed to read config file {}: {e}",
+            path.display()
+        ))
+    })?;
+    toml::from_str(&contents).map_err(|e| toml_error(path, &contents, &e))
+}
+
+/// Builds an `InvalidInput` error for a TOML parse failure, pointing at the line and column.
+fn toml_error(path: &Path, contents: &str, err: &toml::de::Error) -> AppError {
+    let location = err
+        .span()
+        .map(|span| {
+            let before = &contents[..span.start.min(contents.len())];
+            let line = before.matches('\n').count() + 1;
+            let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+            format!(" at line {line}, column {column}")
+        })
+        .unwrap_or_default();
+    AppError::InvalidInput(format!(
+        "Failed to parse config {}{location}: {}",
+        path.display(),
+        err.message().trim_end()
+    ))
+}
+
 /// Configuration that can be loaded from a TOML file.
 ///
 /// Deserializes required fields (type, start, end) and optional pipeline configuration.
@@ -83,7 +703,13 @@ pub struct ResolvedConfigFile {
     /// Whether to clean up temporary ZIP and extracted files (defaults to `true`)
     #[serde(default = "default_cleanup")]
     pub cleanup: bool,
-    /// Flattened resolved configuration with pipeline defaults
+    /// Root data directory, like `--data-dir`
+    #[serde(default)]
+    pub data_dir: Option<PathBuf>,
+    /// Parquet output directory, like `--output-dir`
+    #[serde(default)]
+    pub output_dir: Option<PathBuf>,
+    /// Flattened pipeline settings
     #[serde(flatten)]
     pub resolved: ResolvedConfig,
 }
@@ -92,7 +718,7 @@ impl ResolvedConfigFile {
     /// Loads and validates configuration from a TOML file.
     ///
     /// Deserializes the TOML file and ensures all required fields are present.
-    /// Validates that batch_size and read_concurrency are greater than 0.
+    /// Validates that batch_size, read_concurrency, and the fetch timeouts are greater than 0.
     /// Rejects unknown keys to prevent typos from being silently ignored.
     ///
     /// # Arguments
@@ -108,22 +734,19 @@ impl ResolvedConfigFile {
     /// Returns `InvalidInput` if the TOML is malformed, required fields are missing,
     /// unknown keys are present, or batch_size/read_concurrency are not positive.
     pub fn from_toml_file(path: &Path) -> AppResult<Self> {
-        let contents = fs::read_to_string(path)?;
-        let config: ResolvedConfigFile = toml::from_str(&contents)
-            .map_err(|e| AppError::InvalidInput(format!("Failed to parse config: {e}")))?;
+        let config: ResolvedConfigFile = read_toml_file(path)?;
+        config.resolved.validate()?;
+        Ok(config)
+    }
 
-        if config.resolved.batch_size == 0 {
-            return Err(AppError::InvalidInput(
-                "Batch size must be greater than 0".into(),
-            ));
+    /// Builds the final `ResolvedConfig` exactly like [`PipelineConfigFile::merge`].
+    pub fn merge(&self, data_dir: Option<&Path>, output_dir: Option<&Path>) -> ResolvedConfig {
+        PipelineConfigFile {
+            data_dir: self.data_dir.clone(),
+            output_dir: self.output_dir.clone(),
+            resolved: self.resolved.clone(),
         }
-        if config.resolved.read_concurrency == 0 {
-            return Err(AppError::InvalidInput(
-                "Read concurrency must be greater than 0".into(),
-            ));
-        }
-
-        Ok(config)
+        .merge(data_dir, output_dir)
     }
 }
 
@@ -140,11 +763,246 @@ mod tests {
     #[test]
     fn default_config_values() {
         let config = ResolvedConfig::default();
-        assert_eq!(config.batch_size, 150);
+        assert_eq!(config.batch_size, 500);
         assert_eq!(config.read_concurrency, 16);
+        assert_eq!(config.parallel_periods, 1);
+        assert_eq!(config.periods_in_flight, None);
         assert!(!config.concat_batches);
         assert!(!config.keep_cfs_raw_xml);
+        assert_eq!(config.raw_xml_compression, None);
         assert_eq!(config.concurrent_downloads, 4);
+        assert_eq!(config.max_rps, None);
+        assert_eq!(config.max_bytes_per_sec, None);
+        assert!(!config.verify_remote_checksum);
+        assert_eq!(config.fetch_connect_timeout_secs, 10);
+        assert_eq!(config.fetch_timeout_secs, 60);
+        assert_eq!(config.links_cache_ttl_secs, 21_600);
+        assert!(!config.refresh_links);
+        assert_eq!(config.links_cache_dir, PathBuf::from("data/cache"));
+        assert_eq!(config.proxy, None);
+        assert_eq!(config.ca_cert, None);
+        assert_eq!(config.user_agent, None);
+        assert_eq!(config.concurrent_extractions, 0);
+        assert_eq!(config.max_extracted_bytes, 32 * 1024 * 1024 * 1024);
+        assert_eq!(config.max_extracted_member_bytes, 8 * 1024 * 1024 * 1024);
+        assert_eq!(config.max_zip_entries, 100_000);
+        assert_eq!(config.parquet_compression, ParquetCompressionCodec::Zstd);
+        assert_eq!(config.dedup, DedupStrategy::None);
+        assert_eq!(config.output_format, OutputFormat::Parquet);
+        assert_eq!(config.multiple_cfs, MultipleCfsStrategy::Latest);
+        assert!(config.progress);
+        assert!(!config.checksums);
+        assert!(!config.manifest);
+        assert!(config.duckdb_path.is_none());
+        assert!(!config.keep_going);
+        assert!(!config.fail_fast);
+        assert!(config.cache_dir.is_none());
+        assert!(!config.strict_period_dirs);
+        assert!(!config.lenient);
+        assert!(config.normalize_whitespace);
+        assert!(!config.normalize_amounts);
+        assert_eq!(config.filter_min_amount, None);
+        assert!(!config.null_empty_strings);
+        assert!(!config.report_unknown_elements);
+        assert!(!config.dedupe_by_id);
+        assert!(!config.join_lot_results);
+        assert!(!config.partitioned);
+        assert!(!config.single_file_stream);
+        assert!(!config.incremental);
+        assert_eq!(config.max_entries_per_file, None);
+        assert_eq!(config.retention, None);
+        assert!(!config.retention_dry_run);
+        assert!(!config.enrich_cpv);
+        assert!(config.retry_jitter);
+        assert!(!config.skip_download);
+        assert!(!config.skip_extraction);
+        assert!(!config.skip_parse);
+        assert!(config.exclude_periods.is_empty());
+    }
+
+    #[test]
+    fn duckdb_path_requires_parquet_output_and_feature() {
+        let mut config = ResolvedConfig {
+            duckdb_path: Some(PathBuf::from("sppd.duckdb")),
+            ..Default::default()
+        };
+        assert_eq!(config.validate().is_ok(), cfg!(feature = "duckdb"));
+
+        config.output_format = OutputFormat::Jsonl;
+        assert!(matches!(config.validate(), Err(AppError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn incremental_requires_parquet_batch_output() {
+        let mut config = ResolvedConfig {
+            incremental: true,
+            concat_batches: true,
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+
+        for conflicting in [
+            ResolvedConfig {
+                single_file_stream: true,
+                ..config.clone()
+            },
+            ResolvedConfig {
+                partitioned: true,
+                ..config.clone()
+            },
+            ResolvedConfig {
+                dedupe_by_id: true,
+                ..config.clone()
+            },
+        ] {
+            assert!(matches!(
+                conflicting.validate(),
+                Err(AppError::InvalidInput(_))
+            ));
+        }
+        config.output_format = OutputFormat::Jsonl;
+        assert!(matches!(config.validate(), Err(AppError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn fail_fast_conflicts_with_keep_going() {
+        let mut config = ResolvedConfig {
+            fail_fast: true,
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+
+        config.keep_going = true;
+        assert!(matches!(config.validate(), Err(AppError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn data_and_output_dirs_relocate_paths() {
+        let mut config = ResolvedConfig::default();
+        config.set_data_dir(Path::new("/mnt/sppd"));
+        assert_eq!(config.download_dir_pt, PathBuf::from("/mnt/sppd/tmp/pt"));
+        assert_eq!(config.parquet_dir_mc, PathBuf::from("/mnt/sppd/parquet/mc"));
+        assert_eq!(
+            config.state_file,
+            PathBuf::from("/mnt/sppd/.sppd_state.json")
+        );
+        assert_eq!(config.links_cache_dir, PathBuf::from("/mnt/sppd/cache"));
+        assert_eq!(config.download_dir_agg, PathBuf::from("/mnt/sppd/tmp/agg"));
+
+        config.set_output_dir(Path::new("/out"));
+        assert_eq!(config.parquet_dir_pt, PathBuf::from("/out/pt"));
+        assert_eq!(config.parquet_dir_agg, PathBuf::from("/out/agg"));
+        assert_eq!(config.download_dir_mc, PathBuf::from("/mnt/sppd/tmp/mc"));
+    }
+
+    #[test]
+    fn data_dir_resolves_configured_relative_paths() {
+        let mut config = ResolvedConfig {
+            download_dir_pt: PathBuf::from("downloads/pt"),
+            parquet_dir_pt: PathBuf::from("/srv/parquet/pt"),
+            ..Default::default()
+        };
+        config.set_data_dir(Path::new("/scratch"));
+        assert_eq!(
+            config.download_dir_pt,
+            PathBuf::from("/scratch/downloads/pt")
+        );
+        assert_eq!(config.parquet_dir_pt, PathBuf::from("/srv/parquet/pt"));
+        assert_eq!(config.download_dir_mc, PathBuf::from("/scratch/tmp/mc"));
+    }
+
+    #[test]
+    fn data_dir_is_read_from_toml() {
+        let mut tmp = NamedTempFile::new().unwrap();
+        write!(
+            tmp,
+            r#"
+            type = "pt"
+            start = "202301"
+            end = "202302"
+            data_dir = "/srv/data"
+            output_dir = "/srv/out"
+            "#,
+        )
+        .unwrap();
+
+        let config = ResolvedConfigFile::from_toml_file(tmp.path())
+            .unwrap()
+            .merge(None, None);
+        assert_eq!(config.download_dir_pt, PathBuf::from("/srv/data/tmp/pt"));
+        assert_eq!(config.parquet_dir_pt, PathBuf::from("/srv/out/pt"));
+    }
+
+    #[test]
+    fn toml_relative_output_dir_resolves_against_data_dir() {
+        let mut tmp = NamedTempFile::new().unwrap();
+        write!(
+            tmp,
+            r#"
+            type = "pt"
+            start = "202301"
+            end = "202302"
+            data_dir = "/srv/data"
+            output_dir = "out"
+            "#,
+        )
+        .unwrap();
+
+        let file = ResolvedConfigFile::from_toml_file(tmp.path()).unwrap();
+        let config = file.merge(None, None);
+        assert_eq!(config.parquet_dir_pt, PathBuf::from("/srv/data/out/pt"));
+
+        // Command-line directories override the file's, with the same rules.
+        let config = file.merge(Some(Path::new("/cli")), None);
+        assert_eq!(config.download_dir_pt, PathBuf::from("/cli/tmp/pt"));
+        assert_eq!(config.parquet_dir_pt, PathBuf::from("/cli/out/pt"));
+    }
+
+    #[test]
+    fn parquet_compression_parses_names() {
+        assert_eq!(
+            "snappy".parse::<ParquetCompressionCodec>().unwrap(),
+            ParquetCompressionCodec::Snappy
+        );
+        assert_eq!(
+            "Uncompressed".parse::<ParquetCompressionCodec>().unwrap(),
+            ParquetCompressionCodec::Uncompressed
+        );
+        assert_eq!(
+            "lz4".parse::<ParquetCompressionCodec>().unwrap(),
+            ParquetCompressionCodec::Lz4
+        );
+        assert_eq!(
+            "none".parse::<ParquetCompressionCodec>().unwrap(),
+            ParquetCompressionCodec::Uncompressed
+        );
+        assert!("lzo".parse::<ParquetCompressionCodec>().is_err());
+        assert_eq!(
+            "LZ4".parse::<RawXmlCompressionCodec>().unwrap(),
+            RawXmlCompressionCodec::Lz4
+        );
+        assert!("snappy".parse::<RawXmlCompressionCodec>().is_err());
+    }
+
+    #[test]
+    fn parquet_compression_is_read_from_toml() {
+        let mut tmp = NamedTempFile::new().unwrap();
+        write!(
+            tmp,
+            r#"
+            type = "pt"
+            start = "202301"
+            end = "202302"
+            parquet_compression = "snappy"
+            "#,
+        )
+        .unwrap();
+
+        let config = ResolvedConfigFile::from_toml_file(tmp.path()).unwrap();
+        assert_eq!(
+            config.resolved.parquet_compression,
+            ParquetCompressionCodec::Snappy
+        );
     }
 
     #[test]
@@ -199,6 +1057,52 @@ mod tests {
         )
         .unwrap();
 
-        assert!(ResolvedConfigFile::from_toml_file(tmp.path()).is_err());
+        let err = ResolvedConfigFile::from_toml_file(tmp.path())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("extra_flag"), "{err}");
+    }
+
+    #[test]
+    fn pipeline_config_file_merge_precedence() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(DEFAULT_CONFIG_FILE);
+        std::fs::write(
+            &path,
+            r#"
+            batch_size = 40
+            concurrent_downloads = 8
+            max_retries = 7
+            parquet_compression = "snappy"
+            data_dir = "/srv/data"
+            output_dir = "/srv/out"
+            "#,
+        )
+        .unwrap();
+
+        // File values override defaults, and are looked up at the default path.
+        let file = PipelineConfigFile::load_or_default(None, &path).unwrap();
+        let config = file.clone().merge(None, None);
+        assert_eq!(config.batch_size, 40);
+        assert_eq!(config.concurrent_downloads, 8);
+        assert_eq!(config.max_retries, 7);
+        assert_eq!(config.parquet_compression, ParquetCompressionCodec::Snappy);
+        assert_eq!(config.read_concurrency, 16);
+        assert_eq!(config.download_dir_pt, PathBuf::from("/srv/data/tmp/pt"));
+        assert_eq!(config.parquet_dir_pt, PathBuf::from("/srv/out/pt"));
+
+        // Command-line directories override the file's.
+        let config = file.merge(Some(Path::new("/cli")), Some(Path::new("out")));
+        assert_eq!(config.download_dir_pt, PathBuf::from("/cli/tmp/pt"));
+        assert_eq!(config.parquet_dir_pt, PathBuf::from("/cli/out/pt"));
+
+        // Without a default file, the built-in defaults apply.
+        let missing = dir.path().join("missing.toml");
+        let config = PipelineConfigFile::load_or_default(None, &missing)
+            .unwrap()
+            .merge(None, None);
+        assert_eq!(config.batch_size, 500);
+        assert_eq!(config.parquet_dir_pt, PathBuf::from("data/parquet/pt"));
+        assert!(PipelineConfigFile::load(Some(&missing)).is_err());
     }
 }
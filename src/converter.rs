@@ -0,0 +1,346 @@
+//! Converts Parquet output to CSV or JSON Lines for tools that cannot read Parquet.
+//!
+//! Struct columns are flattened into one column per field, named `{column}_{field}`
+//! (`status_code`, `project_total_amount`, ...). List columns (`categories`, `project_lots`,
+//! `tender_results`, `awarding_criteria`) stay JSON arrays in JSON Lines and are written as
+//! JSON text in CSV, so every CSV cell holds a single value.
+
+use crate::errors::{AppError, AppResult};
+use polars::prelude::*;
+use serde_json::Value;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// File format written by [`convert_parquet`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConvertFormat {
+    /// Comma-separated values with a header row (default)
+    #[default]
+    Csv,
+    /// Newline-delimited JSON, one object per row
+    Jsonl,
+}
+
+impl ConvertFormat {
+    /// File extension of the converted files.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Jsonl => "jsonl",
+        }
+    }
+}
+
+impl FromStr for ConvertFormat {
+    type Err = AppError;
+
+    /// Parses a format name (`csv`, `jsonl`/`ndjson`), case-insensitively.
+    fn from_str(value: &str) -> AppResult<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "jsonl" | "ndjson" => Ok(Self::Jsonl),
+            other => Err(AppError::InvalidInput(format!(
+                "Unknown convert format '{other}' (expected csv or jsonl)"
+            ))),
+        }
+    }
+}
+
+/// Converts a Parquet output produced by the `cli`/`parse` subcommands to `format`.
+///
+/// `input` is either a single Parquet file, written to the file `output`, or a directory of
+/// period outputs, written to the directory `output` (created if missing) as one
+/// `{name}.{csv,jsonl}` per `{name}.parquet` file. A subdirectory holding batch files
+/// (`{period}/batch_N.parquet`) is converted into a single `{period}` file. Each input is
+/// read through [`LazyFrame::scan_parquet`]. Returns the written files in order.
+///
+/// # Errors
+///
+/// Returns `IoError` if `input` does not exist, a directory holds no Parquet output, or an
+/// output file cannot be written, and `ParseError` if a file cannot be read as Parquet or
+/// converted.
+pub fn convert_parquet(
+    input: &Path,
+    output: &Path,
+    format: ConvertFormat,
+) -> AppResult<Vec<PathBuf>> {
+    if !input.exists() {
+        return Err(AppError::IoError(format!(
+            "Path does not exist: {}",
+            input.display()
+        )));
+    }
+    if !input.is_dir() {
+        if let Some(parent) = output.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        convert_file(input, output, format)?;
+        return Ok(vec![output.to_path_buf()]);
+    }
+
+    let mut sources: Vec<PathBuf> = fs::read_dir(input)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect();
+    sources.sort();
+    let mut jobs = Vec::new();
+    for source in sources {
+        let Some(stem) = source.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        let target = output.join(format!("{stem}.{}", format.extension()));
+        if source.is_file() && is_parquet(&source) {
+            jobs.push((source.clone(), target));
+        } else if source.is_dir()
+            && fs::read_dir(&source)?
+                .flatten()
+                .any(|e| is_parquet(&e.path()))
+        {
+            jobs.push((source.join("*.parquet"), target));
+        }
+    }
+    if jobs.is_empty() {
+        return Err(AppError::IoError(format!(
+            "No Parquet files in {}",
+            input.display()
+        )));
+    }
+
+    fs::create_dir_all(output)?;
+    let mut written = Vec::with_capacity(jobs.len());
+    for (source, target) in jobs {
+        convert_file(&source, &target, format)?;
+        written.push(target);
+    }
+    Ok(written)
+}
+
+fn is_parquet(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()) == Some("parquet")
+}
+
+/// Scans `source` (a file or a glob of batch files), flattens its struct columns, and writes
+/// the rows to `target`.
+fn convert_file(source: &Path, target: &Path, format: ConvertFormat) -> AppResult<()> {
+    let frame = LazyFrame::scan_parquet(source, ScanArgsParquet::default())
+        .map_err(|e| convert_error(source, e))?;
+    let schema = frame.schema().map_err(|e| convert_error(source, e))?;
+    let mut columns = Vec::with_capacity(schema.len());
+    for (name, dtype) in schema.iter() {
+        flatten_column(col(name), name, dtype, &mut columns);
+    }
+    let mut df = frame
+        .select(columns)
+        .collect()
+        .map_err(|e| convert_error(source, e))?;
+
+    let file = File::create(target)
+        .map_err(|e| AppError::IoError(format!("Failed to create {}: {e}", target.display())))?;
+    let mut writer = BufWriter::new(file);
+    match format {
+        ConvertFormat::Csv => {
+            lists_to_json_text(&mut df).map_err(|e| convert_error(source, e))?;
+            CsvWriter::new(&mut writer)
+                .finish(&mut df)
+                .map_err(|e| convert_error(source, e))?;
+        }
+        ConvertFormat::Jsonl => write_json_lines(&mut df, &mut writer)?,
+    }
+    writer
+        .flush()
+        .map_err(|e| AppError::IoError(format!("Failed to write {}: {e}", target.display())))
+}
+
+/// Adds `expr` aliased to `name` to `columns`, or one column per field (`{name}_{field}`,
+/// recursively) when `dtype` is a struct.
+fn flatten_column(expr: Expr, name: &str, dtype: &DataType, columns: &mut Vec<Expr>) {
+    match dtype {
+        DataType::Struct(fields) => {
+            for field in fields {
+                flatten_column(
+                    expr.clone().struct_().field_by_name(field.name()),
+                    &format!("{name}_{}", field.name()),
+                    field.data_type(),
+                    columns,
+                );
+            }
+        }
+        _ => columns.push(expr.alias(name)),
+    }
+}
+
+/// Replaces every list column of `df` with its values encoded as JSON text.
+fn lists_to_json_text(df: &mut DataFrame) -> PolarsResult<()> {
+    let list_columns: Vec<String> = df
+        .get_columns()
+        .iter()
+        .filter(|s| matches!(s.dtype(), DataType::List(_)))
+        .map(|s| s.name().to_string())
+        .collect();
+    for name in list_columns {
+        let text: StringChunked = df
+            .column(&name)?
+            .list()?
+            .into_iter()
+            .map(|list| list.map(|values| to_json(AnyValue::List(values)).to_string()))
+            .collect();
+        df.with_column(text.into_series().with_name(&name))?;
+    }
+    Ok(())
+}
+
+/// Writes one JSON object per row of `df`, with the keys in column order.
+fn write_json_lines(df: &mut DataFrame, writer: &mut impl Write) -> AppResult<()> {
+    df.as_single_chunk();
+    let keys = df
+        .get_column_names()
+        .iter()
+        .map(|name| Value::from(*name).to_string())
+        .collect::<Vec<_>>();
+    let write_error =
+        |e: std::io::Error| AppError::IoError(format!("Failed to write JSON Lines: {e}"));
+    for row in 0..df.height() {
+        let mut line = String::from("{");
+        for (i, (key, column)) in keys.iter().zip(df.get_columns()).enumerate() {
+            if i > 0 {
+                line.push(',');
+            }
+            let value = column
+                .get(row)
+                .map_err(|e| AppError::ParseError(format!("Failed to read row {row}: {e}")))?;
+            line.push_str(key);
+            line.push(':');
+            line.push_str(&to_json(value).to_string());
+        }
+        line.push_str("}\n");
+        writer.write_all(line.as_bytes()).map_err(write_error)?;
+    }
+    Ok(())
+}
+
+/// Converts a cell to JSON; lists become arrays and structs objects.
+fn to_json(value: AnyValue) -> Value {
+    match value {
+        AnyValue::Null => Value::Null,
+        AnyValue::Boolean(b) => Value::Bool(b),
+        AnyValue::String(s) => Value::from(s),
+        AnyValue::StringOwned(s) => Value::from(s.as_str()),
+        AnyValue::UInt8(n) => Value::from(n),
+        AnyValue::UInt16(n) => Value::from(n),
+        AnyValue::UInt32(n) => Value::from(n),
+        AnyValue::UInt64(n) => Value::from(n),
+        AnyValue::Int8(n) => Value::from(n),
+        AnyValue::Int16(n) => Value::from(n),
+        AnyValue::Int32(n) => Value::from(n),
+        AnyValue::Int64(n) => Value::from(n),
+        AnyValue::Float32(n) => Value::from(n),
+        AnyValue::Float64(n) => Value::from(n),
+        AnyValue::List(values) => Value::Array(values.iter().map(to_json).collect()),
+        AnyValue::Struct(_, _, fields) => Value::Object(
+            fields
+                .iter()
+                .map(|field| field.name().to_string())
+                .zip(value._iter_struct_av().map(to_json))
+                .collect(),
+        ),
+        AnyValue::StructOwned(payload) => {
+            let (values, fields) = *payload;
+            Value::Object(
+                fields
+                    .iter()
+                    .map(|field| field.name().to_string())
+                    .zip(values.into_iter().map(to_json))
+                    .collect(),
+            )
+        }
+        other => Value::from(other.to_string()),
+    }
+}
+
+fn convert_error(path: &Path, e: PolarsError) -> AppError {
+    AppError::ParseError(format!("Failed to convert {}: {e}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Writes a two-row Parquet file shaped like the period output: a plain column, a struct
+    /// column, and a list-of-struct column.
+    fn write_sample(path: &Path, ids: [&str; 2]) {
+        let status = StructChunked::new("status", &[Series::new("code", ["ADJ", "PUB"])])
+            .unwrap()
+            .into_series();
+        let lot = |id: &str| {
+            StructChunked::new("lot", &[Series::new("id", [id])])
+                .unwrap()
+                .into_series()
+        };
+        let lots = Series::new("project_lots", [lot("1"), lot("2")]);
+        let mut df = DataFrame::new(vec![Series::new("id", ids), status, lots]).unwrap();
+        ParquetWriter::new(File::create(path).unwrap())
+            .finish(&mut df)
+            .unwrap();
+    }
+
+    #[test]
+    fn converts_a_file_to_flattened_csv() {
+        let tmp = TempDir::new().unwrap();
+        let input = tmp.path().join("202401.parquet");
+        write_sample(&input, ["a", "b"]);
+        let output = tmp.path().join("out/202401.csv");
+
+        let written = convert_parquet(&input, &output, ConvertFormat::Csv).unwrap();
+
+        assert_eq!(written, std::slice::from_ref(&output));
+        let csv = fs::read_to_string(&output).unwrap();
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines[0], "id,status_code,project_lots");
+        assert_eq!(lines[1], r#"a,ADJ,"[{""id"":""1""}]""#);
+        assert_eq!(lines.len(), 3);
+    }
+
+    #[test]
+    fn converts_a_directory_of_periods_to_json_lines() {
+        let tmp = TempDir::new().unwrap();
+        let input = tmp.path().join("pt");
+        fs::create_dir_all(input.join("202402")).unwrap();
+        write_sample(&input.join("202401.parquet"), ["a", "b"]);
+        write_sample(&input.join("202402/batch_0.parquet"), ["c", "d"]);
+        write_sample(&input.join("202402/batch_1.parquet"), ["e", "f"]);
+        fs::write(input.join("202401.sha256"), "").unwrap();
+        let output = tmp.path().join("jsonl");
+
+        let written = convert_parquet(&input, &output, ConvertFormat::Jsonl).unwrap();
+
+        assert_eq!(
+            written,
+            [output.join("202401.jsonl"), output.join("202402.jsonl")]
+        );
+        let first = fs::read_to_string(&written[0]).unwrap();
+        assert_eq!(
+            first.lines().next().unwrap(),
+            r#"{"id":"a","status_code":"ADJ","project_lots":[{"id":"1"}]}"#
+        );
+        let batches = fs::read_to_string(&written[1]).unwrap();
+        assert_eq!(batches.lines().count(), 4);
+    }
+
+    #[test]
+    fn rejects_missing_input_and_directory_without_parquet() {
+        let tmp = TempDir::new().unwrap();
+        let out = tmp.path().join("out");
+        assert!(matches!(
+            convert_parquet(&tmp.path().join("missing"), &out, ConvertFormat::Csv),
+            Err(AppError::IoError(_))
+        ));
+        assert!(matches!(
+            convert_parquet(tmp.path(), &out, ConvertFormat::Csv),
+            Err(AppError::IoError(_))
+        ));
+        assert!("NDJSON".parse::<ConvertFormat>().unwrap() == ConvertFormat::Jsonl);
+        assert!("xlsx".parse::<ConvertFormat>().is_err());
+    }
+}
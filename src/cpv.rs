@@ -0,0 +1,108 @@
+//! Descriptions for CPV (Common Procurement Vocabulary) codes.
+//!
+//! The table is embedded from `cpv_2008.csv`, which holds `code,description` rows of the EU
+//! CPV 2008 code list in English: the 45 divisions and a selection of common groups and
+//! classes. Only codes with their own row are described (see [`cpv_description`]).
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Embedded CPV 2008 code list (`code,description`, descriptions quoted when they contain commas).
+const CPV_CSV: &str = include_str!("cpv_2008.csv");
+
+/// Number of digits in a CPV code, without the check digit.
+const CPV_DIGITS: usize = 8;
+
+/// Returns the lookup table from the 8-digit CPV code (check digit removed) to its description.
+fn cpv_table() -> &'static HashMap<&'static str, &'static str> {
+    static TABLE: OnceLock<HashMap<&'static str, &'static str>> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        CPV_CSV
+            .lines()
+            .skip(1)
+            .filter_map(|line| {
+                let (code, description) = line.split_once(',')?;
+                let description = description
+                    .strip_prefix('"')
+                    .and_then(|d| d.strip_suffix('"'))
+                    .unwrap_or(description);
+                Some((strip_check_digit(code), description))
+            })
+            .collect()
+    })
+}
+
+/// Returns the code without its check digit suffix (everything from the `-`).
+fn strip_check_digit(code: &str) -> &str {
+    code.split_once('-')
+        .map_or(code, |(digits, _)| digits)
+        .trim()
+}
+
+/// Returns the English description of a CPV code such as `"45000000-7"`.
+///
+/// The check digit suffix is ignored, so `"45000000-7"`, `"45000000-0"`, and `"45000000"`
+/// all resolve to the same entry. Returns `None` for malformed codes and for codes that are
+/// not in the embedded list; an unlisted code is never described by one of its ancestors.
+pub fn cpv_description(code: &str) -> Option<&'static str> {
+    let digits = strip_check_digit(code);
+    if digits.len() != CPV_DIGITS || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    cpv_table().get(digits).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes_known_codes() {
+        assert_eq!(cpv_description("45000000-7"), Some("Construction work"));
+        assert_eq!(
+            cpv_description("72000000-5"),
+            Some("IT services: consulting, software development, Internet and support")
+        );
+        assert_eq!(
+            cpv_description("45210000-2"),
+            Some("Building construction work")
+        );
+    }
+
+    #[test]
+    fn ignores_the_check_digit_suffix() {
+        assert_eq!(cpv_description("45000000"), Some("Construction work"));
+        assert_eq!(cpv_description("45000000-0"), Some("Construction work"));
+        assert_eq!(cpv_description(" 45000000-7 "), Some("Construction work"));
+    }
+
+    #[test]
+    fn describes_listed_classes_exactly() {
+        assert_eq!(cpv_description("45233140-2"), Some("Roadworks"));
+        assert_eq!(
+            cpv_description("45233000-9"),
+            Some("Construction, foundation and surface works for highways, roads")
+        );
+    }
+
+    #[test]
+    fn unknown_and_malformed_codes_have_no_description() {
+        assert_eq!(cpv_description("99000000-0"), None);
+        // Unlisted codes are not described by their listed ancestors (79000000, 45200000).
+        assert_eq!(cpv_description("79713000-5"), None);
+        assert_eq!(cpv_description("45262000-1"), None);
+        assert_eq!(cpv_description("01000000-0"), None);
+        assert_eq!(cpv_description("4500000-7"), None);
+        assert_eq!(cpv_description("4500000A-7"), None);
+        assert_eq!(cpv_description(""), None);
+    }
+
+    #[test]
+    fn every_embedded_row_parses() {
+        let rows = CPV_CSV.lines().skip(1).count();
+        assert_eq!(cpv_table().len(), rows);
+        assert!(cpv_table()
+            .iter()
+            .all(|(code, description)| code.len() == CPV_DIGITS && !description.contains('"')));
+    }
+}
@@ -1,145 +1,161 @@
+use super::rate_limit::{BandwidthLimiter, RateLimiter};
+use super::retry::{parse_retry_after, retry_with_backoff, RetryConfig};
 use crate::errors::{AppError, AppResult};
-use crate::models::ProcurementType;
-use crate::utils::{format_duration, mb_from_bytes, round_two_decimals};
+use crate::models::{DownloadStats, ProcurementType};
+use crate::ui::{create_byte_progress_bar, create_progress_bar, progress_bar_visible, ProgressBar};
+use crate::utils::{file_digest_hex, format_duration, mb_from_bytes, round_two_decimals};
+use futures::stream::{FuturesUnordered, StreamExt};
+use md5::Md5;
+use sha2::Sha256;
 use std::path::Path;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tokio::fs;
 use tokio::fs::File;
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::sync::Semaphore;
 use tokio::task::JoinHandle;
 use tracing::{debug, info, warn};
 
 /// Result type for parallel download tasks.
-/// Returns (filename, success, optional_error_message)
-type DownloadTaskResult = Result<(String, bool, Option<String>), AppError>;
+/// Returns (period, bytes downloaded on success, optional_error_message)
+type DownloadTaskResult = Result<(String, Option<u64>, Option<String>), AppError>;
 
-/// Extracts HTTP status code from error message if present.
-///
-/// Looks for the pattern "HTTP {status_code}:" in the error message.
-/// Returns `Some(status_code)` if found, `None` otherwise.
-fn extract_status_code(msg: &str) -> Option<u16> {
-    let prefix = "HTTP ";
-    if let Some(start) = msg.find(prefix) {
-        let start = start + prefix.len();
-        let end = msg[start..].find(':').unwrap_or(msg[start..].len());
-        msg[start..start + end].trim().parse().ok()
+/// Throughput in kilobits per second, or 0 when no time was measured.
+fn throughput_kbps(bytes: u64, elapsed: Duration) -> f64 {
+    let secs = elapsed.as_secs_f64();
+    if secs > 0.0 {
+        bytes as f64 * 8.0 / 1000.0 / secs
     } else {
-        None
+        0.0
     }
 }
 
-/// Determines if an error should trigger a retry attempt.
+/// Counts the bytes of one response body as they are written and reports progress.
 ///
-/// Returns `true` for retryable errors (network errors, timeouts, 5xx HTTP status codes).
-/// Returns `false` for non-retryable errors (4xx client errors, I/O errors, validation errors).
-fn should_retry(error: &AppError) -> bool {
-    match error {
-        AppError::NetworkError(msg) => {
-            // Extract status code from message if present
-            if let Some(status_code) = extract_status_code(msg) {
-                // 4xx = client error, don't retry
-                // 5xx = server error, retry
-                status_code >= 500
-            } else {
-                // No status code means network/timeout error - retry by default
-                // Legacy string matching fallback for older error formats
-                !msg.contains("400")
-                    && !msg.contains("401")
-                    && !msg.contains("403")
-                    && !msg.contains("404")
-                    && !msg.contains("client error")
-            }
+/// When the response has a `Content-Length`, every chunk updates the progress bar message
+/// with `{filename} {N} MB / {total} MB`. A byte bar is also advanced by every chunk; unless
+/// the transfer is marked complete, those bytes are taken back when the meter is dropped, so
+/// a failed attempt that is retried is not counted twice.
+struct TransferMeter<'a> {
+    filename: &'a str,
+    bytes: u64,
+    content_length: Option<u64>,
+    started: Instant,
+    bar: &'a ProgressBar,
+    completed: bool,
+}
+
+impl<'a> TransferMeter<'a> {
+    fn new(filename: &'a str, content_length: Option<u64>, bar: &'a ProgressBar) -> Self {
+        Self {
+            filename,
+            bytes: 0,
+            content_length,
+            started: Instant::now(),
+            bar,
+            completed: false,
         }
-        AppError::IoError(_) => false,       // Don't retry I/O errors
-        AppError::ParseError(_) => false,    // Don't retry parse errors
-        AppError::UrlError(_) => false,      // Don't retry URL errors
-        AppError::RegexError(_) => false,    // Don't retry regex errors
-        AppError::SelectorError(_) => false, // Don't retry selector errors
-        AppError::PeriodValidationError { .. } => false, // Don't retry validation errors
-        AppError::InvalidInput(_) => false,  // Don't retry invalid input errors
     }
-}
 
-/// Configuration for retry behavior.
-pub(crate) struct RetryConfig {
-    max_retries: u32,
-    initial_delay_ms: u64,
-    max_delay_ms: u64,
+    fn record(&mut self, chunk_len: usize) {
+        self.bytes += chunk_len as u64;
+        if self.bar.counts_bytes() {
+            self.bar.inc(chunk_len as u64);
+        }
+        if let Some(total) = self.content_length {
+            self.bar.set_message(format!(
+                "{} {:.1} MB / {:.1} MB",
+                self.filename,
+                mb_from_bytes(self.bytes),
+                mb_from_bytes(total)
+            ));
+        }
+    }
+
+    /// Keeps the recorded bytes on the bar once the file is in place.
+    fn complete(&mut self) {
+        self.completed = true;
+    }
 }
 
-impl Default for RetryConfig {
-    fn default() -> Self {
-        Self {
-            max_retries: 3,
-            initial_delay_ms: 1000,
-            max_delay_ms: 10000,
+impl Drop for TransferMeter<'_> {
+    fn drop(&mut self) {
+        if !self.completed && self.bar.counts_bytes() {
+            self.bar.dec(self.bytes);
         }
     }
 }
 
-/// Calculates exponential backoff delay in milliseconds.
+/// Whether the ZIP of `period` still has to be put in `download_dir`; archives already
+/// there are never fetched again.
+pub(crate) fn needs_download(download_dir: &Path, period: &str) -> bool {
+    !download_dir.join(format!("{period}.zip")).exists()
+}
+
+/// Size of the file at `url` as reported by a HEAD request, or `None` when the server sends
+/// no `Content-Length`.
 ///
-/// Formula: `min(initial_delay * 2^attempt, max_delay)`
-fn calculate_backoff(attempt: u32, config: &RetryConfig) -> u64 {
-    let delay = config.initial_delay_ms * 2_u64.pow(attempt);
-    delay.min(config.max_delay_ms)
+/// # Errors
+///
+/// Returns `NetworkError` if the request fails or the status is not a success.
+pub(crate) async fn remote_size(client: &reqwest::Client, url: &str) -> AppResult<Option<u64>> {
+    let response = client
+        .head(url)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|e| AppError::NetworkError(format!("HEAD request for {url} failed: {e}")))?;
+    Ok(response
+        .headers()
+        .get(reqwest::header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok()))
 }
 
-/// Internal retry function that takes RetryConfig directly.
-pub(crate) async fn download_with_retry_internal(
+/// Combined size of the files at `urls` from HEAD requests sent `concurrency` at a time (each
+/// waiting for `rate_limiter` when there is one), or `None` as soon as one request fails or
+/// a server sends no `Content-Length`.
+async fn total_remote_size(
     client: &reqwest::Client,
-    url: &str,
-    tmp_path: &Path,
-    file_path: &Path,
-    filename: &str,
-    retry_config: &RetryConfig,
-) -> AppResult<()> {
-    let mut last_error: Option<AppError> = None;
-
-    for attempt in 0..=retry_config.max_retries {
-        match download_single_file(client, url, tmp_path, file_path, filename).await {
-            Ok(()) => return Ok(()),
-            Err(e) => {
-                if attempt < retry_config.max_retries && should_retry(&e) {
-                    let delay_ms = calculate_backoff(attempt, retry_config);
-                    warn!(
-                        filename = filename,
-                        attempt = attempt + 1,
-                        max_retries = retry_config.max_retries + 1,
-                        delay_ms = delay_ms,
-                        error = %e,
-                        "Retrying download after error"
-                    );
-                    tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms)).await;
-                    last_error = Some(e);
-                    continue;
-                }
-                return Err(e);
+    urls: impl Iterator<Item = &str>,
+    concurrency: usize,
+    rate_limiter: Option<&RateLimiter>,
+) -> Option<u64> {
+    futures::stream::iter(urls)
+        .map(|url| async move {
+            if let Some(rate_limiter) = rate_limiter {
+                rate_limiter.acquire().await;
             }
-        }
-    }
-
-    Err(last_error.unwrap_or_else(|| {
-        AppError::NetworkError(format!(
-            "Download failed after {} retries (no error recorded)",
-            retry_config.max_retries + 1
-        ))
-    }))
+            remote_size(client, url).await.ok().flatten()
+        })
+        .buffer_unordered(concurrency.max(1))
+        .collect::<Vec<_>>()
+        .await
+        .into_iter()
+        .sum()
 }
 
-/// Downloads a single ZIP file.
+/// Downloads a single ZIP file and returns its size in bytes.
 ///
 /// This is a helper function that performs the download of a single file,
 /// used by `download_files` to enable error collection and continuation.
+/// Once the file is in place, its size, duration, and throughput are logged. With a
+/// `bandwidth_limiter`, each chunk waits for its share of the byte budget before the next
+/// one is read. The file is written to `tmp_path` and only renamed to `file_path` once it
+/// starts with the ZIP magic bytes and, with `verify_checksum`, matches the checksum sidecar
+/// published next to it (see [`verify_remote_checksum`]); otherwise `tmp_path` is deleted.
+#[allow(clippy::too_many_arguments)]
 async fn download_single_file(
     client: &reqwest::Client,
     url: &str,
     tmp_path: &Path,
     file_path: &Path,
     filename: &str,
-) -> AppResult<()> {
+    bar: &ProgressBar,
+    bandwidth_limiter: Option<&BandwidthLimiter>,
+    verify_checksum: bool,
+) -> AppResult<u64> {
     // Send request and handle send errors (network/timeout errors)
     let response = client.get(url).send().await.map_err(|e| {
         // For send errors, these are typically network/timeout errors (retryable)
@@ -148,12 +164,23 @@ async fn download_single_file(
 
     // Check status before error_for_status (which converts 4xx/5xx to errors)
     let status = response.status();
+    // A throttled response may say when to come back; the retry loop waits that long.
+    let retry_after = match status.as_u16() {
+        429 | 503 => response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| parse_retry_after(value, std::time::SystemTime::now())),
+        _ => None,
+    };
     let mut response = response.error_for_status().map_err(|e| {
         // Include status code in error message for retry logic
         let status_code = status.as_u16();
-        AppError::NetworkError(format!(
-            "HTTP {status_code}: Failed to download {filename}: {e}"
-        ))
+        let msg = format!("HTTP {status_code}: Failed to download {filename}: {e}");
+        match retry_after {
+            Some(retry_after) => AppError::Throttled { msg, retry_after },
+            None => AppError::NetworkError(msg),
+        }
     })?;
 
     let mut file = File::create(tmp_path).await.map_err(|e| {
@@ -164,7 +191,9 @@ async fn download_single_file(
         ))
     })?;
 
+    let mut meter = TransferMeter::new(filename, response.content_length(), bar);
     while let Some(chunk) = response.chunk().await? {
+        meter.record(chunk.len());
         file.write_all(&chunk).await.map_err(|e| {
             AppError::IoError(format!(
                 "Failed to write to temp file {}: {}",
@@ -172,11 +201,29 @@ async fn download_single_file(
                 e
             ))
         })?;
+        if let Some(bandwidth_limiter) = bandwidth_limiter {
+            bandwidth_limiter.consume(chunk.len()).await;
+        }
     }
 
-    // Ensure the file is closed before renaming
+    // Ensure the file is closed before checking and renaming it
     drop(file);
 
+    // Check the temp file first, so an invalid download never appears under its final name
+    if let Err(e) = verify_zip_magic(tmp_path).await {
+        if let Err(remove_err) = fs::remove_file(tmp_path).await {
+            warn!(
+                file_path = %tmp_path.display(),
+                error = %remove_err,
+                "Failed to remove invalid ZIP file"
+            );
+        }
+        return Err(e);
+    }
+    if verify_checksum {
+        verify_remote_checksum(client, url, tmp_path, filename).await?;
+    }
+
     // Atomically move the temp file to the final destination
     fs::rename(tmp_path, file_path).await.map_err(|e| {
         AppError::IoError(format!(
@@ -187,9 +234,153 @@ async fn download_single_file(
         ))
     })?;
 
+    let elapsed = meter.started.elapsed();
+    info!(
+        filename,
+        bytes_downloaded = meter.bytes,
+        duration_ms = elapsed.as_millis() as u64,
+        throughput_kbps = round_two_decimals(throughput_kbps(meter.bytes, elapsed)),
+        "Downloaded file"
+    );
+
+    meter.complete();
+    Ok(meter.bytes)
+}
+
+/// Checksum sidecar extensions tried after a download, in order, with the hash function
+/// each one names and its digest length in hex characters.
+type SidecarHasher = fn(&Path) -> AppResult<String>;
+const CHECKSUM_SIDECARS: [(&str, usize, SidecarHasher); 2] = [
+    ("sha256", 64, file_digest_hex::<Sha256>),
+    ("md5", 32, file_digest_hex::<Md5>),
+];
+
+/// Verifies the downloaded `tmp_path` against the first usable checksum sidecar published
+/// next to `url`: `{url}.sha256`, then `{url}.md5`.
+///
+/// Fetching a sidecar is best effort. A 404, any other error status, a failed request, or
+/// a body that does not start with a hex digest of the right length moves on to the next
+/// extension, and the file is accepted unverified when no sidecar is usable. On a mismatch
+/// the temp file is deleted, so no ZIP is left behind and the next run downloads it again.
+///
+/// # Errors
+///
+/// Returns `ParseError` if the digests differ, and `IoError` if the file cannot be hashed.
+async fn verify_remote_checksum(
+    client: &reqwest::Client,
+    url: &str,
+    tmp_path: &Path,
+    filename: &str,
+) -> AppResult<()> {
+    for (extension, hex_len, hash) in CHECKSUM_SIDECARS {
+        let sidecar_url = format!("{url}.{extension}");
+        let Some(expected) = fetch_sidecar_digest(client, &sidecar_url, hex_len).await else {
+            continue;
+        };
+
+        let path = tmp_path.to_path_buf();
+        let actual = tokio::task::spawn_blocking(move || hash(&path))
+            .await
+            .map_err(|e| AppError::IoError(format!("Checksum task failed: {e}")))??;
+        if actual != expected {
+            if let Err(e) = fs::remove_file(tmp_path).await {
+                warn!(
+                    file_path = %tmp_path.display(),
+                    error = %e,
+                    "Failed to remove ZIP file with mismatched checksum"
+                );
+            }
+            return Err(AppError::ParseError(format!(
+                "checksum mismatch for {filename}"
+            )));
+        }
+        debug!(filename, algorithm = extension, "Checksum verified");
+        return Ok(());
+    }
+
+    debug!(filename, "No checksum sidecar found, skipping verification");
     Ok(())
 }
 
+/// Fetches the checksum sidecar at `url` and returns its lowercase hex digest, or `None`
+/// when it is missing or unusable.
+async fn fetch_sidecar_digest(
+    client: &reqwest::Client,
+    url: &str,
+    hex_len: usize,
+) -> Option<String> {
+    let response = match client.get(url).send().await {
+        Ok(response) => response,
+        Err(e) => {
+            warn!(url, error = %e, "Failed to fetch checksum sidecar");
+            return None;
+        }
+    };
+    let status = response.status();
+    if !status.is_success() {
+        if status != reqwest::StatusCode::NOT_FOUND {
+            warn!(
+                url,
+                status = status.as_u16(),
+                "Checksum sidecar unavailable"
+            );
+        }
+        return None;
+    }
+
+    let body = match response.text().await {
+        Ok(body) => body,
+        Err(e) => {
+            warn!(url, error = %e, "Failed to read checksum sidecar");
+            return None;
+        }
+    };
+    let digest = parse_sidecar_digest(&body, hex_len);
+    if digest.is_none() {
+        warn!(url, "Ignoring malformed checksum sidecar");
+    }
+    digest
+}
+
+/// Reads the digest at the start of a sidecar body, which is either a bare hex digest or
+/// `sha256sum`/`md5sum` output (`<hex>  <filename>`).
+fn parse_sidecar_digest(body: &str, hex_len: usize) -> Option<String> {
+    let digest = body.split_whitespace().next()?;
+    (digest.len() == hex_len && digest.bytes().all(|b| b.is_ascii_hexdigit()))
+        .then(|| digest.to_ascii_lowercase())
+}
+
+/// Local file header signature that starts every non-empty ZIP archive.
+const ZIP_MAGIC: [u8; 4] = *b"PK\x03\x04";
+
+/// Checks that the downloaded file starts with the ZIP local file header signature.
+///
+/// Servers sometimes answer with an HTML error page and a 200 status; catching it here
+/// gives a clear error instead of a confusing failure during extraction.
+///
+/// # Errors
+///
+/// Returns `ParseError` if the file is shorter than 4 bytes or the magic bytes differ,
+/// and `IoError` if the file cannot be opened.
+pub(super) async fn verify_zip_magic(path: &Path) -> AppResult<()> {
+    let mut file = File::open(path).await.map_err(|e| {
+        AppError::IoError(format!(
+            "Failed to open downloaded file {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+
+    let mut magic = [0u8; 4];
+    match file.read_exact(&mut magic).await {
+        Ok(_) if magic == ZIP_MAGIC => Ok(()),
+        _ => Err(AppError::ParseError(format!(
+            "Downloaded file {} is not a ZIP archive (the server may have returned an HTML error page)",
+            path.display()
+        ))),
+    }
+}
+
 /// Downloads ZIP files to the appropriate directory based on procurement type.
 ///
 /// This function downloads ZIP files from the provided URLs to the directory
@@ -198,9 +389,23 @@ async fn download_single_file(
 /// # Behavior
 ///
 /// - **Atomic downloads**: Files are downloaded to temporary `.part` files and
-///   atomically renamed when complete, preventing partial downloads.
+///   atomically renamed once complete and verified, preventing partial downloads.
 /// - **Skip existing**: Files that already exist are automatically skipped.
-/// - **Progress tracking**: Elapsed time and throughput are logged after downloads complete.
+/// - **Rate limiting**: With `max_rps`, request starts (retries included) are spaced at
+///   least `1 / max_rps` seconds apart across all tasks. A 429 or 503 response with a
+///   `Retry-After` header is retried after that delay instead of the exponential backoff.
+/// - **Checksum sidecars**: With `verify_remote_checksum`, each ZIP is checked against the
+///   `{url}.sha256` or `{url}.md5` file next to it when the server publishes one, before
+///   the `.part` file is renamed; a mismatch deletes it and fails the period.
+/// - **Error policy**: By default every download runs to completion and the stage fails
+///   afterwards if any of them did. With `fail_fast`, the first failed file (after its
+///   retries) cancels the downloads still running or queued.
+/// - **Progress tracking**: When the bar is drawn, the sizes of the pending files are looked
+///   up with HEAD requests first. If every server sends a `Content-Length`, the bar counts
+///   bytes as chunks arrive; otherwise it ticks per file and shows the bytes received for
+///   responses with a `Content-Length`. The bar is hidden when `progress` is disabled or
+///   output is not a terminal. Each file's size and throughput are logged, and the totals after downloads
+///   complete.
 ///
 /// # Arguments
 ///
@@ -216,12 +421,66 @@ async fn download_single_file(
 /// - Network requests fail
 /// - File I/O operations fail
 ///
+#[tracing::instrument(
+    name = "download_files",
+    skip_all,
+    fields(
+        procurement_type = proc_type.display_name(),
+        period_count = filtered_links.len(),
+        concurrent_downloads = config.concurrent_downloads
+    )
+)]
 pub async fn download_files(
     client: &reqwest::Client,
     filtered_links: &std::collections::BTreeMap<String, String>,
     proc_type: &ProcurementType,
     config: &crate::config::ResolvedConfig,
-) -> AppResult<()> {
+) -> AppResult<DownloadStats> {
+    download_files_with_limits(
+        client,
+        filtered_links,
+        proc_type,
+        config,
+        &DownloadLimits::new(config),
+    )
+    .await
+}
+
+/// The limits every download of a run shares: the `concurrent_downloads` semaphore and the
+/// `max_rps` and `max_bytes_per_sec` limiters.
+///
+/// [`download_files`] builds its own; runs that call [`download_files_with_limits`] several
+/// times pass the same one so the limits hold across calls.
+#[derive(Clone)]
+pub(crate) struct DownloadLimits {
+    semaphore: Arc<Semaphore>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    // One byte budget shared by every download task, so the cap holds whatever the concurrency
+    bandwidth_limiter: Option<Arc<BandwidthLimiter>>,
+}
+
+impl DownloadLimits {
+    pub(crate) fn new(config: &crate::config::ResolvedConfig) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(config.concurrent_downloads)),
+            rate_limiter: config
+                .max_rps
+                .map(|max_rps| Arc::new(RateLimiter::new(max_rps))),
+            bandwidth_limiter: config
+                .max_bytes_per_sec
+                .map(|max_bytes_per_sec| Arc::new(BandwidthLimiter::new(max_bytes_per_sec))),
+        }
+    }
+}
+
+/// [`download_files`] with limits shared with other calls.
+pub(crate) async fn download_files_with_limits(
+    client: &reqwest::Client,
+    filtered_links: &std::collections::BTreeMap<String, String>,
+    proc_type: &ProcurementType,
+    config: &crate::config::ResolvedConfig,
+    limits: &DownloadLimits,
+) -> AppResult<DownloadStats> {
     let download_dir = proc_type.download_dir(config);
     // Create directory if it doesn't exist
     if !download_dir.exists() {
@@ -234,41 +493,55 @@ pub async fn download_files(
     // Collect as owned values to avoid lifetime issues with spawned tasks
     let files_to_download: Vec<(String, String)> = filtered_links
         .iter()
-        .filter(|(period, _)| {
-            let file_path = download_dir.join(format!("{period}.zip"));
-            !file_path.exists()
-        })
+        .filter(|(period, _)| needs_download(&download_dir, period))
         .map(|(period, url)| (period.clone(), url.clone()))
         .collect();
 
     let total_files = files_to_download.len();
     let skipped_count = filtered_links.len() - total_files;
+    let start = Instant::now();
 
     if total_files == 0 {
         info!(
             count = filtered_links.len(),
             "All files already exist, skipping downloads"
         );
-        return Ok(());
+        return Ok(DownloadStats {
+            skipped: skipped_count,
+            elapsed_ms: start.elapsed().as_millis() as u64,
+            ..DownloadStats::default()
+        });
     }
-
-    let start = Instant::now();
     let mut total_bytes = 0u64;
 
-    // Create semaphore to limit concurrent downloads
-    let concurrent_downloads = config.concurrent_downloads;
-    let semaphore = Arc::new(Semaphore::new(concurrent_downloads));
     let client = Arc::new(client.clone());
     let download_dir_path = download_dir.clone();
     let download_dir_arc = Arc::new(download_dir_path);
 
-    // Extract retry config values before moving into async blocks
-    let retry_max_retries = config.max_retries;
-    let retry_initial_delay_ms = config.retry_initial_delay_ms;
-    let retry_max_delay_ms = config.retry_max_delay_ms;
+    // Copy retry config before moving into async blocks
+    let retry_config = RetryConfig::from(config);
+    // Count progress in bytes when every size is known up front, in files otherwise. The
+    // sizes are only looked up when the bar is drawn.
+    let total_size = if progress_bar_visible(!config.progress) {
+        total_remote_size(
+            &client,
+            files_to_download.iter().map(|(_, url)| url.as_str()),
+            config.concurrent_downloads,
+            limits.rate_limiter.as_deref(),
+        )
+        .await
+    } else {
+        None
+    };
+    let bar = match total_size {
+        Some(total_size) => create_byte_progress_bar(total_size, !config.progress),
+        None => create_progress_bar(total_files as u64, !config.progress),
+    };
 
     // Pre-allocate errors Vec (usually small, but could accumulate)
     let mut errors = Vec::with_capacity(10);
+    // Failed downloads by period; task errors have no period and always abort the stage
+    let mut failed_periods = std::collections::BTreeMap::new();
     let mut success_count = 0;
 
     // Spawn download tasks with bounded concurrency
@@ -278,17 +551,18 @@ pub async fn download_files(
         let filename = format!("{period}.zip");
 
         // Clone Arc references and owned values for the task
-        let semaphore = semaphore.clone();
+        let DownloadLimits {
+            semaphore,
+            rate_limiter,
+            bandwidth_limiter,
+        } = limits.clone();
         let client = client.clone();
         let download_dir = download_dir_arc.clone();
         let period = period.clone();
         let url = url.clone();
         let filename_for_task = filename.clone();
-
-        // Clone retry config values for this task
-        let max_retries = retry_max_retries;
-        let initial_delay_ms = retry_initial_delay_ms;
-        let max_delay_ms = retry_max_delay_ms;
+        let bar = bar.clone();
+        let verify_checksum = config.verify_remote_checksum;
 
         // Spawn task that will acquire semaphore permit before downloading
         let handle = tokio::spawn(async move {
@@ -313,26 +587,30 @@ pub async fn download_files(
             }
 
             // Attempt download with retry logic
-            // Create RetryConfig from cloned values
-            let retry_config = RetryConfig {
-                max_retries,
-                initial_delay_ms,
-                max_delay_ms,
-            };
-
-            let result = download_with_retry_internal(
-                &client,
-                &url,
-                &tmp_path,
-                &file_path,
-                &filename_for_task,
-                &retry_config,
-            )
+            let result = retry_with_backoff(&filename_for_task, &retry_config, || async {
+                if let Some(rate_limiter) = &rate_limiter {
+                    rate_limiter.acquire().await;
+                }
+                download_single_file(
+                    &client,
+                    &url,
+                    &tmp_path,
+                    &file_path,
+                    &filename_for_task,
+                    &bar,
+                    bandwidth_limiter.as_deref(),
+                    verify_checksum,
+                )
+                .await
+            })
             .await;
+            if !bar.counts_bytes() {
+                bar.inc(1);
+            }
 
             // Handle download result and collect errors
             match &result {
-                Ok(_) => Ok((filename_for_task, true, None)),
+                Ok(bytes) => Ok((period, Some(*bytes), None)),
                 Err(e) => {
                     let error_msg = format!("Failed to download {filename_for_task}: {e}");
                     warn!(
@@ -340,7 +618,7 @@ pub async fn download_files(
                         error = %e,
                         "Failed to download file"
                     );
-                    Ok((filename_for_task, false, Some(error_msg)))
+                    Ok((period, None, Some(error_msg)))
                 }
             }
         });
@@ -348,23 +626,19 @@ pub async fn download_files(
         handles.push(handle);
     }
 
-    // Await all tasks and collect results
-    for handle in handles {
-        match handle.await {
-            Ok(Ok((filename, success, error_msg))) => {
-                if success {
+    // Await tasks as they finish; with `fail_fast` the first failure cancels the rest
+    let mut pending: FuturesUnordered<JoinHandle<DownloadTaskResult>> =
+        handles.into_iter().collect();
+    let mut cancelled = 0;
+    while let Some(joined) = pending.next().await {
+        match joined {
+            Ok(Ok((period, bytes, error_msg))) => {
+                if let Some(bytes) = bytes {
                     success_count += 1;
-                    let file_path = download_dir.join(&filename);
-                    match fs::metadata(&file_path).await {
-                        Ok(metadata) => total_bytes += metadata.len(),
-                        Err(e) => warn!(
-                            file = %file_path.display(),
-                            error = %e,
-                            "Failed to read downloaded file metadata"
-                        ),
-                    }
+                    total_bytes += bytes;
                 } else if let Some(msg) = error_msg {
-                    errors.push(msg);
+                    errors.push(msg.clone());
+                    failed_periods.insert(period, msg);
                 }
             }
             Ok(Err(e)) => {
@@ -374,10 +648,25 @@ pub async fn download_files(
                 errors.push(format!("Task join error: {e}"));
             }
         }
+        if config.fail_fast && !errors.is_empty() && !pending.is_empty() {
+            cancelled = pending.len();
+            for handle in pending.iter() {
+                handle.abort();
+            }
+            warn!(
+                cancelled,
+                "Cancelling remaining downloads after a failure (fail-fast)"
+            );
+            break;
+        }
     }
 
+    bar.finish();
+
     let elapsed = start.elapsed();
     let elapsed_str = format_duration(elapsed);
+    let elapsed_ms = elapsed.as_millis() as u64;
+    let average_kbps = round_two_decimals(throughput_kbps(total_bytes, elapsed));
     let total_mb = mb_from_bytes(total_bytes);
     let throughput = if elapsed.as_secs_f64() > 0.0 {
         total_mb / elapsed.as_secs_f64()
@@ -393,8 +682,11 @@ pub async fn download_files(
             skipped = skipped_count,
             failed = 0,
             elapsed = elapsed_str,
+            elapsed_ms,
+            bytes = total_bytes,
             size_mb = size_mb,
             throughput_mb_s = throughput_mb_s,
+            throughput_kbps = average_kbps,
             "Download completed"
         );
     } else {
@@ -403,8 +695,11 @@ pub async fn download_files(
             failed = errors.len(),
             skipped = skipped_count,
             elapsed = elapsed_str,
+            elapsed_ms,
+            bytes = total_bytes,
             size_mb = size_mb,
             throughput_mb_s = throughput_mb_s,
+            throughput_kbps = average_kbps,
             "Download completed with errors"
         );
     }
@@ -413,56 +708,590 @@ pub async fn download_files(
         debug!(skipped = skipped_count, "Skipped existing files");
     }
 
-    // Return error if any downloads failed
-    if !errors.is_empty() {
+    // Return error if any downloads failed, unless `keep_going` lets the other periods go on
+    let keep_going = config.keep_going && errors.len() == failed_periods.len();
+    if !errors.is_empty() && !keep_going {
+        let cancelled_note = if cancelled > 0 {
+            format!(" ({cancelled} download(s) cancelled by fail-fast)")
+        } else {
+            String::new()
+        };
         return Err(AppError::NetworkError(format!(
-            "Failed to download {} file(s): {}",
+            "Failed to download {} file(s){cancelled_note}: {}",
             errors.len(),
             errors.join("; ")
         )));
     }
 
-    Ok(())
+    Ok(DownloadStats {
+        attempted: total_files,
+        downloaded: success_count,
+        skipped: skipped_count,
+        bytes: total_bytes,
+        failed_periods,
+        elapsed_ms,
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn extract_status_code_no_prefix() {
-        assert!(extract_status_code("network error").is_none());
+    #[tokio::test]
+    async fn download_files_reports_existing_files_as_skipped() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = crate::config::ResolvedConfig {
+            download_dir_pt: tmp.path().to_path_buf(),
+            ..Default::default()
+        };
+        std::fs::write(tmp.path().join("202401.zip"), b"zip").unwrap();
+
+        let mut links = std::collections::BTreeMap::new();
+        links.insert("202401".to_string(), "http://unused".to_string());
+
+        let stats = download_files(
+            &crate::downloader::http::build_client(&config).unwrap(),
+            &links,
+            &ProcurementType::PublicTenders,
+            &config,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(stats.attempted, 0);
+        assert_eq!(stats.downloaded, 0);
+        assert_eq!(stats.skipped, 1);
+        assert_eq!(stats.bytes, 0);
     }
 
-    #[test]
-    fn extract_status_code_with_http() {
-        assert_eq!(extract_status_code("HTTP 404: not found"), Some(404));
-        assert_eq!(extract_status_code("HTTP 500: oh no"), Some(500));
+    /// Serves `body` once over plain HTTP with a `Content-Length` and returns its URL.
+    fn serve_once(body: Vec<u8>) -> String {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/202401.zip", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request).unwrap();
+            let header = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            stream.write_all(header.as_bytes()).unwrap();
+            stream.write_all(&body).unwrap();
+        });
+        url
+    }
+
+    /// Collects formatted log output in memory.
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn download_files_logs_per_file_and_total_throughput() {
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let mut body = b"PK\x03\x04".to_vec();
+        body.resize(1024 * 1024, 0);
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = crate::config::ResolvedConfig {
+            download_dir_pt: tmp.path().to_path_buf(),
+            progress: false,
+            ..Default::default()
+        };
+        let mut links = std::collections::BTreeMap::new();
+        links.insert("202401".to_string(), serve_once(body));
+
+        let stats = download_files(
+            &crate::downloader::http::build_client(&config).unwrap(),
+            &links,
+            &ProcurementType::PublicTenders,
+            &config,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(stats.downloaded, 1);
+        assert_eq!(stats.bytes, 1024 * 1024);
+        let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        let file_line = logs
+            .lines()
+            .find(|line| line.contains("Downloaded file"))
+            .expect("per-file log line");
+        assert!(file_line.contains("filename=\"202401.zip\""), "{file_line}");
+        assert!(
+            file_line.contains("bytes_downloaded=1048576"),
+            "{file_line}"
+        );
+        let kbps = |line: &str| -> f64 {
+            let value = line.split("throughput_kbps=").nth(1).unwrap();
+            value.split_whitespace().next().unwrap().parse().unwrap()
+        };
+        assert!(kbps(file_line) > 0.0, "{file_line}");
+        let summary = logs
+            .lines()
+            .find(|line| line.contains("Download completed"))
+            .expect("summary log line");
+        assert!(summary.contains("bytes=1048576"), "{summary}");
+        assert!(kbps(summary) > 0.0, "{summary}");
+    }
+
+    #[tokio::test]
+    async fn download_files_keep_going_records_unreachable_periods() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut config = crate::config::ResolvedConfig {
+            download_dir_pt: tmp.path().to_path_buf(),
+            progress: false,
+            max_retries: 0,
+            ..Default::default()
+        };
+        let mut links = std::collections::BTreeMap::new();
+        links.insert("202401".to_string(), serve_once(b"PK\x03\x04zip".to_vec()));
+        links.insert(
+            "202402".to_string(),
+            "http://127.0.0.1:1/202402.zip".to_string(),
+        );
+        let client = crate::downloader::http::build_client(&config).unwrap();
+
+        let err = download_files(&client, &links, &ProcurementType::PublicTenders, &config)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::NetworkError(_)));
+
+        config.keep_going = true;
+        std::fs::remove_file(tmp.path().join("202401.zip")).ok();
+        links.insert("202401".to_string(), serve_once(b"PK\x03\x04zip".to_vec()));
+        let stats = download_files(&client, &links, &ProcurementType::PublicTenders, &config)
+            .await
+            .unwrap();
+
+        assert_eq!(stats.downloaded, 1);
+        assert_eq!(stats.failed_periods.keys().collect::<Vec<_>>(), ["202402"]);
+        assert!(tmp.path().join("202401.zip").exists());
+        assert!(!tmp.path().join("202402.zip").exists());
+    }
+
+    #[tokio::test]
+    async fn download_files_max_rps_spaces_out_requests() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = crate::config::ResolvedConfig {
+            download_dir_pt: tmp.path().to_path_buf(),
+            progress: false,
+            max_rps: Some(10.0),
+            ..Default::default()
+        };
+        let mut links = std::collections::BTreeMap::new();
+        for period in ["202401", "202402", "202403"] {
+            links.insert(period.to_string(), serve_once(b"PK\x03\x04zip".to_vec()));
+        }
+
+        let started = Instant::now();
+        let stats = download_files(
+            &crate::downloader::http::build_client(&config).unwrap(),
+            &links,
+            &ProcurementType::PublicTenders,
+            &config,
+        )
+        .await
+        .unwrap();
+
+        // The first request starts at once, the next two 100 ms apart.
+        assert_eq!(stats.downloaded, 3);
+        assert!(started.elapsed() >= Duration::from_millis(200));
+    }
+
+    #[tokio::test]
+    async fn download_files_max_bytes_per_sec_throttles_transfer() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = crate::config::ResolvedConfig {
+            download_dir_pt: tmp.path().to_path_buf(),
+            progress: false,
+            max_bytes_per_sec: Some(20_000),
+            ..Default::default()
+        };
+        let mut links = std::collections::BTreeMap::new();
+        for period in ["202401", "202402"] {
+            let mut body = b"PK\x03\x04".to_vec();
+            body.resize(3_000, b'z');
+            links.insert(period.to_string(), serve_once(body));
+        }
+
+        let started = Instant::now();
+        let stats = download_files(
+            &crate::downloader::http::build_client(&config).unwrap(),
+            &links,
+            &ProcurementType::PublicTenders,
+            &config,
+        )
+        .await
+        .unwrap();
+
+        // 6000 bytes at 20000 bytes per second, shared by both concurrent downloads.
+        assert_eq!(stats.downloaded, 2);
+        assert_eq!(stats.bytes, 6_000);
+        assert!(started.elapsed() >= Duration::from_millis(300));
+    }
+
+    /// Answers consecutive requests with the raw HTTP `responses`, in order, and returns the
+    /// URL they are served at.
+    fn serve_sequence(responses: Vec<Vec<u8>>) -> String {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/202401.zip", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = [0u8; 1024];
+                let _ = stream.read(&mut request).unwrap();
+                stream.write_all(&response).unwrap();
+            }
+        });
+        url
+    }
+
+    /// Downloads period 202401 from a server answering 429 with `retry_after_header`, then
+    /// a ZIP; returns the stats and how long the download took.
+    async fn download_after_too_many_requests(
+        retry_after_header: &str,
+        config: crate::config::ResolvedConfig,
+    ) -> (DownloadStats, Duration) {
+        let throttled = format!(
+            "HTTP/1.1 429 Too Many Requests\r\n{retry_after_header}Content-Length: 0\r\nConnection: close\r\n\r\n"
+        );
+        let ok = b"HTTP/1.1 200 OK\r\nContent-Length: 7\r\nConnection: close\r\n\r\nPK\x03\x04zip";
+        let mut links = std::collections::BTreeMap::new();
+        links.insert(
+            "202401".to_string(),
+            serve_sequence(vec![throttled.into_bytes(), ok.to_vec()]),
+        );
+
+        let started = Instant::now();
+        let stats = download_files(
+            &crate::downloader::http::build_client(&config).unwrap(),
+            &links,
+            &ProcurementType::PublicTenders,
+            &config,
+        )
+        .await
+        .unwrap();
+        (stats, started.elapsed())
+    }
+
+    #[tokio::test]
+    async fn download_files_retries_too_many_requests_with_backoff() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = crate::config::ResolvedConfig {
+            download_dir_pt: tmp.path().to_path_buf(),
+            progress: false,
+            max_retries: 1,
+            retry_initial_delay_ms: 100,
+            retry_jitter: false,
+            ..Default::default()
+        };
+
+        let (stats, elapsed) = download_after_too_many_requests("", config).await;
+
+        assert_eq!(stats.downloaded, 1);
+        assert!(tmp.path().join("202401.zip").exists());
+        assert!(elapsed >= Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn download_files_waits_for_retry_after_on_too_many_requests() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = crate::config::ResolvedConfig {
+            download_dir_pt: tmp.path().to_path_buf(),
+            progress: false,
+            max_retries: 1,
+            retry_initial_delay_ms: 10,
+            retry_jitter: false,
+            ..Default::default()
+        };
+
+        let (stats, elapsed) = download_after_too_many_requests("Retry-After: 1\r\n", config).await;
+
+        // The server-requested second replaces the 10 ms backoff.
+        assert_eq!(stats.downloaded, 1);
+        assert!(tmp.path().join("202401.zip").exists());
+        assert!(elapsed >= Duration::from_secs(1));
+    }
+
+    /// Downloads period 202401 with `verify_remote_checksum` from a server answering with
+    /// the ZIP and then each of the raw `sidecar_responses`.
+    async fn download_with_sidecars(
+        download_dir: &Path,
+        sidecar_responses: Vec<Vec<u8>>,
+    ) -> AppResult<DownloadStats> {
+        let zip = b"HTTP/1.1 200 OK\r\nContent-Length: 7\r\nConnection: close\r\n\r\nPK\x03\x04zip";
+        let mut responses = vec![zip.to_vec()];
+        responses.extend(sidecar_responses);
+        let config = crate::config::ResolvedConfig {
+            download_dir_pt: download_dir.to_path_buf(),
+            progress: false,
+            max_retries: 0,
+            verify_remote_checksum: true,
+            ..Default::default()
+        };
+        let mut links = std::collections::BTreeMap::new();
+        links.insert("202401".to_string(), serve_sequence(responses));
+
+        download_files(
+            &crate::downloader::http::build_client(&config).unwrap(),
+            &links,
+            &ProcurementType::PublicTenders,
+            &config,
+        )
+        .await
+    }
+
+    fn sidecar_response(body: &str) -> Vec<u8> {
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+        .into_bytes()
+    }
+
+    #[tokio::test]
+    async fn download_files_accepts_matching_sha256_sidecar() {
+        use sha2::Digest;
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let digest = format!("{:x}", Sha256::digest(b"PK\x03\x04zip"));
+        let sidecar = sidecar_response(&format!("{digest}  202401.zip\n"));
+
+        let stats = download_with_sidecars(tmp.path(), vec![sidecar])
+            .await
+            .unwrap();
+
+        assert_eq!(stats.downloaded, 1);
+        assert!(tmp.path().join("202401.zip").exists());
+    }
+
+    #[tokio::test]
+    async fn download_files_falls_back_to_md5_sidecar_after_404() {
+        use md5::Digest;
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let not_found =
+            b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec();
+        // A wrong MD5 proves the sidecar was read after the missing SHA-256 one.
+        let wrong_md5 = format!("{:x}", Md5::digest(b"something else"));
+
+        let err = download_with_sidecars(tmp.path(), vec![not_found, sidecar_response(&wrong_md5)])
+            .await
+            .unwrap_err();
+
+        assert!(
+            err.to_string().contains("checksum mismatch for 202401.zip"),
+            "{err}"
+        );
+        assert!(!tmp.path().join("202401.zip").exists());
+        assert!(!tmp.path().join("202401.zip.part").exists());
+    }
+
+    #[tokio::test]
+    async fn download_files_rejects_mismatched_sha256_sidecar() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let sidecar = sidecar_response(&"0".repeat(64));
+
+        let err = download_with_sidecars(tmp.path(), vec![sidecar])
+            .await
+            .unwrap_err();
+
+        assert!(
+            err.to_string()
+                .contains("Parse error: checksum mismatch for 202401.zip"),
+            "{err}"
+        );
+        assert!(!tmp.path().join("202401.zip").exists());
+        assert!(!tmp.path().join("202401.zip.part").exists());
+    }
+
+    #[tokio::test]
+    async fn download_files_accepts_zip_without_sidecar() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let not_found =
+            b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec();
+
+        let stats = download_with_sidecars(tmp.path(), vec![not_found.clone(), not_found])
+            .await
+            .unwrap();
+
+        assert_eq!(stats.downloaded, 1);
+        assert!(tmp.path().join("202401.zip").exists());
     }
 
     #[test]
-    fn should_retry_network_5xx() {
-        let err = AppError::NetworkError("HTTP 500: server".to_string());
-        assert!(should_retry(&err));
+    fn parse_sidecar_digest_reads_bare_and_sum_formats() {
+        let digest = "D41D8CD98F00B204E9800998ECF8427E";
+        assert_eq!(
+            parse_sidecar_digest(digest, 32).as_deref(),
+            Some("d41d8cd98f00b204e9800998ecf8427e")
+        );
+        assert_eq!(
+            parse_sidecar_digest(&format!("{digest}  202401.zip\n"), 32).as_deref(),
+            Some("d41d8cd98f00b204e9800998ecf8427e")
+        );
+        assert_eq!(parse_sidecar_digest(digest, 64), None);
+        assert_eq!(parse_sidecar_digest("<html>Not found</html>", 32), None);
+        assert_eq!(parse_sidecar_digest("", 32), None);
+    }
+
+    #[tokio::test]
+    async fn remote_size_reads_content_length_of_head_response() {
+        let url = serve_once(vec![0; 1234]);
+        let client = crate::downloader::http::build_client(&Default::default()).unwrap();
+
+        assert_eq!(remote_size(&client, &url).await.unwrap(), Some(1234));
     }
 
     #[test]
-    fn should_not_retry_network_4xx() {
-        let err = AppError::NetworkError("HTTP 404: client".to_string());
-        assert!(!should_retry(&err));
+    fn needs_download_only_for_missing_archives() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("202401.zip"), b"PK").unwrap();
+        std::fs::write(tmp.path().join("202402.zip.part"), b"PK").unwrap();
+
+        assert!(!needs_download(tmp.path(), "202401"));
+        assert!(needs_download(tmp.path(), "202402"));
     }
 
     #[test]
-    fn should_not_retry_io_error() {
-        let err = AppError::IoError("disk full".to_string());
-        assert!(!should_retry(&err));
+    fn throughput_kbps_handles_zero_duration() {
+        assert_eq!(throughput_kbps(1000, Duration::ZERO), 0.0);
+        assert_eq!(throughput_kbps(1000, Duration::from_secs(1)), 8.0);
+    }
+
+    #[tokio::test]
+    async fn verify_zip_magic_accepts_zip_header() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("valid.zip");
+        std::fs::write(&path, b"PK\x03\x04rest of archive").unwrap();
+        assert!(verify_zip_magic(&path).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn verify_zip_magic_rejects_html_and_short_files() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let html = tmp.path().join("html.zip");
+        std::fs::write(&html, b"<!DOCTYPE html><html>error</html>").unwrap();
+        let err = verify_zip_magic(&html).await.unwrap_err();
+        assert!(matches!(err, AppError::ParseError(msg) if msg.contains("not a ZIP archive")));
+
+        let short = tmp.path().join("short.zip");
+        std::fs::write(&short, b"PK").unwrap();
+        assert!(matches!(
+            verify_zip_magic(&short).await,
+            Err(AppError::ParseError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn fail_fast_cancels_remaining_downloads_after_a_failure() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = crate::config::ResolvedConfig {
+            download_dir_pt: tmp.path().to_path_buf(),
+            progress: false,
+            max_retries: 0,
+            fail_fast: true,
+            ..Default::default()
+        };
+        let not_found = b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+        // Accepts connections through its backlog but never answers them
+        let stalled = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut links = std::collections::BTreeMap::new();
+        links.insert(
+            "202401".to_string(),
+            serve_sequence(vec![not_found.to_vec()]),
+        );
+        links.insert(
+            "202402".to_string(),
+            format!("http://{}/202402.zip", stalled.local_addr().unwrap()),
+        );
+
+        let result = tokio::time::timeout(
+            Duration::from_secs(10),
+            download_files(
+                &crate::downloader::http::build_client(&config).unwrap(),
+                &links,
+                &ProcurementType::PublicTenders,
+                &config,
+            ),
+        )
+        .await
+        .expect("fail-fast should not wait for the stalled download");
+
+        let err = result.unwrap_err().to_string();
+        assert!(err.contains("1 download(s) cancelled"), "{err}");
+        assert!(!tmp.path().join("202402.zip").exists());
+    }
+
+    fn head_response(content_length: Option<u64>) -> Vec<u8> {
+        let length = content_length
+            .map(|length| format!("Content-Length: {length}\r\n"))
+            .unwrap_or_default();
+        format!("HTTP/1.1 200 OK\r\n{length}Connection: close\r\n\r\n").into_bytes()
+    }
+
+    #[tokio::test]
+    async fn total_remote_size_sums_content_lengths() {
+        let client = reqwest::Client::new();
+        let urls = [
+            serve_sequence(vec![head_response(Some(7))]),
+            serve_sequence(vec![head_response(Some(5))]),
+        ];
+
+        let total = total_remote_size(&client, urls.iter().map(String::as_str), 2, None).await;
+
+        assert_eq!(total, Some(12));
+    }
+
+    #[tokio::test]
+    async fn total_remote_size_is_unknown_when_a_length_is_missing() {
+        let client = reqwest::Client::new();
+        let urls = [
+            serve_sequence(vec![head_response(Some(7))]),
+            serve_sequence(vec![head_response(None)]),
+        ];
+
+        let total = total_remote_size(&client, urls.iter().map(String::as_str), 2, None).await;
+
+        assert_eq!(total, None);
     }
 
     #[test]
-    fn calculate_backoff_capped() {
-        let config = RetryConfig::default();
-        assert_eq!(calculate_backoff(0, &config), 1000);
-        assert_eq!(calculate_backoff(1, &config), 2000);
-        assert_eq!(calculate_backoff(10, &config), 10000);
+    fn transfer_meter_takes_back_bytes_of_unfinished_transfers() {
+        let bar = crate::ui::create_byte_progress_bar(100, true);
+        {
+            let mut failed = TransferMeter::new("202401.zip", Some(40), &bar);
+            failed.record(30);
+            assert_eq!(bar.position(), 30);
+        }
+        assert_eq!(bar.position(), 0);
+
+        let mut done = TransferMeter::new("202401.zip", Some(40), &bar);
+        done.record(40);
+        done.complete();
+        drop(done);
+        assert_eq!(bar.position(), 40);
     }
 }
@@ -0,0 +1,205 @@
+use crate::config::ResolvedConfig;
+use crate::errors::{AppError, AppResult};
+use reqwest::{Certificate, ClientBuilder, Proxy};
+use std::fs;
+
+/// User agent sent when `user_agent` is not configured.
+pub fn default_user_agent() -> String {
+    format!("sppd-cli/{}", crate::cli::APP_VERSION)
+}
+
+/// Builds the HTTP client shared by link fetching and ZIP downloads.
+///
+/// See [`client_builder`] for the settings applied.
+///
+/// # Errors
+///
+/// Returns `InvalidInput` for a malformed `proxy` URL or an unreadable or empty `ca_cert`
+/// bundle, and `NetworkError` if the TLS backend cannot be initialized.
+pub fn build_client(config: &ResolvedConfig) -> AppResult<reqwest::Client> {
+    client_builder(config)?
+        .build()
+        .map_err(|e| AppError::NetworkError(format!("Failed to build HTTP client: {e}")))
+}
+
+/// Starts a client builder with the settings every sppd-cli client shares, so callers can
+/// add their own timeouts.
+///
+/// - transport-level gzip (`Content-Encoding: gzip`) is decoded transparently
+/// - the user agent is `user_agent`, or `sppd-cli/<version>` when unset
+/// - `proxy`, when set, is used for every request; otherwise the standard `HTTPS_PROXY`,
+///   `HTTP_PROXY`, `ALL_PROXY`, and `NO_PROXY` environment variables apply. An empty
+///   `proxy` disables proxies entirely, environment variables included
+/// - the certificates in the `ca_cert` PEM bundle are trusted in addition to the
+///   built-in roots
+pub(crate) fn client_builder(config: &ResolvedConfig) -> AppResult<ClientBuilder> {
+    let user_agent = config.user_agent.clone().unwrap_or_else(default_user_agent);
+    let mut builder = reqwest::Client::builder().gzip(true).user_agent(user_agent);
+
+    if config.proxy.as_deref() == Some("") {
+        builder = builder.no_proxy();
+    } else if let Some(proxy) = &config.proxy {
+        let proxy = Proxy::all(proxy.as_str())
+            .map_err(|e| AppError::InvalidInput(format!("Invalid proxy URL '{proxy}': {e}")))?;
+        builder = builder.proxy(proxy);
+    }
+
+    if let Some(path) = &config.ca_cert {
+        let pem = fs::read(path).map_err(|e| {
+            AppError::InvalidInput(format!(
+                "Failed to read CA certificate bundle {}: {e}",
+                path.display()
+            ))
+        })?;
+        let certificates = Certificate::from_pem_bundle(&pem).map_err(|e| {
+            AppError::InvalidInput(format!(
+                "Invalid CA certificate bundle {}: {e}",
+                path.display()
+            ))
+        })?;
+        if certificates.is_empty() {
+            return Err(AppError::InvalidInput(format!(
+                "No PEM certificates found in CA certificate bundle {}",
+                path.display()
+            )));
+        }
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    Ok(builder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    // Self-signed test certificate (CN=sppd-cli test CA), valid until 2126.
+    const TEST_CA: &str = "-----BEGIN CERTIFICATE-----
+MIIBjTCCATOgAwIBAgIUF/TjVgTXaXGQP8xz/l18EycAtYUwCgYIKoZIzj0EAwIw
+GzEZMBcGA1UEAwwQc3BwZC1jbGkgdGVzdCBDQTAgFw0yNjEwMTgwNTQzMjZaGA8y
+MTI2MDkyNDA1NDMyNlowGzEZMBcGA1UEAwwQc3BwZC1jbGkgdGVzdCBDQTBZMBMG
+ByqGSM49AgEGCCqGSM49AwEHA0IABGC5JnCBF5PW0xE3qh91+dYXxiwlKcPsMdWT
+e7iT1gnHsFvs/E/567m7bF9g8tk6lh1HmgT/3QbJ2znWY7iPJPGjUzBRMB0GA1Ud
+DgQWBBRYtsxJl5qPD7zpod/Lumb3z9kkrTAfBgNVHSMEGDAWgBRYtsxJl5qPD7zp
+od/Lumb3z9kkrTAPBgNVHRMBAf8EBTADAQH/MAoGCCqGSM49BAMCA0gAMEUCIEem
+uLrgmlRdvq25QKhta6L6v1rL1gp/qIAGpuHX+zmaAiEAhIKWQBFhr0YaEpz4gss4
+F0hdASS2tziwR+oJnF264xI=
+-----END CERTIFICATE-----
+";
+
+    fn config_with(proxy: Option<&str>, ca_cert: Option<PathBuf>) -> ResolvedConfig {
+        ResolvedConfig {
+            proxy: proxy.map(str::to_string),
+            ca_cert,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn builds_default_client() {
+        assert!(build_client(&ResolvedConfig::default()).is_ok());
+        assert!(default_user_agent().starts_with("sppd-cli/"));
+        assert!(default_user_agent().ends_with(crate::cli::APP_VERSION));
+    }
+
+    #[test]
+    fn accepts_proxy_and_ca_bundle() {
+        let tmp = TempDir::new().unwrap();
+        let ca = tmp.path().join("ca.pem");
+        fs::write(&ca, TEST_CA).unwrap();
+
+        let config = config_with(Some("http://proxy.internal:3128"), Some(ca));
+
+        assert!(build_client(&config).is_ok());
+    }
+
+    /// Answers one HTTP request with an empty 200 response; returns the server URL and a
+    /// handle yielding the request line.
+    fn serve_one_request() -> (String, std::thread::JoinHandle<String>) {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = format!("http://{}", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request_line = String::new();
+            BufReader::new(&stream)
+                .read_line(&mut request_line)
+                .unwrap();
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+                .unwrap();
+            request_line
+        });
+        (addr, handle)
+    }
+
+    #[tokio::test]
+    async fn requests_go_through_configured_proxy() {
+        let (proxy_url, proxy) = serve_one_request();
+        let client = build_client(&config_with(Some(&proxy_url), None)).unwrap();
+
+        let response = client
+            .get("http://contrataciondelestado.invalid/feed.zip")
+            .send()
+            .await
+            .unwrap();
+
+        assert!(response.status().is_success());
+        // A forward proxy receives the absolute URL of the target.
+        assert_eq!(
+            proxy.join().unwrap().trim_end(),
+            "GET http://contrataciondelestado.invalid/feed.zip HTTP/1.1"
+        );
+    }
+
+    #[tokio::test]
+    async fn empty_proxy_connects_directly() {
+        let (server_url, server) = serve_one_request();
+        let client = build_client(&config_with(Some(""), None)).unwrap();
+
+        let response = client
+            .get(format!("{server_url}/feed.zip"))
+            .send()
+            .await
+            .unwrap();
+
+        assert!(response.status().is_success());
+        assert_eq!(server.join().unwrap().trim_end(), "GET /feed.zip HTTP/1.1");
+    }
+
+    #[test]
+    fn rejects_malformed_proxy_url() {
+        let err = build_client(&config_with(Some("not a url"), None)).unwrap_err();
+
+        match err {
+            AppError::InvalidInput(msg) => assert!(msg.contains("Invalid proxy URL"), "{msg}"),
+            other => panic!("expected InvalidInput, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn rejects_missing_and_empty_ca_bundles() {
+        let tmp = TempDir::new().unwrap();
+        let empty = tmp.path().join("empty.pem");
+        fs::write(&empty, "not a certificate").unwrap();
+
+        let cases = [
+            (
+                tmp.path().join("missing.pem"),
+                "Failed to read CA certificate",
+            ),
+            (empty, "No PEM certificates found"),
+        ];
+        for (path, expected) in cases {
+            match build_client(&config_with(None, Some(path))).unwrap_err() {
+                AppError::InvalidInput(msg) => assert!(msg.contains(expected), "{msg}"),
+                other => panic!("expected InvalidInput, got {other:?}"),
+            }
+        }
+    }
+}
@@ -1,14 +1,21 @@
-use crate::errors::AppResult;
+use super::http::client_builder;
+use super::links_cache::{links_cache_path, read_cached_links, write_cached_links};
+use super::retry::{retry_with_backoff, RetryConfig};
+use crate::config::ResolvedConfig;
+use crate::errors::{AppError, AppResult};
+use crate::models::ProcurementType;
 use regex::Regex;
 use scraper::{Html, Selector};
 use std::collections::BTreeMap;
 use std::sync::OnceLock;
-use tracing::info;
+use std::time::{Duration, SystemTime};
+use tracing::{info, warn};
 use url::Url;
 
 // Data source URLs
 const MINOR_CONTRACTS_URL: &str = "https://www.hacienda.gob.es/es-es/gobiernoabierto/datos%20abiertos/paginas/contratosmenores.aspx";
 const PUBLIC_TENDERS_URL: &str = "https://www.hacienda.gob.es/es-ES/GobiernoAbierto/Datos%20Abiertos/Paginas/LicitacionesContratante.aspx";
+const AGGREGATED_PLATFORMS_URL: &str = "https://www.hacienda.gob.es/es-ES/GobiernoAbierto/Datos%20Abiertos/Paginas/PlataformasAgregadasSinMenores.aspx";
 
 // Selectors and Patterns
 const ZIP_LINK_SELECTOR: &str = r#"a[href$=".zip"]"#;
@@ -22,43 +29,215 @@ static PERIOD_REGEX: OnceLock<Regex> = OnceLock::new();
 /// Compiled once at initialization for performance.
 static ZIP_LINK_SELECTOR_CACHED: OnceLock<Selector> = OnceLock::new();
 
-/// Fetches all available ZIP file links from both procurement data sources.
+/// Source of landing-page HTML.
 ///
-/// This function sequentially fetches links from both the minor contracts and
-/// public tenders data source pages. It parses HTML to extract ZIP file links
-/// and extracts period identifiers (e.g., "202301") from filenames.
+/// Abstracted so link fetching can be exercised in tests without network access.
+pub(crate) trait PageFetcher {
+    /// Returns the body of the page at `url`.
+    async fn fetch_page(&self, url: &str) -> AppResult<String>;
+}
+
+/// [`PageFetcher`] backed by a `reqwest` client.
+struct HttpPageFetcher<'a> {
+    client: &'a reqwest::Client,
+}
+
+impl PageFetcher for HttpPageFetcher<'_> {
+    async fn fetch_page(&self, url: &str) -> AppResult<String> {
+        // The status code is kept in the message for retry decisions
+        let response = self.client.get(url).send().await?;
+        let status = response.status();
+        let body = response
+            .error_for_status()
+            .map_err(|e| AppError::NetworkError(format!("HTTP {}: {e}", status.as_u16())))?
+            .text()
+            .await?;
+        Ok(body)
+    }
+}
+
+/// Returns the landing page URL listing the ZIP archives for a procurement type.
+fn landing_page_url(proc_type: &ProcurementType) -> &'static str {
+    match proc_type {
+        ProcurementType::MinorContracts => MINOR_CONTRACTS_URL,
+        ProcurementType::PublicTenders => PUBLIC_TENDERS_URL,
+        ProcurementType::AggregatedPlatforms => AGGREGATED_PLATFORMS_URL,
+    }
+}
+
+/// Builds the HTTP client used for landing pages: the shared client settings from
+/// [`client_builder`] plus the configured timeouts.
+fn build_fetch_client(config: &ResolvedConfig) -> AppResult<reqwest::Client> {
+    client_builder(config)?
+        .connect_timeout(Duration::from_secs(config.fetch_connect_timeout_secs))
+        .timeout(Duration::from_secs(config.fetch_timeout_secs))
+        .build()
+        .map_err(|e| AppError::NetworkError(format!("Failed to build HTTP client: {e}")))
+}
+
+/// Fetches the ZIP file links for a single procurement type.
+///
+/// Only the landing page for `proc_type` is requested, so a run for one type does not
+/// depend on the other source being reachable. Timeouts, retries, and the links cache
+/// are configured as in [`fetch_all_links`].
 ///
 /// # Returns
 ///
-/// Returns a tuple containing maps of period strings to download URLs:
-/// - **First element**: Minor contracts links (period -> URL)
-/// - **Second element**: Public tenders links (period -> URL)
+/// A map of period strings to download URLs (e.g., "202301" -> URL).
 ///
 /// # Errors
 ///
 /// Returns an error if:
-/// - Network requests fail
+/// - Network requests fail after all retries (`NetworkError` naming the source page)
 /// - HTML parsing fails
 /// - URLs cannot be parsed
 ///
-pub async fn fetch_all_links() -> AppResult<(BTreeMap<String, String>, BTreeMap<String, String>)> {
-    let client = reqwest::Client::new();
-    // Sequential fetch: simple and reliable for two landing pages.
-    info!("Fetching minor contracts links");
-    let minor_links = fetch_zip(&client, MINOR_CONTRACTS_URL).await?;
+#[tracing::instrument(
+    name = "fetch_links_for",
+    skip_all,
+    fields(procurement_type = proc_type.display_name(), periods = tracing::field::Empty)
+)]
+pub async fn fetch_links_for(
+    proc_type: &ProcurementType,
+    config: &ResolvedConfig,
+) -> AppResult<BTreeMap<String, String>> {
+    let client = build_fetch_client(config)?;
+    let fetcher = HttpPageFetcher { client: &client };
+    let links = cached_links_with(&fetcher, proc_type, config, SystemTime::now()).await?;
+    tracing::Span::current().record("periods", links.len());
+    Ok(links)
+}
+
+/// Fetches all available ZIP file links from every procurement data source.
+///
+/// This function sequentially fetches links from the landing page of every
+/// [`ProcurementType`] (minor contracts, public tenders, and aggregated platforms). It
+/// parses HTML to extract ZIP file links and extracts period identifiers (e.g., "202301")
+/// from filenames. Use [`fetch_links_for`] when only one procurement type is needed.
+///
+/// Requests use the `fetch_connect_timeout_secs`/`fetch_timeout_secs` timeouts from
+/// `config` and are retried with the same backoff settings as ZIP downloads
+/// (`max_retries`, `retry_initial_delay_ms`, `retry_max_delay_ms`).
+///
+/// The links of each page are cached in `{links_cache_dir}/links_{mc,pt,agg}.json`; a cache
+/// younger than `links_cache_ttl_secs` is used instead of fetching the page, unless
+/// `refresh_links` is set.
+///
+/// # Returns
+///
+/// A map from each procurement type to its map of period strings to download URLs
+/// (e.g., "202301" -> URL).
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - Network requests fail after all retries (`NetworkError` naming the source page)
+/// - HTML parsing fails
+/// - URLs cannot be parsed
+///
+#[tracing::instrument(
+    name = "fetch_all_links",
+    skip_all,
+    fields(periods = tracing::field::Empty)
+)]
+pub async fn fetch_all_links(
+    config: &ResolvedConfig,
+) -> AppResult<BTreeMap<ProcurementType, BTreeMap<String, String>>> {
+    let client = build_fetch_client(config)?;
+    let fetcher = HttpPageFetcher { client: &client };
+    let links = fetch_all_links_with(&fetcher, config, SystemTime::now()).await?;
+    tracing::Span::current().record("periods", links.values().map(BTreeMap::len).sum::<usize>());
+    Ok(links)
+}
+
+/// Fetches the links of every procurement type through `fetcher`, one page after another.
+async fn fetch_all_links_with<F: PageFetcher>(
+    fetcher: &F,
+    config: &ResolvedConfig,
+    now: SystemTime,
+) -> AppResult<BTreeMap<ProcurementType, BTreeMap<String, String>>> {
+    // Sequential fetch: simple and reliable for a few landing pages.
+    let mut links = BTreeMap::new();
+    for proc_type in ProcurementType::ALL {
+        links.insert(
+            proc_type,
+            cached_links_with(fetcher, &proc_type, config, now).await?,
+        );
+    }
+    Ok(links)
+}
+
+/// Returns the links for `proc_type` from the links cache when it was written less than
+/// `links_cache_ttl_secs` before `now`, and otherwise fetches them with
+/// [`fetch_links_with`] and rewrites the cache.
+///
+/// `refresh_links` skips the cache lookup (the cache is still rewritten), and a TTL of 0
+/// disables the cache entirely. Failing to write the cache only logs a warning.
+async fn cached_links_with<F: PageFetcher>(
+    fetcher: &F,
+    proc_type: &ProcurementType,
+    config: &ResolvedConfig,
+    now: SystemTime,
+) -> AppResult<BTreeMap<String, String>> {
+    let ttl = Duration::from_secs(config.links_cache_ttl_secs);
+    let cache_path = links_cache_path(&config.links_cache_dir, proc_type);
+    if !ttl.is_zero() && !config.refresh_links {
+        if let Some(links) = read_cached_links(&cache_path, ttl, now) {
+            info!(
+                procurement_type = proc_type.display_name(),
+                periods_found = links.len(),
+                cache = %cache_path.display(),
+                "Using cached links"
+            );
+            return Ok(links);
+        }
+    }
+
+    let links = fetch_links_with(fetcher, proc_type, &RetryConfig::from(config)).await?;
+    if !ttl.is_zero() {
+        if let Err(e) = write_cached_links(&cache_path, &links, now) {
+            warn!(
+                cache = %cache_path.display(),
+                error = %e,
+                "Failed to write links cache"
+            );
+        }
+    }
+    Ok(links)
+}
+
+/// Fetches and parses the landing page for `proc_type` with retries, naming the page in
+/// the final error.
+async fn fetch_links_with<F: PageFetcher>(
+    fetcher: &F,
+    proc_type: &ProcurementType,
+    retry_config: &RetryConfig,
+) -> AppResult<BTreeMap<String, String>> {
+    let url = landing_page_url(proc_type);
+    let base_url = Url::parse(url)?;
+
     info!(
-        periods_found = minor_links.len(),
-        "Minor contracts links fetched"
+        procurement_type = proc_type.display_name(),
+        "Fetching links"
     );
+    let html = retry_with_backoff(url, retry_config, || fetcher.fetch_page(url))
+        .await
+        .map_err(|e| match e {
+            AppError::NetworkError(msg) => AppError::NetworkError(format!(
+                "Failed to fetch {} page {url} after {} attempt(s): {msg}",
+                proc_type.display_name(),
+                retry_config.max_retries + 1
+            )),
+            other => other,
+        })?;
 
-    info!("Fetching public tenders links");
-    let public_links = fetch_zip(&client, PUBLIC_TENDERS_URL).await?;
+    let links = parse_zip_links(&html, &base_url)?;
     info!(
-        periods_found = public_links.len(),
-        "Public tenders links fetched"
+        procurement_type = proc_type.display_name(),
+        periods_found = links.len(),
+        "Links fetched"
     );
-
-    Ok((minor_links, public_links))
+    Ok(links)
 }
 
 /// Fetches ZIP file links from a single procurement data page.
@@ -91,16 +270,30 @@ pub async fn fetch_zip(
     // parse the base URL
     let base_url = Url::parse(input_url)?;
 
-    // fetch the page content
-    let response = client
-        .get(base_url.as_str())
-        .send()
-        .await?
-        .error_for_status()?
-        .text()
+    let html = HttpPageFetcher { client }
+        .fetch_page(base_url.as_str())
         .await?;
 
-    parse_zip_links(&response, &base_url)
+    parse_zip_links(&html, &base_url)
+}
+
+/// Like [`fetch_zip`], but builds the HTTP client from `builder`.
+///
+/// Lets library users set their own headers (such as `User-Agent`), proxy, timeouts, or TLS
+/// configuration on a `reqwest::ClientBuilder` without constructing the client themselves.
+///
+/// # Errors
+///
+/// Returns `NetworkError` if the client cannot be built, and otherwise the errors of
+/// [`fetch_zip`].
+pub async fn fetch_zip_with_client_builder(
+    builder: reqwest::ClientBuilder,
+    input_url: &str,
+) -> AppResult<BTreeMap<String, String>> {
+    let client = builder
+        .build()
+        .map_err(|e| AppError::NetworkError(format!("Failed to build HTTP client: {e}")))?;
+    fetch_zip(&client, input_url).await
 }
 
 /// Parses HTML content and extracts ZIP file links, extracting period identifiers from filenames.
@@ -122,18 +315,14 @@ pub fn parse_zip_links(html: &str, base_url: &Url) -> AppResult<BTreeMap<String,
         Selector::parse(ZIP_LINK_SELECTOR).expect("ZIP_LINK_SELECTOR is a valid CSS selector")
     });
 
-    let period_regex = PERIOD_REGEX.get_or_init(|| {
-        Regex::new(PERIOD_REGEX_PATTERN).expect("PERIOD_REGEX_PATTERN is a valid regex pattern")
-    });
-
     for url in document
         .select(selector)
         .filter_map(|el| el.value().attr("href"))
         .filter_map(|href| base_url.join(href).ok())
     {
         if let Some(filename) = url.path_segments().and_then(|mut s| s.next_back()) {
-            if let Some(m) = period_regex.captures(filename).and_then(|c| c.get(1)) {
-                links.insert(m.as_str().to_string(), url.to_string());
+            if let Some(period) = period_from_zip_name(filename) {
+                links.insert(period.to_string(), url.to_string());
             }
         }
     }
@@ -141,10 +330,247 @@ pub fn parse_zip_links(html: &str, base_url: &Url) -> AppResult<BTreeMap<String,
     Ok(links)
 }
 
+/// Extracts the period from a ZIP file name such as `licitaciones_202301.zip`.
+///
+/// Returns `None` when the name does not match [`PERIOD_REGEX_PATTERN`].
+pub(super) fn period_from_zip_name(filename: &str) -> Option<&str> {
+    let period_regex = PERIOD_REGEX.get_or_init(|| {
+        Regex::new(PERIOD_REGEX_PATTERN).expect("PERIOD_REGEX_PATTERN is a valid regex pattern")
+    });
+    period_regex
+        .captures(filename)
+        .and_then(|c| c.get(1))
+        .map(|m| m.as_str())
+}
+
 #[cfg(test)]
 mod tests {
-    use super::parse_zip_links;
-    use url::Url;
+    use super::*;
+    use std::cell::RefCell;
+
+    /// Records requested URLs and serves a fixed page.
+    struct RecordingFetcher {
+        requested: RefCell<Vec<String>>,
+    }
+
+    impl PageFetcher for RecordingFetcher {
+        async fn fetch_page(&self, url: &str) -> AppResult<String> {
+            self.requested.borrow_mut().push(url.to_string());
+            Ok(r#"<a href="files/data_202401.zip">202401</a>"#.to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn fetch_links_with_requests_only_the_selected_page() {
+        let fetcher = RecordingFetcher {
+            requested: RefCell::new(Vec::new()),
+        };
+
+        let links = fetch_links_with(
+            &fetcher,
+            &ProcurementType::MinorContracts,
+            &RetryConfig::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(*fetcher.requested.borrow(), vec![MINOR_CONTRACTS_URL]);
+        assert!(links.contains_key("202401"));
+    }
+
+    #[tokio::test]
+    async fn fetch_all_links_requests_every_landing_page() {
+        let fetcher = recording_fetcher();
+        let config = ResolvedConfig {
+            links_cache_ttl_secs: 0,
+            ..ResolvedConfig::default()
+        };
+
+        let links = fetch_all_links_with(&fetcher, &config, SystemTime::now())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            *fetcher.requested.borrow(),
+            [
+                MINOR_CONTRACTS_URL,
+                PUBLIC_TENDERS_URL,
+                AGGREGATED_PLATFORMS_URL
+            ]
+        );
+        assert_eq!(
+            links.keys().copied().collect::<Vec<_>>(),
+            ProcurementType::ALL
+        );
+        assert!(links[&ProcurementType::AggregatedPlatforms].contains_key("202401"));
+    }
+
+    /// Config caching links in `cache_dir` for an hour.
+    fn cache_config(cache_dir: &std::path::Path) -> ResolvedConfig {
+        ResolvedConfig {
+            links_cache_dir: cache_dir.to_path_buf(),
+            links_cache_ttl_secs: 3600,
+            ..ResolvedConfig::default()
+        }
+    }
+
+    fn recording_fetcher() -> RecordingFetcher {
+        RecordingFetcher {
+            requested: RefCell::new(Vec::new()),
+        }
+    }
+
+    #[tokio::test]
+    async fn cached_links_are_used_until_they_expire() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = cache_config(tmp.path());
+        let proc_type = ProcurementType::PublicTenders;
+        let fetched_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+        let fetcher = recording_fetcher();
+        let links = cached_links_with(&fetcher, &proc_type, &config, fetched_at)
+            .await
+            .unwrap();
+        assert_eq!(fetcher.requested.borrow().len(), 1);
+        assert!(tmp.path().join("links_pt.json").exists());
+
+        // Cache hit: the page is not requested again
+        let fetcher = recording_fetcher();
+        let hit = fetched_at + Duration::from_secs(3599);
+        let cached = cached_links_with(&fetcher, &proc_type, &config, hit)
+            .await
+            .unwrap();
+        assert_eq!(cached, links);
+        assert!(fetcher.requested.borrow().is_empty());
+
+        // Expired: fetched again and the cache rewritten with the new time
+        let fetcher = recording_fetcher();
+        let expired = fetched_at + Duration::from_secs(3600);
+        cached_links_with(&fetcher, &proc_type, &config, expired)
+            .await
+            .unwrap();
+        assert_eq!(fetcher.requested.borrow().len(), 1);
+        let fetcher = recording_fetcher();
+        cached_links_with(
+            &fetcher,
+            &proc_type,
+            &config,
+            expired + Duration::from_secs(1),
+        )
+        .await
+        .unwrap();
+        assert!(fetcher.requested.borrow().is_empty());
+    }
+
+    #[tokio::test]
+    async fn corrupt_cache_is_refetched_and_rewritten() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = cache_config(tmp.path());
+        let cache = tmp.path().join("links_mc.json");
+        std::fs::write(&cache, "not json").unwrap();
+        let now = SystemTime::now();
+
+        let fetcher = recording_fetcher();
+        let links = cached_links_with(&fetcher, &ProcurementType::MinorContracts, &config, now)
+            .await
+            .unwrap();
+
+        assert_eq!(fetcher.requested.borrow().len(), 1);
+        assert!(links.contains_key("202401"));
+        assert_eq!(
+            read_cached_links(&cache, Duration::from_secs(3600), now),
+            Some(links)
+        );
+    }
+
+    #[tokio::test]
+    async fn refresh_links_bypasses_a_fresh_cache() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut config = cache_config(tmp.path());
+        let proc_type = ProcurementType::MinorContracts;
+        let now = SystemTime::now();
+        let stale =
+            BTreeMap::from([("201901".to_string(), "https://old/x_201901.zip".to_string())]);
+        write_cached_links(&links_cache_path(tmp.path(), &proc_type), &stale, now).unwrap();
+
+        config.refresh_links = true;
+        let fetcher = recording_fetcher();
+        let links = cached_links_with(&fetcher, &proc_type, &config, now)
+            .await
+            .unwrap();
+
+        assert_eq!(fetcher.requested.borrow().len(), 1);
+        assert!(links.contains_key("202401"));
+        assert!(!links.contains_key("201901"));
+    }
+
+    #[tokio::test]
+    async fn zero_ttl_disables_the_cache() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut config = cache_config(tmp.path());
+        config.links_cache_ttl_secs = 0;
+
+        let fetcher = recording_fetcher();
+        cached_links_with(
+            &fetcher,
+            &ProcurementType::PublicTenders,
+            &config,
+            SystemTime::now(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(fetcher.requested.borrow().len(), 1);
+        assert!(!tmp.path().join("links_pt.json").exists());
+    }
+
+    /// Serves one landing page listing a ZIP link; returns the page URL and a handle
+    /// yielding the request head.
+    fn serve_landing_page() -> (String, std::thread::JoinHandle<String>) {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/datos/page.aspx", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut head = String::new();
+            let mut reader = BufReader::new(&stream);
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                head.push_str(&line);
+            }
+            let body = r#"<a href="files/data_202401.zip">202401</a>"#;
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+            head
+        });
+        (url, handle)
+    }
+
+    #[tokio::test]
+    async fn fetch_zip_with_client_builder_uses_the_given_builder() {
+        let (url, server) = serve_landing_page();
+        let builder = reqwest::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .user_agent("embedding-app/1.0");
+
+        let links = fetch_zip_with_client_builder(builder, &url).await.unwrap();
+
+        assert_eq!(
+            links.get("202401").map(String::as_str),
+            Some(url.replace("page.aspx", "files/data_202401.zip").as_str())
+        );
+        let head = server.join().unwrap().to_lowercase();
+        assert!(head.contains("user-agent: embedding-app/1.0"));
+    }
 
     #[test]
     fn test_parse_zip_links_basic() {
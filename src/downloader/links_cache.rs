@@ -0,0 +1,135 @@
+use crate::errors::{AppError, AppResult};
+use crate::models::ProcurementType;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+/// Landing page links saved by an earlier run, with the time they were fetched.
+///
+/// Serialized as `{"fetched_at": 1717000000, "links": {"202401": "https://..."}}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct LinksCache {
+    /// Fetch time in seconds since the Unix epoch
+    fetched_at: u64,
+    /// Period -> ZIP URL, as returned by [`super::fetch_links_for`]
+    links: BTreeMap<String, String>,
+}
+
+/// Path of the links cache for `proc_type`: `{cache_dir}/links_{mc,pt,agg}.json`.
+pub(super) fn links_cache_path(cache_dir: &Path, proc_type: &ProcurementType) -> PathBuf {
+    cache_dir.join(format!("links_{}.json", proc_type.short_name()))
+}
+
+/// Returns the cached links at `path` when they were fetched less than `ttl` before `now`.
+///
+/// A missing or expired cache returns `None`. So does a cache that cannot be read or
+/// parsed, after logging a warning, so the links are fetched again and the file rewritten.
+pub(super) fn read_cached_links(
+    path: &Path,
+    ttl: Duration,
+    now: SystemTime,
+) -> Option<BTreeMap<String, String>> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return None,
+        Err(e) => {
+            warn!(path = %path.display(), error = %e, "Ignoring unreadable links cache");
+            return None;
+        }
+    };
+    let cache: LinksCache = match serde_json::from_str(&contents) {
+        Ok(cache) => cache,
+        Err(e) => {
+            warn!(path = %path.display(), error = %e, "Ignoring corrupt links cache");
+            return None;
+        }
+    };
+    let now_secs = now.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    // A fetch time in the future (clock changes) counts as expired.
+    let age = now_secs.checked_sub(cache.fetched_at)?;
+    (age < ttl.as_secs()).then_some(cache.links)
+}
+
+/// Writes `links` to `path` as fetched at `now`, atomically (temp file + rename) and
+/// creating parent directories.
+pub(super) fn write_cached_links(
+    path: &Path,
+    links: &BTreeMap<String, String>,
+    now: SystemTime,
+) -> AppResult<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let cache = LinksCache {
+        fetched_at: now.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()),
+        links: links.clone(),
+    };
+    let json = serde_json::to_string_pretty(&cache)
+        .map_err(|e| AppError::ParseError(format!("Failed to serialize links cache: {e}")))?;
+    let tmp_path = path.with_extension("json.part");
+    fs::write(&tmp_path, json)?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn links() -> BTreeMap<String, String> {
+        BTreeMap::from([(
+            "202401".to_string(),
+            "https://example.com/data_202401.zip".to_string(),
+        )])
+    }
+
+    #[test]
+    fn cache_path_names_the_procurement_type() {
+        let dir = Path::new("data/cache");
+        assert_eq!(
+            links_cache_path(dir, &ProcurementType::MinorContracts),
+            PathBuf::from("data/cache/links_mc.json")
+        );
+        assert_eq!(
+            links_cache_path(dir, &ProcurementType::PublicTenders),
+            PathBuf::from("data/cache/links_pt.json")
+        );
+        assert_eq!(
+            links_cache_path(dir, &ProcurementType::AggregatedPlatforms),
+            PathBuf::from("data/cache/links_agg.json")
+        );
+    }
+
+    #[test]
+    fn fresh_cache_is_returned_until_the_ttl_elapses() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("cache/links_pt.json");
+        let fetched_at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let ttl = Duration::from_secs(3600);
+
+        write_cached_links(&path, &links(), fetched_at).unwrap();
+
+        let later = fetched_at + Duration::from_secs(3599);
+        assert_eq!(read_cached_links(&path, ttl, later), Some(links()));
+        let expired = fetched_at + ttl;
+        assert_eq!(read_cached_links(&path, ttl, expired), None);
+        let earlier = fetched_at - Duration::from_secs(1);
+        assert_eq!(read_cached_links(&path, ttl, earlier), None);
+        assert!(!path.with_extension("json.part").exists());
+    }
+
+    #[test]
+    fn missing_or_corrupt_cache_is_ignored() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("links_mc.json");
+        let ttl = Duration::from_secs(3600);
+
+        assert_eq!(read_cached_links(&path, ttl, SystemTime::now()), None);
+        fs::write(&path, "{\"fetched_at\": \"yesterday\"").unwrap();
+        assert_eq!(read_cached_links(&path, ttl, SystemTime::now()), None);
+    }
+}
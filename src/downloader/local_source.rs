@@ -0,0 +1,257 @@
+use super::file_downloader::{needs_download, verify_zip_magic};
+use super::link_fetcher::period_from_zip_name;
+use crate::errors::{AppError, AppResult};
+use crate::models::{DownloadStats, ProcurementType};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tokio::fs;
+use tracing::{debug, info, warn};
+use url::Url;
+
+/// Lists the period ZIPs in a local directory, as a stand-in for [`super::fetch_links_for`].
+///
+/// Only files directly inside `source_dir` are considered. File names must match the same
+/// pattern as the published archives (e.g. `licitacionesPerfilesContratanteCompleto3_202301.zip`);
+/// other files are ignored. Values are `file://` URLs of the canonicalized paths, so the
+/// result can be fed to [`super::filter_periods_by_range`] unchanged.
+///
+/// # Errors
+///
+/// Returns `IoError` if the directory cannot be read, or `InvalidInput` if two files map
+/// to the same period.
+pub fn local_zip_links(source_dir: &Path) -> AppResult<BTreeMap<String, String>> {
+    let entries = std::fs::read_dir(source_dir).map_err(|e| {
+        AppError::IoError(format!(
+            "Failed to read source directory {}: {e}",
+            source_dir.display()
+        ))
+    })?;
+
+    let mut links = BTreeMap::new();
+    let mut sources: BTreeMap<String, PathBuf> = BTreeMap::new();
+    for entry in entries {
+        let path = entry?.path();
+        if !path.is_file() {
+            continue;
+        }
+        let Some(period) = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(period_from_zip_name)
+            .map(str::to_string)
+        else {
+            debug!(file = %path.display(), "Ignoring file without a period in its name");
+            continue;
+        };
+        if let Some(existing) = sources.get(&period) {
+            return Err(AppError::InvalidInput(format!(
+                "Period {period} appears twice in {}: {} and {}",
+                source_dir.display(),
+                existing.display(),
+                path.display()
+            )));
+        }
+
+        let canonical = path.canonicalize()?;
+        let url = Url::from_file_path(&canonical).map_err(|_| {
+            AppError::InvalidInput(format!(
+                "Cannot build a file URL for {}",
+                canonical.display()
+            ))
+        })?;
+        links.insert(period.clone(), url.to_string());
+        sources.insert(period, path);
+    }
+
+    info!(
+        count = links.len(),
+        source_dir = %source_dir.display(),
+        "Found local ZIP files"
+    );
+    Ok(links)
+}
+
+/// Copies local period ZIPs into the download directory, as a stand-in for
+/// [`super::download_files`].
+///
+/// `links` maps periods to `file://` URLs as returned by [`local_zip_links`]. Each file is
+/// copied to `{download_dir}/{period}.zip` through a `.part` file and checked for the ZIP
+/// signature, so the rest of the pipeline (extraction, cleanup) never touches the source
+/// directory. Periods whose ZIP already exists are skipped.
+///
+/// # Errors
+///
+/// Returns `InvalidInput` for a URL that is not a `file://` URL, `ParseError` for a file
+/// that is not a ZIP archive, or `IoError` if a copy fails. With `keep_going`, these
+/// per-period errors are recorded in [`DownloadStats::failed_periods`] instead.
+pub async fn copy_local_files(
+    links: &BTreeMap<String, String>,
+    proc_type: &ProcurementType,
+    config: &crate::config::ResolvedConfig,
+) -> AppResult<DownloadStats> {
+    let download_dir = proc_type.download_dir(config);
+    fs::create_dir_all(&download_dir)
+        .await
+        .map_err(|e| AppError::IoError(format!("Failed to create directory: {e}")))?;
+
+    let start = Instant::now();
+    let mut stats = DownloadStats::default();
+    for (period, link) in links {
+        if !needs_download(&download_dir, period) {
+            stats.skipped += 1;
+            continue;
+        }
+        stats.attempted += 1;
+
+        match copy_local_file(period, link, &download_dir).await {
+            Ok(bytes) => {
+                stats.downloaded += 1;
+                stats.bytes += bytes;
+            }
+            Err(e) if config.keep_going => {
+                warn!(period, error = %e, "Failed to copy local file, continuing");
+                stats.failed_periods.insert(period.clone(), e.to_string());
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    stats.elapsed_ms = start.elapsed().as_millis() as u64;
+
+    info!(
+        copied = stats.downloaded,
+        skipped = stats.skipped,
+        bytes = stats.bytes,
+        "Local copy completed"
+    );
+    Ok(stats)
+}
+
+/// Path of the file behind a `file://` URL from [`local_zip_links`], or `None` for any
+/// other URL.
+pub(crate) fn local_source_path(link: &str) -> Option<PathBuf> {
+    Url::parse(link)
+        .ok()
+        .filter(|url| url.scheme() == "file")
+        .and_then(|url| url.to_file_path().ok())
+}
+
+/// Copies the ZIP archive at the `file://` URL `link` to `{period}.zip` in `download_dir`,
+/// returning its size in bytes.
+async fn copy_local_file(period: &str, link: &str, download_dir: &Path) -> AppResult<u64> {
+    let source = local_source_path(link)
+        .ok_or_else(|| AppError::InvalidInput(format!("Not a local file URL: {link}")))?;
+    let file_path = download_dir.join(format!("{period}.zip"));
+    let tmp_path = download_dir.join(format!("{period}.zip.part"));
+    let bytes = fs::copy(&source, &tmp_path)
+        .await
+        .map_err(|e| AppError::IoError(format!("Failed to copy {}: {e}", source.display())))?;
+    if let Err(e) = verify_zip_magic(&tmp_path).await {
+        if let Err(remove_err) = fs::remove_file(&tmp_path).await {
+            warn!(
+                file_path = %tmp_path.display(),
+                error = %remove_err,
+                "Failed to remove invalid ZIP file"
+            );
+        }
+        return Err(e);
+    }
+    fs::rename(&tmp_path, &file_path)
+        .await
+        .map_err(|e| AppError::IoError(format!("Failed to move {}: {e}", tmp_path.display())))?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn local_zip_links_reads_periods_from_file_names() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("licitaciones_202301.zip"), b"PK\x03\x04").unwrap();
+        std::fs::write(tmp.path().join("licitaciones_2022.zip"), b"PK\x03\x04").unwrap();
+        std::fs::write(tmp.path().join("README.txt"), b"ignored").unwrap();
+        std::fs::create_dir(tmp.path().join("nested_202302.zip")).unwrap();
+
+        let links = local_zip_links(tmp.path()).unwrap();
+
+        assert_eq!(links.keys().collect::<Vec<_>>(), ["2022", "202301"]);
+        assert!(links["202301"].starts_with("file://"));
+        assert!(links["202301"].ends_with("licitaciones_202301.zip"));
+    }
+
+    #[test]
+    fn local_zip_links_rejects_duplicate_periods() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("a_202301.zip"), b"PK\x03\x04").unwrap();
+        std::fs::write(tmp.path().join("b_202301.zip"), b"PK\x03\x04").unwrap();
+
+        let err = local_zip_links(tmp.path()).unwrap_err();
+        assert!(matches!(err, AppError::InvalidInput(_)));
+    }
+
+    #[tokio::test]
+    async fn copy_local_files_copies_and_skips_existing() {
+        let source = TempDir::new().unwrap();
+        let data = TempDir::new().unwrap();
+        std::fs::write(source.path().join("x_202301.zip"), b"PK\x03\x04zip").unwrap();
+        std::fs::write(source.path().join("x_202302.zip"), b"PK\x03\x04zip").unwrap();
+        let config = crate::config::ResolvedConfig {
+            download_dir_pt: data.path().join("pt"),
+            ..Default::default()
+        };
+        std::fs::create_dir_all(&config.download_dir_pt).unwrap();
+        std::fs::write(config.download_dir_pt.join("202302.zip"), b"PK\x03\x04old").unwrap();
+
+        let links = local_zip_links(source.path()).unwrap();
+        let stats = copy_local_files(&links, &ProcurementType::PublicTenders, &config)
+            .await
+            .unwrap();
+
+        assert_eq!((stats.downloaded, stats.skipped, stats.bytes), (1, 1, 7));
+        assert!(config.download_dir_pt.join("202301.zip").exists());
+        assert!(source.path().join("x_202301.zip").exists());
+    }
+
+    #[tokio::test]
+    async fn copy_local_files_rejects_non_zip_files() {
+        let source = TempDir::new().unwrap();
+        let data = TempDir::new().unwrap();
+        std::fs::write(source.path().join("x_202301.zip"), b"<html>").unwrap();
+        let config = crate::config::ResolvedConfig {
+            download_dir_pt: data.path().join("pt"),
+            ..Default::default()
+        };
+
+        let links = local_zip_links(source.path()).unwrap();
+        let result = copy_local_files(&links, &ProcurementType::PublicTenders, &config).await;
+
+        assert!(matches!(result, Err(AppError::ParseError(_))));
+        assert!(!config.download_dir_pt.join("202301.zip").exists());
+        assert!(!config.download_dir_pt.join("202301.zip.part").exists());
+    }
+
+    #[tokio::test]
+    async fn copy_local_files_keep_going_records_failed_periods() {
+        let source = TempDir::new().unwrap();
+        let data = TempDir::new().unwrap();
+        std::fs::write(source.path().join("x_202301.zip"), b"<html>").unwrap();
+        std::fs::write(source.path().join("x_202302.zip"), b"PK\x03\x04zip").unwrap();
+        let config = crate::config::ResolvedConfig {
+            download_dir_pt: data.path().join("pt"),
+            keep_going: true,
+            ..Default::default()
+        };
+
+        let links = local_zip_links(source.path()).unwrap();
+        let stats = copy_local_files(&links, &ProcurementType::PublicTenders, &config)
+            .await
+            .unwrap();
+
+        assert_eq!(stats.downloaded, 1);
+        assert_eq!(stats.failed_periods.keys().collect::<Vec<_>>(), ["202301"]);
+        assert!(config.download_dir_pt.join("202302.zip").exists());
+    }
+}
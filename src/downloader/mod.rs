@@ -1,13 +1,28 @@
 //! Download and fetch operations for procurement data.
 //!
 //! This module provides functions to fetch ZIP file links from Spanish procurement data sources
-//! and download the archives for processing. The main entry points are [`fetch_all_links`] and [`download_files`].
+//! and download the archives for processing. Every HTTP client comes from [`http::build_client`], which applies the proxy, CA bundle, and user agent settings. The main entry points are [`fetch_links_for`] (or [`fetch_all_links`] for every source) and [`download_files`].
+//! For offline machines, [`local_zip_links`] and [`copy_local_files`] take the archives from a local mirror instead.
 
 mod file_downloader;
+pub mod http;
 mod link_fetcher;
+mod links_cache;
+mod local_source;
 mod period_filter;
+mod rate_limit;
+mod retry;
 
 // Re-export public API
 pub use file_downloader::download_files;
-pub use link_fetcher::{fetch_all_links, fetch_zip, parse_zip_links};
-pub use period_filter::{filter_periods_by_range, validate_period_format};
+pub(crate) use file_downloader::{
+    download_files_with_limits, needs_download, remote_size, DownloadLimits,
+};
+pub use link_fetcher::{
+    fetch_all_links, fetch_links_for, fetch_zip, fetch_zip_with_client_builder, parse_zip_links,
+};
+pub(crate) use local_source::local_source_path;
+pub use local_source::{copy_local_files, local_zip_links};
+pub use period_filter::{
+    current_month, exclude_periods, filter_periods_by_range, recent_periods, validate_period_format,
+};
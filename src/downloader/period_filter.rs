@@ -1,9 +1,12 @@
 use crate::errors::{AppError, AppResult};
 use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::info;
 
-/// Validates that a period string matches the expected format (YYYY or YYYYMM).
+/// Validates that a period string matches the expected format (YYYY, YYYYMM, or YYYYQn).
 ///
-/// Checks that the period contains only ASCII digits and has exactly 4 digits (YYYY) or 6 digits (YYYYMM).
+/// Checks that the period contains only ASCII digits and has exactly 4 digits (YYYY) or 6 digits (YYYYMM),
+/// or is a quarter: 4 digits, `Q`, and a quarter number from 1 to 4 (e.g. `2023Q2`).
 ///
 /// Returns `Ok(())` if valid, or `InvalidInput` error otherwise.
 pub fn validate_period_format(period: &str) -> AppResult<()> {
@@ -12,6 +15,15 @@ pub fn validate_period_format(period: &str) -> AppResult<()> {
             "Period must be YYYY or YYYYMM format (4 or 6 digits), got empty string".to_string(),
         ));
     }
+    if let Some((year, quarter)) = period.split_once('Q') {
+        let valid_year = year.len() == 4 && year.chars().all(|c| c.is_ascii_digit());
+        if !valid_year || !matches!(quarter, "1" | "2" | "3" | "4") {
+            return Err(AppError::InvalidInput(format!(
+                "Quarter period must be YYYYQn with n from 1 to 4, got: {period}"
+            )));
+        }
+        return Ok(());
+    }
     if !period.chars().all(|c| c.is_ascii_digit()) {
         return Err(AppError::InvalidInput(format!(
             "Period must contain only digits, got: {period}"
@@ -27,11 +39,26 @@ pub fn validate_period_format(period: &str) -> AppResult<()> {
     }
 }
 
+/// Returns the first and last month (`YYYYMM`) of a `YYYYQn` quarter, or `None` for other periods.
+///
+/// Expects a period already checked by [`validate_period_format`].
+fn quarter_months(period: &str) -> Option<(String, String)> {
+    let (year, quarter) = period.split_once('Q')?;
+    let quarter: u32 = quarter.parse().ok()?;
+    let last_month = quarter * 3;
+    Some((
+        format!("{year}{:02}", last_month - 2),
+        format!("{year}{last_month:02}"),
+    ))
+}
+
 /// Filters links by period range, validating that specified periods exist.
 ///
 /// This function filters a map of period-to-URL links based on a start and/or end period.
 /// Periods are compared correctly, handling both YYYY and YYYYMM formats. The range is inclusive
-/// on both ends.
+/// on both ends. A quarter (`YYYYQn`) stands for its three months: as a start it selects from
+/// the quarter's first month and as an end up to its last month, so `2023Q1`..`2023Q2` selects
+/// `202301` through `202306`. A quarter must contain at least one available month.
 ///
 /// # Arguments
 ///
@@ -46,8 +73,8 @@ pub fn validate_period_format(period: &str) -> AppResult<()> {
 /// # Errors
 ///
 /// Returns `InvalidInput` if `start_period` or `end_period` has an invalid format
-/// (not YYYY or YYYYMM). Returns `PeriodValidationError` if the period format is valid
-/// but doesn't exist in the `links` map.
+/// (not YYYY, YYYYMM, or YYYYQn). Returns `PeriodValidationError` if the period format is valid
+/// but doesn't exist in the `links` map (or, for a quarter, none of its months do).
 ///
 pub fn filter_periods_by_range(
     links: &BTreeMap<String, String>,
@@ -58,7 +85,11 @@ pub fn filter_periods_by_range(
 
     for period in [start_period, end_period].into_iter().flatten() {
         validate_period_format(period)?;
-        if !links.contains_key(period) {
+        let exists = match quarter_months(period) {
+            Some((first, last)) => links.range(first..=last).next().is_some(),
+            None => links.contains_key(period),
+        };
+        if !exists {
             return Err(AppError::PeriodValidationError {
                 period: period.to_string(),
                 available: available_str.clone(),
@@ -66,8 +97,8 @@ pub fn filter_periods_by_range(
         }
     }
 
-    let start_key = start_period.map(|s| s.to_string());
-    let end_key = end_period.map(|e| e.to_string());
+    let start_key = start_period.map(|s| quarter_months(s).map_or_else(|| s.to_string(), |q| q.0));
+    let end_key = end_period.map(|e| quarter_months(e).map_or_else(|| e.to_string(), |q| q.1));
 
     if let (Some(start), Some(end)) = (&start_key, &end_key) {
         if start > end {
@@ -92,9 +123,85 @@ pub fn filter_periods_by_range(
     Ok(filtered)
 }
 
+/// Removes the `excluded` periods from an already filtered `links` map.
+///
+/// Each excluded period must be `YYYY`, `YYYYMM`, or `YYYYQn`; a quarter removes its three
+/// months. Excluding a period that is not in `links` is not an error and is only logged.
+///
+/// # Errors
+///
+/// Returns `InvalidInput` if an excluded period has an invalid format.
+pub fn exclude_periods(links: &mut BTreeMap<String, String>, excluded: &[String]) -> AppResult<()> {
+    for period in excluded {
+        validate_period_format(period)?;
+        let keys: Vec<String> = match quarter_months(period) {
+            Some((first, last)) => links.range(first..=last).map(|(k, _)| k.clone()).collect(),
+            None => links
+                .contains_key(period)
+                .then(|| period.clone())
+                .into_iter()
+                .collect(),
+        };
+        if keys.is_empty() {
+            info!(period = %period, "Excluded period is not in the selected range");
+        }
+        for key in keys {
+            links.remove(&key);
+            info!(period = %key, "Excluding period");
+        }
+    }
+    Ok(())
+}
+
+/// Selects the `n` most recent monthly (`YYYYMM`) periods in `links` that are not after
+/// `today` (a `YYYYMM` month, see [`current_month`]).
+///
+/// Yearly archives are never selected. When fewer than `n` monthly periods are available,
+/// all of them are returned, so the result may be shorter than `n` (or empty).
+pub fn recent_periods(
+    links: &BTreeMap<String, String>,
+    n: usize,
+    today: &str,
+) -> BTreeMap<String, String> {
+    links
+        .range(..=today.to_string())
+        .rev()
+        .filter(|(period, _)| period.len() == 6 && validate_period_format(period).is_ok())
+        .take(n)
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect()
+}
+
+/// Returns the current UTC month as `YYYYMM`, read from the system clock.
+pub fn current_month() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() / 86_400) as i64;
+    let (year, month) = year_month_from_days(days);
+    format!("{year:04}{month:02}")
+}
+
+/// Converts days since 1970-01-01 to a (year, month) pair in the proleptic Gregorian calendar.
+fn year_month_from_days(days: i64) -> (i64, i64) {
+    // Howard Hinnant's civil_from_days, counting from 0000-03-01 in 400-year eras.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{filter_periods_by_range, validate_period_format};
+    use super::{
+        current_month, exclude_periods, filter_periods_by_range, quarter_months, recent_periods,
+        validate_period_format, year_month_from_days,
+    };
     use crate::errors::AppError;
     use std::collections::BTreeMap;
 
@@ -553,4 +660,145 @@ mod tests {
             _ => panic!("Expected InvalidInput error"),
         }
     }
+
+    #[test]
+    fn test_validate_period_format_quarters() {
+        for period in ["2023Q1", "2023Q2", "2023Q3", "2023Q4"] {
+            assert!(validate_period_format(period).is_ok(), "{period}");
+        }
+        for period in ["2023Q5", "2023Q0", "2023Q", "23Q1", "2023Q12", "20a3Q1"] {
+            match validate_period_format(period) {
+                Err(AppError::InvalidInput(msg)) => assert!(msg.contains("YYYYQn"), "{msg}"),
+                other => panic!("Expected InvalidInput for {period}, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_quarter_months_boundaries() {
+        let months = |period| quarter_months(period).unwrap();
+        assert_eq!(
+            months("2023Q1"),
+            ("202301".to_string(), "202303".to_string())
+        );
+        assert_eq!(
+            months("2023Q2"),
+            ("202304".to_string(), "202306".to_string())
+        );
+        assert_eq!(
+            months("2023Q3"),
+            ("202307".to_string(), "202309".to_string())
+        );
+        assert_eq!(
+            months("2023Q4"),
+            ("202310".to_string(), "202312".to_string())
+        );
+        assert_eq!(quarter_months("202304"), None);
+    }
+
+    #[test]
+    fn test_filter_with_quarters() {
+        let mut links = BTreeMap::new();
+        for period in [
+            "2022", "202212", "202301", "202303", "202304", "202306", "202307",
+        ] {
+            links.insert(
+                period.to_string(),
+                format!("https://example.com/{period}.zip"),
+            );
+        }
+
+        let filtered = filter_periods_by_range(&links, Some("2023Q1"), Some("2023Q2")).unwrap();
+        assert_eq!(
+            filtered.keys().collect::<Vec<_>>(),
+            ["202301", "202303", "202304", "202306"]
+        );
+
+        // Quarters mix with month bounds and select whole quarters at either end.
+        let filtered = filter_periods_by_range(&links, Some("202212"), Some("2023Q1")).unwrap();
+        assert_eq!(
+            filtered.keys().collect::<Vec<_>>(),
+            ["202212", "202301", "202303"]
+        );
+        let filtered = filter_periods_by_range(&links, Some("2023Q2"), None).unwrap();
+        assert_eq!(
+            filtered.keys().collect::<Vec<_>>(),
+            ["202304", "202306", "202307"]
+        );
+    }
+
+    #[test]
+    fn test_filter_rejects_unavailable_and_reversed_quarters() {
+        let links = create_test_links(); // 202301..202305
+
+        match filter_periods_by_range(&links, Some("2023Q4"), None) {
+            Err(AppError::PeriodValidationError { period, .. }) => assert_eq!(period, "2023Q4"),
+            other => panic!("Expected PeriodValidationError, got {other:?}"),
+        }
+        assert!(matches!(
+            filter_periods_by_range(&links, Some("2023Q2"), Some("2023Q1")),
+            Err(AppError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            filter_periods_by_range(&links, Some("2023Q5"), None),
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_exclude_periods_removes_months_and_quarters() {
+        let mut links =
+            filter_periods_by_range(&create_test_links(), Some("202301"), None).unwrap();
+
+        exclude_periods(
+            &mut links,
+            &[
+                "202302".to_string(),
+                "202312".to_string(),
+                "2023Q2".to_string(),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(links.keys().collect::<Vec<_>>(), ["202301", "202303"]);
+        assert!(matches!(
+            exclude_periods(&mut links, &["2023-07".to_string()]),
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_recent_periods_takes_latest_months_up_to_today() {
+        let mut links = create_test_links(); // 202301..202305
+        links.insert("2022".to_string(), String::new());
+        links.insert("202307".to_string(), String::new());
+
+        let recent = recent_periods(&links, 3, "202306");
+        assert_eq!(
+            recent.keys().collect::<Vec<_>>(),
+            ["202303", "202304", "202305"]
+        );
+        let recent = recent_periods(&links, 2, "202312");
+        assert_eq!(recent.keys().collect::<Vec<_>>(), ["202305", "202307"]);
+    }
+
+    #[test]
+    fn test_recent_periods_returns_fewer_when_not_enough_exist() {
+        let mut links = create_test_links();
+        links.insert("2022".to_string(), String::new());
+
+        assert_eq!(recent_periods(&links, 12, "202305").len(), 5);
+        assert!(recent_periods(&links, 3, "202212").is_empty());
+    }
+
+    #[test]
+    fn test_year_month_from_days() {
+        assert_eq!(year_month_from_days(0), (1970, 1));
+        assert_eq!(year_month_from_days(19_782), (2024, 2)); // 2024-02-29
+        assert_eq!(year_month_from_days(19_783), (2024, 3));
+        assert_eq!(year_month_from_days(20_453), (2025, 12)); // 2025-12-31
+        let month = current_month();
+        assert_eq!(month.len(), 6);
+        assert!(validate_period_format(&month).is_ok());
+    }
 }
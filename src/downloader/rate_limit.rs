@@ -0,0 +1,68 @@
+use tokio::sync::Mutex;
+use tokio::time::{Duration, Instant};
+
+/// Enforces a minimum interval between request starts across concurrent download tasks.
+///
+/// Built from `--max-rps`: with `max_rps = 2.0`, two consecutive requests start at least
+/// 500 ms apart, whichever tasks send them. Each caller reserves the next free slot under
+/// the lock and then sleeps until it, so waiting tasks do not hold the lock.
+pub(crate) struct RateLimiter {
+    interval: Duration,
+    next_start: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    /// Creates a limiter allowing at most `max_rps` request starts per second.
+    ///
+    /// `max_rps` must be positive and finite (see [`crate::config::ResolvedConfig::validate`]).
+    pub(crate) fn new(max_rps: f64) -> Self {
+        Self {
+            interval: Duration::from_secs_f64(1.0 / max_rps),
+            next_start: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Waits until the caller may start its next request.
+    pub(crate) async fn acquire(&self) {
+        let start = {
+            let mut next_start = self.next_start.lock().await;
+            let start = (*next_start).max(Instant::now());
+            *next_start = start + self.interval;
+            start
+        };
+        tokio::time::sleep_until(start).await;
+    }
+}
+
+/// Caps the combined download bandwidth of concurrent download tasks.
+///
+/// Built from `--max-bytes-per-sec`: every chunk written reserves `len / max_bytes_per_sec`
+/// seconds of the shared budget, and the writer sleeps until its reservation ends before
+/// reading the next chunk. Idle time is not banked, so a pause does not allow a burst later.
+pub(crate) struct BandwidthLimiter {
+    bytes_per_sec: f64,
+    next_free: Mutex<Instant>,
+}
+
+impl BandwidthLimiter {
+    /// Creates a limiter allowing at most `max_bytes_per_sec` bytes per second.
+    ///
+    /// `max_bytes_per_sec` must be positive (see [`crate::config::ResolvedConfig::validate`]).
+    pub(crate) fn new(max_bytes_per_sec: u64) -> Self {
+        Self {
+            bytes_per_sec: max_bytes_per_sec as f64,
+            next_free: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Accounts for `bytes` just received and waits until they fit under the cap.
+    pub(crate) async fn consume(&self, bytes: usize) {
+        let until = {
+            let mut next_free = self.next_free.lock().await;
+            let start = (*next_free).max(Instant::now());
+            *next_free = start + Duration::from_secs_f64(bytes as f64 / self.bytes_per_sec);
+            *next_free
+        };
+        tokio::time::sleep_until(until).await;
+    }
+}
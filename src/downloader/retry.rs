@@ -0,0 +1,332 @@
+use crate::errors::{AppError, AppResult};
+use std::future::Future;
+use std::time::{Duration, SystemTime};
+use tracing::warn;
+
+/// Extracts HTTP status code from error message if present.
+///
+/// Looks for the pattern "HTTP {status_code}:" in the error message.
+/// Returns `Some(status_code)` if found, `None` otherwise.
+fn extract_status_code(msg: &str) -> Option<u16> {
+    let prefix = "HTTP ";
+    if let Some(start) = msg.find(prefix) {
+        let start = start + prefix.len();
+        let end = msg[start..].find(':').unwrap_or(msg[start..].len());
+        msg[start..start + end].trim().parse().ok()
+    } else {
+        None
+    }
+}
+
+/// Determines if an error should trigger a retry attempt.
+///
+/// Returns `true` for retryable errors (network errors, timeouts, 5xx HTTP status codes,
+/// and 429 Too Many Requests, including throttled responses with a `Retry-After` delay).
+/// Returns `false` for non-retryable errors (4xx client errors, I/O errors, validation errors).
+pub(crate) fn should_retry(error: &AppError) -> bool {
+    match error {
+        AppError::NetworkError(msg) => {
+            // Extract status code from message if present
+            if let Some(status_code) = extract_status_code(msg) {
+                // 4xx = client error, don't retry (except 429 Too Many Requests)
+                // 5xx = server error, retry
+                status_code >= 500 || status_code == 429
+            } else {
+                // No status code means network/timeout error - retry by default
+                // Legacy string matching fallback for older error formats
+                !msg.contains("400")
+                    && !msg.contains("401")
+                    && !msg.contains("403")
+                    && !msg.contains("404")
+                    && !msg.contains("client error")
+            }
+        }
+        AppError::Throttled { .. } => true, // Retry after the requested delay
+        AppError::IoError(_) => false,      // Don't retry I/O errors
+        AppError::ParseError(_) => false,   // Don't retry parse errors
+        AppError::UrlError(_) => false,     // Don't retry URL errors
+        AppError::RegexError(_) => false,   // Don't retry regex errors
+        AppError::SelectorError(_) => false, // Don't retry selector errors
+        AppError::PeriodValidationError { .. } => false, // Don't retry validation errors
+        AppError::InvalidInput(_) => false, // Don't retry invalid input errors
+        AppError::PartialFailure { .. } => false, // Don't retry partial run failures
+    }
+}
+
+/// Parses a `Retry-After` header value, either delay-seconds (`"120"`) or an HTTP-date
+/// (`"Wed, 21 Oct 2015 07:28:00 GMT"`), into the delay from `now`.
+///
+/// Dates in the past yield a zero delay. Returns `None` for malformed values.
+pub(crate) fn parse_retry_after(value: &str, now: SystemTime) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(date.duration_since(now).unwrap_or(Duration::ZERO))
+}
+
+/// Configuration for retry behavior.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RetryConfig {
+    pub(crate) max_retries: u32,
+    pub(crate) initial_delay_ms: u64,
+    pub(crate) max_delay_ms: u64,
+    /// Whether to randomize each delay (see [`calculate_backoff`])
+    pub(crate) jitter: bool,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_delay_ms: 1000,
+            max_delay_ms: 10000,
+            jitter: true,
+        }
+    }
+}
+
+impl From<&crate::config::ResolvedConfig> for RetryConfig {
+    fn from(config: &crate::config::ResolvedConfig) -> Self {
+        Self {
+            max_retries: config.max_retries,
+            initial_delay_ms: config.retry_initial_delay_ms,
+            max_delay_ms: config.retry_max_delay_ms,
+            jitter: config.retry_jitter,
+        }
+    }
+}
+
+/// Calculates exponential backoff delay in milliseconds.
+///
+/// Formula: `min(initial_delay * 2^attempt, max_delay)`. With `jitter`, the result is then
+/// multiplied by a random factor in `[0.5, 1.5)` so that concurrent processes retrying the
+/// same server spread out instead of retrying in lockstep; a capped delay can therefore
+/// reach 1.5 × `max_delay`.
+pub(crate) fn calculate_backoff(attempt: u32, config: &RetryConfig, jitter: bool) -> u64 {
+    let delay = config
+        .initial_delay_ms
+        .saturating_mul(2_u64.saturating_pow(attempt))
+        .min(config.max_delay_ms);
+    if jitter {
+        (delay as f64 * (0.5 + rand::random::<f64>())) as u64
+    } else {
+        delay
+    }
+}
+
+/// Runs `operation` until it succeeds, retrying retryable errors with exponential backoff.
+///
+/// `label` identifies the operation in retry warnings (e.g., a filename or page URL).
+/// Non-retryable errors (see [`should_retry`]) are returned immediately; otherwise the
+/// last error is returned once `max_retries` retries have been used. For an
+/// [`AppError::Throttled`] error, the server-requested delay replaces the backoff, still
+/// capped at `max_delay_ms`.
+pub(crate) async fn retry_with_backoff<T, F, Fut>(
+    label: &str,
+    retry_config: &RetryConfig,
+    mut operation: F,
+) -> AppResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = AppResult<T>>,
+{
+    let mut attempt = 0;
+    loop {
+        match operation().await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt < retry_config.max_retries && should_retry(&e) => {
+                let delay_ms = match &e {
+                    AppError::Throttled { retry_after, .. } => {
+                        (retry_after.as_millis() as u64).min(retry_config.max_delay_ms)
+                    }
+                    _ => calculate_backoff(attempt, retry_config, retry_config.jitter),
+                };
+                warn!(
+                    target_name = label,
+                    attempt = attempt + 1,
+                    max_retries = retry_config.max_retries + 1,
+                    delay_ms = delay_ms,
+                    error = %e,
+                    "Retrying after error"
+                );
+                tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms)).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn fast_retry_config() -> RetryConfig {
+        RetryConfig {
+            max_retries: 2,
+            initial_delay_ms: 1,
+            max_delay_ms: 1,
+            jitter: false,
+        }
+    }
+
+    #[test]
+    fn extract_status_code_no_prefix() {
+        assert!(extract_status_code("network error").is_none());
+    }
+
+    #[test]
+    fn extract_status_code_with_http() {
+        assert_eq!(extract_status_code("HTTP 404: not found"), Some(404));
+        assert_eq!(extract_status_code("HTTP 500: oh no"), Some(500));
+    }
+
+    #[test]
+    fn should_retry_network_5xx() {
+        let err = AppError::NetworkError("HTTP 500: server".to_string());
+        assert!(should_retry(&err));
+    }
+
+    #[test]
+    fn should_not_retry_network_4xx() {
+        let err = AppError::NetworkError("HTTP 404: client".to_string());
+        assert!(!should_retry(&err));
+    }
+
+    #[test]
+    fn should_retry_too_many_requests() {
+        let err = AppError::NetworkError("HTTP 429: slow down".to_string());
+        assert!(should_retry(&err));
+    }
+
+    #[test]
+    fn parse_retry_after_seconds() {
+        let now = SystemTime::now();
+        assert_eq!(
+            parse_retry_after("120", now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(parse_retry_after(" 0 ", now), Some(Duration::ZERO));
+        assert_eq!(parse_retry_after("-5", now), None);
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn parse_retry_after_http_date() {
+        let now = httpdate::parse_http_date("Wed, 21 Oct 2015 07:28:00 GMT").unwrap();
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now),
+            Some(Duration::from_secs(30))
+        );
+        // A date in the past means "retry now".
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:00:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[tokio::test]
+    async fn retry_with_backoff_caps_retry_after_at_max_delay() {
+        let calls = Cell::new(0);
+        let started = std::time::Instant::now();
+        let result = retry_with_backoff("page", &fast_retry_config(), || {
+            calls.set(calls.get() + 1);
+            let call = calls.get();
+            async move {
+                if call < 2 {
+                    Err(AppError::Throttled {
+                        msg: "HTTP 429: Too Many Requests".into(),
+                        retry_after: Duration::from_secs(3600),
+                    })
+                } else {
+                    Ok("body")
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), "body");
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn should_not_retry_io_error() {
+        let err = AppError::IoError("disk full".to_string());
+        assert!(!should_retry(&err));
+    }
+
+    #[test]
+    fn calculate_backoff_capped() {
+        let config = RetryConfig::default();
+        assert_eq!(calculate_backoff(0, &config, false), 1000);
+        assert_eq!(calculate_backoff(1, &config, false), 2000);
+        assert_eq!(calculate_backoff(10, &config, false), 10000);
+        assert_eq!(calculate_backoff(70, &config, false), 10000);
+    }
+
+    #[test]
+    fn calculate_backoff_jitter_stays_in_range_and_varies() {
+        let config = RetryConfig::default();
+        let delays: Vec<u64> = (0..1000)
+            .map(|_| calculate_backoff(1, &config, true))
+            .collect();
+
+        assert!(delays.iter().all(|d| (1000..3000).contains(d)));
+        assert!(delays.iter().any(|d| *d != delays[0]));
+    }
+
+    #[tokio::test]
+    async fn retry_with_backoff_recovers_from_503() {
+        let calls = Cell::new(0);
+        let result = retry_with_backoff("page", &fast_retry_config(), || {
+            calls.set(calls.get() + 1);
+            let call = calls.get();
+            async move {
+                if call < 2 {
+                    Err(AppError::NetworkError(
+                        "HTTP 503: Service Unavailable".into(),
+                    ))
+                } else {
+                    Ok("body")
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), "body");
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[tokio::test]
+    async fn retry_with_backoff_gives_up_after_max_retries() {
+        let calls = Cell::new(0);
+        let result: AppResult<()> = retry_with_backoff("page", &fast_retry_config(), || {
+            calls.set(calls.get() + 1);
+            async {
+                Err(AppError::NetworkError(
+                    "HTTP 503: Service Unavailable".into(),
+                ))
+            }
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[tokio::test]
+    async fn retry_with_backoff_does_not_retry_404() {
+        let calls = Cell::new(0);
+        let result: AppResult<()> = retry_with_backoff("page", &fast_retry_config(), || {
+            calls.set(calls.get() + 1);
+            async { Err(AppError::NetworkError("HTTP 404: Not Found".into())) }
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(calls.get(), 1);
+    }
+}
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 /// Application error types for the SPPD CLI.
 ///
 /// Represents all possible errors that can occur during the procurement data download and processing workflow.
@@ -7,6 +9,9 @@ pub enum AppError {
     /// Network request failed (e.g., HTTP errors, timeouts)
     #[error("Network error: {0}")]
     NetworkError(String),
+    /// HTTP 429 or 503 response whose `Retry-After` header asks to wait `retry_after`
+    #[error("Network error: {msg}")]
+    Throttled { msg: String, retry_after: Duration },
     /// Failed to parse HTML/XML content
     #[error("Parse error: {0}")]
     ParseError(String),
@@ -28,6 +33,24 @@ pub enum AppError {
     /// IO operation failed (e.g., file read/write errors)
     #[error("IO error: {0}")]
     IoError(String),
+    /// Some periods failed while the rest of the run succeeded (`--keep-going`)
+    #[error("{failed} period(s) failed at {stage}, {succeeded} succeeded: {details}")]
+    PartialFailure {
+        stage: String,
+        succeeded: usize,
+        failed: usize,
+        details: String,
+    },
+}
+
+impl AppError {
+    /// Process exit code for this error: `2` for partial failures, `1` for anything else.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            AppError::PartialFailure { .. } => 2,
+            _ => 1,
+        }
+    }
 }
 
 // Conversion implementations for common errors
@@ -75,6 +98,7 @@ pub type AppResult<T> = Result<T, AppError>;
 #[cfg(test)]
 mod tests {
     use super::AppError;
+    use std::time::Duration;
 
     #[test]
     fn test_period_validation_error_display() {
@@ -96,6 +120,15 @@ mod tests {
         assert!(err.to_string().contains("Connection timeout"));
     }
 
+    #[test]
+    fn test_throttled_error_display() {
+        let err = AppError::Throttled {
+            msg: "HTTP 429: slow down".to_string(),
+            retry_after: Duration::from_secs(5),
+        };
+        assert_eq!(err.to_string(), "Network error: HTTP 429: slow down");
+    }
+
     #[test]
     fn test_url_error_display() {
         let err = AppError::UrlError("Invalid URL format".to_string());
@@ -121,6 +154,22 @@ mod tests {
         assert!(err.to_string().contains("Invalid input"));
     }
 
+    #[test]
+    fn test_partial_failure_display_and_exit_code() {
+        let err = AppError::PartialFailure {
+            stage: "download".to_string(),
+            succeeded: 2,
+            failed: 1,
+            details: "202402 (download): connection refused".to_string(),
+        };
+        assert_eq!(
+            err.to_string(),
+            "1 period(s) failed at download, 2 succeeded: 202402 (download): connection refused"
+        );
+        assert_eq!(err.exit_code(), 2);
+        assert_eq!(AppError::IoError("disk full".to_string()).exit_code(), 1);
+    }
+
     #[test]
     fn test_app_error_implements_error_trait() {
         use std::error::Error;
@@ -1,11 +1,13 @@
 use crate::errors::{AppError, AppResult};
-use crate::models::ProcurementType;
+use crate::models::{ExtractionStats, ProcurementType};
 use crate::utils::{format_duration, mb_from_bytes, round_two_decimals};
 use rayon::{prelude::*, ThreadPoolBuilder};
+use std::borrow::Cow;
 use std::collections::{BTreeMap, HashSet};
 use std::fs::{self, File};
 use std::io::{copy, BufWriter, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::Instant;
 use tracing::{debug, info, warn};
@@ -24,6 +26,11 @@ use zip::ZipArchive;
 ///   ZIP file is skipped.
 /// - **Missing files**: Missing ZIP files are logged as warnings but don't fail the
 ///   operation.
+/// - **Nested archives**: `.zip` members (e.g. one archive per day) are expanded inside
+///   the period directory, up to two levels deep.
+/// - **Concurrency**: Up to `concurrent_extractions` archives are extracted at once
+///   (twice the CPU count when 0). A failing archive does not stop the others; all
+///   failures are reported together once every archive has been processed.
 /// - **Progress tracking**: Elapsed time and throughput are logged after extraction.
 ///
 /// # Arguments
@@ -41,13 +48,22 @@ use zip::ZipArchive;
 ///
 /// Returns an error if:
 /// - The extraction directory doesn't exist
-/// - ZIP file extraction fails for any file
+/// - ZIP file extraction fails for any file, unless `keep_going` is set, in which case the
+///   failures are recorded in [`ExtractionStats::failed_periods`]
 ///
+#[tracing::instrument(
+    name = "extract_all_zips",
+    skip_all,
+    fields(
+        procurement_type = procurement_type.display_name(),
+        period_count = target_links.len()
+    )
+)]
 pub async fn extract_all_zips(
     target_links: &BTreeMap<String, String>,
     procurement_type: &ProcurementType,
     config: &crate::config::ResolvedConfig,
-) -> AppResult<()> {
+) -> AppResult<ExtractionStats> {
     let extract_dir = procurement_type.extract_dir(config);
     if !extract_dir.exists() {
         return Err(AppError::IoError(format!(
@@ -69,18 +85,7 @@ pub async fn extract_all_zips(
             continue;
         }
 
-        // Check if extraction directory already exists
-        let extract_dir_path = zip_path
-            .parent()
-            .ok_or_else(|| {
-                AppError::InvalidInput(format!(
-                    "ZIP file has no parent directory: {}",
-                    zip_path.display()
-                ))
-            })?
-            .join(period);
-
-        if !extract_dir_path.exists() {
+        if !is_extracted(&extract_dir, period) {
             zips_to_extract.push(zip_path);
         }
     }
@@ -95,7 +100,11 @@ pub async fn extract_all_zips(
             missing = missing_zips.len(),
             "All ZIP files already extracted, skipping extraction"
         );
-        return Ok(());
+        return Ok(ExtractionStats {
+            skipped: skipped_count,
+            missing: missing_zips.len(),
+            ..ExtractionStats::default()
+        });
     }
 
     // Log warnings for missing ZIP files
@@ -116,22 +125,27 @@ pub async fn extract_all_zips(
 
     let start = Instant::now();
 
-    let cpu_count = std::thread::available_parallelism()
-        .map(|p| p.get())
-        .unwrap_or(1);
-    let thread_count = cpu_count.saturating_mul(2);
+    let thread_count = match config.concurrent_extractions {
+        0 => std::thread::available_parallelism()
+            .map(|p| p.get())
+            .unwrap_or(1)
+            .saturating_mul(2),
+        n => n,
+    };
     let rayon_pool = ThreadPoolBuilder::new()
         .num_threads(thread_count)
         .build()
         .map_err(|e| AppError::IoError(format!("Failed to configure rayon thread pool: {e}")))?;
 
+    let limits = ExtractionLimits::from_config(config);
+
     // Run parallel extraction using rayon within spawn_blocking
     let results = tokio::task::spawn_blocking(move || {
         rayon_pool.install(|| {
             zips_to_extract
                 .par_iter()
                 .map(|zip_path| {
-                    let result = extract_zip_sync(zip_path);
+                    let result = extract_zip_sync(zip_path, limits);
                     (zip_path.clone(), result)
                 })
                 .collect::<Vec<(PathBuf, AppResult<()>)>>()
@@ -142,6 +156,7 @@ pub async fn extract_all_zips(
 
     // Collect errors
     let mut errors = Vec::new();
+    let mut failed_periods = BTreeMap::new();
     let mut extracted_bytes = 0u64;
     for (zip_path, result) in results {
         if let Err(e) = result {
@@ -151,7 +166,10 @@ pub async fn extract_all_zips(
                 error = %e,
                 "Failed to extract ZIP file"
             );
-            errors.push(error_msg);
+            errors.push(error_msg.clone());
+            if let Some(period) = zip_path.file_stem() {
+                failed_periods.insert(period.to_string_lossy().into_owned(), error_msg);
+            }
             continue;
         }
 
@@ -160,7 +178,7 @@ pub async fn extract_all_zips(
         }
     }
 
-    if !errors.is_empty() {
+    if !errors.is_empty() && !config.keep_going {
         return Err(AppError::IoError(format!(
             "Failed to extract {} ZIP file(s): {}",
             errors.len(),
@@ -183,8 +201,10 @@ pub async fn extract_all_zips(
     let size_mb = round_two_decimals(total_mb);
     let throughput_mb_s = round_two_decimals(throughput);
 
+    let extracted = total_zips - errors.len();
     info!(
-        extracted = total_zips,
+        extracted,
+        failed = errors.len(),
         skipped = skipped_count,
         missing = missing_zips.len(),
         elapsed = elapsed_str,
@@ -193,12 +213,83 @@ pub async fn extract_all_zips(
         "Extraction completed"
     );
 
-    Ok(())
+    Ok(ExtractionStats {
+        extracted,
+        skipped: skipped_count,
+        missing: missing_zips.len(),
+        bytes: extracted_bytes,
+        failed_periods,
+        elapsed_ms: elapsed.as_millis() as u64,
+    })
+}
+
+/// Size and member-count limits applied while extracting one period archive (including
+/// its nested archives). Declared sizes can be forged, so the byte limits are enforced on
+/// the bytes actually written as well as on the sizes declared in the central directory.
+#[derive(Debug, Clone, Copy)]
+struct ExtractionLimits {
+    /// Total uncompressed bytes across all members (`max_extracted_bytes`)
+    max_total_bytes: u64,
+    /// Uncompressed bytes of any single member (`max_extracted_member_bytes`)
+    max_member_bytes: u64,
+    /// Members in any single archive (`max_zip_entries`)
+    max_entries: usize,
+}
+
+impl ExtractionLimits {
+    fn from_config(config: &crate::config::ResolvedConfig) -> Self {
+        Self {
+            max_total_bytes: config.max_extracted_bytes,
+            max_member_bytes: config.max_extracted_member_bytes,
+            max_entries: config.max_zip_entries,
+        }
+    }
+
+    /// Returns the error for the first limit exceeded by `member`, if any.
+    fn exceeded(
+        &self,
+        zip_path: &Path,
+        member: &str,
+        member_bytes: u64,
+        total_bytes: u64,
+    ) -> Option<AppError> {
+        if member_bytes > self.max_member_bytes {
+            return Some(AppError::InvalidInput(format!(
+                "ZIP member '{}' in {} exceeds the max_extracted_member_bytes limit of {} bytes",
+                member,
+                zip_path.display(),
+                self.max_member_bytes
+            )));
+        }
+        if total_bytes > self.max_total_bytes {
+            return Some(AppError::InvalidInput(format!(
+                "ZIP member '{}' pushes {} past the max_extracted_bytes limit of {} bytes",
+                member,
+                zip_path.display(),
+                self.max_total_bytes
+            )));
+        }
+        None
+    }
 }
 
 /// Synchronous function to extract a single ZIP file.
 /// This is used by rayon for parallel processing.
-fn extract_zip_sync(zip_path: &Path) -> AppResult<()> {
+///
+/// Entries are streamed from the `ZipFile` reader into the output file with `io::copy`
+/// through a [`COPY_BUFFER_SIZE`] buffer, so peak memory does not grow with the size of
+/// large XML members.
+/// Directory entries are skipped, as are entries without a safe `enclosed_name` (absolute
+/// paths or `..` components), which are logged and counted.
+/// Member names are made valid on Windows (see [`sanitize_member_path`]) on every platform,
+/// members that end up with the same path get a numeric suffix instead of overwriting each
+/// other, and paths over `MAX_PATH` get the `\\?\` prefix on Windows.
+/// Inner `.zip` members are expanded afterwards (see [`expand_nested_zips`]).
+///
+/// Extraction is aborted when any of the [`ExtractionLimits`] trips, and the partially
+/// extracted directory is removed so the next run retries the archive instead of
+/// skipping it.
+fn extract_zip_sync(zip_path: &Path, limits: ExtractionLimits) -> AppResult<()> {
     let zip_file_name = zip_path
         .file_stem()
         .and_then(|s| s.to_str())
@@ -226,8 +317,33 @@ fn extract_zip_sync(zip_path: &Path) -> AppResult<()> {
         return Ok(());
     }
 
+    let written_bytes = AtomicU64::new(0);
+    let result = extract_zip_into(zip_path, &extract_dir, limits, &written_bytes)
+        .and_then(|()| expand_nested_zips(&extract_dir, 1, limits, &written_bytes));
+    if result.is_err() {
+        if let Err(e) = fs::remove_dir_all(&extract_dir) {
+            warn!(
+                extract_dir = %extract_dir.display(),
+                error = %e,
+                "Failed to remove partially extracted directory"
+            );
+        }
+    }
+    result
+}
+
+/// Extracts `zip_path` into `extract_dir`, counting written bytes in `written_bytes`.
+///
+/// The counter is shared with nested archives of the same period so `max_total_bytes`
+/// bounds the combined output.
+fn extract_zip_into(
+    zip_path: &Path,
+    extract_dir: &Path,
+    limits: ExtractionLimits,
+    written_bytes: &AtomicU64,
+) -> AppResult<()> {
     // Create extraction directory
-    std::fs::create_dir_all(&extract_dir).map_err(|e| {
+    std::fs::create_dir_all(extract_dir).map_err(|e| {
         AppError::IoError(format!(
             "Failed to create extraction directory {}: {}",
             extract_dir.display(),
@@ -252,8 +368,23 @@ fn extract_zip_sync(zip_path: &Path) -> AppResult<()> {
         ))
     })?;
 
+    if archive.len() > limits.max_entries {
+        return Err(AppError::InvalidInput(format!(
+            "ZIP archive {} has {} members, exceeding the max_zip_entries limit of {}",
+            zip_path.display(),
+            archive.len(),
+            limits.max_entries
+        )));
+    }
+
     let mut entries = Vec::with_capacity(archive.len());
     let mut created_dirs = HashSet::new();
+    let already_written = written_bytes.load(Ordering::Relaxed);
+    let mut declared_bytes = 0u64;
+    let mut unsafe_entries = 0usize;
+    let mut sanitized_entries = 0usize;
+    let mut renamed_duplicates = 0usize;
+    let mut used_paths = HashSet::new();
 
     for i in 0..archive.len() {
         let file = archive.by_index(i).map_err(|e| {
@@ -265,15 +396,57 @@ fn extract_zip_sync(zip_path: &Path) -> AppResult<()> {
             ))
         })?;
 
-        let out_path = match file.enclosed_name() {
-            Some(path) => extract_dir.join(path),
-            None => continue,
+        let (relative_path, sanitized) = match file.enclosed_name() {
+            Some(path) => sanitize_member_path(path),
+            None => {
+                unsafe_entries += 1;
+                warn!(
+                    zip_file = %zip_path.display(),
+                    entry = file.name(),
+                    "Rejecting ZIP entry with unsafe path"
+                );
+                continue;
+            }
         };
 
         if file.name().ends_with('/') {
             continue;
         }
 
+        if sanitized {
+            sanitized_entries += 1;
+            debug!(
+                zip_file = %zip_path.display(),
+                entry = file.name(),
+                path = %relative_path.display(),
+                "Sanitized ZIP entry name"
+            );
+        }
+
+        // Members whose names became equal (after sanitizing, duplicated in the archive, or
+        // differing only in case, which NTFS and APFS treat as the same file) get a numeric
+        // suffix instead of overwriting each other.
+        let mut out_path = extract_dir.join(relative_path);
+        if used_paths.contains(&collision_key(&out_path)) {
+            out_path = (1..)
+                .map(|n| with_numeric_suffix(&out_path, n))
+                .find(|candidate| !used_paths.contains(&collision_key(candidate)))
+                .expect("an unused suffix exists");
+            renamed_duplicates += 1;
+        }
+        used_paths.insert(collision_key(&out_path));
+        let out_path = windows_long_path(&out_path);
+
+        declared_bytes = declared_bytes.saturating_add(file.size());
+        if let Some(err) = limits.exceeded(
+            zip_path,
+            file.name(),
+            file.size(),
+            already_written.saturating_add(declared_bytes),
+        ) {
+            return Err(err);
+        }
+
         if let Some(parent) = out_path.parent() {
             if created_dirs.insert(parent.to_path_buf()) {
                 std::fs::create_dir_all(parent).map_err(|e| {
@@ -286,15 +459,41 @@ fn extract_zip_sync(zip_path: &Path) -> AppResult<()> {
             }
         }
 
-        entries.push((i, out_path));
+        entries.push((i, file.name().to_string(), out_path));
     }
 
     drop(archive);
 
+    if unsafe_entries > 0 {
+        warn!(
+            zip_file = %zip_path.display(),
+            skipped_unsafe = unsafe_entries,
+            "Skipped ZIP entries with unsafe paths"
+        );
+    }
+    if sanitized_entries > 0 || renamed_duplicates > 0 {
+        warn!(
+            zip_file = %zip_path.display(),
+            sanitized = sanitized_entries,
+            renamed_duplicates,
+            "Renamed ZIP entries with names invalid on Windows or clashing with another entry"
+        );
+    }
+
+    let compressed_bytes = fs::metadata(zip_path).map(|m| m.len()).unwrap_or(0);
+    if compressed_bytes > 0 && declared_bytes / compressed_bytes > SUSPICIOUS_COMPRESSION_RATIO {
+        warn!(
+            zip_file = %zip_path.display(),
+            compressed_bytes,
+            declared_bytes,
+            "ZIP archive has a suspiciously high compression ratio"
+        );
+    }
+
     let zip_path_arc = Arc::new(zip_path.to_path_buf());
     entries
         .par_iter()
-        .map(|(index, out_path)| {
+        .map(|(index, name, out_path)| {
             let zip_path = zip_path_arc.clone();
             let file = File::open(&*zip_path).map_err(|e| {
                 AppError::IoError(format!(
@@ -329,21 +528,42 @@ fn extract_zip_sync(zip_path: &Path) -> AppResult<()> {
                 ))
             })?;
 
-            let mut writer = BufWriter::with_capacity(32 * 1024, out_file);
+            let mut writer = BufWriter::with_capacity(
+                COPY_BUFFER_SIZE,
+                LimitedWriter {
+                    inner: out_file,
+                    written: written_bytes,
+                    limit: limits.max_total_bytes,
+                    member_written: 0,
+                    member_limit: limits.max_member_bytes,
+                },
+            );
+            let limit_error = |writer: &BufWriter<LimitedWriter<File>>| {
+                limits.exceeded(
+                    &zip_path,
+                    name,
+                    writer.get_ref().member_written,
+                    written_bytes.load(Ordering::Relaxed),
+                )
+            };
             copy(&mut file, &mut writer).map_err(|e| {
-                AppError::IoError(format!(
-                    "Failed to copy file from ZIP {} to {}: {}",
-                    zip_path.display(),
-                    out_path.display(),
-                    e
-                ))
+                limit_error(&writer).unwrap_or_else(|| {
+                    AppError::IoError(format!(
+                        "Failed to copy file from ZIP {} to {}: {}",
+                        zip_path.display(),
+                        out_path.display(),
+                        e
+                    ))
+                })
             })?;
             writer.flush().map_err(|e| {
-                AppError::IoError(format!(
-                    "Failed to flush file {}: {}",
-                    out_path.display(),
-                    e
-                ))
+                limit_error(&writer).unwrap_or_else(|| {
+                    AppError::IoError(format!(
+                        "Failed to flush file {}: {}",
+                        out_path.display(),
+                        e
+                    ))
+                })
             })?;
 
             Ok(())
@@ -353,6 +573,231 @@ fn extract_zip_sync(zip_path: &Path) -> AppResult<()> {
     Ok(())
 }
 
+/// Characters Windows does not allow in file names, besides control characters.
+const WINDOWS_INVALID_CHARS: [char; 9] = ['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// Device names Windows reserves in every directory, with or without an extension.
+const WINDOWS_RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Makes one path component of a ZIP member name valid on Windows (NTFS).
+///
+/// Invalid characters (`<>:"/\|?*` and control characters) become `_`, trailing dots and
+/// spaces are trimmed, and reserved device names (`CON`, `NUL`, `COM1`, ...) get a `_`
+/// suffix on their stem. A component left empty becomes `_`. Valid components are
+/// returned borrowed.
+fn sanitize_component(component: &str) -> Cow<'_, str> {
+    let mut sanitized: String = component
+        .chars()
+        .map(|c| {
+            if c.is_control() || WINDOWS_INVALID_CHARS.contains(&c) {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    sanitized.truncate(sanitized.trim_end_matches(['.', ' ']).len());
+    if sanitized.is_empty() {
+        sanitized.push('_');
+    }
+    let stem = sanitized.split('.').next().unwrap_or_default();
+    if WINDOWS_RESERVED_NAMES
+        .iter()
+        .any(|reserved| stem.eq_ignore_ascii_case(reserved))
+    {
+        sanitized.insert(stem.len(), '_');
+    }
+    if sanitized == component {
+        Cow::Borrowed(component)
+    } else {
+        Cow::Owned(sanitized)
+    }
+}
+
+/// Sanitizes every component of the relative member path `path` (see
+/// [`sanitize_component`]) and returns the result with whether anything changed.
+///
+/// The same names are produced on every platform, so an archive extracts identically on
+/// Linux and Windows.
+fn sanitize_member_path(path: &Path) -> (PathBuf, bool) {
+    let mut sanitized = PathBuf::new();
+    let mut changed = false;
+    for component in path.components() {
+        match component {
+            std::path::Component::Normal(name) => {
+                let name = name.to_string_lossy();
+                let clean = sanitize_component(&name);
+                changed |= matches!(clean, Cow::Owned(_)) || matches!(name, Cow::Owned(_));
+                sanitized.push(clean.as_ref());
+            }
+            other => sanitized.push(other.as_os_str()),
+        }
+    }
+    (sanitized, changed)
+}
+
+/// Case-insensitive key under which extracted paths are checked for collisions.
+fn collision_key(path: &Path) -> String {
+    path.to_string_lossy().to_lowercase()
+}
+
+/// `path` with `_{n}` appended to its file stem (`a/feed.atom` -> `a/feed_2.atom`).
+fn with_numeric_suffix(path: &Path, n: usize) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default();
+    let name = match path.extension() {
+        Some(extension) => format!("{stem}_{n}.{}", extension.to_string_lossy()),
+        None => format!("{stem}_{n}"),
+    };
+    path.with_file_name(name)
+}
+
+/// Longest path Windows accepts without the `\\?\` prefix.
+#[cfg(windows)]
+const WINDOWS_MAX_PATH: usize = 260;
+
+/// Prefixes paths longer than `MAX_PATH` with `\\?\` (or `\\?\UNC\` for network paths)
+/// after making them absolute, so Windows opens them without the length limit.
+#[cfg(windows)]
+fn windows_long_path(path: &Path) -> PathBuf {
+    let Ok(absolute) = std::path::absolute(path) else {
+        return path.to_path_buf();
+    };
+    let raw = absolute.to_string_lossy();
+    if raw.len() < WINDOWS_MAX_PATH || raw.starts_with(r"\\?\") {
+        return path.to_path_buf();
+    }
+    match raw.strip_prefix(r"\\") {
+        Some(unc) => PathBuf::from(format!(r"\\?\UNC\{unc}")),
+        None => PathBuf::from(format!(r"\\?\{raw}")),
+    }
+}
+
+/// Paths need no prefix outside Windows.
+#[cfg(not(windows))]
+fn windows_long_path(path: &Path) -> PathBuf {
+    path.to_path_buf()
+}
+
+/// Deepest level of nested archives expanded inside a period (the period ZIP is level 0).
+const MAX_NESTED_ZIP_DEPTH: u32 = 2;
+
+/// Expands `.zip` members found under `dir` (e.g. one archive per day inside a monthly ZIP).
+///
+/// Each inner archive is extracted next to itself into a directory named after its stem and
+/// then removed, so `find_xmls` sees the XML files. Inner directories that already exist are
+/// skipped, archives deeper than [`MAX_NESTED_ZIP_DEPTH`] are left untouched with a warning,
+/// and failures are collected and reported together once every archive has been tried.
+fn expand_nested_zips(
+    dir: &Path,
+    depth: u32,
+    limits: ExtractionLimits,
+    written_bytes: &AtomicU64,
+) -> AppResult<()> {
+    let nested_zips: Vec<PathBuf> = walkdir::WalkDir::new(dir)
+        .into_iter()
+        .flatten()
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| entry.into_path())
+        .filter(|path| {
+            path.extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| e.eq_ignore_ascii_case("zip"))
+        })
+        .collect();
+
+    if nested_zips.is_empty() {
+        return Ok(());
+    }
+    if depth > MAX_NESTED_ZIP_DEPTH {
+        warn!(
+            dir = %dir.display(),
+            archives = nested_zips.len(),
+            max_depth = MAX_NESTED_ZIP_DEPTH,
+            "Nested ZIP depth limit reached, leaving inner archives unextracted"
+        );
+        return Ok(());
+    }
+
+    let mut errors = Vec::new();
+    for inner_zip in nested_zips {
+        let target = inner_zip.with_extension("");
+        if target.exists() {
+            debug!(
+                zip_file = %inner_zip.display(),
+                "Skipping nested ZIP, directory already exists"
+            );
+            continue;
+        }
+        let result = extract_zip_into(&inner_zip, &target, limits, written_bytes)
+            .and_then(|()| fs::remove_file(&inner_zip).map_err(AppError::from))
+            .and_then(|()| expand_nested_zips(&target, depth + 1, limits, written_bytes));
+        if let Err(e) = result {
+            warn!(
+                zip_file = %inner_zip.display(),
+                error = %e,
+                "Failed to extract nested ZIP file"
+            );
+            errors.push(format!("{}: {}", inner_zip.display(), e));
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(AppError::IoError(format!(
+            "Failed to extract {} nested ZIP file(s): {}",
+            errors.len(),
+            errors.join("; ")
+        )));
+    }
+    Ok(())
+}
+
+/// Size of the write buffer used when streaming a ZIP entry to disk.
+const COPY_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Declared uncompressed/compressed ratio above which an archive is logged as suspicious.
+/// XML feeds typically compress 10-30x; zip bombs reach ratios in the thousands.
+const SUSPICIOUS_COMPRESSION_RATIO: u64 = 200;
+
+/// Writer for one ZIP member that fails once the member exceeds `member_limit` or the
+/// counter shared by all members of the period exceeds `limit`.
+struct LimitedWriter<'a, W> {
+    inner: W,
+    written: &'a AtomicU64,
+    limit: u64,
+    member_written: u64,
+    member_limit: u64,
+}
+
+impl<W: Write> Write for LimitedWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.member_written += buf.len() as u64;
+        if self.member_written > self.member_limit {
+            return Err(std::io::Error::other("member size limit exceeded"));
+        }
+        let total = self.written.fetch_add(buf.len() as u64, Ordering::Relaxed) + buf.len() as u64;
+        if total > self.limit {
+            return Err(std::io::Error::other("extraction size limit exceeded"));
+        }
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Whether `period` already has an extraction directory in `extract_dir`; such periods are
+/// not extracted again.
+pub(crate) fn is_extracted(extract_dir: &Path, period: &str) -> bool {
+    extract_dir.join(period).exists()
+}
+
 fn extracted_dir_for_zip(zip_path: &Path) -> Option<PathBuf> {
     let parent = zip_path.parent()?;
     let stem = zip_path.file_stem()?;
@@ -380,6 +825,22 @@ mod tests {
     use std::fs;
     use tempfile::TempDir;
 
+    const UNLIMITED: ExtractionLimits = ExtractionLimits {
+        max_total_bytes: u64::MAX,
+        max_member_bytes: u64::MAX,
+        max_entries: usize::MAX,
+    };
+
+    #[test]
+    fn is_extracted_checks_period_directory() {
+        let tmp = TempDir::new().unwrap();
+        fs::create_dir_all(tmp.path().join("202401")).unwrap();
+        fs::write(tmp.path().join("202402.zip"), b"PK").unwrap();
+
+        assert!(is_extracted(tmp.path(), "202401"));
+        assert!(!is_extracted(tmp.path(), "202402"));
+    }
+
     #[test]
     fn extracted_dir_for_zip_returns_parent_stem() {
         let zip = PathBuf::from("/tmp/data/202401.zip");
@@ -400,4 +861,394 @@ mod tests {
         let size = directory_size(&base);
         assert_eq!(size, 30);
     }
+
+    #[test]
+    fn extract_zip_sync_streams_large_entries_and_skips_unsafe_paths() {
+        use zip::write::FileOptions;
+
+        let tmp = TempDir::new().unwrap();
+        let zip_path = tmp.path().join("202401.zip");
+        // Larger than the 64 KiB copy buffer so the entry is written in several chunks
+        let content: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        {
+            let mut writer = zip::ZipWriter::new(fs::File::create(&zip_path).unwrap());
+            writer
+                .start_file("feed/big.atom", FileOptions::default())
+                .unwrap();
+            writer.write_all(&content).unwrap();
+            writer
+                .start_file("../escape.atom", FileOptions::default())
+                .unwrap();
+            writer.write_all(b"nope").unwrap();
+            writer.finish().unwrap();
+        }
+
+        extract_zip_sync(&zip_path, UNLIMITED).unwrap();
+
+        let extracted = fs::read(tmp.path().join("202401/feed/big.atom")).unwrap();
+        assert_eq!(extracted, content);
+        assert!(!tmp.path().join("escape.atom").exists());
+    }
+
+    #[test]
+    fn sanitize_component_keeps_valid_names() {
+        for name in [
+            "202401.atom",
+            "feed",
+            "ficheros_1.xml",
+            ".hidden",
+            "año 2024.xml",
+        ] {
+            assert!(matches!(sanitize_component(name), Cow::Borrowed(n) if n == name));
+        }
+    }
+
+    #[test]
+    fn sanitize_component_replaces_invalid_characters() {
+        assert_eq!(sanitize_component("a<b>c.xml"), "a_b_c.xml");
+        assert_eq!(sanitize_component("a:b.xml"), "a_b.xml");
+        assert_eq!(sanitize_component("\"q\"|p?*.xml"), "_q__p__.xml");
+        assert_eq!(sanitize_component(r"a\b/c"), "a_b_c");
+        assert_eq!(sanitize_component("tab\there\u{1}.xml"), "tab_here_.xml");
+    }
+
+    #[test]
+    fn sanitize_component_trims_trailing_dots_and_spaces() {
+        assert_eq!(sanitize_component("feed.atom. "), "feed.atom");
+        assert_eq!(sanitize_component("dir..."), "dir");
+        assert_eq!(sanitize_component(" lead"), " lead");
+        assert_eq!(sanitize_component(". ."), "_");
+        assert_eq!(sanitize_component(""), "_");
+    }
+
+    #[test]
+    fn sanitize_component_suffixes_reserved_device_names() {
+        assert_eq!(sanitize_component("CON"), "CON_");
+        assert_eq!(sanitize_component("nul.xml"), "nul_.xml");
+        assert_eq!(sanitize_component("Com1.tar.gz"), "Com1_.tar.gz");
+        assert_eq!(sanitize_component("lpt9."), "lpt9_");
+        assert_eq!(sanitize_component("CONSOLE.xml"), "CONSOLE.xml");
+        assert_eq!(sanitize_component("COM10"), "COM10");
+        assert_eq!(sanitize_component("xaux"), "xaux");
+    }
+
+    #[test]
+    fn sanitize_member_path_rebuilds_every_component() {
+        assert_eq!(
+            sanitize_member_path(Path::new("feed/202401.atom")),
+            (PathBuf::from("feed/202401.atom"), false)
+        );
+        assert_eq!(
+            sanitize_member_path(Path::new("a:b /aux/c?.xml")),
+            (PathBuf::from("a_b/aux_/c_.xml"), true)
+        );
+    }
+
+    #[test]
+    fn with_numeric_suffix_appends_to_stem() {
+        assert_eq!(
+            with_numeric_suffix(Path::new("dir/feed.atom"), 2),
+            PathBuf::from("dir/feed_2.atom")
+        );
+        assert_eq!(
+            with_numeric_suffix(Path::new("dir/feed"), 1),
+            PathBuf::from("dir/feed_1")
+        );
+    }
+
+    #[test]
+    fn extract_zip_sync_sanitizes_names_and_suffixes_collisions() {
+        use zip::write::FileOptions;
+
+        let tmp = TempDir::new().unwrap();
+        let zip_path = tmp.path().join("202401.zip");
+        {
+            let mut writer = zip::ZipWriter::new(fs::File::create(&zip_path).unwrap());
+            for (name, content) in [
+                ("feed/a_b.atom", b"first".as_slice()),
+                ("feed/a:b.atom", b"second".as_slice()),
+                ("feed/a?b.atom. ", b"third".as_slice()),
+                ("con.atom", b"device".as_slice()),
+            ] {
+                writer.start_file(name, FileOptions::default()).unwrap();
+                writer.write_all(content).unwrap();
+            }
+            writer.finish().unwrap();
+        }
+
+        extract_zip_sync(&zip_path, UNLIMITED).unwrap();
+
+        let dir = tmp.path().join("202401");
+        assert_eq!(fs::read(dir.join("feed/a_b.atom")).unwrap(), b"first");
+        assert_eq!(fs::read(dir.join("feed/a_b_1.atom")).unwrap(), b"second");
+        assert_eq!(fs::read(dir.join("feed/a_b_2.atom")).unwrap(), b"third");
+        assert_eq!(fs::read(dir.join("con_.atom")).unwrap(), b"device");
+    }
+
+    #[test]
+    fn extract_zip_sync_suffixes_names_differing_only_in_case() {
+        let tmp = TempDir::new().unwrap();
+        let zip_path = tmp.path().join("202401.zip");
+        create_test_zip(
+            &zip_path,
+            &[
+                ("feed/Data.atom", b"upper"),
+                ("feed/data.atom", b"lower"),
+                ("FEED/DATA.atom", b"shout"),
+            ],
+        );
+
+        extract_zip_sync(&zip_path, UNLIMITED).unwrap();
+
+        let dir = tmp.path().join("202401");
+        assert_eq!(fs::read(dir.join("feed/Data.atom")).unwrap(), b"upper");
+        assert_eq!(fs::read(dir.join("feed/data_1.atom")).unwrap(), b"lower");
+        assert_eq!(fs::read(dir.join("FEED/DATA_2.atom")).unwrap(), b"shout");
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn windows_long_path_prefixes_paths_over_max_path() {
+        let short = PathBuf::from(r"C:\data\202401\feed.atom");
+        assert_eq!(windows_long_path(&short), short);
+
+        let long = PathBuf::from(format!(r"C:\data\{}\feed.atom", "d".repeat(300)));
+        let prefixed = windows_long_path(&long);
+        assert!(prefixed.to_string_lossy().starts_with(r"\\?\C:\data\"));
+        assert_eq!(windows_long_path(&prefixed), prefixed);
+
+        let unc = PathBuf::from(format!(r"\\server\share\{}", "d".repeat(300)));
+        assert!(windows_long_path(&unc)
+            .to_string_lossy()
+            .starts_with(r"\\?\UNC\server\share\"));
+    }
+
+    #[test]
+    fn extract_zip_sync_rejects_archives_over_size_limit() {
+        use zip::write::FileOptions;
+
+        let tmp = TempDir::new().unwrap();
+        let zip_path = tmp.path().join("202402.zip");
+        {
+            let mut writer = zip::ZipWriter::new(fs::File::create(&zip_path).unwrap());
+            for name in ["a.atom", "b.atom"] {
+                writer.start_file(name, FileOptions::default()).unwrap();
+                writer.write_all(&[b'x'; 600]).unwrap();
+            }
+            writer.finish().unwrap();
+        }
+
+        let limits = ExtractionLimits {
+            max_total_bytes: 1000,
+            ..UNLIMITED
+        };
+        let err = extract_zip_sync(&zip_path, limits).unwrap_err();
+        assert!(
+            matches!(err, AppError::InvalidInput(msg) if msg.contains("max_extracted_bytes") && msg.contains("b.atom"))
+        );
+    }
+
+    #[test]
+    fn extract_zip_sync_aborts_on_oversized_member_and_cleans_up() {
+        let tmp = TempDir::new().unwrap();
+        let zip_path = tmp.path().join("202403.zip");
+        // 4 MiB of zeros deflates to a few KiB
+        create_test_zip(
+            &zip_path,
+            &[
+                ("small.atom", b"<feed/>"),
+                ("bomb.atom", &vec![0u8; 4 * 1024 * 1024]),
+            ],
+        );
+        assert!(fs::metadata(&zip_path).unwrap().len() < 64 * 1024);
+
+        let limits = ExtractionLimits {
+            max_member_bytes: 1024 * 1024,
+            ..UNLIMITED
+        };
+        let err = extract_zip_sync(&zip_path, limits).unwrap_err();
+
+        assert!(matches!(
+            &err,
+            AppError::InvalidInput(msg)
+                if msg.contains("bomb.atom") && msg.contains("max_extracted_member_bytes")
+        ));
+        assert!(!tmp.path().join("202403").exists());
+    }
+
+    #[test]
+    fn extract_zip_sync_rejects_archives_over_entry_limit() {
+        let tmp = TempDir::new().unwrap();
+        let zip_path = tmp.path().join("202404.zip");
+        create_test_zip(
+            &zip_path,
+            &[
+                ("a.atom", b"<feed/>"),
+                ("b.atom", b"<feed/>"),
+                ("c.atom", b"<feed/>"),
+            ],
+        );
+
+        let limits = ExtractionLimits {
+            max_entries: 2,
+            ..UNLIMITED
+        };
+        let err = extract_zip_sync(&zip_path, limits).unwrap_err();
+
+        assert!(matches!(&err, AppError::InvalidInput(msg) if msg.contains("max_zip_entries")));
+        assert!(!tmp.path().join("202404").exists());
+    }
+
+    #[tokio::test]
+    async fn extract_all_zips_continues_past_a_corrupt_archive() {
+        use zip::write::FileOptions;
+
+        let tmp = TempDir::new().unwrap();
+        let config = crate::config::ResolvedConfig {
+            download_dir_pt: tmp.path().to_path_buf(),
+            concurrent_extractions: 2,
+            ..Default::default()
+        };
+        for period in ["202401", "202403"] {
+            let mut writer = zip::ZipWriter::new(
+                fs::File::create(tmp.path().join(format!("{period}.zip"))).unwrap(),
+            );
+            writer
+                .start_file("feed.atom", FileOptions::default())
+                .unwrap();
+            writer.write_all(b"<feed/>").unwrap();
+            writer.finish().unwrap();
+        }
+        fs::write(tmp.path().join("202402.zip"), b"not a zip").unwrap();
+        let links: BTreeMap<String, String> = ["202401", "202402", "202403"]
+            .into_iter()
+            .map(|p| (p.to_string(), String::new()))
+            .collect();
+
+        let err = extract_all_zips(&links, &ProcurementType::PublicTenders, &config)
+            .await
+            .unwrap_err();
+
+        assert!(
+            matches!(&err, AppError::IoError(msg) if msg.contains("1 ZIP file(s)") && msg.contains("202402.zip"))
+        );
+        assert!(tmp.path().join("202401/feed.atom").exists());
+        assert!(tmp.path().join("202403/feed.atom").exists());
+    }
+
+    /// Writes a ZIP archive at `path` with the given `(name, contents)` members.
+    fn create_test_zip(path: &Path, members: &[(&str, &[u8])]) {
+        use zip::write::FileOptions;
+
+        let mut writer = zip::ZipWriter::new(fs::File::create(path).unwrap());
+        for (name, contents) in members {
+            writer.start_file(*name, FileOptions::default()).unwrap();
+            writer.write_all(contents).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    #[tokio::test]
+    async fn nested_zips_are_expanded_and_parseable() {
+        let tmp = TempDir::new().unwrap();
+        let config = crate::config::ResolvedConfig {
+            download_dir_pt: tmp.path().join("tmp"),
+            parquet_dir_pt: tmp.path().join("parquet"),
+            ..Default::default()
+        };
+        fs::create_dir_all(tmp.path().join("tmp")).unwrap();
+
+        let day_zip = tmp.path().join("20120101.zip");
+        create_test_zip(
+            &day_zip,
+            &[(
+                "licitaciones.atom",
+                b"<feed><entry><id>nested-1</id></entry></feed>",
+            )],
+        );
+        let day_bytes = fs::read(&day_zip).unwrap();
+        create_test_zip(
+            &tmp.path().join("tmp/2012.zip"),
+            &[("20120101.zip", &day_bytes)],
+        );
+        let mut links = BTreeMap::new();
+        links.insert("2012".to_string(), String::new());
+
+        extract_all_zips(&links, &ProcurementType::PublicTenders, &config)
+            .await
+            .unwrap();
+        assert!(tmp
+            .path()
+            .join("tmp/2012/20120101/licitaciones.atom")
+            .exists());
+        assert!(!tmp.path().join("tmp/2012/20120101.zip").exists());
+
+        let stats = crate::parser::parse_xmls(
+            &links,
+            &ProcurementType::PublicTenders,
+            config.batch_size,
+            &config,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(stats.entries, 1);
+    }
+
+    #[test]
+    fn expand_nested_zips_stops_at_depth_limit() {
+        let tmp = TempDir::new().unwrap();
+        let level3 = tmp.path().join("level3.zip");
+        create_test_zip(&level3, &[("deep.atom", b"<feed/>")]);
+        let level2 = tmp.path().join("level2.zip");
+        create_test_zip(&level2, &[("level3.zip", &fs::read(&level3).unwrap())]);
+        let period = tmp.path().join("period");
+        fs::create_dir_all(&period).unwrap();
+        create_test_zip(
+            &period.join("level1.zip"),
+            &[("level2.zip", &fs::read(&level2).unwrap())],
+        );
+
+        expand_nested_zips(&period, 1, UNLIMITED, &AtomicU64::new(0)).unwrap();
+
+        assert!(period.join("level1/level2/level3.zip").exists());
+        assert!(!period.join("level1/level2/level3").exists());
+    }
+
+    #[test]
+    fn limited_writer_fails_once_shared_total_exceeds_limit() {
+        let written = AtomicU64::new(0);
+        let mut first = LimitedWriter {
+            inner: Vec::new(),
+            written: &written,
+            limit: 10,
+            member_written: 0,
+            member_limit: u64::MAX,
+        };
+        first.write_all(&[0; 6]).unwrap();
+        let mut second = LimitedWriter {
+            inner: Vec::new(),
+            written: &written,
+            limit: 10,
+            member_written: 0,
+            member_limit: u64::MAX,
+        };
+        assert!(second.write_all(&[0; 6]).is_err());
+    }
+
+    #[test]
+    fn limited_writer_fails_once_member_exceeds_limit() {
+        // Covers archives whose declared sizes understate the real member size
+        let written = AtomicU64::new(0);
+        let mut writer = LimitedWriter {
+            inner: Vec::new(),
+            written: &written,
+            limit: u64::MAX,
+            member_written: 0,
+            member_limit: 8,
+        };
+        writer.write_all(&[0; 8]).unwrap();
+        assert!(writer.write_all(&[0; 1]).is_err());
+        assert_eq!(writer.inner.len(), 8);
+    }
 }
@@ -7,18 +7,41 @@
 //! - [`downloader`] - Fetches ZIP file links and downloads archives from Spanish procurement data sources
 //! - [`extractor`] - Extracts ZIP files containing XML/Atom feeds
 //! - [`parser`] - Parses XML/Atom files and converts them to Parquet format (see [`models::Entry`] for the output schema documentation)
+//! - [`pipeline`] - Programmatic entry point running the whole workflow ([`pipeline::run`])
 //! - [`cli`] - Command-line interface for orchestrating the download and processing workflow
 //! - [`models`] - Data structures representing procurement entries and types (each `Entry` mirrors the Parquet output schema)
 //! - [`errors`] - Error types used throughout the application
+//! - [`cpv`] - CPV code descriptions used by `--enrich-cpv`
+//! - [`converter`] - Converts Parquet output to CSV or JSON Lines with flattened columns (`convert` subcommand)
 //! - [`config`] - Configuration types and helpers for pipeline defaults and TOML loading
+//! - [`manifest`] - Per-period run manifest written by `--manifest`
+//! - [`state`] - Run state file used by `--since-last-run` to resume after the last completed period
+//! - [`transformer`] - Post-processing hooks applied to parsed entries before they are written
+//! - [`telemetry`] - Tracing subscriber setup, with optional OpenTelemetry export (`otel` feature)
+//! - `metrics` - Prometheus counters served on `/metrics` (`metrics` feature)
+//! - `query` - SQL over the Parquet output with an embedded DuckDB (`duckdb` feature)
 //!
-//! For detailed usage, examples, and the full output schema (13–14 Parquet columns), see the [repository README](https://github.com/Alvaro2c/sppd-cli).
+//! For detailed usage, examples, and the full output schema (18–20 Parquet columns), see the [repository README](https://github.com/Alvaro2c/sppd-cli).
 
 pub mod cli;
 pub mod config;
+pub mod converter;
+pub mod cpv;
 pub mod downloader;
 pub mod errors;
 pub mod extractor;
+pub mod manifest;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 pub mod models;
 pub mod parser;
+pub mod pipeline;
+#[cfg(feature = "duckdb")]
+pub mod query;
+pub mod state;
+pub mod telemetry;
+pub mod transformer;
+mod ui;
 mod utils;
+
+pub use downloader::parse_zip_links;
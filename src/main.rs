@@ -1,19 +1,17 @@
 use sppd_cli::cli;
-use sppd_cli::errors::AppResult;
-use tracing::info_span;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+use std::process::ExitCode;
 
 #[tokio::main]
-async fn main() -> AppResult<()> {
-    // Initialize tracing subscriber with environment filter
+async fn main() -> ExitCode {
+    // Tracing is initialized by `cli::cli()` once arguments are parsed, so that
+    // `--log-format`, `--log-file`, and `--otel-endpoint` can configure it.
     // Default to INFO level, but can be overridden with RUST_LOG env var
-    tracing_subscriber::registry()
-        .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
-        .with(tracing_subscriber::fmt::layer())
-        .init();
-
-    let _span = info_span!("main").entered();
-
-    cli::cli().await?;
-    Ok(())
+    match cli::cli().await {
+        Ok(()) => ExitCode::SUCCESS,
+        // Exit code 2 marks a partial failure (`--keep-going`), 1 any other error
+        Err(e) => {
+            eprintln!("Error: {e}");
+            ExitCode::from(e.exit_code())
+        }
+    }
 }
@@ -0,0 +1,177 @@
+use crate::errors::{AppError, AppResult};
+use crate::models::{ParseStats, ProcurementType};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File name of the manifest written to the Parquet directory by `--manifest`.
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// What one run did for one period.
+///
+/// Fields of stages that did not run (or found nothing for the period) are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeriodManifest {
+    /// Period (`YYYY` or `YYYYMM`)
+    pub period: String,
+    /// URL the ZIP archive was downloaded from (`None` for parse-only runs)
+    pub source_url: Option<String>,
+    /// Size of the ZIP archive in the download directory in bytes
+    pub zip_bytes: Option<u64>,
+    /// XML/Atom files found in the extracted period directory
+    pub xml_files: Option<usize>,
+    /// Entries written
+    pub entries: Option<usize>,
+    /// Output files written, lot results included
+    pub output_paths: Vec<PathBuf>,
+    /// Wall-clock time spent parsing the period in milliseconds
+    pub parse_elapsed_ms: Option<u64>,
+}
+
+/// Machine-readable record of one run, one [`PeriodManifest`] per selected period.
+///
+/// Serialized as pretty-printed JSON with periods in ascending order, so manifests of two
+/// runs can be compared with a plain diff.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunManifest {
+    /// Human-readable procurement type (see [`ProcurementType::display_name`])
+    pub procurement_type: String,
+    pub periods: Vec<PeriodManifest>,
+    /// Wall-clock time of the whole run in milliseconds
+    pub total_elapsed_ms: u64,
+}
+
+impl RunManifest {
+    /// Creates an empty manifest for the selected periods.
+    pub fn new<'a>(
+        proc_type: &ProcurementType,
+        periods: impl IntoIterator<Item = &'a String>,
+    ) -> Self {
+        Self {
+            procurement_type: proc_type.display_name().to_string(),
+            periods: periods
+                .into_iter()
+                .map(|period| PeriodManifest {
+                    period: period.clone(),
+                    ..PeriodManifest::default()
+                })
+                .collect(),
+            total_elapsed_ms: 0,
+        }
+    }
+
+    /// Records the download URL of each period from the period-to-URL `links`.
+    pub fn record_sources(&mut self, links: &BTreeMap<String, String>) {
+        for period in &mut self.periods {
+            period.source_url = links.get(&period.period).cloned();
+        }
+    }
+
+    /// Records the size of each period's `{period}.zip` found in `download_dir`.
+    ///
+    /// Must run before cleanup, which deletes the archives.
+    pub fn record_zip_sizes(&mut self, download_dir: &Path) {
+        for period in &mut self.periods {
+            period.zip_bytes = fs::metadata(download_dir.join(format!("{}.zip", period.period)))
+                .ok()
+                .map(|metadata| metadata.len());
+        }
+    }
+
+    /// Records archive sizes measured earlier, e.g. per period before its own cleanup.
+    /// Periods missing from `sizes` keep an unknown size.
+    pub fn record_zip_bytes(&mut self, sizes: &BTreeMap<String, u64>) {
+        for period in &mut self.periods {
+            period.zip_bytes = sizes.get(&period.period).copied();
+        }
+    }
+
+    /// Records the per-period details of the parsing stage.
+    pub fn record_parse(&mut self, stats: &ParseStats) {
+        for period in &mut self.periods {
+            if let Some(parsed) = stats.periods.get(&period.period) {
+                period.xml_files = Some(parsed.xml_files);
+                period.entries = Some(parsed.entries);
+                period.output_paths = parsed.output_paths.clone();
+                period.parse_elapsed_ms = Some(parsed.elapsed_ms);
+            }
+        }
+    }
+
+    /// Writes the manifest atomically (temp file + rename), creating parent directories.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IoError` if the file cannot be written and `ParseError` if serialization fails.
+    pub fn save(&self, path: &Path) -> AppResult<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| AppError::ParseError(format!("Failed to serialize manifest: {e}")))?;
+        let tmp_path = path.with_extension("json.part");
+        fs::write(&tmp_path, json)?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PeriodParseStats;
+    use tempfile::TempDir;
+
+    #[test]
+    fn manifest_combines_stage_details_per_period() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("202401.zip"), b"zip!").unwrap();
+        let links = BTreeMap::from([
+            (
+                "202401".to_string(),
+                "https://example.com/a.zip".to_string(),
+            ),
+            (
+                "202402".to_string(),
+                "https://example.com/b.zip".to_string(),
+            ),
+        ]);
+        let mut stats = ParseStats::default();
+        stats.periods.insert(
+            "202401".to_string(),
+            PeriodParseStats {
+                xml_files: 2,
+                entries: 5,
+                output_paths: vec![PathBuf::from("parquet/202401.parquet")],
+                elapsed_ms: 7,
+            },
+        );
+
+        let mut manifest = RunManifest::new(&ProcurementType::PublicTenders, links.keys());
+        manifest.record_sources(&links);
+        manifest.record_zip_sizes(tmp.path());
+        manifest.record_parse(&stats);
+
+        let january = &manifest.periods[0];
+        assert_eq!(
+            january.source_url.as_deref(),
+            Some("https://example.com/a.zip")
+        );
+        assert_eq!(january.zip_bytes, Some(4));
+        assert_eq!(january.xml_files, Some(2));
+        assert_eq!(january.entries, Some(5));
+        assert_eq!(january.parse_elapsed_ms, Some(7));
+        let february = &manifest.periods[1];
+        assert_eq!(february.period, "202402");
+        assert_eq!(february.zip_bytes, None);
+        assert_eq!(february.entries, None);
+        assert!(february.output_paths.is_empty());
+
+        let path = tmp.path().join("parquet/manifest.json");
+        manifest.save(&path).unwrap();
+        let saved: RunManifest =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved, manifest);
+    }
+}
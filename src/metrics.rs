@@ -0,0 +1,201 @@
+//! Prometheus metrics for pipeline runs (requires the `metrics` feature).
+//!
+//! Counters are kept in a process-wide registry and updated at the end of each pipeline
+//! phase. [`serve`] exposes them on `/metrics` in the Prometheus text exposition format.
+
+use crate::errors::{AppError, AppResult};
+use crate::models::{DownloadStats, ParseStats, ProcurementType};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::net::SocketAddr;
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tracing::{info, warn};
+
+/// Label set rendered as `{key="value",...}`, kept sorted for stable output.
+type Labels = Vec<(&'static str, String)>;
+
+#[derive(Default)]
+struct Registry {
+    downloads_total: BTreeMap<Labels, u64>,
+    parse_errors_total: BTreeMap<Labels, u64>,
+    entries_parsed_total: BTreeMap<Labels, u64>,
+    last_run_timestamp_seconds: Option<u64>,
+}
+
+static REGISTRY: OnceLock<Mutex<Registry>> = OnceLock::new();
+
+fn with_registry<T>(f: impl FnOnce(&mut Registry) -> T) -> T {
+    let registry = REGISTRY.get_or_init(|| Mutex::new(Registry::default()));
+    let mut guard = registry.lock().unwrap_or_else(|e| e.into_inner());
+    f(&mut guard)
+}
+
+fn proc_type_label(proc_type: &ProcurementType) -> String {
+    proc_type.display_name().to_string()
+}
+
+/// Records the outcome of the download phase.
+pub fn record_downloads(proc_type: &ProcurementType, stats: &DownloadStats) {
+    with_registry(|r| {
+        for (status, count) in [("downloaded", stats.downloaded), ("skipped", stats.skipped)] {
+            let labels = vec![
+                ("procurement_type", proc_type_label(proc_type)),
+                ("status", status.to_string()),
+            ];
+            *r.downloads_total.entry(labels).or_default() += count as u64;
+        }
+    });
+}
+
+/// Records a period whose parsing failed.
+pub fn record_parse_error(period: &str) {
+    with_registry(|r| {
+        let labels = vec![("period", period.to_string())];
+        *r.parse_errors_total.entry(labels).or_default() += 1;
+    });
+}
+
+/// Records the entries produced per period by the parsing phase.
+pub fn record_parse(proc_type: &ProcurementType, stats: &ParseStats) {
+    with_registry(|r| {
+        for (period, entries) in &stats.entries_by_period {
+            let labels = vec![
+                ("period", period.clone()),
+                ("procurement_type", proc_type_label(proc_type)),
+            ];
+            *r.entries_parsed_total.entry(labels).or_default() += *entries as u64;
+        }
+    });
+}
+
+/// Sets `sppd_last_run_timestamp_seconds` to the current time.
+pub fn record_run_completed() {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    with_registry(|r| r.last_run_timestamp_seconds = Some(now));
+}
+
+fn write_family(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    kind: &str,
+    samples: &BTreeMap<Labels, u64>,
+) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+    for (labels, value) in samples {
+        let rendered: Vec<String> = labels
+            .iter()
+            .map(|(k, v)| format!("{k}=\"{}\"", v.replace('\\', "\\\\").replace('"', "\\\"")))
+            .collect();
+        let _ = writeln!(out, "{name}{{{}}} {value}", rendered.join(","));
+    }
+}
+
+/// Renders all metrics in the Prometheus text exposition format.
+pub fn render() -> String {
+    with_registry(|r| {
+        let mut out = String::new();
+        write_family(
+            &mut out,
+            "sppd_downloads_total",
+            "ZIP downloads by procurement type and status.",
+            "counter",
+            &r.downloads_total,
+        );
+        write_family(
+            &mut out,
+            "sppd_parse_errors_total",
+            "Periods whose parsing failed.",
+            "counter",
+            &r.parse_errors_total,
+        );
+        write_family(
+            &mut out,
+            "sppd_entries_parsed_total",
+            "Entries written to Parquet by period and procurement type.",
+            "counter",
+            &r.entries_parsed_total,
+        );
+        let _ = writeln!(
+            out,
+            "# HELP sppd_last_run_timestamp_seconds Unix time of the last successful run."
+        );
+        let _ = writeln!(out, "# TYPE sppd_last_run_timestamp_seconds gauge");
+        if let Some(ts) = r.last_run_timestamp_seconds {
+            let _ = writeln!(out, "sppd_last_run_timestamp_seconds {ts}");
+        }
+        out
+    })
+}
+
+/// Binds `addr` and serves `/metrics` on a background task for the rest of the process.
+///
+/// Returns the bound address (useful when `addr` uses port 0).
+///
+/// # Errors
+///
+/// Returns `IoError` if the address cannot be bound.
+pub async fn serve(addr: SocketAddr) -> AppResult<SocketAddr> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .map_err(|e| AppError::IoError(format!("Failed to bind metrics server on {addr}: {e}")))?;
+    let local_addr = listener.local_addr()?;
+    info!(addr = %local_addr, "Serving Prometheus metrics on /metrics");
+
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = match listener.accept().await {
+                Ok(conn) => conn,
+                Err(e) => {
+                    warn!(error = %e, "Failed to accept metrics connection");
+                    continue;
+                }
+            };
+            tokio::spawn(async move {
+                let mut buf = [0u8; 1024];
+                let n = stream.read(&mut buf).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]);
+                let response = if request.starts_with("GET /metrics ") {
+                    let body = render();
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    )
+                } else {
+                    "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                        .to_string()
+                };
+                if let Err(e) = stream.write_all(response.as_bytes()).await {
+                    warn!(error = %e, "Failed to write metrics response");
+                }
+            });
+        }
+    });
+
+    Ok(local_addr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_uses_prometheus_text_format() {
+        let mut stats = ParseStats::default();
+        stats.entries_by_period.insert("199901".to_string(), 3);
+        record_parse(&ProcurementType::MinorContracts, &stats);
+
+        let body = render();
+        assert!(body.contains("# TYPE sppd_entries_parsed_total counter"));
+        assert!(body.contains(
+            "sppd_entries_parsed_total{period=\"199901\",procurement_type=\"Minor Contracts\"} 3"
+        ));
+    }
+}
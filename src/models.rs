@@ -1,5 +1,10 @@
+use crate::errors::{AppError, AppResult};
+use crate::utils::{format_duration, mb_from_bytes, round_two_decimals};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 /// Represents a procurement project lot with budget, classification, and location.
@@ -27,6 +32,12 @@ pub struct ProcurementProjectLot {
     pub country_code: Option<String>,
     /// List URI for country code
     pub country_code_list_uri: Option<String>,
+    /// NUTS code of the lot's `<cac:RealizedLocation>` (e.g. ES511)
+    #[serde(default)]
+    pub country_subentity_code: Option<String>,
+    /// List URI for country_subentity_code
+    #[serde(default)]
+    pub country_subentity_code_list_uri: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -51,6 +62,33 @@ pub struct TermsFundingProgram {
     pub list_uri: Option<String>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+/// Metadata of the Atom feed an entry was read from.
+///
+/// Taken from the `<id>`, `<title>`, and `<updated>` children of `<feed>` that come before
+/// its first entry; useful to tell whether a feed changed since it was last processed.
+pub struct FeedMetadata {
+    /// Feed-level `<id>`, kept verbatim
+    pub feed_id: Option<String>,
+    /// Feed-level `<title>`
+    pub feed_title: Option<String>,
+    /// Feed-level `<updated>` timestamp
+    pub feed_updated: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+/// One award criterion of the tender.
+///
+/// Corresponds to a `<cac:TenderingTerms>/<cac:AwardingTerms>/<cac:AwardingCriteria>` element.
+pub struct AwardingCriterion {
+    /// Criterion description (e.g. "Precio")
+    pub description: Option<String>,
+    /// Criterion weighting (`<cbc:WeightNumeric>`)
+    pub weight: Option<String>,
+    /// Criterion type code (`<cbc:AwardingCriteriaTypeCode>`, e.g. "OBJ" or "SUBJ")
+    pub type_code: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 /// Represents one output row derived from a `<cac:TenderResult>` plus an optional lot.
 pub struct TenderResultRow {
@@ -78,13 +116,23 @@ pub struct TenderResultRow {
     pub result_payable_amount: Option<String>,
     /// `currencyID` attribute from the payable amount.
     pub result_payable_currency: Option<String>,
+    /// `<cac:TenderResult>/<cbc:ReceivedTenderQuantity>` (number of bids received)
+    pub result_received_tender_quantity: Option<String>,
+    /// `<cac:TenderResult>/<cbc:LowerTenderAmount>` (lowest bid)
+    pub result_lower_tender_amount: Option<String>,
+    /// `currencyID` attribute from the lower tender amount.
+    pub result_lower_tender_currency: Option<String>,
+    /// `<cac:TenderResult>/<cbc:HigherTenderAmount>` (highest bid)
+    pub result_higher_tender_amount: Option<String>,
+    /// `currencyID` attribute from the higher tender amount.
+    pub result_higher_tender_currency: Option<String>,
 }
 
 /// Represents a single entry element from an XML/Atom feed.
 ///
 /// Corresponds to an `<entry>` element in Atom feeds from Spanish procurement data sources.
 /// All fields are optional to handle variations in the source data format.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Entry {
     /// Atom entry ID
     pub id: Option<String>,
@@ -96,12 +144,24 @@ pub struct Entry {
     pub summary: Option<String>,
     /// Last updated timestamp
     pub updated: Option<String>,
+    /// `term` attributes of the entry-level `<category>` elements, in document order
+    #[serde(default)]
+    pub categories: Vec<String>,
+    /// Entry-level `<author>/<name>` text (several authors are joined with `_`)
+    #[serde(default)]
+    pub author: Option<String>,
+    /// Whether the row comes from an `<at:deleted-entry>` tombstone (a withdrawn notice)
+    #[serde(default)]
+    pub is_deleted: bool,
     /// `<cbc-place-ext:ContractFolderStatusCode>`
     pub status: StatusCode,
     /// `<cbc:ContractFolderID>`
     pub contract_id: Option<String>,
     /// `<cac:LocatedContractingParty>/<cac:Party>/<cac:PartyName>/<cbc:Name>`
     pub contracting_party_name: Option<String>,
+    /// `<cac:LocatedContractingParty>/<cac:Party>/<cac:PartyIdentification>/<cbc:ID>`
+    /// (NIF/CIF or DIR3 code; several identifiers are joined with `_`)
+    pub contracting_party_id: Option<String>,
     /// `<cac:LocatedContractingParty>/<cac:Party>/<cbc:WebsiteURI>`
     pub contracting_party_website: Option<String>,
     /// `<cac:LocatedContractingParty>/<cbc:ContractingPartyTypeCode>`
@@ -146,14 +206,30 @@ pub struct Entry {
     pub project_country_code: Option<String>,
     /// listURI attribute for project_country_code
     pub project_country_code_list_uri: Option<String>,
+    /// `<cac:ProcurementProject>/<cac:PlannedPeriod>/<cbc:DurationMeasure>`
+    pub project_duration_measure: Option<String>,
+    /// unitCode attribute for project_duration_measure (e.g. `DAY`, `MON`, `ANN`)
+    pub project_duration_unit: Option<String>,
+    /// `<cac:ProcurementProject>/<cac:RealizedLocation>/<cbc:CountrySubentity>` (e.g. region name)
+    pub project_country_subentity: Option<String>,
+    /// `<cac:ProcurementProject>/<cac:RealizedLocation>/<cbc:CountrySubentityCode>` (NUTS code)
+    pub project_country_subentity_code: Option<String>,
+    /// listURI attribute for project_country_subentity_code
+    pub project_country_subentity_code_list_uri: Option<String>,
     /// Collection of parsed `<cac:ProcurementProjectLot>` values
     pub project_lots: Vec<ProcurementProjectLot>,
     /// Tender result rows expanded per lot; each row carries the previous `result_*` metadata plus `result_id`/`result_lot_id`.
     pub tender_results: Vec<TenderResultRow>,
     /// `<cac:TenderingTerms>/<cbc:FundingProgramCode>`
     pub terms_funding_program: TermsFundingProgram,
+    /// Collection of parsed `<cac:AwardingCriteria>` values
+    pub awarding_criteria: Vec<AwardingCriterion>,
+    /// `<cac:TenderingProcess>/<cac:TenderSubmissionDeadlinePeriod>/<cbc:StartDate>`
+    pub process_start_date: Option<String>,
     /// `<cac:TenderingProcess>/<cac:TenderSubmissionDeadlinePeriod>/<cbc:EndDate>`
     pub process_end_date: Option<String>,
+    /// `<cac:TenderingProcess>/<cac:OpenTenderEvent>/<cbc:OccurrenceDate>`
+    pub process_open_date: Option<String>,
     /// `<cac:TenderingProcess>/<cbc:ProcedureCode>`
     pub process_procedure_code: Option<String>,
     /// listURI attribute for process_procedure_code
@@ -164,41 +240,69 @@ pub struct Entry {
     pub process_urgency_code_list_uri: Option<String>,
     /// Entire `<cac-place-ext:ContractFolderStatus>` XML
     pub cfs_raw_xml: Option<String>,
+    /// Metadata of the feed the entry comes from, the same for every entry of a file
+    #[serde(default)]
+    pub feed_metadata: FeedMetadata,
 }
 
 // Procurement type aliases
 const MINOR_CONTRACTS_ALIASES: &[&str] = &["mc", "minor-contracts", "min"];
 const PUBLIC_TENDERS_ALIASES: &[&str] = &["pt", "pub", "public-tenders"];
+const AGGREGATED_PLATFORMS_ALIASES: &[&str] = &["agg", "agregadas", "aggregated-platforms"];
 
 /// Type of procurement data to download.
 ///
-/// Spanish public procurement data is organized into two main categories:
+/// Spanish public procurement data is organized into three datasets, published with the
+/// same ZIP/Atom structure:
 /// - **Minor Contracts** (`MinorContracts`): Contracts below certain value thresholds
 /// - **Public Tenders** (`PublicTenders`): Formal public procurement processes
+/// - **Aggregated Platforms** (`AggregatedPlatforms`): Tenders published on the regional
+///   contracting platforms aggregated into the national one (agregadas)
 ///
 /// This enum is used throughout the CLI to determine which data source to query
 /// and where to store downloaded and processed files.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum ProcurementType {
     /// Minor contracts (contratos menores)
     MinorContracts,
     /// Public tenders (licitaciones)
     PublicTenders,
+    /// Tenders from the aggregated regional platforms (plataformas agregadas)
+    AggregatedPlatforms,
 }
 
 impl ProcurementType {
+    /// Every procurement type, in declaration order.
+    pub const ALL: [Self; 3] = [
+        Self::MinorContracts,
+        Self::PublicTenders,
+        Self::AggregatedPlatforms,
+    ];
+
     /// Returns a human-readable name for the procurement type.
     pub fn display_name(&self) -> &'static str {
         match self {
             Self::MinorContracts => "Minor Contracts",
             Self::PublicTenders => "Public Tenders",
+            Self::AggregatedPlatforms => "Aggregated Platforms",
+        }
+    }
+
+    /// Returns the short alias (`mc`, `pt`, `agg`) used in directory and file names.
+    pub fn short_name(&self) -> &'static str {
+        match self {
+            Self::MinorContracts => "mc",
+            Self::PublicTenders => "pt",
+            Self::AggregatedPlatforms => "agg",
         }
     }
+
     /// Returns the download directory path for the procurement type (for ZIP downloads).
     pub fn download_dir(&self, config: &crate::config::ResolvedConfig) -> PathBuf {
         match self {
             Self::MinorContracts => config.download_dir_mc.clone(),
             Self::PublicTenders => config.download_dir_pt.clone(),
+            Self::AggregatedPlatforms => config.download_dir_agg.clone(),
         }
     }
 
@@ -213,6 +317,7 @@ impl ProcurementType {
         match self {
             Self::MinorContracts => config.parquet_dir_mc.clone(),
             Self::PublicTenders => config.parquet_dir_pt.clone(),
+            Self::AggregatedPlatforms => config.parquet_dir_agg.clone(),
         }
     }
 
@@ -224,10 +329,12 @@ impl ProcurementType {
     /// Known aliases:
     /// - Minor Contracts: `"mc"`, `"minor-contracts"`, `"min"`
     /// - Public Tenders: `"pt"`, `"pub"`, `"public-tenders"`
+    /// - Aggregated Platforms: `"agg"`, `"agregadas"`, `"aggregated-platforms"`
     pub fn is_known_type(value: &str) -> bool {
         let lower = value.trim().to_lowercase();
         MINOR_CONTRACTS_ALIASES.contains(&lower.as_str())
             || PUBLIC_TENDERS_ALIASES.contains(&lower.as_str())
+            || AGGREGATED_PLATFORMS_ALIASES.contains(&lower.as_str())
     }
 }
 
@@ -240,8 +347,10 @@ impl From<&str> for ProcurementType {
     ///
     /// **Public Tenders aliases:** `"pt"`, `"pub"`, `"public-tenders"`
     ///
-    /// Unknown values default to `PublicTenders` (a warning is logged by the CLI).
-    /// Use `is_known_type()` to check if a value is recognized before converting.
+    /// **Aggregated Platforms aliases:** `"agg"`, `"agregadas"`, `"aggregated-platforms"`
+    ///
+    /// Unknown values default to `PublicTenders`. Use [`FromStr`] (`value.parse()`) to reject
+    /// them instead, as the CLI does; a `TryFrom<&str>` impl is not possible next to this one.
     fn from(value: &str) -> Self {
         // Trim whitespace and compare case-insensitively
         let lower = value.trim().to_lowercase();
@@ -250,6 +359,8 @@ impl From<&str> for ProcurementType {
             Self::MinorContracts
         } else if PUBLIC_TENDERS_ALIASES.contains(&lower.as_str()) {
             Self::PublicTenders
+        } else if AGGREGATED_PLATFORMS_ALIASES.contains(&lower.as_str()) {
+            Self::AggregatedPlatforms
         } else {
             // Default silently to PublicTenders; callers can decide to log if needed.
             Self::PublicTenders
@@ -257,9 +368,382 @@ impl From<&str> for ProcurementType {
     }
 }
 
+impl FromStr for ProcurementType {
+    type Err = AppError;
+
+    /// Parses a procurement type alias like the `From<&str>` impl, but rejects unknown values.
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidInput` listing the valid aliases if `value` is not one of them.
+    fn from_str(value: &str) -> AppResult<Self> {
+        if Self::is_known_type(value) {
+            Ok(Self::from(value))
+        } else {
+            Err(AppError::InvalidInput(format!(
+                "Unknown procurement type '{}' (expected one of: {}, {}, {})",
+                value.trim(),
+                MINOR_CONTRACTS_ALIASES.join(", "),
+                PUBLIC_TENDERS_ALIASES.join(", "),
+                AGGREGATED_PLATFORMS_ALIASES.join(", ")
+            )))
+        }
+    }
+}
+
+/// Statistics returned by the download stage.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DownloadStats {
+    /// Files that were not present locally and had to be fetched
+    pub attempted: usize,
+    /// Files fetched during this run
+    pub downloaded: usize,
+    /// Files skipped because the ZIP already existed locally
+    pub skipped: usize,
+    /// Total size of the downloaded files in bytes
+    pub bytes: u64,
+    /// Periods whose download failed, with the error (only with `keep_going`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub failed_periods: BTreeMap<String, String>,
+    /// Wall-clock time of the stage in milliseconds
+    pub elapsed_ms: u64,
+}
+
+impl DownloadStats {
+    /// Adds the statistics of another call for a disjoint set of periods.
+    pub(crate) fn merge(&mut self, other: Self) {
+        self.attempted += other.attempted;
+        self.downloaded += other.downloaded;
+        self.skipped += other.skipped;
+        self.bytes += other.bytes;
+        self.failed_periods.extend(other.failed_periods);
+        self.elapsed_ms += other.elapsed_ms;
+    }
+}
+
+/// Statistics returned by the extraction stage.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExtractionStats {
+    /// ZIP archives extracted during this run
+    pub extracted: usize,
+    /// ZIP archives skipped because their directory already existed
+    pub skipped: usize,
+    /// Periods whose ZIP archive was not found
+    pub missing: usize,
+    /// Total size of the extracted files in bytes
+    pub bytes: u64,
+    /// Periods whose extraction failed, with the error (only with `keep_going`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub failed_periods: BTreeMap<String, String>,
+    /// Wall-clock time of the stage in milliseconds
+    pub elapsed_ms: u64,
+}
+
+impl ExtractionStats {
+    /// Adds the statistics of another call for a disjoint set of periods.
+    pub(crate) fn merge(&mut self, other: Self) {
+        self.extracted += other.extracted;
+        self.skipped += other.skipped;
+        self.missing += other.missing;
+        self.bytes += other.bytes;
+        self.failed_periods.extend(other.failed_periods);
+        self.elapsed_ms += other.elapsed_ms;
+    }
+}
+
+/// Statistics returned by the parsing stage.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParseStats {
+    /// Periods that produced Parquet output
+    pub periods_parsed: usize,
+    /// Periods skipped because they contained no entries
+    pub periods_skipped: usize,
+    /// XML/Atom files read
+    pub xml_files: usize,
+    /// Entries written to Parquet
+    pub entries: usize,
+    /// Malformed entries dropped by lenient parsing
+    pub skipped_entries: usize,
+    /// Amounts left as read because `normalize_amounts` could not normalize them
+    #[serde(default)]
+    pub unnormalized_amounts: usize,
+    /// Parquet files written
+    pub parquet_files: usize,
+    /// Total size of the Parquet files in bytes
+    pub parquet_bytes: u64,
+    /// Entries written per parsed period
+    pub entries_by_period: BTreeMap<String, usize>,
+    /// Details of every period read, including empty ones; recorded for the run manifest
+    /// and left out of the summary JSON
+    #[serde(skip)]
+    pub periods: BTreeMap<String, PeriodParseStats>,
+    /// Periods whose parsing failed, with the error (only with `keep_going`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub failed_periods: BTreeMap<String, String>,
+    /// Wall-clock time of the stage in milliseconds
+    pub elapsed_ms: u64,
+}
+
+impl ParseStats {
+    /// Adds the statistics of another call for a disjoint set of periods.
+    pub(crate) fn merge(&mut self, other: Self) {
+        self.periods_parsed += other.periods_parsed;
+        self.periods_skipped += other.periods_skipped;
+        self.xml_files += other.xml_files;
+        self.entries += other.entries;
+        self.skipped_entries += other.skipped_entries;
+        self.unnormalized_amounts += other.unnormalized_amounts;
+        self.parquet_files += other.parquet_files;
+        self.parquet_bytes += other.parquet_bytes;
+        self.entries_by_period.extend(other.entries_by_period);
+        self.periods.extend(other.periods);
+        self.failed_periods.extend(other.failed_periods);
+        self.elapsed_ms += other.elapsed_ms;
+    }
+}
+
+/// Parsing details of one period, as recorded in the run manifest.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeriodParseStats {
+    /// XML/Atom files read
+    pub xml_files: usize,
+    /// Entries written
+    pub entries: usize,
+    /// Output files written (empty when the period had no entries)
+    pub output_paths: Vec<PathBuf>,
+    /// Wall-clock time of the period in milliseconds
+    pub elapsed_ms: u64,
+}
+
+/// Per-stage statistics for one pipeline run.
+///
+/// Stages that did not run (e.g., download and extraction with `--parse-only`) are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunSummary {
+    /// Human-readable procurement type (see [`ProcurementType::display_name`])
+    pub procurement_type: String,
+    /// Number of periods selected for the run
+    pub periods: usize,
+    pub download: Option<DownloadStats>,
+    pub extraction: Option<ExtractionStats>,
+    pub parse: Option<ParseStats>,
+    /// Wall-clock time of the whole run in milliseconds
+    pub total_elapsed_ms: u64,
+}
+
+impl RunSummary {
+    /// Creates an empty summary for the given procurement type and period count.
+    pub fn new(proc_type: &ProcurementType, periods: usize) -> Self {
+        Self {
+            procurement_type: proc_type.display_name().to_string(),
+            periods,
+            ..Self::default()
+        }
+    }
+
+    /// Lists the periods that failed in any stage as `(stage, period, error)`, in pipeline order.
+    pub fn failures(&self) -> Vec<(&'static str, &str, &str)> {
+        let stages = [
+            (
+                "download",
+                self.download.as_ref().map(|d| &d.failed_periods),
+            ),
+            (
+                "extraction",
+                self.extraction.as_ref().map(|e| &e.failed_periods),
+            ),
+            ("parse", self.parse.as_ref().map(|p| &p.failed_periods)),
+        ];
+        stages
+            .into_iter()
+            .filter_map(|(stage, failed)| failed.map(|failed| (stage, failed)))
+            .flat_map(|(stage, failed)| {
+                failed
+                    .iter()
+                    .map(move |(period, error)| (stage, period.as_str(), error.as_str()))
+            })
+            .collect()
+    }
+
+    /// Returns a [`AppError::PartialFailure`] describing the failed periods, if any.
+    ///
+    /// Periods failing in any stage count as failed; the rest of the selected periods count
+    /// as succeeded.
+    pub fn partial_failure(&self) -> Option<AppError> {
+        let failures = self.failures();
+        if failures.is_empty() {
+            return None;
+        }
+        let mut stages: Vec<&str> = failures.iter().map(|(stage, _, _)| *stage).collect();
+        stages.dedup();
+        Some(AppError::PartialFailure {
+            stage: stages.join(", "),
+            succeeded: self.periods.saturating_sub(failures.len()),
+            failed: failures.len(),
+            details: failures
+                .iter()
+                .map(|(stage, period, error)| format!("{period} ({stage}): {error}"))
+                .collect::<Vec<_>>()
+                .join("; "),
+        })
+    }
+
+    /// Renders the summary as a plain-text table, one row per stage, followed by the failed
+    /// periods if any.
+    pub fn to_table(&self) -> String {
+        let mut rows: Vec<(&str, String, u64)> = Vec::new();
+        if let Some(d) = &self.download {
+            rows.push((
+                "Download",
+                format!(
+                    "{} downloaded, {} skipped, {} MB",
+                    d.downloaded,
+                    d.skipped,
+                    round_two_decimals(mb_from_bytes(d.bytes))
+                ),
+                d.elapsed_ms,
+            ));
+        }
+        if let Some(e) = &self.extraction {
+            rows.push((
+                "Extraction",
+                format!(
+                    "{} extracted, {} skipped, {} missing, {} MB",
+                    e.extracted,
+                    e.skipped,
+                    e.missing,
+                    round_two_decimals(mb_from_bytes(e.bytes))
+                ),
+                e.elapsed_ms,
+            ));
+        }
+        if let Some(p) = &self.parse {
+            let mut details = format!(
+                "{} XML files, {} entries, {} periods ({} empty), {} Parquet files, {} MB",
+                p.xml_files,
+                p.entries,
+                p.periods_parsed,
+                p.periods_skipped,
+                p.parquet_files,
+                round_two_decimals(mb_from_bytes(p.parquet_bytes))
+            );
+            if p.skipped_entries > 0 {
+                details.push_str(&format!(
+                    ", {} malformed entries skipped",
+                    p.skipped_entries
+                ));
+            }
+            if p.unnormalized_amounts > 0 {
+                details.push_str(&format!(
+                    ", {} amounts not normalized",
+                    p.unnormalized_amounts
+                ));
+            }
+            rows.push(("Parse", details, p.elapsed_ms));
+        }
+        rows.push((
+            "Total",
+            format!("{} periods", self.periods),
+            self.total_elapsed_ms,
+        ));
+
+        let width = rows.iter().map(|(_, d, _)| d.len()).max().unwrap_or(0);
+        let mut table = format!("Run summary: {}\n", self.procurement_type);
+        for (stage, details, elapsed_ms) in rows {
+            table.push_str(&format!(
+                "  {stage:<10}  {details:<width$}  {}\n",
+                format_duration(Duration::from_millis(elapsed_ms))
+            ));
+        }
+        let failures = self.failures();
+        if !failures.is_empty() {
+            table.push_str("Failed periods:\n");
+            for (stage, period, error) in failures {
+                table.push_str(&format!("  {period:<10}  {stage:<10}  {error}\n"));
+            }
+        }
+        table
+    }
+}
+
+/// What a run would do with one period, as planned by `--dry-run`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PeriodPlan {
+    pub period: String,
+    /// Whether the ZIP would be downloaded (`None` when the download stage is skipped)
+    pub download: Option<bool>,
+    /// Size in bytes of the ZIP to download, when the server or source file reports it
+    pub download_bytes: Option<u64>,
+    /// Whether the ZIP would be extracted (`None` when the extraction stage is skipped)
+    pub extract: Option<bool>,
+    /// File or directory the period would be written to (`None` when parsing is skipped)
+    pub output_path: Option<PathBuf>,
+    /// Whether `output_path` already exists and would be replaced
+    pub overwrite: bool,
+}
+
+/// The periods a run would process and what it would do with each, see [`PeriodPlan`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunPlan {
+    /// Human-readable procurement type (see [`ProcurementType::display_name`])
+    pub procurement_type: String,
+    pub periods: Vec<PeriodPlan>,
+}
+
+impl RunPlan {
+    /// Renders the plan as a plain-text table with one row per period.
+    pub fn to_table(&self) -> String {
+        let stage = |planned: Option<bool>| match planned {
+            Some(true) => "yes".to_string(),
+            Some(false) => "no (exists)".to_string(),
+            None => "skipped".to_string(),
+        };
+        let mut rows = vec![[
+            "Period".to_string(),
+            "Download".to_string(),
+            "Extraction".to_string(),
+            "Output".to_string(),
+        ]];
+        for plan in &self.periods {
+            let mut download = stage(plan.download);
+            if let (Some(true), Some(bytes)) = (plan.download, plan.download_bytes) {
+                download.push_str(&format!(
+                    " ({} MB)",
+                    round_two_decimals(mb_from_bytes(bytes))
+                ));
+            }
+            let output = match &plan.output_path {
+                Some(path) if plan.overwrite => format!("{} (overwrite)", path.display()),
+                Some(path) => format!("{} (new)", path.display()),
+                None => "skipped".to_string(),
+            };
+            rows.push([plan.period.clone(), download, stage(plan.extract), output]);
+        }
+
+        let widths: Vec<usize> = (0..3)
+            .map(|column| rows.iter().map(|row| row[column].len()).max().unwrap_or(0))
+            .collect();
+        let mut table = format!(
+            "Dry run: {}, {} periods (nothing downloaded or written)\n",
+            self.procurement_type,
+            self.periods.len()
+        );
+        for [period, download, extraction, output] in rows {
+            table.push_str(&format!(
+                "  {period:<w0$}  {download:<w1$}  {extraction:<w2$}  {output}\n",
+                w0 = widths[0],
+                w1 = widths[1],
+                w2 = widths[2]
+            ));
+        }
+        table
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::ProcurementType;
+    use super::{AppError, ParseStats, PeriodPlan, ProcurementType, RunPlan, RunSummary};
+    use std::path::PathBuf;
 
     #[test]
     fn test_procurement_type_minor_contracts_primary_alias() {
@@ -312,6 +796,33 @@ mod tests {
         assert_eq!(proc_type, ProcurementType::PublicTenders);
     }
 
+    #[test]
+    fn test_procurement_type_parse_accepts_aliases() {
+        assert_eq!(
+            "MC".parse::<ProcurementType>().unwrap(),
+            ProcurementType::MinorContracts
+        );
+        assert_eq!(
+            " public-tenders ".parse::<ProcurementType>().unwrap(),
+            ProcurementType::PublicTenders
+        );
+    }
+
+    #[test]
+    fn test_procurement_type_parse_rejects_unknown_values() {
+        for value in ["pubic-tenders", "", "   "] {
+            match value.parse::<ProcurementType>() {
+                Err(AppError::InvalidInput(message)) => assert!(
+                    message.contains(
+                        "mc, minor-contracts, min, pt, pub, public-tenders, agg, agregadas, aggregated-platforms"
+                    ),
+                    "{message}"
+                ),
+                other => panic!("expected InvalidInput for {value:?}, got {other:?}"),
+            }
+        }
+    }
+
     #[test]
     fn test_procurement_type_display_name() {
         let minor = ProcurementType::MinorContracts;
@@ -319,6 +830,41 @@ mod tests {
 
         let public = ProcurementType::PublicTenders;
         assert_eq!(public.display_name(), "Public Tenders");
+
+        let aggregated = ProcurementType::AggregatedPlatforms;
+        assert_eq!(aggregated.display_name(), "Aggregated Platforms");
+    }
+
+    #[test]
+    fn test_procurement_type_aggregated_platforms_aliases() {
+        for alias in ["agg", "agregadas", "aggregated-platforms", " AGREGADAS "] {
+            assert!(ProcurementType::is_known_type(alias));
+            assert_eq!(
+                alias.parse::<ProcurementType>().unwrap(),
+                ProcurementType::AggregatedPlatforms
+            );
+        }
+    }
+
+    #[test]
+    fn test_procurement_type_short_names_and_dirs() {
+        let config = crate::config::ResolvedConfig::default();
+        let short_names: Vec<&str> = ProcurementType::ALL
+            .iter()
+            .map(ProcurementType::short_name)
+            .collect();
+        assert_eq!(short_names, ["mc", "pt", "agg"]);
+        for proc_type in ProcurementType::ALL {
+            let short_name = proc_type.short_name();
+            assert_eq!(
+                proc_type.download_dir(&config),
+                PathBuf::from(format!("data/tmp/{short_name}"))
+            );
+            assert_eq!(
+                proc_type.parquet_dir(&config),
+                PathBuf::from(format!("data/parquet/{short_name}"))
+            );
+        }
     }
 
     #[test]
@@ -360,4 +906,65 @@ mod tests {
         assert!(!ProcurementType::is_known_type(""));
         assert!(!ProcurementType::is_known_type("   "));
     }
+
+    #[test]
+    fn run_summary_table_lists_only_completed_stages() {
+        let mut summary = RunSummary::new(&ProcurementType::PublicTenders, 2);
+        summary.parse = Some(ParseStats {
+            xml_files: 3,
+            entries: 42,
+            ..ParseStats::default()
+        });
+
+        let table = summary.to_table();
+        assert!(table.starts_with("Run summary: Public Tenders"));
+        assert!(table.contains("3 XML files, 42 entries"));
+        assert!(table.contains("2 periods"));
+        assert!(!table.contains("Download"));
+        assert!(!table.contains("Extraction"));
+        assert!(!table.contains("malformed"));
+
+        summary.parse.as_mut().unwrap().skipped_entries = 2;
+        assert!(summary.to_table().contains("2 malformed entries skipped"));
+    }
+
+    #[test]
+    fn run_plan_table_lists_each_period_action() {
+        let plan = RunPlan {
+            procurement_type: "Public Tenders".to_string(),
+            periods: vec![
+                PeriodPlan {
+                    period: "202401".to_string(),
+                    download: Some(true),
+                    download_bytes: Some(1_572_864),
+                    extract: Some(true),
+                    output_path: Some("out/202401.parquet".into()),
+                    overwrite: false,
+                },
+                PeriodPlan {
+                    period: "202402".to_string(),
+                    download: Some(false),
+                    extract: None,
+                    output_path: Some("out/202402.parquet".into()),
+                    overwrite: true,
+                    ..PeriodPlan::default()
+                },
+            ],
+        };
+
+        let table = plan.to_table();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(
+            lines[0],
+            "Dry run: Public Tenders, 2 periods (nothing downloaded or written)"
+        );
+        assert_eq!(
+            lines[2],
+            "  202401  yes (1.5 MB)  yes         out/202401.parquet (new)"
+        );
+        assert_eq!(
+            lines[3],
+            "  202402  no (exists)   skipped     out/202402.parquet (overwrite)"
+        );
+    }
 }
@@ -0,0 +1,116 @@
+use crate::errors::{AppError, AppResult};
+use crate::utils::file_digest_hex;
+use sha2::Sha256;
+use std::path::{Path, PathBuf};
+
+/// Writes a `sha256sum`-compatible checksum file covering the output files of one period.
+///
+/// `paths` is either the single concatenated file (`{parquet_dir}/{period}.parquet`) or the
+/// batch files of the period (`{parquet_dir}/{period}/batch_N.parquet`). In both cases the
+/// checksum file is `{parquet_dir}/{period}.sha256`, with one `<hex>  <filename>` line per
+/// file and file names relative to `{parquet_dir}`, so `sha256sum -c` can be run from there.
+/// The `part-N` files of a partition (`.../year=YYYY/month=MM/`) are handled like batch
+/// files, giving `.../year=YYYY/month=MM.sha256`. An existing checksum file is overwritten.
+///
+/// Returns the path of the checksum file.
+///
+/// # Errors
+///
+/// Returns `InvalidInput` if `paths` is empty or the files do not belong to a single
+/// period, or `IoError` if a file cannot be read or the checksum file cannot be written.
+pub fn write_checksums(paths: &[PathBuf]) -> AppResult<PathBuf> {
+    let first = paths
+        .first()
+        .ok_or_else(|| AppError::InvalidInput("No files to checksum".to_string()))?;
+    let is_batch = first
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with("batch_") || name.starts_with("part-"));
+    let period_path = if is_batch {
+        first.parent().unwrap_or(Path::new("")).to_path_buf()
+    } else {
+        first.with_extension("")
+    };
+    let base_dir = period_path.parent().unwrap_or(Path::new(""));
+    let checksum_path = period_path.with_extension("sha256");
+
+    let mut lines = String::new();
+    for path in paths {
+        let name = path.strip_prefix(base_dir).map_err(|_| {
+            AppError::InvalidInput(format!(
+                "{} is not part of the output for {}",
+                path.display(),
+                period_path.display()
+            ))
+        })?;
+        // sha256sum expects forward slashes in the file column.
+        let name = name
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        lines.push_str(&format!("{}  {name}\n", file_digest_hex::<Sha256>(path)?));
+    }
+
+    std::fs::write(&checksum_path, lines).map_err(|e| {
+        AppError::IoError(format!(
+            "Failed to write checksum file {}: {e}",
+            checksum_path.display()
+        ))
+    })?;
+    Ok(checksum_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    // sha256("abc") and sha256("") from FIPS 180-2.
+    const ABC: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+    const EMPTY: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+    #[test]
+    fn write_checksums_covers_batch_files() {
+        let tmp = TempDir::new().unwrap();
+        let period_dir = tmp.path().join("202401");
+        std::fs::create_dir_all(&period_dir).unwrap();
+        let batches = vec![
+            period_dir.join("batch_0.parquet"),
+            period_dir.join("batch_1.parquet"),
+        ];
+        std::fs::write(&batches[0], b"abc").unwrap();
+        std::fs::write(&batches[1], b"").unwrap();
+
+        let path = write_checksums(&batches).unwrap();
+
+        assert_eq!(path, tmp.path().join("202401.sha256"));
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            format!("{ABC}  202401/batch_0.parquet\n{EMPTY}  202401/batch_1.parquet\n")
+        );
+    }
+
+    #[test]
+    fn write_checksums_overwrites_previous_file() {
+        let tmp = TempDir::new().unwrap();
+        let file = tmp.path().join("202401.parquet");
+        std::fs::write(tmp.path().join("202401.sha256"), "stale\nstale\n").unwrap();
+        std::fs::write(&file, b"abc").unwrap();
+
+        let path = write_checksums(std::slice::from_ref(&file)).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(path).unwrap(),
+            format!("{ABC}  202401.parquet\n")
+        );
+    }
+
+    #[test]
+    fn write_checksums_rejects_empty_input() {
+        assert!(matches!(
+            write_checksums(&[]),
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+}
@@ -0,0 +1,228 @@
+use polars::export::arrow::datatypes::{ArrowDataType, ArrowSchema, PhysicalType, PrimitiveType};
+use polars::io::parquet::write::BatchedWriter;
+use polars::prelude::*;
+use polars_parquet::read::ParquetError;
+use polars_parquet::write::{
+    array_to_columns, to_parquet_schema, transverse, CompressionOptions, Compressor, DynIter,
+    DynStreamingIterator, Encoding, FallibleStreamingIterator, SchemaDescriptor, Version,
+    WriteOptions,
+};
+use std::io::Write;
+
+/// Rows per row group when writing a whole `DataFrame`, as in `ParquetWriter::finish`.
+const ROW_GROUP_SIZE: usize = 512 * 512;
+
+/// Parquet writer that compresses one column with its own codec.
+///
+/// Polars' `ParquetWriter` applies a single codec to every column. This wraps its batched
+/// writer (which owns the file layout and footer) and encodes the row groups itself, so
+/// that the pages of `column` are compressed with `column_compression` and all other pages
+/// with the file's `compression`. Without an override it defers to the batched writer.
+pub(super) struct ColumnCompressionWriter<W: Write> {
+    writer: BatchedWriter<W>,
+    parquet_schema: SchemaDescriptor,
+    encodings: Vec<Vec<Encoding>>,
+    /// Write options per top-level column, `None` when no column is overridden
+    column_options: Option<Vec<WriteOptions>>,
+}
+
+impl<W: Write> ColumnCompressionWriter<W> {
+    /// Starts a Parquet file with `schema`, compressing `column` (if present) with the
+    /// codec of `column_override`.
+    pub(super) fn new(
+        writer: W,
+        schema: &Schema,
+        compression: ParquetCompression,
+        column_override: Option<(&str, ParquetCompression)>,
+    ) -> PolarsResult<Self> {
+        let arrow_schema = ArrowSchema::from(schema.to_arrow(true).fields);
+        let parquet_schema = to_parquet_schema(&arrow_schema)?;
+        let encodings = arrow_schema
+            .fields
+            .iter()
+            .map(|field| transverse(&field.data_type, encoding_for))
+            .collect();
+        let column_options = column_override.map(|(column, column_compression)| {
+            arrow_schema
+                .fields
+                .iter()
+                .map(|field| {
+                    let codec = if field.name == column {
+                        column_compression
+                    } else {
+                        compression
+                    };
+                    write_options(codec.into())
+                })
+                .collect()
+        });
+        let writer = ParquetWriter::new(writer)
+            .with_compression(compression)
+            .batched(schema)?;
+
+        Ok(Self {
+            writer,
+            parquet_schema,
+            encodings,
+            column_options,
+        })
+    }
+
+    /// Appends one row group per chunk of `df`, whose chunks must be aligned.
+    pub(super) fn write_batch(&mut self, df: &DataFrame) -> PolarsResult<()> {
+        let Some(column_options) = &self.column_options else {
+            return self.writer.write_batch(df);
+        };
+        for batch in df.iter_chunks(true) {
+            if batch.is_empty() {
+                continue;
+            }
+            let mut columns = Vec::with_capacity(batch.columns().len());
+            for (((array, type_), encoding), options) in batch
+                .columns()
+                .iter()
+                .zip(self.parquet_schema.fields())
+                .zip(&self.encodings)
+                .zip(column_options)
+            {
+                for pages in array_to_columns(array, type_.clone(), *options, encoding)? {
+                    let pages =
+                        pages.map(|page| page.map_err(|e| ParquetError::OutOfSpec(e.to_string())));
+                    let compressed = Compressor::new_from_vec(pages, options.compression, vec![])
+                        .map_err(PolarsError::from);
+                    columns.push(Ok(DynStreamingIterator::new(compressed)));
+                }
+            }
+            self.writer
+                .write_row_groups(vec![DynIter::new(columns.into_iter())])?;
+        }
+        Ok(())
+    }
+
+    /// Writes the footer and returns the size of the file.
+    pub(super) fn finish(&self) -> PolarsResult<u64> {
+        self.writer.finish()
+    }
+}
+
+/// Writes `df` as one Parquet file, like `ParquetWriter::finish`, with the column override
+/// of [`ColumnCompressionWriter`].
+pub(super) fn write_dataframe<W: Write>(
+    writer: W,
+    df: &mut DataFrame,
+    compression: ParquetCompression,
+    column_override: Option<(&str, ParquetCompression)>,
+) -> PolarsResult<u64> {
+    if column_override.is_none() {
+        return ParquetWriter::new(writer)
+            .with_compression(compression)
+            .finish(df);
+    }
+    df.as_single_chunk_par();
+    let mut writer =
+        ColumnCompressionWriter::new(writer, &df.schema(), compression, column_override)?;
+    for offset in (0..df.height()).step_by(ROW_GROUP_SIZE) {
+        writer.write_batch(&df.slice(offset as i64, ROW_GROUP_SIZE))?;
+    }
+    writer.finish()
+}
+
+fn write_options(compression: CompressionOptions) -> WriteOptions {
+    WriteOptions {
+        write_statistics: true,
+        version: Version::V1,
+        compression,
+        data_pagesize_limit: None,
+    }
+}
+
+/// Page encoding of a leaf column, matching the choice of Polars' `ParquetWriter`.
+fn encoding_for(data_type: &ArrowDataType) -> Encoding {
+    match data_type.to_physical_type() {
+        PhysicalType::Dictionary(_)
+        | PhysicalType::LargeBinary
+        | PhysicalType::LargeUtf8
+        | PhysicalType::Utf8View
+        | PhysicalType::BinaryView => Encoding::RleDictionary,
+        PhysicalType::Primitive(
+            PrimitiveType::Float16 | PrimitiveType::Float32 | PrimitiveType::Float64,
+        ) => Encoding::Plain,
+        PhysicalType::Primitive(_) => Encoding::RleDictionary,
+        _ => Encoding::Plain,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars_parquet::parquet::compression::Compression;
+    use std::fs::File;
+
+    /// Compression of each column chunk in the first row group, by column name.
+    fn column_codecs(path: &std::path::Path) -> Vec<(String, Compression)> {
+        let mut reader = ParquetReader::new(File::open(path).unwrap());
+        let metadata = reader.get_metadata().unwrap();
+        metadata.row_groups[0]
+            .columns()
+            .iter()
+            .map(|column| {
+                (
+                    column.descriptor().path_in_schema.join("."),
+                    column.compression(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn write_dataframe_compresses_overridden_column_with_its_codec() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("out.parquet");
+        let mut df = df!(
+            "id" => ["a", "b", "c"],
+            "cfs_raw_xml" => ["<cfs>a</cfs>", "<cfs>b</cfs>", "<cfs>c</cfs>"],
+            "amount" => [1.5, 2.5, 3.5],
+        )
+        .unwrap();
+
+        write_dataframe(
+            File::create(&path).unwrap(),
+            &mut df,
+            ParquetCompression::Zstd(None),
+            Some(("cfs_raw_xml", ParquetCompression::Lz4Raw)),
+        )
+        .unwrap();
+
+        assert_eq!(
+            column_codecs(&path),
+            [
+                ("id".to_string(), Compression::Zstd),
+                ("cfs_raw_xml".to_string(), Compression::Lz4Raw),
+                ("amount".to_string(), Compression::Zstd),
+            ]
+        );
+        let read = ParquetReader::new(File::open(&path).unwrap())
+            .finish()
+            .unwrap();
+        assert!(read.equals(&df));
+    }
+
+    #[test]
+    fn write_dataframe_without_override_uses_file_codec() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("out.parquet");
+        let mut df = df!("id" => ["a"], "cfs_raw_xml" => ["<cfs/>"]).unwrap();
+
+        write_dataframe(
+            File::create(&path).unwrap(),
+            &mut df,
+            ParquetCompression::Snappy,
+            None,
+        )
+        .unwrap();
+
+        assert!(column_codecs(&path)
+            .iter()
+            .all(|(_, codec)| *codec == Compression::Snappy));
+    }
+}
@@ -10,13 +10,21 @@ pub type ParsedContractFolderStatus = ScopeResult;
 pub struct ContractFolderStatusHandler {
     scope: Option<ContractFolderStatusScope>,
     keep_raw_xml: bool,
+    normalize_whitespace: bool,
+    report_unknown_elements: bool,
 }
 
 impl ContractFolderStatusHandler {
-    pub fn new(keep_raw_xml: bool) -> Self {
+    pub fn new(
+        keep_raw_xml: bool,
+        normalize_whitespace: bool,
+        report_unknown_elements: bool,
+    ) -> Self {
         Self {
             scope: None,
             keep_raw_xml,
+            normalize_whitespace,
+            report_unknown_elements,
         }
     }
 
@@ -29,7 +37,12 @@ impl ContractFolderStatusHandler {
     }
 
     pub fn start(&mut self, event: Event) -> AppResult<()> {
-        self.scope = Some(ContractFolderStatusScope::start(event, self.keep_raw_xml)?);
+        self.scope = Some(ContractFolderStatusScope::start(
+            event,
+            self.keep_raw_xml,
+            self.normalize_whitespace,
+            self.report_unknown_elements,
+        )?);
         Ok(())
     }
 
@@ -59,14 +72,14 @@ mod tests {
 
     #[test]
     fn start_marks_handler_active() {
-        let mut handler = ContractFolderStatusHandler::new(true);
+        let mut handler = ContractFolderStatusHandler::new(true, true, false);
         handler.start(start_event()).unwrap();
         assert!(handler.is_active());
     }
 
     #[test]
     fn reset_marks_handler_inactive() {
-        let mut handler = ContractFolderStatusHandler::new(true);
+        let mut handler = ContractFolderStatusHandler::new(true, true, false);
         handler.start(start_event()).unwrap();
         handler.reset();
         assert!(!handler.is_active());
@@ -74,7 +87,7 @@ mod tests {
 
     #[test]
     fn captures_project_name() {
-        let mut handler = ContractFolderStatusHandler::new(true);
+        let mut handler = ContractFolderStatusHandler::new(true, true, false);
         handler.start(start_event()).unwrap();
         handler
             .handle_event(Event::Start(quick_xml::events::BytesStart::new(
@@ -115,7 +128,7 @@ mod tests {
 
     #[test]
     fn captures_status_code() {
-        let mut handler = ContractFolderStatusHandler::new(true);
+        let mut handler = ContractFolderStatusHandler::new(true, true, false);
         handler.start(start_event()).unwrap();
         handler
             .handle_event(Event::Start(quick_xml::events::BytesStart::new(
@@ -143,7 +156,7 @@ mod tests {
 
     #[test]
     fn captures_id() {
-        let mut handler = ContractFolderStatusHandler::new(true);
+        let mut handler = ContractFolderStatusHandler::new(true, true, false);
         handler.start(start_event()).unwrap();
         handler
             .handle_event(Event::Start(quick_xml::events::BytesStart::new(
@@ -171,7 +184,7 @@ mod tests {
 
     #[test]
     fn skip_raw_xml_when_disabled() {
-        let mut handler = ContractFolderStatusHandler::new(false);
+        let mut handler = ContractFolderStatusHandler::new(false, true, false);
         handler.start(start_event()).unwrap();
         handler
             .handle_event(Event::Start(quick_xml::events::BytesStart::new(
@@ -198,9 +211,103 @@ mod tests {
         assert_eq!(captured.cfs_raw_xml, None);
     }
 
+    #[test]
+    fn counts_unmapped_elements_when_reporting() {
+        let mut handler = ContractFolderStatusHandler::new(false, true, true);
+        handler.start(start_event()).unwrap();
+        handler
+            .handle_event(Event::Start(BytesStart::new("cbc:ContractFolderID")))
+            .unwrap();
+        handler
+            .handle_event(Event::Text(BytesText::new("ID-42")))
+            .unwrap();
+        handler
+            .handle_event(Event::End(BytesEnd::new("cbc:ContractFolderID")))
+            .unwrap();
+        for _ in 0..2 {
+            handler
+                .handle_event(Event::Start(BytesStart::new(
+                    "cac-place-ext:SubcontractingConditions",
+                )))
+                .unwrap();
+            handler
+                .handle_event(Event::Empty(BytesStart::new("cbc:MadeUpCode")))
+                .unwrap();
+            handler
+                .handle_event(Event::End(BytesEnd::new(
+                    "cac-place-ext:SubcontractingConditions",
+                )))
+                .unwrap();
+        }
+
+        let captured = handler
+            .handle_end(Event::End(BytesEnd::new("ContractFolderStatus")))
+            .unwrap()
+            .expect("expected captured data");
+
+        assert_eq!(captured.contract_id.as_deref(), Some("ID-42"));
+        assert_eq!(
+            captured.unknown_elements,
+            std::collections::BTreeMap::from([
+                ("MadeUpCode".to_string(), 2),
+                ("SubcontractingConditions".to_string(), 2),
+            ])
+        );
+    }
+
+    #[test]
+    fn does_not_count_elements_when_not_reporting() {
+        let mut handler = ContractFolderStatusHandler::new(false, true, false);
+        handler.start(start_event()).unwrap();
+        handler
+            .handle_event(Event::Empty(BytesStart::new("cbc:MadeUpCode")))
+            .unwrap();
+
+        let captured = handler
+            .handle_end(Event::End(BytesEnd::new("ContractFolderStatus")))
+            .unwrap()
+            .expect("expected captured data");
+
+        assert!(captured.unknown_elements.is_empty());
+    }
+
+    #[test]
+    fn captures_received_tender_quantity() {
+        let mut handler = ContractFolderStatusHandler::new(false, true, false);
+        handler.start(start_event()).unwrap();
+        handler
+            .handle_event(Event::Start(BytesStart::new("cac:TenderResult")))
+            .unwrap();
+        handler
+            .handle_event(Event::Start(BytesStart::new("ReceivedTenderQuantity")))
+            .unwrap();
+        handler
+            .handle_event(Event::Text(BytesText::new("5")))
+            .unwrap();
+        handler
+            .handle_event(Event::End(BytesEnd::new("ReceivedTenderQuantity")))
+            .unwrap();
+        handler
+            .handle_event(Event::End(BytesEnd::new("cac:TenderResult")))
+            .unwrap();
+
+        let captured = handler
+            .handle_end(Event::End(BytesEnd::new("ContractFolderStatus")))
+            .unwrap()
+            .expect("expected captured data");
+
+        assert_eq!(captured.tender_results.len(), 1);
+        assert_eq!(
+            captured.tender_results[0]
+                .result_received_tender_quantity
+                .as_deref(),
+            Some("5")
+        );
+    }
+
     #[test]
     fn captures_multiple_procurement_project_lots() {
-        let mut handler = ContractFolderStatusHandler::new(true);
+        let mut handler = ContractFolderStatusHandler::new(true, true, false);
         handler.start(start_event()).unwrap();
 
         handler
@@ -310,4 +417,357 @@ mod tests {
             Some("CPV-B1".to_string())
         );
     }
+
+    #[test]
+    fn captures_received_tender_statistics_per_result() {
+        let mut handler = ContractFolderStatusHandler::new(false, true, false);
+        handler.start(start_event()).unwrap();
+
+        for (lot_id, quantity, lower, higher) in [
+            ("1", "3", Some("900.00"), Some("1200.50")),
+            ("2", "1", None, None),
+        ] {
+            handler
+                .handle_event(Event::Start(BytesStart::new("cac:TenderResult")))
+                .unwrap();
+            let mut fields = vec![("cbc:ReceivedTenderQuantity", quantity, None)];
+            if let (Some(lower), Some(higher)) = (lower, higher) {
+                fields.push(("cbc:LowerTenderAmount", lower, Some("EUR")));
+                fields.push(("cbc:HigherTenderAmount", higher, Some("USD")));
+            }
+            for (tag, value, currency) in fields {
+                let mut start = BytesStart::new(tag);
+                if let Some(currency) = currency {
+                    start.push_attribute(("currencyID", currency));
+                }
+                handler.handle_event(Event::Start(start)).unwrap();
+                handler
+                    .handle_event(Event::Text(BytesText::new(value)))
+                    .unwrap();
+                handler
+                    .handle_event(Event::End(BytesEnd::new(tag)))
+                    .unwrap();
+            }
+            handler
+                .handle_event(Event::Start(BytesStart::new("cac:AwardedTenderedProject")))
+                .unwrap();
+            handler
+                .handle_event(Event::Start(BytesStart::new("cbc:ProcurementProjectLotID")))
+                .unwrap();
+            handler
+                .handle_event(Event::Text(BytesText::new(lot_id)))
+                .unwrap();
+            handler
+                .handle_event(Event::End(BytesEnd::new("cbc:ProcurementProjectLotID")))
+                .unwrap();
+            handler
+                .handle_event(Event::End(BytesEnd::new("cac:AwardedTenderedProject")))
+                .unwrap();
+            handler
+                .handle_event(Event::End(BytesEnd::new("cac:TenderResult")))
+                .unwrap();
+        }
+
+        let captured = handler
+            .handle_end(Event::End(BytesEnd::new("ContractFolderStatus")))
+            .unwrap()
+            .expect("expected captured data");
+
+        assert_eq!(captured.tender_results.len(), 2);
+        let first = &captured.tender_results[0];
+        assert_eq!(first.result_lot_id.as_deref(), Some("1"));
+        assert_eq!(first.result_received_tender_quantity.as_deref(), Some("3"));
+        assert_eq!(first.result_lower_tender_amount.as_deref(), Some("900.00"));
+        assert_eq!(first.result_lower_tender_currency.as_deref(), Some("EUR"));
+        assert_eq!(
+            first.result_higher_tender_amount.as_deref(),
+            Some("1200.50")
+        );
+        assert_eq!(first.result_higher_tender_currency.as_deref(), Some("USD"));
+        let second = &captured.tender_results[1];
+        assert_eq!(second.result_lot_id.as_deref(), Some("2"));
+        assert_eq!(second.result_received_tender_quantity.as_deref(), Some("1"));
+        assert_eq!(second.result_lower_tender_amount, None);
+        assert_eq!(second.result_higher_tender_currency, None);
+    }
+
+    #[test]
+    fn captures_open_tender_event_date() {
+        let mut handler = ContractFolderStatusHandler::new(false, true, false);
+        handler.start(start_event()).unwrap();
+        handler
+            .handle_event(Event::Start(BytesStart::new("cac:TenderingProcess")))
+            .unwrap();
+        handler
+            .handle_event(Event::Start(BytesStart::new("cac:OpenTenderEvent")))
+            .unwrap();
+        handler
+            .handle_event(Event::Start(BytesStart::new("cbc:OccurrenceDate")))
+            .unwrap();
+        handler
+            .handle_event(Event::Text(BytesText::new("2023-03-15")))
+            .unwrap();
+        handler
+            .handle_event(Event::End(BytesEnd::new("cbc:OccurrenceDate")))
+            .unwrap();
+        handler
+            .handle_event(Event::End(BytesEnd::new("cac:OpenTenderEvent")))
+            .unwrap();
+        handler
+            .handle_event(Event::End(BytesEnd::new("cac:TenderingProcess")))
+            .unwrap();
+
+        let captured = handler
+            .handle_end(Event::End(BytesEnd::new("ContractFolderStatus")))
+            .unwrap()
+            .expect("expected captured data");
+
+        assert_eq!(captured.process_open_date.as_deref(), Some("2023-03-15"));
+        assert_eq!(captured.process_end_date, None);
+    }
+
+    #[test]
+    fn captures_deadline_period_start_and_end_dates() {
+        let mut handler = ContractFolderStatusHandler::new(false, true, false);
+        handler.start(start_event()).unwrap();
+        handler
+            .handle_event(Event::Start(BytesStart::new("cac:TenderingProcess")))
+            .unwrap();
+        handler
+            .handle_event(Event::Start(BytesStart::new(
+                "cac:TenderSubmissionDeadlinePeriod",
+            )))
+            .unwrap();
+        for (tag, value) in [
+            ("cbc:StartDate", "2023-03-01"),
+            ("cbc:EndDate", "2023-03-31"),
+        ] {
+            handler
+                .handle_event(Event::Start(BytesStart::new(tag)))
+                .unwrap();
+            handler
+                .handle_event(Event::Text(BytesText::new(value)))
+                .unwrap();
+            handler
+                .handle_event(Event::End(BytesEnd::new(tag)))
+                .unwrap();
+        }
+        handler
+            .handle_event(Event::End(BytesEnd::new(
+                "cac:TenderSubmissionDeadlinePeriod",
+            )))
+            .unwrap();
+        // Dates of sibling periods are outside the deadline period and must be ignored.
+        handler
+            .handle_event(Event::Start(BytesStart::new(
+                "cac:DocumentAvailabilityPeriod",
+            )))
+            .unwrap();
+        for (tag, value) in [
+            ("cbc:StartDate", "2023-02-01"),
+            ("cbc:EndDate", "2023-02-28"),
+        ] {
+            handler
+                .handle_event(Event::Start(BytesStart::new(tag)))
+                .unwrap();
+            handler
+                .handle_event(Event::Text(BytesText::new(value)))
+                .unwrap();
+            handler
+                .handle_event(Event::End(BytesEnd::new(tag)))
+                .unwrap();
+        }
+        handler
+            .handle_event(Event::End(BytesEnd::new("cac:DocumentAvailabilityPeriod")))
+            .unwrap();
+        handler
+            .handle_event(Event::End(BytesEnd::new("cac:TenderingProcess")))
+            .unwrap();
+
+        let captured = handler
+            .handle_end(Event::End(BytesEnd::new("ContractFolderStatus")))
+            .unwrap()
+            .expect("expected captured data");
+
+        assert_eq!(captured.process_start_date.as_deref(), Some("2023-03-01"));
+        assert_eq!(captured.process_end_date.as_deref(), Some("2023-03-31"));
+        assert_eq!(captured.process_open_date, None);
+    }
+
+    #[test]
+    fn captures_planned_period_duration_with_unit() {
+        let mut handler = ContractFolderStatusHandler::new(false, true, false);
+        handler.start(start_event()).unwrap();
+        handler
+            .handle_event(Event::Start(BytesStart::new("cac:ProcurementProject")))
+            .unwrap();
+        handler
+            .handle_event(Event::Start(BytesStart::new("cac:PlannedPeriod")))
+            .unwrap();
+        handler
+            .handle_event(Event::Start(
+                BytesStart::new("cbc:DurationMeasure").with_attributes([("unitCode", "MON")]),
+            ))
+            .unwrap();
+        handler
+            .handle_event(Event::Text(BytesText::new("24")))
+            .unwrap();
+        handler
+            .handle_event(Event::End(BytesEnd::new("cbc:DurationMeasure")))
+            .unwrap();
+        handler
+            .handle_event(Event::End(BytesEnd::new("cac:PlannedPeriod")))
+            .unwrap();
+        // A DurationMeasure outside PlannedPeriod is not the contract duration.
+        handler
+            .handle_event(Event::Start(BytesStart::new("cbc:DurationMeasure")))
+            .unwrap();
+        handler
+            .handle_event(Event::Text(BytesText::new("99")))
+            .unwrap();
+        handler
+            .handle_event(Event::End(BytesEnd::new("cbc:DurationMeasure")))
+            .unwrap();
+        handler
+            .handle_event(Event::End(BytesEnd::new("cac:ProcurementProject")))
+            .unwrap();
+
+        let captured = handler
+            .handle_end(Event::End(BytesEnd::new("ContractFolderStatus")))
+            .unwrap()
+            .expect("expected captured data");
+
+        assert_eq!(captured.project_duration_measure.as_deref(), Some("24"));
+        assert_eq!(captured.project_duration_unit.as_deref(), Some("MON"));
+    }
+
+    /// Feeds a `<cac:TenderResult>` whose `AwardedTenderedProject` holds `lot_id_event`
+    /// (if any) and returns the resulting tender result rows.
+    fn tender_results_with_lot_id(
+        lot_id_event: Option<Vec<Event<'static>>>,
+    ) -> Vec<crate::models::TenderResultRow> {
+        let mut handler = ContractFolderStatusHandler::new(false, true, false);
+        handler.start(start_event()).unwrap();
+        handler
+            .handle_event(Event::Start(BytesStart::new("cac:TenderResult")))
+            .unwrap();
+        handler
+            .handle_event(Event::Start(BytesStart::new("cac:AwardedTenderedProject")))
+            .unwrap();
+        for event in lot_id_event.unwrap_or_default() {
+            handler.handle_event(event).unwrap();
+        }
+        handler
+            .handle_event(Event::End(BytesEnd::new("cac:AwardedTenderedProject")))
+            .unwrap();
+        handler
+            .handle_event(Event::End(BytesEnd::new("cac:TenderResult")))
+            .unwrap();
+
+        handler
+            .handle_end(Event::End(BytesEnd::new("ContractFolderStatus")))
+            .unwrap()
+            .expect("expected captured data")
+            .tender_results
+    }
+
+    #[test]
+    fn empty_result_lot_id_defaults_to_zero() {
+        let results = tender_results_with_lot_id(Some(vec![Event::Empty(BytesStart::new(
+            "cbc:ProcurementProjectLotID",
+        ))]));
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].result_lot_id.as_deref(), Some("0"));
+    }
+
+    #[test]
+    fn missing_result_lot_id_defaults_to_zero() {
+        let results = tender_results_with_lot_id(None);
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].result_id.as_deref(), Some("1"));
+        assert_eq!(results[0].result_lot_id.as_deref(), Some("0"));
+    }
+
+    #[test]
+    fn result_lot_id_text_is_kept() {
+        let results = tender_results_with_lot_id(Some(vec![
+            Event::Start(BytesStart::new("cbc:ProcurementProjectLotID")),
+            Event::Text(BytesText::new("3")),
+            Event::End(BytesEnd::new("cbc:ProcurementProjectLotID")),
+        ]));
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].result_lot_id.as_deref(), Some("3"));
+    }
+
+    #[test]
+    fn captures_realized_location_subentity_alongside_country_code() {
+        let mut handler = ContractFolderStatusHandler::new(false, true, false);
+        handler.start(start_event()).unwrap();
+        let events = vec![
+            Event::Start(BytesStart::new("cac:ProcurementProject")),
+            Event::Start(BytesStart::new("cac:RealizedLocation")),
+            Event::Start(BytesStart::new("cbc:CountrySubentity")),
+            Event::Text(BytesText::new("Madrid")),
+            Event::End(BytesEnd::new("cbc:CountrySubentity")),
+            Event::Start(
+                BytesStart::new("cbc:CountrySubentityCode")
+                    .with_attributes([("listURI", "urn:nuts")]),
+            ),
+            Event::Text(BytesText::new("ES300")),
+            Event::End(BytesEnd::new("cbc:CountrySubentityCode")),
+            Event::Start(BytesStart::new("cac:Address")),
+            Event::Start(BytesStart::new("cac:Country")),
+            Event::Start(BytesStart::new("cbc:IdentificationCode")),
+            Event::Text(BytesText::new("ES")),
+            Event::End(BytesEnd::new("cbc:IdentificationCode")),
+            Event::End(BytesEnd::new("cac:Country")),
+            Event::End(BytesEnd::new("cac:Address")),
+            Event::End(BytesEnd::new("cac:RealizedLocation")),
+            Event::End(BytesEnd::new("cac:ProcurementProject")),
+            // A lot's realized location goes to the lot, not the project values.
+            Event::Start(BytesStart::new("cac:ProcurementProjectLot")),
+            Event::Start(BytesStart::new("cac:ProcurementProject")),
+            Event::Start(BytesStart::new("cac:RealizedLocation")),
+            Event::Start(
+                BytesStart::new("cbc:CountrySubentityCode")
+                    .with_attributes([("listURI", "urn:nuts")]),
+            ),
+            Event::Text(BytesText::new("ES511")),
+            Event::End(BytesEnd::new("cbc:CountrySubentityCode")),
+            Event::End(BytesEnd::new("cac:RealizedLocation")),
+            Event::End(BytesEnd::new("cac:ProcurementProject")),
+            Event::End(BytesEnd::new("cac:ProcurementProjectLot")),
+        ];
+        for event in events {
+            handler.handle_event(event).unwrap();
+        }
+
+        let captured = handler
+            .handle_end(Event::End(BytesEnd::new("ContractFolderStatus")))
+            .unwrap()
+            .expect("expected captured data");
+
+        assert_eq!(
+            captured.project_country_subentity.as_deref(),
+            Some("Madrid")
+        );
+        assert_eq!(
+            captured.project_country_subentity_code.as_deref(),
+            Some("ES300")
+        );
+        assert_eq!(
+            captured.project_country_subentity_code_list_uri.as_deref(),
+            Some("urn:nuts")
+        );
+        assert_eq!(captured.project_country_code.as_deref(), Some("ES"));
+        let lot = &captured.project_lots[0];
+        assert_eq!(lot.country_subentity_code.as_deref(), Some("ES511"));
+        assert_eq!(
+            lot.country_subentity_code_list_uri.as_deref(),
+            Some("urn:nuts")
+        );
+    }
 }
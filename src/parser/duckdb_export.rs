@@ -0,0 +1,204 @@
+//! Loads the parsed periods into a DuckDB database (`duckdb` feature).
+//!
+//! Every period written by `parse_xmls` is also appended to the database at `duckdb_path`:
+//! one `entries` row per entry, with each struct column flattened into `{column}_{field}`
+//! columns, and one child table per list column (`project_lots`, `tender_results`,
+//! `awarding_criteria`, `categories`) holding a row per list element; elements that are not
+//! structs, such as the `categories` terms, go to a single `value` column. Every table has a
+//! `period` column, and the child rows point at their entry through `entry_id`.
+
+use crate::errors::{AppError, AppResult};
+use crate::query::{quote_identifier, quote_literal};
+use duckdb::Connection;
+use polars::prelude::*;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// DuckDB allows a single open database per file; periods parsed concurrently take turns.
+static DATABASE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Replaces the rows of `period` in the database at `db_path` with the entries in the
+/// period's Parquet `files`.
+///
+/// The database and its tables are created on first use. A column that appears in a later
+/// period (e.g. `cfs_raw_xml`) is added to the existing table, and rows of earlier periods
+/// get nulls for it. `entry_id` comes from a sequence, so it is unique across periods and
+/// reprocessing a period gives its entries new ids. Returns the number of entries loaded.
+///
+/// # Errors
+///
+/// Returns `ParseError` if the schema of the first file cannot be read, and `IoError` if
+/// DuckDB cannot open the database or rejects a statement; the period's previous rows are
+/// kept in that case.
+pub(crate) fn load_period(db_path: &Path, period: &str, files: &[PathBuf]) -> AppResult<usize> {
+    let Some(first) = files.first() else {
+        return Ok(0);
+    };
+    let schema = LazyFrame::scan_parquet(first, ScanArgsParquet::default())
+        .and_then(|lf| lf.schema())
+        .map_err(|e| {
+            AppError::ParseError(format!("Failed to read Parquet schema of {first:?}: {e}"))
+        })?;
+
+    let _guard = DATABASE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut conn = Connection::open(db_path).map_err(|e| {
+        AppError::IoError(format!(
+            "Failed to open DuckDB database {}: {e}",
+            db_path.display()
+        ))
+    })?;
+    let tx = conn.transaction().map_err(database_error)?;
+
+    let sources = files
+        .iter()
+        .map(|path| quote_literal(&path.to_string_lossy()))
+        .collect::<Vec<_>>()
+        .join(", ");
+    tx.execute_batch(&format!(
+        "CREATE SEQUENCE IF NOT EXISTS entry_id_seq;
+         CREATE TEMP TABLE period_source AS
+             SELECT nextval('entry_id_seq') AS entry_id, *
+             FROM read_parquet([{sources}], union_by_name = true);"
+    ))
+    .map_err(database_error)?;
+
+    let period = quote_literal(period);
+    for table in period_tables(&tx)? {
+        tx.execute_batch(&format!(
+            "DELETE FROM {} WHERE period = {period};",
+            quote_identifier(&table)
+        ))
+        .map_err(database_error)?;
+    }
+
+    let mut entry_columns = Vec::new();
+    let mut child_tables = Vec::new();
+    for (name, dtype) in schema.iter() {
+        match dtype {
+            DataType::List(inner) => child_tables.push((name.as_str(), inner.as_ref())),
+            _ => flatten_column(&quote_identifier(name), name, dtype, &mut entry_columns),
+        }
+    }
+    append_rows(
+        &tx,
+        "entries",
+        &format!(
+            "SELECT {period} AS period, entry_id, {} FROM period_source",
+            entry_columns.join(", ")
+        ),
+    )?;
+    for (table, element) in child_tables {
+        let mut columns = Vec::new();
+        match element {
+            DataType::Struct(fields) => {
+                for field in fields {
+                    let expr = format!("item.{}", quote_identifier(field.name()));
+                    flatten_column(&expr, field.name(), field.data_type(), &mut columns);
+                }
+            }
+            _ => columns.push("item AS value".to_string()),
+        }
+        append_rows(
+            &tx,
+            table,
+            &format!(
+                "SELECT {period} AS period, entry_id, {} FROM (
+                     SELECT entry_id, UNNEST({}) AS item FROM period_source
+                 )",
+                columns.join(", "),
+                quote_identifier(table)
+            ),
+        )?;
+    }
+
+    let entries: i64 = tx
+        .query_row("SELECT count(*) FROM period_source", [], |row| row.get(0))
+        .map_err(database_error)?;
+    tx.execute_batch("DROP TABLE period_source;")
+        .map_err(database_error)?;
+    tx.commit().map_err(database_error)?;
+    Ok(entries as usize)
+}
+
+/// Adds `expr AS name` to `columns`, or one column per field (`{name}_{field}`, recursively)
+/// when `dtype` is a struct.
+fn flatten_column(expr: &str, name: &str, dtype: &DataType, columns: &mut Vec<String>) {
+    match dtype {
+        DataType::Struct(fields) => {
+            for field in fields {
+                flatten_column(
+                    &format!("{expr}.{}", quote_identifier(field.name())),
+                    &format!("{name}_{}", field.name()),
+                    field.data_type(),
+                    columns,
+                );
+            }
+        }
+        _ => columns.push(format!("{expr} AS {}", quote_identifier(name))),
+    }
+}
+
+/// Appends the rows of `select` to `table`, creating the table from them on first use and
+/// adding any column it lacks.
+fn append_rows(conn: &Connection, table: &str, select: &str) -> AppResult<()> {
+    conn.execute_batch(&format!(
+        "CREATE OR REPLACE TEMP TABLE new_rows AS {select};"
+    ))
+    .map_err(database_error)?;
+
+    let exists: bool = conn
+        .query_row(
+            "SELECT count(*) > 0 FROM duckdb_tables() WHERE NOT temporary AND table_name = ?",
+            [table],
+            |row| row.get(0),
+        )
+        .map_err(database_error)?;
+    let table = quote_identifier(table);
+    if exists {
+        let existing = describe(conn, &table)?;
+        for (name, column_type) in describe(conn, "new_rows")? {
+            if !existing.iter().any(|(existing, _)| *existing == name) {
+                conn.execute_batch(&format!(
+                    "ALTER TABLE {table} ADD COLUMN {} {column_type};",
+                    quote_identifier(&name)
+                ))
+                .map_err(database_error)?;
+            }
+        }
+        conn.execute_batch(&format!(
+            "INSERT INTO {table} BY NAME SELECT * FROM new_rows;"
+        ))
+        .map_err(database_error)?;
+    } else {
+        conn.execute_batch(&format!("CREATE TABLE {table} AS SELECT * FROM new_rows;"))
+            .map_err(database_error)?;
+    }
+    conn.execute_batch("DROP TABLE new_rows;")
+        .map_err(database_error)
+}
+
+/// Returns the name and type of each column of `relation`.
+fn describe(conn: &Connection, relation: &str) -> AppResult<Vec<(String, String)>> {
+    let mut stmt = conn
+        .prepare(&format!("DESCRIBE {relation}"))
+        .map_err(database_error)?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(database_error)?;
+    rows.collect::<Result<_, _>>().map_err(database_error)
+}
+
+/// Lists the tables of the database that hold rows per period.
+fn period_tables(conn: &Connection) -> AppResult<Vec<String>> {
+    let mut stmt = conn
+        .prepare("SELECT DISTINCT table_name FROM duckdb_columns() WHERE column_name = 'period'")
+        .map_err(database_error)?;
+    let rows = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(database_error)?;
+    rows.collect::<Result<_, _>>().map_err(database_error)
+}
+
+fn database_error(e: duckdb::Error) -> AppError {
+    AppError::IoError(format!("DuckDB export failed: {e}"))
+}
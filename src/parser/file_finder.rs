@@ -1,10 +1,14 @@
+use crate::downloader::validate_period_format;
 use crate::errors::{AppError, AppResult};
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::PathBuf;
+use tracing::{debug, info};
 
 /// Finds all XML/Atom files organized by subdirectory.
 ///
 /// This function scans the immediate subdirectories of the given path and
-/// recursively collects all `.xml` and `.atom` files within each subdirectory.
+/// recursively collects all `.xml` and `.atom` files (plain or gzip-compressed as
+/// `.xml.gz`/`.atom.gz`) within each subdirectory.
 /// Files in the top-level directory are ignored.
 ///
 /// # Returns
@@ -46,23 +50,134 @@ pub fn find_xmls(path: &std::path::Path) -> AppResult<Vec<(String, Vec<PathBuf>)
     Ok(out)
 }
 
-/// Recursively collects `.xml` or `.atom` files in a directory (including subdirs).
+/// How a directory name was matched to a period, best first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum PeriodDirMatch {
+    Exact,
+    CaseInsensitive,
+    Fuzzy,
+}
+
+/// Matches the subdirectories found by [`find_xmls`] to the requested `periods`, returning
+/// one `(period, files)` pair per matched period, sorted by period.
+///
+/// A directory matches a period when its name equals it, equals it ignoring case, or
+/// (fuzzy) contains it as its only period-shaped run of digits, the same `YYYY`/`YYYYMM`
+/// digits the ZIP names carry (`licitaciones_202403` or `202403 (copy)` match `202403`).
+/// Each period takes its best-matching directory, and fuzzy matches are logged. With
+/// `strict`, only exact names match. Directories left unmatched (such as `__MACOSX`) are
+/// listed in a single debug log.
+///
+/// # Errors
+///
+/// Returns `InvalidInput` if two directories match one period equally well.
+pub fn match_period_dirs<'a>(
+    subdirs: Vec<(String, Vec<PathBuf>)>,
+    periods: impl IntoIterator<Item = &'a String>,
+    strict: bool,
+) -> AppResult<Vec<(String, Vec<PathBuf>)>> {
+    let periods: BTreeSet<&str> = periods.into_iter().map(String::as_str).collect();
+    let mut candidates: BTreeMap<String, Vec<(PeriodDirMatch, String, Vec<PathBuf>)>> =
+        BTreeMap::new();
+    let mut unmatched = Vec::new();
+    for (name, files) in subdirs {
+        match match_period_dir(&name, &periods, strict) {
+            Some((period, kind)) => candidates
+                .entry(period.to_string())
+                .or_default()
+                .push((kind, name, files)),
+            None => unmatched.push(name),
+        }
+    }
+
+    let mut matched = Vec::with_capacity(candidates.len());
+    for (period, mut dirs) in candidates {
+        dirs.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+        let best = dirs[0].0;
+        let tied: Vec<&str> = dirs
+            .iter()
+            .filter(|(kind, _, _)| *kind == best)
+            .map(|(_, name, _)| name.as_str())
+            .collect();
+        if tied.len() > 1 {
+            return Err(AppError::InvalidInput(format!(
+                "Period {period} matches several extracted directories: {}",
+                tied.join(", ")
+            )));
+        }
+        let mut dirs = dirs.into_iter();
+        let (kind, name, files) = dirs.next().expect("every candidate list is non-empty");
+        if kind == PeriodDirMatch::Fuzzy {
+            info!(period = %period, directory = %name, "Matched extracted directory to period by name pattern");
+        }
+        unmatched.extend(dirs.map(|(_, name, _)| name));
+        matched.push((period, files));
+    }
+
+    if !unmatched.is_empty() {
+        unmatched.sort();
+        debug!(
+            directories = %unmatched.join(", "),
+            "Extracted directories not matching any requested period"
+        );
+    }
+    Ok(matched)
+}
+
+/// Returns the period `name` matches and how, if any (see [`match_period_dirs`]).
+fn match_period_dir<'a>(
+    name: &str,
+    periods: &BTreeSet<&'a str>,
+    strict: bool,
+) -> Option<(&'a str, PeriodDirMatch)> {
+    if let Some(period) = periods.get(name) {
+        return Some((period, PeriodDirMatch::Exact));
+    }
+    if strict {
+        return None;
+    }
+    if let Some(period) = periods.iter().find(|p| p.eq_ignore_ascii_case(name)) {
+        return Some((period, PeriodDirMatch::CaseInsensitive));
+    }
+    let mut runs = name
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|run| validate_period_format(run).is_ok());
+    match (runs.next(), runs.next()) {
+        (Some(run), None) => periods.get(run).map(|p| (*p, PeriodDirMatch::Fuzzy)),
+        _ => None,
+    }
+}
+
+/// Recursively collects `.xml` or `.atom` files, plain or with a `.gz` suffix, in a
+/// directory (including subdirs), sorted by file name within each directory.
 pub(crate) fn collect_xmls(dir: &std::path::Path) -> Vec<PathBuf> {
     // Pre-allocate with conservative estimate (usually 1-20 XML files per directory)
     let mut v = Vec::with_capacity(20);
-    let walker = walkdir::WalkDir::new(dir).into_iter();
+    let walker = walkdir::WalkDir::new(dir).sort_by_file_name().into_iter();
     for entry in walker.flatten() {
-        if entry.file_type().is_file() {
-            if let Some(ext) = entry.path().extension().and_then(|e| e.to_str()) {
-                if ext.eq_ignore_ascii_case("xml") || ext.eq_ignore_ascii_case("atom") {
-                    v.push(entry.path().to_path_buf());
-                }
-            }
+        if entry.file_type().is_file() && is_xml_file(entry.path()) {
+            v.push(entry.path().to_path_buf());
         }
     }
     v
 }
 
+/// Returns whether `path` has an `.xml`/`.atom` extension, optionally followed by `.gz`
+/// (case-insensitive).
+fn is_xml_file(path: &std::path::Path) -> bool {
+    let is_xml_ext =
+        |ext: &std::ffi::OsStr| ext.eq_ignore_ascii_case("xml") || ext.eq_ignore_ascii_case("atom");
+    match path.extension() {
+        Some(ext) if ext.eq_ignore_ascii_case("gz") => path
+            .file_stem()
+            .map(std::path::Path::new)
+            .and_then(|stem| stem.extension())
+            .is_some_and(is_xml_ext),
+        Some(ext) => is_xml_ext(ext),
+        None => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -126,6 +241,23 @@ mod tests {
         assert_eq!(files.len(), 6);
     }
 
+    #[test]
+    fn test_collect_xmls_includes_gzipped_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let base_dir = temp_dir.path().join("base");
+        fs::create_dir_all(&base_dir).unwrap();
+
+        create_test_xml_file(&base_dir.join("feed.xml.gz"), "");
+        create_test_xml_file(&base_dir.join("FEED.ATOM.GZ"), "");
+        create_test_xml_file(&base_dir.join("notes.txt.gz"), "");
+        create_test_xml_file(&base_dir.join("archive.gz"), "");
+
+        let files = collect_xmls(&base_dir);
+        assert_eq!(files.len(), 2);
+        assert!(files.iter().any(|p| p.ends_with("feed.xml.gz")));
+        assert!(files.iter().any(|p| p.ends_with("FEED.ATOM.GZ")));
+    }
+
     #[test]
     fn test_find_xmls_with_subdirectories() {
         let temp_dir = TempDir::new().unwrap();
@@ -198,4 +330,98 @@ mod tests {
         let (_, files) = &result[0];
         assert_eq!(files.len(), 3);
     }
+
+    fn period_dirs(names: &[&str]) -> Vec<(String, Vec<PathBuf>)> {
+        names
+            .iter()
+            .map(|name| {
+                (
+                    name.to_string(),
+                    vec![PathBuf::from(name).join("feed.atom")],
+                )
+            })
+            .collect()
+    }
+
+    fn periods(periods: &[&str]) -> Vec<String> {
+        periods.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn match_period_dirs_prefers_exact_names() {
+        let periods = periods(&["202401", "202402"]);
+        let matched = match_period_dirs(
+            period_dirs(&["202402", "__MACOSX", "202401", "202401_old", "202403"]),
+            &periods,
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(
+            matched,
+            [
+                (
+                    "202401".to_string(),
+                    vec![PathBuf::from("202401/feed.atom")]
+                ),
+                (
+                    "202402".to_string(),
+                    vec![PathBuf::from("202402/feed.atom")]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn match_period_dirs_accepts_fuzzy_names_unless_strict() {
+        let periods = periods(&["2023", "202403"]);
+        let dirs = period_dirs(&[
+            "licitacionesPerfilesContratanteCompleto3_202403",
+            "2023 (copy)",
+        ]);
+
+        let matched = match_period_dirs(dirs.clone(), &periods, false).unwrap();
+        assert_eq!(
+            matched,
+            [
+                (
+                    "2023".to_string(),
+                    vec![PathBuf::from("2023 (copy)/feed.atom")]
+                ),
+                (
+                    "202403".to_string(),
+                    vec![PathBuf::from(
+                        "licitacionesPerfilesContratanteCompleto3_202403/feed.atom"
+                    )]
+                ),
+            ]
+        );
+        assert!(match_period_dirs(dirs, &periods, true).unwrap().is_empty());
+    }
+
+    #[test]
+    fn match_period_dirs_ignores_names_with_several_periods() {
+        let periods = periods(&["2023", "2024"]);
+        let matched = match_period_dirs(period_dirs(&["2023_2024"]), &periods, false).unwrap();
+        assert!(matched.is_empty());
+    }
+
+    #[test]
+    fn match_period_dirs_rejects_ambiguous_matches() {
+        let periods = periods(&["202403"]);
+        let result = match_period_dirs(
+            period_dirs(&["backup_202403", "202403 (copy)"]),
+            &periods,
+            false,
+        );
+        match result {
+            Err(AppError::InvalidInput(message)) => {
+                assert!(
+                    message.contains("202403 (copy), backup_202403"),
+                    "{message}"
+                )
+            }
+            other => panic!("expected an ambiguity error, got {other:?}"),
+        }
+    }
 }
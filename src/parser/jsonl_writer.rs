@@ -0,0 +1,261 @@
+use crate::errors::{AppError, AppResult};
+use crate::models::{
+    AwardingCriterion, Entry, ProcurementProjectLot, StatusCode, TenderResultRow,
+    TermsFundingProgram,
+};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use super::normalize::parse_sme_indicator;
+
+/// JSON Lines record for one `Entry`, mirroring the Parquet schema.
+///
+/// Top-level keys match the Parquet column names and nested objects match the struct
+/// fields, so `project_lots`, `tender_results`, and `awarding_criteria` are JSON arrays of
+/// objects.
+#[derive(Serialize)]
+pub(super) struct EntryRecord<'a> {
+    id: &'a Option<String>,
+    title: &'a Option<String>,
+    link: &'a Option<String>,
+    summary: &'a Option<String>,
+    updated: &'a Option<String>,
+    categories: &'a [String],
+    author: &'a Option<String>,
+    is_deleted: bool,
+    status: &'a StatusCode,
+    contract_id: &'a Option<String>,
+    contracting_party: ContractingPartyRecord<'a>,
+    project: ProjectRecord<'a>,
+    project_lots: &'a [ProcurementProjectLot],
+    tender_results: Vec<TenderResultRecord<'a>>,
+    terms_funding_program: &'a TermsFundingProgram,
+    awarding_criteria: &'a [AwardingCriterion],
+    process: ProcessRecord<'a>,
+    feed_metadata: FeedMetadataRecord<'a>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cfs_raw_xml: Option<&'a Option<String>>,
+}
+
+#[derive(Serialize)]
+struct ContractingPartyRecord<'a> {
+    name: &'a Option<String>,
+    id: &'a Option<String>,
+    website: &'a Option<String>,
+    type_code: &'a Option<String>,
+    type_code_list_uri: &'a Option<String>,
+    activity_code: &'a Option<String>,
+    activity_code_list_uri: &'a Option<String>,
+    city: &'a Option<String>,
+    zip: &'a Option<String>,
+    country_code: &'a Option<String>,
+    country_code_list_uri: &'a Option<String>,
+}
+
+#[derive(Serialize)]
+struct ProjectRecord<'a> {
+    name: &'a Option<String>,
+    type_code: &'a Option<String>,
+    type_code_list_uri: &'a Option<String>,
+    sub_type_code: &'a Option<String>,
+    sub_type_code_list_uri: &'a Option<String>,
+    total_amount: &'a Option<String>,
+    total_currency: &'a Option<String>,
+    tax_exclusive_amount: &'a Option<String>,
+    tax_exclusive_currency: &'a Option<String>,
+    cpv_code: &'a Option<String>,
+    cpv_code_list_uri: &'a Option<String>,
+    country_code: &'a Option<String>,
+    country_code_list_uri: &'a Option<String>,
+    duration_measure: &'a Option<String>,
+    duration_unit: &'a Option<String>,
+    country_subentity: &'a Option<String>,
+    country_subentity_code: &'a Option<String>,
+    country_subentity_code_list_uri: &'a Option<String>,
+}
+
+#[derive(Serialize)]
+struct ProcessRecord<'a> {
+    start_date: &'a Option<String>,
+    end_date: &'a Option<String>,
+    open_date: &'a Option<String>,
+    procedure_code: &'a Option<String>,
+    procedure_code_list_uri: &'a Option<String>,
+    urgency_code: &'a Option<String>,
+    urgency_code_list_uri: &'a Option<String>,
+}
+
+/// A tender result with the `result_sme_awarded` boolean the Parquet struct derives.
+#[derive(Serialize)]
+struct TenderResultRecord<'a> {
+    #[serde(flatten)]
+    row: &'a TenderResultRow,
+    result_sme_awarded: Option<bool>,
+}
+
+#[derive(Serialize)]
+struct FeedMetadataRecord<'a> {
+    id: &'a Option<String>,
+    title: &'a Option<String>,
+    updated: &'a Option<String>,
+}
+
+impl<'a> EntryRecord<'a> {
+    /// Borrows `entry` as a record; `cfs_raw_xml` is only emitted when `keep_cfs_raw_xml` is set.
+    pub(super) fn new(entry: &'a Entry, keep_cfs_raw_xml: bool) -> Self {
+        Self {
+            id: &entry.id,
+            title: &entry.title,
+            link: &entry.link,
+            summary: &entry.summary,
+            updated: &entry.updated,
+            categories: &entry.categories,
+            author: &entry.author,
+            is_deleted: entry.is_deleted,
+            status: &entry.status,
+            contract_id: &entry.contract_id,
+            contracting_party: ContractingPartyRecord {
+                name: &entry.contracting_party_name,
+                id: &entry.contracting_party_id,
+                website: &entry.contracting_party_website,
+                type_code: &entry.contracting_party_type_code,
+                type_code_list_uri: &entry.contracting_party_type_code_list_uri,
+                activity_code: &entry.contracting_party_activity_code,
+                activity_code_list_uri: &entry.contracting_party_activity_code_list_uri,
+                city: &entry.contracting_party_city,
+                zip: &entry.contracting_party_zip,
+                country_code: &entry.contracting_party_country_code,
+                country_code_list_uri: &entry.contracting_party_country_code_list_uri,
+            },
+            project: ProjectRecord {
+                name: &entry.project_name,
+                type_code: &entry.project_type_code,
+                type_code_list_uri: &entry.project_type_code_list_uri,
+                sub_type_code: &entry.project_sub_type_code,
+                sub_type_code_list_uri: &entry.project_sub_type_code_list_uri,
+                total_amount: &entry.project_total_amount,
+                total_currency: &entry.project_total_currency,
+                tax_exclusive_amount: &entry.project_tax_exclusive_amount,
+                tax_exclusive_currency: &entry.project_tax_exclusive_currency,
+                cpv_code: &entry.project_cpv_code,
+                cpv_code_list_uri: &entry.project_cpv_code_list_uri,
+                country_code: &entry.project_country_code,
+                country_code_list_uri: &entry.project_country_code_list_uri,
+                duration_measure: &entry.project_duration_measure,
+                duration_unit: &entry.project_duration_unit,
+                country_subentity: &entry.project_country_subentity,
+                country_subentity_code: &entry.project_country_subentity_code,
+                country_subentity_code_list_uri: &entry.project_country_subentity_code_list_uri,
+            },
+            project_lots: &entry.project_lots,
+            tender_results: entry
+                .tender_results
+                .iter()
+                .map(|row| TenderResultRecord {
+                    row,
+                    result_sme_awarded: row
+                        .result_sme_awarded_indicator
+                        .as_deref()
+                        .and_then(parse_sme_indicator),
+                })
+                .collect(),
+            terms_funding_program: &entry.terms_funding_program,
+            awarding_criteria: &entry.awarding_criteria,
+            process: ProcessRecord {
+                start_date: &entry.process_start_date,
+                end_date: &entry.process_end_date,
+                open_date: &entry.process_open_date,
+                procedure_code: &entry.process_procedure_code,
+                procedure_code_list_uri: &entry.process_procedure_code_list_uri,
+                urgency_code: &entry.process_urgency_code,
+                urgency_code_list_uri: &entry.process_urgency_code_list_uri,
+            },
+            feed_metadata: FeedMetadataRecord {
+                id: &entry.feed_metadata.feed_id,
+                title: &entry.feed_metadata.feed_title,
+                updated: &entry.feed_metadata.feed_updated,
+            },
+            cfs_raw_xml: keep_cfs_raw_xml.then_some(&entry.cfs_raw_xml),
+        }
+    }
+}
+
+/// Appends `entries` to `writer` as JSON Lines (one [`EntryRecord`] per line).
+pub(super) fn write_jsonl<W: Write>(
+    writer: W,
+    entries: &[Entry],
+    keep_cfs_raw_xml: bool,
+) -> AppResult<()> {
+    let mut writer = BufWriter::new(writer);
+    for entry in entries {
+        serde_json::to_writer(&mut writer, &EntryRecord::new(entry, keep_cfs_raw_xml))
+            .map_err(|e| AppError::ParseError(format!("Failed to serialize entry: {e}")))?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes `entries` to `path` as JSON Lines, appending when `append` is set.
+pub(super) fn write_jsonl_file(
+    path: &Path,
+    entries: &[Entry],
+    keep_cfs_raw_xml: bool,
+    append: bool,
+) -> AppResult<()> {
+    let file = File::options()
+        .create(true)
+        .write(true)
+        .append(append)
+        .truncate(!append)
+        .open(path)
+        .map_err(|e| AppError::IoError(format!("Failed to create JSONL file {path:?}: {e}")))?;
+    write_jsonl(file, entries, keep_cfs_raw_xml)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry_with_lot() -> Entry {
+        // Missing `Option` fields deserialize as `None`.
+        let mut entry: Entry = serde_json::from_value(serde_json::json!({
+            "status": {},
+            "project_lots": [],
+            "tender_results": [],
+            "terms_funding_program": {},
+            "awarding_criteria": []
+        }))
+        .unwrap();
+        entry.id = Some("id-1".to_string());
+        entry.contract_id = Some("C-1".to_string());
+        entry.contracting_party_name = Some("Ayuntamiento".to_string());
+        entry.process_start_date = Some("2024-01-01".to_string());
+        entry.project_lots = vec![ProcurementProjectLot {
+            id: Some("LOT-1".to_string()),
+            ..Default::default()
+        }];
+        entry.cfs_raw_xml = Some("<xml/>".to_string());
+        entry
+    }
+
+    #[test]
+    fn write_jsonl_emits_one_nested_object_per_line() {
+        let entries = vec![entry_with_lot(), entry_with_lot()];
+        let mut out = Vec::new();
+        write_jsonl(&mut out, &entries, false).unwrap();
+
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let value: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(value["contract_id"], "C-1");
+        assert_eq!(value["contracting_party"]["name"], "Ayuntamiento");
+        assert_eq!(value["process"]["start_date"], "2024-01-01");
+        assert_eq!(value["project_lots"][0]["id"], "LOT-1");
+        assert!(value["tender_results"].as_array().unwrap().is_empty());
+        assert!(value.get("cfs_raw_xml").is_none());
+    }
+}
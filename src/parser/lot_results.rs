@@ -0,0 +1,269 @@
+use crate::errors::{AppError, AppResult};
+use crate::models::{Entry, ProcurementProjectLot, TenderResultRow};
+use polars::prelude::*;
+
+/// `result_lot_id` of tender results that are not tied to a specific lot.
+const FOLDER_LEVEL_LOT_ID: &str = "0";
+
+/// Columns of the `{period}_lots.parquet` file written with `join_lot_results`.
+///
+/// - `id`, `contract_id`, `updated`: the parent entry
+/// - `lot_*`: every field of the `project_lots` struct
+/// - `result_*`: the matched `tender_results` row, null when the lot has no result.
+///   `result_lot_id` is `"0"` when a folder-level result was attached to the lot.
+pub const LOT_RESULT_COLUMNS: [&str; 21] = [
+    "id",
+    "contract_id",
+    "updated",
+    "lot_id",
+    "lot_name",
+    "lot_total_amount",
+    "lot_total_currency",
+    "lot_tax_exclusive_amount",
+    "lot_tax_exclusive_currency",
+    "lot_cpv_code",
+    "lot_cpv_code_list_uri",
+    "lot_country_code",
+    "lot_country_code_list_uri",
+    "lot_country_subentity_code",
+    "lot_country_subentity_code_list_uri",
+    "result_id",
+    "result_lot_id",
+    "result_code",
+    "result_winning_party",
+    "result_payable_amount",
+    "result_payable_currency",
+];
+
+/// One project lot of an entry joined with one of its tender results.
+pub(super) struct LotResultRow {
+    id: Option<String>,
+    contract_id: Option<String>,
+    updated: Option<String>,
+    lot: ProcurementProjectLot,
+    result: Option<TenderResultRow>,
+}
+
+/// Joins the project lots of each entry with its tender results, one row per lot and match.
+///
+/// A lot matches the results whose `result_lot_id` equals the lot `id`. A lot without a
+/// lot-specific result gets the entry's folder-level results (`result_lot_id` `"0"`)
+/// instead, and a lot with neither is kept with null result fields (left join). Several
+/// matching results yield one row each. Entries without lots produce no rows, and results
+/// naming a lot that is not listed in `project_lots` are dropped.
+pub(super) fn lot_result_rows(entries: &[Entry]) -> Vec<LotResultRow> {
+    let mut rows = Vec::new();
+    for entry in entries {
+        let results_for = |lot_id: Option<&str>| -> Vec<&TenderResultRow> {
+            entry
+                .tender_results
+                .iter()
+                .filter(|result| lot_id.is_some() && result.result_lot_id.as_deref() == lot_id)
+                .collect()
+        };
+        let folder_results = results_for(Some(FOLDER_LEVEL_LOT_ID));
+
+        for lot in &entry.project_lots {
+            let lot_results = results_for(lot.id.as_deref());
+            let matched = if lot_results.is_empty() {
+                &folder_results
+            } else {
+                &lot_results
+            };
+            let row = |result: Option<&TenderResultRow>| LotResultRow {
+                id: entry.id.clone(),
+                contract_id: entry.contract_id.clone(),
+                updated: entry.updated.clone(),
+                lot: lot.clone(),
+                result: result.cloned(),
+            };
+            if matched.is_empty() {
+                rows.push(row(None));
+            } else {
+                rows.extend(matched.iter().map(|result| row(Some(result))));
+            }
+        }
+    }
+    rows
+}
+
+/// Builds the flat DataFrame written to `{period}_lots.parquet`, with the
+/// [`LOT_RESULT_COLUMNS`] as string columns.
+pub(super) fn lot_results_to_dataframe(rows: &[LotResultRow]) -> AppResult<DataFrame> {
+    let column = |name: &str, value: fn(&LotResultRow) -> Option<String>| {
+        Series::new(name, rows.iter().map(value).collect::<Vec<_>>())
+    };
+    let result_column = |name: &str, value: fn(&TenderResultRow) -> Option<String>| {
+        Series::new(
+            name,
+            rows.iter()
+                .map(|row| row.result.as_ref().and_then(value))
+                .collect::<Vec<_>>(),
+        )
+    };
+
+    DataFrame::new(vec![
+        column("id", |row| row.id.clone()),
+        column("contract_id", |row| row.contract_id.clone()),
+        column("updated", |row| row.updated.clone()),
+        column("lot_id", |row| row.lot.id.clone()),
+        column("lot_name", |row| row.lot.name.clone()),
+        column("lot_total_amount", |row| row.lot.total_amount.clone()),
+        column("lot_total_currency", |row| row.lot.total_currency.clone()),
+        column("lot_tax_exclusive_amount", |row| {
+            row.lot.tax_exclusive_amount.clone()
+        }),
+        column("lot_tax_exclusive_currency", |row| {
+            row.lot.tax_exclusive_currency.clone()
+        }),
+        column("lot_cpv_code", |row| row.lot.cpv_code.clone()),
+        column("lot_cpv_code_list_uri", |row| {
+            row.lot.cpv_code_list_uri.clone()
+        }),
+        column("lot_country_code", |row| row.lot.country_code.clone()),
+        column("lot_country_code_list_uri", |row| {
+            row.lot.country_code_list_uri.clone()
+        }),
+        column("lot_country_subentity_code", |row| {
+            row.lot.country_subentity_code.clone()
+        }),
+        column("lot_country_subentity_code_list_uri", |row| {
+            row.lot.country_subentity_code_list_uri.clone()
+        }),
+        result_column("result_id", |r| r.result_id.clone()),
+        result_column("result_lot_id", |r| r.result_lot_id.clone()),
+        result_column("result_code", |r| r.result_code.clone()),
+        result_column("result_winning_party", |r| r.result_winning_party.clone()),
+        result_column("result_payable_amount", |r| r.result_payable_amount.clone()),
+        result_column("result_payable_currency", |r| {
+            r.result_payable_currency.clone()
+        }),
+    ])
+    .map_err(|e| AppError::ParseError(format!("Failed to build lot results DataFrame: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lot(id: &str) -> ProcurementProjectLot {
+        ProcurementProjectLot {
+            id: Some(id.to_string()),
+            name: Some(format!("Lote {id}")),
+            ..Default::default()
+        }
+    }
+
+    fn result(result_id: &str, lot_id: &str, winner: &str) -> TenderResultRow {
+        TenderResultRow {
+            result_id: Some(result_id.to_string()),
+            result_lot_id: Some(lot_id.to_string()),
+            result_winning_party: Some(winner.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn entry(
+        contract_id: &str,
+        lots: Vec<ProcurementProjectLot>,
+        results: Vec<TenderResultRow>,
+    ) -> Entry {
+        // Missing `Option` fields deserialize as `None`.
+        let mut entry: Entry = serde_json::from_value(serde_json::json!({
+            "status": {},
+            "project_lots": [],
+            "tender_results": [],
+            "terms_funding_program": {},
+            "awarding_criteria": []
+        }))
+        .unwrap();
+        entry.contract_id = Some(contract_id.to_string());
+        entry.project_lots = lots;
+        entry.tender_results = results;
+        entry
+    }
+
+    fn summary(rows: &[LotResultRow]) -> Vec<(String, String, Option<String>)> {
+        rows.iter()
+            .map(|row| {
+                (
+                    row.contract_id.clone().unwrap(),
+                    row.lot.id.clone().unwrap(),
+                    row.result
+                        .as_ref()
+                        .and_then(|r| r.result_winning_party.clone()),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn partial_awards_keep_unmatched_lots_with_null_results() {
+        let entries = vec![entry(
+            "C-1",
+            vec![lot("1"), lot("2"), lot("3")],
+            vec![
+                result("1", "1", "Empresa A"),
+                result("2", "2", "Empresa B"),
+                result("2", "2", "Empresa C"),
+                result("3", "9", "Lote inexistente"),
+            ],
+        )];
+
+        let rows = lot_result_rows(&entries);
+
+        let expected = [
+            ("C-1", "1", Some("Empresa A")),
+            ("C-1", "2", Some("Empresa B")),
+            ("C-1", "2", Some("Empresa C")),
+            ("C-1", "3", None),
+        ]
+        .map(|(c, l, w)| (c.to_string(), l.to_string(), w.map(str::to_string)));
+        assert_eq!(summary(&rows), expected);
+    }
+
+    #[test]
+    fn folder_level_results_fill_lots_without_their_own_result() {
+        let entries = vec![
+            entry(
+                "C-2",
+                vec![lot("1"), lot("2")],
+                vec![
+                    result("1", "0", "Adjudicatario global"),
+                    result("2", "2", "Empresa B"),
+                ],
+            ),
+            entry("C-3", Vec::new(), vec![result("1", "0", "Sin lotes")]),
+        ];
+
+        let rows = lot_result_rows(&entries);
+
+        assert_eq!(
+            summary(&rows),
+            [
+                ("C-2", "1", Some("Adjudicatario global")),
+                ("C-2", "2", Some("Empresa B")),
+            ]
+            .map(|(c, l, w)| (c.to_string(), l.to_string(), w.map(str::to_string)))
+        );
+        assert_eq!(
+            rows[0].result.as_ref().unwrap().result_lot_id.as_deref(),
+            Some("0")
+        );
+    }
+
+    #[test]
+    fn dataframe_has_lot_result_columns() {
+        let entries = vec![entry(
+            "C-1",
+            vec![lot("1"), lot("2")],
+            vec![result("1", "1", "Empresa A")],
+        )];
+
+        let df = lot_results_to_dataframe(&lot_result_rows(&entries)).unwrap();
+
+        assert_eq!(df.get_column_names(), LOT_RESULT_COLUMNS);
+        assert_eq!(df.height(), 2);
+        assert_eq!(df.column("result_winning_party").unwrap().null_count(), 1);
+    }
+}
@@ -1,17 +1,45 @@
 //! XML parsing and Parquet output operations.
 //!
-//! This module extracts, parses, and transforms XML/Atom files into Parquet format. It handles
+//! This module extracts, parses, and transforms XML/Atom files into Parquet (or JSON Lines) format. It handles
 //! ZIP extraction, XML parsing into `Entry` structures, and writing to Parquet files.
-//! Main entry points are [`find_xmls`] and [`parse_xmls`].
+//! Main entry points are [`find_xmls`] (with [`match_period_dirs`]) and [`parse_xmls`]; [`parse_period_entries`] returns the
+//! entries of one period directory in memory without writing anything. [`check_parquet_dir`] verifies the output,
+//! [`parquet_stats`] summarizes it, [`parquet_diff`] compares two outputs, [`write_checksums`] records SHA-256 digests of it,
+//! and [`apply_retention`] deletes the output of periods past a rolling window. [`output_schema`] describes the columns written.
+//! With the `duckdb` feature, each parsed period can also be loaded into a DuckDB database.
 
+mod checksums;
 mod cleanup;
+mod column_compression;
 mod contract_folder_status;
+#[cfg(feature = "duckdb")]
+mod duckdb_export;
 mod file_finder;
+mod jsonl_writer;
+mod lot_results;
+mod normalize;
+mod output_schema;
+mod parquet_check;
+mod parquet_diff;
+mod parquet_stats;
 mod parquet_writer;
+mod processed_files;
+mod retention;
 mod scope;
 mod xml_parser;
 
 // Re-export public API
+pub use checksums::write_checksums;
 pub use cleanup::cleanup_files;
-pub use file_finder::find_xmls;
+pub use file_finder::{find_xmls, match_period_dirs};
+pub use lot_results::LOT_RESULT_COLUMNS;
+pub use output_schema::{output_schema, OutputSchema, SchemaField};
+pub use parquet_check::{
+    check_parquet_dir, ParquetCheckReport, ParquetFileCheck, EXPECTED_COLUMNS,
+};
+pub use parquet_diff::{parquet_diff, period_output, ParquetDiff, DIFF_TYPE_COLUMN};
+pub use parquet_stats::{parquet_stats, ParquetStats};
 pub use parquet_writer::parse_xmls;
+pub(crate) use parquet_writer::{parse_xmls_with_pool, parser_pool, period_output_path};
+pub use retention::apply_retention;
+pub use xml_parser::parse_period_entries;
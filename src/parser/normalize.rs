@@ -0,0 +1,232 @@
+//! Normalization of monetary amounts written with locale-specific separators.
+//!
+//! Older minor-contracts feeds write amounts with a decimal comma and dot thousands
+//! separators (`12.345,67`), newer ones with a decimal dot (`12345.67`). With
+//! `normalize_amounts`, every amount field is rewritten to the dot-decimal form without
+//! thousands separators so it can be cast to a number.
+//!
+//! Boolean indicators such as `<cbc:SMEAwardedIndicator>` are also spelled in several ways
+//! and are read through [`parse_sme_indicator`].
+
+use crate::models::Entry;
+
+/// Currency markers removed from either end of an amount.
+const CURRENCY_SYMBOLS: [&str; 5] = ["€", "$", "£", "EUR", "eur"];
+
+/// Rewrites `raw` as an optional `-` sign, digits, and at most one `.` decimal separator.
+///
+/// Surrounding whitespace and currency symbols are trimmed and thousands separators
+/// (`.`, `,`, or spaces) are removed. When both `.` and `,` appear, the last one is the
+/// decimal separator. A lone `,` or `.` is a decimal separator unless exactly three digits
+/// follow it. Returns `None` when the value cannot be normalized confidently: empty or
+/// non-numeric text, misplaced thousands separators, or a lone `,` or `.` followed by three
+/// digits (`1,234` and `12.345` may be thousands groups or decimals).
+pub(crate) fn normalize_amount(raw: &str) -> Option<String> {
+    let mut value = raw.trim();
+    loop {
+        let before = value;
+        for symbol in CURRENCY_SYMBOLS {
+            value = value
+                .trim_start_matches(symbol)
+                .trim_end_matches(symbol)
+                .trim();
+        }
+        if value == before {
+            break;
+        }
+    }
+    let (sign, body) = match value.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", value.strip_prefix('+').unwrap_or(value)),
+    };
+    let body: String = body
+        .chars()
+        .filter(|c| *c != ' ' && *c != '\u{a0}')
+        .collect();
+    if body.is_empty()
+        || !body
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == '.' || c == ',')
+    {
+        return None;
+    }
+
+    let last_dot = body.rfind('.');
+    let last_comma = body.rfind(',');
+    let (integer, fraction) = match (last_dot, last_comma) {
+        (None, None) => (body.as_str(), None),
+        (Some(dot), Some(comma)) => {
+            let (decimal, thousands) = if dot > comma { ('.', ',') } else { (',', '.') };
+            let (integer, fraction) = body.split_once(decimal)?;
+            if fraction.contains(decimal) || fraction.contains(thousands) {
+                return None;
+            }
+            (check_thousands_groups(integer, thousands)?, Some(fraction))
+        }
+        (Some(_), None) | (None, Some(_)) => {
+            let separator = if last_dot.is_some() { '.' } else { ',' };
+            let (integer, fraction) = body.split_once(separator)?;
+            if fraction.contains(separator) {
+                (check_thousands_groups(&body, separator)?, None)
+            } else if fraction.len() == 3 {
+                return None;
+            } else {
+                (integer, Some(fraction))
+            }
+        }
+    };
+    let mut integer = integer.replace(['.', ','], "");
+    if integer.is_empty() {
+        if fraction.is_none_or(str::is_empty) {
+            return None;
+        }
+        integer.push('0');
+    }
+    Some(match fraction {
+        Some(fraction) if !fraction.is_empty() => format!("{sign}{integer}.{fraction}"),
+        _ => format!("{sign}{integer}"),
+    })
+}
+
+/// Checks that `separator` splits `integer` into thousands groups (1–3 digits, then groups
+/// of exactly 3) and returns it unchanged; the separators are removed by the caller.
+fn check_thousands_groups(integer: &str, separator: char) -> Option<&str> {
+    let mut groups = integer.split(separator);
+    let first = groups.next()?;
+    if first.is_empty() || first.len() > 3 {
+        return None;
+    }
+    groups.all(|group| group.len() == 3).then_some(integer)
+}
+
+/// Normalizes every amount field of `entry` in place: the project totals, the lot amounts,
+/// and the tender result amounts.
+///
+/// Values that cannot be normalized are left untouched; returns how many there were.
+pub(super) fn normalize_entry_amounts(entry: &mut Entry) -> usize {
+    let mut amounts = vec![
+        &mut entry.project_total_amount,
+        &mut entry.project_tax_exclusive_amount,
+    ];
+    for lot in &mut entry.project_lots {
+        amounts.push(&mut lot.total_amount);
+        amounts.push(&mut lot.tax_exclusive_amount);
+    }
+    for result in &mut entry.tender_results {
+        amounts.push(&mut result.result_tax_exclusive_amount);
+        amounts.push(&mut result.result_payable_amount);
+        amounts.push(&mut result.result_lower_tender_amount);
+        amounts.push(&mut result.result_higher_tender_amount);
+    }
+
+    let mut unnormalized = 0;
+    for amount in amounts.into_iter().flatten() {
+        match normalize_amount(amount) {
+            Some(normalized) => *amount = normalized,
+            None => unnormalized += 1,
+        }
+    }
+    unnormalized
+}
+
+/// Parses a `<cbc:SMEAwardedIndicator>` value case-insensitively: `true`/`yes`/`1` and
+/// `false`/`no`/`0`, ignoring surrounding whitespace. Anything else is `None`.
+pub(super) fn parse_sme_indicator(raw: &str) -> Option<bool> {
+    match raw.trim().to_ascii_lowercase().as_str() {
+        "true" | "yes" | "1" => Some(true),
+        "false" | "no" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ProcurementProjectLot, TenderResultRow};
+
+    #[test]
+    fn normalizes_both_separator_conventions() {
+        assert_eq!(normalize_amount("1.234,56").as_deref(), Some("1234.56"));
+        assert_eq!(normalize_amount("1,234.56").as_deref(), Some("1234.56"));
+        assert_eq!(normalize_amount("1234.56").as_deref(), Some("1234.56"));
+        assert_eq!(normalize_amount("1234,56").as_deref(), Some("1234.56"));
+    }
+
+    #[test]
+    fn leaves_empty_and_non_numeric_values_alone() {
+        assert_eq!(normalize_amount(""), None);
+        assert_eq!(normalize_amount("N/A"), None);
+        assert_eq!(normalize_amount("   "), None);
+        assert_eq!(normalize_amount("12a4"), None);
+    }
+
+    #[test]
+    fn trims_currency_symbols_and_thousands_groups() {
+        assert_eq!(
+            normalize_amount(" 12.345,67 € ").as_deref(),
+            Some("12345.67")
+        );
+        assert_eq!(normalize_amount("EUR 1 234,5").as_deref(), Some("1234.5"));
+        assert_eq!(
+            normalize_amount("$1,234,567.89").as_deref(),
+            Some("1234567.89")
+        );
+        assert_eq!(normalize_amount("1.234.567").as_deref(), Some("1234567"));
+        assert_eq!(normalize_amount("-1.234,56").as_deref(), Some("-1234.56"));
+        assert_eq!(normalize_amount("500").as_deref(), Some("500"));
+        assert_eq!(normalize_amount(",5").as_deref(), Some("0.5"));
+    }
+
+    #[test]
+    fn rejects_ambiguous_or_malformed_separators() {
+        // A lone comma or dot before three digits may be a thousands separator.
+        assert_eq!(normalize_amount("1,234"), None);
+        assert_eq!(normalize_amount("12.345"), None);
+        // Several dots can only be thousands separators.
+        assert_eq!(normalize_amount("1.234.567").as_deref(), Some("1234567"));
+        assert_eq!(normalize_amount("12.34.56"), None);
+        assert_eq!(normalize_amount("1.234,56,7"), None);
+        assert_eq!(normalize_amount("1234.567,8"), None);
+    }
+
+    #[test]
+    fn normalize_entry_amounts_rewrites_every_amount_field() {
+        let mut entry = Entry {
+            project_total_amount: Some("1.234,56".to_string()),
+            project_tax_exclusive_amount: Some("N/A".to_string()),
+            project_lots: vec![ProcurementProjectLot {
+                total_amount: Some("100,5".to_string()),
+                ..ProcurementProjectLot::default()
+            }],
+            tender_results: vec![TenderResultRow {
+                result_payable_amount: Some("2.000,00".to_string()),
+                ..TenderResultRow::default()
+            }],
+            ..Entry::default()
+        };
+
+        assert_eq!(normalize_entry_amounts(&mut entry), 1);
+
+        assert_eq!(entry.project_total_amount.as_deref(), Some("1234.56"));
+        assert_eq!(entry.project_tax_exclusive_amount.as_deref(), Some("N/A"));
+        assert_eq!(entry.project_lots[0].total_amount.as_deref(), Some("100.5"));
+        assert_eq!(
+            entry.tender_results[0].result_payable_amount.as_deref(),
+            Some("2000.00")
+        );
+        assert_eq!(entry.tender_results[0].result_lower_tender_amount, None);
+    }
+
+    #[test]
+    fn parse_sme_indicator_accepts_boolean_spellings() {
+        for raw in ["true", "TRUE", "True", "yes", "Yes", "1", " true "] {
+            assert_eq!(parse_sme_indicator(raw), Some(true), "{raw}");
+        }
+        for raw in ["false", "FALSE", "no", "NO", "0", "false\n"] {
+            assert_eq!(parse_sme_indicator(raw), Some(false), "{raw}");
+        }
+        for raw in ["", "2", "si", "n/a", "truee", "-1"] {
+            assert_eq!(parse_sme_indicator(raw), None, "{raw}");
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use super::parquet_writer::entries_to_dataframe;
+use crate::errors::{AppError, AppResult};
+use polars::prelude::*;
+use serde::Serialize;
+
+/// A column, or a field of a struct column, of the output schema.
+///
+/// Serialized as `{"name": "status", "type": "struct", "fields": [...]}`; `fields` is
+/// omitted for columns that are not (lists of) structs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SchemaField {
+    /// Column or struct field name
+    pub name: String,
+    /// Polars type name: `str`, `bool`, `u32`, `struct`, or `list[...]` of one of those
+    #[serde(rename = "type")]
+    pub dtype: String,
+    /// Fields of the struct, or of the list items when they are structs
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<SchemaField>,
+}
+
+impl SchemaField {
+    fn new(name: &str, dtype: &DataType) -> Self {
+        let mut inner = dtype;
+        while let DataType::List(item) = inner {
+            inner = item;
+        }
+        let fields = match inner {
+            DataType::Struct(fields) => fields
+                .iter()
+                .map(|field| Self::new(field.name(), field.data_type()))
+                .collect(),
+            _ => Vec::new(),
+        };
+        Self {
+            name: name.to_string(),
+            dtype: type_name(dtype),
+            fields,
+        }
+    }
+
+    fn write_tree(&self, depth: usize, tree: &mut String) {
+        tree.push_str(&format!(
+            "{:indent$}{}: {}\n",
+            "",
+            self.name,
+            self.dtype,
+            indent = depth * 2
+        ));
+        for field in &self.fields {
+            field.write_tree(depth + 1, tree);
+        }
+    }
+}
+
+/// Columns written to the Parquet output, in order, with their nested struct fields.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OutputSchema {
+    pub columns: Vec<SchemaField>,
+}
+
+impl OutputSchema {
+    /// Renders the schema as an indented tree, one `name: type` line per column and field.
+    pub fn to_tree(&self) -> String {
+        let mut tree = String::new();
+        for column in &self.columns {
+            column.write_tree(0, &mut tree);
+        }
+        tree
+    }
+
+    /// Renders the schema as pretty-printed JSON (`{"columns": [...]}`).
+    ///
+    /// # Errors
+    ///
+    /// Returns `ParseError` if the schema cannot be serialized.
+    pub fn to_json(&self) -> AppResult<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| AppError::ParseError(format!("Failed to serialize output schema: {e}")))
+    }
+}
+
+/// Type name of `dtype`, with list item types spelled out and struct fields left to
+/// [`SchemaField::fields`].
+fn type_name(dtype: &DataType) -> String {
+    match dtype {
+        DataType::List(item) => format!("list[{}]", type_name(item)),
+        DataType::Struct(_) => "struct".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Schema of the Parquet output, read from the DataFrame the parser builds for an empty
+/// batch, so it always matches what is written.
+///
+/// With `keep_cfs_raw_xml`, the trailing `cfs_raw_xml` column is included. The
+/// `project_cpv_description` column added by `enrich_cpv` is not.
+///
+/// # Errors
+///
+/// Returns `ParseError` if the empty DataFrame cannot be built.
+pub fn output_schema(keep_cfs_raw_xml: bool) -> AppResult<OutputSchema> {
+    let df = entries_to_dataframe(Vec::new(), keep_cfs_raw_xml)?;
+    let columns = df
+        .schema()
+        .iter()
+        .map(|(name, dtype)| SchemaField::new(name, dtype))
+        .collect();
+    Ok(OutputSchema { columns })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::parquet_check::EXPECTED_COLUMNS;
+    use super::*;
+    use crate::models::{AwardingCriterion, Entry, ProcurementProjectLot, TenderResultRow};
+
+    fn column<'a>(schema: &'a OutputSchema, name: &str) -> &'a SchemaField {
+        schema
+            .columns
+            .iter()
+            .find(|column| column.name == name)
+            .unwrap()
+    }
+
+    #[test]
+    fn output_schema_lists_columns_and_nested_fields() {
+        let schema = output_schema(false).unwrap();
+        let names: Vec<&str> = schema.columns.iter().map(|c| c.name.as_str()).collect();
+        for expected in EXPECTED_COLUMNS {
+            assert!(names.contains(&expected), "{expected}");
+        }
+        assert!(!names.contains(&"cfs_raw_xml"));
+        assert_eq!(
+            output_schema(true).unwrap().columns.last().unwrap().name,
+            "cfs_raw_xml"
+        );
+
+        assert_eq!(column(&schema, "categories").dtype, "list[str]");
+        let results = column(&schema, "tender_results");
+        assert_eq!(results.dtype, "list[struct]");
+        let quantity = results
+            .fields
+            .iter()
+            .find(|field| field.name == "result_received_tender_quantity")
+            .unwrap();
+        assert_eq!(quantity.dtype, "u32");
+        let lot_fields: Vec<&str> = column(&schema, "project_lots")
+            .fields
+            .iter()
+            .map(|field| field.name.as_str())
+            .collect();
+        assert!(lot_fields.contains(&"country_subentity_code"));
+    }
+
+    #[test]
+    fn output_schema_matches_a_non_empty_batch() {
+        let entry = Entry {
+            project_lots: vec![ProcurementProjectLot::default()],
+            tender_results: vec![TenderResultRow::default()],
+            awarding_criteria: vec![AwardingCriterion::default()],
+            ..Default::default()
+        };
+        let df = entries_to_dataframe(vec![entry], false).unwrap();
+
+        let empty = entries_to_dataframe(Vec::new(), false).unwrap();
+        assert_eq!(df.schema(), empty.schema());
+    }
+
+    #[test]
+    fn tree_and_json_render_nested_fields() {
+        let schema = output_schema(false).unwrap();
+
+        let tree = schema.to_tree();
+        assert!(tree.starts_with("id: str\n"));
+        assert!(tree.contains("\nstatus: struct\n  code: str\n  list_uri: str\n"));
+        assert!(tree.contains("\nawarding_criteria: list[struct]\n  description: str\n"));
+
+        let json: serde_json::Value = serde_json::from_str(&schema.to_json().unwrap()).unwrap();
+        assert_eq!(
+            json["columns"][0],
+            serde_json::json!({"name": "id", "type": "str"})
+        );
+        let status = json["columns"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|column| column["name"] == "status")
+            .unwrap();
+        assert_eq!(status["fields"][0]["name"], "code");
+    }
+}
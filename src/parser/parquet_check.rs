@@ -0,0 +1,214 @@
+use super::lot_results::LOT_RESULT_COLUMNS;
+use crate::errors::{AppError, AppResult};
+use polars::prelude::*;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+/// Top-level columns every Parquet output file must contain (`cfs_raw_xml` is optional).
+pub const EXPECTED_COLUMNS: [&str; 14] = [
+    "id",
+    "title",
+    "link",
+    "summary",
+    "updated",
+    "status",
+    "contract_id",
+    "contracting_party",
+    "project",
+    "project_lots",
+    "tender_results",
+    "terms_funding_program",
+    "awarding_criteria",
+    "process",
+];
+
+/// Outcome of checking a single Parquet file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParquetFileCheck {
+    /// Path of the checked file
+    pub path: PathBuf,
+    /// Row count from the file metadata, if the file could be opened
+    pub rows: Option<usize>,
+    /// Number of top-level columns, if the file could be opened
+    pub columns: Option<usize>,
+    /// Why the file failed the check, or `None` if it passed
+    pub error: Option<String>,
+}
+
+/// Result of checking every Parquet file under an output directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParquetCheckReport {
+    /// Directory that was scanned
+    pub dir: PathBuf,
+    /// One result per Parquet file, sorted by path
+    pub files: Vec<ParquetFileCheck>,
+}
+
+impl ParquetCheckReport {
+    /// Number of files that failed the check.
+    pub fn error_count(&self) -> usize {
+        self.files.iter().filter(|f| f.error.is_some()).count()
+    }
+
+    /// Renders a per-file table followed by a one-line total, in the style of the run summary.
+    pub fn to_table(&self) -> String {
+        let names: Vec<String> = self
+            .files
+            .iter()
+            .map(|f| {
+                f.path
+                    .strip_prefix(&self.dir)
+                    .unwrap_or(&f.path)
+                    .display()
+                    .to_string()
+            })
+            .collect();
+        let width = names.iter().map(String::len).max().unwrap_or(0);
+
+        let mut table = format!("Parquet check: {}\n", self.dir.display());
+        for (name, file) in names.iter().zip(&self.files) {
+            let rows = file.rows.map_or("-".to_string(), |r| r.to_string());
+            let columns = file.columns.map_or("-".to_string(), |c| c.to_string());
+            let status = file
+                .error
+                .as_deref()
+                .map_or("OK".to_string(), |e| format!("ERROR: {e}"));
+            table.push_str(&format!(
+                "  {name:<width$}  {rows:>10} rows  {columns:>3} columns  {status}\n"
+            ));
+        }
+        table.push_str(&format!(
+            "  {} files, {} errors\n",
+            self.files.len(),
+            self.error_count()
+        ));
+        table
+    }
+}
+
+/// Checks every `.parquet` file under `dir` (period files and per-batch files alike)
+/// without decoding any rows.
+///
+/// Each file is opened with [`ParquetReader`]; a file fails the check when it cannot be
+/// opened, reports zero rows, or is missing any of the [`EXPECTED_COLUMNS`] (the
+/// [`LOT_RESULT_COLUMNS`] for `{period}_lots.parquet` files and the partitioned `lots/` tree).
+///
+/// # Errors
+///
+/// Returns `IoError` if `dir` does not exist. Problems with individual files are recorded
+/// in the report instead.
+pub fn check_parquet_dir(dir: &Path) -> AppResult<ParquetCheckReport> {
+    if !dir.exists() {
+        return Err(AppError::IoError(format!(
+            "Directory does not exist: {}",
+            dir.display()
+        )));
+    }
+
+    let mut paths: Vec<PathBuf> = walkdir::WalkDir::new(dir)
+        .into_iter()
+        .flatten()
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| entry.into_path())
+        .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("parquet"))
+        .collect();
+    paths.sort();
+
+    Ok(ParquetCheckReport {
+        dir: dir.to_path_buf(),
+        files: paths
+            .into_iter()
+            .map(|path| {
+                let in_lots_tree = path.strip_prefix(dir).is_ok_and(|p| p.starts_with("lots"));
+                check_parquet_file(path, in_lots_tree)
+            })
+            .collect(),
+    })
+}
+
+fn check_parquet_file(path: PathBuf, in_lots_tree: bool) -> ParquetFileCheck {
+    let mut check = ParquetFileCheck {
+        path,
+        rows: None,
+        columns: None,
+        error: None,
+    };
+
+    let metadata = File::open(&check.path)
+        .map_err(|e| e.to_string())
+        .and_then(|file| {
+            let mut reader = ParquetReader::new(file);
+            let schema = reader.schema().map_err(|e| e.to_string())?;
+            let rows = reader.num_rows().map_err(|e| e.to_string())?;
+            Ok((schema, rows))
+        });
+    let (schema, rows) = match metadata {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            check.error = Some(format!("failed to open: {e}"));
+            return check;
+        }
+    };
+    check.rows = Some(rows);
+    check.columns = Some(schema.fields.len());
+
+    let is_lots_file = in_lots_tree
+        || check
+            .path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.ends_with("_lots.parquet"));
+    let expected: &[&str] = if is_lots_file {
+        &LOT_RESULT_COLUMNS
+    } else {
+        &EXPECTED_COLUMNS
+    };
+    let missing: Vec<&str> = expected
+        .iter()
+        .copied()
+        .filter(|column| !schema.fields.iter().any(|field| field.name == *column))
+        .collect();
+    if !missing.is_empty() {
+        check.error = Some(format!("missing columns: {}", missing.join(", ")));
+    } else if rows == 0 {
+        check.error = Some("no rows".to_string());
+    }
+    check
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn check_flags_unreadable_and_incomplete_files() {
+        let tmp = TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("broken.parquet"), b"not parquet").unwrap();
+        let mut df = df!("id" => ["a"], "contract_id" => ["C-1"]).unwrap();
+        ParquetWriter::new(File::create(tmp.path().join("partial.parquet")).unwrap())
+            .finish(&mut df)
+            .unwrap();
+        std::fs::write(tmp.path().join("notes.txt"), b"ignored").unwrap();
+
+        let report = check_parquet_dir(tmp.path()).unwrap();
+
+        assert_eq!(report.files.len(), 2);
+        assert_eq!(report.error_count(), 2);
+        assert!(report.files[0]
+            .error
+            .as_deref()
+            .unwrap()
+            .starts_with("failed to open"));
+        let partial = &report.files[1];
+        assert_eq!(partial.rows, Some(1));
+        assert!(partial.error.as_deref().unwrap().contains("project"));
+        assert!(!partial.error.as_deref().unwrap().contains("contract_id"));
+    }
+
+    #[test]
+    fn check_missing_dir_errors() {
+        let tmp = TempDir::new().unwrap();
+        assert!(check_parquet_dir(&tmp.path().join("missing")).is_err());
+    }
+}
@@ -0,0 +1,221 @@
+use super::parquet_stats::parquet_files;
+use crate::errors::{AppError, AppResult};
+use polars::lazy::prelude::{LazyFrame, ScanArgsParquet};
+use polars::prelude::*;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+/// Name of the column classifying each row of a [`ParquetDiff`].
+pub const DIFF_TYPE_COLUMN: &str = "diff_type";
+
+/// Contract-level differences between two Parquet outputs.
+#[derive(Debug, Clone)]
+pub struct ParquetDiff {
+    /// Output compared from (the older run or period)
+    pub from: PathBuf,
+    /// Output compared to
+    pub to: PathBuf,
+    /// Contracts in `to` but not in `from`
+    pub new: usize,
+    /// Contracts in `from` but not in `to`
+    pub removed: usize,
+    /// Contracts in both whose `project.total_amount` differs
+    pub changed: usize,
+    /// One row per differing contract: `contract_id`, `diff_type` (`new`, `removed`, or
+    /// `changed`), `from_total_amount`, and `to_total_amount`, sorted by type and id
+    pub rows: DataFrame,
+}
+
+impl ParquetDiff {
+    /// Renders the counts as a small table, in the style of the run summary.
+    pub fn to_table(&self) -> String {
+        let mut table = format!(
+            "Parquet diff: {} -> {}\n",
+            self.from.display(),
+            self.to.display()
+        );
+        table.push_str(&format!("  New contracts      {}\n", self.new));
+        table.push_str(&format!("  Removed contracts  {}\n", self.removed));
+        table.push_str(&format!("  Changed contracts  {}\n", self.changed));
+        table
+    }
+
+    /// Writes [`ParquetDiff::rows`] to `path`, creating parent directories.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IoError` if the file cannot be created and `ParseError` if writing fails.
+    pub fn write_parquet(&self, path: &Path) -> AppResult<()> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = File::create(path).map_err(|e| {
+            AppError::IoError(format!(
+                "Failed to create diff file {}: {e}",
+                path.display()
+            ))
+        })?;
+        ParquetWriter::new(file)
+            .finish(&mut self.rows.clone())
+            .map_err(|e| {
+                AppError::ParseError(format!("Failed to write diff file {}: {e}", path.display()))
+            })?;
+        Ok(())
+    }
+}
+
+/// Returns the output of `period` in `parquet_dir`: the concatenated `{period}.parquet`, the
+/// `{period}/` batch directory, or the `year=YYYY/month=MM/` partition of `--partitioned`
+/// runs, whichever exists first.
+///
+/// # Errors
+///
+/// Returns `IoError` if the period has no output in `parquet_dir`.
+pub fn period_output(parquet_dir: &Path, period: &str) -> AppResult<PathBuf> {
+    let (year, month) = period.split_at(period.len().min(4));
+    let month = if month.is_empty() { "00" } else { month };
+    let candidates = [
+        parquet_dir.join(format!("{period}.parquet")),
+        parquet_dir.join(period),
+        parquet_dir
+            .join(format!("year={year}"))
+            .join(format!("month={month}")),
+    ];
+    candidates
+        .into_iter()
+        .find(|path| path.exists())
+        .ok_or_else(|| {
+            AppError::IoError(format!(
+                "No Parquet output for period {period} in {}",
+                parquet_dir.display()
+            ))
+        })
+}
+
+/// Compares two Parquet outputs (files or period directories, as accepted by
+/// [`parquet_stats`](super::parquet_stats)) by `contract_id`.
+///
+/// Each side is reduced to the most recently `updated` row per non-null `contract_id`, and
+/// the two sides are combined with a full outer join. A contract is `new` when only `to`
+/// has it, `removed` when only `from` has it, and `changed` when both have it with a
+/// different `project.total_amount`. Amounts are compared as numbers, so `100` and
+/// `100.00` are equal; an amount that appears or disappears counts as a change.
+///
+/// # Errors
+///
+/// Returns `IoError` if either path does not exist or holds no Parquet files, and
+/// `ParseError` if the files cannot be read or lack the `contract_id`, `updated`, or
+/// `project` columns.
+pub fn parquet_diff(from: &Path, to: &Path) -> AppResult<ParquetDiff> {
+    let joined = latest_amounts(from, "from")?
+        .join(
+            latest_amounts(to, "to")?,
+            [col("contract_id")],
+            [col("contract_id")],
+            JoinArgs::new(JoinType::Outer).with_coalesce(JoinCoalesce::CoalesceColumns),
+        )
+        .with_column(
+            when(col("in_from").is_null())
+                .then(lit("new"))
+                .when(col("in_to").is_null())
+                .then(lit("removed"))
+                .when(col("from_total_amount").neq_missing(col("to_total_amount")))
+                .then(lit("changed"))
+                .otherwise(lit(NULL).cast(DataType::String))
+                .alias(DIFF_TYPE_COLUMN),
+        )
+        .filter(col(DIFF_TYPE_COLUMN).is_not_null())
+        .select([
+            col("contract_id"),
+            col(DIFF_TYPE_COLUMN),
+            col("from_total_amount"),
+            col("to_total_amount"),
+        ])
+        .sort(
+            [DIFF_TYPE_COLUMN, "contract_id"],
+            SortMultipleOptions::default(),
+        );
+    let rows = joined.collect().map_err(|e| diff_error(from, to, e))?;
+
+    let diff_types = rows
+        .column(DIFF_TYPE_COLUMN)
+        .and_then(|c| c.str().cloned())
+        .map_err(|e| diff_error(from, to, e))?;
+    let count = |kind: &str| diff_types.into_iter().filter(|t| *t == Some(kind)).count();
+
+    Ok(ParquetDiff {
+        from: from.to_path_buf(),
+        to: to.to_path_buf(),
+        new: count("new"),
+        removed: count("removed"),
+        changed: count("changed"),
+        rows,
+    })
+}
+
+/// Scans `path` down to one row per `contract_id` (the most recently `updated` one) with
+/// `{side}_total_amount` and an `in_{side}` presence marker for the outer join.
+fn latest_amounts(path: &Path, side: &str) -> AppResult<LazyFrame> {
+    let files = parquet_files(path)?;
+    let frame = LazyFrame::scan_parquet_files(files.into(), ScanArgsParquet::default())
+        .map_err(|e| AppError::ParseError(format!("Failed to read {}: {e}", path.display())))?;
+    Ok(frame
+        .filter(col("contract_id").is_not_null())
+        .sort(
+            ["updated"],
+            SortMultipleOptions::default()
+                .with_order_descending(true)
+                .with_nulls_last(true)
+                .with_maintain_order(true),
+        )
+        .unique_stable(
+            Some(vec!["contract_id".to_string()]),
+            UniqueKeepStrategy::First,
+        )
+        .select([
+            col("contract_id"),
+            col("project")
+                .struct_()
+                .field_by_name("total_amount")
+                .cast(DataType::Float64)
+                .alias(&format!("{side}_total_amount")),
+            lit(true).alias(&format!("in_{side}")),
+        ]))
+}
+
+fn diff_error(from: &Path, to: &Path, e: PolarsError) -> AppError {
+    AppError::ParseError(format!(
+        "Failed to compare {} with {}: {e}",
+        from.display(),
+        to.display()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn period_output_prefers_file_then_directory_then_partition() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        assert!(matches!(
+            period_output(root, "202401"),
+            Err(AppError::IoError(_))
+        ));
+
+        std::fs::create_dir_all(root.join("year=2024/month=01")).unwrap();
+        assert_eq!(
+            period_output(root, "202401").unwrap(),
+            root.join("year=2024").join("month=01")
+        );
+        std::fs::create_dir(root.join("202401")).unwrap();
+        assert_eq!(period_output(root, "202401").unwrap(), root.join("202401"));
+        std::fs::write(root.join("202401.parquet"), b"").unwrap();
+        assert_eq!(
+            period_output(root, "202401").unwrap(),
+            root.join("202401.parquet")
+        );
+    }
+}
@@ -0,0 +1,323 @@
+use super::parquet_check::EXPECTED_COLUMNS;
+use crate::errors::{AppError, AppResult};
+use polars::lazy::prelude::{LazyFrame, ScanArgsParquet};
+use polars::prelude::*;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+/// Summary of one Parquet output (a period file or a directory of batch files).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParquetStats {
+    /// File or directory that was summarized
+    pub path: PathBuf,
+    /// Number of Parquet files read
+    pub files: usize,
+    /// Total number of rows
+    pub rows: usize,
+    /// Number of distinct non-null `contract_id` values
+    pub distinct_contract_ids: usize,
+    /// Sum of `project.total_amount` over the rows where it parses as a number
+    pub project_total_amount: f64,
+    /// Rows per `status.code` (`None` for a missing code), most frequent first; ties are
+    /// ordered by code with missing codes last
+    pub status_counts: Vec<(Option<String>, usize)>,
+}
+
+impl ParquetStats {
+    /// Renders the summary as a small table, in the style of the run summary.
+    pub fn to_table(&self) -> String {
+        let mut table = format!("Parquet stats: {}\n", self.path.display());
+        table.push_str(&format!("  Files                 {}\n", self.files));
+        table.push_str(&format!("  Rows                  {}\n", self.rows));
+        table.push_str(&format!(
+            "  Distinct contract ids {}\n",
+            self.distinct_contract_ids
+        ));
+        table.push_str(&format!(
+            "  Project total amount  {:.2}\n",
+            self.project_total_amount
+        ));
+        table.push_str("  Entries per status:\n");
+        let width = self
+            .status_counts
+            .iter()
+            .map(|(code, _)| code.as_deref().unwrap_or("(none)").len())
+            .max()
+            .unwrap_or(0);
+        for (code, count) in &self.status_counts {
+            let code = code.as_deref().unwrap_or("(none)");
+            table.push_str(&format!("    {code:<width$}  {count:>10}\n"));
+        }
+        table
+    }
+}
+
+/// Summarizes a Parquet output produced by the `cli`/`parse` subcommands.
+///
+/// `path` is either a single Parquet file (a concatenated `{period}.parquet` or one batch
+/// file) or a period directory, in which case every `*.parquet` file directly inside it is
+/// scanned together. The rows are read through a [`LazyFrame`], so only the `contract_id`,
+/// `project`, and `status` columns are decoded.
+///
+/// # Errors
+///
+/// Returns `IoError` if `path` does not exist or a directory holds no Parquet files, and
+/// `InvalidInput` if a file cannot be read as Parquet or lacks any of the
+/// [`EXPECTED_COLUMNS`].
+pub fn parquet_stats(path: &Path) -> AppResult<ParquetStats> {
+    let files = parquet_files(path)?;
+    for file in &files {
+        check_columns(file)?;
+    }
+
+    let scan_path = if path.is_dir() {
+        path.join("*.parquet")
+    } else {
+        path.to_path_buf()
+    };
+    let frame = LazyFrame::scan_parquet(&scan_path, ScanArgsParquet::default())
+        .map_err(|e| stats_error(path, e))?;
+
+    let totals = frame
+        .clone()
+        .select([
+            len().alias("rows"),
+            col("contract_id")
+                .drop_nulls()
+                .n_unique()
+                .alias("distinct_contract_ids"),
+            col("project")
+                .struct_()
+                .field_by_name("total_amount")
+                .cast(DataType::Float64)
+                .sum()
+                .alias("project_total_amount"),
+        ])
+        .collect()
+        .map_err(|e| stats_error(path, e))?;
+    let count = |name: &str| -> AppResult<usize> {
+        let value = totals
+            .column(name)
+            .and_then(|c| c.cast(&DataType::UInt64))
+            .map_err(|e| stats_error(path, e))?
+            .u64()
+            .map_err(|e| stats_error(path, e))?
+            .get(0)
+            .unwrap_or(0);
+        Ok(value as usize)
+    };
+    let project_total_amount = totals
+        .column("project_total_amount")
+        .and_then(|c| c.f64().cloned())
+        .map_err(|e| stats_error(path, e))?
+        .get(0)
+        .unwrap_or(0.0);
+
+    let by_status = frame
+        .select([col("status").struct_().field_by_name("code").alias("code")])
+        .group_by([col("code")])
+        .agg([len().alias("count")])
+        .sort(
+            ["count", "code"],
+            SortMultipleOptions::default()
+                .with_order_descendings([true, false])
+                .with_nulls_last(true),
+        )
+        .collect()
+        .map_err(|e| stats_error(path, e))?;
+    let codes = by_status
+        .column("code")
+        .and_then(|c| c.str().cloned())
+        .map_err(|e| stats_error(path, e))?;
+    let counts = by_status
+        .column("count")
+        .and_then(|c| c.cast(&DataType::UInt64))
+        .map_err(|e| stats_error(path, e))?;
+    let counts = counts.u64().map_err(|e| stats_error(path, e))?;
+    let status_counts = codes
+        .into_iter()
+        .zip(counts)
+        .map(|(code, count)| (code.map(str::to_string), count.unwrap_or(0) as usize))
+        .collect();
+
+    Ok(ParquetStats {
+        path: path.to_path_buf(),
+        files: files.len(),
+        rows: count("rows")?,
+        distinct_contract_ids: count("distinct_contract_ids")?,
+        project_total_amount,
+        status_counts,
+    })
+}
+
+/// Lists the Parquet files to summarize: `path` itself, or the `*.parquet` files directly
+/// inside it when it is a directory.
+pub(super) fn parquet_files(path: &Path) -> AppResult<Vec<PathBuf>> {
+    if !path.exists() {
+        return Err(AppError::IoError(format!(
+            "Path does not exist: {}",
+            path.display()
+        )));
+    }
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut files: Vec<PathBuf> = std::fs::read_dir(path)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|p| p.is_file() && p.extension().and_then(|e| e.to_str()) == Some("parquet"))
+        .collect();
+    if files.is_empty() {
+        return Err(AppError::IoError(format!(
+            "No Parquet files in {}",
+            path.display()
+        )));
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Reads the schema of `file` and checks that it has every one of the [`EXPECTED_COLUMNS`].
+fn check_columns(file: &Path) -> AppResult<()> {
+    let schema = File::open(file)
+        .map_err(|e| e.to_string())
+        .and_then(|f| ParquetReader::new(f).schema().map_err(|e| e.to_string()))
+        .map_err(|e| {
+            AppError::InvalidInput(format!(
+                "{} is not a readable Parquet file: {e}",
+                file.display()
+            ))
+        })?;
+    let missing: Vec<&str> = EXPECTED_COLUMNS
+        .iter()
+        .copied()
+        .filter(|column| !schema.fields.iter().any(|field| field.name == *column))
+        .collect();
+    if !missing.is_empty() {
+        return Err(AppError::InvalidInput(format!(
+            "{} is not an sppd-cli output file (missing columns: {})",
+            file.display(),
+            missing.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+fn stats_error(path: &Path, e: PolarsError) -> AppError {
+    AppError::ParseError(format!(
+        "Failed to compute stats for {}: {e}",
+        path.display()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const FEED: &str = r#"<feed>
+  <entry><id>e1</id><cac-place-ext:ContractFolderStatus>
+    <cbc:ContractFolderID>C-1</cbc:ContractFolderID>
+    <cbc-place-ext:ContractFolderStatusCode>ADJ</cbc-place-ext:ContractFolderStatusCode>
+    <cac:ProcurementProject><cac:BudgetAmount><cbc:TotalAmount currencyID="EUR">100.5</cbc:TotalAmount></cac:BudgetAmount></cac:ProcurementProject>
+  </cac-place-ext:ContractFolderStatus></entry>
+  <entry><id>e2</id><cac-place-ext:ContractFolderStatus>
+    <cbc:ContractFolderID>C-1</cbc:ContractFolderID>
+    <cbc-place-ext:ContractFolderStatusCode>RES</cbc-place-ext:ContractFolderStatusCode>
+    <cac:ProcurementProject><cac:BudgetAmount><cbc:TotalAmount currencyID="EUR">200</cbc:TotalAmount></cac:BudgetAmount></cac:ProcurementProject>
+  </cac-place-ext:ContractFolderStatus></entry>
+  <entry><id>e3</id><cac-place-ext:ContractFolderStatus>
+    <cbc:ContractFolderID>C-2</cbc:ContractFolderID>
+    <cbc-place-ext:ContractFolderStatusCode>RES</cbc-place-ext:ContractFolderStatusCode>
+  </cac-place-ext:ContractFolderStatus></entry>
+  <entry><id>e4</id></entry>
+</feed>"#;
+
+    async fn parse_fixture(tmp: &TempDir, concat_batches: bool) -> PathBuf {
+        let config = crate::config::ResolvedConfig {
+            download_dir_pt: tmp.path().join("tmp"),
+            parquet_dir_pt: tmp.path().join("parquet"),
+            batch_size: 1,
+            concat_batches,
+            ..Default::default()
+        };
+        let period_dir = tmp.path().join("tmp/202301");
+        std::fs::create_dir_all(&period_dir).unwrap();
+        std::fs::write(period_dir.join("a.atom"), FEED).unwrap();
+        std::fs::write(
+            period_dir.join("b.atom"),
+            "<feed><entry><id>e5</id></entry></feed>",
+        )
+        .unwrap();
+        let mut links = std::collections::BTreeMap::new();
+        links.insert("202301".to_string(), String::new());
+        super::super::parse_xmls(
+            &links,
+            &crate::models::ProcurementType::PublicTenders,
+            config.batch_size,
+            &config,
+            None,
+        )
+        .await
+        .unwrap();
+        tmp.path().join("parquet")
+    }
+
+    fn assert_fixture_stats(stats: &ParquetStats) {
+        assert_eq!(stats.rows, 5);
+        assert_eq!(stats.distinct_contract_ids, 2);
+        assert!((stats.project_total_amount - 300.5).abs() < 1e-9);
+        assert_eq!(
+            stats.status_counts,
+            [
+                (Some("RES".to_string()), 2),
+                (None, 2),
+                (Some("ADJ".to_string()), 1)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn stats_for_concatenated_period_file() {
+        let tmp = TempDir::new().unwrap();
+        let parquet_dir = parse_fixture(&tmp, true).await;
+
+        let stats = parquet_stats(&parquet_dir.join("202301.parquet")).unwrap();
+
+        assert_eq!(stats.files, 1);
+        assert_fixture_stats(&stats);
+        assert!(stats.to_table().contains("Distinct contract ids 2"));
+    }
+
+    #[tokio::test]
+    async fn stats_for_batch_directory() {
+        let tmp = TempDir::new().unwrap();
+        let parquet_dir = parse_fixture(&tmp, false).await;
+
+        let stats = parquet_stats(&parquet_dir.join("202301")).unwrap();
+
+        assert_eq!(stats.files, 2);
+        assert_fixture_stats(&stats);
+    }
+
+    #[test]
+    fn stats_rejects_foreign_and_invalid_files() {
+        let tmp = TempDir::new().unwrap();
+        let foreign = tmp.path().join("foreign.parquet");
+        let mut df = df!("id" => ["a"]).unwrap();
+        ParquetWriter::new(File::create(&foreign).unwrap())
+            .finish(&mut df)
+            .unwrap();
+        let broken = tmp.path().join("broken.parquet");
+        std::fs::write(&broken, b"not parquet").unwrap();
+
+        for path in [foreign, broken] {
+            let err = parquet_stats(&path).unwrap_err();
+            assert!(matches!(err, AppError::InvalidInput(_)), "{err:?}");
+        }
+        assert!(matches!(
+            parquet_stats(&tmp.path().join("missing.parquet")),
+            Err(AppError::IoError(_))
+        ));
+    }
+}
@@ -1,20 +1,60 @@
+use crate::config::{DedupStrategy, OutputFormat, ParquetCompressionCodec, RawXmlCompressionCodec};
 use crate::errors::{AppError, AppResult};
-use crate::models::{Entry, ProcurementProjectLot, TenderResultRow};
+use crate::models::{
+    AwardingCriterion, Entry, ParseStats, PeriodParseStats, ProcurementProjectLot, TenderResultRow,
+};
+use crate::transformer::Transformer;
+use crate::ui::{create_progress_bar, ProgressBar};
 use crate::utils::{format_duration, mb_from_bytes, round_two_decimals};
+use futures::channel::oneshot;
+use futures::future;
 use futures::stream::{self, StreamExt, TryStreamExt};
 use polars::lazy::prelude::{LazyFrame, ScanArgsParquet};
 use polars::prelude::*;
 use rayon::prelude::*;
 use rayon::ThreadPoolBuilder;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 use std::fs::{self as std_fs, File};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::time::Instant;
 use tokio::fs as tokio_fs;
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
+
+use super::checksums::write_checksums;
+use super::column_compression::{write_dataframe, ColumnCompressionWriter};
+use super::file_finder::{find_xmls, match_period_dirs};
+use super::jsonl_writer::write_jsonl_file;
+use super::lot_results::{lot_result_rows, lot_results_to_dataframe};
+use super::normalize::{normalize_entry_amounts, parse_sme_indicator};
+use super::processed_files::{batch_files, ProcessedFile, ProcessedFiles};
+use super::xml_parser::{gunzip_if_compressed, parse_xml_bytes_cached, ParseOptions, ParsedFeed};
+
+/// Maps the configured codec to the Polars Parquet compression setting.
+fn parquet_compression(codec: ParquetCompressionCodec) -> ParquetCompression {
+    match codec {
+        ParquetCompressionCodec::Zstd => ParquetCompression::Zstd(None),
+        ParquetCompressionCodec::Snappy => ParquetCompression::Snappy,
+        ParquetCompressionCodec::Lz4 => ParquetCompression::Lz4Raw,
+        ParquetCompressionCodec::Uncompressed => ParquetCompression::Uncompressed,
+    }
+}
 
-use super::file_finder::find_xmls;
-use super::xml_parser::parse_xml_bytes;
+/// Compression override of the `cfs_raw_xml` column, when the column is written and
+/// `raw_xml_compression` is set.
+fn raw_xml_override(
+    config: &crate::config::ResolvedConfig,
+) -> Option<(&'static str, ParquetCompression)> {
+    if !config.keep_cfs_raw_xml {
+        return None;
+    }
+    let compression = match config.raw_xml_compression? {
+        RawXmlCompressionCodec::Lz4 => ParquetCompression::Lz4Raw,
+        RawXmlCompressionCodec::Zstd => ParquetCompression::Zstd(None),
+        RawXmlCompressionCodec::None => ParquetCompression::Uncompressed,
+    };
+    Some(("cfs_raw_xml", compression))
+}
 
 fn lots_to_struct_series(lots: &[ProcurementProjectLot]) -> AppResult<Series> {
     let mut ids = Vec::with_capacity(lots.len());
@@ -27,6 +67,8 @@ fn lots_to_struct_series(lots: &[ProcurementProjectLot]) -> AppResult<Series> {
     let mut cpv_list_uris = Vec::with_capacity(lots.len());
     let mut countries = Vec::with_capacity(lots.len());
     let mut country_list_uris = Vec::with_capacity(lots.len());
+    let mut subentity_codes = Vec::with_capacity(lots.len());
+    let mut subentity_code_list_uris = Vec::with_capacity(lots.len());
 
     for lot in lots {
         ids.push(lot.id.clone());
@@ -39,6 +81,8 @@ fn lots_to_struct_series(lots: &[ProcurementProjectLot]) -> AppResult<Series> {
         cpv_list_uris.push(lot.cpv_code_list_uri.clone());
         countries.push(lot.country_code.clone());
         country_list_uris.push(lot.country_code_list_uri.clone());
+        subentity_codes.push(lot.country_subentity_code.clone());
+        subentity_code_list_uris.push(lot.country_subentity_code_list_uri.clone());
     }
 
     let df = DataFrame::new(vec![
@@ -52,12 +96,35 @@ fn lots_to_struct_series(lots: &[ProcurementProjectLot]) -> AppResult<Series> {
         Series::new("cpv_code_list_uri", cpv_list_uris),
         Series::new("country_code", countries),
         Series::new("country_code_list_uri", country_list_uris),
+        Series::new("country_subentity_code", subentity_codes),
+        Series::new("country_subentity_code_list_uri", subentity_code_list_uris),
     ])
     .map_err(|e| AppError::ParseError(format!("Failed to build lot struct: {e}")))?;
 
     Ok(df.into_struct("lot").into_series())
 }
 
+fn awarding_criteria_to_struct_series(criteria: &[AwardingCriterion]) -> AppResult<Series> {
+    let mut descriptions = Vec::with_capacity(criteria.len());
+    let mut weights = Vec::with_capacity(criteria.len());
+    let mut type_codes = Vec::with_capacity(criteria.len());
+
+    for criterion in criteria {
+        descriptions.push(criterion.description.clone());
+        weights.push(criterion.weight.clone());
+        type_codes.push(criterion.type_code.clone());
+    }
+
+    let df = DataFrame::new(vec![
+        Series::new("description", descriptions),
+        Series::new("weight", weights),
+        Series::new("type_code", type_codes),
+    ])
+    .map_err(|e| AppError::ParseError(format!("Failed to build awarding criterion struct: {e}")))?;
+
+    Ok(df.into_struct("criterion").into_series())
+}
+
 fn tender_results_to_struct_series(results: &[TenderResultRow]) -> AppResult<Series> {
     let mut result_ids = Vec::with_capacity(results.len());
     let mut result_lot_ids = Vec::with_capacity(results.len());
@@ -66,11 +133,17 @@ fn tender_results_to_struct_series(results: &[TenderResultRow]) -> AppResult<Ser
     let mut descriptions = Vec::with_capacity(results.len());
     let mut winning_parties = Vec::with_capacity(results.len());
     let mut sme_indicators = Vec::with_capacity(results.len());
+    let mut sme_awarded = Vec::with_capacity(results.len());
     let mut award_dates = Vec::with_capacity(results.len());
     let mut tax_exclusive_amounts = Vec::with_capacity(results.len());
     let mut tax_exclusive_currencies = Vec::with_capacity(results.len());
     let mut payable_amounts = Vec::with_capacity(results.len());
     let mut payable_currencies = Vec::with_capacity(results.len());
+    let mut received_tender_quantities = Vec::with_capacity(results.len());
+    let mut lower_tender_amounts = Vec::with_capacity(results.len());
+    let mut lower_tender_currencies = Vec::with_capacity(results.len());
+    let mut higher_tender_amounts = Vec::with_capacity(results.len());
+    let mut higher_tender_currencies = Vec::with_capacity(results.len());
 
     for result in results {
         result_ids.push(result.result_id.clone());
@@ -80,11 +153,22 @@ fn tender_results_to_struct_series(results: &[TenderResultRow]) -> AppResult<Ser
         descriptions.push(result.result_description.clone());
         winning_parties.push(result.result_winning_party.clone());
         sme_indicators.push(result.result_sme_awarded_indicator.clone());
+        sme_awarded.push(
+            result
+                .result_sme_awarded_indicator
+                .as_deref()
+                .and_then(parse_sme_indicator),
+        );
         award_dates.push(result.result_award_date.clone());
         tax_exclusive_amounts.push(result.result_tax_exclusive_amount.clone());
         tax_exclusive_currencies.push(result.result_tax_exclusive_currency.clone());
         payable_amounts.push(result.result_payable_amount.clone());
         payable_currencies.push(result.result_payable_currency.clone());
+        received_tender_quantities.push(result.result_received_tender_quantity.clone());
+        lower_tender_amounts.push(result.result_lower_tender_amount.clone());
+        lower_tender_currencies.push(result.result_lower_tender_currency.clone());
+        higher_tender_amounts.push(result.result_higher_tender_amount.clone());
+        higher_tender_currencies.push(result.result_higher_tender_currency.clone());
     }
 
     let df = DataFrame::new(vec![
@@ -95,11 +179,20 @@ fn tender_results_to_struct_series(results: &[TenderResultRow]) -> AppResult<Ser
         Series::new("result_description", descriptions),
         Series::new("result_winning_party", winning_parties),
         Series::new("result_sme_awarded_indicator", sme_indicators),
+        Series::new("result_sme_awarded", sme_awarded),
         Series::new("result_award_date", award_dates),
         Series::new("result_tax_exclusive_amount", tax_exclusive_amounts),
         Series::new("result_tax_exclusive_currency", tax_exclusive_currencies),
         Series::new("result_payable_amount", payable_amounts),
         Series::new("result_payable_currency", payable_currencies),
+        Series::new(
+            "result_received_tender_quantity",
+            received_tender_quantities,
+        ),
+        Series::new("result_lower_tender_amount", lower_tender_amounts),
+        Series::new("result_lower_tender_currency", lower_tender_currencies),
+        Series::new("result_higher_tender_amount", higher_tender_amounts),
+        Series::new("result_higher_tender_currency", higher_tender_currencies),
     ])
     .map_err(|e| AppError::ParseError(format!("Failed to build tender_result struct: {e}")))?;
 
@@ -144,8 +237,30 @@ fn terms_funding_program_to_struct(entries: &[Entry]) -> AppResult<Series> {
     Ok(df.into_struct("terms_funding_program").into_series())
 }
 
+fn feed_metadata_to_struct(entries: &[Entry]) -> AppResult<Series> {
+    let mut ids = Vec::with_capacity(entries.len());
+    let mut titles = Vec::with_capacity(entries.len());
+    let mut updateds = Vec::with_capacity(entries.len());
+
+    for entry in entries {
+        ids.push(entry.feed_metadata.feed_id.clone());
+        titles.push(entry.feed_metadata.feed_title.clone());
+        updateds.push(entry.feed_metadata.feed_updated.clone());
+    }
+
+    let df = DataFrame::new(vec![
+        Series::new("id", ids),
+        Series::new("title", titles),
+        Series::new("updated", updateds),
+    ])
+    .map_err(|e| AppError::ParseError(format!("Failed to build feed_metadata struct: {e}")))?;
+
+    Ok(df.into_struct("feed_metadata").into_series())
+}
+
 fn contracting_party_to_struct(entries: &[Entry]) -> AppResult<Series> {
     let mut names = Vec::with_capacity(entries.len());
+    let mut ids = Vec::with_capacity(entries.len());
     let mut websites = Vec::with_capacity(entries.len());
     let mut type_codes = Vec::with_capacity(entries.len());
     let mut type_code_list_uris = Vec::with_capacity(entries.len());
@@ -158,6 +273,7 @@ fn contracting_party_to_struct(entries: &[Entry]) -> AppResult<Series> {
 
     for entry in entries {
         names.push(entry.contracting_party_name.clone());
+        ids.push(entry.contracting_party_id.clone());
         websites.push(entry.contracting_party_website.clone());
         type_codes.push(entry.contracting_party_type_code.clone());
         type_code_list_uris.push(entry.contracting_party_type_code_list_uri.clone());
@@ -171,6 +287,7 @@ fn contracting_party_to_struct(entries: &[Entry]) -> AppResult<Series> {
 
     let df = DataFrame::new(vec![
         Series::new("name", names),
+        Series::new("id", ids),
         Series::new("website", websites),
         Series::new("type_code", type_codes),
         Series::new("type_code_list_uri", type_code_list_uris),
@@ -200,6 +317,11 @@ fn project_to_struct(entries: &[Entry]) -> AppResult<Series> {
     let mut cpv_code_list_uris = Vec::with_capacity(entries.len());
     let mut country_codes = Vec::with_capacity(entries.len());
     let mut country_code_list_uris = Vec::with_capacity(entries.len());
+    let mut duration_measures = Vec::with_capacity(entries.len());
+    let mut duration_units = Vec::with_capacity(entries.len());
+    let mut country_subentities = Vec::with_capacity(entries.len());
+    let mut country_subentity_codes = Vec::with_capacity(entries.len());
+    let mut country_subentity_code_list_uris = Vec::with_capacity(entries.len());
 
     for entry in entries {
         names.push(entry.project_name.clone());
@@ -215,6 +337,12 @@ fn project_to_struct(entries: &[Entry]) -> AppResult<Series> {
         cpv_code_list_uris.push(entry.project_cpv_code_list_uri.clone());
         country_codes.push(entry.project_country_code.clone());
         country_code_list_uris.push(entry.project_country_code_list_uri.clone());
+        duration_measures.push(entry.project_duration_measure.clone());
+        duration_units.push(entry.project_duration_unit.clone());
+        country_subentities.push(entry.project_country_subentity.clone());
+        country_subentity_codes.push(entry.project_country_subentity_code.clone());
+        country_subentity_code_list_uris
+            .push(entry.project_country_subentity_code_list_uri.clone());
     }
 
     let df = DataFrame::new(vec![
@@ -231,6 +359,14 @@ fn project_to_struct(entries: &[Entry]) -> AppResult<Series> {
         Series::new("cpv_code_list_uri", cpv_code_list_uris),
         Series::new("country_code", country_codes),
         Series::new("country_code_list_uri", country_code_list_uris),
+        Series::new("duration_measure", duration_measures),
+        Series::new("duration_unit", duration_units),
+        Series::new("country_subentity", country_subentities),
+        Series::new("country_subentity_code", country_subentity_codes),
+        Series::new(
+            "country_subentity_code_list_uri",
+            country_subentity_code_list_uris,
+        ),
     ])
     .map_err(|e| AppError::ParseError(format!("Failed to build project struct: {e}")))?;
 
@@ -238,14 +374,18 @@ fn project_to_struct(entries: &[Entry]) -> AppResult<Series> {
 }
 
 fn process_to_struct(entries: &[Entry]) -> AppResult<Series> {
+    let mut start_dates = Vec::with_capacity(entries.len());
     let mut end_dates = Vec::with_capacity(entries.len());
+    let mut open_dates = Vec::with_capacity(entries.len());
     let mut procedure_codes = Vec::with_capacity(entries.len());
     let mut procedure_code_list_uris = Vec::with_capacity(entries.len());
     let mut urgency_codes = Vec::with_capacity(entries.len());
     let mut urgency_code_list_uris = Vec::with_capacity(entries.len());
 
     for entry in entries {
+        start_dates.push(entry.process_start_date.clone());
         end_dates.push(entry.process_end_date.clone());
+        open_dates.push(entry.process_open_date.clone());
         procedure_codes.push(entry.process_procedure_code.clone());
         procedure_code_list_uris.push(entry.process_procedure_code_list_uri.clone());
         urgency_codes.push(entry.process_urgency_code.clone());
@@ -253,7 +393,9 @@ fn process_to_struct(entries: &[Entry]) -> AppResult<Series> {
     }
 
     let df = DataFrame::new(vec![
+        Series::new("start_date", start_dates),
         Series::new("end_date", end_dates),
+        Series::new("open_date", open_dates),
         Series::new("procedure_code", procedure_codes),
         Series::new("procedure_code_list_uri", procedure_code_list_uris),
         Series::new("urgency_code", urgency_codes),
@@ -271,30 +413,49 @@ fn process_to_struct(entries: &[Entry]) -> AppResult<Series> {
 ///
 /// # Schema
 ///
-/// Creates 13-14 columns:
-/// - `id`, `title`, `link`, `summary`, `updated`, `contract_id`: string columns
+/// Creates 18-19 columns (plus `project_cpv_description` when `enrich_cpv` is set, added by [`add_cpv_descriptions`]):
+/// - `id`, `title`, `link`, `summary`, `updated`, `author`, `contract_id`: string columns
+/// - `categories`: list(string) - `term` attributes of the entry's `<category>` elements
+/// - `is_deleted`: boolean, true for `<at:deleted-entry>` tombstone rows
 /// - `status`: struct(code, list_uri)
-/// - `contracting_party`: struct(name, website, type_code, type_code_list_uri, activity_code,
+/// - `contracting_party`: struct(name, id, website, type_code, type_code_list_uri, activity_code,
 ///   activity_code_list_uri, city, zip, country_code, country_code_list_uri)
 /// - `project`: struct(name, type_code, type_code_list_uri, sub_type_code, sub_type_code_list_uri,
 ///   total_amount, total_currency, tax_exclusive_amount, tax_exclusive_currency,
-///   cpv_code, cpv_code_list_uri, country_code, country_code_list_uri)
-/// - `project_lots`: list(struct(...)) - nested procurement lots with 10 fields each
-/// - `tender_results`: list(struct(...)) - nested tender results with 12 fields each
+///   cpv_code, cpv_code_list_uri, country_code, country_code_list_uri, duration_measure,
+///   duration_unit, country_subentity, country_subentity_code, country_subentity_code_list_uri)
+/// - `project_lots`: list(struct(...)) - nested procurement lots with 12 fields each
+/// - `tender_results`: list(struct(...)) - nested tender results, with `result_received_tender_quantity`
+///   cast to `UInt32` and `result_sme_awarded` parsed from `result_sme_awarded_indicator`
 /// - `terms_funding_program`: struct(code, list_uri)
-/// - `process`: struct(end_date, procedure_code, procedure_code_list_uri, urgency_code, urgency_code_list_uri)
+/// - `awarding_criteria`: list(struct(description, weight, type_code))
+/// - `process`: struct(start_date, end_date, open_date, procedure_code, procedure_code_list_uri, urgency_code, urgency_code_list_uri)
+/// - `feed_metadata`: struct(id, title, updated) - the feed-level Atom metadata of the source file
 /// - `cfs_raw_xml` (optional): raw ContractFolderStatus XML when keep_cfs_raw_xml=true
-fn entries_to_dataframe(entries: Vec<Entry>, keep_cfs_raw_xml: bool) -> AppResult<DataFrame> {
+pub(super) fn entries_to_dataframe(
+    entries: Vec<Entry>,
+    keep_cfs_raw_xml: bool,
+) -> AppResult<DataFrame> {
     let empty: Vec<Option<String>> = Vec::new();
     if entries.is_empty() {
-        let empty_list = Series::new("project_lots", Vec::<Series>::new());
-        let empty_tender_results = Series::new("tender_results", Vec::<Series>::new());
+        // Typed from zero-length structs so the empty frame has the same schema as any other.
+        let empty_list_of = |name: &str, items: Series| {
+            Series::new_empty(name, &DataType::List(Box::new(items.dtype().clone())))
+        };
+        let empty_list = empty_list_of("project_lots", lots_to_struct_series(&[])?);
+        let empty_tender_results =
+            empty_list_of("tender_results", tender_results_to_struct_series(&[])?);
+        let empty_awarding_criteria = empty_list_of(
+            "awarding_criteria",
+            awarding_criteria_to_struct_series(&[])?,
+        );
         let empty_entries: &[Entry] = &[];
         let contracting_party_struct = contracting_party_to_struct(empty_entries)?;
         let project_struct = project_to_struct(empty_entries)?;
         let process_struct = process_to_struct(empty_entries)?;
         let status_struct = status_to_struct(empty_entries)?;
         let terms_struct = terms_funding_program_to_struct(empty_entries)?;
+        let feed_metadata_struct = feed_metadata_to_struct(empty_entries)?;
 
         let mut columns = vec![
             Series::new("id", empty.clone()),
@@ -302,6 +463,9 @@ fn entries_to_dataframe(entries: Vec<Entry>, keep_cfs_raw_xml: bool) -> AppResul
             Series::new("link", empty.clone()),
             Series::new("summary", empty.clone()),
             Series::new("updated", empty.clone()),
+            Series::new_empty("categories", &DataType::List(Box::new(DataType::String))),
+            Series::new("author", empty.clone()),
+            Series::new("is_deleted", Vec::<bool>::new()),
             status_struct,
             Series::new("contract_id", empty.clone()),
             contracting_party_struct,
@@ -309,15 +473,18 @@ fn entries_to_dataframe(entries: Vec<Entry>, keep_cfs_raw_xml: bool) -> AppResul
             empty_list,
             empty_tender_results,
             terms_struct,
+            empty_awarding_criteria,
             process_struct,
+            feed_metadata_struct,
         ];
 
         if keep_cfs_raw_xml {
             columns.push(Series::new("cfs_raw_xml", empty));
         }
 
-        return DataFrame::new(columns)
-            .map_err(|e| AppError::ParseError(format!("Failed to create DataFrame: {e}")));
+        let df = DataFrame::new(columns)
+            .map_err(|e| AppError::ParseError(format!("Failed to create DataFrame: {e}")))?;
+        return cast_received_tender_quantity(df);
     }
 
     let len = entries.len();
@@ -326,6 +493,9 @@ fn entries_to_dataframe(entries: Vec<Entry>, keep_cfs_raw_xml: bool) -> AppResul
     let mut links = Vec::with_capacity(len);
     let mut summaries = Vec::with_capacity(len);
     let mut updateds = Vec::with_capacity(len);
+    let mut categories: Vec<Series> = Vec::with_capacity(len);
+    let mut authors = Vec::with_capacity(len);
+    let mut is_deleted = Vec::with_capacity(len);
     let mut contract_ids = Vec::with_capacity(len);
     let mut project_lots_structs: Vec<Series> = Vec::with_capacity(len);
     let mut cfs_raw_xmls = if keep_cfs_raw_xml {
@@ -340,6 +510,9 @@ fn entries_to_dataframe(entries: Vec<Entry>, keep_cfs_raw_xml: bool) -> AppResul
         links.push(entry.link.clone());
         summaries.push(entry.summary.clone());
         updateds.push(entry.updated.clone());
+        categories.push(Series::new("", &entry.categories));
+        authors.push(entry.author.clone());
+        is_deleted.push(entry.is_deleted);
         contract_ids.push(entry.contract_id.clone());
         let lot_struct = lots_to_struct_series(&entry.project_lots)?;
         project_lots_structs.push(lot_struct);
@@ -353,12 +526,18 @@ fn entries_to_dataframe(entries: Vec<Entry>, keep_cfs_raw_xml: bool) -> AppResul
     let process_struct = process_to_struct(&entries)?;
     let status_struct = status_to_struct(&entries)?;
     let terms_struct = terms_funding_program_to_struct(&entries)?;
+    let feed_metadata_struct = feed_metadata_to_struct(&entries)?;
     let project_lots_series = Series::new("project_lots", project_lots_structs);
     let tender_results_structs = entries
         .iter()
         .map(|entry| tender_results_to_struct_series(&entry.tender_results))
         .collect::<AppResult<Vec<_>>>()?;
     let tender_results_series = Series::new("tender_results", tender_results_structs);
+    let awarding_criteria_structs = entries
+        .iter()
+        .map(|entry| awarding_criteria_to_struct_series(&entry.awarding_criteria))
+        .collect::<AppResult<Vec<_>>>()?;
+    let awarding_criteria_series = Series::new("awarding_criteria", awarding_criteria_structs);
 
     let mut columns = vec![
         Series::new("id", ids),
@@ -366,6 +545,9 @@ fn entries_to_dataframe(entries: Vec<Entry>, keep_cfs_raw_xml: bool) -> AppResul
         Series::new("link", links),
         Series::new("summary", summaries),
         Series::new("updated", updateds),
+        Series::new("categories", categories),
+        Series::new("author", authors),
+        Series::new("is_deleted", is_deleted),
         status_struct,
         Series::new("contract_id", contract_ids),
         contracting_party_struct,
@@ -373,31 +555,82 @@ fn entries_to_dataframe(entries: Vec<Entry>, keep_cfs_raw_xml: bool) -> AppResul
         project_lots_series,
         tender_results_series,
         terms_struct,
+        awarding_criteria_series,
         process_struct,
+        feed_metadata_struct,
     ];
 
     if keep_cfs_raw_xml {
         columns.push(Series::new("cfs_raw_xml", cfs_raw_xmls));
     }
 
-    DataFrame::new(columns)
-        .map_err(|e| AppError::ParseError(format!("Failed to create DataFrame: {e}")))
+    let df = DataFrame::new(columns)
+        .map_err(|e| AppError::ParseError(format!("Failed to create DataFrame: {e}")))?;
+    cast_received_tender_quantity(df)
+}
+
+/// Casts the `result_received_tender_quantity` field of `tender_results` to `UInt32`.
+///
+/// The cast is attempted strictly. When a count in the batch is not numeric, the field is
+/// cast leniently instead (those counts become null, with a warning) rather than left as
+/// text, so every batch of a period keeps the same schema and can still be concatenated.
+fn cast_received_tender_quantity(df: DataFrame) -> AppResult<DataFrame> {
+    let DataType::List(inner) = df
+        .column("tender_results")
+        .map_err(|e| AppError::ParseError(format!("Missing tender_results column: {e}")))?
+        .dtype()
+    else {
+        return Ok(df);
+    };
+    let DataType::Struct(fields) = inner.as_ref() else {
+        return Ok(df);
+    };
+    let fields = fields
+        .iter()
+        .map(|field| {
+            if field.name() == "result_received_tender_quantity" {
+                Field::new(field.name(), DataType::UInt32)
+            } else {
+                field.clone()
+            }
+        })
+        .collect();
+    let target = DataType::List(Box::new(DataType::Struct(fields)));
+
+    let strict = df
+        .clone()
+        .lazy()
+        .with_column(col("tender_results").strict_cast(target.clone()))
+        .collect();
+    match strict {
+        Ok(df) => Ok(df),
+        Err(e) => {
+            warn!(error = %e, "Non-numeric received tender quantities were stored as null");
+            df.lazy()
+                .with_column(col("tender_results").cast(target))
+                .collect()
+                .map_err(|e| {
+                    AppError::ParseError(format!("Failed to cast received tender quantities: {e}"))
+                })
+        }
+    }
 }
 
 async fn read_xml_contents(paths: &[PathBuf], concurrency: usize) -> AppResult<Vec<Vec<u8>>> {
     let read_concurrency = concurrency.max(1);
     stream::iter(paths.iter().cloned())
         .map(|path| async move {
-            tokio_fs::read(&path)
+            let bytes = tokio_fs::read(&path)
                 .await
-                .map_err(|e| AppError::IoError(format!("Failed to read XML file {path:?}: {e}")))
+                .map_err(|e| AppError::IoError(format!("Failed to read XML file {path:?}: {e}")))?;
+            gunzip_if_compressed(&path, bytes)
         })
         .buffered(read_concurrency)
         .try_collect()
         .await
 }
 
-/// Parses XML/Atom files and converts them to Parquet format.
+/// Parses XML/Atom files and converts them to Parquet (or JSON Lines) format.
 ///
 /// This function processes extracted XML/Atom files from the extraction directory,
 /// parses them into `Entry` structures, and writes the results as Parquet files.
@@ -414,6 +647,9 @@ async fn read_xml_contents(paths: &[PathBuf], concurrency: usize) -> AppResult<V
 /// The function expects the following structure:
 /// - Input: `{extract_dir}/{period}/` (contains XML/Atom files)
 /// - Output: `{parquet_dir}/{period}.parquet` (or `{parquet_dir}/{period}/batch_*.parquet` if not concat)
+/// - With `output_format = jsonl`: `{parquet_dir}/{period}.jsonl` (or `{parquet_dir}/{period}/batch_*.jsonl`)
+/// - With `partitioned`: `{parquet_dir}/year=YYYY/month=MM/part-*.parquet` (`month=00` for
+///   year-only periods)
 ///
 /// # Optimizations
 ///
@@ -430,15 +666,27 @@ async fn read_xml_contents(paths: &[PathBuf], concurrency: usize) -> AppResult<V
 /// * `procurement_type` - Procurement type determining the extract and parquet directories
 /// * `batch_size` - Number of XML files to process per chunk (affects memory usage)
 /// * `config` - Resolved configuration containing directory paths and concurrency settings
+/// * `transformer` - Optional hook applied to every batch of entries before it is written
+///   (see [`crate::transformer::from_config`] for the built-in ones)
 ///
 /// # Behavior
 ///
-/// - **Filtering**: Only processes subdirectories whose names match keys in `target_links`
+/// - **Filtering**: Only processes subdirectories matched to keys in `target_links` by
+///   [`match_period_dirs`] (exact names only with `strict_period_dirs`)
 /// - **Skip empty**: Subdirectories with no entries are skipped (logged but not an error)
 /// - **Batch output**: Each chunk results in a batch_N.parquet file per period
+/// - **Amounts**: With `normalize_amounts`, amount fields are rewritten to dot-decimal form
+///   without thousands separators; values left as read are counted in
+///   [`ParseStats::unnormalized_amounts`]
+/// - **Transformer**: `transformer` runs on each batch after amount normalization and
+///   `dedupe_by_id`; a batch it empties writes nothing
+/// - **DuckDB sink**: With `duckdb_path` (`duckdb` feature), each period is also loaded into
+///   normalized tables of that database, replacing the period's previous rows
 /// - **Memory controls**: `batch_size` bounds the in-flight DataFrame and `read_concurrency` limits
 ///   parallel file reads. `parser_threads` limits the rayon thread pool for XML parsing parallelism.
-/// - **Progress tracking**: Elapsed time and throughput are logged after parsing completes
+/// - **Progress tracking**: A progress bar ticks per XML file and shows the current period and
+///   batch (hidden when `progress` is disabled or output is not a terminal); elapsed time and
+///   throughput are logged after parsing completes
 ///
 /// # Errors
 ///
@@ -447,33 +695,103 @@ async fn read_xml_contents(paths: &[PathBuf], concurrency: usize) -> AppResult<V
 /// - XML parsing fails
 /// - DataFrame creation fails
 /// - Parquet file writing fails
+///
+/// With `keep_going`, errors of a single period are recorded in
+/// [`ParseStats::failed_periods`] and the remaining periods are still parsed.
+#[tracing::instrument(
+    name = "parse_xmls",
+    skip_all,
+    fields(
+        procurement_type = procurement_type.display_name(),
+        period_count = target_links.len(),
+        batch_size = batch_size
+    )
+)]
 pub async fn parse_xmls(
     target_links: &BTreeMap<String, String>,
     procurement_type: &crate::models::ProcurementType,
     batch_size: usize,
     config: &crate::config::ResolvedConfig,
-) -> AppResult<()> {
+    transformer: Option<Box<dyn Transformer>>,
+) -> AppResult<ParseStats> {
+    let rayon_pool = parser_pool(config)?;
+    parse_xmls_with_pool(
+        target_links,
+        procurement_type,
+        batch_size,
+        config,
+        &rayon_pool,
+        transformer.as_deref(),
+    )
+    .await
+}
+
+/// Builds the rayon pool for XML parsing with `parser_threads` threads.
+///
+/// This is critical in Docker environments where available_parallelism() may return the
+/// host's CPU count, not the container's limit. By default (parser_threads=0), we
+/// auto-detect. Otherwise, use the configured value.
+pub(crate) fn parser_pool(config: &crate::config::ResolvedConfig) -> AppResult<rayon::ThreadPool> {
+    let num_threads = if config.parser_threads > 0 {
+        config.parser_threads
+    } else {
+        std::thread::available_parallelism()
+            .map(|p| p.get())
+            .unwrap_or(1)
+    };
+
+    let rayon_pool = ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .build()
+        .map_err(|e| {
+            AppError::IoError(format!(
+                "Failed to configure rayon thread pool for XML parsing: {e}"
+            ))
+        })?;
+
+    info!(
+        "XML parsing thread pool configured with {} threads",
+        num_threads
+    );
+    Ok(rayon_pool)
+}
+
+/// [`parse_xmls`] on a parser pool shared with other calls.
+pub(crate) async fn parse_xmls_with_pool(
+    target_links: &BTreeMap<String, String>,
+    procurement_type: &crate::models::ProcurementType,
+    batch_size: usize,
+    config: &crate::config::ResolvedConfig,
+    rayon_pool: &rayon::ThreadPool,
+    transformer: Option<&dyn Transformer>,
+) -> AppResult<ParseStats> {
     let extract_dir = procurement_type.extract_dir(config);
     let parquet_dir = procurement_type.parquet_dir(config);
 
     // Create parquet directory if it doesn't exist
     std_fs::create_dir_all(&parquet_dir)
         .map_err(|e| AppError::IoError(format!("Failed to create parquet directory: {e}")))?;
+    if let Some(cache_dir) = &config.cache_dir {
+        std_fs::create_dir_all(cache_dir).map_err(|e| {
+            AppError::IoError(format!(
+                "Failed to create cache directory {}: {e}",
+                cache_dir.display()
+            ))
+        })?;
+    }
 
     // Find all subdirectories with XML/atom files
     let subdirs = find_xmls(&extract_dir)?;
 
-    // Filter subdirectories that match keys in target_links
-    let subdirs_to_process: Vec<_> = subdirs
-        .into_iter()
-        .filter(|(subdir_name, _)| target_links.contains_key(subdir_name))
-        .collect();
+    // Match subdirectories to the keys in target_links
+    let subdirs_to_process =
+        match_period_dirs(subdirs, target_links.keys(), config.strict_period_dirs)?;
 
     let total_subdirs = subdirs_to_process.len();
 
     if total_subdirs == 0 {
         info!("No matching subdirectories found for parsing");
-        return Ok(());
+        return Ok(ParseStats::default());
     }
 
     // Calculate total XML files across all periods for logging
@@ -482,177 +800,153 @@ pub async fn parse_xmls(
         .map(|(_, files)| files.len())
         .sum();
 
+    let total_batches: usize = subdirs_to_process
+        .iter()
+        .map(|(_, files)| files.len().div_ceil(batch_size.max(1)))
+        .sum();
+
     let start = Instant::now();
     let mut total_parquet_bytes = 0u64;
 
-    info!(total = total_subdirs, "Starting XML parsing");
-
-    // Configure rayon thread pool for XML parsing.
-    // This is critical in Docker environments where available_parallelism() may return the host's CPU count,
-    // not the container's limit. By default (parser_threads=0), we auto-detect. Otherwise, use the configured value.
-    let num_threads = if config.parser_threads > 0 {
-        config.parser_threads
-    } else {
-        std::thread::available_parallelism()
-            .map(|p| p.get())
-            .unwrap_or(1)
-    };
-
-    let rayon_pool = ThreadPoolBuilder::new()
-        .num_threads(num_threads)
-        .build()
-        .map_err(|e| {
-            AppError::IoError(format!(
-                "Failed to configure rayon thread pool for XML parsing: {e}"
-            ))
-        })?;
-
     info!(
-        "XML parsing thread pool configured with {} threads",
-        num_threads
+        total = total_subdirs,
+        xml_files = total_xml_files,
+        batches = total_batches,
+        "Starting XML parsing"
     );
+    let progress = ParseProgress {
+        bar: create_progress_bar(total_xml_files as u64, !config.progress),
+        batches_started: AtomicUsize::new(0),
+        total_batches,
+        skipped_entries: AtomicUsize::new(0),
+        unnormalized_amounts: AtomicUsize::new(0),
+    };
+
+    if config.dedup == DedupStrategy::Latest && config.output_format != OutputFormat::Parquet {
+        warn!("dedup is only applied to concatenated Parquet output; JSON Lines files keep every entry");
+    }
+    if config.join_lot_results && config.output_format != OutputFormat::Parquet {
+        warn!("join_lot_results only applies to Parquet output; no lot results file is written");
+    }
+    if config.raw_xml_compression.is_some() && config.output_format != OutputFormat::Parquet {
+        warn!("raw_xml_compression only applies to Parquet output; JSON Lines files are not compressed");
+    }
+    if config.enrich_cpv && config.output_format != OutputFormat::Parquet {
+        warn!("enrich_cpv only applies to Parquet output; JSON Lines files have no project_cpv_description");
+    }
+    if config.max_entries_per_file.is_some()
+        && (config.output_format != OutputFormat::Parquet
+            || config.concat_batches
+            || config.single_file_stream)
+    {
+        warn!("max_entries_per_file only splits Parquet batch files; concatenated, streamed, and JSON Lines output is written whole");
+    }
 
+    if config.single_file_stream {
+        if config.output_format != OutputFormat::Parquet {
+            warn!("single_file_stream only applies to Parquet output; JSON Lines batches are written as usual");
+        } else if config.concat_batches || config.dedup == DedupStrategy::Latest {
+            warn!("single_file_stream is enabled: concat_batches and dedup are ignored");
+        }
+    }
     // Warn about concat_batches memory usage if enabled.
-    if config.concat_batches {
+    if config.concat_batches
+        && !(config.single_file_stream && config.output_format == OutputFormat::Parquet)
+    {
         warn!("concat_batches is enabled: entire periods will be loaded into memory before concatenation. Ensure sufficient RAM is available.");
     }
 
     let mut processed_count = 0;
     let mut skipped_count = 0;
-
-    // Process each subdirectory
-    for (subdir_name, xml_files) in subdirs_to_process {
-        let chunk_size = batch_size.max(1);
-        let mut has_entries = false;
-        let mut batch_index = 0;
-        let period_dir = parquet_dir.join(&subdir_name);
-        let mut period_dir_created = false;
-        let mut batch_paths: Vec<PathBuf> = Vec::new();
-
-        for xml_chunk in xml_files.chunks(chunk_size) {
-            let xml_contents = read_xml_contents(xml_chunk, config.read_concurrency).await?;
-
-            // Use scoped rayon pool for parallel XML parsing.
-            // This respects the configured thread count instead of using the global pool.
-            let parsed_entry_batches: Vec<Vec<Entry>> = rayon_pool.install(|| {
-                xml_contents
-                    .par_iter()
-                    .map(|content| parse_xml_bytes(content, config.keep_cfs_raw_xml))
-                    .collect::<AppResult<Vec<_>>>()
-            })?;
-
-            // Drop raw XML bytes here to free memory before DataFrame construction.
-            // This is important for peak memory management: raw XML + parsed entries
-            // would otherwise both exist in memory simultaneously.
-            drop(xml_contents);
-
-            let mut chunk_entries = Vec::new();
-            for mut entries in parsed_entry_batches {
-                if entries.is_empty() {
+    let mut total_entries = 0;
+    let mut parquet_file_count = 0;
+    let mut entries_by_period = BTreeMap::new();
+    let mut periods = BTreeMap::new();
+    let mut failed_periods = BTreeMap::new();
+    let context = PeriodContext {
+        parquet_dir: &parquet_dir,
+        batch_size,
+        rayon_pool,
+        progress: &progress,
+        config,
+        transformer,
+    };
+    // Parse up to `parallel_periods` periods at once; results are recorded as they complete.
+    // Without `keep_going`, a failure stops new periods from starting, but the periods in
+    // flight still run to completion so that every error is reported.
+    let failed = AtomicBool::new(false);
+    let mut errors = BTreeMap::new();
+    let mut results = stream::iter(subdirs_to_process)
+        .take_while(|_| future::ready(!failed.load(Ordering::Relaxed)))
+        .map(|(subdir_name, xml_files)| {
+            let context = &context;
+            async move {
+                let period_start = Instant::now();
+                let output = parse_period(&subdir_name, &xml_files, context).await;
+                (subdir_name, xml_files.len(), period_start, output)
+            }
+        })
+        .buffer_unordered(config.parallel_periods.max(1));
+    while let Some((subdir_name, xml_file_count, period_start, output)) = results.next().await {
+        let output = match output {
+            Ok(output) => output,
+            Err(e) => {
+                #[cfg(feature = "metrics")]
+                crate::metrics::record_parse_error(&subdir_name);
+                if !config.keep_going {
+                    failed.store(true, Ordering::Relaxed);
+                    errors.insert(subdir_name, e);
                     continue;
                 }
-                chunk_entries.append(&mut entries);
-            }
-
-            if chunk_entries.is_empty() {
+                warn!(period = subdir_name, error = %e, "Failed to parse period, continuing");
+                failed_periods.insert(subdir_name, e.to_string());
                 continue;
             }
+        };
 
-            if !period_dir_created {
-                if period_dir.exists() {
-                    std_fs::remove_dir_all(&period_dir).map_err(|e| {
-                        AppError::IoError(format!(
-                            "Failed to remove previous parquet directory {period_dir:?}: {e}"
-                        ))
-                    })?;
-                }
-                std_fs::create_dir_all(&period_dir).map_err(|e| {
-                    AppError::IoError(format!(
-                        "Failed to create parquet period directory {period_dir:?}: {e}"
-                    ))
-                })?;
-                period_dir_created = true;
-            }
-
-            has_entries = true;
-            let mut chunk_df = entries_to_dataframe(chunk_entries, config.keep_cfs_raw_xml)?;
-            let batch_path = period_dir.join(format!("batch_{batch_index}.parquet"));
-            let mut file = File::create(&batch_path).map_err(|e| {
-                AppError::IoError(format!(
-                    "Failed to create Parquet batch file {batch_path:?}: {e}"
-                ))
-            })?;
-
-            ParquetWriter::new(&mut file)
-                .finish(&mut chunk_df)
-                .map_err(|e| AppError::ParseError(format!("Failed to write Parquet batch: {e}")))?;
-
-            batch_paths.push(batch_path);
-            batch_index += 1;
-        }
-
-        if !has_entries {
-            skipped_count += 1;
-            if period_dir_created {
-                std_fs::remove_dir_all(&period_dir).map_err(|e| {
-                    AppError::IoError(format!(
-                        "Failed to remove empty parquet directory {period_dir:?}: {e}"
-                    ))
-                })?;
+        let mut period_stats = PeriodParseStats {
+            xml_files: xml_file_count,
+            ..PeriodParseStats::default()
+        };
+        match output {
+            Some(output) => {
+                processed_count += 1;
+                total_entries += output.entries;
+                parquet_file_count += output.parquet_files;
+                total_parquet_bytes += output.parquet_bytes;
+                entries_by_period.insert(subdir_name.clone(), output.entries);
+                period_stats.entries = output.entries;
+                period_stats.output_paths = output.paths;
             }
-            continue;
-        }
-
-        let mut output_paths = Vec::new();
-        if config.concat_batches {
-            let glob_path = period_dir.join("batch_*.parquet");
-            let glob_str = glob_path.to_string_lossy().into_owned();
-            let mut combined = LazyFrame::scan_parquet(&glob_str, ScanArgsParquet::default())
-                .map_err(|e| {
-                    AppError::ParseError(format!(
-                        "Failed to scan parquet batches for {subdir_name}: {e}"
-                    ))
-                })?
-                .collect()
-                .map_err(|e| {
-                    AppError::ParseError(format!(
-                        "Failed to collect combined DataFrame for {subdir_name}: {e}"
-                    ))
-                })?;
-
-            let final_path = parquet_dir.join(format!("{subdir_name}.parquet"));
-            let mut final_file = File::create(&final_path).map_err(|e| {
-                AppError::IoError(format!(
-                    "Failed to create final Parquet file {final_path:?}: {e}"
-                ))
-            })?;
-
-            ParquetWriter::new(&mut final_file)
-                .finish(&mut combined)
-                .map_err(|e| {
-                    AppError::ParseError(format!("Failed to write final Parquet file: {e}"))
-                })?;
-
-            output_paths.push(final_path);
-            std_fs::remove_dir_all(&period_dir).map_err(|e| {
-                AppError::IoError(format!(
-                    "Failed to remove temporary parquet directory {period_dir:?}: {e}"
-                ))
-            })?;
-        } else {
-            output_paths.extend(batch_paths.iter().cloned());
-        }
-
-        for output_path in output_paths {
-            let metadata = std_fs::metadata(&output_path).map_err(|e| {
-                AppError::IoError(format!(
-                    "Failed to read Parquet file metadata {output_path:?}: {e}"
-                ))
-            })?;
-            total_parquet_bytes += metadata.len();
+            None => skipped_count += 1,
         }
+        period_stats.elapsed_ms = period_start.elapsed().as_millis() as u64;
+        periods.insert(subdir_name, period_stats);
+    }
+    drop(results);
+    if errors.len() > 1 {
+        let details: Vec<String> = errors
+            .iter()
+            .map(|(period, e)| format!("{period}: {e}"))
+            .collect();
+        return Err(AppError::ParseError(format!(
+            "{} periods failed to parse: {}",
+            errors.len(),
+            details.join("; ")
+        )));
+    }
+    if let Some((_, e)) = errors.pop_first() {
+        return Err(e);
+    }
 
-        processed_count += 1;
+    progress.bar.finish();
+    let skipped_entries = progress.skipped_entries.into_inner();
+    let unnormalized_amounts = progress.unnormalized_amounts.into_inner();
+    if unnormalized_amounts > 0 {
+        warn!(
+            unnormalized_amounts,
+            "Some amounts could not be normalized and were kept as read"
+        );
     }
 
     let elapsed = start.elapsed();
@@ -670,94 +964,1085 @@ pub async fn parse_xmls(
         processed = processed_count,
         skipped = skipped_count,
         xml_files = total_xml_files,
-        parquet_files = processed_count,
+        parquet_files = parquet_file_count,
+        skipped_entries,
         elapsed = elapsed_str,
         output_size_mb = size_mb,
         throughput_mb_s = throughput_mb_s,
-        parser_threads = num_threads,
+        parser_threads = rayon_pool.current_num_threads(),
         "Parsing completed"
     );
 
-    Ok(())
+    Ok(ParseStats {
+        periods_parsed: processed_count,
+        periods_skipped: skipped_count,
+        xml_files: total_xml_files,
+        entries: total_entries,
+        skipped_entries,
+        unnormalized_amounts,
+        parquet_files: parquet_file_count,
+        parquet_bytes: total_parquet_bytes,
+        entries_by_period,
+        periods,
+        failed_periods,
+        elapsed_ms: elapsed.as_millis() as u64,
+    })
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::models::{StatusCode, TermsFundingProgram};
+/// Progress of the parsing phase: the bar ticks per XML file and its message names the
+/// current period and batch. Shared by the periods parsed concurrently.
+struct ParseProgress {
+    bar: ProgressBar,
+    batches_started: AtomicUsize,
+    total_batches: usize,
+    /// Malformed entries dropped so far by lenient parsing
+    skipped_entries: AtomicUsize,
+    /// Amounts kept as read so far because they could not be normalized
+    unnormalized_amounts: AtomicUsize,
+}
 
-    #[test]
-    fn entries_to_dataframe_empty_yields_zero_rows() {
-        let df = entries_to_dataframe(vec![], false).unwrap();
-        assert_eq!(df.height(), 0);
-        assert_eq!(df.width(), 13);
+impl ParseProgress {
+    fn start_batch(&self, period: &str) {
+        let started = self.batches_started.fetch_add(1, Ordering::Relaxed) + 1;
+        self.bar
+            .set_message(format!("{period} batch {started}/{}", self.total_batches));
     }
+}
 
-    #[test]
-    fn entries_to_dataframe_single_entry() {
-        let entry = Entry {
-            id: Some("id".to_string()),
-            title: Some("title".to_string()),
-            link: Some("link".to_string()),
-            summary: Some("summary".to_string()),
-            updated: Some("2023-01-01".to_string()),
-            status: StatusCode::default(),
-            contract_id: None,
-            contracting_party_name: None,
-            contracting_party_website: None,
-            contracting_party_type_code: None,
-            contracting_party_type_code_list_uri: None,
-            contracting_party_activity_code: None,
-            contracting_party_activity_code_list_uri: None,
-            contracting_party_city: None,
-            contracting_party_zip: None,
-            contracting_party_country_code: None,
-            contracting_party_country_code_list_uri: None,
-            project_name: None,
-            project_type_code: None,
-            project_type_code_list_uri: None,
-            project_sub_type_code: None,
-            project_sub_type_code_list_uri: None,
-            project_total_amount: None,
-            project_total_currency: None,
-            project_tax_exclusive_amount: None,
-            project_tax_exclusive_currency: None,
-            project_cpv_code: None,
-            project_cpv_code_list_uri: None,
-            project_country_code: None,
-            project_country_code_list_uri: None,
-            project_lots: Vec::new(),
-            tender_results: vec![TenderResultRow {
-                result_id: Some("1".to_string()),
-                result_lot_id: Some("0".to_string()),
-                ..Default::default()
-            }],
-            terms_funding_program: TermsFundingProgram::default(),
-            process_end_date: None,
-            process_procedure_code: None,
-            process_procedure_code_list_uri: None,
-            process_urgency_code: None,
-            process_urgency_code_list_uri: None,
-            cfs_raw_xml: Some("<xml/>".to_string()),
-        };
+/// Everything `parse_period` needs besides the period itself, shared by the periods of a
+/// `parse_xmls` run.
+struct PeriodContext<'a> {
+    parquet_dir: &'a Path,
+    batch_size: usize,
+    rayon_pool: &'a rayon::ThreadPool,
+    progress: &'a ParseProgress,
+    config: &'a crate::config::ResolvedConfig,
+    transformer: Option<&'a dyn Transformer>,
+}
 
-        let df = entries_to_dataframe(vec![entry], true).unwrap();
-        assert_eq!(df.height(), 1);
-        let tender_results_series = df.column("tender_results").unwrap();
-        assert_eq!(tender_results_series.len(), 1);
-        assert_eq!(df.width(), 14);
-        let lots_col = df.column("project_lots").unwrap();
-        assert!(matches!(lots_col.dtype(), DataType::List(_)));
-        let contracting_party_col = df.column("contracting_party").unwrap();
-        assert!(matches!(contracting_party_col.dtype(), DataType::Struct(_)));
-        let project_col = df.column("project").unwrap();
-        assert!(matches!(project_col.dtype(), DataType::Struct(_)));
-        let process_col = df.column("process").unwrap();
-        assert!(matches!(process_col.dtype(), DataType::Struct(_)));
-        let value = df.column("id").unwrap().get(0).unwrap();
+/// Output of one successfully parsed period.
+#[derive(Default)]
+struct PeriodOutput {
+    entries: usize,
+    parquet_files: usize,
+    parquet_bytes: u64,
+    /// Every file written, lot results included
+    paths: Vec<PathBuf>,
+}
+
+/// Name of the column added by `enrich_cpv`.
+const CPV_DESCRIPTION_COLUMN: &str = "project_cpv_description";
+
+/// Appends the `project_cpv_description` column: the description of every code in
+/// `project.cpv_code`, joined with `_` like the codes themselves. Codes without a
+/// description are left out, and a row where none resolves is null.
+fn add_cpv_descriptions(df: &mut DataFrame) -> AppResult<()> {
+    let to_parse_error = |e: PolarsError| {
+        AppError::ParseError(format!(
+            "Failed to add {CPV_DESCRIPTION_COLUMN} column: {e}"
+        ))
+    };
+    let cpv_codes = df
+        .column("project")
+        .and_then(|project| project.struct_()?.field_by_name("cpv_code"))
+        .map_err(to_parse_error)?;
+    let descriptions: Vec<Option<String>> = cpv_codes
+        .str()
+        .map_err(to_parse_error)?
+        .into_iter()
+        .map(|codes| {
+            let described: Vec<&str> = codes?
+                .split('_')
+                .filter_map(crate::cpv::cpv_description)
+                .collect();
+            (!described.is_empty()).then(|| described.join("_"))
+        })
+        .collect();
+    df.with_column(Series::new(CPV_DESCRIPTION_COLUMN, descriptions))
+        .map_err(to_parse_error)?;
+    Ok(())
+}
+
+/// Name of the temporary column holding the deduplication key.
+const DEDUP_KEY_COLUMN: &str = "__dedup_key";
+
+/// Keeps the most recently `updated` row per `contract_id`, falling back to `id` when
+/// `contract_id` is null. Rows without an `updated` value sort last.
+fn dedup_latest(frame: LazyFrame) -> LazyFrame {
+    frame
+        .with_column(coalesce(&[col("contract_id"), col("id")]).alias(DEDUP_KEY_COLUMN))
+        .sort(
+            ["updated"],
+            SortMultipleOptions::default()
+                .with_order_descending(true)
+                .with_nulls_last(true)
+                .with_maintain_order(true),
+        )
+        .unique_stable(
+            Some(vec![DEDUP_KEY_COLUMN.to_string()]),
+            UniqueKeepStrategy::First,
+        )
+        .drop([DEDUP_KEY_COLUMN])
+}
+
+/// Removes the entries whose `id` is already in `seen` and records the remaining ids.
+///
+/// Within `entries` the last occurrence of an id is kept; entries without an `id` are
+/// always kept. The order of the kept entries is preserved. Returns the number of removed
+/// entries.
+fn drop_seen_ids(entries: &mut Vec<Entry>, seen: &mut HashSet<String>) -> usize {
+    let before = entries.len();
+    let mut kept = Vec::with_capacity(before);
+    for entry in entries.drain(..).rev() {
+        if let Some(id) = &entry.id {
+            if !seen.insert(id.clone()) {
+                continue;
+            }
+        }
+        kept.push(entry);
+    }
+    kept.reverse();
+    *entries = kept;
+    before - entries.len()
+}
+
+/// Directory holding a period's files in the `partitioned` layout:
+/// `{root}/year=YYYY/month=MM`, with `month=00` for a year-only period (`YYYY`).
+///
+/// Keeping every period at the same depth lets one `year=*/month=*/*.parquet` glob read
+/// monthly and yearly archives alike.
+fn partition_dir(root: &Path, period: &str) -> PathBuf {
+    let (year, month) = period.split_at(period.len().min(4));
+    let month = if month.is_empty() { "00" } else { month };
+    root.join(format!("year={year}"))
+        .join(format!("month={month}"))
+}
+
+/// Writes the unmapped element counts of `period` to `{parquet_dir}/{period}.unknown_elements.json`
+/// as a JSON object of local name to occurrence count, sorted by name. An existing report is
+/// overwritten, and an empty object means every element was mapped.
+///
+/// Returns the path of the report.
+fn write_unknown_elements_report(
+    parquet_dir: &Path,
+    period: &str,
+    unknown_elements: &BTreeMap<String, u64>,
+) -> AppResult<PathBuf> {
+    let path = parquet_dir.join(format!("{period}.unknown_elements.json"));
+    let json = serde_json::to_string_pretty(unknown_elements).map_err(|e| {
+        AppError::ParseError(format!("Failed to serialize unknown elements report: {e}"))
+    })?;
+    std_fs::write(&path, json + "\n").map_err(|e| {
+        AppError::IoError(format!(
+            "Failed to write unknown elements report {path:?}: {e}"
+        ))
+    })?;
+    Ok(path)
+}
+
+/// Parses a batch of XML documents on the scoped rayon pool, which respects the configured
+/// thread count instead of using the global pool.
+///
+/// The work is spawned onto the pool and awaited, so other periods parsed concurrently
+/// keep making progress in the meantime.
+async fn parse_batch(
+    rayon_pool: &rayon::ThreadPool,
+    xml_contents: Vec<Vec<u8>>,
+    bar: ProgressBar,
+    config: &crate::config::ResolvedConfig,
+) -> AppResult<Vec<ParsedFeed>> {
+    let options = ParseOptions::from(config);
+    let cache_dir = config.cache_dir.clone();
+    let (sender, receiver) = oneshot::channel();
+    rayon_pool.spawn(move || {
+        let parsed_feeds = xml_contents
+            .par_iter()
+            .map(|content| {
+                let feed = parse_xml_bytes_cached(content, options, cache_dir.as_deref());
+                bar.inc(1);
+                feed
+            })
+            .collect::<AppResult<Vec<_>>>();
+
+        // Drop raw XML bytes here to free memory before DataFrame construction.
+        // This is important for peak memory management: raw XML + parsed entries
+        // would otherwise both exist in memory simultaneously.
+        drop(xml_contents);
+        // The receiver is only gone when the period was cancelled.
+        let _ = sender.send(parsed_feeds);
+    });
+    receiver
+        .await
+        .map_err(|_| AppError::ParseError("XML parsing task did not complete".into()))?
+}
+
+/// Where the output of `period` is written: the single period file when batches are
+/// concatenated or streamed, and the directory of batch files otherwise. With
+/// `partitioned`, both live in the period's [`partition_dir`].
+///
+/// Whatever is at this path is replaced when the period is parsed again.
+pub(crate) fn period_output_path(
+    parquet_dir: &Path,
+    period: &str,
+    config: &crate::config::ResolvedConfig,
+) -> PathBuf {
+    let extension = match config.output_format {
+        OutputFormat::Parquet => "parquet",
+        OutputFormat::Jsonl => "jsonl",
+    };
+    let single_file = config.concat_batches
+        || (config.single_file_stream && config.output_format == OutputFormat::Parquet);
+    match (single_file, config.partitioned) {
+        (true, true) => partition_dir(parquet_dir, period).join(format!("part-0.{extension}")),
+        (true, false) => parquet_dir.join(format!("{period}.{extension}")),
+        (false, true) => partition_dir(parquet_dir, period),
+        (false, false) => parquet_dir.join(period),
+    }
+}
+
+/// Parses the XML files of one period into Parquet batches (optionally concatenated).
+///
+/// With `partitioned`, the period is written to its [`partition_dir`] as `part-N` files
+/// (`part-0` when concatenated), and only that partition is replaced on re-runs.
+///
+/// With `single_file_stream` (Parquet output), each batch is appended as a row group to one
+/// period file through a [`BatchedWriter`], so only one batch is held in memory and no
+/// batch files or concatenation pass are needed. The file is written as `*.parquet.part`
+/// and renamed once complete.
+///
+/// With `dedupe_by_id`, the batches are parsed last-first so that the last occurrence of
+/// each entry `id` (in file-name order) is the one written.
+///
+/// With `duckdb_path` (`duckdb` feature), the written entries then replace the period's
+/// rows in that database.
+///
+/// With `incremental`, the batch directory is kept and XML files listed (by name and
+/// SHA-256) in its `.processed_files.json` manifest are skipped: only the new files are
+/// parsed, into batches numbered after the existing ones, and the manifest is updated after
+/// each batch, so an interrupted run resumes after its last written batch.
+/// Concatenation then reads every batch, and the batches stay next to the period file.
+/// Without a readable manifest the period is written from scratch. A period with no new
+/// files writes nothing and reports zero entries.
+///
+/// Returns `None` when the period contains no entries; any partial output is removed. On
+/// error, the files this call started writing are removed too, except for the batches and
+/// manifest of `incremental`, which the next run resumes from.
+async fn parse_period(
+    subdir_name: &str,
+    xml_files: &[PathBuf],
+    context: &PeriodContext<'_>,
+) -> AppResult<Option<PeriodOutput>> {
+    let mut partial_output = Vec::new();
+    let output = write_period(subdir_name, xml_files, context, &mut partial_output).await;
+    if output.is_err() {
+        for path in partial_output {
+            let removed = if path.is_dir() {
+                std_fs::remove_dir_all(&path)
+            } else {
+                std_fs::remove_file(&path)
+            };
+            if let Err(e) = removed {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    warn!(path = %path.display(), error = %e, "Failed to remove partial output");
+                }
+            }
+        }
+    }
+    output
+}
+
+/// Writes the output of one period for [`parse_period`], adding each file or directory it
+/// creates (or truncates) to `partial_output`.
+async fn write_period(
+    subdir_name: &str,
+    xml_files: &[PathBuf],
+    context: &PeriodContext<'_>,
+    partial_output: &mut Vec<PathBuf>,
+) -> AppResult<Option<PeriodOutput>> {
+    let PeriodContext {
+        parquet_dir,
+        batch_size,
+        rayon_pool,
+        progress,
+        config,
+        transformer,
+    } = *context;
+    let chunk_size = batch_size.max(1);
+    let mut entry_count = 0;
+    let mut parquet_files = 0;
+    let mut parquet_bytes = 0u64;
+    let mut has_entries = false;
+    let mut batch_index = 0;
+    let (period_dir, batch_prefix) = if config.partitioned {
+        (partition_dir(parquet_dir, subdir_name), "part-")
+    } else {
+        (parquet_dir.join(subdir_name), "batch_")
+    };
+    let mut period_dir_created = false;
+    let mut batch_paths: Vec<PathBuf> = Vec::new();
+    let mut seen_ids = HashSet::new();
+    let mut duplicates_removed = 0;
+    let mut lot_rows = Vec::new();
+    let mut unknown_elements: BTreeMap<String, u64> = BTreeMap::new();
+    let stream = config.single_file_stream && config.output_format == OutputFormat::Parquet;
+    let stream_path = period_output_path(parquet_dir, subdir_name, config);
+    let stream_part_path = stream_path.with_extension("parquet.part");
+    let mut stream_writer: Option<ColumnCompressionWriter<File>> = None;
+
+    let mut manifest = None;
+    let mut appending = false;
+    let mut pending_files = xml_files.to_vec();
+    let mut pending_records = Vec::new();
+    if config.incremental {
+        let files = xml_files
+            .iter()
+            .map(|path| ProcessedFile::from_path(path))
+            .collect::<AppResult<Vec<_>>>()?;
+        let previous = if period_dir.exists() {
+            ProcessedFiles::load(&period_dir)
+        } else {
+            None
+        };
+        match previous {
+            Some(previous) => {
+                let (new_paths, new_files): (Vec<_>, Vec<_>) = xml_files
+                    .iter()
+                    .cloned()
+                    .zip(files)
+                    .filter(|(_, file)| !previous.contains(file))
+                    .unzip();
+                for file in new_files.iter().filter(|file| previous.has_changed(file)) {
+                    warn!(
+                        period = subdir_name,
+                        file = file.name,
+                        "XML file changed since it was processed; its previous entries stay in the earlier batches"
+                    );
+                }
+                info!(
+                    period = subdir_name,
+                    new_files = new_paths.len(),
+                    processed_files = xml_files.len() - new_paths.len(),
+                    "Skipping XML files already processed"
+                );
+                if new_paths.is_empty() {
+                    progress.bar.inc(xml_files.len() as u64);
+                    return Ok(Some(PeriodOutput::default()));
+                }
+                progress.bar.inc((xml_files.len() - new_paths.len()) as u64);
+                manifest = Some(previous);
+                appending = true;
+                // The existing batches stay in place; new ones are numbered after them.
+                period_dir_created = true;
+                batch_index = batch_files(&period_dir, batch_prefix)?
+                    .last()
+                    .map_or(0, |(index, _)| index + 1);
+                pending_files = new_paths;
+                pending_records = new_files;
+            }
+            None => {
+                manifest = Some(ProcessedFiles::default());
+                pending_records = files;
+            }
+        }
+    }
+
+    let mut xml_chunks: Vec<(usize, &[PathBuf])> = pending_files
+        .chunks(chunk_size)
+        .enumerate()
+        .map(|(i, chunk)| (i * chunk_size, chunk))
+        .collect();
+    if config.dedupe_by_id {
+        xml_chunks.reverse();
+    }
+    for (chunk_offset, xml_chunk) in xml_chunks {
+        // The manifest is saved after every batch, so that a run interrupted halfway does
+        // not parse the files of its written batches again.
+        let mut record_chunk = |period_dir_created: bool| -> AppResult<()> {
+            if let Some(manifest) = &mut manifest {
+                manifest.files.extend_from_slice(
+                    &pending_records[chunk_offset..chunk_offset + xml_chunk.len()],
+                );
+                if period_dir_created {
+                    manifest.save(&period_dir)?;
+                }
+            }
+            Ok(())
+        };
+        progress.start_batch(subdir_name);
+        let xml_contents = read_xml_contents(xml_chunk, config.read_concurrency).await?;
+
+        let parsed_feeds =
+            parse_batch(rayon_pool, xml_contents, progress.bar.clone(), config).await?;
+
+        let mut chunk_entries = Vec::new();
+        for mut feed in parsed_feeds {
+            progress
+                .skipped_entries
+                .fetch_add(feed.skipped_entries, Ordering::Relaxed);
+            for (name, count) in std::mem::take(&mut feed.unknown_elements) {
+                *unknown_elements.entry(name).or_default() += count;
+            }
+            if feed.entries.is_empty() {
+                continue;
+            }
+            chunk_entries.append(&mut feed.entries);
+        }
+        if config.normalize_amounts {
+            let unnormalized: usize = chunk_entries.iter_mut().map(normalize_entry_amounts).sum();
+            progress
+                .unnormalized_amounts
+                .fetch_add(unnormalized, Ordering::Relaxed);
+        }
+        if config.dedupe_by_id {
+            duplicates_removed += drop_seen_ids(&mut chunk_entries, &mut seen_ids);
+        }
+        if let Some(transformer) = transformer {
+            transformer.transform(&mut chunk_entries)?;
+        }
+
+        if chunk_entries.is_empty() {
+            record_chunk(period_dir_created)?;
+            continue;
+        }
+
+        if !period_dir_created {
+            if period_dir.exists() {
+                std_fs::remove_dir_all(&period_dir).map_err(|e| {
+                    AppError::IoError(format!(
+                        "Failed to remove previous parquet directory {period_dir:?}: {e}"
+                    ))
+                })?;
+            }
+            if !config.incremental {
+                partial_output.push(period_dir.clone());
+            }
+            std_fs::create_dir_all(&period_dir).map_err(|e| {
+                AppError::IoError(format!(
+                    "Failed to create parquet period directory {period_dir:?}: {e}"
+                ))
+            })?;
+            period_dir_created = true;
+        }
+
+        has_entries = true;
+        entry_count += chunk_entries.len();
+
+        if config.output_format == OutputFormat::Jsonl {
+            // JSON Lines batches are appended straight to the period file when concatenating.
+            let (jsonl_path, append) = if config.concat_batches {
+                (
+                    period_output_path(parquet_dir, subdir_name, config),
+                    !batch_paths.is_empty(),
+                )
+            } else {
+                (
+                    period_dir.join(format!("{batch_prefix}{batch_index}.jsonl")),
+                    false,
+                )
+            };
+            if !append {
+                partial_output.push(jsonl_path.clone());
+            }
+            write_jsonl_file(&jsonl_path, &chunk_entries, config.keep_cfs_raw_xml, append)?;
+            if !append {
+                batch_paths.push(jsonl_path);
+            }
+            batch_index += 1;
+            continue;
+        }
+
+        if config.join_lot_results {
+            lot_rows.extend(lot_result_rows(&chunk_entries));
+        }
+        let mut chunk_df = entries_to_dataframe(chunk_entries, config.keep_cfs_raw_xml)?;
+        if config.enrich_cpv {
+            add_cpv_descriptions(&mut chunk_df)?;
+        }
+        if stream {
+            // One chunk per column so that the batch becomes exactly one row group.
+            chunk_df.as_single_chunk_par();
+            let writer = match &mut stream_writer {
+                Some(writer) => writer,
+                None => {
+                    partial_output.push(stream_part_path.clone());
+                    let file = File::create(&stream_part_path).map_err(|e| {
+                        AppError::IoError(format!(
+                            "Failed to create Parquet file {stream_part_path:?}: {e}"
+                        ))
+                    })?;
+                    let writer = ColumnCompressionWriter::new(
+                        file,
+                        &chunk_df.schema(),
+                        parquet_compression(config.parquet_compression),
+                        raw_xml_override(config),
+                    )
+                    .map_err(|e| {
+                        AppError::ParseError(format!("Failed to start Parquet file: {e}"))
+                    })?;
+                    stream_writer.insert(writer)
+                }
+            };
+            writer.write_batch(&chunk_df).map_err(|e| {
+                AppError::ParseError(format!("Failed to append Parquet row group: {e}"))
+            })?;
+            batch_index += 1;
+            continue;
+        }
+        // A batch over `max_entries_per_file` rows is split into `{prefix}{i}_{j}` shards.
+        let shards = match config.max_entries_per_file {
+            Some(max_entries) if chunk_df.height() > max_entries => (0..chunk_df.height())
+                .step_by(max_entries)
+                .enumerate()
+                .map(|(shard, offset)| {
+                    (
+                        format!("{batch_prefix}{batch_index}_{shard}.parquet"),
+                        chunk_df.slice(offset as i64, max_entries),
+                    )
+                })
+                .collect(),
+            _ => vec![(format!("{batch_prefix}{batch_index}.parquet"), chunk_df)],
+        };
+        for (name, mut shard_df) in shards {
+            let batch_path = period_dir.join(name);
+            let mut file = File::create(&batch_path).map_err(|e| {
+                AppError::IoError(format!(
+                    "Failed to create Parquet batch file {batch_path:?}: {e}"
+                ))
+            })?;
+
+            write_dataframe(
+                &mut file,
+                &mut shard_df,
+                parquet_compression(config.parquet_compression),
+                raw_xml_override(config),
+            )
+            .map_err(|e| AppError::ParseError(format!("Failed to write Parquet batch: {e}")))?;
+
+            batch_paths.push(batch_path);
+        }
+        batch_index += 1;
+        record_chunk(period_dir_created)?;
+    }
+
+    if config.dedupe_by_id {
+        info!(
+            period = subdir_name,
+            duplicates_removed, "Removed duplicate entries by id (last occurrence kept)"
+        );
+    }
+
+    if !has_entries {
+        if appending {
+            // The new files held no entries; they were recorded so they are skipped next time.
+            return Ok(Some(PeriodOutput::default()));
+        }
+        if period_dir_created {
+            std_fs::remove_dir_all(&period_dir).map_err(|e| {
+                AppError::IoError(format!(
+                    "Failed to remove empty parquet directory {period_dir:?}: {e}"
+                ))
+            })?;
+        }
+        return Ok(None);
+    }
+
+    let mut output_paths = Vec::new();
+    if let Some(writer) = stream_writer.take() {
+        writer
+            .finish()
+            .map_err(|e| AppError::ParseError(format!("Failed to finish Parquet file: {e}")))?;
+        drop(writer);
+        std_fs::rename(&stream_part_path, &stream_path).map_err(|e| {
+            AppError::IoError(format!(
+                "Failed to move Parquet file into place {stream_path:?}: {e}"
+            ))
+        })?;
+        debug!(
+            period = subdir_name,
+            row_groups = batch_index,
+            "Streamed period Parquet file"
+        );
+        output_paths.push(stream_path);
+        if !config.partitioned {
+            // The period directory only held stale batches from earlier runs.
+            std_fs::remove_dir_all(&period_dir).map_err(|e| {
+                AppError::IoError(format!(
+                    "Failed to remove temporary parquet directory {period_dir:?}: {e}"
+                ))
+            })?;
+        }
+    } else if config.concat_batches && config.output_format == OutputFormat::Parquet {
+        let glob_path = period_dir.join(format!("{batch_prefix}*.parquet"));
+        let glob_str = glob_path.to_string_lossy().into_owned();
+        let mut combined = LazyFrame::scan_parquet(&glob_str, ScanArgsParquet::default())
+            .map_err(|e| {
+                AppError::ParseError(format!(
+                    "Failed to scan parquet batches for {subdir_name}: {e}"
+                ))
+            })?
+            .collect()
+            .map_err(|e| {
+                AppError::ParseError(format!(
+                    "Failed to collect combined DataFrame for {subdir_name}: {e}"
+                ))
+            })?;
+
+        if config.dedup == DedupStrategy::Latest {
+            let before = combined.height();
+            combined = dedup_latest(combined.lazy()).collect().map_err(|e| {
+                AppError::ParseError(format!(
+                    "Failed to deduplicate entries for {subdir_name}: {e}"
+                ))
+            })?;
+            entry_count = combined.height();
+            info!(
+                period = subdir_name,
+                rows_before = before,
+                rows_dropped = before - entry_count,
+                "Deduplicated concatenated entries (latest updated wins)"
+            );
+        }
+
+        if config.partitioned {
+            // The batches are already in memory; replace them with the single part file.
+            std_fs::remove_dir_all(&period_dir)
+                .and_then(|()| std_fs::create_dir_all(&period_dir))
+                .map_err(|e| {
+                    AppError::IoError(format!(
+                        "Failed to clear partition directory {period_dir:?}: {e}"
+                    ))
+                })?;
+        }
+        let final_path = period_output_path(parquet_dir, subdir_name, config);
+        partial_output.push(final_path.clone());
+        let mut final_file = File::create(&final_path).map_err(|e| {
+            AppError::IoError(format!(
+                "Failed to create final Parquet file {final_path:?}: {e}"
+            ))
+        })?;
+
+        write_dataframe(
+            &mut final_file,
+            &mut combined,
+            parquet_compression(config.parquet_compression),
+            raw_xml_override(config),
+        )
+        .map_err(|e| AppError::ParseError(format!("Failed to write final Parquet file: {e}")))?;
+
+        output_paths.push(final_path);
+        if !config.partitioned && !config.incremental {
+            std_fs::remove_dir_all(&period_dir).map_err(|e| {
+                AppError::IoError(format!(
+                    "Failed to remove temporary parquet directory {period_dir:?}: {e}"
+                ))
+            })?;
+        }
+    } else {
+        if config.concat_batches && !config.partitioned {
+            // The JSON Lines period file was written directly; the batch directory is empty.
+            std_fs::remove_dir_all(&period_dir).map_err(|e| {
+                AppError::IoError(format!(
+                    "Failed to remove temporary parquet directory {period_dir:?}: {e}"
+                ))
+            })?;
+        }
+        if appending {
+            // The period output is every batch, not only the ones written by this run.
+            output_paths.extend(
+                batch_files(&period_dir, batch_prefix)?
+                    .into_iter()
+                    .map(|(_, path)| path),
+            );
+        } else {
+            output_paths.extend(batch_paths.iter().cloned());
+        }
+    }
+
+    #[cfg(feature = "duckdb")]
+    if let Some(db_path) = &config.duckdb_path {
+        let entries = super::duckdb_export::load_period(db_path, subdir_name, &output_paths)?;
+        debug!(period = subdir_name, entries, db_path = %db_path.display(), "Loaded period into DuckDB");
+    }
+
+    // Partitioned lot files live in their own `lots/` tree so that a glob over the
+    // partitions only matches files with the entry schema.
+    let mut lots_output = None;
+    if config.join_lot_results && config.output_format == OutputFormat::Parquet {
+        let lots_path = if config.partitioned {
+            partition_dir(&parquet_dir.join("lots"), subdir_name).join("part-0.parquet")
+        } else {
+            parquet_dir.join(format!("{subdir_name}_lots.parquet"))
+        };
+        if lot_rows.is_empty() {
+            // Do not leave a lot file from an earlier run next to the new output.
+            if lots_path.exists() {
+                std_fs::remove_file(&lots_path).map_err(|e| {
+                    AppError::IoError(format!(
+                        "Failed to remove previous lot results file {lots_path:?}: {e}"
+                    ))
+                })?;
+            }
+        } else {
+            let mut lots_df = lot_results_to_dataframe(&lot_rows)?;
+            if let Some(parent) = lots_path.parent() {
+                std_fs::create_dir_all(parent).map_err(|e| {
+                    AppError::IoError(format!(
+                        "Failed to create lot results directory {parent:?}: {e}"
+                    ))
+                })?;
+            }
+            let mut lots_file = File::create(&lots_path).map_err(|e| {
+                AppError::IoError(format!(
+                    "Failed to create lot results file {lots_path:?}: {e}"
+                ))
+            })?;
+            ParquetWriter::new(&mut lots_file)
+                .with_compression(parquet_compression(config.parquet_compression))
+                .finish(&mut lots_df)
+                .map_err(|e| {
+                    AppError::ParseError(format!("Failed to write lot results file: {e}"))
+                })?;
+            debug!(
+                period = subdir_name,
+                rows = lots_df.height(),
+                "Wrote lot results file"
+            );
+            if config.partitioned {
+                lots_output = Some(lots_path);
+            } else {
+                output_paths.push(lots_path);
+            }
+        }
+    }
+
+    for output_path in output_paths.iter().chain(&lots_output) {
+        let metadata = std_fs::metadata(output_path).map_err(|e| {
+            AppError::IoError(format!(
+                "Failed to read Parquet file metadata {output_path:?}: {e}"
+            ))
+        })?;
+        parquet_bytes += metadata.len();
+        parquet_files += 1;
+    }
+    if config.report_unknown_elements {
+        let report_path =
+            write_unknown_elements_report(parquet_dir, subdir_name, &unknown_elements)?;
+        if unknown_elements.is_empty() {
+            debug!(period = subdir_name, path = %report_path.display(), "No unknown elements found");
+        } else {
+            info!(
+                period = subdir_name,
+                distinct = unknown_elements.len(),
+                path = %report_path.display(),
+                "Found ContractFolderStatus elements the parser does not map"
+            );
+        }
+    }
+    if config.checksums {
+        let checksum_path = write_checksums(&output_paths)?;
+        debug!(period = subdir_name, path = %checksum_path.display(), "Wrote checksum file");
+        if let Some(lots_path) = &lots_output {
+            write_checksums(std::slice::from_ref(lots_path))?;
+        }
+    }
+
+    Ok(Some(PeriodOutput {
+        entries: entry_count,
+        parquet_files,
+        parquet_bytes,
+        paths: output_paths.into_iter().chain(lots_output).collect(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::processed_files::PROCESSED_FILES_NAME;
+    use super::super::xml_parser::parse_xml_bytes;
+    use super::*;
+    use crate::models::{FeedMetadata, StatusCode, TermsFundingProgram};
+
+    #[test]
+    fn parquet_compression_maps_codecs() {
+        assert_eq!(
+            parquet_compression(ParquetCompressionCodec::Zstd),
+            ParquetCompression::Zstd(None)
+        );
+        assert_eq!(
+            parquet_compression(ParquetCompressionCodec::Snappy),
+            ParquetCompression::Snappy
+        );
+        assert_eq!(
+            parquet_compression(ParquetCompressionCodec::Lz4),
+            ParquetCompression::Lz4Raw
+        );
+        assert_eq!(
+            parquet_compression(ParquetCompressionCodec::Uncompressed),
+            ParquetCompression::Uncompressed
+        );
+    }
+
+    #[test]
+    fn every_compression_codec_round_trips() {
+        let entries = vec![
+            Entry {
+                id: Some("a".to_string()),
+                ..Default::default()
+            },
+            Entry {
+                id: Some("b".to_string()),
+                ..Default::default()
+            },
+        ];
+        let tmp = tempfile::TempDir::new().unwrap();
+        for codec in [
+            ParquetCompressionCodec::Zstd,
+            ParquetCompressionCodec::Snappy,
+            ParquetCompressionCodec::Lz4,
+            ParquetCompressionCodec::Uncompressed,
+        ] {
+            let mut df = entries_to_dataframe(entries.clone(), false).unwrap();
+            let path = tmp.path().join(format!("{codec:?}.parquet"));
+            write_dataframe(
+                &mut File::create(&path).unwrap(),
+                &mut df,
+                parquet_compression(codec),
+                None,
+            )
+            .unwrap();
+
+            let read = ParquetReader::new(File::open(&path).unwrap())
+                .finish()
+                .unwrap();
+            assert_eq!(read.height(), 2, "{codec:?}");
+            assert!(read.equals_missing(&df), "{codec:?}");
+        }
+    }
+
+    #[test]
+    fn partition_dir_uses_month_00_for_year_only_periods() {
+        let root = Path::new("out");
+        assert_eq!(
+            partition_dir(root, "202403"),
+            root.join("year=2024").join("month=03")
+        );
+        assert_eq!(
+            partition_dir(root, "2023"),
+            root.join("year=2023").join("month=00")
+        );
+    }
+
+    #[test]
+    fn entries_to_dataframe_empty_yields_zero_rows() {
+        let df = entries_to_dataframe(vec![], false).unwrap();
+        assert_eq!(df.height(), 0);
+        assert_eq!(df.width(), 18);
+    }
+
+    #[test]
+    fn entries_to_dataframe_single_entry() {
+        let entry = Entry {
+            id: Some("id".to_string()),
+            title: Some("title".to_string()),
+            link: Some("link".to_string()),
+            summary: Some("summary".to_string()),
+            updated: Some("2023-01-01".to_string()),
+            categories: Vec::new(),
+            author: None,
+            is_deleted: false,
+            status: StatusCode::default(),
+            contract_id: None,
+            contracting_party_name: None,
+            contracting_party_id: None,
+            contracting_party_website: None,
+            contracting_party_type_code: None,
+            contracting_party_type_code_list_uri: None,
+            contracting_party_activity_code: None,
+            contracting_party_activity_code_list_uri: None,
+            contracting_party_city: None,
+            contracting_party_zip: None,
+            contracting_party_country_code: None,
+            contracting_party_country_code_list_uri: None,
+            project_name: None,
+            project_type_code: None,
+            project_type_code_list_uri: None,
+            project_sub_type_code: None,
+            project_sub_type_code_list_uri: None,
+            project_total_amount: None,
+            project_total_currency: None,
+            project_tax_exclusive_amount: None,
+            project_tax_exclusive_currency: None,
+            project_cpv_code: None,
+            project_cpv_code_list_uri: None,
+            project_country_code: None,
+            project_country_code_list_uri: None,
+            project_duration_measure: None,
+            project_duration_unit: None,
+            project_country_subentity: None,
+            project_country_subentity_code: None,
+            project_country_subentity_code_list_uri: None,
+            project_lots: Vec::new(),
+            awarding_criteria: Vec::new(),
+            tender_results: vec![TenderResultRow {
+                result_id: Some("1".to_string()),
+                result_lot_id: Some("0".to_string()),
+                ..Default::default()
+            }],
+            terms_funding_program: TermsFundingProgram::default(),
+            process_start_date: None,
+            process_end_date: None,
+            process_open_date: None,
+            process_procedure_code: None,
+            process_procedure_code_list_uri: None,
+            process_urgency_code: None,
+            process_urgency_code_list_uri: None,
+            cfs_raw_xml: Some("<xml/>".to_string()),
+            feed_metadata: FeedMetadata::default(),
+        };
+
+        let df = entries_to_dataframe(vec![entry], true).unwrap();
+        assert_eq!(df.height(), 1);
+        let tender_results_series = df.column("tender_results").unwrap();
+        let DataType::List(inner) = tender_results_series.dtype() else {
+            panic!("tender_results is not a list");
+        };
+        let DataType::Struct(fields) = inner.as_ref() else {
+            panic!("tender_results items are not structs");
+        };
+        let quantity = fields
+            .iter()
+            .find(|field| field.name() == "result_received_tender_quantity")
+            .unwrap();
+        assert_eq!(quantity.data_type(), &DataType::UInt32);
+        assert_eq!(tender_results_series.len(), 1);
+        assert_eq!(df.width(), 19);
+        let lots_col = df.column("project_lots").unwrap();
+        assert!(matches!(lots_col.dtype(), DataType::List(_)));
+        let contracting_party_col = df.column("contracting_party").unwrap();
+        assert!(matches!(contracting_party_col.dtype(), DataType::Struct(_)));
+        let project_col = df.column("project").unwrap();
+        assert!(matches!(project_col.dtype(), DataType::Struct(_)));
+        let process_col = df.column("process").unwrap();
+        assert!(matches!(process_col.dtype(), DataType::Struct(_)));
+        let value = df.column("id").unwrap().get(0).unwrap();
         assert_eq!(value, AnyValue::String("id"));
     }
 
+    #[test]
+    fn entries_to_dataframe_casts_received_tender_quantity() {
+        let quantities = |values: &[&str]| {
+            let entry = Entry {
+                tender_results: values
+                    .iter()
+                    .map(|value| TenderResultRow {
+                        result_received_tender_quantity: Some(value.to_string()),
+                        ..Default::default()
+                    })
+                    .collect(),
+                ..Default::default()
+            };
+            let df = entries_to_dataframe(vec![entry], false).unwrap();
+            let column = df
+                .lazy()
+                .select([col("tender_results")
+                    .explode()
+                    .struct_()
+                    .field_by_name("result_received_tender_quantity")])
+                .collect()
+                .unwrap();
+            let column = column.column("result_received_tender_quantity").unwrap();
+            column.u32().unwrap().into_iter().collect::<Vec<_>>()
+        };
+
+        assert_eq!(quantities(&["5", "12"]), [Some(5), Some(12)]);
+        // A non-numeric count is nulled instead of changing the batch schema.
+        assert_eq!(quantities(&["5", "n/a"]), [Some(5), None]);
+    }
+
+    #[test]
+    fn entries_to_dataframe_derives_sme_awarded_and_keeps_raw_indicator() {
+        let entry = Entry {
+            tender_results: [Some("TRUE"), Some("0"), Some("unknown"), None]
+                .into_iter()
+                .map(|indicator| TenderResultRow {
+                    result_sme_awarded_indicator: indicator.map(str::to_string),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+        let df = entries_to_dataframe(vec![entry], false).unwrap();
+        let results = df
+            .lazy()
+            .select([
+                col("tender_results")
+                    .explode()
+                    .struct_()
+                    .field_by_name("result_sme_awarded_indicator"),
+                col("tender_results")
+                    .explode()
+                    .struct_()
+                    .field_by_name("result_sme_awarded"),
+            ])
+            .collect()
+            .unwrap();
+
+        let raw: Vec<Option<&str>> = results
+            .column("result_sme_awarded_indicator")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(raw, [Some("TRUE"), Some("0"), Some("unknown"), None]);
+        let awarded: Vec<Option<bool>> = results
+            .column("result_sme_awarded")
+            .unwrap()
+            .bool()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(awarded, [Some(true), Some(false), None, None]);
+    }
+
+    #[test]
+    fn entries_to_dataframe_keeps_folder_and_lot_subentity_codes() {
+        let entry = Entry {
+            project_country_subentity_code: Some("ES300".to_string()),
+            project_lots: vec![ProcurementProjectLot {
+                country_subentity_code: Some("ES511".to_string()),
+                country_subentity_code_list_uri: Some("urn:nuts".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let df = entries_to_dataframe(vec![entry], false).unwrap();
+        let codes = df
+            .lazy()
+            .select([
+                col("project")
+                    .struct_()
+                    .field_by_name("country_subentity_code")
+                    .alias("project_code"),
+                col("project_lots")
+                    .explode()
+                    .struct_()
+                    .field_by_name("country_subentity_code")
+                    .alias("lot_code"),
+                col("project_lots")
+                    .explode()
+                    .struct_()
+                    .field_by_name("country_subentity_code_list_uri")
+                    .alias("lot_list_uri"),
+            ])
+            .collect()
+            .unwrap();
+
+        let value = |name: &str| codes.column(name).unwrap().str().unwrap().get(0);
+        assert_eq!(value("project_code"), Some("ES300"));
+        assert_eq!(value("lot_code"), Some("ES511"));
+        assert_eq!(value("lot_list_uri"), Some("urn:nuts"));
+    }
+
     #[test]
     fn entries_to_dataframe_excludes_cfs_raw_xml_when_disabled() {
         let entry = Entry {
@@ -766,9 +2051,13 @@ mod tests {
             link: Some("link".to_string()),
             summary: Some("summary".to_string()),
             updated: Some("2023-01-01".to_string()),
+            categories: Vec::new(),
+            author: None,
+            is_deleted: false,
             status: StatusCode::default(),
             contract_id: None,
             contracting_party_name: None,
+            contracting_party_id: None,
             contracting_party_website: None,
             contracting_party_type_code: None,
             contracting_party_type_code_list_uri: None,
@@ -791,20 +2080,29 @@ mod tests {
             project_cpv_code_list_uri: None,
             project_country_code: None,
             project_country_code_list_uri: None,
+            project_duration_measure: None,
+            project_duration_unit: None,
+            project_country_subentity: None,
+            project_country_subentity_code: None,
+            project_country_subentity_code_list_uri: None,
             project_lots: Vec::new(),
+            awarding_criteria: Vec::new(),
             tender_results: Vec::new(),
             terms_funding_program: TermsFundingProgram::default(),
+            process_start_date: None,
             process_end_date: None,
+            process_open_date: None,
             process_procedure_code: None,
             process_procedure_code_list_uri: None,
             process_urgency_code: None,
             process_urgency_code_list_uri: None,
             cfs_raw_xml: Some("<xml/>".to_string()),
+            feed_metadata: FeedMetadata::default(),
         };
 
         let df = entries_to_dataframe(vec![entry], false).unwrap();
         assert_eq!(df.height(), 1);
-        assert_eq!(df.width(), 13);
+        assert_eq!(df.width(), 18);
         assert!(df.column("cfs_raw_xml").is_err());
     }
 
@@ -816,9 +2114,13 @@ mod tests {
             link: Some("link".to_string()),
             summary: Some("summary".to_string()),
             updated: Some("2023-01-01".to_string()),
+            categories: Vec::new(),
+            author: None,
+            is_deleted: false,
             status: StatusCode::default(),
             contract_id: None,
             contracting_party_name: None,
+            contracting_party_id: None,
             contracting_party_website: None,
             contracting_party_type_code: None,
             contracting_party_type_code_list_uri: None,
@@ -841,21 +2143,1242 @@ mod tests {
             project_cpv_code_list_uri: None,
             project_country_code: None,
             project_country_code_list_uri: None,
+            project_duration_measure: None,
+            project_duration_unit: None,
+            project_country_subentity: None,
+            project_country_subentity_code: None,
+            project_country_subentity_code_list_uri: None,
             project_lots: Vec::new(),
+            awarding_criteria: Vec::new(),
             tender_results: Vec::new(),
             terms_funding_program: TermsFundingProgram::default(),
+            process_start_date: None,
             process_end_date: None,
+            process_open_date: None,
             process_procedure_code: None,
             process_procedure_code_list_uri: None,
             process_urgency_code: None,
             process_urgency_code_list_uri: None,
             cfs_raw_xml: Some("<xml/>".to_string()),
+            feed_metadata: FeedMetadata::default(),
         };
 
         let df = entries_to_dataframe(vec![entry], true).unwrap();
         assert_eq!(df.height(), 1);
-        assert_eq!(df.width(), 14);
+        assert_eq!(df.width(), 19);
         let cfs_xml_col = df.column("cfs_raw_xml").unwrap();
         assert_eq!(cfs_xml_col.get(0).unwrap(), AnyValue::String("<xml/>"));
     }
+
+    #[tokio::test]
+    async fn parse_xmls_stats_match_output_on_disk() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = crate::config::ResolvedConfig {
+            download_dir_pt: tmp.path().join("tmp"),
+            parquet_dir_pt: tmp.path().join("parquet"),
+            batch_size: 1,
+            ..Default::default()
+        };
+        let period_dir = tmp.path().join("tmp/202401");
+        std_fs::create_dir_all(&period_dir).unwrap();
+        for (name, id) in [("a.atom", "id1"), ("b.atom", "id2")] {
+            std_fs::write(
+                period_dir.join(name),
+                format!("<feed><entry><id>{id}</id><title>t</title></entry></feed>"),
+            )
+            .unwrap();
+        }
+        let mut links = BTreeMap::new();
+        links.insert("202401".to_string(), String::new());
+
+        let stats = parse_xmls(
+            &links,
+            &crate::models::ProcurementType::PublicTenders,
+            config.batch_size,
+            &config,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let written: Vec<_> = std_fs::read_dir(tmp.path().join("parquet/202401"))
+            .unwrap()
+            .map(|e| e.unwrap().metadata().unwrap().len())
+            .collect();
+        assert_eq!(stats.periods_parsed, 1);
+        assert_eq!(stats.xml_files, 2);
+        assert_eq!(stats.entries, 2);
+        assert_eq!(stats.parquet_files, written.len());
+        assert_eq!(stats.parquet_bytes, written.iter().sum::<u64>());
+    }
+
+    #[tokio::test]
+    async fn gzipped_feed_parses_like_plain_feed() {
+        use std::io::Write;
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let feed = "<feed><entry><id>https://example.com/gz-1</id><title>Obra</title>\
+            <cac-place-ext:ContractFolderStatus><cbc:ContractFolderID>C-1</cbc:ContractFolderID>\
+            </cac-place-ext:ContractFolderStatus></entry></feed>";
+        let plain = tmp.path().join("feed.atom");
+        let gzipped = tmp.path().join("feed.atom.gz");
+        std_fs::write(&plain, feed).unwrap();
+        let mut encoder =
+            flate2::write::GzEncoder::new(File::create(&gzipped).unwrap(), Default::default());
+        encoder.write_all(feed.as_bytes()).unwrap();
+        encoder.finish().unwrap();
+
+        let contents = read_xml_contents(&[plain, gzipped], 2).await.unwrap();
+        let parsed: Vec<serde_json::Value> = contents
+            .iter()
+            .map(|content| {
+                let feed = parse_xml_bytes(content, ParseOptions::default()).unwrap();
+                serde_json::to_value(feed.entries).unwrap()
+            })
+            .collect();
+
+        assert_eq!(parsed[0], parsed[1]);
+        assert_eq!(parsed[1][0]["contract_id"], "C-1");
+    }
+
+    #[tokio::test]
+    async fn parse_xmls_regenerates_checksum_file_per_period() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = crate::config::ResolvedConfig {
+            download_dir_pt: tmp.path().join("tmp"),
+            parquet_dir_pt: tmp.path().join("parquet"),
+            batch_size: 1,
+            checksums: true,
+            ..Default::default()
+        };
+        let period_dir = tmp.path().join("tmp/202401");
+        std_fs::create_dir_all(&period_dir).unwrap();
+        for (name, id) in [("a.atom", "id1"), ("b.atom", "id2")] {
+            std_fs::write(
+                period_dir.join(name),
+                format!("<feed><entry><id>{id}</id></entry></feed>"),
+            )
+            .unwrap();
+        }
+        let mut links = BTreeMap::new();
+        links.insert("202401".to_string(), String::new());
+
+        // Parsing twice must leave one line per batch file, not an appended copy.
+        for _ in 0..2 {
+            parse_xmls(
+                &links,
+                &crate::models::ProcurementType::PublicTenders,
+                config.batch_size,
+                &config,
+                None,
+            )
+            .await
+            .unwrap();
+        }
+
+        let checksums = std_fs::read_to_string(tmp.path().join("parquet/202401.sha256")).unwrap();
+        let names: Vec<&str> = checksums
+            .lines()
+            .map(|line| line.split_once("  ").unwrap().1)
+            .collect();
+        assert_eq!(names, ["202401/batch_0.parquet", "202401/batch_1.parquet"]);
+    }
+
+    #[tokio::test]
+    async fn parse_xmls_reports_unknown_elements_per_period() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = crate::config::ResolvedConfig {
+            download_dir_pt: tmp.path().join("tmp"),
+            parquet_dir_pt: tmp.path().join("parquet"),
+            batch_size: 1,
+            report_unknown_elements: true,
+            ..Default::default()
+        };
+        let period_dir = tmp.path().join("tmp/202401");
+        std_fs::create_dir_all(&period_dir).unwrap();
+        // The made-up element appears twice in one file and once in the other.
+        for (name, id, made_up) in [("a.atom", "id1", 2), ("b.atom", "id2", 1)] {
+            std_fs::write(
+                period_dir.join(name),
+                format!(
+                    "<feed><entry><id>{id}</id><cac-place-ext:ContractFolderStatus>\
+                     <cbc:ContractFolderID>{id}</cbc:ContractFolderID>\
+                     <cac:ProcurementProject><cbc:Name>Project</cbc:Name></cac:ProcurementProject>\
+                     {}</cac-place-ext:ContractFolderStatus></entry></feed>",
+                    "<cac-place-ext:MadeUpConditions>x</cac-place-ext:MadeUpConditions>"
+                        .repeat(made_up)
+                ),
+            )
+            .unwrap();
+        }
+        let mut links = BTreeMap::new();
+        links.insert("202401".to_string(), String::new());
+
+        parse_xmls(
+            &links,
+            &crate::models::ProcurementType::PublicTenders,
+            config.batch_size,
+            &config,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let report: BTreeMap<String, u64> = serde_json::from_str(
+            &std_fs::read_to_string(tmp.path().join("parquet/202401.unknown_elements.json"))
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            report,
+            BTreeMap::from([("MadeUpConditions".to_string(), 3)])
+        );
+    }
+
+    #[test]
+    fn period_output_path_follows_output_layout() {
+        let root = Path::new("out");
+        let output = |concat_batches, partitioned, output_format| {
+            let config = crate::config::ResolvedConfig {
+                concat_batches,
+                partitioned,
+                output_format,
+                ..Default::default()
+            };
+            period_output_path(root, "202401", &config)
+        };
+
+        assert_eq!(
+            output(false, false, OutputFormat::Parquet),
+            root.join("202401")
+        );
+        assert_eq!(
+            output(true, false, OutputFormat::Jsonl),
+            root.join("202401.jsonl")
+        );
+        assert_eq!(
+            output(false, true, OutputFormat::Parquet),
+            root.join("year=2024/month=01")
+        );
+        assert_eq!(
+            output(true, true, OutputFormat::Parquet),
+            root.join("year=2024/month=01/part-0.parquet")
+        );
+    }
+
+    /// Writes one feed per period under `root`, each entry named `{period}-{n}`; `None`
+    /// writes a malformed feed instead.
+    fn write_period_feeds(root: &Path, periods: &[(&str, Option<usize>)]) {
+        for (period, entries) in periods {
+            let period_dir = root.join(period);
+            std_fs::create_dir_all(&period_dir).unwrap();
+            let feed = match entries {
+                Some(count) => (0..*count)
+                    .map(|n| format!("<entry><id>{period}-{n}</id></entry>"))
+                    .collect::<String>(),
+                None => "<entry><id>broken</id><title>t</summary></entry>".to_string(),
+            };
+            std_fs::write(period_dir.join("feed.atom"), format!("<feed>{feed}</feed>")).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn parse_xmls_parallel_periods_write_every_period() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = crate::config::ResolvedConfig {
+            download_dir_pt: tmp.path().join("tmp"),
+            parquet_dir_pt: tmp.path().join("parquet"),
+            concat_batches: true,
+            parallel_periods: 2,
+            ..Default::default()
+        };
+        write_period_feeds(
+            &tmp.path().join("tmp"),
+            &[("202401", Some(2)), ("202402", Some(3))],
+        );
+        let links = BTreeMap::from([
+            ("202401".to_string(), String::new()),
+            ("202402".to_string(), String::new()),
+        ]);
+
+        let stats = parse_xmls(
+            &links,
+            &crate::models::ProcurementType::PublicTenders,
+            config.batch_size,
+            &config,
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(stats.periods_parsed, 2);
+        assert_eq!(stats.entries, 5);
+        for (period, count) in [("202401", 2), ("202402", 3)] {
+            let path = tmp.path().join(format!("parquet/{period}.parquet"));
+            let df = ParquetReader::new(File::open(path).unwrap())
+                .finish()
+                .unwrap();
+            let ids: Vec<String> = df
+                .column("id")
+                .unwrap()
+                .str()
+                .unwrap()
+                .into_no_null_iter()
+                .map(str::to_string)
+                .collect();
+            let expected: Vec<String> = (0..count).map(|n| format!("{period}-{n}")).collect();
+            assert_eq!(ids, expected);
+            assert_eq!(stats.entries_by_period[period], count);
+        }
+    }
+
+    #[tokio::test]
+    async fn parse_xmls_parallel_periods_report_every_failure() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let mut config = crate::config::ResolvedConfig {
+            download_dir_pt: tmp.path().join("tmp"),
+            parquet_dir_pt: tmp.path().join("parquet"),
+            batch_size: 1,
+            parallel_periods: 3,
+            ..Default::default()
+        };
+        write_period_feeds(
+            &tmp.path().join("tmp"),
+            &[("202401", None), ("202402", Some(1)), ("202403", Some(1))],
+        );
+        // 202403 writes its first batch before its second file fails to parse.
+        std_fs::write(
+            tmp.path().join("tmp/202403/z.atom"),
+            "<feed><entry><id>broken</id><title>t</summary></entry></feed>",
+        )
+        .unwrap();
+        let links: BTreeMap<String, String> = ["202401", "202402", "202403"]
+            .into_iter()
+            .map(|period| (period.to_string(), String::new()))
+            .collect();
+        let procurement_type = crate::models::ProcurementType::PublicTenders;
+
+        let err = parse_xmls(&links, &procurement_type, config.batch_size, &config, None)
+            .await
+            .unwrap_err();
+        let AppError::ParseError(message) = &err else {
+            panic!("{err:?}");
+        };
+        assert!(
+            message.starts_with("2 periods failed to parse: 202401: "),
+            "{message}"
+        );
+        assert!(message.contains("; 202403: "), "{message}");
+        let parquet_dir = tmp.path().join("parquet");
+        assert!(!parquet_dir.join("202401").exists());
+        assert!(!parquet_dir.join("202403").exists());
+
+        config.keep_going = true;
+        let stats = parse_xmls(&links, &procurement_type, config.batch_size, &config, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            stats.failed_periods.keys().collect::<Vec<_>>(),
+            ["202401", "202403"]
+        );
+        assert_eq!(stats.periods_parsed, 1);
+        assert_eq!(stats.entries_by_period["202402"], 1);
+        assert!(!parquet_dir.join("202403").exists());
+    }
+
+    #[test]
+    fn drop_seen_ids_keeps_last_occurrence_and_entries_without_id() {
+        let entry = |id: Option<&str>, title: &str| {
+            let mut entry: Entry = serde_json::from_value(serde_json::json!({
+                "status": {},
+                "project_lots": [],
+                "tender_results": [],
+                "terms_funding_program": {},
+                "awarding_criteria": []
+            }))
+            .unwrap();
+            entry.id = id.map(str::to_string);
+            entry.title = Some(title.to_string());
+            entry
+        };
+        let mut seen = HashSet::from(["e9".to_string()]);
+        let mut entries = vec![
+            entry(Some("e1"), "first"),
+            entry(None, "no id"),
+            entry(Some("e2"), "only"),
+            entry(Some("e1"), "second"),
+            entry(Some("e9"), "seen in a later batch"),
+            entry(None, "no id"),
+        ];
+
+        let removed = drop_seen_ids(&mut entries, &mut seen);
+
+        let titles: Vec<_> = entries
+            .iter()
+            .map(|e| e.title.as_deref().unwrap())
+            .collect();
+        assert_eq!(removed, 2);
+        assert_eq!(titles, ["no id", "only", "second", "no id"]);
+        assert!(seen.contains("e1") && seen.contains("e2"));
+    }
+
+    #[tokio::test]
+    async fn dedupe_by_id_keeps_last_occurrence_across_batches() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = crate::config::ResolvedConfig {
+            download_dir_pt: tmp.path().join("tmp"),
+            parquet_dir_pt: tmp.path().join("parquet"),
+            batch_size: 1,
+            concat_batches: true,
+            dedupe_by_id: true,
+            ..Default::default()
+        };
+        let period_dir = tmp.path().join("tmp/202401");
+        std_fs::create_dir_all(&period_dir).unwrap();
+        std_fs::write(
+            period_dir.join("a.atom"),
+            "<feed><entry><id>e1</id><title>old</title></entry><entry><id>e2</id></entry></feed>",
+        )
+        .unwrap();
+        std_fs::write(
+            period_dir.join("b.atom"),
+            "<feed><entry><id>e1</id><title>new</title></entry></feed>",
+        )
+        .unwrap();
+        let mut links = BTreeMap::new();
+        links.insert("202401".to_string(), String::new());
+
+        let stats = parse_xmls(
+            &links,
+            &crate::models::ProcurementType::PublicTenders,
+            config.batch_size,
+            &config,
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(stats.entries, 2);
+        let file = File::open(tmp.path().join("parquet/202401.parquet")).unwrap();
+        let df = ParquetReader::new(file).finish().unwrap();
+        let ids = df.column("id").unwrap().str().unwrap();
+        let titles = df.column("title").unwrap().str().unwrap();
+        let e1_title = ids
+            .into_iter()
+            .zip(titles.into_iter())
+            .find(|(id, _)| *id == Some("e1"))
+            .and_then(|(_, title)| title);
+        assert_eq!(df.height(), 2);
+        assert_eq!(e1_title, Some("new"));
+    }
+
+    #[test]
+    fn entries_to_dataframe_flags_deleted_entries() {
+        let xml = r#"<feed xmlns:at="http://purl.org/atompub/tombstones/1.0">
+  <entry><id>https://example.com/live</id><category term="Servicios"/></entry>
+  <at:deleted-entry ref="https://example.com/gone" when="2024-03-01T10:00:00Z"/>
+</feed>"#;
+        let feed = parse_xml_bytes(xml.as_bytes(), ParseOptions::default()).unwrap();
+
+        let df = entries_to_dataframe(feed.entries, false).unwrap();
+
+        assert_eq!(df.column("is_deleted").unwrap().dtype(), &DataType::Boolean);
+        let rows: Vec<(Option<&str>, Option<bool>)> = df
+            .column("id")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .zip(df.column("is_deleted").unwrap().bool().unwrap())
+            .collect();
+        assert_eq!(
+            rows,
+            [(Some("live"), Some(false)), (Some("gone"), Some(true))]
+        );
+        let categories = df.column("categories").unwrap();
+        assert_eq!(
+            categories.dtype(),
+            &DataType::List(Box::new(DataType::String))
+        );
+        let categories: Vec<Vec<Option<String>>> = categories
+            .list()
+            .unwrap()
+            .into_iter()
+            .map(|terms| {
+                terms
+                    .unwrap()
+                    .str()
+                    .unwrap()
+                    .into_iter()
+                    .map(|term| term.map(str::to_string))
+                    .collect()
+            })
+            .collect();
+        assert_eq!(categories, [vec![Some("Servicios".to_string())], vec![]]);
+    }
+
+    #[test]
+    fn entries_to_dataframe_writes_author_column() {
+        let xml = r#"<feed>
+  <author><name>Plataforma de Contratación del Sector Público</name></author>
+  <entry>
+    <id>https://example.com/a</id>
+    <author><name>Ayuntamiento de Ejemplo</name></author>
+  </entry>
+  <entry><id>https://example.com/b</id></entry>
+</feed>"#;
+        let feed = parse_xml_bytes(xml.as_bytes(), ParseOptions::default()).unwrap();
+
+        let df = entries_to_dataframe(feed.entries, false).unwrap();
+
+        let author = df.column("author").unwrap();
+        assert_eq!(author.dtype(), &DataType::String);
+        let authors: Vec<Option<&str>> = author.str().unwrap().into_iter().collect();
+        assert_eq!(authors, [Some("Ayuntamiento de Ejemplo"), None]);
+    }
+
+    #[test]
+    fn entries_to_dataframe_writes_feed_metadata_struct() {
+        let feed = |id: &str| {
+            let xml = format!(
+                "<feed><id>urn:feed:{id}</id><title>Feed {id}</title>\
+                 <updated>2024-03-0{id}</updated><entry><id>e{id}</id></entry></feed>"
+            );
+            parse_xml_bytes(xml.as_bytes(), ParseOptions::default())
+                .unwrap()
+                .entries
+        };
+        let mut entries = feed("1");
+        entries.extend(feed("2"));
+
+        let df = entries_to_dataframe(entries, false).unwrap();
+
+        let metadata = df.column("feed_metadata").unwrap().struct_().unwrap();
+        let field = |name: &str| -> Vec<Option<String>> {
+            metadata
+                .field_by_name(name)
+                .unwrap()
+                .str()
+                .unwrap()
+                .into_iter()
+                .map(|value| value.map(str::to_string))
+                .collect()
+        };
+        assert_eq!(
+            field("id"),
+            [
+                Some("urn:feed:1".to_string()),
+                Some("urn:feed:2".to_string())
+            ]
+        );
+        assert_eq!(
+            field("title"),
+            [Some("Feed 1".to_string()), Some("Feed 2".to_string())]
+        );
+        assert_eq!(
+            field("updated"),
+            [
+                Some("2024-03-01".to_string()),
+                Some("2024-03-02".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn add_cpv_descriptions_describes_each_project_code() {
+        let entry = |cpv_code: Option<&str>| Entry {
+            project_cpv_code: cpv_code.map(str::to_string),
+            ..Default::default()
+        };
+        let mut df = entries_to_dataframe(
+            vec![
+                entry(Some("45000000-7")),
+                entry(Some("45233140-2_72000000-5")),
+                entry(Some("99000000-0")),
+                entry(Some("79713000-5")),
+                entry(None),
+            ],
+            false,
+        )
+        .unwrap();
+
+        add_cpv_descriptions(&mut df).unwrap();
+
+        let descriptions: Vec<Option<&str>> = df
+            .column(CPV_DESCRIPTION_COLUMN)
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .collect();
+        assert_eq!(
+            descriptions,
+            [
+                Some("Construction work"),
+                Some(
+                    "Roadworks_IT services: consulting, software development, Internet and support"
+                ),
+                None,
+                None,
+                None,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn single_file_stream_appends_one_row_group_per_batch() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = crate::config::ResolvedConfig {
+            download_dir_pt: tmp.path().join("tmp"),
+            parquet_dir_pt: tmp.path().join("parquet"),
+            batch_size: 1,
+            single_file_stream: true,
+            // Ignored in streaming mode.
+            concat_batches: true,
+            ..Default::default()
+        };
+        let period_dir = tmp.path().join("tmp/202401");
+        std_fs::create_dir_all(&period_dir).unwrap();
+        for (file, ids) in [
+            ("a.atom", ["a1", "a2"]),
+            ("b.atom", ["b1", "b2"]),
+            ("c.atom", ["c1", "c2"]),
+        ] {
+            let entries: String = ids
+                .iter()
+                .map(|id| format!("<entry><id>{id}</id></entry>"))
+                .collect();
+            std_fs::write(period_dir.join(file), format!("<feed>{entries}</feed>")).unwrap();
+        }
+        // A stale batch directory from an earlier run is replaced.
+        std_fs::create_dir_all(tmp.path().join("parquet/202401")).unwrap();
+        let mut links = BTreeMap::new();
+        links.insert("202401".to_string(), String::new());
+
+        let stats = parse_xmls(
+            &links,
+            &crate::models::ProcurementType::PublicTenders,
+            config.batch_size,
+            &config,
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(stats.parquet_files, 1);
+        let output = tmp.path().join("parquet/202401.parquet");
+        let mut reader = ParquetReader::new(File::open(&output).unwrap());
+        assert_eq!(reader.get_metadata().unwrap().row_groups.len(), 3);
+        let df = reader.finish().unwrap();
+        assert_eq!(df.height(), 6);
+        assert_eq!(df.column("id").unwrap().str().unwrap().get(4), Some("c1"));
+        assert!(!tmp.path().join("parquet/202401").exists());
+        assert!(!tmp.path().join("parquet/202401.parquet.part").exists());
+    }
+
+    #[tokio::test]
+    async fn keep_cfs_raw_xml_controls_raw_xml_column() {
+        for keep_cfs_raw_xml in [false, true] {
+            let tmp = tempfile::TempDir::new().unwrap();
+            let config = crate::config::ResolvedConfig {
+                download_dir_pt: tmp.path().join("tmp"),
+                parquet_dir_pt: tmp.path().join("parquet"),
+                concat_batches: true,
+                keep_cfs_raw_xml,
+                raw_xml_compression: Some(RawXmlCompressionCodec::Lz4),
+                ..Default::default()
+            };
+            let period_dir = tmp.path().join("tmp/202401");
+            std_fs::create_dir_all(&period_dir).unwrap();
+            std_fs::write(
+                period_dir.join("a.atom"),
+                "<feed><entry><id>a1</id><ContractFolderStatus><ContractFolderID>X</ContractFolderID></ContractFolderStatus></entry></feed>",
+            )
+            .unwrap();
+            let mut links = BTreeMap::new();
+            links.insert("202401".to_string(), String::new());
+
+            parse_xmls(
+                &links,
+                &crate::models::ProcurementType::PublicTenders,
+                config.batch_size,
+                &config,
+                None,
+            )
+            .await
+            .unwrap();
+
+            let output = tmp.path().join("parquet/202401.parquet");
+            let df = ParquetReader::new(File::open(&output).unwrap())
+                .finish()
+                .unwrap();
+            match df.column("cfs_raw_xml") {
+                Ok(column) => {
+                    assert!(keep_cfs_raw_xml);
+                    let xml = column.str().unwrap().get(0).unwrap();
+                    assert!(
+                        xml.contains("<ContractFolderID>X</ContractFolderID>"),
+                        "{xml}"
+                    );
+                }
+                Err(_) => assert!(!keep_cfs_raw_xml),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn join_lot_results_writes_flat_lot_file() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = crate::config::ResolvedConfig {
+            download_dir_pt: tmp.path().join("tmp"),
+            parquet_dir_pt: tmp.path().join("parquet"),
+            join_lot_results: true,
+            ..Default::default()
+        };
+        let period_dir = tmp.path().join("tmp/202401");
+        std_fs::create_dir_all(&period_dir).unwrap();
+        let lot = |id: &str| {
+            format!(
+                r#"<cac:ProcurementProjectLot><cbc:ID schemeName="ID_LOTE">{id}</cbc:ID></cac:ProcurementProjectLot>"#
+            )
+        };
+        // Lot 1 is awarded, lot 2 is not.
+        std_fs::write(
+            period_dir.join("a.atom"),
+            format!(
+                "<feed><entry><id>e1</id><cac-place-ext:ContractFolderStatus>                 <cbc:ContractFolderID>C-1</cbc:ContractFolderID>{}{}                 <cac:TenderResult><cbc:ResultCode>8</cbc:ResultCode>                 <cac:WinningParty><cac:PartyName><cbc:Name>Empresa A</cbc:Name></cac:PartyName></cac:WinningParty>                 <cac:AwardedTenderedProject><cbc:ProcurementProjectLotID>1</cbc:ProcurementProjectLotID></cac:AwardedTenderedProject>                 </cac:TenderResult></cac-place-ext:ContractFolderStatus></entry></feed>",
+                lot("1"),
+                lot("2"),
+            ),
+        )
+        .unwrap();
+        let mut links = BTreeMap::new();
+        links.insert("202401".to_string(), String::new());
+
+        let stats = parse_xmls(
+            &links,
+            &crate::models::ProcurementType::PublicTenders,
+            config.batch_size,
+            &config,
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(stats.parquet_files, 2);
+        let file = File::open(tmp.path().join("parquet/202401_lots.parquet")).unwrap();
+        let df = ParquetReader::new(file).finish().unwrap();
+        let column = |name: &str| -> Vec<Option<String>> {
+            df.column(name)
+                .unwrap()
+                .str()
+                .unwrap()
+                .into_iter()
+                .map(|v| v.map(str::to_string))
+                .collect()
+        };
+        assert_eq!(column("lot_id"), [Some("1".into()), Some("2".into())]);
+        assert_eq!(
+            column("contract_id"),
+            [Some("C-1".into()), Some("C-1".into())]
+        );
+        assert_eq!(
+            column("result_winning_party"),
+            [Some("Empresa A".into()), None]
+        );
+        let report = super::super::check_parquet_dir(&tmp.path().join("parquet")).unwrap();
+        assert_eq!(report.error_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn concat_with_dedup_latest_keeps_newest_row_per_contract() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = crate::config::ResolvedConfig {
+            download_dir_pt: tmp.path().join("tmp"),
+            parquet_dir_pt: tmp.path().join("parquet"),
+            batch_size: 1,
+            concat_batches: true,
+            dedup: DedupStrategy::Latest,
+            ..Default::default()
+        };
+        let period_dir = tmp.path().join("tmp/202401");
+        std_fs::create_dir_all(&period_dir).unwrap();
+        let entry = |id: &str, updated: &str, contract_id: Option<&str>| {
+            let cfs = contract_id
+                .map(|c| {
+                    format!(
+                        "<cac-place-ext:ContractFolderStatus><cbc:ContractFolderID>{c}</cbc:ContractFolderID></cac-place-ext:ContractFolderStatus>"
+                    )
+                })
+                .unwrap_or_default();
+            format!("<entry><id>{id}</id><updated>{updated}</updated>{cfs}</entry>")
+        };
+        // Two batches (batch_size = 1): C-1 is republished in the second file with a newer
+        // `updated`; the entries without a contract_id are deduplicated by `id`.
+        std_fs::write(
+            period_dir.join("a.atom"),
+            format!(
+                "<feed>{}{}{}</feed>",
+                entry("e1", "2024-01-01T00:00:00Z", Some("C-1")),
+                entry("e2", "2024-01-01T00:00:00Z", Some("C-2")),
+                entry("e3", "2024-01-01T00:00:00Z", None),
+            ),
+        )
+        .unwrap();
+        std_fs::write(
+            period_dir.join("b.atom"),
+            format!(
+                "<feed>{}{}</feed>",
+                entry("e1-v2", "2024-01-15T00:00:00Z", Some("C-1")),
+                entry("e3", "2024-01-15T00:00:00Z", None),
+            ),
+        )
+        .unwrap();
+        let mut links = BTreeMap::new();
+        links.insert("202401".to_string(), String::new());
+
+        let stats = parse_xmls(
+            &links,
+            &crate::models::ProcurementType::PublicTenders,
+            config.batch_size,
+            &config,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let file = File::open(tmp.path().join("parquet/202401.parquet")).unwrap();
+        let df = ParquetReader::new(file)
+            .finish()
+            .unwrap()
+            .sort(["id"], SortMultipleOptions::default())
+            .unwrap();
+        assert_eq!(df.height(), 3);
+        assert_eq!(stats.entries, 3);
+        let ids: Vec<_> = df
+            .column("id")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+        assert_eq!(ids, ["e1-v2", "e2", "e3"]);
+        let e3_updated = df.column("updated").unwrap().get(2).unwrap();
+        assert_eq!(e3_updated, AnyValue::String("2024-01-15T00:00:00Z"));
+    }
+
+    #[test]
+    fn jsonl_record_field_names_match_parquet_columns() {
+        // Missing `Option` fields deserialize as `None`.
+        let mut entry: Entry = serde_json::from_value(serde_json::json!({
+            "status": {},
+            "project_lots": [{}],
+            "tender_results": [{}],
+            "terms_funding_program": {},
+            "awarding_criteria": [{}]
+        }))
+        .unwrap();
+        entry.cfs_raw_xml = Some("<xml/>".to_string());
+
+        let json = serde_json::to_value(super::super::jsonl_writer::EntryRecord::new(&entry, true))
+            .unwrap();
+        let round_trip: serde_json::Value =
+            serde_json::from_str(&serde_json::to_string(&json).unwrap()).unwrap();
+        assert_eq!(round_trip, json);
+
+        let df = entries_to_dataframe(vec![entry], true).unwrap();
+        let object_keys = |value: &serde_json::Value| -> Vec<String> {
+            value.as_object().unwrap().keys().cloned().collect()
+        };
+        let struct_fields = |dtype: &DataType| -> Vec<String> {
+            let fields = match dtype {
+                DataType::Struct(fields) => fields,
+                DataType::List(inner) => match inner.as_ref() {
+                    DataType::Struct(fields) => fields,
+                    other => panic!("unexpected list dtype {other:?}"),
+                },
+                other => panic!("unexpected dtype {other:?}"),
+            };
+            let mut names: Vec<String> = fields.iter().map(|f| f.name().to_string()).collect();
+            names.sort();
+            names
+        };
+
+        let mut columns: Vec<String> = df
+            .get_column_names()
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        columns.sort();
+        assert_eq!(object_keys(&json), columns);
+        for column in [
+            "status",
+            "contracting_party",
+            "project",
+            "terms_funding_program",
+            "process",
+            "feed_metadata",
+        ] {
+            let mut keys = object_keys(&json[column]);
+            keys.sort();
+            assert_eq!(
+                keys,
+                struct_fields(df.column(column).unwrap().dtype()),
+                "{column}"
+            );
+        }
+        for column in ["project_lots", "tender_results", "awarding_criteria"] {
+            let mut keys = object_keys(&json[column][0]);
+            keys.sort();
+            assert_eq!(
+                keys,
+                struct_fields(df.column(column).unwrap().dtype()),
+                "{column}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn parse_xmls_writes_concatenated_jsonl() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = crate::config::ResolvedConfig {
+            download_dir_pt: tmp.path().join("tmp"),
+            parquet_dir_pt: tmp.path().join("out"),
+            batch_size: 1,
+            concat_batches: true,
+            output_format: OutputFormat::Jsonl,
+            ..Default::default()
+        };
+        let period_dir = tmp.path().join("tmp/202401");
+        std_fs::create_dir_all(&period_dir).unwrap();
+        for (name, id) in [("a.atom", "id1"), ("b.atom", "id2")] {
+            std_fs::write(
+                period_dir.join(name),
+                format!("<feed><entry><id>{id}</id><title>t</title></entry></feed>"),
+            )
+            .unwrap();
+        }
+        let mut links = BTreeMap::new();
+        links.insert("202401".to_string(), String::new());
+
+        let stats = parse_xmls(
+            &links,
+            &crate::models::ProcurementType::PublicTenders,
+            config.batch_size,
+            &config,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let text = std_fs::read_to_string(tmp.path().join("out/202401.jsonl")).unwrap();
+        let mut ids: Vec<String> = text
+            .lines()
+            .map(|line| {
+                let value: serde_json::Value = serde_json::from_str(line).unwrap();
+                value["id"].as_str().unwrap().to_string()
+            })
+            .collect();
+        ids.sort();
+        assert_eq!(ids, ["id1", "id2"]);
+        assert_eq!(stats.entries, 2);
+        assert_eq!(stats.parquet_files, 1);
+        assert!(!tmp.path().join("out/202401").exists());
+    }
+
+    #[tokio::test]
+    async fn parse_xmls_applies_transformer_before_writing() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = crate::config::ResolvedConfig {
+            download_dir_pt: tmp.path().join("tmp"),
+            parquet_dir_pt: tmp.path().join("out"),
+            output_format: OutputFormat::Jsonl,
+            concat_batches: true,
+            ..Default::default()
+        };
+        let period_dir = tmp.path().join("tmp/202401");
+        std_fs::create_dir_all(&period_dir).unwrap();
+        let entry = |id: &str, amount: &str| {
+            format!(
+                "<entry><id>{id}</id><cac-place-ext:ContractFolderStatus><cac:ProcurementProject>\
+                 <cac:BudgetAmount><cbc:TotalAmount>{amount}</cbc:TotalAmount></cac:BudgetAmount>\
+                 </cac:ProcurementProject></cac-place-ext:ContractFolderStatus></entry>"
+            )
+        };
+        std_fs::write(
+            period_dir.join("a.atom"),
+            format!(
+                "<feed>{}{}</feed>",
+                entry("small", "50"),
+                entry("large", "5000")
+            ),
+        )
+        .unwrap();
+        let mut links = BTreeMap::new();
+        links.insert("202401".to_string(), String::new());
+
+        let stats = parse_xmls(
+            &links,
+            &crate::models::ProcurementType::PublicTenders,
+            config.batch_size,
+            &config,
+            Some(Box::new(crate::transformer::FilterByMinAmount {
+                threshold: 1000.0,
+            })),
+        )
+        .await
+        .unwrap();
+
+        let text = std_fs::read_to_string(tmp.path().join("out/202401.jsonl")).unwrap();
+        let ids: Vec<String> = text
+            .lines()
+            .map(|line| {
+                let value: serde_json::Value = serde_json::from_str(line).unwrap();
+                value["id"].as_str().unwrap().to_string()
+            })
+            .collect();
+        assert_eq!(ids, ["large"]);
+        assert_eq!(stats.entries, 1);
+    }
+
+    #[tokio::test]
+    async fn batch_size_one_writes_one_batch_file_per_xml_file() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = crate::config::ResolvedConfig {
+            download_dir_pt: tmp.path().join("tmp"),
+            parquet_dir_pt: tmp.path().join("parquet"),
+            batch_size: 1,
+            ..Default::default()
+        };
+        let period_dir = tmp.path().join("tmp/202401");
+        std_fs::create_dir_all(&period_dir).unwrap();
+        for i in 0..3 {
+            std_fs::write(
+                period_dir.join(format!("{i}.atom")),
+                format!("<feed><entry><id>id{i}</id></entry></feed>"),
+            )
+            .unwrap();
+        }
+        let mut links = BTreeMap::new();
+        links.insert("202401".to_string(), String::new());
+
+        let stats = parse_xmls(
+            &links,
+            &crate::models::ProcurementType::PublicTenders,
+            config.batch_size,
+            &config,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let mut written: Vec<String> = std_fs::read_dir(tmp.path().join("parquet/202401"))
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        written.sort();
+        assert_eq!(
+            written,
+            ["batch_0.parquet", "batch_1.parquet", "batch_2.parquet"]
+        );
+        assert_eq!(stats.parquet_files, 3);
+    }
+
+    /// Parses period 202401 of `config` and returns its stats.
+    async fn parse_202401(config: &crate::config::ResolvedConfig) -> ParseStats {
+        let links = BTreeMap::from([("202401".to_string(), String::new())]);
+        parse_xmls(
+            &links,
+            &crate::models::ProcurementType::PublicTenders,
+            config.batch_size,
+            config,
+            None,
+        )
+        .await
+        .unwrap()
+    }
+
+    /// Sorted file names in `dir`.
+    fn file_names(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = std_fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    fn incremental_config(root: &Path) -> crate::config::ResolvedConfig {
+        crate::config::ResolvedConfig {
+            download_dir_pt: root.join("tmp"),
+            parquet_dir_pt: root.join("parquet"),
+            batch_size: 1,
+            incremental: true,
+            ..Default::default()
+        }
+    }
+
+    fn write_feed(dir: &Path, name: &str, id: &str) {
+        std_fs::create_dir_all(dir).unwrap();
+        std_fs::write(
+            dir.join(name),
+            format!("<feed><entry><id>{id}</id></entry></feed>"),
+        )
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn incremental_appends_batches_for_new_files_only() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = crate::config::ResolvedConfig {
+            concat_batches: true,
+            ..incremental_config(tmp.path())
+        };
+        let xml_dir = tmp.path().join("tmp/202401");
+        write_feed(&xml_dir, "a.atom", "id1");
+        write_feed(&xml_dir, "b.atom", "id2");
+        assert_eq!(parse_202401(&config).await.entries, 2);
+
+        write_feed(&xml_dir, "c.atom", "id3");
+        let stats = parse_202401(&config).await;
+
+        assert_eq!(stats.entries, 1);
+        let batch_dir = tmp.path().join("parquet/202401");
+        assert_eq!(
+            file_names(&batch_dir),
+            [
+                PROCESSED_FILES_NAME,
+                "batch_0.parquet",
+                "batch_1.parquet",
+                "batch_2.parquet"
+            ]
+        );
+        let manifest = ProcessedFiles::load(&batch_dir).unwrap();
+        let names: Vec<&str> = manifest.files.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["a.atom", "b.atom", "c.atom"]);
+        // The period file is concatenated from every batch, old and new.
+        let combined =
+            ParquetReader::new(File::open(tmp.path().join("parquet/202401.parquet")).unwrap())
+                .finish()
+                .unwrap();
+        assert_eq!(combined.height(), 3);
+    }
+
+    #[tokio::test]
+    async fn incremental_rerun_without_new_files_writes_nothing() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = incremental_config(tmp.path());
+        write_feed(&tmp.path().join("tmp/202401"), "a.atom", "id1");
+        parse_202401(&config).await;
+        let batch = tmp.path().join("parquet/202401/batch_0.parquet");
+        let written = std_fs::metadata(&batch).unwrap().modified().unwrap();
+
+        let stats = parse_202401(&config).await;
+
+        assert_eq!(stats.entries, 0);
+        assert_eq!(stats.parquet_files, 0);
+        assert_eq!(
+            file_names(&tmp.path().join("parquet/202401")),
+            [PROCESSED_FILES_NAME, "batch_0.parquet"]
+        );
+        assert_eq!(
+            std_fs::metadata(&batch).unwrap().modified().unwrap(),
+            written
+        );
+    }
+
+    #[tokio::test]
+    async fn incremental_records_each_batch_before_a_later_one_fails() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = incremental_config(tmp.path());
+        let xml_dir = tmp.path().join("tmp/202401");
+        write_feed(&xml_dir, "a.atom", "id1");
+        std_fs::write(xml_dir.join("b.atom"), "<feed><entry><id>id2</id></feed>").unwrap();
+        let links = BTreeMap::from([("202401".to_string(), String::new())]);
+        let procurement_type = crate::models::ProcurementType::PublicTenders;
+        assert!(parse_xmls(&links, &procurement_type, 1, &config, None)
+            .await
+            .is_err());
+
+        let batch_dir = tmp.path().join("parquet/202401");
+        let manifest = ProcessedFiles::load(&batch_dir).unwrap();
+        let names: Vec<&str> = manifest.files.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["a.atom"]);
+
+        // The rerun only parses the file that failed.
+        write_feed(&xml_dir, "b.atom", "id2");
+        assert_eq!(parse_202401(&config).await.entries, 1);
+        assert_eq!(
+            file_names(&batch_dir),
+            [PROCESSED_FILES_NAME, "batch_0.parquet", "batch_1.parquet"]
+        );
+    }
+
+    #[tokio::test]
+    async fn incremental_with_corrupt_manifest_rewrites_the_period() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = incremental_config(tmp.path());
+        let xml_dir = tmp.path().join("tmp/202401");
+        write_feed(&xml_dir, "a.atom", "id1");
+        write_feed(&xml_dir, "b.atom", "id2");
+        parse_202401(&config).await;
+        let batch_dir = tmp.path().join("parquet/202401");
+        std_fs::write(batch_dir.join(PROCESSED_FILES_NAME), "{not json").unwrap();
+        std_fs::remove_file(xml_dir.join("b.atom")).unwrap();
+
+        let stats = parse_202401(&config).await;
+
+        // Every file is parsed again and the stale batch of the removed file is gone.
+        assert_eq!(stats.entries, 1);
+        assert_eq!(
+            file_names(&batch_dir),
+            [PROCESSED_FILES_NAME, "batch_0.parquet"]
+        );
+        assert_eq!(ProcessedFiles::load(&batch_dir).unwrap().files.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn max_entries_per_file_splits_batches_into_shards() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = crate::config::ResolvedConfig {
+            download_dir_pt: tmp.path().join("tmp"),
+            parquet_dir_pt: tmp.path().join("parquet"),
+            max_entries_per_file: Some(3),
+            ..Default::default()
+        };
+        let feed: String = (0..10)
+            .map(|i| format!("<entry><id>id{i}</id></entry>"))
+            .collect();
+        std_fs::create_dir_all(tmp.path().join("tmp/202401")).unwrap();
+        std_fs::write(
+            tmp.path().join("tmp/202401/feed.atom"),
+            format!("<feed>{feed}</feed>"),
+        )
+        .unwrap();
+
+        let stats = parse_202401(&config).await;
+
+        let batch_dir = tmp.path().join("parquet/202401");
+        assert_eq!(
+            file_names(&batch_dir),
+            [
+                "batch_0_0.parquet",
+                "batch_0_1.parquet",
+                "batch_0_2.parquet",
+                "batch_0_3.parquet"
+            ]
+        );
+        let rows: Vec<usize> = (0..4)
+            .map(|shard| {
+                let path = batch_dir.join(format!("batch_0_{shard}.parquet"));
+                ParquetReader::new(File::open(path).unwrap())
+                    .finish()
+                    .unwrap()
+                    .height()
+            })
+            .collect();
+        assert_eq!(rows, [3, 3, 3, 1]);
+        assert_eq!(stats.entries, 10);
+        assert_eq!(stats.parquet_files, 4);
+    }
 }
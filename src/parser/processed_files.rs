@@ -0,0 +1,193 @@
+use crate::errors::{AppError, AppResult};
+use crate::utils::file_digest_hex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Name of the manifest kept in a period's batch directory by `incremental` runs.
+pub(super) const PROCESSED_FILES_NAME: &str = ".processed_files.json";
+
+/// One XML file already written to a period's batches.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(super) struct ProcessedFile {
+    /// File name of the XML/Atom file, without its directory
+    pub name: String,
+    /// Lowercase hex SHA-256 digest of the file contents
+    pub sha256: String,
+}
+
+impl ProcessedFile {
+    /// Names and hashes the file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IoError` if the file cannot be read.
+    pub(super) fn from_path(path: &Path) -> AppResult<Self> {
+        Ok(Self {
+            name: path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            sha256: file_digest_hex::<sha2::Sha256>(path)?,
+        })
+    }
+}
+
+/// Manifest of the XML files whose entries are in a period's batches, stored as
+/// `{period_dir}/.processed_files.json`.
+///
+/// Serialized as `{"files": [{"name": "feed.atom", "sha256": "..."}]}`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(super) struct ProcessedFiles {
+    pub files: Vec<ProcessedFile>,
+}
+
+impl ProcessedFiles {
+    /// Loads the manifest of `period_dir`.
+    ///
+    /// Returns `None` when there is none, and also, after logging a warning, when it cannot
+    /// be read or parsed: the batches can then not be matched to their files, so the period
+    /// has to be written again from scratch.
+    pub(super) fn load(period_dir: &Path) -> Option<Self> {
+        let path = period_dir.join(PROCESSED_FILES_NAME);
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return None,
+            Err(e) => {
+                warn!(path = %path.display(), error = %e, "Ignoring unreadable processed files manifest");
+                return None;
+            }
+        };
+        match serde_json::from_str(&contents) {
+            Ok(manifest) => Some(manifest),
+            Err(e) => {
+                warn!(path = %path.display(), error = %e, "Ignoring corrupt processed files manifest");
+                None
+            }
+        }
+    }
+
+    /// Whether a file with the same name and contents is recorded.
+    pub(super) fn contains(&self, file: &ProcessedFile) -> bool {
+        self.files.contains(file)
+    }
+
+    /// Whether a file with this name but other contents is recorded.
+    pub(super) fn has_changed(&self, file: &ProcessedFile) -> bool {
+        self.files
+            .iter()
+            .any(|recorded| recorded.name == file.name && recorded.sha256 != file.sha256)
+    }
+
+    /// Writes the manifest to `period_dir` atomically (temp file + rename).
+    pub(super) fn save(&self, period_dir: &Path) -> AppResult<()> {
+        let path = period_dir.join(PROCESSED_FILES_NAME);
+        let json = serde_json::to_string_pretty(self).map_err(|e| {
+            AppError::ParseError(format!("Failed to serialize processed files manifest: {e}"))
+        })?;
+        let tmp_path = path.with_extension("json.part");
+        fs::write(&tmp_path, json)
+            .and_then(|()| fs::rename(&tmp_path, &path))
+            .map_err(|e| {
+                AppError::IoError(format!(
+                    "Failed to write processed files manifest {}: {e}",
+                    path.display()
+                ))
+            })
+    }
+}
+
+/// Batch files `{prefix}N.parquet` (and their `{prefix}N_M.parquet` shards) in
+/// `period_dir`, ordered by `N`.
+pub(super) fn batch_files(period_dir: &Path, prefix: &str) -> AppResult<Vec<(usize, PathBuf)>> {
+    let mut batches = Vec::new();
+    for entry in fs::read_dir(period_dir)? {
+        let path = entry?.path();
+        let index = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix(prefix))
+            .and_then(|rest| rest.strip_suffix(".parquet"))
+            .and_then(|rest| rest.split('_').next())
+            .and_then(|index| index.parse().ok());
+        if let Some(index) = index {
+            batches.push((index, path));
+        }
+    }
+    batches.sort();
+    Ok(batches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn manifest_round_trips_and_matches_name_and_hash() {
+        let tmp = TempDir::new().unwrap();
+        let feed = tmp.path().join("feed.atom");
+        fs::write(&feed, "<feed/>").unwrap();
+        let file = ProcessedFile::from_path(&feed).unwrap();
+        assert_eq!(file.name, "feed.atom");
+        assert_eq!(file.sha256.len(), 64);
+
+        let manifest = ProcessedFiles {
+            files: vec![file.clone()],
+        };
+        manifest.save(tmp.path()).unwrap();
+        let loaded = ProcessedFiles::load(tmp.path()).unwrap();
+        assert_eq!(loaded, manifest);
+        assert!(loaded.contains(&file));
+
+        let changed = ProcessedFile {
+            sha256: "0".repeat(64),
+            ..file
+        };
+        assert!(!loaded.contains(&changed));
+        assert!(loaded.has_changed(&changed));
+    }
+
+    #[test]
+    fn missing_or_corrupt_manifest_loads_as_none() {
+        let tmp = TempDir::new().unwrap();
+        assert_eq!(ProcessedFiles::load(tmp.path()), None);
+
+        fs::write(tmp.path().join(PROCESSED_FILES_NAME), "{\"files\": [").unwrap();
+        assert_eq!(ProcessedFiles::load(tmp.path()), None);
+    }
+
+    #[test]
+    fn batch_files_are_ordered_by_index() {
+        let tmp = TempDir::new().unwrap();
+        for name in [
+            "batch_10.parquet",
+            "batch_2.parquet",
+            "batch_2_1.parquet",
+            "other.parquet",
+            PROCESSED_FILES_NAME,
+        ] {
+            fs::write(tmp.path().join(name), "").unwrap();
+        }
+
+        let batches: Vec<(usize, String)> = batch_files(tmp.path(), "batch_")
+            .unwrap()
+            .into_iter()
+            .map(|(index, path)| {
+                (
+                    index,
+                    path.file_name().unwrap().to_string_lossy().into_owned(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            batches,
+            [
+                (2, "batch_2.parquet".to_string()),
+                (2, "batch_2_1.parquet".to_string()),
+                (10, "batch_10.parquet".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,291 @@
+use crate::errors::{AppError, AppResult};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// Period a Parquet output entry belongs to, from its path relative to the Parquet directory.
+///
+/// Recognises the flat layout (`{period}.parquet`, `{period}/`, `{period}_lots.parquet`, and
+/// sidecars such as `{period}.sha256` or `{period}.jsonl`) and the partitioned one
+/// (`year=YYYY/month=MM` with its `.sha256` sidecar, also under `lots/`; `month=00` is the
+/// year-only period). Anything else, such as `manifest.json`, belongs to no period.
+fn output_period(relative: &Path) -> Option<String> {
+    let names: Vec<&str> = relative
+        .iter()
+        .map(|component| component.to_str())
+        .collect::<Option<_>>()?;
+    let names = match names.as_slice() {
+        ["lots", rest @ ..] => rest,
+        names => names,
+    };
+    let period = match names {
+        [name] => {
+            let stem = name.split('.').next()?;
+            stem.strip_suffix("_lots").unwrap_or(stem).to_string()
+        }
+        [year, month] => {
+            let year = year.strip_prefix("year=")?;
+            let month = month.split('.').next()?.strip_prefix("month=")?;
+            match month {
+                "00" => year.to_string(),
+                month => format!("{year}{month}"),
+            }
+        }
+        _ => return None,
+    };
+    period_key(&period).map(|_| period)
+}
+
+/// Sort key of a `YYYY` or `YYYYMM` period; a year-only period sorts before its months.
+fn period_key(period: &str) -> Option<(u32, u32)> {
+    if !period.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    match period.len() {
+        4 => Some((period.parse().ok()?, 0)),
+        6 => {
+            let year = period[..4].parse().ok()?;
+            let month = period[4..].parse().ok()?;
+            (1..=12).contains(&month).then_some((year, month))
+        }
+        _ => None,
+    }
+}
+
+/// Selects the output entries to delete to keep only the `keep` most recent periods.
+///
+/// `entries` are paths relative to the Parquet directory. Entries that do not belong to a
+/// period (see [`output_period`]) are never selected. Returns the victims in input order.
+pub(super) fn select_victims(entries: &[PathBuf], keep: usize) -> Vec<PathBuf> {
+    let periods: BTreeSet<(u32, u32)> = entries
+        .iter()
+        .filter_map(|entry| output_period(entry))
+        .filter_map(|period| period_key(&period))
+        .collect();
+    // With fewer than `keep` periods, the oldest one is kept and nothing is deleted.
+    let oldest_kept = periods.iter().rev().nth(keep.saturating_sub(1));
+    let Some(&oldest_kept) = oldest_kept.or(periods.first()) else {
+        return Vec::new();
+    };
+    entries
+        .iter()
+        .filter(|entry| {
+            output_period(entry)
+                .and_then(|period| period_key(&period))
+                .is_some_and(|key| key < oldest_kept)
+        })
+        .cloned()
+        .collect()
+}
+
+/// Lists the candidate entries of `parquet_dir`, relative to it: its direct children plus
+/// the `month=MM` entries of the partitioned `year=YYYY/` directories (also under `lots/`).
+async fn list_output_entries(parquet_dir: &Path) -> AppResult<Vec<PathBuf>> {
+    let mut entries = Vec::new();
+    let mut dirs = vec![PathBuf::new()];
+    while let Some(relative) = dirs.pop() {
+        let mut read_dir = tokio::fs::read_dir(parquet_dir.join(&relative)).await?;
+        while let Some(entry) = read_dir.next_entry().await? {
+            let name = entry.file_name();
+            let path = relative.join(&name);
+            let name = name.to_string_lossy();
+            let descend = entry.file_type().await?.is_dir()
+                && ((relative.as_os_str().is_empty() && name == "lots")
+                    || name.starts_with("year="));
+            if descend {
+                dirs.push(path);
+            } else {
+                entries.push(path);
+            }
+        }
+    }
+    entries.sort();
+    Ok(entries)
+}
+
+/// Deletes the Parquet output of every period older than the `keep` most recent ones in
+/// `parquet_dir`, logging each deletion, and returns the deleted paths.
+///
+/// With `dry_run`, nothing is deleted and each path that would be is printed instead.
+/// Partition `year=YYYY` directories left empty are removed too. A missing `parquet_dir` has
+/// nothing to delete.
+///
+/// # Errors
+///
+/// Returns `IoError` if the directory cannot be listed or an entry cannot be deleted.
+pub async fn apply_retention(
+    parquet_dir: &Path,
+    keep: usize,
+    dry_run: bool,
+) -> AppResult<Vec<PathBuf>> {
+    if !parquet_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let entries = list_output_entries(parquet_dir).await?;
+    let victims: Vec<PathBuf> = select_victims(&entries, keep)
+        .into_iter()
+        .map(|relative| parquet_dir.join(relative))
+        .collect();
+
+    for path in &victims {
+        if dry_run {
+            println!("Would remove {}", path.display());
+            continue;
+        }
+        let removed = if path.is_dir() {
+            tokio::fs::remove_dir_all(path).await
+        } else {
+            tokio::fs::remove_file(path).await
+        };
+        removed
+            .map_err(|e| AppError::IoError(format!("Failed to remove {}: {e}", path.display())))?;
+        info!(path = %path.display(), "Removed output past the retention window");
+        if let Some(parent) = path.parent().filter(|parent| {
+            parent
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with("year="))
+        }) {
+            // Fails while other months remain, which is fine.
+            let _ = tokio::fs::remove_dir(parent).await;
+        }
+    }
+
+    if dry_run {
+        info!(
+            would_remove = victims.len(),
+            keep, "Retention dry run, nothing removed"
+        );
+    } else {
+        info!(removed = victims.len(), keep, "Retention applied");
+    }
+    Ok(victims)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn paths(names: &[&str]) -> Vec<PathBuf> {
+        names.iter().map(PathBuf::from).collect()
+    }
+
+    #[test]
+    fn output_period_reads_flat_and_partitioned_names() {
+        for (name, period) in [
+            ("202401.parquet", Some("202401")),
+            ("202401", Some("202401")),
+            ("2023.jsonl", Some("2023")),
+            ("202401_lots.parquet", Some("202401")),
+            ("202401.unknown_elements.json", Some("202401")),
+            ("year=2024/month=03", Some("202403")),
+            ("year=2023/month=00.sha256", Some("2023")),
+            ("lots/year=2024/month=01", Some("202401")),
+            ("manifest.json", None),
+            ("202413.parquet", None),
+            ("2024Q1.parquet", None),
+            ("notes", None),
+            ("year=2024/readme.txt", None),
+            ("year=abcd/month=01", None),
+        ] {
+            assert_eq!(output_period(Path::new(name)).as_deref(), period, "{name}");
+        }
+    }
+
+    #[test]
+    fn select_victims_keeps_most_recent_periods_and_skips_junk() {
+        let entries = paths(&[
+            "2022.parquet",
+            "2022.sha256",
+            "2023",
+            "202301.parquet",
+            "202301_lots.parquet",
+            "202302",
+            "202302.sha256",
+            "202303.parquet",
+            "manifest.json",
+            "backup",
+            "2023-old",
+        ]);
+
+        assert_eq!(
+            select_victims(&entries, 2),
+            paths(&[
+                "2022.parquet",
+                "2022.sha256",
+                "2023",
+                "202301.parquet",
+                "202301_lots.parquet",
+            ])
+        );
+        assert_eq!(
+            select_victims(&entries, 4),
+            paths(&["2022.parquet", "2022.sha256"])
+        );
+        assert!(select_victims(&entries, 5).is_empty());
+        assert!(select_victims(&paths(&["manifest.json", "junk"]), 1).is_empty());
+    }
+
+    #[test]
+    fn select_victims_handles_partitioned_layout() {
+        let entries = paths(&[
+            "lots/year=2023/month=12",
+            "year=2023/month=00",
+            "year=2023/month=12",
+            "year=2023/month=12.sha256",
+            "year=2024/month=01",
+        ]);
+
+        assert_eq!(
+            select_victims(&entries, 1),
+            paths(&[
+                "lots/year=2023/month=12",
+                "year=2023/month=00",
+                "year=2023/month=12",
+                "year=2023/month=12.sha256",
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn apply_retention_deletes_old_periods_and_honours_dry_run() {
+        let tmp = TempDir::new().unwrap();
+        let root = tmp.path();
+        for file in [
+            "202401.parquet",
+            "202401.sha256",
+            "202402/batch_1.parquet",
+            "202403.parquet",
+            "manifest.json",
+            "old/202001.parquet",
+            "year=2023/month=12/part-0.parquet",
+        ] {
+            let path = root.join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+
+        let planned = apply_retention(root, 2, true).await.unwrap();
+        assert_eq!(planned.len(), 3);
+        assert!(root.join("202401.parquet").exists());
+
+        let removed = apply_retention(root, 2, false).await.unwrap();
+        assert_eq!(removed, planned);
+        assert!(!root.join("202401.parquet").exists());
+        assert!(!root.join("202401.sha256").exists());
+        assert!(!root.join("year=2023").exists());
+        for kept in [
+            "202402/batch_1.parquet",
+            "202403.parquet",
+            "manifest.json",
+            "old/202001.parquet",
+        ] {
+            assert!(root.join(kept).exists(), "{kept}");
+        }
+
+        assert!(apply_retention(&root.join("missing"), 2, false)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -1,14 +1,83 @@
 use crate::errors::{AppError, AppResult};
-use crate::models::{ProcurementProjectLot, StatusCode, TenderResultRow, TermsFundingProgram};
+use crate::models::{
+    AwardingCriterion, ProcurementProjectLot, StatusCode, TenderResultRow, TermsFundingProgram,
+};
+use crate::utils::normalize_whitespace;
 use quick_xml::events::{BytesStart, Event};
+use quick_xml::name::QName;
 use quick_xml::writer::Writer;
+use std::collections::BTreeMap;
 use std::io::Cursor;
 
+/// Local names of every element the scope maps to a field or scope flag somewhere in the
+/// subtree. With `report_unknown_elements`, elements with any other local name are counted
+/// in [`ScopeResult::unknown_elements`]; add a name here when matching it below.
+const MAPPED_LOCAL_NAMES: [&[u8]; 57] = [
+    b"ActivityCode",
+    b"AwardDate",
+    b"AwardedTenderedProject",
+    b"AwardingCriteria",
+    b"AwardingCriteriaTypeCode",
+    b"AwardingTerms",
+    b"BudgetAmount",
+    b"CityName",
+    b"ContractFolderID",
+    b"ContractFolderStatusCode",
+    b"ContractingPartyTypeCode",
+    b"Country",
+    b"CountrySubentity",
+    b"CountrySubentityCode",
+    b"Description",
+    b"DurationMeasure",
+    b"EndDate",
+    b"FundingProgramCode",
+    b"HigherTenderAmount",
+    b"ID",
+    b"IdentificationCode",
+    b"ItemClassificationCode",
+    b"LegalMonetaryTotal",
+    b"LocatedContractingParty",
+    b"LowerTenderAmount",
+    b"Name",
+    b"OccurrenceDate",
+    b"OpenTenderEvent",
+    b"Party",
+    b"PartyIdentification",
+    b"PartyName",
+    b"PayableAmount",
+    b"PlannedPeriod",
+    b"PostalAddress",
+    b"PostalZone",
+    b"ProcedureCode",
+    b"ProcurementProject",
+    b"ProcurementProjectLot",
+    b"ProcurementProjectLotID",
+    b"RealizedLocation",
+    b"ReceivedTenderQuantity",
+    b"RequiredCommodityClassification",
+    b"ResultCode",
+    b"SMEAwardedIndicator",
+    b"StartDate",
+    b"SubTypeCode",
+    b"TaxExclusiveAmount",
+    b"TenderResult",
+    b"TenderSubmissionDeadlinePeriod",
+    b"TenderingProcess",
+    b"TenderingTerms",
+    b"TotalAmount",
+    b"TypeCode",
+    b"UrgencyCode",
+    b"WebsiteURI",
+    b"WeightNumeric",
+    b"WinningParty",
+];
+
 /// Result from finishing a ContractFolderStatus scope.
 pub struct ScopeResult {
     pub status: StatusCode,
     pub contract_id: Option<String>,
     pub contracting_party_name: Option<String>,
+    pub contracting_party_id: Option<String>,
     pub contracting_party_website: Option<String>,
     pub contracting_party_type_code: Option<String>,
     pub contracting_party_type_code_list_uri: Option<String>,
@@ -31,15 +100,168 @@ pub struct ScopeResult {
     pub project_cpv_code_list_uri: Option<String>,
     pub project_country_code: Option<String>,
     pub project_country_code_list_uri: Option<String>,
+    pub project_duration_measure: Option<String>,
+    pub project_duration_unit: Option<String>,
+    pub project_country_subentity: Option<String>,
+    pub project_country_subentity_code: Option<String>,
+    pub project_country_subentity_code_list_uri: Option<String>,
     pub project_lots: Vec<ProcurementProjectLot>,
     pub tender_results: Vec<TenderResultRow>,
     pub terms_funding_program: TermsFundingProgram,
+    pub awarding_criteria: Vec<AwardingCriterion>,
+    pub process_start_date: Option<String>,
     pub process_end_date: Option<String>,
+    pub process_open_date: Option<String>,
     pub process_procedure_code: Option<String>,
     pub process_procedure_code_list_uri: Option<String>,
     pub process_urgency_code: Option<String>,
     pub process_urgency_code_list_uri: Option<String>,
     pub cfs_raw_xml: Option<String>,
+    /// Occurrences of each unmapped element local name (empty unless `report_unknown_elements`)
+    pub unknown_elements: BTreeMap<String, u64>,
+}
+
+impl ScopeResult {
+    /// Merges a later `<ContractFolderStatus>` subtree of the same entry into this one.
+    ///
+    /// Fields present in `later` replace the current values and absent ones are kept, so
+    /// a modification that only restates some elements does not erase the rest. Lots, tender
+    /// results, and awarding criteria are replaced as a whole when `later` has any. Raw XML is appended so
+    /// no subtree is lost.
+    pub fn merge(&mut self, later: ScopeResult) {
+        if later.status.code.is_some() {
+            self.status = later.status;
+        }
+        if later.terms_funding_program.code.is_some() {
+            self.terms_funding_program = later.terms_funding_program;
+        }
+        if !later.project_lots.is_empty() {
+            self.project_lots = later.project_lots;
+        }
+        if !later.tender_results.is_empty() {
+            self.tender_results = later.tender_results;
+        }
+        if !later.awarding_criteria.is_empty() {
+            self.awarding_criteria = later.awarding_criteria;
+        }
+        self.cfs_raw_xml = match (self.cfs_raw_xml.take(), later.cfs_raw_xml) {
+            (Some(earlier), Some(raw)) => Some(earlier + raw.as_str()),
+            (earlier, raw) => earlier.or(raw),
+        };
+        overlay(&mut self.contract_id, later.contract_id);
+        overlay(
+            &mut self.contracting_party_name,
+            later.contracting_party_name,
+        );
+        overlay(&mut self.contracting_party_id, later.contracting_party_id);
+        overlay(
+            &mut self.contracting_party_website,
+            later.contracting_party_website,
+        );
+        overlay(
+            &mut self.contracting_party_type_code,
+            later.contracting_party_type_code,
+        );
+        overlay(
+            &mut self.contracting_party_type_code_list_uri,
+            later.contracting_party_type_code_list_uri,
+        );
+        overlay(
+            &mut self.contracting_party_activity_code,
+            later.contracting_party_activity_code,
+        );
+        overlay(
+            &mut self.contracting_party_activity_code_list_uri,
+            later.contracting_party_activity_code_list_uri,
+        );
+        overlay(
+            &mut self.contracting_party_city,
+            later.contracting_party_city,
+        );
+        overlay(&mut self.contracting_party_zip, later.contracting_party_zip);
+        overlay(
+            &mut self.contracting_party_country_code,
+            later.contracting_party_country_code,
+        );
+        overlay(
+            &mut self.contracting_party_country_code_list_uri,
+            later.contracting_party_country_code_list_uri,
+        );
+        overlay(&mut self.project_name, later.project_name);
+        overlay(&mut self.project_type_code, later.project_type_code);
+        overlay(
+            &mut self.project_type_code_list_uri,
+            later.project_type_code_list_uri,
+        );
+        overlay(&mut self.project_sub_type_code, later.project_sub_type_code);
+        overlay(
+            &mut self.project_sub_type_code_list_uri,
+            later.project_sub_type_code_list_uri,
+        );
+        overlay(&mut self.project_total_amount, later.project_total_amount);
+        overlay(
+            &mut self.project_total_currency,
+            later.project_total_currency,
+        );
+        overlay(
+            &mut self.project_tax_exclusive_amount,
+            later.project_tax_exclusive_amount,
+        );
+        overlay(
+            &mut self.project_tax_exclusive_currency,
+            later.project_tax_exclusive_currency,
+        );
+        overlay(&mut self.project_cpv_code, later.project_cpv_code);
+        overlay(
+            &mut self.project_cpv_code_list_uri,
+            later.project_cpv_code_list_uri,
+        );
+        overlay(&mut self.project_country_code, later.project_country_code);
+        overlay(
+            &mut self.project_country_code_list_uri,
+            later.project_country_code_list_uri,
+        );
+        overlay(
+            &mut self.project_duration_measure,
+            later.project_duration_measure,
+        );
+        overlay(&mut self.project_duration_unit, later.project_duration_unit);
+        overlay(
+            &mut self.project_country_subentity,
+            later.project_country_subentity,
+        );
+        overlay(
+            &mut self.project_country_subentity_code,
+            later.project_country_subentity_code,
+        );
+        overlay(
+            &mut self.project_country_subentity_code_list_uri,
+            later.project_country_subentity_code_list_uri,
+        );
+        overlay(&mut self.process_start_date, later.process_start_date);
+        overlay(&mut self.process_end_date, later.process_end_date);
+        overlay(&mut self.process_open_date, later.process_open_date);
+        overlay(
+            &mut self.process_procedure_code,
+            later.process_procedure_code,
+        );
+        overlay(
+            &mut self.process_procedure_code_list_uri,
+            later.process_procedure_code_list_uri,
+        );
+        overlay(&mut self.process_urgency_code, later.process_urgency_code);
+        overlay(
+            &mut self.process_urgency_code_list_uri,
+            later.process_urgency_code_list_uri,
+        );
+    }
+}
+
+/// Replaces `slot` with `value` when `value` is present.
+fn overlay(slot: &mut Option<String>, value: Option<String>) {
+    if value.is_some() {
+        *slot = value;
+    }
 }
 
 /// Which text-capturing element is currently active.
@@ -54,13 +276,18 @@ enum ActiveField {
     ProjectTaxExclusiveAmount,
     ProjectCpvCode,
     ProjectCountryCode,
+    ProjectDurationMeasure,
+    ProjectCountrySubentity,
+    ProjectCountrySubentityCode,
     ProjectLotId,
     ProjectLotName,
     ProjectLotTotalAmount,
     ProjectLotTaxExclusiveAmount,
     ProjectLotCpvCode,
     ProjectLotCountryCode,
+    ProjectLotCountrySubentityCode,
     ContractingPartyName,
+    ContractingPartyId,
     ContractingPartyWebsite,
     ContractingPartyTypeCode,
     ContractingPartyActivityCode,
@@ -74,9 +301,17 @@ enum ActiveField {
     ResultAwardDate,
     ResultTaxExclusiveAmount,
     ResultPayableAmount,
+    ResultReceivedTenderQuantity,
+    ResultLowerTenderAmount,
+    ResultHigherTenderAmount,
     ResultLotId,
     TermsFundingProgramCode,
+    AwardingCriterionTypeCode,
+    AwardingCriterionDescription,
+    AwardingCriterionWeight,
+    ProcessStartDate,
     ProcessEndDate,
+    ProcessOpenDate,
     ProcessProcedureCode,
     ProcessUrgencyCode,
 }
@@ -87,6 +322,7 @@ pub struct ContractFolderStatusScope {
     pub status: StatusCode,
     pub contract_id: Option<String>,
     pub contracting_party_name: Option<String>,
+    pub contracting_party_id: Option<String>,
     pub contracting_party_website: Option<String>,
     pub contracting_party_type_code: Option<String>,
     pub contracting_party_type_code_list_uri: Option<String>,
@@ -109,15 +345,24 @@ pub struct ContractFolderStatusScope {
     pub project_cpv_code_list_uri: Option<String>,
     pub project_country_code: Option<String>,
     pub project_country_code_list_uri: Option<String>,
+    pub project_duration_measure: Option<String>,
+    pub project_duration_unit: Option<String>,
+    pub project_country_subentity: Option<String>,
+    pub project_country_subentity_code: Option<String>,
+    pub project_country_subentity_code_list_uri: Option<String>,
     pub project_lots: Vec<ProcurementProjectLot>,
     pub current_lot: Option<ProcurementProjectLot>,
+    pub awarding_criteria: Vec<AwardingCriterion>,
+    pub current_criterion: Option<AwardingCriterion>,
     pub tender_results: Vec<TenderResultRow>,
     pub current_tender_result: Option<TenderResultRow>,
     pub current_tender_result_lot_ids: Vec<String>,
     pub tender_result_counter: i32,
     tender_result_lot_id_buffer: Option<String>,
     pub terms_funding_program: TermsFundingProgram,
+    pub process_start_date: Option<String>,
     pub process_end_date: Option<String>,
+    pub process_open_date: Option<String>,
     pub process_procedure_code: Option<String>,
     pub process_procedure_code_list_uri: Option<String>,
     pub process_urgency_code: Option<String>,
@@ -149,6 +394,9 @@ pub struct ContractFolderStatusScope {
     in_awarding_terms: bool,
     in_awarding_criteria: bool,
     in_deadline_period: bool,
+    in_open_tender_event: bool,
+    in_planned_period: bool,
+    in_realized_location: bool,
 
     // Currently capturing (for leaf elements with text)
     active_field: Option<ActiveField>,
@@ -158,11 +406,27 @@ pub struct ContractFolderStatusScope {
     // Raw XML capture
     depth: u32,
     writer: Option<Writer<Cursor<Vec<u8>>>>,
+
+    /// Whether captured text gets its whitespace runs collapsed
+    normalize_whitespace: bool,
+
+    /// Occurrences of unmapped element local names, counted only when reporting is enabled
+    unknown_elements: Option<BTreeMap<String, u64>>,
 }
 
 impl ContractFolderStatusScope {
     /// Creates a new scope initialized with the `<ContractFolderStatus>` start event.
-    pub fn start(event: Event, keep_raw_xml: bool) -> AppResult<Self> {
+    ///
+    /// With `normalize_whitespace`, captured field text is passed through
+    /// [`normalize_whitespace`]; the raw XML buffer is unaffected. With
+    /// `report_unknown_elements`, elements whose local name is not in [`MAPPED_LOCAL_NAMES`]
+    /// are counted; otherwise nothing is looked up.
+    pub fn start(
+        event: Event,
+        keep_raw_xml: bool,
+        normalize_whitespace: bool,
+        report_unknown_elements: bool,
+    ) -> AppResult<Self> {
         let writer = if keep_raw_xml {
             let cursor = Cursor::new(Vec::with_capacity(16 * 1024));
             let mut w = Writer::new(cursor);
@@ -178,6 +442,7 @@ impl ContractFolderStatusScope {
             status: StatusCode::default(),
             contract_id: None,
             contracting_party_name: None,
+            contracting_party_id: None,
             contracting_party_website: None,
             contracting_party_type_code: None,
             contracting_party_type_code_list_uri: None,
@@ -200,15 +465,24 @@ impl ContractFolderStatusScope {
             project_cpv_code_list_uri: None,
             project_country_code: None,
             project_country_code_list_uri: None,
+            project_duration_measure: None,
+            project_duration_unit: None,
+            project_country_subentity: None,
+            project_country_subentity_code: None,
+            project_country_subentity_code_list_uri: None,
             project_lots: Vec::new(),
             current_lot: None,
+            awarding_criteria: Vec::new(),
+            current_criterion: None,
             tender_results: Vec::new(),
             current_tender_result: None,
             current_tender_result_lot_ids: Vec::new(),
             tender_result_counter: 0,
             tender_result_lot_id_buffer: None,
             terms_funding_program: TermsFundingProgram::default(),
+            process_start_date: None,
             process_end_date: None,
+            process_open_date: None,
             process_procedure_code: None,
             process_procedure_code_list_uri: None,
             process_urgency_code: None,
@@ -236,11 +510,16 @@ impl ContractFolderStatusScope {
             in_awarding_terms: false,
             in_awarding_criteria: false,
             in_deadline_period: false,
+            in_open_tender_event: false,
+            in_planned_period: false,
+            in_realized_location: false,
             active_field: None,
             project_name_captured: false,
             project_lot_name_captured: false,
             depth: 1,
             writer,
+            normalize_whitespace,
+            unknown_elements: report_unknown_elements.then(BTreeMap::new),
         })
     }
 
@@ -251,6 +530,7 @@ impl ContractFolderStatusScope {
                 self.depth = self.depth.saturating_add(1);
                 let qname = e.name();
                 let name = qname.as_ref();
+                self.count_unknown_element(name);
                 self.update_scope_flags_on_start(name);
                 let mut field = self.determine_active_field(name);
                 if field.is_none()
@@ -266,6 +546,7 @@ impl ContractFolderStatusScope {
                     } else {
                         self.prepare_multivalue(field);
                         self.capture_currency(field, e);
+                        self.capture_unit_code(field, e);
                         self.capture_list_uri(field, e);
                     }
                     self.active_field = Some(field);
@@ -276,30 +557,28 @@ impl ContractFolderStatusScope {
             Event::Empty(e) => {
                 let qname = e.name();
                 let name = qname.as_ref();
+                self.count_unknown_element(name);
                 self.update_scope_flags_on_start(name);
                 if let Some(field) = self.determine_active_field(name) {
                     if field == ActiveField::ResultLotId {
-                        self.tender_result_lot_id_buffer = Some(String::new());
-                        self.push_result_lot_id();
+                        // `<cbc:ProcurementProjectLotID/>` carries no lot ID; the row falls
+                        // back to "0" in `push_current_tender_result`.
+                        self.tender_result_lot_id_buffer = None;
                     } else {
                         self.prepare_multivalue(field);
                         self.ensure_field_exists(field);
                     }
                 }
             }
-            Event::Text(text) => {
-                if self.active_field.is_some() {
-                    let decoded = text
-                        .decode()
-                        .map_err(|e| AppError::ParseError(format!("Failed to decode text: {e}")))?;
-                    self.append_text(&decoded);
-                }
+            Event::Text(text) if self.active_field.is_some() => {
+                let decoded = text
+                    .decode()
+                    .map_err(|e| AppError::ParseError(format!("Failed to decode text: {e}")))?;
+                self.append_text(&decoded);
             }
-            Event::CData(cdata) => {
-                if self.active_field.is_some() {
-                    let fragment = String::from_utf8_lossy(cdata.as_ref());
-                    self.append_text(&fragment);
-                }
+            Event::CData(cdata) if self.active_field.is_some() => {
+                let fragment = String::from_utf8_lossy(cdata.as_ref());
+                self.append_text(&fragment);
             }
             Event::End(e) => {
                 let qname = e.name();
@@ -335,6 +614,19 @@ impl ContractFolderStatusScope {
         self.write_main_event(event)
     }
 
+    /// Counts `name` when reporting unknown elements and its local name is not mapped.
+    fn count_unknown_element(&mut self, name: &[u8]) {
+        let Some(unknown_elements) = self.unknown_elements.as_mut() else {
+            return;
+        };
+        let local = QName(name).local_name();
+        if !MAPPED_LOCAL_NAMES.contains(&local.as_ref()) {
+            *unknown_elements
+                .entry(String::from_utf8_lossy(local.as_ref()).into_owned())
+                .or_default() += 1;
+        }
+    }
+
     fn update_scope_flags_on_start(&mut self, name: &[u8]) {
         if matches_local_name(name, b"ProcurementProjectLot") {
             self.in_project_lot = true;
@@ -376,8 +668,12 @@ impl ContractFolderStatusScope {
             self.in_awarding_terms = true;
         } else if matches_local_name(name, b"AwardingCriteria") {
             self.in_awarding_criteria = true;
+            self.push_current_criterion();
+            self.current_criterion = Some(AwardingCriterion::default());
         } else if matches_local_name(name, b"TenderSubmissionDeadlinePeriod") {
             self.in_deadline_period = true;
+        } else if matches_local_name(name, b"OpenTenderEvent") {
+            self.in_open_tender_event = true;
         }
 
         if self.in_project && !self.in_project_lot {
@@ -385,6 +681,10 @@ impl ContractFolderStatusScope {
                 self.in_budget_amount = true;
             } else if matches_local_name(name, b"RequiredCommodityClassification") {
                 self.in_required_classification = true;
+            } else if matches_local_name(name, b"PlannedPeriod") {
+                self.in_planned_period = true;
+            } else if matches_local_name(name, b"RealizedLocation") {
+                self.in_realized_location = true;
             }
         }
 
@@ -393,6 +693,8 @@ impl ContractFolderStatusScope {
                 self.in_lot_budget_amount = true;
             } else if matches_local_name(name, b"RequiredCommodityClassification") {
                 self.in_lot_required_classification = true;
+            } else if matches_local_name(name, b"RealizedLocation") {
+                self.in_realized_location = true;
             }
         }
 
@@ -412,6 +714,7 @@ impl ContractFolderStatusScope {
             self.in_lot_budget_amount = false;
             self.in_lot_required_classification = false;
             self.in_lot_country = false;
+            self.in_realized_location = false;
             self.push_current_lot();
         } else if matches_local_name(name, b"ProcurementProject") {
             self.in_project = false;
@@ -451,8 +754,11 @@ impl ContractFolderStatusScope {
             self.in_awarding_terms = false;
         } else if matches_local_name(name, b"AwardingCriteria") {
             self.in_awarding_criteria = false;
+            self.push_current_criterion();
         } else if matches_local_name(name, b"TenderSubmissionDeadlinePeriod") {
             self.in_deadline_period = false;
+        } else if matches_local_name(name, b"OpenTenderEvent") {
+            self.in_open_tender_event = false;
         }
 
         if matches_local_name(name, b"BudgetAmount") {
@@ -463,6 +769,12 @@ impl ContractFolderStatusScope {
             self.in_required_classification = false;
             self.in_lot_required_classification = false;
         }
+        if matches_local_name(name, b"PlannedPeriod") {
+            self.in_planned_period = false;
+        }
+        if matches_local_name(name, b"RealizedLocation") {
+            self.in_realized_location = false;
+        }
         if matches_local_name(name, b"AwardedTenderedProject") {
             self.in_awarded_tendered_project = false;
         }
@@ -493,11 +805,32 @@ impl ContractFolderStatusScope {
                 ActiveField::ResultPayableAmount => {
                     self.current_tender_result_mut().result_payable_currency = Some(currency)
                 }
+                ActiveField::ResultLowerTenderAmount => {
+                    self.current_tender_result_mut()
+                        .result_lower_tender_currency = Some(currency)
+                }
+                ActiveField::ResultHigherTenderAmount => {
+                    self.current_tender_result_mut()
+                        .result_higher_tender_currency = Some(currency)
+                }
                 _ => {}
             }
         }
     }
 
+    fn capture_unit_code(&mut self, field: ActiveField, start: &BytesStart) {
+        if field != ActiveField::ProjectDurationMeasure {
+            return;
+        }
+        if let Some(attr) = start
+            .attributes()
+            .filter_map(|a| a.ok())
+            .find(|a| a.key.as_ref() == b"unitCode")
+        {
+            self.project_duration_unit = Some(String::from_utf8_lossy(&attr.value).into_owned());
+        }
+    }
+
     fn set_current_lot_currency(&mut self, field: ActiveField, currency: String) {
         if let Some(lot) = &mut self.current_lot {
             match field {
@@ -532,7 +865,12 @@ impl ContractFolderStatusScope {
                 ActiveField::ProjectSubTypeCode => self.project_sub_type_code_list_uri = Some(uri),
                 ActiveField::ProjectCpvCode => self.project_cpv_code_list_uri = Some(uri),
                 ActiveField::ProjectCountryCode => self.project_country_code_list_uri = Some(uri),
-                ActiveField::ProjectLotCpvCode | ActiveField::ProjectLotCountryCode => {
+                ActiveField::ProjectCountrySubentityCode => {
+                    self.project_country_subentity_code_list_uri = Some(uri)
+                }
+                ActiveField::ProjectLotCpvCode
+                | ActiveField::ProjectLotCountryCode
+                | ActiveField::ProjectLotCountrySubentityCode => {
                     self.set_current_lot_list_uri(field, uri)
                 }
                 ActiveField::ResultCode => {
@@ -555,6 +893,9 @@ impl ContractFolderStatusScope {
             match field {
                 ActiveField::ProjectLotCpvCode => lot.cpv_code_list_uri = Some(uri),
                 ActiveField::ProjectLotCountryCode => lot.country_code_list_uri = Some(uri),
+                ActiveField::ProjectLotCountrySubentityCode => {
+                    lot.country_subentity_code_list_uri = Some(uri)
+                }
                 _ => {}
             }
         }
@@ -580,13 +921,18 @@ impl ContractFolderStatusScope {
             ActiveField::ProjectTaxExclusiveAmount => &mut self.project_tax_exclusive_amount,
             ActiveField::ProjectCpvCode => &mut self.project_cpv_code,
             ActiveField::ProjectCountryCode => &mut self.project_country_code,
+            ActiveField::ProjectDurationMeasure => &mut self.project_duration_measure,
+            ActiveField::ProjectCountrySubentity => &mut self.project_country_subentity,
+            ActiveField::ProjectCountrySubentityCode => &mut self.project_country_subentity_code,
             ActiveField::ProjectLotId
             | ActiveField::ProjectLotName
             | ActiveField::ProjectLotTotalAmount
             | ActiveField::ProjectLotTaxExclusiveAmount
             | ActiveField::ProjectLotCpvCode
-            | ActiveField::ProjectLotCountryCode => self.project_lot_field_ref(field),
+            | ActiveField::ProjectLotCountryCode
+            | ActiveField::ProjectLotCountrySubentityCode => self.project_lot_field_ref(field),
             ActiveField::ContractingPartyName => &mut self.contracting_party_name,
+            ActiveField::ContractingPartyId => &mut self.contracting_party_id,
             ActiveField::ContractingPartyWebsite => &mut self.contracting_party_website,
             ActiveField::ContractingPartyTypeCode => &mut self.contracting_party_type_code,
             ActiveField::ContractingPartyActivityCode => &mut self.contracting_party_activity_code,
@@ -599,9 +945,17 @@ impl ContractFolderStatusScope {
             | ActiveField::ResultSmeAwardedIndicator
             | ActiveField::ResultAwardDate
             | ActiveField::ResultTaxExclusiveAmount
-            | ActiveField::ResultPayableAmount => self.tender_result_field_ref(field),
+            | ActiveField::ResultPayableAmount
+            | ActiveField::ResultReceivedTenderQuantity
+            | ActiveField::ResultLowerTenderAmount
+            | ActiveField::ResultHigherTenderAmount => self.tender_result_field_ref(field),
             ActiveField::TermsFundingProgramCode => &mut self.terms_funding_program.code,
+            ActiveField::AwardingCriterionTypeCode
+            | ActiveField::AwardingCriterionDescription
+            | ActiveField::AwardingCriterionWeight => self.awarding_criterion_field_ref(field),
+            ActiveField::ProcessStartDate => &mut self.process_start_date,
             ActiveField::ProcessEndDate => &mut self.process_end_date,
+            ActiveField::ProcessOpenDate => &mut self.process_open_date,
             ActiveField::ProcessProcedureCode => &mut self.process_procedure_code,
             ActiveField::ProcessUrgencyCode => &mut self.process_urgency_code,
             _ => unreachable!("Invalid active field: {:?}", field),
@@ -619,6 +973,7 @@ impl ContractFolderStatusScope {
             ActiveField::ProjectLotTaxExclusiveAmount => &mut lot.tax_exclusive_amount,
             ActiveField::ProjectLotCpvCode => &mut lot.cpv_code,
             ActiveField::ProjectLotCountryCode => &mut lot.country_code,
+            ActiveField::ProjectLotCountrySubentityCode => &mut lot.country_subentity_code,
             _ => unreachable!("Invalid lot field: {:?}", field),
         }
     }
@@ -629,6 +984,24 @@ impl ContractFolderStatusScope {
         }
     }
 
+    fn awarding_criterion_field_ref(&mut self, field: ActiveField) -> &mut Option<String> {
+        let criterion = self
+            .current_criterion
+            .get_or_insert_with(AwardingCriterion::default);
+        match field {
+            ActiveField::AwardingCriterionTypeCode => &mut criterion.type_code,
+            ActiveField::AwardingCriterionDescription => &mut criterion.description,
+            ActiveField::AwardingCriterionWeight => &mut criterion.weight,
+            _ => unreachable!("Invalid awarding criterion field: {:?}", field),
+        }
+    }
+
+    fn push_current_criterion(&mut self) {
+        if let Some(criterion) = self.current_criterion.take() {
+            self.awarding_criteria.push(criterion);
+        }
+    }
+
     fn push_result_lot_id(&mut self) {
         if let Some(buffer) = self.tender_result_lot_id_buffer.take() {
             if !buffer.is_empty() {
@@ -688,6 +1061,9 @@ impl ContractFolderStatusScope {
             ActiveField::ResultAwardDate => &mut row.result_award_date,
             ActiveField::ResultTaxExclusiveAmount => &mut row.result_tax_exclusive_amount,
             ActiveField::ResultPayableAmount => &mut row.result_payable_amount,
+            ActiveField::ResultReceivedTenderQuantity => &mut row.result_received_tender_quantity,
+            ActiveField::ResultLowerTenderAmount => &mut row.result_lower_tender_amount,
+            ActiveField::ResultHigherTenderAmount => &mut row.result_higher_tender_amount,
             _ => unreachable!("Invalid tender result field: {:?}", field),
         }
     }
@@ -706,6 +1082,7 @@ impl ContractFolderStatusScope {
     pub fn finish(mut self, event: Event) -> AppResult<ScopeResult> {
         self.push_current_lot();
         self.push_current_tender_result();
+        self.push_current_criterion();
 
         let cfs_raw_xml = if let Some(mut writer) = self.writer {
             writer
@@ -726,6 +1103,7 @@ impl ContractFolderStatusScope {
             status: self.status,
             contract_id: self.contract_id,
             contracting_party_name: self.contracting_party_name,
+            contracting_party_id: self.contracting_party_id,
             contracting_party_website: self.contracting_party_website,
             contracting_party_type_code: self.contracting_party_type_code,
             contracting_party_type_code_list_uri: self.contracting_party_type_code_list_uri,
@@ -748,15 +1126,24 @@ impl ContractFolderStatusScope {
             project_cpv_code_list_uri: self.project_cpv_code_list_uri,
             project_country_code: self.project_country_code,
             project_country_code_list_uri: self.project_country_code_list_uri,
+            project_duration_measure: self.project_duration_measure,
+            project_duration_unit: self.project_duration_unit,
+            project_country_subentity: self.project_country_subentity,
+            project_country_subentity_code: self.project_country_subentity_code,
+            project_country_subentity_code_list_uri: self.project_country_subentity_code_list_uri,
             project_lots: self.project_lots,
             tender_results: self.tender_results,
             terms_funding_program: self.terms_funding_program,
+            awarding_criteria: self.awarding_criteria,
+            process_start_date: self.process_start_date,
             process_end_date: self.process_end_date,
+            process_open_date: self.process_open_date,
             process_procedure_code: self.process_procedure_code,
             process_procedure_code_list_uri: self.process_procedure_code_list_uri,
             process_urgency_code: self.process_urgency_code,
             process_urgency_code_list_uri: self.process_urgency_code_list_uri,
             cfs_raw_xml,
+            unknown_elements: self.unknown_elements.unwrap_or_default(),
         })
     }
 
@@ -769,6 +1156,18 @@ impl ContractFolderStatusScope {
             return Some(ActiveField::Id);
         }
 
+        if self.in_awarding_criteria {
+            if matches_local_name(name, b"AwardingCriteriaTypeCode") {
+                return Some(ActiveField::AwardingCriterionTypeCode);
+            }
+            if matches_local_name(name, b"Description") {
+                return Some(ActiveField::AwardingCriterionDescription);
+            }
+            if matches_local_name(name, b"WeightNumeric") {
+                return Some(ActiveField::AwardingCriterionWeight);
+            }
+        }
+
         // ProcurementProjectLot takes precedence when we're inside it
         if self.in_project_lot {
             if matches_local_name(name, b"Name")
@@ -791,6 +1190,9 @@ impl ContractFolderStatusScope {
             if self.in_lot_country && matches_local_name(name, b"IdentificationCode") {
                 return Some(ActiveField::ProjectLotCountryCode);
             }
+            if self.in_realized_location && matches_local_name(name, b"CountrySubentityCode") {
+                return Some(ActiveField::ProjectLotCountrySubentityCode);
+            }
         }
 
         if self.in_project && !self.in_project_lot {
@@ -818,6 +1220,17 @@ impl ContractFolderStatusScope {
             if self.in_country && matches_local_name(name, b"IdentificationCode") {
                 return Some(ActiveField::ProjectCountryCode);
             }
+            if self.in_planned_period && matches_local_name(name, b"DurationMeasure") {
+                return Some(ActiveField::ProjectDurationMeasure);
+            }
+            if self.in_realized_location {
+                if matches_local_name(name, b"CountrySubentity") {
+                    return Some(ActiveField::ProjectCountrySubentity);
+                }
+                if matches_local_name(name, b"CountrySubentityCode") {
+                    return Some(ActiveField::ProjectCountrySubentityCode);
+                }
+            }
         }
 
         if self.in_contracting_party {
@@ -834,6 +1247,9 @@ impl ContractFolderStatusScope {
                 if self.in_party_name && matches_local_name(name, b"Name") {
                     return Some(ActiveField::ContractingPartyName);
                 }
+                if self.in_party_identification && matches_local_name(name, b"ID") {
+                    return Some(ActiveField::ContractingPartyId);
+                }
                 if self.in_postal_address {
                     if matches_local_name(name, b"CityName") {
                         return Some(ActiveField::ContractingPartyCity);
@@ -869,6 +1285,15 @@ impl ContractFolderStatusScope {
             if matches_local_name(name, b"AwardDate") {
                 return Some(ActiveField::ResultAwardDate);
             }
+            if matches_local_name(name, b"ReceivedTenderQuantity") {
+                return Some(ActiveField::ResultReceivedTenderQuantity);
+            }
+            if matches_local_name(name, b"LowerTenderAmount") {
+                return Some(ActiveField::ResultLowerTenderAmount);
+            }
+            if matches_local_name(name, b"HigherTenderAmount") {
+                return Some(ActiveField::ResultHigherTenderAmount);
+            }
         }
 
         if self.in_legal_monetary_total && matches_local_name(name, b"TaxExclusiveAmount") {
@@ -879,9 +1304,15 @@ impl ContractFolderStatusScope {
         }
 
         if self.in_tendering_process {
+            if self.in_deadline_period && matches_local_name(name, b"StartDate") {
+                return Some(ActiveField::ProcessStartDate);
+            }
             if self.in_deadline_period && matches_local_name(name, b"EndDate") {
                 return Some(ActiveField::ProcessEndDate);
             }
+            if self.in_open_tender_event && matches_local_name(name, b"OccurrenceDate") {
+                return Some(ActiveField::ProcessOpenDate);
+            }
             if matches_local_name(name, b"ProcedureCode") {
                 return Some(ActiveField::ProcessProcedureCode);
             }
@@ -909,15 +1340,25 @@ impl ContractFolderStatusScope {
                 .tender_result_lot_id_buffer
                 .get_or_insert_with(String::new);
             buffer.push_str(text);
+            if self.normalize_whitespace {
+                *buffer = normalize_whitespace(buffer);
+            }
             return;
         }
 
+        let normalize = self.normalize_whitespace;
         let target = self.field_ref(field);
         if let Some(existing) = target {
             existing.push_str(text);
         } else {
             *target = Some(text.to_owned());
         }
+        // Normalized after appending, so whitespace between fragments is collapsed too.
+        if normalize {
+            if let Some(value) = target {
+                *value = normalize_whitespace(value);
+            }
+        }
     }
 
     /// Ensures a field exists (for empty elements).
@@ -933,9 +1374,11 @@ impl ContractFolderStatusScope {
     }
 }
 
-/// Checks if a qualified name ends with the given local name.
-fn matches_local_name(qname: &[u8], local: &[u8]) -> bool {
-    qname.ends_with(local)
-        && (qname.len() == local.len()
-            || qname.get(qname.len() - local.len() - 1).copied() == Some(b':'))
+/// Checks whether the namespace-stripped local name of `qname` is exactly `local`.
+///
+/// The prefix is resolved with [`QName::local_name`], so `cbc:Name` and an unprefixed
+/// `Name` both match `Name`, while names that merely end with it (`cbc:PartyName`,
+/// `x:cbc:Name`) do not.
+pub(super) fn matches_local_name(qname: &[u8], local: &[u8]) -> bool {
+    QName(qname).local_name().as_ref() == local
 }
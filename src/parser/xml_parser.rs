@@ -1,15 +1,19 @@
-use super::contract_folder_status::ContractFolderStatusHandler;
+use super::contract_folder_status::{ContractFolderStatusHandler, ParsedContractFolderStatus};
+use crate::config::MultipleCfsStrategy;
 use crate::errors::{AppError, AppResult};
 use crate::models::{
-    Entry, ProcurementProjectLot, StatusCode, TenderResultRow, TermsFundingProgram,
+    AwardingCriterion, Entry, FeedMetadata, ProcurementProjectLot, StatusCode, TenderResultRow,
+    TermsFundingProgram,
 };
-use quick_xml::events::Event;
+use crate::utils::normalize_whitespace;
+use quick_xml::events::{BytesStart, Event};
 use quick_xml::reader::Reader;
-#[cfg(test)]
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
 use std::fs;
-use std::io::Cursor;
-#[cfg(test)]
-use std::path::Path;
+use std::io::{Cursor, Read, Write};
+use std::path::{Path, PathBuf};
 
 /// Represents the current field being parsed within an entry
 enum EntryField {
@@ -17,6 +21,42 @@ enum EntryField {
     Title,
     Summary,
     Updated,
+    /// `<name>` inside the entry-level `<author>`
+    AuthorName,
+}
+
+/// Feed-level metadata collected before the first entry of a document.
+struct FeedHeader {
+    /// Whether no `<entry>` (or `<at:deleted-entry>`) has been seen yet
+    inside: bool,
+    current_field: Option<EntryField>,
+    metadata: FeedMetadata,
+    normalize_whitespace: bool,
+}
+
+impl FeedHeader {
+    fn new(normalize_whitespace: bool) -> Self {
+        Self {
+            inside: true,
+            current_field: None,
+            metadata: FeedMetadata::default(),
+            normalize_whitespace,
+        }
+    }
+
+    fn set_field_text(&mut self, text: String) {
+        let text = if self.normalize_whitespace {
+            normalize_whitespace(&text)
+        } else {
+            text
+        };
+        match self.current_field {
+            Some(EntryField::Id) => self.metadata.feed_id = Some(text),
+            Some(EntryField::Title) => self.metadata.feed_title = Some(text),
+            Some(EntryField::Updated) => self.metadata.feed_updated = Some(text),
+            _ => {}
+        }
+    }
 }
 
 /// Builder for constructing Entry structs during XML parsing.
@@ -26,9 +66,17 @@ struct EntryBuilder {
     link: Option<String>,
     summary: Option<String>,
     updated: Option<String>,
+    /// `term` attributes of the entry-level `<category>` elements, in document order
+    categories: Vec<String>,
+    /// `<name>` texts of the entry-level `<author>` elements, in document order
+    authors: Vec<String>,
+    /// Whether the parser is inside an entry-level `<author>` element
+    inside_author: bool,
+    is_deleted: bool,
     status: StatusCode,
     contract_id: Option<String>,
     contracting_party_name: Option<String>,
+    contracting_party_id: Option<String>,
     contracting_party_website: Option<String>,
     contracting_party_type_code: Option<String>,
     contracting_party_type_code_list_uri: Option<String>,
@@ -51,10 +99,18 @@ struct EntryBuilder {
     project_cpv_code_list_uri: Option<String>,
     project_country_code: Option<String>,
     project_country_code_list_uri: Option<String>,
+    project_duration_measure: Option<String>,
+    project_duration_unit: Option<String>,
+    project_country_subentity: Option<String>,
+    project_country_subentity_code: Option<String>,
+    project_country_subentity_code_list_uri: Option<String>,
     project_lots: Vec<ProcurementProjectLot>,
     tender_results: Vec<TenderResultRow>,
     terms_funding_program: TermsFundingProgram,
+    awarding_criteria: Vec<AwardingCriterion>,
+    process_start_date: Option<String>,
     process_end_date: Option<String>,
+    process_open_date: Option<String>,
     process_procedure_code: Option<String>,
     process_procedure_code_list_uri: Option<String>,
     process_urgency_code: Option<String>,
@@ -62,19 +118,37 @@ struct EntryBuilder {
     cfs_raw_xml: Option<String>,
     current_field: Option<EntryField>,
     contract_folder_status_handler: ContractFolderStatusHandler,
+    /// Finished `<ContractFolderStatus>` subtrees of the current entry, in document order
+    contract_folder_statuses: Vec<ParsedContractFolderStatus>,
+    multiple_cfs: MultipleCfsStrategy,
+    /// Whether text fields get their whitespace runs collapsed
+    normalize_whitespace: bool,
+    /// Unmapped `<ContractFolderStatus>` element counts of every subtree so far; kept across
+    /// entries
+    unknown_elements: BTreeMap<String, u64>,
 }
 
 impl EntryBuilder {
-    fn new(keep_raw_xml: bool) -> Self {
+    fn new(
+        keep_raw_xml: bool,
+        multiple_cfs: MultipleCfsStrategy,
+        normalize_whitespace: bool,
+        report_unknown_elements: bool,
+    ) -> Self {
         Self {
             id: None,
             title: None,
             link: None,
             summary: None,
             updated: None,
+            categories: Vec::new(),
+            authors: Vec::new(),
+            inside_author: false,
+            is_deleted: false,
             status: StatusCode::default(),
             contract_id: None,
             contracting_party_name: None,
+            contracting_party_id: None,
             contracting_party_website: None,
             contracting_party_type_code: None,
             contracting_party_type_code_list_uri: None,
@@ -97,17 +171,33 @@ impl EntryBuilder {
             project_cpv_code_list_uri: None,
             project_country_code: None,
             project_country_code_list_uri: None,
+            project_duration_measure: None,
+            project_duration_unit: None,
+            project_country_subentity: None,
+            project_country_subentity_code: None,
+            project_country_subentity_code_list_uri: None,
             project_lots: Vec::new(),
             tender_results: Vec::new(),
             terms_funding_program: TermsFundingProgram::default(),
+            awarding_criteria: Vec::new(),
+            process_start_date: None,
             process_end_date: None,
+            process_open_date: None,
             process_procedure_code: None,
             process_procedure_code_list_uri: None,
             process_urgency_code: None,
             process_urgency_code_list_uri: None,
             cfs_raw_xml: None,
             current_field: None,
-            contract_folder_status_handler: ContractFolderStatusHandler::new(keep_raw_xml),
+            contract_folder_status_handler: ContractFolderStatusHandler::new(
+                keep_raw_xml,
+                normalize_whitespace,
+                report_unknown_elements,
+            ),
+            contract_folder_statuses: Vec::new(),
+            multiple_cfs,
+            normalize_whitespace,
+            unknown_elements: BTreeMap::new(),
         }
     }
 
@@ -117,8 +207,13 @@ impl EntryBuilder {
         self.link = None;
         self.summary = None;
         self.updated = None;
+        self.categories.clear();
+        self.authors.clear();
+        self.inside_author = false;
+        self.is_deleted = false;
         self.contract_id = None;
         self.contracting_party_name = None;
+        self.contracting_party_id = None;
         self.contracting_party_website = None;
         self.contracting_party_type_code = None;
         self.contracting_party_type_code_list_uri = None;
@@ -141,11 +236,19 @@ impl EntryBuilder {
         self.project_cpv_code_list_uri = None;
         self.project_country_code = None;
         self.project_country_code_list_uri = None;
+        self.project_duration_measure = None;
+        self.project_duration_unit = None;
+        self.project_country_subentity = None;
+        self.project_country_subentity_code = None;
+        self.project_country_subentity_code_list_uri = None;
         self.project_lots.clear();
         self.tender_results.clear();
         self.status = StatusCode::default();
         self.terms_funding_program = TermsFundingProgram::default();
+        self.awarding_criteria.clear();
+        self.process_start_date = None;
         self.process_end_date = None;
+        self.process_open_date = None;
         self.process_procedure_code = None;
         self.process_procedure_code_list_uri = None;
         self.process_urgency_code = None;
@@ -153,22 +256,27 @@ impl EntryBuilder {
         self.cfs_raw_xml = None;
         self.current_field = None;
         self.contract_folder_status_handler.reset();
+        self.contract_folder_statuses.clear();
     }
 
     fn set_field_text(&mut self, text: String) {
+        let text = if self.normalize_whitespace {
+            normalize_whitespace(&text)
+        } else {
+            text
+        };
         if let Some(ref field) = self.current_field {
             match field {
-                EntryField::Id => {
-                    let cleaned = text
-                        .rsplit('/')
-                        .find(|segment| !segment.is_empty())
-                        .unwrap_or(&text)
-                        .to_string();
-                    self.id = Some(cleaned);
-                }
+                EntryField::Id => self.id = Some(clean_id(&text)),
                 EntryField::Title => self.title = Some(text),
                 EntryField::Summary => self.summary = Some(text),
                 EntryField::Updated => self.updated = Some(text),
+                // Appended, as entity references split the name into several text events
+                EntryField::AuthorName => {
+                    if let Some(name) = self.authors.last_mut() {
+                        name.push_str(&text);
+                    }
+                }
             }
         }
     }
@@ -177,6 +285,18 @@ impl EntryBuilder {
         self.link = Some(href);
     }
 
+    fn add_category(&mut self, term: String) {
+        self.categories.push(term);
+    }
+
+    /// Starts a tombstone row from an `<at:deleted-entry ref="..." when="...">` element:
+    /// `ref` becomes the id and `when` the updated timestamp.
+    fn start_deleted_entry(&mut self, e: &BytesStart) {
+        self.id = attribute_value(e, b"ref").map(|r| clean_id(&r));
+        self.updated = attribute_value(e, b"when");
+        self.is_deleted = true;
+    }
+
     fn set_current_field(&mut self, field: EntryField) {
         self.current_field = Some(field);
     }
@@ -198,10 +318,43 @@ impl EntryBuilder {
     }
 
     fn handle_contract_folder_status_end(&mut self, event: Event) -> AppResult<()> {
-        if let Some(p) = self.contract_folder_status_handler.handle_end(event)? {
+        if let Some(mut p) = self.contract_folder_status_handler.handle_end(event)? {
+            // Counted per subtree, whichever subtree `multiple_cfs` keeps
+            for (name, count) in std::mem::take(&mut p.unknown_elements) {
+                *self.unknown_elements.entry(name).or_default() += count;
+            }
+            self.contract_folder_statuses.push(p);
+        }
+        Ok(())
+    }
+
+    /// Resolves the collected `<ContractFolderStatus>` subtrees into the entry fields.
+    ///
+    /// Entries normally carry one subtree. When a feed repeats it (e.g. for modifications),
+    /// [`MultipleCfsStrategy::Latest`] keeps the last one in document order and
+    /// [`MultipleCfsStrategy::Merge`] folds them in order with [`ParsedContractFolderStatus::merge`].
+    fn apply_contract_folder_statuses(&mut self) {
+        let mut statuses = std::mem::take(&mut self.contract_folder_statuses);
+        if statuses.len() > 1 {
+            tracing::debug!(
+                id = self.id.as_deref().unwrap_or_default(),
+                count = statuses.len(),
+                strategy = ?self.multiple_cfs,
+                "Entry has multiple ContractFolderStatus elements"
+            );
+        }
+        let resolved = match self.multiple_cfs {
+            MultipleCfsStrategy::Latest => statuses.pop(),
+            MultipleCfsStrategy::Merge => statuses.into_iter().reduce(|mut merged, next| {
+                merged.merge(next);
+                merged
+            }),
+        };
+        if let Some(p) = resolved {
             self.status = p.status;
             self.contract_id = p.contract_id;
             self.contracting_party_name = p.contracting_party_name;
+            self.contracting_party_id = p.contracting_party_id;
             self.contracting_party_website = p.contracting_party_website;
             self.contracting_party_type_code = p.contracting_party_type_code;
             self.contracting_party_type_code_list_uri = p.contracting_party_type_code_list_uri;
@@ -226,20 +379,29 @@ impl EntryBuilder {
             self.project_cpv_code_list_uri = p.project_cpv_code_list_uri;
             self.project_country_code = p.project_country_code;
             self.project_country_code_list_uri = p.project_country_code_list_uri;
+            self.project_duration_measure = p.project_duration_measure;
+            self.project_duration_unit = p.project_duration_unit;
+            self.project_country_subentity = p.project_country_subentity;
+            self.project_country_subentity_code = p.project_country_subentity_code;
+            self.project_country_subentity_code_list_uri =
+                p.project_country_subentity_code_list_uri;
             self.project_lots = p.project_lots;
             self.tender_results = p.tender_results;
             self.terms_funding_program = p.terms_funding_program;
+            self.awarding_criteria = p.awarding_criteria;
+            self.process_start_date = p.process_start_date;
             self.process_end_date = p.process_end_date;
+            self.process_open_date = p.process_open_date;
             self.process_procedure_code = p.process_procedure_code;
             self.process_procedure_code_list_uri = p.process_procedure_code_list_uri;
             self.process_urgency_code = p.process_urgency_code;
             self.process_urgency_code_list_uri = p.process_urgency_code_list_uri;
             self.cfs_raw_xml = p.cfs_raw_xml;
         }
-        Ok(())
     }
 
     fn build(&mut self) -> Option<Entry> {
+        self.apply_contract_folder_statuses();
         if self.id.is_some() || self.title.is_some() {
             Some(Entry {
                 id: self.id.take(),
@@ -247,9 +409,16 @@ impl EntryBuilder {
                 link: self.link.take(),
                 summary: self.summary.take(),
                 updated: self.updated.take(),
+                categories: std::mem::take(&mut self.categories),
+                author: {
+                    self.authors.retain(|name| !name.is_empty());
+                    (!self.authors.is_empty()).then(|| self.authors.join("_"))
+                },
+                is_deleted: self.is_deleted,
                 status: std::mem::take(&mut self.status),
                 contract_id: self.contract_id.take(),
                 contracting_party_name: self.contracting_party_name.take(),
+                contracting_party_id: self.contracting_party_id.take(),
                 contracting_party_website: self.contracting_party_website.take(),
                 contracting_party_type_code: self.contracting_party_type_code.take(),
                 contracting_party_type_code_list_uri: self
@@ -278,15 +447,26 @@ impl EntryBuilder {
                 project_cpv_code_list_uri: self.project_cpv_code_list_uri.take(),
                 project_country_code: self.project_country_code.take(),
                 project_country_code_list_uri: self.project_country_code_list_uri.take(),
+                project_duration_measure: self.project_duration_measure.take(),
+                project_duration_unit: self.project_duration_unit.take(),
+                project_country_subentity: self.project_country_subentity.take(),
+                project_country_subentity_code: self.project_country_subentity_code.take(),
+                project_country_subentity_code_list_uri: self
+                    .project_country_subentity_code_list_uri
+                    .take(),
                 project_lots: std::mem::take(&mut self.project_lots),
                 tender_results: std::mem::take(&mut self.tender_results),
                 terms_funding_program: std::mem::take(&mut self.terms_funding_program),
+                awarding_criteria: std::mem::take(&mut self.awarding_criteria),
+                process_start_date: self.process_start_date.take(),
                 process_end_date: self.process_end_date.take(),
+                process_open_date: self.process_open_date.take(),
                 process_procedure_code: self.process_procedure_code.take(),
                 process_procedure_code_list_uri: self.process_procedure_code_list_uri.take(),
                 process_urgency_code: self.process_urgency_code.take(),
                 process_urgency_code_list_uri: self.process_urgency_code_list_uri.take(),
                 cfs_raw_xml: self.cfs_raw_xml.take(),
+                feed_metadata: FeedMetadata::default(),
             })
         } else {
             None
@@ -294,8 +474,85 @@ impl EntryBuilder {
     }
 }
 
+/// Entries parsed from one XML/Atom document.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ParsedFeed {
+    /// Entries in document order
+    pub entries: Vec<Entry>,
+    /// Malformed entries dropped in lenient mode (always 0 in strict mode)
+    pub skipped_entries: usize,
+    /// Occurrences of each `<ContractFolderStatus>` element local name the parser does not
+    /// map, across all entries (empty unless `report_unknown_elements`)
+    pub unknown_elements: BTreeMap<String, u64>,
+    /// Feed-level `<id>`, `<title>`, and `<updated>`, also copied into every entry
+    pub metadata: FeedMetadata,
+}
+
+/// Options controlling how [`parse_xml_bytes`] reads a document.
+///
+/// The defaults match those of [`crate::config::ResolvedConfig`]: strict parsing, whitespace
+/// normalization, the latest `<ContractFolderStatus>` per entry, no raw XML, and no unknown
+/// element report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Whether to keep the raw ContractFolderStatus XML in [`Entry::cfs_raw_xml`]
+    pub keep_raw_xml: bool,
+    /// How an entry with more than one `<ContractFolderStatus>` is resolved
+    pub multiple_cfs: MultipleCfsStrategy,
+    /// Whether an error inside an `<entry>` drops only that entry
+    pub lenient: bool,
+    /// Whether runs of whitespace in the extracted text fields are collapsed
+    pub normalize_whitespace: bool,
+    /// Whether unmapped ContractFolderStatus elements are counted
+    pub report_unknown_elements: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            keep_raw_xml: false,
+            multiple_cfs: MultipleCfsStrategy::default(),
+            lenient: false,
+            normalize_whitespace: true,
+            report_unknown_elements: false,
+        }
+    }
+}
+
+impl From<&crate::config::ResolvedConfig> for ParseOptions {
+    fn from(config: &crate::config::ResolvedConfig) -> Self {
+        Self {
+            keep_raw_xml: config.keep_cfs_raw_xml,
+            multiple_cfs: config.multiple_cfs,
+            lenient: config.lenient,
+            normalize_whitespace: config.normalize_whitespace,
+            report_unknown_elements: config.report_unknown_elements,
+        }
+    }
+}
+
 /// Parses XML content provided as bytes.
-pub fn parse_xml_bytes(content: &[u8], keep_raw_xml: bool) -> AppResult<Vec<Entry>> {
+///
+/// Elements are matched on their namespace-stripped local name, so Atom and
+/// ContractFolderStatus elements are recognised with any prefix or none.
+/// `options.multiple_cfs` decides how an entry with more than one `<ContractFolderStatus>`
+/// is resolved.
+///
+/// By default the first XML error fails the whole document. With `options.lenient`, an error
+/// inside an `<entry>` drops only that entry (counted in [`ParsedFeed::skipped_entries`]) and
+/// parsing resumes at the next `<entry>`; errors between entries are logged and skipped.
+///
+/// With `options.normalize_whitespace`, runs of ASCII whitespace in the extracted text fields
+/// are collapsed to one space and trimmed (see [`crate::utils::normalize_whitespace`]); the
+/// raw ContractFolderStatus XML is always kept verbatim.
+///
+/// With `options.report_unknown_elements`, elements inside `<ContractFolderStatus>` whose
+/// local name is not mapped to any field are counted in [`ParsedFeed::unknown_elements`], to
+/// spot new elements in the feed schema.
+///
+/// The feed's own `<id>`, `<title>`, and `<updated>`, read before the first entry, end up
+/// in [`ParsedFeed::metadata`] and in [`Entry::feed_metadata`].
+pub fn parse_xml_bytes(content: &[u8], options: ParseOptions) -> AppResult<ParsedFeed> {
     let cursor = Cursor::new(content);
     let mut reader = Reader::from_reader(cursor);
     reader.config_mut().trim_text(true);
@@ -304,127 +561,376 @@ pub fn parse_xml_bytes(content: &[u8], keep_raw_xml: bool) -> AppResult<Vec<Entr
     let estimated_capacity = (content.len() / 1024).max(100);
     let mut buf = Vec::with_capacity(8192);
     let mut result = Vec::with_capacity(estimated_capacity);
+    let mut skipped_entries = 0;
+    let mut last_error_position = None;
 
     let mut inside_entry = false;
-    let mut builder = EntryBuilder::new(keep_raw_xml);
+    let mut header = FeedHeader::new(options.normalize_whitespace);
+    let mut builder = EntryBuilder::new(
+        options.keep_raw_xml,
+        options.multiple_cfs,
+        options.normalize_whitespace,
+        options.report_unknown_elements,
+    );
 
     loop {
-        match reader.read_event_into(&mut buf)? {
-            Event::Start(e) => {
-                if inside_entry && e.name().as_ref().ends_with(b":ContractFolderStatus") {
-                    builder.start_contract_folder_status(Event::Start(e.into_owned()))?;
-                    continue;
-                }
+        let outcome = match reader.read_event_into(&mut buf) {
+            Ok(Event::Eof) => break,
+            Ok(event) => handle_event(
+                event,
+                &mut inside_entry,
+                &mut header,
+                &mut builder,
+                &mut result,
+            ),
+            Err(e) => Err(e.into()),
+        };
+        if let Err(e) = outcome {
+            let position = reader.buffer_position();
+            // A reader that cannot move past the error would loop forever.
+            if !options.lenient || last_error_position == Some(position) {
+                return Err(e);
+            }
+            last_error_position = Some(position);
+            if inside_entry {
+                skipped_entries += 1;
+                tracing::warn!(
+                    id = builder.id.as_deref().unwrap_or_default(),
+                    position,
+                    error = %e,
+                    "Skipping malformed entry"
+                );
+                inside_entry = false;
+                builder.reset();
+            } else {
+                tracing::warn!(position, error = %e, "Skipping malformed XML outside an entry");
+            }
+        }
+        buf.clear();
+    }
 
-                if builder.is_inside_contract_folder_status() {
-                    builder.handle_contract_folder_status_event(Event::Start(e.into_owned()))?;
-                    continue;
-                }
+    for entry in &mut result {
+        entry.feed_metadata = header.metadata.clone();
+    }
+    Ok(ParsedFeed {
+        entries: result,
+        skipped_entries,
+        unknown_elements: builder.unknown_elements,
+        metadata: header.metadata,
+    })
+}
 
-                match e.name().as_ref() {
-                    b"entry" => {
-                        inside_entry = true;
-                        builder.reset();
-                    }
-                    b"id" if inside_entry => {
-                        builder.set_current_field(EntryField::Id);
-                    }
-                    b"title" if inside_entry => {
-                        builder.set_current_field(EntryField::Title);
+/// Like [`parse_xml_bytes`], but reuses earlier results from a content-addressed cache.
+///
+/// Without `cache_dir` this is exactly [`parse_xml_bytes`]. With it, the document is looked
+/// up as `{cache_dir}/{hash}.bin`, where `hash` is the hex SHA-256 of the XML bytes together
+/// with the crate version, [`CACHE_FORMAT_VERSION`], and every field of `options`, so a cached feed is only reused for the same
+/// content parsed the same way by the same release. A hit is deserialized with `bincode`
+/// without parsing; on a miss the document is parsed and the feed is written to the cache.
+/// Unreadable cache files count as misses and failed writes are logged, so the cache never
+/// fails a run.
+pub fn parse_xml_bytes_cached(
+    content: &[u8],
+    options: ParseOptions,
+    cache_dir: Option<&Path>,
+) -> AppResult<ParsedFeed> {
+    let Some(cache_dir) = cache_dir else {
+        return parse_xml_bytes(content, options);
+    };
+    let cache_path = cache_path(cache_dir, content, options);
+    if let Ok(bytes) = fs::read(&cache_path) {
+        match bincode::deserialize(&bytes) {
+            Ok(feed) => return Ok(feed),
+            Err(e) => {
+                tracing::debug!(path = %cache_path.display(), error = %e, "Ignoring unreadable cache file");
+            }
+        }
+    }
+
+    let feed = parse_xml_bytes(content, options)?;
+    if let Err(e) = write_cache_file(&cache_path, &feed) {
+        tracing::warn!(path = %cache_path.display(), error = %e, "Failed to write parse cache file");
+    }
+    Ok(feed)
+}
+
+/// Parses every XML/Atom file of a single period directory into memory.
+///
+/// Files are found with the same rules as the pipeline (`.xml`/`.atom`, optionally
+/// gzip-compressed, in `dir` and its subdirectories, sorted by file name) and parsed with the
+/// default options: strict parsing, whitespace normalization, the latest
+/// `<ContractFolderStatus>` per entry, and no raw XML. Entries are returned in file order.
+/// Nothing is written to disk, so this suits embedding the parser in another program.
+///
+/// # Errors
+///
+/// Returns `IoError` if `dir` is not a directory or a file cannot be read, and `ParseError`
+/// if a file cannot be decompressed or parsed.
+pub fn parse_period_entries(dir: &Path) -> AppResult<Vec<Entry>> {
+    if !dir.is_dir() {
+        return Err(AppError::IoError(format!(
+            "Period directory {dir:?} does not exist or is not a directory"
+        )));
+    }
+    let mut entries = Vec::new();
+    for path in super::file_finder::collect_xmls(dir) {
+        let bytes = fs::read(&path)
+            .map_err(|e| AppError::IoError(format!("Failed to read XML file {path:?}: {e}")))?;
+        let content = gunzip_if_compressed(&path, bytes)?;
+        let feed = parse_xml_bytes(&content, ParseOptions::default())?;
+        entries.extend(feed.entries);
+    }
+    Ok(entries)
+}
+
+/// First two bytes of every gzip stream.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Decompresses `bytes` when the file is gzip-compressed (`.gz` extension or gzip magic
+/// bytes); plain XML is returned unchanged.
+pub(super) fn gunzip_if_compressed(path: &Path, bytes: Vec<u8>) -> AppResult<Vec<u8>> {
+    let has_gz_extension = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("gz"));
+    if !has_gz_extension && !bytes.starts_with(&GZIP_MAGIC) {
+        return Ok(bytes);
+    }
+    let mut decoded = Vec::with_capacity(bytes.len() * 4);
+    flate2::read::MultiGzDecoder::new(bytes.as_slice())
+        .read_to_end(&mut decoded)
+        .map_err(|e| AppError::ParseError(format!("Failed to decompress {path:?}: {e}")))?;
+    Ok(decoded)
+}
+
+/// Version of the cached [`ParsedFeed`] encoding, hashed into every cache file name. Bump it
+/// whenever `ParsedFeed` or the models it holds change, so bincode never decodes a cache file
+/// written with another layout.
+const CACHE_FORMAT_VERSION: u32 = 7;
+
+/// Returns the cache file of `content` parsed with `options`.
+///
+/// The key includes the `Debug` form of `options`, so every option, including ones added
+/// later, separates cache files.
+fn cache_path(cache_dir: &Path, content: &[u8], options: ParseOptions) -> PathBuf {
+    let mut hasher = Sha256::new();
+    hasher.update(
+        format!(
+            "sppd-cli {} {CACHE_FORMAT_VERSION} {options:?}\n",
+            env!("CARGO_PKG_VERSION")
+        )
+        .as_bytes(),
+    );
+    hasher.update(content);
+    let hash: String = hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    cache_dir.join(format!("{hash}.bin"))
+}
+
+/// Writes `feed` to `path` through a temporary file, so parallel parsers writing the same
+/// entry never leave a partial file behind.
+fn write_cache_file(path: &Path, feed: &ParsedFeed) -> AppResult<()> {
+    let bytes = bincode::serialize(feed)
+        .map_err(|e| AppError::ParseError(format!("Failed to serialize parsed feed: {e}")))?;
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let mut file = tempfile::NamedTempFile::new_in(dir)?;
+    file.write_all(&bytes)?;
+    file.persist(path)
+        .map_err(|e| AppError::IoError(format!("Failed to write {}: {e}", path.display())))?;
+    Ok(())
+}
+
+/// Keeps the last non-empty path segment of an Atom id (`.../licitacion/123` -> `123`).
+fn clean_id(text: &str) -> String {
+    text.rsplit('/')
+        .find(|segment| !segment.is_empty())
+        .unwrap_or(text)
+        .to_string()
+}
+
+/// Returns the value of the attribute named `key` (without prefix handling).
+fn attribute_value(e: &BytesStart, key: &[u8]) -> Option<String> {
+    e.attributes()
+        .filter_map(|a| a.ok())
+        .find(|a| a.key.as_ref() == key)
+        .map(|a| String::from_utf8_lossy(&a.value).into_owned())
+}
+
+/// Applies one reader event to the entry being built, pushing finished entries to `result`.
+///
+/// Atom tombstones (`<at:deleted-entry>`, RFC 6721) sit next to the entries and are built
+/// into rows of their own with `is_deleted` set. Until the first of either, the feed-level
+/// `<id>`, `<title>`, and `<updated>` go to `header`.
+fn handle_event(
+    event: Event,
+    inside_entry: &mut bool,
+    header: &mut FeedHeader,
+    builder: &mut EntryBuilder,
+    result: &mut Vec<Entry>,
+) -> AppResult<()> {
+    match event {
+        Event::Start(e) => {
+            if *inside_entry && e.local_name().as_ref() == b"ContractFolderStatus" {
+                builder.start_contract_folder_status(Event::Start(e.into_owned()))?;
+                return Ok(());
+            }
+
+            if builder.is_inside_contract_folder_status() {
+                builder.handle_contract_folder_status_event(Event::Start(e.into_owned()))?;
+                return Ok(());
+            }
+
+            match e.local_name().as_ref() {
+                b"entry" => {
+                    *inside_entry = true;
+                    header.inside = false;
+                    builder.reset();
+                }
+                b"deleted-entry" if !*inside_entry => {
+                    *inside_entry = true;
+                    header.inside = false;
+                    builder.reset();
+                    builder.start_deleted_entry(&e);
+                }
+                b"category" if *inside_entry => {
+                    if let Some(term) = attribute_value(&e, b"term") {
+                        builder.add_category(term);
                     }
-                    b"summary" if inside_entry => {
-                        builder.set_current_field(EntryField::Summary);
+                }
+                b"id" if header.inside => header.current_field = Some(EntryField::Id),
+                b"title" if header.inside => header.current_field = Some(EntryField::Title),
+                b"updated" if header.inside => header.current_field = Some(EntryField::Updated),
+                b"id" if *inside_entry => {
+                    builder.set_current_field(EntryField::Id);
+                }
+                b"title" if *inside_entry => {
+                    builder.set_current_field(EntryField::Title);
+                }
+                b"summary" if *inside_entry => {
+                    builder.set_current_field(EntryField::Summary);
+                }
+                b"updated" if *inside_entry => {
+                    builder.set_current_field(EntryField::Updated);
+                }
+                b"author" if *inside_entry => {
+                    builder.inside_author = true;
+                }
+                // Party names inside <ContractFolderStatus> never get here: that subtree
+                // is routed to its own handler above.
+                b"name" if *inside_entry && builder.inside_author => {
+                    builder.authors.push(String::new());
+                    builder.set_current_field(EntryField::AuthorName);
+                }
+                b"link" if *inside_entry => {
+                    if let Some(href) = attribute_value(&e, b"href") {
+                        builder.set_link(href);
                     }
-                    b"updated" if inside_entry => {
-                        builder.set_current_field(EntryField::Updated);
+                }
+                _ => {}
+            }
+        }
+        Event::Empty(e) if !*inside_entry && e.local_name().as_ref() == b"deleted-entry" => {
+            header.inside = false;
+            builder.reset();
+            builder.start_deleted_entry(&e);
+            if let Some(entry) = builder.build() {
+                result.push(entry);
+            }
+            builder.reset();
+        }
+        Event::Empty(e) if *inside_entry => {
+            if builder.is_inside_contract_folder_status() {
+                builder.handle_contract_folder_status_event(Event::Empty(e.into_owned()))?;
+            } else {
+                match e.local_name().as_ref() {
+                    b"link" => {
+                        if let Some(href) = attribute_value(&e, b"href") {
+                            builder.set_link(href);
+                        }
                     }
-                    b"link" if inside_entry => {
-                        if let Some(href) = e
-                            .attributes()
-                            .filter_map(|a| a.ok())
-                            .find(|a| a.key.as_ref() == b"href")
-                        {
-                            let href_str = String::from_utf8_lossy(&href.value);
-                            builder.set_link(href_str.into_owned());
+                    b"category" => {
+                        if let Some(term) = attribute_value(&e, b"term") {
+                            builder.add_category(term);
                         }
                     }
                     _ => {}
                 }
             }
-            Event::Empty(e) if inside_entry => {
-                if builder.is_inside_contract_folder_status() {
-                    builder.handle_contract_folder_status_event(Event::Empty(e.into_owned()))?;
-                } else if e.name().as_ref() == b"link" {
-                    if let Some(href) = e
-                        .attributes()
-                        .filter_map(|a| a.ok())
-                        .find(|a| a.key.as_ref() == b"href")
-                    {
-                        let href_str = String::from_utf8_lossy(&href.value);
-                        builder.set_link(href_str.into_owned());
-                    }
+        }
+        Event::CData(e) if *inside_entry && builder.is_inside_contract_folder_status() => {
+            builder.handle_contract_folder_status_event(Event::CData(e.into_owned()))?;
+        }
+        Event::Comment(e) if *inside_entry && builder.is_inside_contract_folder_status() => {
+            builder.handle_contract_folder_status_event(Event::Comment(e.into_owned()))?;
+        }
+        Event::PI(e) if *inside_entry && builder.is_inside_contract_folder_status() => {
+            builder.handle_contract_folder_status_event(Event::PI(e.into_owned()))?;
+        }
+        Event::End(e) => {
+            if builder.is_inside_contract_folder_status() {
+                if e.local_name().as_ref() == b"ContractFolderStatus" {
+                    builder.handle_contract_folder_status_end(Event::End(e.into_owned()))?;
+                } else {
+                    builder.handle_contract_folder_status_event(Event::End(e.into_owned()))?;
                 }
+                return Ok(());
             }
-            Event::CData(e) if inside_entry && builder.is_inside_contract_folder_status() => {
-                builder.handle_contract_folder_status_event(Event::CData(e.into_owned()))?;
-            }
-            Event::Comment(e) if inside_entry && builder.is_inside_contract_folder_status() => {
-                builder.handle_contract_folder_status_event(Event::Comment(e.into_owned()))?;
-            }
-            Event::PI(e) if inside_entry && builder.is_inside_contract_folder_status() => {
-                builder.handle_contract_folder_status_event(Event::PI(e.into_owned()))?;
-            }
-            Event::End(e) => {
-                if builder.is_inside_contract_folder_status() {
-                    if e.name().as_ref().ends_with(b":ContractFolderStatus") {
-                        builder.handle_contract_folder_status_end(Event::End(e.into_owned()))?;
-                    } else {
-                        builder.handle_contract_folder_status_event(Event::End(e.into_owned()))?;
-                    }
-                    continue;
-                }
 
-                match e.name().as_ref() {
-                    b"entry" => {
-                        inside_entry = false;
-                        if let Some(entry) = builder.build() {
-                            result.push(entry);
-                        }
-                        builder.reset();
+            match e.local_name().as_ref() {
+                b"entry" | b"deleted-entry" => {
+                    *inside_entry = false;
+                    if let Some(entry) = builder.build() {
+                        result.push(entry);
                     }
-                    b"id" | b"title" | b"summary" | b"updated" => {
-                        builder.clear_current_field();
-                    }
-                    _ => {}
+                    builder.reset();
                 }
-            }
-            Event::Text(e) if inside_entry => {
-                if builder.is_inside_contract_folder_status() {
-                    builder.handle_contract_folder_status_event(Event::Text(e.into_owned()))?;
-                } else if builder.current_field.is_some() {
-                    let txt = e
-                        .decode()
-                        .map_err(|e| {
-                            AppError::ParseError(format!("Failed to decode XML text: {e}"))
-                        })?
-                        .into_owned();
-                    builder.set_field_text(txt);
+                b"author" => {
+                    builder.inside_author = false;
+                }
+                b"id" | b"title" | b"summary" | b"updated" | b"name" => {
+                    header.current_field = None;
+                    builder.clear_current_field();
                 }
+                _ => {}
             }
-            Event::Eof => break,
-            _ => {}
         }
-        buf.clear();
+        Event::Text(e) if header.current_field.is_some() => {
+            let txt = e
+                .decode()
+                .map_err(|e| AppError::ParseError(format!("Failed to decode XML text: {e}")))?
+                .into_owned();
+            header.set_field_text(txt);
+        }
+        Event::Text(e) if *inside_entry => {
+            if builder.is_inside_contract_folder_status() {
+                builder.handle_contract_folder_status_event(Event::Text(e.into_owned()))?;
+            } else if builder.current_field.is_some() {
+                let txt = e
+                    .decode()
+                    .map_err(|e| AppError::ParseError(format!("Failed to decode XML text: {e}")))?
+                    .into_owned();
+                builder.set_field_text(txt);
+            }
+        }
+        _ => {}
     }
-
-    Ok(result)
+    Ok(())
 }
 
 /// Parses an XML file from disk and delegates to `parse_xml_bytes`.
 #[cfg(test)]
 pub(crate) fn parse_xml(path: &Path) -> AppResult<Vec<Entry>> {
     let content = fs::read(path)?;
-    parse_xml_bytes(&content, true)
+    parse_xml_bytes(
+        &content,
+        ParseOptions {
+            keep_raw_xml: true,
+            ..ParseOptions::default()
+        },
+    )
+    .map(|feed| feed.entries)
 }
 
 #[cfg(test)]
@@ -527,6 +1033,35 @@ mod tests {
         assert_eq!(result[0].link, None);
     }
 
+    #[test]
+    fn test_parse_period_entries_concatenates_files_in_order() {
+        let temp_dir = TempDir::new().unwrap();
+        let period_dir = temp_dir.path().join("202401");
+        create_test_xml_file(
+            &period_dir.join("a.atom"),
+            "<feed><entry><id>a1</id></entry><entry><id>a2</id></entry></feed>",
+        );
+        create_test_xml_file(&period_dir.join("notes.txt"), "not xml");
+        fs::create_dir_all(period_dir.join("sub")).unwrap();
+        let mut gz = flate2::write::GzEncoder::new(
+            fs::File::create(period_dir.join("sub/b.xml.gz")).unwrap(),
+            Default::default(),
+        );
+        gz.write_all(b"<feed><entry><id>b1</id></entry></feed>")
+            .unwrap();
+        gz.finish().unwrap();
+
+        let entries = parse_period_entries(&period_dir).unwrap();
+
+        let ids: Vec<_> = entries.iter().filter_map(|e| e.id.as_deref()).collect();
+        assert_eq!(ids, ["a1", "a2", "b1"]);
+        assert!(entries.iter().all(|e| e.cfs_raw_xml.is_none()));
+        assert!(matches!(
+            parse_period_entries(&temp_dir.path().join("missing")),
+            Err(AppError::IoError(_))
+        ));
+    }
+
     #[test]
     fn test_parse_xml_minimal_entry_title_only() {
         let temp_dir = TempDir::new().unwrap();
@@ -628,4 +1163,603 @@ mod tests {
         assert!(result[0].title.is_some());
         assert!(result[0].summary.is_some());
     }
+
+    const TWO_CFS_FEED: &str = r#"<feed xmlns="http://www.w3.org/2005/Atom">
+  <entry>
+    <id>https://example.com/entry/cfs-twice</id>
+    <cac-place-ext:ContractFolderStatus>
+      <cbc:ContractFolderID>C-1</cbc:ContractFolderID>
+      <cbc-place-ext:ContractFolderStatusCode>PUB</cbc-place-ext:ContractFolderStatusCode>
+      <cac:ProcurementProject><cbc:Name>Obra</cbc:Name></cac:ProcurementProject>
+    </cac-place-ext:ContractFolderStatus>
+    <cac-place-ext:ContractFolderStatus>
+      <cbc:ContractFolderID>C-1</cbc:ContractFolderID>
+      <cbc-place-ext:ContractFolderStatusCode>ADJ</cbc-place-ext:ContractFolderStatusCode>
+    </cac-place-ext:ContractFolderStatus>
+  </entry>
+</feed>"#;
+
+    #[test]
+    fn multiple_cfs_latest_keeps_last_subtree() {
+        let entries = parse_xml_bytes(
+            TWO_CFS_FEED.as_bytes(),
+            ParseOptions {
+                keep_raw_xml: true,
+                multiple_cfs: MultipleCfsStrategy::Latest,
+                ..ParseOptions::default()
+            },
+        )
+        .unwrap()
+        .entries;
+
+        assert_eq!(entries.len(), 1);
+        let entry = &entries[0];
+        assert_eq!(entry.id.as_deref(), Some("cfs-twice"));
+        assert_eq!(entry.contract_id.as_deref(), Some("C-1"));
+        assert_eq!(entry.status.code.as_deref(), Some("ADJ"));
+        assert_eq!(entry.project_name, None);
+        let raw = entry.cfs_raw_xml.as_deref().unwrap();
+        assert!(raw.contains("ADJ") && !raw.contains("PUB"));
+    }
+
+    #[test]
+    fn multiple_cfs_merge_overlays_later_subtrees() {
+        let entries = parse_xml_bytes(
+            TWO_CFS_FEED.as_bytes(),
+            ParseOptions {
+                keep_raw_xml: true,
+                multiple_cfs: MultipleCfsStrategy::Merge,
+                ..ParseOptions::default()
+            },
+        )
+        .unwrap()
+        .entries;
+
+        assert_eq!(entries.len(), 1);
+        let entry = &entries[0];
+        assert_eq!(entry.contract_id.as_deref(), Some("C-1"));
+        assert_eq!(entry.status.code.as_deref(), Some("ADJ"));
+        assert_eq!(entry.project_name.as_deref(), Some("Obra"));
+        let raw = entry.cfs_raw_xml.as_deref().unwrap();
+        assert!(raw.find("PUB").unwrap() < raw.find("ADJ").unwrap());
+    }
+
+    #[test]
+    fn matches_local_name_ignores_prefix_but_not_suffixes() {
+        use super::super::scope::matches_local_name;
+
+        assert!(matches_local_name(b"cbc:Name", b"Name"));
+        assert!(matches_local_name(b"Name", b"Name"));
+        assert!(!matches_local_name(b"cbc:PartyName", b"Name"));
+        assert!(!matches_local_name(b"PartyName", b"Name"));
+        assert!(!matches_local_name(b"x:cbc:Name", b"Name"));
+    }
+
+    #[test]
+    fn parses_mixed_prefix_feed() {
+        let xml = r#"<atom:feed xmlns:atom="http://www.w3.org/2005/Atom"
+    xmlns:cbc="urn:oasis:names:specification:ubl:schema:xsd:CommonBasicComponents-2"
+    xmlns:cac="urn:oasis:names:specification:ubl:schema:xsd:CommonAggregateComponents-2">
+  <atom:entry>
+    <atom:id>https://example.com/entry/prefixed</atom:id>
+    <atom:link href="https://example.com/prefixed"/>
+    <cac-place-ext:ContractFolderStatus>
+      <cbc:ContractFolderID>P-1</cbc:ContractFolderID>
+      <cac:ProcurementProject><cbc:Name>Prefixed</cbc:Name></cac:ProcurementProject>
+    </cac-place-ext:ContractFolderStatus>
+  </atom:entry>
+  <entry>
+    <id>https://example.com/entry/unprefixed</id>
+    <ContractFolderStatus xmlns="urn:dgpe:names:draft:codice-place-ext:schema:xsd:CommonAggregateComponents-2">
+      <ContractFolderID xmlns="urn:oasis:names:specification:ubl:schema:xsd:CommonBasicComponents-2">U-1</ContractFolderID>
+      <cac:ProcurementProject><Name>Unprefixed</Name></cac:ProcurementProject>
+    </ContractFolderStatus>
+  </entry>
+</atom:feed>"#;
+
+        let entries = parse_xml_bytes(xml.as_bytes(), ParseOptions::default())
+            .unwrap()
+            .entries;
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].id.as_deref(), Some("prefixed"));
+        assert_eq!(
+            entries[0].link.as_deref(),
+            Some("https://example.com/prefixed")
+        );
+        assert_eq!(entries[0].contract_id.as_deref(), Some("P-1"));
+        assert_eq!(entries[0].project_name.as_deref(), Some("Prefixed"));
+        assert_eq!(entries[1].id.as_deref(), Some("unprefixed"));
+        assert_eq!(entries[1].contract_id.as_deref(), Some("U-1"));
+        assert_eq!(entries[1].project_name.as_deref(), Some("Unprefixed"));
+    }
+
+    fn contracting_party_feed(identifications: &str) -> String {
+        format!(
+            r#"<feed xmlns:cbc="urn:cbc" xmlns:cac="urn:cac">
+  <entry>
+    <id>https://example.com/entry/party</id>
+    <cac-place-ext:ContractFolderStatus>
+      <cac-place-ext:LocatedContractingParty>
+        <cac:Party>
+          {identifications}
+          <cac:PartyName><cbc:Name>Ayuntamiento de Madrid</cbc:Name></cac:PartyName>
+        </cac:Party>
+      </cac-place-ext:LocatedContractingParty>
+      <cac:TenderResult>
+        <cac:WinningParty>
+          <cac:PartyIdentification><cbc:ID schemeName="NIF">B00000000</cbc:ID></cac:PartyIdentification>
+        </cac:WinningParty>
+      </cac:TenderResult>
+    </cac-place-ext:ContractFolderStatus>
+  </entry>
+</feed>"#
+        )
+    }
+
+    #[test]
+    fn parses_contracting_party_id() {
+        let xml = contracting_party_feed(
+            r#"<cac:PartyIdentification><cbc:ID schemeID="NIF">P2807900B</cbc:ID></cac:PartyIdentification>"#,
+        );
+
+        let entries = parse_xml_bytes(xml.as_bytes(), ParseOptions::default())
+            .unwrap()
+            .entries;
+
+        assert_eq!(
+            entries[0].contracting_party_id.as_deref(),
+            Some("P2807900B")
+        );
+        assert_eq!(
+            entries[0].contracting_party_name.as_deref(),
+            Some("Ayuntamiento de Madrid")
+        );
+    }
+
+    #[test]
+    fn joins_multiple_contracting_party_ids_skipping_empty_ones() {
+        let xml = contracting_party_feed(
+            r#"<cac:PartyIdentification><cbc:ID schemeID="DIR3"></cbc:ID></cac:PartyIdentification>
+          <cac:PartyIdentification><cbc:ID schemeID="DIR3">L01280796</cbc:ID></cac:PartyIdentification>
+          <cac:PartyIdentification><cbc:ID schemeID="NIF">P2807900B</cbc:ID></cac:PartyIdentification>"#,
+        );
+
+        let entries = parse_xml_bytes(xml.as_bytes(), ParseOptions::default())
+            .unwrap()
+            .entries;
+
+        assert_eq!(
+            entries[0].contracting_party_id.as_deref(),
+            Some("L01280796_P2807900B")
+        );
+    }
+
+    #[test]
+    fn parses_each_awarding_criterion_separately() {
+        let xml = r#"<feed xmlns:cbc="urn:cbc" xmlns:cac="urn:cac">
+  <entry>
+    <id>https://example.com/entry/criteria</id>
+    <cac-place-ext:ContractFolderStatus>
+      <cac:TenderingTerms>
+        <cac:AwardingTerms>
+          <cac:AwardingCriteria>
+            <cbc:AwardingCriteriaTypeCode>OBJ</cbc:AwardingCriteriaTypeCode>
+            <cbc:Description>Precio</cbc:Description>
+            <cbc:WeightNumeric>60</cbc:WeightNumeric>
+          </cac:AwardingCriteria>
+          <cac:AwardingCriteria>
+            <cbc:AwardingCriteriaTypeCode>SUBJ</cbc:AwardingCriteriaTypeCode>
+            <cbc:Description>Calidad técnica</cbc:Description>
+            <cbc:WeightNumeric>40</cbc:WeightNumeric>
+          </cac:AwardingCriteria>
+        </cac:AwardingTerms>
+      </cac:TenderingTerms>
+      <cac:TenderResult>
+        <cbc:Description>Adjudicado</cbc:Description>
+      </cac:TenderResult>
+    </cac-place-ext:ContractFolderStatus>
+  </entry>
+</feed>"#;
+
+        let entries = parse_xml_bytes(xml.as_bytes(), ParseOptions::default())
+            .unwrap()
+            .entries;
+
+        let criteria: Vec<_> = entries[0]
+            .awarding_criteria
+            .iter()
+            .map(|c| {
+                (
+                    c.type_code.as_deref(),
+                    c.description.as_deref(),
+                    c.weight.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            criteria,
+            [
+                (Some("OBJ"), Some("Precio"), Some("60")),
+                (Some("SUBJ"), Some("Calidad técnica"), Some("40")),
+            ]
+        );
+        assert_eq!(
+            entries[0].tender_results[0].result_description.as_deref(),
+            Some("Adjudicado")
+        );
+    }
+
+    #[test]
+    fn parses_feed_metadata_before_the_first_entry() {
+        let xml = r#"<feed xmlns="http://www.w3.org/2005/Atom">
+  <id>https://contrataciondelestado.es/sindicacion/licitacionesPerfilContratante</id>
+  <title>Licitaciones publicadas en la Plataforma</title>
+  <updated>2024-03-04T10:00:00+01:00</updated>
+  <link href="https://example.com/next.atom" rel="next"/>
+  <entry>
+    <id>https://example.com/entry/1</id>
+    <title>Obra</title>
+    <updated>2024-03-01T10:00:00+01:00</updated>
+  </entry>
+  <entry><id>https://example.com/entry/2</id></entry>
+</feed>"#;
+        let feed = parse_xml_bytes(xml.as_bytes(), ParseOptions::default()).unwrap();
+
+        let expected = FeedMetadata {
+            feed_id: Some(
+                "https://contrataciondelestado.es/sindicacion/licitacionesPerfilContratante"
+                    .to_string(),
+            ),
+            feed_title: Some("Licitaciones publicadas en la Plataforma".to_string()),
+            feed_updated: Some("2024-03-04T10:00:00+01:00".to_string()),
+        };
+        assert_eq!(feed.metadata, expected);
+        assert_eq!(feed.entries.len(), 2);
+        assert!(feed.entries.iter().all(|e| e.feed_metadata == expected));
+        assert_eq!(feed.entries[0].id.as_deref(), Some("1"));
+        assert_eq!(feed.entries[0].title.as_deref(), Some("Obra"));
+        assert_eq!(
+            feed.entries[0].updated.as_deref(),
+            Some("2024-03-01T10:00:00+01:00")
+        );
+
+        let headless = parse_xml_bytes(
+            b"<feed><entry><id>a</id></entry><title>late</title></feed>",
+            ParseOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(headless.metadata, FeedMetadata::default());
+    }
+
+    #[test]
+    fn parses_entry_author_name_apart_from_party_names() {
+        let xml = r#"<feed xmlns="http://www.w3.org/2005/Atom">
+  <author><name>Feed author</name></author>
+  <entry>
+    <id>https://example.com/entry/1</id>
+    <author>
+      <name>Ayuntamiento de Ejemplo</name>
+      <uri>https://example.com</uri>
+    </author>
+    <author><name>Diputación</name></author>
+    <cac-place-ext:ContractFolderStatus>
+      <cac-place-ext:LocatedContractingParty>
+        <cac:Party><cac:PartyName><cbc:Name>Órgano de contratación</cbc:Name></cac:PartyName></cac:Party>
+      </cac-place-ext:LocatedContractingParty>
+    </cac-place-ext:ContractFolderStatus>
+    <title>Obra</title>
+  </entry>
+  <entry><id>https://example.com/entry/2</id><name>Not an author</name></entry>
+</feed>"#;
+        let entries = parse_xml_bytes(xml.as_bytes(), ParseOptions::default())
+            .unwrap()
+            .entries;
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            entries[0].author.as_deref(),
+            Some("Ayuntamiento de Ejemplo_Diputación")
+        );
+        assert_eq!(
+            entries[0].contracting_party_name.as_deref(),
+            Some("Órgano de contratación")
+        );
+        assert_eq!(entries[0].title.as_deref(), Some("Obra"));
+        assert_eq!(entries[1].author, None);
+    }
+
+    #[test]
+    fn parses_categories_and_deleted_entry_tombstones() {
+        let xml = r#"<feed xmlns="http://www.w3.org/2005/Atom" xmlns:at="http://purl.org/atompub/tombstones/1.0">
+  <at:deleted-entry ref="https://contrataciondelestado.es/sindicacion/licitacionesPerfilContratante/111" when="2024-03-01T10:00:00+01:00"/>
+  <entry>
+    <id>https://contrataciondelestado.es/sindicacion/licitacionesPerfilContratante/222</id>
+    <category term="Servicios"/>
+    <category term="Anulada" scheme="urn:estado"></category>
+    <updated>2024-03-02T10:00:00+01:00</updated>
+  </entry>
+  <at:deleted-entry ref="https://contrataciondelestado.es/sindicacion/licitacionesPerfilContratante/333" when="2024-03-03T10:00:00+01:00">
+    <at:comment>Publicación retirada</at:comment>
+  </at:deleted-entry>
+</feed>"#;
+
+        let entries = parse_xml_bytes(xml.as_bytes(), ParseOptions::default())
+            .unwrap()
+            .entries;
+
+        let rows: Vec<_> = entries
+            .iter()
+            .map(|e| (e.id.as_deref(), e.updated.as_deref(), e.is_deleted))
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                (Some("111"), Some("2024-03-01T10:00:00+01:00"), true),
+                (Some("222"), Some("2024-03-02T10:00:00+01:00"), false),
+                (Some("333"), Some("2024-03-03T10:00:00+01:00"), true),
+            ]
+        );
+        assert!(entries[0].categories.is_empty());
+        assert_eq!(entries[1].categories, ["Servicios", "Anulada"]);
+    }
+
+    #[test]
+    fn keeps_folder_and_lot_realized_location_codes_apart() {
+        let xml = r#"<feed xmlns:cac="urn:cac" xmlns:cbc="urn:cbc">
+  <entry>
+    <id>https://example.com/1</id>
+    <cac-place-ext:ContractFolderStatus>
+      <cac:LocatedContractingParty><cac:Party><cac:PostalAddress>
+        <cbc:CountrySubentityCode listURI="urn:postal">ES616</cbc:CountrySubentityCode>
+      </cac:PostalAddress></cac:Party></cac:LocatedContractingParty>
+      <cac:ProcurementProject>
+        <cac:RealizedLocation>
+          <cbc:CountrySubentityCode listURI="urn:nuts">ES300</cbc:CountrySubentityCode>
+        </cac:RealizedLocation>
+      </cac:ProcurementProject>
+      <cac:ProcurementProjectLot>
+        <cbc:ID schemeName="ID_LOTE">1</cbc:ID>
+        <cac:ProcurementProject>
+          <cac:RealizedLocation>
+            <cbc:CountrySubentityCode listURI="urn:nuts">ES511</cbc:CountrySubentityCode>
+          </cac:RealizedLocation>
+        </cac:ProcurementProject>
+      </cac:ProcurementProjectLot>
+      <cac:ProcurementProjectLot>
+        <cbc:ID schemeName="ID_LOTE">2</cbc:ID>
+      </cac:ProcurementProjectLot>
+    </cac-place-ext:ContractFolderStatus>
+  </entry>
+</feed>"#;
+
+        let entries = parse_xml_bytes(xml.as_bytes(), ParseOptions::default())
+            .unwrap()
+            .entries;
+
+        let entry = &entries[0];
+        assert_eq!(
+            entry.project_country_subentity_code.as_deref(),
+            Some("ES300")
+        );
+        assert_eq!(
+            entry.project_country_subentity_code_list_uri.as_deref(),
+            Some("urn:nuts")
+        );
+        let lot_codes: Vec<_> = entry
+            .project_lots
+            .iter()
+            .map(|lot| {
+                (
+                    lot.country_subentity_code.as_deref(),
+                    lot.country_subentity_code_list_uri.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(lot_codes, [(Some("ES511"), Some("urn:nuts")), (None, None)]);
+    }
+
+    const ONE_BROKEN_ENTRY_FEED: &str = r#"<feed xmlns:cbc="urn:cbc">
+  <entry><id>https://example.com/a</id></entry>
+  <entry>
+    <id>https://example.com/b</id>
+    <cac-place-ext:ContractFolderStatus>
+      <cbc:ContractFolderID>B-1</cbc:ProjectName>
+    </cac-place-ext:ContractFolderStatus>
+  </entry>
+  <entry><id>https://example.com/c</id></entry>
+</feed>"#;
+
+    #[test]
+    fn strict_parsing_fails_on_malformed_entry() {
+        let result = parse_xml_bytes(ONE_BROKEN_ENTRY_FEED.as_bytes(), ParseOptions::default());
+        assert!(matches!(result, Err(AppError::ParseError(_))));
+    }
+
+    #[test]
+    fn lenient_parsing_skips_malformed_entry() {
+        let feed = parse_xml_bytes(
+            ONE_BROKEN_ENTRY_FEED.as_bytes(),
+            ParseOptions {
+                lenient: true,
+                ..ParseOptions::default()
+            },
+        )
+        .unwrap();
+
+        let ids: Vec<_> = feed.entries.iter().map(|e| e.id.as_deref()).collect();
+        assert_eq!(ids, [Some("a"), Some("c")]);
+        assert_eq!(feed.skipped_entries, 1);
+    }
+
+    const MULTI_LINE_FEED: &str = "<feed><entry>
+    <id>ws</id>
+    <title>Suministro de
+\t\tmaterial\tde oficina</title>
+    <cac-place-ext:ContractFolderStatus>
+      <cac:ProcurementProject>
+        <cbc:Name>
+          Obras de
+          reforma\u{a0}integral
+        </cbc:Name>
+      </cac:ProcurementProject>
+    </cac-place-ext:ContractFolderStatus>
+  </entry></feed>";
+
+    #[test]
+    fn whitespace_is_normalized_in_text_fields() {
+        let feed = parse_xml_bytes(
+            MULTI_LINE_FEED.as_bytes(),
+            ParseOptions {
+                keep_raw_xml: true,
+                ..ParseOptions::default()
+            },
+        )
+        .unwrap();
+
+        let entry = &feed.entries[0];
+        assert_eq!(
+            entry.title.as_deref(),
+            Some("Suministro de material de oficina")
+        );
+        assert_eq!(
+            entry.project_name.as_deref(),
+            Some("Obras de reforma\u{a0}integral")
+        );
+        // The raw XML is kept verbatim.
+        assert!(entry.cfs_raw_xml.as_deref().unwrap().contains("Obras de\n"));
+
+        let feed = parse_xml_bytes(
+            MULTI_LINE_FEED.as_bytes(),
+            ParseOptions {
+                normalize_whitespace: false,
+                ..ParseOptions::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            feed.entries[0].title.as_deref(),
+            Some("Suministro de\n\t\tmaterial\tde oficina")
+        );
+    }
+
+    #[test]
+    fn lenient_parsing_stops_at_truncated_document() {
+        let xml = "<feed><entry><id>a</id></entry><entry><id>b</id><title";
+        let feed = parse_xml_bytes(
+            xml.as_bytes(),
+            ParseOptions {
+                lenient: true,
+                ..ParseOptions::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(feed.entries.len(), 1);
+        assert_eq!(feed.skipped_entries, 1);
+    }
+
+    #[test]
+    fn cached_parse_reuses_the_cache_file_without_parsing() {
+        let tmp = TempDir::new().unwrap();
+        // Parsing this document would fail, so only a cache hit can succeed.
+        let content = b"<feed><entry><id>broken</id></feed";
+        let cache_path = cache_path(tmp.path(), content, ParseOptions::default());
+        let cached = ParsedFeed {
+            entries: vec![Entry {
+                id: Some("from-cache".to_string()),
+                ..Entry::default()
+            }],
+            skipped_entries: 2,
+            unknown_elements: BTreeMap::new(),
+            metadata: FeedMetadata::default(),
+        };
+        write_cache_file(&cache_path, &cached).unwrap();
+
+        let feed =
+            parse_xml_bytes_cached(content, ParseOptions::default(), Some(tmp.path())).unwrap();
+
+        assert_eq!(feed.entries.len(), 1);
+        assert_eq!(feed.entries[0].id.as_deref(), Some("from-cache"));
+        assert_eq!(feed.skipped_entries, 2);
+    }
+
+    #[test]
+    fn cached_parse_writes_a_cache_file_on_miss() {
+        let tmp = TempDir::new().unwrap();
+        let content = ONE_BROKEN_ENTRY_FEED.as_bytes();
+
+        let feed = parse_xml_bytes_cached(
+            content,
+            ParseOptions {
+                lenient: true,
+                ..ParseOptions::default()
+            },
+            Some(tmp.path()),
+        )
+        .unwrap();
+
+        let files: Vec<_> = fs::read_dir(tmp.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(
+            files,
+            [cache_path(
+                tmp.path(),
+                content,
+                ParseOptions {
+                    lenient: true,
+                    ..ParseOptions::default()
+                }
+            )]
+        );
+        let cached: ParsedFeed = bincode::deserialize(&fs::read(&files[0]).unwrap()).unwrap();
+        assert_eq!(cached.entries.len(), feed.entries.len());
+        assert_eq!(cached.entries[1].id.as_deref(), Some("c"));
+        assert_eq!(cached.skipped_entries, 1);
+
+        // Other parse options do not share the cache file.
+        assert_ne!(
+            cache_path(tmp.path(), content, ParseOptions::default()),
+            files[0]
+        );
+    }
+
+    #[test]
+    fn cache_path_depends_on_every_parse_option() {
+        let dir = Path::new("cache");
+        let default = ParseOptions::default();
+        let variants = [
+            ParseOptions {
+                keep_raw_xml: true,
+                ..default
+            },
+            ParseOptions {
+                multiple_cfs: MultipleCfsStrategy::Merge,
+                ..default
+            },
+            ParseOptions {
+                lenient: true,
+                ..default
+            },
+            ParseOptions {
+                normalize_whitespace: false,
+                ..default
+            },
+            ParseOptions {
+                report_unknown_elements: true,
+                ..default
+            },
+        ];
+
+        let mut paths: Vec<PathBuf> = variants
+            .iter()
+            .map(|options| cache_path(dir, b"<feed/>", *options))
+            .collect();
+        paths.push(cache_path(dir, b"<feed/>", default));
+        paths.sort();
+        paths.dedup();
+        assert_eq!(paths.len(), variants.len() + 1);
+    }
 }
@@ -0,0 +1,1207 @@
+use crate::config::{OutputFormat, ResolvedConfig};
+use crate::downloader::http::build_client;
+use crate::downloader::{
+    copy_local_files, download_files, download_files_with_limits, exclude_periods, fetch_links_for,
+    filter_periods_by_range, local_source_path, local_zip_links, needs_download, remote_size,
+    DownloadLimits,
+};
+use crate::errors::{AppError, AppResult};
+use crate::extractor::{extract_all_zips, is_extracted};
+use crate::manifest::{RunManifest, MANIFEST_FILE_NAME};
+use crate::models::{
+    DownloadStats, ExtractionStats, ParseStats, PeriodPlan, ProcurementType, RunPlan, RunSummary,
+};
+use crate::parser::{
+    apply_retention, cleanup_files, find_xmls, parse_xmls, parse_xmls_with_pool, parser_pool,
+    period_output_path,
+};
+use crate::state::{resume_point, ResumePoint, RunState};
+use crate::transformer;
+use crate::utils::{mb_from_bytes, round_two_decimals};
+use futures::stream::{self, StreamExt};
+use futures::FutureExt;
+use std::collections::BTreeMap;
+use std::future::{self, Future};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Parameters of one pipeline run, for [`run`].
+///
+/// Everything not covered by a field (batch size, retries, `keep_going`, ...) comes from
+/// `config`. `output_format`, `data_dir`, and `output_dir` override the matching settings of
+/// `config` when set, like the `--format`, `--data-dir`, and `--output-dir` flags.
+///
+/// ```
+/// use sppd_cli::models::ProcurementType;
+/// use sppd_cli::pipeline::PipelineOptions;
+///
+/// let options = PipelineOptions::new(ProcurementType::PublicTenders);
+/// assert!(options.cleanup);
+/// assert_eq!(options.start_period, None);
+/// assert_eq!(options.config.batch_size, 500);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PipelineOptions {
+    pub procurement_type: ProcurementType,
+    /// First period to process (`YYYY` or `YYYYMM`); `None` starts at the first available one
+    pub start_period: Option<String>,
+    /// Last period to process; `None` ends at the last available one
+    pub end_period: Option<String>,
+    /// Whether to delete the downloaded ZIPs and extracted files after parsing
+    pub cleanup: bool,
+    /// Output file format, overriding `config.output_format`
+    pub output_format: Option<OutputFormat>,
+    /// Root of the data layout, see [`ResolvedConfig::set_data_dir`]
+    pub data_dir: Option<PathBuf>,
+    /// Directory for Parquet output, see [`ResolvedConfig::set_output_dir`]; a relative
+    /// path resolves against `data_dir` when both are set
+    pub output_dir: Option<PathBuf>,
+    /// Remaining pipeline settings
+    pub config: ResolvedConfig,
+}
+
+impl PipelineOptions {
+    /// Options processing every available period of `procurement_type` with the default
+    /// configuration and cleanup enabled.
+    pub fn new(procurement_type: ProcurementType) -> Self {
+        Self {
+            procurement_type,
+            start_period: None,
+            end_period: None,
+            cleanup: true,
+            output_format: None,
+            data_dir: None,
+            output_dir: None,
+            config: ResolvedConfig::default(),
+        }
+    }
+
+    /// Returns `config` with the format and directory overrides applied.
+    pub fn resolved_config(&self) -> ResolvedConfig {
+        let mut config = self.config.clone();
+        if let Some(output_format) = self.output_format {
+            config.output_format = output_format;
+        }
+        if let Some(data_dir) = &self.data_dir {
+            config.set_data_dir(data_dir);
+        }
+        if let Some(output_dir) = &self.output_dir {
+            let output_dir = match &self.data_dir {
+                Some(data_dir) => data_dir.join(output_dir),
+                None => output_dir.clone(),
+            };
+            config.set_output_dir(&output_dir);
+        }
+        config
+    }
+}
+
+/// Runs the whole pipeline (download, extraction, parsing, cleanup) for `options`.
+///
+/// Lists the available periods (from the landing page, or from `config.source_dir` when set),
+/// keeps those between `start_period` and `end_period`, and processes them like the `cli`
+/// subcommand: the stages honour the `skip_*` settings, the run state file is updated after
+/// parsing, and a manifest is written when `config.manifest` is set. Nothing is printed;
+/// the returned summary holds the statistics of each stage.
+///
+/// ```no_run
+/// use sppd_cli::models::ProcurementType;
+/// use sppd_cli::pipeline::{run, PipelineOptions};
+///
+/// # async fn example() -> sppd_cli::errors::AppResult<()> {
+/// let mut options = PipelineOptions::new(ProcurementType::MinorContracts);
+/// options.start_period = Some("202401".to_string());
+/// options.end_period = Some("202403".to_string());
+/// options.data_dir = Some("/scratch/sppd".into());
+/// let summary = run(options).await?;
+/// println!("{}", summary.to_table());
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// Returns `InvalidInput` if the configuration or a period is invalid,
+/// `PeriodValidationError` if a period is not available, and the error of the first failing
+/// stage otherwise. With `config.keep_going`, failed periods are reported in the summary
+/// (see [`RunSummary::partial_failure`]) instead.
+pub async fn run(options: PipelineOptions) -> AppResult<RunSummary> {
+    let config = options.resolved_config();
+    config.validate()?;
+    let links = available_links(&options.procurement_type, &config).await?;
+    info!(periods = links.len(), "Link fetching completed");
+    run_with_links(&links, options).await
+}
+
+/// Like [`run`], for available periods already listed in the period-to-URL `links`.
+pub(crate) async fn run_with_links(
+    links: &BTreeMap<String, String>,
+    options: PipelineOptions,
+) -> AppResult<RunSummary> {
+    let config = options.resolved_config();
+    run_workflow(
+        links,
+        options.procurement_type,
+        options.start_period.as_deref(),
+        options.end_period.as_deref(),
+        options.cleanup,
+        &config,
+    )
+    .await
+}
+
+/// Polls for new periods after an initial run, for `--watch`.
+///
+/// Every `interval`, `fetch_links` lists the available periods again. The periods after the
+/// last completed one in the state file are processed one at a time, in order, like
+/// [`run_with_links`] with `options` (whose `start_period` and `end_period` are replaced),
+/// and `on_run` receives the summary of each. A failed poll or period is logged and the
+/// remaining periods wait for the next poll: periods that failed are not recorded as
+/// completed, so they are picked up again.
+///
+/// Returns once `shutdown` completes: at once while waiting for the next poll, after the
+/// current period otherwise.
+pub(crate) async fn watch_new_periods<F, Fut>(
+    options: &PipelineOptions,
+    interval: Duration,
+    mut fetch_links: F,
+    shutdown: impl Future<Output = ()>,
+    mut on_run: impl FnMut(&RunSummary),
+) where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = AppResult<BTreeMap<String, String>>>,
+{
+    let state_file = options.resolved_config().state_file;
+    let mut shutdown = std::pin::pin!(shutdown);
+    info!(
+        interval_secs = interval.as_secs(),
+        "Watching for new periods"
+    );
+    loop {
+        tokio::select! {
+            biased;
+            () = &mut shutdown => {
+                info!("Shutdown requested, stopping watch");
+                return;
+            }
+            () = tokio::time::sleep(interval) => {}
+        }
+
+        let links = match fetch_links().await {
+            Ok(links) => links,
+            Err(e) => {
+                warn!(error = %e, "Failed to fetch links, retrying at next poll");
+                continue;
+            }
+        };
+        let start_period = match resume_point(&options.procurement_type, &links, &state_file) {
+            Ok(ResumePoint::UpToDate) => {
+                info!("No new periods");
+                continue;
+            }
+            Ok(ResumePoint::From(period)) => Some(period),
+            Ok(ResumePoint::FromBeginning) => None,
+            Err(e) => {
+                warn!(error = %e, "Failed to read run state, retrying at next poll");
+                continue;
+            }
+        };
+
+        let mut new_periods = match filter_periods_by_range(&links, start_period.as_deref(), None) {
+            Ok(new_periods) => new_periods,
+            Err(e) => {
+                warn!(error = %e, "Failed to select new periods, retrying at next poll");
+                continue;
+            }
+        };
+        if let Err(e) = exclude_periods(&mut new_periods, &options.config.exclude_periods) {
+            warn!(error = %e, "Failed to select new periods, retrying at next poll");
+            continue;
+        }
+
+        info!(
+            start_period = start_period.as_deref().unwrap_or("first available"),
+            periods = new_periods.len(),
+            "New periods available"
+        );
+        for period in new_periods.keys() {
+            // A shutdown requested during the previous period stops before the next one.
+            if shutdown.as_mut().now_or_never().is_some() {
+                info!("Shutdown requested, stopping watch");
+                return;
+            }
+            let mut run_options = options.clone();
+            run_options.start_period = Some(period.clone());
+            run_options.end_period = Some(period.clone());
+            match run_with_links(&links, run_options).await {
+                Ok(summary) => {
+                    on_run(&summary);
+                    if summary.partial_failure().is_some() {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    warn!(period = %period, error = %e, "Run failed, retrying at next poll");
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Runs the stages for the periods of `links` between `start_period` and `end_period`.
+pub(crate) async fn run_workflow(
+    links: &BTreeMap<String, String>,
+    proc_type: ProcurementType,
+    start_period: Option<&str>,
+    end_period: Option<&str>,
+    should_cleanup: bool,
+    resolved_config: &ResolvedConfig,
+) -> AppResult<RunSummary> {
+    let start = Instant::now();
+    let mut target_links = filter_periods_by_range(links, start_period, end_period)?;
+    exclude_periods(&mut target_links, &resolved_config.exclude_periods)?;
+    let mut summary = RunSummary::new(&proc_type, target_links.len());
+    let mut manifest = RunManifest::new(&proc_type, target_links.keys());
+    manifest.record_sources(&target_links);
+    // Periods failing a stage under `--keep-going` are dropped from the later stages.
+    let selected_links = target_links.clone();
+
+    print_download_info(&proc_type, start_period, end_period, target_links.len());
+
+    if let Some(periods_in_flight) = resolved_config.periods_in_flight {
+        let stages = run_periods_overlapped(
+            &target_links,
+            &proc_type,
+            should_cleanup,
+            periods_in_flight,
+            resolved_config,
+        )
+        .await?;
+        if let Some(download_stats) = stages.download {
+            #[cfg(feature = "metrics")]
+            crate::metrics::record_downloads(&proc_type, &download_stats);
+            remove_failed_periods(&mut target_links, &download_stats.failed_periods);
+            summary.download = Some(download_stats);
+        }
+        if let Some(extraction_stats) = stages.extraction {
+            remove_failed_periods(&mut target_links, &extraction_stats.failed_periods);
+            summary.extraction = Some(extraction_stats);
+        }
+        manifest.record_zip_bytes(&stages.zip_bytes);
+        if let Some(parse_stats) = stages.parse {
+            #[cfg(feature = "metrics")]
+            crate::metrics::record_parse(&proc_type, &parse_stats);
+            manifest.record_parse(&parse_stats);
+            remove_failed_periods(&mut target_links, &parse_stats.failed_periods);
+            summary.parse = Some(parse_stats);
+        }
+    } else {
+        if resolved_config.skip_download {
+            info!("Skipping download phase");
+        } else {
+            let download_stats = fetch_archives(&target_links, &proc_type, resolved_config).await?;
+            #[cfg(feature = "metrics")]
+            crate::metrics::record_downloads(&proc_type, &download_stats);
+            remove_failed_periods(&mut target_links, &download_stats.failed_periods);
+            summary.download = Some(download_stats);
+        }
+
+        if resolved_config.skip_extraction {
+            info!("Skipping extraction phase");
+        } else {
+            info!("Starting extraction phase");
+            let extraction_stats =
+                extract_all_zips(&target_links, &proc_type, resolved_config).await?;
+            remove_failed_periods(&mut target_links, &extraction_stats.failed_periods);
+            summary.extraction = Some(extraction_stats);
+        }
+        manifest.record_zip_sizes(&proc_type.download_dir(resolved_config));
+
+        if resolved_config.skip_parse {
+            // Nothing was parsed, so the files stay for a later run.
+            info!("Skipping parsing phase, keeping downloaded and extracted files");
+        } else {
+            let parse_stats = parse_xmls(
+                &target_links,
+                &proc_type,
+                resolved_config.batch_size,
+                resolved_config,
+                transformer::from_config(resolved_config),
+            )
+            .await?;
+            #[cfg(feature = "metrics")]
+            crate::metrics::record_parse(&proc_type, &parse_stats);
+            manifest.record_parse(&parse_stats);
+            remove_failed_periods(&mut target_links, &parse_stats.failed_periods);
+            summary.parse = Some(parse_stats);
+
+            // Files of failed periods are kept so they can be inspected or retried.
+            cleanup_files(&target_links, &proc_type, should_cleanup, resolved_config).await?;
+        }
+    }
+
+    // Without parsing nothing is complete, so the state is untouched.
+    if !resolved_config.skip_parse {
+        // Never record past a failed period, so `--since-last-run` retries it.
+        let first_failure = selected_links
+            .keys()
+            .find(|period| !target_links.contains_key(*period));
+        let last_completed = selected_links
+            .keys()
+            .take_while(|period| Some(*period) != first_failure)
+            .last();
+        if let Some(last_period) = last_completed {
+            let state_path = &resolved_config.state_file;
+            let mut state = RunState::load(state_path)?.unwrap_or_default();
+            state.record_completion(&proc_type, last_period);
+            state.save(state_path)?;
+        }
+        apply_output_retention(&proc_type, resolved_config).await?;
+    }
+
+    if target_links.len() == selected_links.len() {
+        info!(
+            procurement_type = proc_type.display_name(),
+            periods_processed = target_links.len(),
+            "All operations completed successfully"
+        );
+    } else {
+        warn!(
+            procurement_type = proc_type.display_name(),
+            periods_processed = target_links.len(),
+            periods_failed = selected_links.len() - target_links.len(),
+            "Operations completed with failed periods"
+        );
+    }
+
+    #[cfg(feature = "metrics")]
+    crate::metrics::record_run_completed();
+    summary.total_elapsed_ms = start.elapsed().as_millis() as u64;
+    manifest.total_elapsed_ms = summary.total_elapsed_ms;
+    write_manifest(&manifest, &proc_type, resolved_config)?;
+    Ok(summary)
+}
+
+/// Statistics of the stages run by [`run_periods_overlapped`], summed over the periods.
+///
+/// A stage is `None` when it is skipped, like in the summary of a stage-by-stage run.
+#[derive(Debug, Default)]
+struct OverlappedStages {
+    download: Option<DownloadStats>,
+    extraction: Option<ExtractionStats>,
+    parse: Option<ParseStats>,
+    /// Size of each period's ZIP, taken before its cleanup deletes it
+    zip_bytes: BTreeMap<String, u64>,
+}
+
+/// Runs the stages period by period instead of stage by stage (`periods_in_flight`).
+///
+/// Each period is downloaded, extracted, parsed, and cleaned up on its own, and up to
+/// `periods_in_flight` periods are processed at once, so parsing starts as soon as the first
+/// archive is in. The periods share one set of download limits and one parser thread pool,
+/// so `concurrent_downloads`, `max_rps`, `max_bytes_per_sec`, and `parser_threads` hold
+/// for the whole run. The stage statistics add up to those of a stage-by-stage run; stage
+/// times are summed over the periods.
+///
+/// A period failing a stage skips its later stages and keeps its files. With `keep_going`
+/// the other periods carry on; otherwise no new period is started after the first error,
+/// which is returned once the periods in flight have finished, so none of them is left
+/// writing in the background.
+async fn run_periods_overlapped(
+    target_links: &BTreeMap<String, String>,
+    proc_type: &ProcurementType,
+    should_cleanup: bool,
+    periods_in_flight: usize,
+    resolved_config: &ResolvedConfig,
+) -> AppResult<OverlappedStages> {
+    let client = if resolved_config.skip_download || resolved_config.source_dir.is_some() {
+        None
+    } else {
+        Some(build_client(resolved_config)?)
+    };
+    let download_limits = DownloadLimits::new(resolved_config);
+    let rayon_pool = if resolved_config.skip_parse {
+        None
+    } else {
+        Some(parser_pool(resolved_config)?)
+    };
+    info!(
+        periods = target_links.len(),
+        periods_in_flight, "Processing periods through all stages"
+    );
+
+    let mut stages = OverlappedStages {
+        download: (!resolved_config.skip_download).then(DownloadStats::default),
+        extraction: (!resolved_config.skip_extraction).then(ExtractionStats::default),
+        parse: (!resolved_config.skip_parse).then(ParseStats::default),
+        zip_bytes: BTreeMap::new(),
+    };
+    let failed = AtomicBool::new(false);
+    let mut first_error = None;
+    let mut runs = stream::iter(target_links)
+        .take_while(|_| future::ready(!failed.load(Ordering::Relaxed)))
+        .map(|(period, link)| {
+            let (client, download_limits, rayon_pool) =
+                (client.as_ref(), &download_limits, rayon_pool.as_ref());
+            async move {
+                let links = BTreeMap::from([(period.clone(), link.clone())]);
+                run_period(
+                    &links,
+                    proc_type,
+                    should_cleanup,
+                    resolved_config,
+                    client,
+                    download_limits,
+                    rayon_pool,
+                )
+                .await
+                .map(|run| (period, run))
+            }
+        })
+        .buffer_unordered(periods_in_flight);
+    while let Some(result) = runs.next().await {
+        let (period, run) = match result {
+            Ok(result) => result,
+            Err(e) => {
+                failed.store(true, Ordering::Relaxed);
+                if first_error.is_some() {
+                    warn!(error = %e, "Another period failed while the run was stopping");
+                } else {
+                    first_error = Some(e);
+                }
+                continue;
+            }
+        };
+        if let (Some(total), Some(stats)) = (&mut stages.download, run.download) {
+            total.merge(stats);
+        }
+        if let (Some(total), Some(stats)) = (&mut stages.extraction, run.extraction) {
+            total.merge(stats);
+        }
+        if let (Some(total), Some(stats)) = (&mut stages.parse, run.parse) {
+            total.merge(stats);
+        }
+        if let Some(bytes) = run.zip_bytes {
+            stages.zip_bytes.insert(period.clone(), bytes);
+        }
+    }
+    drop(runs);
+    match first_error {
+        Some(e) => Err(e),
+        None => Ok(stages),
+    }
+}
+
+/// What [`run_period`] did with one period.
+#[derive(Debug, Default)]
+struct PeriodRun {
+    download: Option<DownloadStats>,
+    extraction: Option<ExtractionStats>,
+    parse: Option<ParseStats>,
+    zip_bytes: Option<u64>,
+}
+
+/// Runs every enabled stage for the single period of `links`, stopping after a stage that
+/// records it as failed.
+async fn run_period(
+    links: &BTreeMap<String, String>,
+    proc_type: &ProcurementType,
+    should_cleanup: bool,
+    resolved_config: &ResolvedConfig,
+    client: Option<&reqwest::Client>,
+    download_limits: &DownloadLimits,
+    rayon_pool: Option<&rayon::ThreadPool>,
+) -> AppResult<PeriodRun> {
+    let mut run = PeriodRun::default();
+
+    if !resolved_config.skip_download {
+        let stats = match client {
+            Some(client) => {
+                download_files_with_limits(
+                    client,
+                    links,
+                    proc_type,
+                    resolved_config,
+                    download_limits,
+                )
+                .await?
+            }
+            None => copy_local_files(links, proc_type, resolved_config).await?,
+        };
+        let failed = !stats.failed_periods.is_empty();
+        run.download = Some(stats);
+        if failed {
+            return Ok(run);
+        }
+    }
+
+    if !resolved_config.skip_extraction {
+        let stats = extract_all_zips(links, proc_type, resolved_config).await?;
+        let failed = !stats.failed_periods.is_empty();
+        run.extraction = Some(stats);
+        if failed {
+            return Ok(run);
+        }
+    }
+
+    let download_dir = proc_type.download_dir(resolved_config);
+    run.zip_bytes = links.keys().find_map(|period| {
+        std::fs::metadata(download_dir.join(format!("{period}.zip")))
+            .ok()
+            .map(|metadata| metadata.len())
+    });
+
+    if let Some(rayon_pool) = rayon_pool {
+        let stats = parse_xmls_with_pool(
+            links,
+            proc_type,
+            resolved_config.batch_size,
+            resolved_config,
+            rayon_pool,
+            transformer::from_config(resolved_config).as_deref(),
+        )
+        .await?;
+        let failed = !stats.failed_periods.is_empty();
+        run.parse = Some(stats);
+        // Files of a failed period are kept so they can be inspected or retried.
+        if !failed {
+            cleanup_files(links, proc_type, should_cleanup, resolved_config).await?;
+        }
+    }
+    Ok(run)
+}
+
+/// Removes the periods that failed a stage (with `--keep-going`) from `target_links`.
+fn remove_failed_periods(
+    target_links: &mut BTreeMap<String, String>,
+    failed_periods: &BTreeMap<String, String>,
+) {
+    for period in failed_periods.keys() {
+        target_links.remove(period);
+    }
+}
+
+/// Writes `manifest` to the Parquet directory when `--manifest` is enabled.
+fn write_manifest(
+    manifest: &RunManifest,
+    proc_type: &ProcurementType,
+    resolved_config: &ResolvedConfig,
+) -> AppResult<()> {
+    if resolved_config.manifest {
+        let path = proc_type
+            .parquet_dir(resolved_config)
+            .join(MANIFEST_FILE_NAME);
+        manifest.save(&path)?;
+        info!(path = %path.display(), "Run manifest written");
+    }
+    Ok(())
+}
+
+/// Deletes the output of the periods past `retention` from the Parquet directory, if set.
+async fn apply_output_retention(
+    proc_type: &ProcurementType,
+    resolved_config: &ResolvedConfig,
+) -> AppResult<()> {
+    if let Some(keep) = resolved_config.retention {
+        apply_retention(
+            &proc_type.parquet_dir(resolved_config),
+            keep,
+            resolved_config.retention_dry_run,
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// Downloads the ZIP archives for the requested periods and stops.
+///
+/// Extraction, parsing, and cleanup are skipped, so the archives stay in the download
+/// directory regardless of `--no-cleanup`. The run state file is not updated because no
+/// period has been parsed.
+pub(crate) async fn run_download_only(
+    links: &BTreeMap<String, String>,
+    proc_type: ProcurementType,
+    start_period: Option<&str>,
+    end_period: Option<&str>,
+    resolved_config: &ResolvedConfig,
+) -> AppResult<RunSummary> {
+    let start = Instant::now();
+    let mut target_links = filter_periods_by_range(links, start_period, end_period)?;
+    exclude_periods(&mut target_links, &resolved_config.exclude_periods)?;
+    let mut summary = RunSummary::new(&proc_type, target_links.len());
+
+    print_download_info(&proc_type, start_period, end_period, target_links.len());
+
+    let stats = fetch_archives(&target_links, &proc_type, resolved_config).await?;
+    #[cfg(feature = "metrics")]
+    crate::metrics::record_downloads(&proc_type, &stats);
+    let mut manifest = RunManifest::new(&proc_type, target_links.keys());
+    manifest.record_sources(&target_links);
+    manifest.record_zip_sizes(&proc_type.download_dir(resolved_config));
+
+    info!(
+        procurement_type = proc_type.display_name(),
+        downloaded = stats.downloaded,
+        skipped = stats.skipped,
+        size_mb = round_two_decimals(mb_from_bytes(stats.bytes)),
+        download_dir = %proc_type.download_dir(resolved_config).display(),
+        "Download-only run completed"
+    );
+
+    summary.download = Some(stats);
+    #[cfg(feature = "metrics")]
+    crate::metrics::record_run_completed();
+    summary.total_elapsed_ms = start.elapsed().as_millis() as u64;
+    manifest.total_elapsed_ms = summary.total_elapsed_ms;
+    write_manifest(&manifest, &proc_type, resolved_config)?;
+    Ok(summary)
+}
+
+/// Parses already-extracted periods without touching the network.
+///
+/// The target period set is built from the subdirectories of the extraction directory
+/// (see [`local_period_links`]) and then filtered by the requested range.
+pub(crate) async fn run_parse_only(
+    proc_type: ProcurementType,
+    start_period: Option<&str>,
+    end_period: Option<&str>,
+    resolved_config: &ResolvedConfig,
+) -> AppResult<RunSummary> {
+    let start = Instant::now();
+    let extract_dir = proc_type.extract_dir(resolved_config);
+    let local_links = local_period_links(&extract_dir)?;
+    let mut target_links = filter_periods_by_range(&local_links, start_period, end_period)?;
+    exclude_periods(&mut target_links, &resolved_config.exclude_periods)?;
+    let mut summary = RunSummary::new(&proc_type, target_links.len());
+
+    info!(
+        procurement_type = proc_type.display_name(),
+        periods = target_links.len(),
+        extract_dir = %extract_dir.display(),
+        "Starting offline parsing"
+    );
+
+    let parse_stats = parse_xmls(
+        &target_links,
+        &proc_type,
+        resolved_config.batch_size,
+        resolved_config,
+        transformer::from_config(resolved_config),
+    )
+    .await?;
+    #[cfg(feature = "metrics")]
+    crate::metrics::record_parse(&proc_type, &parse_stats);
+    // Parse-only runs read local files, so there are no source URLs or archive sizes.
+    let mut manifest = RunManifest::new(&proc_type, target_links.keys());
+    manifest.record_parse(&parse_stats);
+    summary.parse = Some(parse_stats);
+    apply_output_retention(&proc_type, resolved_config).await?;
+
+    info!(
+        procurement_type = proc_type.display_name(),
+        periods_processed = target_links.len(),
+        "All operations completed successfully"
+    );
+
+    #[cfg(feature = "metrics")]
+    crate::metrics::record_run_completed();
+    summary.total_elapsed_ms = start.elapsed().as_millis() as u64;
+    manifest.total_elapsed_ms = summary.total_elapsed_ms;
+    write_manifest(&manifest, &proc_type, resolved_config)?;
+    Ok(summary)
+}
+
+/// Plans a run over the periods of `links` between `start_period` and `end_period` without
+/// downloading, extracting, or writing anything (`--dry-run`).
+///
+/// Each period gets the decisions the stages would make, honouring the `skip_*` settings.
+/// Download sizes come from HEAD requests, or from the source files with `source_dir`; a
+/// failed HEAD request only leaves the size unknown.
+pub(crate) async fn plan_run(
+    links: &BTreeMap<String, String>,
+    proc_type: ProcurementType,
+    start_period: Option<&str>,
+    end_period: Option<&str>,
+    resolved_config: &ResolvedConfig,
+) -> AppResult<RunPlan> {
+    let mut target_links = filter_periods_by_range(links, start_period, end_period)?;
+    exclude_periods(&mut target_links, &resolved_config.exclude_periods)?;
+    let download_dir = proc_type.download_dir(resolved_config);
+    let extract_dir = proc_type.extract_dir(resolved_config);
+    let parquet_dir = proc_type.parquet_dir(resolved_config);
+    let client = if resolved_config.skip_download || resolved_config.source_dir.is_some() {
+        None
+    } else {
+        Some(build_client(resolved_config)?)
+    };
+
+    let periods = stream::iter(&target_links)
+        .map(|(period, link)| {
+            let (download_dir, extract_dir, parquet_dir) =
+                (&download_dir, &extract_dir, &parquet_dir);
+            let client = client.as_ref();
+            async move {
+                let download =
+                    (!resolved_config.skip_download).then(|| needs_download(download_dir, period));
+                let download_bytes = match download {
+                    Some(true) => zip_size(client, link).await,
+                    _ => None,
+                };
+                let output_path = (!resolved_config.skip_parse)
+                    .then(|| period_output_path(parquet_dir, period, resolved_config));
+                PeriodPlan {
+                    period: period.clone(),
+                    download,
+                    download_bytes,
+                    extract: (!resolved_config.skip_extraction)
+                        .then(|| !is_extracted(extract_dir, period)),
+                    overwrite: output_path.as_ref().is_some_and(|path| path.exists()),
+                    output_path,
+                }
+            }
+        })
+        .buffered(resolved_config.concurrent_downloads.max(1))
+        .collect()
+        .await;
+
+    Ok(RunPlan {
+        procurement_type: proc_type.display_name().to_string(),
+        periods,
+    })
+}
+
+/// Size of the ZIP behind `link`: the size of the source file for a `file://` link, or the
+/// `Content-Length` of a HEAD request otherwise.
+async fn zip_size(client: Option<&reqwest::Client>, link: &str) -> Option<u64> {
+    if let Some(path) = local_source_path(link) {
+        return std::fs::metadata(path).ok().map(|metadata| metadata.len());
+    }
+    match remote_size(client?, link).await {
+        Ok(size) => size,
+        Err(e) => {
+            warn!(url = link, error = %e, "Failed to get download size");
+            None
+        }
+    }
+}
+
+/// Builds a period map from the extracted subdirectories found under `extract_dir`.
+///
+/// Keys are the subdirectory names (e.g., "202403") and values are the subdirectory paths,
+/// so the result can be passed anywhere a links map is expected. Returns `IoError` if the
+/// extraction directory does not exist.
+fn local_period_links(extract_dir: &Path) -> AppResult<BTreeMap<String, String>> {
+    if !extract_dir.exists() {
+        return Err(AppError::IoError(format!(
+            "Directory does not exist: {}",
+            extract_dir.display()
+        )));
+    }
+
+    Ok(find_xmls(extract_dir)?
+        .into_iter()
+        .map(|(period, _)| {
+            let path = extract_dir.join(&period).display().to_string();
+            (period, path)
+        })
+        .collect())
+}
+
+/// Lists the available periods, from `source_dir` when set or from the landing page otherwise.
+///
+/// With `skip_download`, the periods come from the download directory instead: its
+/// extracted subdirectories when extraction is skipped too, its `{period}.zip` files
+/// otherwise.
+pub(crate) async fn available_links(
+    proc_type: &ProcurementType,
+    resolved_config: &ResolvedConfig,
+) -> AppResult<BTreeMap<String, String>> {
+    if resolved_config.skip_download {
+        return if resolved_config.skip_extraction {
+            local_period_links(&proc_type.extract_dir(resolved_config))
+        } else {
+            downloaded_zip_links(&proc_type.download_dir(resolved_config))
+        };
+    }
+    match &resolved_config.source_dir {
+        Some(source_dir) => local_zip_links(source_dir),
+        None => fetch_links_for(proc_type, resolved_config).await,
+    }
+}
+
+/// Builds a period map from the `{period}.zip` files left in `download_dir` by earlier runs.
+///
+/// Returns `IoError` if the download directory does not exist.
+fn downloaded_zip_links(download_dir: &Path) -> AppResult<BTreeMap<String, String>> {
+    let entries = std::fs::read_dir(download_dir).map_err(|e| {
+        AppError::IoError(format!(
+            "Failed to read download directory {}: {e}",
+            download_dir.display()
+        ))
+    })?;
+
+    let mut links = BTreeMap::new();
+    for entry in entries {
+        let path = entry?.path();
+        if !path.is_file() || path.extension().and_then(|ext| ext.to_str()) != Some("zip") {
+            continue;
+        }
+        if let Some(period) = path.file_stem().and_then(|stem| stem.to_str()) {
+            if !period.is_empty() && period.bytes().all(|b| b.is_ascii_digit()) {
+                links.insert(period.to_string(), path.display().to_string());
+            }
+        }
+    }
+    Ok(links)
+}
+
+/// Puts the ZIPs for `target_links` in the download directory, copying them from
+/// `source_dir` when set and downloading them otherwise.
+async fn fetch_archives(
+    target_links: &BTreeMap<String, String>,
+    proc_type: &ProcurementType,
+    resolved_config: &ResolvedConfig,
+) -> AppResult<DownloadStats> {
+    if resolved_config.source_dir.is_some() {
+        return copy_local_files(target_links, proc_type, resolved_config).await;
+    }
+    let client = build_client(resolved_config)?;
+    download_files(&client, target_links, proc_type, resolved_config).await
+}
+
+fn print_download_info(
+    proc_type: &ProcurementType,
+    start_period: Option<&str>,
+    end_period: Option<&str>,
+    periods_count: usize,
+) {
+    let start_text = start_period.unwrap_or("first available");
+    let end_text = end_period.unwrap_or("last available");
+    info!(
+        procurement_type = proc_type.display_name(),
+        periods = periods_count,
+        start_period = start_text,
+        end_period = end_text,
+        "Starting download"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn local_period_links_uses_extracted_subdirectories() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(tmp.path().join("202403")).unwrap();
+        std::fs::write(tmp.path().join("202403/feed.atom"), "<feed></feed>").unwrap();
+        std::fs::create_dir_all(tmp.path().join("202404")).unwrap();
+
+        let links = local_period_links(tmp.path()).unwrap();
+        assert_eq!(links.len(), 1);
+        assert!(links.contains_key("202403"));
+
+        let filtered = filter_periods_by_range(&links, Some("202403"), Some("202403")).unwrap();
+        assert_eq!(filtered.len(), 1);
+    }
+
+    #[test]
+    fn local_period_links_missing_dir_errors() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        assert!(local_period_links(&tmp.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn downloaded_zip_links_lists_period_archives_only() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("202403.zip"), b"PK").unwrap();
+        std::fs::write(tmp.path().join("2023.zip"), b"PK").unwrap();
+        std::fs::write(tmp.path().join("202404.zip.part"), b"PK").unwrap();
+        std::fs::write(tmp.path().join("notes.zip"), b"PK").unwrap();
+        std::fs::create_dir_all(tmp.path().join("202402")).unwrap();
+
+        let links = downloaded_zip_links(tmp.path()).unwrap();
+        assert_eq!(links.keys().collect::<Vec<_>>(), ["2023", "202403"]);
+        assert!(downloaded_zip_links(&tmp.path().join("missing")).is_err());
+    }
+
+    #[tokio::test]
+    async fn plan_run_reports_stage_decisions_without_writing() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let source = tmp.path().join("mirror/202402.zip");
+        std::fs::create_dir_all(source.parent().unwrap()).unwrap();
+        std::fs::write(&source, b"PK\x03\x04zip").unwrap();
+        let mut config = ResolvedConfig {
+            source_dir: Some(tmp.path().join("mirror")),
+            concat_batches: true,
+            ..Default::default()
+        };
+        config.set_data_dir(&tmp.path().join("data"));
+        std::fs::create_dir_all(tmp.path().join("data/tmp/pt/202401")).unwrap();
+        std::fs::write(tmp.path().join("data/tmp/pt/202401.zip"), b"PK").unwrap();
+        std::fs::create_dir_all(tmp.path().join("data/parquet/pt")).unwrap();
+        std::fs::write(tmp.path().join("data/parquet/pt/202401.parquet"), b"PAR1").unwrap();
+        let links = BTreeMap::from([
+            ("202401".to_string(), "file:///unused.zip".to_string()),
+            (
+                "202402".to_string(),
+                url::Url::from_file_path(&source).unwrap().to_string(),
+            ),
+        ]);
+
+        let plan = plan_run(&links, ProcurementType::PublicTenders, None, None, &config)
+            .await
+            .unwrap();
+
+        let parquet_dir = tmp.path().join("data/parquet/pt");
+        assert_eq!(
+            plan.periods,
+            [
+                PeriodPlan {
+                    period: "202401".to_string(),
+                    download: Some(false),
+                    download_bytes: None,
+                    extract: Some(false),
+                    output_path: Some(parquet_dir.join("202401.parquet")),
+                    overwrite: true,
+                },
+                PeriodPlan {
+                    period: "202402".to_string(),
+                    download: Some(true),
+                    download_bytes: Some(7),
+                    extract: Some(true),
+                    output_path: Some(parquet_dir.join("202402.parquet")),
+                    overwrite: false,
+                },
+            ]
+        );
+        assert!(!tmp.path().join("data/tmp/pt/202402.zip").exists());
+    }
+
+    #[test]
+    fn test_print_download_info_runs() {
+        print_download_info(
+            &ProcurementType::MinorContracts,
+            Some("202301"),
+            Some("202302"),
+            3,
+        );
+        print_download_info(&ProcurementType::PublicTenders, None, None, 5);
+    }
+
+    #[cfg(feature = "metrics")]
+    #[tokio::test]
+    async fn metrics_endpoint_reports_parse_only_run() {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let config = ResolvedConfig {
+            download_dir_pt: tmp.path().join("tmp"),
+            parquet_dir_pt: tmp.path().join("parquet"),
+            ..Default::default()
+        };
+        let period_dir = tmp.path().join("tmp/202402");
+        std::fs::create_dir_all(&period_dir).unwrap();
+        std::fs::write(
+            period_dir.join("a.atom"),
+            "<feed><entry><id>id1</id><title>t</title></entry></feed>",
+        )
+        .unwrap();
+
+        let addr = crate::metrics::serve("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        run_parse_only(ProcurementType::PublicTenders, None, None, &config)
+            .await
+            .unwrap();
+
+        let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains(
+            "sppd_entries_parsed_total{period=\"202402\",procurement_type=\"Public Tenders\"} 1"
+        ));
+        let timestamp = response
+            .lines()
+            .find_map(|l| l.strip_prefix("sppd_last_run_timestamp_seconds "))
+            .unwrap();
+        assert!(timestamp.parse::<u64>().unwrap() > 0);
+    }
+
+    /// Serves a landing page linking only to period 202401 on its first request and to
+    /// 202401 and 202402 afterwards, plus a ZIP with one entry for any other path. Returns
+    /// the base URL and the number of landing page requests served.
+    fn serve_growing_listing() -> (String, std::sync::Arc<std::sync::atomic::AtomicUsize>) {
+        use std::io::{BufRead, BufReader, Write};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        zip.start_file("feed.atom", zip::write::FileOptions::default())
+            .unwrap();
+        zip.write_all(b"<feed><entry><id>new</id></entry></feed>")
+            .unwrap();
+        let zip = zip.finish().unwrap().into_inner();
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let page_requests = std::sync::Arc::new(AtomicUsize::new(0));
+        let served = page_requests.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(&stream);
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut header = String::new();
+                while reader.read_line(&mut header).unwrap() > 2 {
+                    header.clear();
+                }
+
+                let body = if request_line.starts_with("GET /page ") {
+                    let mut html = String::from(r#"<a href="/pt_202401.zip">202401</a>"#);
+                    if served.fetch_add(1, Ordering::SeqCst) > 0 {
+                        html.push_str(r#"<a href="/pt_202402.zip">202402</a>"#);
+                    }
+                    html.into_bytes()
+                } else {
+                    zip.clone()
+                };
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    body.len()
+                )
+                .unwrap();
+                stream.write_all(&body).unwrap();
+            }
+        });
+        (base_url, page_requests)
+    }
+
+    #[tokio::test]
+    async fn watch_new_periods_processes_period_published_after_first_poll() {
+        use crate::downloader::parse_zip_links;
+        use std::sync::atomic::Ordering;
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let (base_url, page_requests) = serve_growing_listing();
+        let mut options = PipelineOptions::new(ProcurementType::PublicTenders);
+        options.data_dir = Some(tmp.path().to_path_buf());
+        options.config.concat_batches = true;
+        options.config.progress = false;
+        let state_file = options.resolved_config().state_file;
+        let mut state = RunState::default();
+        state.record_completion(&ProcurementType::PublicTenders, "202401");
+        state.save(&state_file).unwrap();
+
+        let client = reqwest::Client::new();
+        let page_url = url::Url::parse(&format!("{base_url}/page")).unwrap();
+        let fetch_links = || async {
+            let html = client
+                .get(page_url.clone())
+                .send()
+                .await
+                .unwrap()
+                .text()
+                .await
+                .unwrap();
+            parse_zip_links(&html, &page_url)
+        };
+        let (stop, stopped) = futures::channel::oneshot::channel::<()>();
+        let mut stop = Some(stop);
+        let mut runs = Vec::new();
+        watch_new_periods(
+            &options,
+            Duration::from_millis(10),
+            fetch_links,
+            async {
+                let _ = stopped.await;
+            },
+            |summary| {
+                runs.push(summary.parse.clone().unwrap().entries_by_period);
+                if let Some(stop) = stop.take() {
+                    let _ = stop.send(());
+                }
+            },
+        )
+        .await;
+
+        // The first poll only lists the completed period; the second one finds 202402.
+        assert_eq!(page_requests.load(Ordering::SeqCst), 2);
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].keys().collect::<Vec<_>>(), ["202402"]);
+        assert!(tmp.path().join("parquet/pt/202402.parquet").exists());
+        assert!(!tmp.path().join("parquet/pt/202401.parquet").exists());
+        let state = RunState::load(&state_file).unwrap().unwrap();
+        assert_eq!(
+            state.last_completed_period(&ProcurementType::PublicTenders),
+            Some("202402")
+        );
+    }
+
+    #[tokio::test]
+    async fn watch_new_periods_stops_after_the_current_period_on_shutdown() {
+        use crate::downloader::parse_zip_links;
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let (base_url, _) = serve_growing_listing();
+        let mut options = PipelineOptions::new(ProcurementType::PublicTenders);
+        options.data_dir = Some(tmp.path().to_path_buf());
+        options.config.concat_batches = true;
+        options.config.progress = false;
+        let state_file = options.resolved_config().state_file;
+
+        let client = reqwest::Client::new();
+        let page_url = url::Url::parse(&format!("{base_url}/page")).unwrap();
+        let fetch_page = || async {
+            let html = client
+                .get(page_url.clone())
+                .send()
+                .await
+                .unwrap()
+                .text()
+                .await
+                .unwrap();
+            parse_zip_links(&html, &page_url)
+        };
+        // Past the first request, so the first poll lists both 202401 and 202402.
+        fetch_page().await.unwrap();
+        let (stop, stopped) = futures::channel::oneshot::channel::<()>();
+        let mut stop = Some(stop);
+        let mut runs = Vec::new();
+        watch_new_periods(
+            &options,
+            Duration::from_millis(10),
+            fetch_page,
+            async {
+                let _ = stopped.await;
+            },
+            |summary| {
+                runs.push(summary.parse.clone().unwrap().entries_by_period);
+                if let Some(stop) = stop.take() {
+                    let _ = stop.send(());
+                }
+            },
+        )
+        .await;
+
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].keys().collect::<Vec<_>>(), ["202401"]);
+        assert!(tmp.path().join("parquet/pt/202401.parquet").exists());
+        assert!(!tmp.path().join("parquet/pt/202402.parquet").exists());
+        let state = RunState::load(&state_file).unwrap().unwrap();
+        assert_eq!(
+            state.last_completed_period(&ProcurementType::PublicTenders),
+            Some("202401")
+        );
+    }
+}
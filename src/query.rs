@@ -0,0 +1,195 @@
+//! SQL queries over the Parquet output with an embedded DuckDB (`duckdb` feature).
+
+use crate::errors::{AppError, AppResult};
+use comfy_table::Table;
+use duckdb::Connection;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Name of the view combining every period view.
+pub const ALL_PERIODS_VIEW: &str = "all_periods";
+
+/// Runs `sql` against the Parquet output in `parquet_dir` and renders the result as a table.
+///
+/// Every period found by [`period_sources`] is registered as a view named after the period
+/// (quote it in SQL, e.g. `SELECT * FROM "202301"`), and [`ALL_PERIODS_VIEW`] combines them
+/// with `UNION ALL BY NAME`, so periods written with different optional columns still line
+/// up. `sql` must be a single query returning rows; every value is rendered as text.
+///
+/// # Errors
+///
+/// Returns `IoError` if `parquet_dir` holds no period output, or `InvalidInput` if DuckDB
+/// rejects a view definition or the query.
+pub fn run_query(parquet_dir: &Path, sql: &str) -> AppResult<String> {
+    let sources = period_sources(parquet_dir)?;
+    let conn = Connection::open_in_memory().map_err(query_error)?;
+
+    let mut view_sql = String::new();
+    for (period, source) in &sources {
+        view_sql.push_str(&format!(
+            "CREATE VIEW {} AS SELECT * FROM read_parquet({});\n",
+            quote_identifier(period),
+            quote_literal(&source.to_string_lossy())
+        ));
+    }
+    let union = sources
+        .keys()
+        .map(|period| format!("SELECT * FROM {}", quote_identifier(period)))
+        .collect::<Vec<_>>()
+        .join(" UNION ALL BY NAME ");
+    view_sql.push_str(&format!("CREATE VIEW {ALL_PERIODS_VIEW} AS {union};"));
+    conn.execute_batch(&view_sql).map_err(query_error)?;
+
+    // Casting every column to text lets DuckDB format dates, decimals, lists, and structs.
+    let sql = sql.trim().trim_end_matches(';');
+    let mut stmt = conn
+        .prepare(&format!("SELECT COLUMNS(*)::VARCHAR FROM ({sql})"))
+        .map_err(query_error)?;
+    let mut rows = stmt.query([]).map_err(query_error)?;
+
+    let mut table = Table::new();
+    let mut header_set = false;
+    while let Some(row) = rows.next().map_err(query_error)? {
+        let stmt = row.as_ref();
+        if !header_set {
+            table.set_header(stmt.column_names());
+            header_set = true;
+        }
+        let cells = (0..stmt.column_count())
+            .map(|i| row.get::<_, Option<String>>(i))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(query_error)?;
+        table.add_row(cells.into_iter().map(|cell| cell.unwrap_or_default()));
+    }
+    if !header_set {
+        if let Some(stmt) = rows.as_ref() {
+            table.set_header(stmt.column_names());
+        }
+    }
+    Ok(table.to_string())
+}
+
+/// Lists the period outputs in `parquet_dir`, keyed by period.
+///
+/// A concatenated `{period}.parquet` file is used when present; otherwise a `{period}/`
+/// directory of batch files is read through a `*.parquet` glob. Only all-digit period names
+/// are considered, so `{period}_lots.parquet` files are ignored.
+///
+/// # Errors
+///
+/// Returns `IoError` if the directory cannot be read or holds no period output.
+pub fn period_sources(parquet_dir: &Path) -> AppResult<BTreeMap<String, PathBuf>> {
+    let entries = std::fs::read_dir(parquet_dir).map_err(|e| {
+        AppError::IoError(format!(
+            "Failed to read Parquet directory {}: {e}",
+            parquet_dir.display()
+        ))
+    })?;
+
+    let mut sources = BTreeMap::new();
+    for entry in entries {
+        let path = entry?.path();
+        let is_file = path.is_file();
+        let name = if is_file {
+            if path.extension().and_then(|ext| ext.to_str()) != Some("parquet") {
+                continue;
+            }
+            path.file_stem()
+        } else {
+            path.file_name()
+        };
+        let Some(period) = name
+            .and_then(|name| name.to_str())
+            .filter(|name| !name.is_empty() && name.bytes().all(|b| b.is_ascii_digit()))
+            .map(str::to_string)
+        else {
+            continue;
+        };
+
+        if is_file {
+            sources.insert(period, path);
+        } else if has_parquet_files(&path) {
+            sources
+                .entry(period)
+                .or_insert_with(|| path.join("*.parquet"));
+        }
+    }
+
+    if sources.is_empty() {
+        return Err(AppError::IoError(format!(
+            "No period Parquet output in {}",
+            parquet_dir.display()
+        )));
+    }
+    Ok(sources)
+}
+
+fn has_parquet_files(dir: &Path) -> bool {
+    std::fs::read_dir(dir).is_ok_and(|entries| {
+        entries
+            .flatten()
+            .any(|entry| entry.path().extension().and_then(|ext| ext.to_str()) == Some("parquet"))
+    })
+}
+
+pub(crate) fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+pub(crate) fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+fn query_error(e: duckdb::Error) -> AppError {
+    AppError::InvalidInput(format!("Query failed: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars::prelude::*;
+    use std::fs::File;
+    use tempfile::TempDir;
+
+    fn write_parquet(path: &Path, ids: &[&str]) {
+        let mut df = df!("id" => ids, "contract_id" => ids).unwrap();
+        ParquetWriter::new(File::create(path).unwrap())
+            .finish(&mut df)
+            .unwrap();
+    }
+
+    #[test]
+    fn counts_rows_across_period_files_and_batch_directories() {
+        let tmp = TempDir::new().unwrap();
+        write_parquet(&tmp.path().join("202301.parquet"), &["a", "b"]);
+        std::fs::create_dir(tmp.path().join("202302")).unwrap();
+        write_parquet(&tmp.path().join("202302/batch_0.parquet"), &["c"]);
+        write_parquet(&tmp.path().join("202302/batch_1.parquet"), &["d"]);
+        write_parquet(&tmp.path().join("202301_lots.parquet"), &["x"]);
+
+        let sources = period_sources(tmp.path()).unwrap();
+        assert_eq!(sources.keys().collect::<Vec<_>>(), ["202301", "202302"]);
+
+        let output = run_query(tmp.path(), "SELECT count(*) AS n FROM all_periods;").unwrap();
+        assert!(output.contains(" n "), "{output}");
+        assert!(output.contains(" 4 "), "{output}");
+
+        let output = run_query(tmp.path(), r#"SELECT id FROM "202302" ORDER BY id"#).unwrap();
+        assert!(output.contains(" c ") && output.contains(" d "), "{output}");
+    }
+
+    #[test]
+    fn reports_invalid_sql_and_missing_output() {
+        let tmp = TempDir::new().unwrap();
+        assert!(matches!(
+            run_query(tmp.path(), "SELECT 1"),
+            Err(AppError::IoError(_))
+        ));
+
+        write_parquet(&tmp.path().join("202301.parquet"), &["a"]);
+        assert!(matches!(
+            run_query(tmp.path(), "SELECT * FROM missing_view"),
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+}
@@ -0,0 +1,310 @@
+use crate::downloader::validate_period_format;
+use crate::errors::{AppError, AppResult};
+use crate::models::ProcurementType;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Last successfully completed period for one procurement type.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PeriodState {
+    /// Latest period (`YYYY` or `YYYYMM`) processed by a successful run
+    pub last_completed_period: String,
+    /// Completion time in seconds since the Unix epoch
+    pub timestamp: u64,
+}
+
+/// Persistent record of successful pipeline runs, keyed by procurement type.
+///
+/// Serialized as `{"public-tenders": {"last_completed_period": "202312", "timestamp": ...}}`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunState {
+    #[serde(flatten)]
+    pub types: BTreeMap<String, PeriodState>,
+}
+
+impl RunState {
+    /// Loads the state file, returning `None` if it does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns `IoError` if the file cannot be read and `ParseError` if it is not valid JSON.
+    pub fn load(path: &Path) -> AppResult<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = fs::read_to_string(path)?;
+        let state = serde_json::from_str(&contents).map_err(|e| {
+            AppError::ParseError(format!(
+                "Failed to parse state file {}: {e}",
+                path.display()
+            ))
+        })?;
+        Ok(Some(state))
+    }
+
+    /// Writes the state file atomically (temp file + rename), creating parent directories.
+    pub fn save(&self, path: &Path) -> AppResult<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| AppError::ParseError(format!("Failed to serialize state: {e}")))?;
+        let tmp_path = path.with_extension("json.part");
+        fs::write(&tmp_path, json)?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    /// Returns the last completed period recorded for the procurement type.
+    pub fn last_completed_period(&self, proc_type: &ProcurementType) -> Option<&str> {
+        self.types
+            .get(state_key(proc_type))
+            .map(|s| s.last_completed_period.as_str())
+    }
+
+    /// Records `period` as the last completed period for the procurement type.
+    pub fn record_completion(&mut self, proc_type: &ProcurementType, period: &str) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.types.insert(
+            state_key(proc_type).to_string(),
+            PeriodState {
+                last_completed_period: period.to_string(),
+                timestamp,
+            },
+        );
+    }
+}
+
+fn state_key(proc_type: &ProcurementType) -> &'static str {
+    match proc_type {
+        ProcurementType::MinorContracts => "minor-contracts",
+        ProcurementType::PublicTenders => "public-tenders",
+        ProcurementType::AggregatedPlatforms => "aggregated-platforms",
+    }
+}
+
+/// Returns the period immediately after `period`.
+///
+/// Yearly periods advance by one year (`2023` -> `2024`) and monthly periods by one
+/// month, wrapping December into January of the next year (`202312` -> `202401`).
+///
+/// # Errors
+///
+/// Returns `InvalidInput` if the period is not `YYYY` or `YYYYMM`, or the month is not 01-12.
+pub fn next_period(period: &str) -> AppResult<String> {
+    validate_period_format(period)?;
+    if period.contains('Q') {
+        return Err(AppError::InvalidInput(format!(
+            "Expected a YYYY or YYYYMM period, got quarter: {period}"
+        )));
+    }
+    let year: u32 = period[..4].parse()?;
+    if period.len() == 4 {
+        return Ok(format!("{:04}", year + 1));
+    }
+
+    let month: u32 = period[4..].parse()?;
+    match month {
+        1..=11 => Ok(format!("{year:04}{:02}", month + 1)),
+        12 => Ok(format!("{:04}01", year + 1)),
+        _ => Err(AppError::InvalidInput(format!(
+            "Invalid month in period: {period}"
+        ))),
+    }
+}
+
+/// Returns the first available period that covers `period` or comes after it.
+///
+/// Used to turn the period after the last run into a key that exists in `links`
+/// (e.g., `2024` resolves to `202401` when the current year is published monthly). Periods
+/// are compared by `(year, month)`, not as strings: a yearly key covers all of its months,
+/// so `202306` resolves to `2023` once that year is only published as a whole.
+pub fn first_available_from(links: &BTreeMap<String, String>, period: &str) -> Option<String> {
+    let (start, _) = period_months(period)?;
+    links
+        .keys()
+        .filter_map(|key| period_months(key).map(|(first, last)| (first, last, key)))
+        .filter(|(_, last, _)| *last >= start)
+        .min_by_key(|(first, _, _)| *first)
+        .map(|(_, _, key)| key.clone())
+}
+
+/// First and last `(year, month)` covered by a `YYYY` or `YYYYMM` period; a yearly period
+/// covers January to December. `None` for anything else, quarters included.
+fn period_months(period: &str) -> Option<((u32, u32), (u32, u32))> {
+    validate_period_format(period).ok()?;
+    let year = period.get(..4)?.parse().ok()?;
+    match period.len() {
+        4 => Some(((year, 1), (year, 12))),
+        6 => {
+            let month = period[4..].parse().ok()?;
+            Some(((year, month), (year, month)))
+        }
+        _ => None,
+    }
+}
+
+/// Where a `--since-last-run` invocation should start.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum ResumePoint {
+    /// No state recorded for this procurement type: process every period.
+    FromBeginning,
+    /// Start at this available period.
+    From(String),
+    /// Every available period was already processed.
+    UpToDate,
+}
+
+/// Determines the start period for `--since-last-run` from the state file.
+///
+/// The period after the last completed one is resolved to the first available link at or
+/// after it, so a yearly period (`2023`) rolls over into the monthly listing (`202401`).
+pub(crate) fn resume_point(
+    proc_type: &ProcurementType,
+    links: &BTreeMap<String, String>,
+    state_path: &Path,
+) -> AppResult<ResumePoint> {
+    let state = match RunState::load(state_path)? {
+        Some(state) => state,
+        None => return Ok(ResumePoint::FromBeginning),
+    };
+    let last_period = match state.last_completed_period(proc_type) {
+        Some(period) => period,
+        None => return Ok(ResumePoint::FromBeginning),
+    };
+
+    let next = next_period(last_period)?;
+    Ok(match first_available_from(links, &next) {
+        Some(period) => ResumePoint::From(period),
+        None => ResumePoint::UpToDate,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn next_period_increments_month() {
+        assert_eq!(next_period("202301").unwrap(), "202302");
+        assert_eq!(next_period("202309").unwrap(), "202310");
+    }
+
+    #[test]
+    fn next_period_wraps_december() {
+        assert_eq!(next_period("202312").unwrap(), "202401");
+        assert_eq!(next_period("199912").unwrap(), "200001");
+    }
+
+    #[test]
+    fn next_period_increments_year() {
+        assert_eq!(next_period("2023").unwrap(), "2024");
+    }
+
+    #[test]
+    fn next_period_rejects_invalid_month() {
+        assert!(next_period("202300").is_err());
+        assert!(next_period("202313").is_err());
+        assert!(next_period("20231").is_err());
+    }
+
+    #[test]
+    fn first_available_from_crosses_yearly_to_monthly() {
+        let mut links = BTreeMap::new();
+        links.insert("2023".to_string(), "a".to_string());
+        links.insert("202401".to_string(), "b".to_string());
+        links.insert("202402".to_string(), "c".to_string());
+
+        assert_eq!(
+            first_available_from(&links, "2024"),
+            Some("202401".to_string())
+        );
+        assert_eq!(
+            first_available_from(&links, "202402"),
+            Some("202402".to_string())
+        );
+        assert_eq!(first_available_from(&links, "202403"), None);
+    }
+
+    #[test]
+    fn first_available_from_compares_yearly_and_monthly_keys_by_month() {
+        let mut links = BTreeMap::new();
+        links.insert("2022".to_string(), "a".to_string());
+        links.insert("2023".to_string(), "b".to_string());
+        links.insert("202401".to_string(), "c".to_string());
+        links.insert("202406".to_string(), "d".to_string());
+
+        // June 2023 is only published inside the yearly 2023 file.
+        assert_eq!(
+            first_available_from(&links, "202306"),
+            Some("2023".to_string())
+        );
+        assert_eq!(
+            first_available_from(&links, "2023"),
+            Some("2023".to_string())
+        );
+        assert_eq!(
+            first_available_from(&links, "202402"),
+            Some("202406".to_string())
+        );
+        assert_eq!(first_available_from(&links, "202407"), None);
+    }
+
+    #[test]
+    fn state_round_trips_and_missing_file_is_none() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("nested/.sppd_state.json");
+        assert!(RunState::load(&path).unwrap().is_none());
+
+        let mut state = RunState::default();
+        state.record_completion(&ProcurementType::PublicTenders, "202312");
+        state.save(&path).unwrap();
+
+        let loaded = RunState::load(&path).unwrap().unwrap();
+        assert_eq!(
+            loaded.last_completed_period(&ProcurementType::PublicTenders),
+            Some("202312")
+        );
+        assert_eq!(
+            loaded.last_completed_period(&ProcurementType::MinorContracts),
+            None
+        );
+    }
+
+    #[test]
+    fn resume_point_follows_state_file() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let state_path = tmp.path().join(".sppd_state.json");
+        let mut links = BTreeMap::new();
+        links.insert("2023".to_string(), "a".to_string());
+        links.insert("202401".to_string(), "b".to_string());
+
+        let proc_type = ProcurementType::PublicTenders;
+        assert_eq!(
+            resume_point(&proc_type, &links, &state_path).unwrap(),
+            ResumePoint::FromBeginning
+        );
+
+        let mut state = RunState::default();
+        state.record_completion(&proc_type, "2023");
+        state.save(&state_path).unwrap();
+        assert_eq!(
+            resume_point(&proc_type, &links, &state_path).unwrap(),
+            ResumePoint::From("202401".to_string())
+        );
+
+        state.record_completion(&proc_type, "202401");
+        state.save(&state_path).unwrap();
+        assert_eq!(
+            resume_point(&proc_type, &links, &state_path).unwrap(),
+            ResumePoint::UpToDate
+        );
+    }
+}
@@ -0,0 +1,268 @@
+use crate::errors::{AppError, AppResult};
+use std::path::Path;
+use std::str::FromStr;
+use tracing::level_filters::LevelFilter;
+use tracing::Subscriber;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer};
+
+/// Format of the log lines written by the tracing subscriber.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable lines (default)
+    #[default]
+    Text,
+    /// One JSON object per line with `timestamp`, `level`, `target`, the event's fields
+    /// (including `message`), and the current `span` and `spans` list
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = AppError;
+
+    /// Parses a log format name (`text`, `json`), case-insensitively.
+    fn from_str(value: &str) -> AppResult<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            other => Err(AppError::InvalidInput(format!(
+                "Unknown log format '{other}' (expected text or json)"
+            ))),
+        }
+    }
+}
+
+/// Keeps the tracing exporters alive for the duration of the program.
+///
+/// When OpenTelemetry export is enabled, dropping the guard flushes any pending spans
+/// and shuts down the tracer provider. Hold it until the pipeline has finished.
+pub struct TracingGuard {
+    #[cfg(feature = "otel")]
+    provider: Option<opentelemetry_sdk::trace::SdkTracerProvider>,
+    /// Flushes the `--log-file` writer when dropped
+    #[cfg(feature = "file-logging")]
+    _log_file: Option<tracing_appender::non_blocking::WorkerGuard>,
+}
+
+impl Drop for TracingGuard {
+    fn drop(&mut self) {
+        #[cfg(feature = "otel")]
+        if let Some(provider) = self.provider.take() {
+            if let Err(e) = provider.shutdown() {
+                eprintln!("Failed to shut down OpenTelemetry tracer provider: {e}");
+            }
+        }
+    }
+}
+
+/// Initializes the global tracing subscriber.
+///
+/// Logs are written in `log_format` with an `EnvFilter` (`default_level`, unless `RUST_LOG`
+/// is set, in which case it wins), to stdout or, with `log_file`, appended to that file through a non-blocking
+/// writer (`file-logging` feature). When `otel_endpoint` is set, spans are additionally
+/// exported to that OTLP/HTTP endpoint (e.g., `http://localhost:4318/v1/traces`) through an
+/// `OpenTelemetryLayer`.
+///
+/// # Errors
+///
+/// Returns `InvalidInput` if an endpoint or log file is given but the binary was built
+/// without the `otel` or `file-logging` feature, or if the OTLP exporter cannot be created,
+/// and `IoError` if the log file cannot be opened.
+pub fn init_tracing(
+    otel_endpoint: Option<&str>,
+    log_format: LogFormat,
+    log_file: Option<&Path>,
+    default_level: LevelFilter,
+) -> AppResult<TracingGuard> {
+    #[cfg(feature = "file-logging")]
+    let (writer, log_file_guard) = match log_file {
+        Some(path) => {
+            let file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|e| {
+                    AppError::IoError(format!("Failed to open log file {}: {e}", path.display()))
+                })?;
+            let (writer, guard) = tracing_appender::non_blocking(file);
+            (Some(BoxMakeWriter::new(writer)), Some(guard))
+        }
+        None => (None, None),
+    };
+    #[cfg(not(feature = "file-logging"))]
+    let writer: Option<BoxMakeWriter> = match log_file {
+        Some(_) => {
+            return Err(AppError::InvalidInput(
+                "--log-file requires sppd-cli to be built with the `file-logging` feature".into(),
+            ))
+        }
+        None => None,
+    };
+
+    let registry = tracing_subscriber::registry()
+        .with(env_filter(default_level))
+        .with(fmt_layer(log_format, writer));
+
+    #[cfg(feature = "otel")]
+    {
+        use opentelemetry::trace::TracerProvider as _;
+
+        let provider = otel_endpoint.map(build_tracer_provider).transpose()?;
+        let otel_layer = provider
+            .as_ref()
+            .map(|p| tracing_opentelemetry::layer().with_tracer(p.tracer("sppd-cli")));
+        registry.with(otel_layer).init();
+
+        Ok(TracingGuard {
+            provider,
+            #[cfg(feature = "file-logging")]
+            _log_file: log_file_guard,
+        })
+    }
+
+    #[cfg(not(feature = "otel"))]
+    {
+        if otel_endpoint.is_some() {
+            return Err(AppError::InvalidInput(
+                "--otel-endpoint requires sppd-cli to be built with the `otel` feature".into(),
+            ));
+        }
+        registry.init();
+
+        Ok(TracingGuard {
+            #[cfg(feature = "file-logging")]
+            _log_file: log_file_guard,
+        })
+    }
+}
+
+/// Filter from `RUST_LOG` when it is set and valid, otherwise enabling `default_level` and
+/// more severe events.
+fn env_filter(default_level: LevelFilter) -> EnvFilter {
+    EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::default().add_directive(default_level.into()))
+}
+
+/// Builds the log line layer in `format`, writing to `writer` (without ANSI colors) or to
+/// stdout when it is `None`.
+fn fmt_layer<S>(format: LogFormat, writer: Option<BoxMakeWriter>) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    let layer = tracing_subscriber::fmt::layer();
+    let layer = match writer {
+        Some(writer) => layer.with_ansi(false).with_writer(writer),
+        None => layer.with_writer(BoxMakeWriter::new(std::io::stdout)),
+    };
+    match format {
+        LogFormat::Text => layer.boxed(),
+        LogFormat::Json => layer
+            .json()
+            .flatten_event(true)
+            .with_current_span(true)
+            .with_span_list(true)
+            .boxed(),
+    }
+}
+
+#[cfg(feature = "otel")]
+fn build_tracer_provider(endpoint: &str) -> AppResult<opentelemetry_sdk::trace::SdkTracerProvider> {
+    use opentelemetry_otlp::WithExportConfig;
+
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .with_endpoint(endpoint)
+        .build()
+        .map_err(|e| AppError::InvalidInput(format!("Failed to create OTLP exporter: {e}")))?;
+
+    let resource = opentelemetry_sdk::Resource::builder()
+        .with_service_name(env!("CARGO_PKG_NAME"))
+        .build();
+
+    Ok(opentelemetry_sdk::trace::SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(resource)
+        .build())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[cfg(not(feature = "otel"))]
+    #[test]
+    fn otel_endpoint_requires_feature() {
+        let err = init_tracing(
+            Some("http://localhost:4318/v1/traces"),
+            LogFormat::Text,
+            None,
+            LevelFilter::INFO,
+        );
+        assert!(err.is_err());
+    }
+
+    #[cfg(not(feature = "file-logging"))]
+    #[test]
+    fn log_file_requires_feature() {
+        let err = init_tracing(
+            None,
+            LogFormat::Json,
+            Some(Path::new("sppd.log")),
+            LevelFilter::INFO,
+        );
+        assert!(matches!(err, Err(AppError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn log_format_parses_names() {
+        assert_eq!("JSON".parse::<LogFormat>().unwrap(), LogFormat::Json);
+        assert_eq!(" text ".parse::<LogFormat>().unwrap(), LogFormat::Text);
+        assert!("yaml".parse::<LogFormat>().is_err());
+    }
+
+    #[test]
+    fn json_format_writes_one_object_per_line() {
+        let buffer = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&buffer);
+        let writer = BoxMakeWriter::new(move || SharedBuffer(Arc::clone(&sink)));
+        let subscriber =
+            tracing_subscriber::registry().with(fmt_layer(LogFormat::Json, Some(writer)));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("parse_xmls", procurement_type = "Public Tenders");
+            let _entered = span.enter();
+            tracing::info!(periods_count = 3, "Starting XML parsing");
+        });
+
+        let output = String::from_utf8(buffer.lock().unwrap().clone()).unwrap();
+        let lines: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 1);
+        let line = &lines[0];
+        assert!(line["timestamp"].is_string());
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(line["target"], module_path!());
+        assert_eq!(line["message"], "Starting XML parsing");
+        assert_eq!(line["periods_count"], 3);
+        assert_eq!(line["span"]["name"], "parse_xmls");
+        assert_eq!(line["span"]["procurement_type"], "Public Tenders");
+        assert_eq!(line["spans"][0]["name"], "parse_xmls");
+    }
+
+    /// `io::Write` handle appending to a shared buffer.
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+}
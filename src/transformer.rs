@@ -0,0 +1,338 @@
+//! Post-processing hooks applied to parsed entries before they are written.
+//!
+//! A [`Transformer`] receives each batch of entries after amount normalization and
+//! `dedupe_by_id`, and may rewrite or drop entries before the batch is serialized. Library
+//! users pass their own to [`crate::parser::parse_xmls`]; the built-in ones are enabled with
+//! `filter_min_amount` and `null_empty_strings` (see [`from_config`]).
+
+use crate::config::ResolvedConfig;
+use crate::errors::AppResult;
+use crate::models::Entry;
+
+/// Rewrites a batch of parsed entries in place before serialization.
+///
+/// Batches of different periods may be transformed concurrently, hence `Send + Sync`.
+pub trait Transformer: Send + Sync {
+    /// Transforms `entries`, which may be modified, reordered, or filtered.
+    ///
+    /// # Errors
+    ///
+    /// An error fails the period being parsed, like a parsing error.
+    fn transform(&self, entries: &mut Vec<Entry>) -> AppResult<()>;
+}
+
+/// Applies each transformer in order.
+impl Transformer for Vec<Box<dyn Transformer>> {
+    fn transform(&self, entries: &mut Vec<Entry>) -> AppResult<()> {
+        for transformer in self {
+            transformer.transform(entries)?;
+        }
+        Ok(())
+    }
+}
+
+/// Drops entries whose `project_total_amount` is below `threshold`.
+///
+/// Entries without an amount, or with one that does not parse as a number, are kept. Amounts
+/// written with a decimal comma only parse once rewritten by `normalize_amounts`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FilterByMinAmount {
+    /// Smallest project total amount kept
+    pub threshold: f64,
+}
+
+impl Transformer for FilterByMinAmount {
+    fn transform(&self, entries: &mut Vec<Entry>) -> AppResult<()> {
+        entries.retain(|entry| {
+            entry
+                .project_total_amount
+                .as_deref()
+                .and_then(|amount| amount.trim().parse::<f64>().ok())
+                .is_none_or(|amount| amount >= self.threshold)
+        });
+        Ok(())
+    }
+}
+
+/// Replaces empty strings (`Some("")`) with `None` in every optional text field of an
+/// entry, including those of its lots, tender results, and awarding criteria, and drops
+/// empty `categories` terms.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NullifyEmptyStrings;
+
+impl Transformer for NullifyEmptyStrings {
+    fn transform(&self, entries: &mut Vec<Entry>) -> AppResult<()> {
+        for entry in entries {
+            for field in entry_string_fields(entry) {
+                if field.as_deref() == Some("") {
+                    *field = None;
+                }
+            }
+            entry.categories.retain(|term| !term.is_empty());
+        }
+        Ok(())
+    }
+}
+
+/// Every `Option<String>` field of `entry` and its nested records.
+fn entry_string_fields(entry: &mut Entry) -> Vec<&mut Option<String>> {
+    let mut fields = vec![
+        &mut entry.id,
+        &mut entry.title,
+        &mut entry.link,
+        &mut entry.summary,
+        &mut entry.updated,
+        &mut entry.author,
+        &mut entry.status.code,
+        &mut entry.status.list_uri,
+        &mut entry.contract_id,
+        &mut entry.contracting_party_name,
+        &mut entry.contracting_party_id,
+        &mut entry.contracting_party_website,
+        &mut entry.contracting_party_type_code,
+        &mut entry.contracting_party_type_code_list_uri,
+        &mut entry.contracting_party_activity_code,
+        &mut entry.contracting_party_activity_code_list_uri,
+        &mut entry.contracting_party_city,
+        &mut entry.contracting_party_zip,
+        &mut entry.contracting_party_country_code,
+        &mut entry.contracting_party_country_code_list_uri,
+        &mut entry.project_name,
+        &mut entry.project_type_code,
+        &mut entry.project_type_code_list_uri,
+        &mut entry.project_sub_type_code,
+        &mut entry.project_sub_type_code_list_uri,
+        &mut entry.project_total_amount,
+        &mut entry.project_total_currency,
+        &mut entry.project_tax_exclusive_amount,
+        &mut entry.project_tax_exclusive_currency,
+        &mut entry.project_cpv_code,
+        &mut entry.project_cpv_code_list_uri,
+        &mut entry.project_country_code,
+        &mut entry.project_country_code_list_uri,
+        &mut entry.project_duration_measure,
+        &mut entry.project_duration_unit,
+        &mut entry.project_country_subentity,
+        &mut entry.project_country_subentity_code,
+        &mut entry.project_country_subentity_code_list_uri,
+        &mut entry.terms_funding_program.code,
+        &mut entry.terms_funding_program.list_uri,
+        &mut entry.process_start_date,
+        &mut entry.process_end_date,
+        &mut entry.process_open_date,
+        &mut entry.process_procedure_code,
+        &mut entry.process_procedure_code_list_uri,
+        &mut entry.process_urgency_code,
+        &mut entry.process_urgency_code_list_uri,
+        &mut entry.cfs_raw_xml,
+        &mut entry.feed_metadata.feed_id,
+        &mut entry.feed_metadata.feed_title,
+        &mut entry.feed_metadata.feed_updated,
+    ];
+    for lot in &mut entry.project_lots {
+        fields.extend([
+            &mut lot.id,
+            &mut lot.name,
+            &mut lot.total_amount,
+            &mut lot.total_currency,
+            &mut lot.tax_exclusive_amount,
+            &mut lot.tax_exclusive_currency,
+            &mut lot.cpv_code,
+            &mut lot.cpv_code_list_uri,
+            &mut lot.country_code,
+            &mut lot.country_code_list_uri,
+            &mut lot.country_subentity_code,
+            &mut lot.country_subentity_code_list_uri,
+        ]);
+    }
+    for result in &mut entry.tender_results {
+        fields.extend([
+            &mut result.result_id,
+            &mut result.result_lot_id,
+            &mut result.result_code,
+            &mut result.result_code_list_uri,
+            &mut result.result_description,
+            &mut result.result_winning_party,
+            &mut result.result_sme_awarded_indicator,
+            &mut result.result_award_date,
+            &mut result.result_tax_exclusive_amount,
+            &mut result.result_tax_exclusive_currency,
+            &mut result.result_payable_amount,
+            &mut result.result_payable_currency,
+            &mut result.result_received_tender_quantity,
+            &mut result.result_lower_tender_amount,
+            &mut result.result_lower_tender_currency,
+            &mut result.result_higher_tender_amount,
+            &mut result.result_higher_tender_currency,
+        ]);
+    }
+    for criterion in &mut entry.awarding_criteria {
+        fields.extend([
+            &mut criterion.description,
+            &mut criterion.weight,
+            &mut criterion.type_code,
+        ]);
+    }
+    fields
+}
+
+/// Builds the built-in transformers enabled in `config`: [`NullifyEmptyStrings`] with
+/// `null_empty_strings`, then [`FilterByMinAmount`] with `filter_min_amount`.
+///
+/// Returns `None` when neither is enabled.
+pub fn from_config(config: &ResolvedConfig) -> Option<Box<dyn Transformer>> {
+    let mut transformers: Vec<Box<dyn Transformer>> = Vec::new();
+    if config.null_empty_strings {
+        transformers.push(Box::new(NullifyEmptyStrings));
+    }
+    if let Some(threshold) = config.filter_min_amount {
+        transformers.push(Box::new(FilterByMinAmount { threshold }));
+    }
+    match transformers.len() {
+        0 => None,
+        1 => transformers.pop(),
+        _ => Some(Box::new(transformers)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ProcurementProjectLot, TenderResultRow};
+
+    fn entry(id: &str, amount: Option<&str>) -> Entry {
+        Entry {
+            id: Some(id.to_string()),
+            project_total_amount: amount.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    fn ids(entries: &[Entry]) -> Vec<&str> {
+        entries
+            .iter()
+            .filter_map(|entry| entry.id.as_deref())
+            .collect()
+    }
+
+    #[test]
+    fn filter_by_min_amount_drops_entries_below_threshold() {
+        let mut entries = vec![
+            entry("low", Some("999.99")),
+            entry("equal", Some("1000")),
+            entry("high", Some(" 25000.50 ")),
+            entry("missing", None),
+            entry("unparsed", Some("1.234,56")),
+        ];
+
+        FilterByMinAmount { threshold: 1000.0 }
+            .transform(&mut entries)
+            .unwrap();
+
+        assert_eq!(ids(&entries), ["equal", "high", "missing", "unparsed"]);
+    }
+
+    #[test]
+    fn nullify_empty_strings_clears_top_level_and_nested_fields() {
+        let mut entries = vec![Entry {
+            title: Some(String::new()),
+            project_name: Some("Obras".to_string()),
+            contracting_party_zip: Some(String::new()),
+            project_lots: vec![ProcurementProjectLot {
+                name: Some(String::new()),
+                cpv_code: Some("45000000".to_string()),
+                ..Default::default()
+            }],
+            tender_results: vec![TenderResultRow {
+                result_winning_party: Some(String::new()),
+                ..Default::default()
+            }],
+            ..entry("1", Some(""))
+        }];
+
+        NullifyEmptyStrings.transform(&mut entries).unwrap();
+
+        let entry = &entries[0];
+        assert_eq!(entry.id.as_deref(), Some("1"));
+        assert_eq!(entry.title, None);
+        assert_eq!(entry.project_total_amount, None);
+        assert_eq!(entry.contracting_party_zip, None);
+        assert_eq!(entry.project_name.as_deref(), Some("Obras"));
+        assert_eq!(entry.project_lots[0].name, None);
+        assert_eq!(entry.project_lots[0].cpv_code.as_deref(), Some("45000000"));
+        assert_eq!(entry.tender_results[0].result_winning_party, None);
+    }
+
+    /// Replaces every null in `value` with an empty string.
+    fn fill_nulls(value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::Null => *value = serde_json::Value::String(String::new()),
+            serde_json::Value::Array(items) => items.iter_mut().for_each(fill_nulls),
+            serde_json::Value::Object(fields) => fields.values_mut().for_each(fill_nulls),
+            _ => {}
+        }
+    }
+
+    /// JSON paths of the empty strings left in `value`.
+    fn empty_string_paths(value: &serde_json::Value, path: String, paths: &mut Vec<String>) {
+        match value {
+            serde_json::Value::String(text) if text.is_empty() => paths.push(path),
+            serde_json::Value::Array(items) => {
+                for (i, item) in items.iter().enumerate() {
+                    empty_string_paths(item, format!("{path}[{i}]"), paths);
+                }
+            }
+            serde_json::Value::Object(fields) => {
+                for (name, field) in fields {
+                    empty_string_paths(field, format!("{path}.{name}"), paths);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn nullify_empty_strings_covers_every_text_field() {
+        // Every `Option<String>` serializes as null; setting them all to "" through serde
+        // catches fields added to the models but not to `entry_string_fields`.
+        let entry = Entry {
+            categories: vec![String::new(), "Obras".to_string()],
+            project_lots: vec![ProcurementProjectLot::default()],
+            tender_results: vec![TenderResultRow::default()],
+            awarding_criteria: vec![Default::default()],
+            ..Default::default()
+        };
+        let mut value = serde_json::to_value(&entry).unwrap();
+        fill_nulls(&mut value);
+        let mut entries = vec![serde_json::from_value(value).unwrap()];
+
+        NullifyEmptyStrings.transform(&mut entries).unwrap();
+
+        let mut paths = Vec::new();
+        empty_string_paths(
+            &serde_json::to_value(&entries[0]).unwrap(),
+            "entry".to_string(),
+            &mut paths,
+        );
+        assert!(paths.is_empty(), "empty strings left in {paths:?}");
+        assert_eq!(entries[0].categories, ["Obras"]);
+    }
+
+    #[test]
+    fn from_config_chains_enabled_transformers() {
+        assert!(from_config(&ResolvedConfig::default()).is_none());
+
+        let config = ResolvedConfig {
+            null_empty_strings: true,
+            filter_min_amount: Some(100.0),
+            ..Default::default()
+        };
+        let transformer = from_config(&config).unwrap();
+        let mut entries = vec![entry("empty", Some("")), entry("low", Some("50"))];
+        transformer.transform(&mut entries).unwrap();
+
+        // The empty amount is nulled first, so the entry is kept by the filter.
+        assert_eq!(ids(&entries), ["empty"]);
+        assert_eq!(entries[0].project_total_amount, None);
+    }
+}
@@ -0,0 +1,264 @@
+use crate::utils::{format_duration, mb_from_bytes};
+use std::io::{IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Width of the bar itself, in characters.
+const BAR_WIDTH: usize = 30;
+
+/// Minimum time between two redraws, so per-file ticks from many threads stay cheap.
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Terminal progress bar drawn on stderr.
+///
+/// Cloning is cheap and every clone drives the same bar, so a handle can be moved into
+/// rayon closures and ticked from worker threads. A hidden bar (see [`create_progress_bar`])
+/// keeps counting but never draws.
+#[derive(Clone)]
+pub struct ProgressBar {
+    inner: Arc<Inner>,
+}
+
+/// What the position of a bar counts, which decides how it is rendered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Unit {
+    /// Items such as files, shown as `{position}/{total}`
+    Steps,
+    /// Bytes, shown as `{position} MB/{total} MB`
+    Bytes,
+}
+
+struct Inner {
+    total: u64,
+    unit: Unit,
+    position: AtomicU64,
+    message: Mutex<String>,
+    visible: bool,
+    started: Instant,
+    last_draw: Mutex<Option<Instant>>,
+    finished: AtomicBool,
+}
+
+/// Creates a progress bar of `total` steps.
+///
+/// The bar is hidden when `quiet` is set or when stdout or stderr is not a terminal
+/// (cron jobs, CI logs, redirected output), so no control characters end up in log files.
+pub fn create_progress_bar(total: u64, quiet: bool) -> ProgressBar {
+    ProgressBar::new(total, Unit::Steps, progress_bar_visible(quiet))
+}
+
+/// Creates a progress bar of `total_bytes` bytes, advanced by [`ProgressBar::inc`] with the
+/// size of each chunk received and rendered in megabytes.
+///
+/// It is hidden under the same conditions as [`create_progress_bar`].
+pub fn create_byte_progress_bar(total_bytes: u64, quiet: bool) -> ProgressBar {
+    ProgressBar::new(total_bytes, Unit::Bytes, progress_bar_visible(quiet))
+}
+
+/// Whether a bar created with `quiet` would be drawn, so that work done only to feed the
+/// bar (such as looking up download sizes) can be skipped when it would not be.
+pub fn progress_bar_visible(quiet: bool) -> bool {
+    !quiet && std::io::stdout().is_terminal() && std::io::stderr().is_terminal()
+}
+
+impl ProgressBar {
+    fn new(total: u64, unit: Unit, visible: bool) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                total,
+                unit,
+                position: AtomicU64::new(0),
+                message: Mutex::new(String::new()),
+                visible,
+                started: Instant::now(),
+                last_draw: Mutex::new(None),
+                finished: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    /// Advances the bar by `delta` steps.
+    pub fn inc(&self, delta: u64) {
+        self.inner.position.fetch_add(delta, Ordering::Relaxed);
+        self.inner.draw(false);
+    }
+
+    /// Moves the bar back by `delta` steps (without going below zero), e.g. to discard the
+    /// bytes of a failed download attempt that is about to be retried.
+    pub fn dec(&self, delta: u64) {
+        let _ =
+            self.inner
+                .position
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |position| {
+                    Some(position.saturating_sub(delta))
+                });
+        self.inner.draw(false);
+    }
+
+    /// Whether the position counts bytes (see [`create_byte_progress_bar`]).
+    pub fn counts_bytes(&self) -> bool {
+        self.inner.unit == Unit::Bytes
+    }
+
+    /// Current position of the bar.
+    #[cfg(test)]
+    pub(crate) fn position(&self) -> u64 {
+        self.inner.position.load(Ordering::Relaxed)
+    }
+
+    /// Replaces the text shown before the bar (e.g. the current period and batch).
+    pub fn set_message(&self, message: impl Into<String>) {
+        *self.inner.lock_message() = message.into();
+        self.inner.draw(true);
+    }
+
+    /// Draws the final state and moves to a new line. Called automatically on drop.
+    pub fn finish(&self) {
+        self.inner.finish();
+    }
+}
+
+impl Inner {
+    fn lock_message(&self) -> std::sync::MutexGuard<'_, String> {
+        self.message.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn draw(&self, force: bool) {
+        if !self.visible || self.finished.load(Ordering::Relaxed) {
+            return;
+        }
+        {
+            let mut last_draw = self.last_draw.lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            if !force && last_draw.is_some_and(|t| now.duration_since(t) < REDRAW_INTERVAL) {
+                return;
+            }
+            *last_draw = Some(now);
+        }
+        let line = render_line(
+            &self.lock_message(),
+            self.position.load(Ordering::Relaxed),
+            self.total,
+            self.unit,
+            self.started.elapsed(),
+        );
+        let mut stderr = std::io::stderr().lock();
+        let _ = write!(stderr, "\r\x1b[2K{line}");
+        let _ = stderr.flush();
+    }
+
+    fn finish(&self) {
+        if self.finished.load(Ordering::Relaxed) {
+            return;
+        }
+        self.draw(true);
+        if self.finished.swap(true, Ordering::Relaxed) {
+            return;
+        }
+        if self.visible && self.last_draw.lock().is_ok_and(|t| t.is_some()) {
+            let _ = writeln!(std::io::stderr());
+        }
+    }
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+/// Formats one bar line: `{message} [=====>    ] {position}/{total} ({percent}%) {elapsed}`,
+/// with the position and total in megabytes (`12.5 MB/40.0 MB`) for a byte bar.
+fn render_line(message: &str, position: u64, total: u64, unit: Unit, elapsed: Duration) -> String {
+    let position = position.min(total);
+    let filled = (position as usize * BAR_WIDTH)
+        .checked_div(total as usize)
+        .unwrap_or(BAR_WIDTH);
+    let percent = (position * 100).checked_div(total).unwrap_or(100);
+    let mut bar = "=".repeat(filled);
+    if filled < BAR_WIDTH {
+        bar.push('>');
+        bar.push_str(&" ".repeat(BAR_WIDTH - filled - 1));
+    }
+    let prefix = if message.is_empty() {
+        String::new()
+    } else {
+        format!("{message} ")
+    };
+    let counts = match unit {
+        Unit::Steps => format!("{position}/{total}"),
+        Unit::Bytes => format!(
+            "{:.1} MB/{:.1} MB",
+            mb_from_bytes(position),
+            mb_from_bytes(total)
+        ),
+    };
+    format!(
+        "{prefix}[{bar}] {counts} ({percent}%) {}",
+        format_duration(elapsed)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_line_scales_bar_to_total() {
+        let line = render_line(
+            "202401 batch 1/4",
+            15,
+            60,
+            Unit::Steps,
+            Duration::from_secs(61),
+        );
+        assert_eq!(
+            line,
+            "202401 batch 1/4 [=======>                      ] 15/60 (25%) 00:01:01"
+        );
+        let done = render_line("", 60, 60, Unit::Steps, Duration::ZERO);
+        assert!(done.starts_with(&format!("[{}] 60/60 (100%)", "=".repeat(BAR_WIDTH))));
+    }
+
+    #[test]
+    fn render_line_shows_megabytes_for_byte_bars() {
+        let line = render_line(
+            "",
+            3 * 1_048_576,
+            12 * 1_048_576,
+            Unit::Bytes,
+            Duration::ZERO,
+        );
+        assert!(line.ends_with("] 3.0 MB/12.0 MB (25%) 00:00:00"), "{line}");
+    }
+
+    #[test]
+    fn dec_moves_the_bar_back_without_underflow() {
+        let bar = create_byte_progress_bar(100, true);
+        assert!(bar.counts_bytes());
+        bar.inc(40);
+        bar.dec(15);
+        assert_eq!(bar.position(), 25);
+        bar.dec(50);
+        assert_eq!(bar.position(), 0);
+    }
+
+    #[test]
+    fn quiet_bar_counts_ticks_from_clones_across_threads() {
+        let bar = create_progress_bar(100, true);
+        std::thread::scope(|s| {
+            for _ in 0..4 {
+                let bar = bar.clone();
+                s.spawn(move || {
+                    for _ in 0..25 {
+                        bar.inc(1);
+                    }
+                });
+            }
+        });
+        bar.set_message("202401");
+        bar.finish();
+        assert_eq!(bar.inner.position.load(Ordering::Relaxed), 100);
+    }
+}
@@ -1,3 +1,8 @@
+use crate::errors::{AppError, AppResult};
+use sha2::Digest;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
 use std::time::Duration;
 
 /// Formats a `Duration` as `HH:MM:SS`.
@@ -19,6 +24,38 @@ pub fn round_two_decimals(value: f64) -> f64 {
     (value * 100.0).round() / 100.0
 }
 
+/// Collapses every run of ASCII whitespace in `s` to a single space and trims both ends.
+///
+/// Other Unicode whitespace (e.g. non-breaking spaces) is kept as is.
+pub fn normalize_whitespace(s: &str) -> String {
+    s.split_ascii_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Returns the lowercase hex digest of the file at `path` with the hash function `D`
+/// (e.g. `sha2::Sha256` or `md5::Md5`).
+pub(crate) fn file_digest_hex<D: Digest>(path: &Path) -> AppResult<String> {
+    let mut file = File::open(path).map_err(|e| {
+        AppError::IoError(format!(
+            "Failed to open {} for hashing: {e}",
+            path.display()
+        ))
+    })?;
+    let mut hasher = D::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -46,4 +83,26 @@ mod tests {
         assert_eq!(round_two_decimals(1.234), 1.23);
         assert_eq!(round_two_decimals(1.235), 1.24);
     }
+
+    #[test]
+    fn normalize_whitespace_collapses_multi_line_text() {
+        assert_eq!(
+            normalize_whitespace("\n\t\tSuministro de\n\t\tmaterial  de oficina\n\t"),
+            "Suministro de material de oficina"
+        );
+        assert_eq!(normalize_whitespace("  \r\n "), "");
+    }
+
+    #[test]
+    fn normalize_whitespace_collapses_tabs() {
+        assert_eq!(normalize_whitespace("Lote\t1\t\tObras"), "Lote 1 Obras");
+    }
+
+    #[test]
+    fn normalize_whitespace_keeps_unicode_whitespace() {
+        assert_eq!(
+            normalize_whitespace(" Año\u{a0}2024 \n Cádiz\u{2003}"),
+            "Año\u{a0}2024 Cádiz\u{2003}"
+        );
+    }
 }
@@ -0,0 +1,57 @@
+//! End-to-end test for the `check` subcommand.
+//!
+//! A Parquet fixture is produced by running `sppd-cli parse` on a small feed, then
+//! `sppd-cli check` is run against it before and after adding a zero-row copy of it.
+
+use polars::prelude::*;
+use std::path::Path;
+use std::process::{Command, Output};
+
+fn sppd_cli(args: &[&str], data_dir: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_sppd-cli"))
+        .args(args)
+        .arg("--data-dir")
+        .arg(data_dir)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn check_reports_valid_output_and_fails_on_empty_file() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let period_dir = tmp.path().join("tmp/pt/202401");
+    std::fs::create_dir_all(&period_dir).unwrap();
+    std::fs::write(
+        period_dir.join("feed.atom"),
+        "<feed><entry><id>check-1</id></entry></feed>",
+    )
+    .unwrap();
+
+    let parse = sppd_cli(&["parse", "-t", "pt"], tmp.path());
+    assert!(parse.status.success(), "parse failed: {parse:?}");
+
+    let check = sppd_cli(&["check", "-t", "pt"], tmp.path());
+    let stdout = String::from_utf8_lossy(&check.stdout);
+    assert!(check.status.success(), "check failed: {check:?}");
+    assert!(stdout.contains("1 files, 0 errors"), "{stdout}");
+
+    let fixture = walkdir::WalkDir::new(tmp.path().join("parquet/pt"))
+        .into_iter()
+        .flatten()
+        .find(|entry| entry.path().extension().is_some_and(|e| e == "parquet"))
+        .unwrap()
+        .into_path();
+    let mut empty = ParquetReader::new(std::fs::File::open(&fixture).unwrap())
+        .finish()
+        .unwrap()
+        .head(Some(0));
+    ParquetWriter::new(std::fs::File::create(tmp.path().join("parquet/pt/empty.parquet")).unwrap())
+        .finish(&mut empty)
+        .unwrap();
+
+    let check = sppd_cli(&["check", "-t", "pt"], tmp.path());
+    let stdout = String::from_utf8_lossy(&check.stdout);
+    assert!(!check.status.success());
+    assert!(stdout.contains("2 files, 1 errors"), "{stdout}");
+    assert!(stdout.contains("ERROR: no rows"), "{stdout}");
+}
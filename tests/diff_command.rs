@@ -0,0 +1,145 @@
+//! End-to-end test for the `diff` subcommand.
+//!
+//! Two small period fixtures are written with Polars, one as a concatenated
+//! `{period}.parquet` file and one as a `{period}/` batch directory, and compared.
+
+use polars::prelude::*;
+use std::path::Path;
+use std::process::{Command, Output};
+
+/// Writes rows of `(contract_id, updated, project.total_amount)` to `path`.
+fn write_fixture(path: &Path, rows: &[(Option<&str>, &str, Option<&str>)]) {
+    let project = DataFrame::new(vec![Series::new(
+        "total_amount",
+        rows.iter().map(|row| row.2).collect::<Vec<_>>(),
+    )])
+    .unwrap()
+    .into_struct("project")
+    .into_series();
+    let mut df = DataFrame::new(vec![
+        Series::new(
+            "contract_id",
+            rows.iter().map(|row| row.0).collect::<Vec<_>>(),
+        ),
+        Series::new("updated", rows.iter().map(|row| row.1).collect::<Vec<_>>()),
+        project,
+    ])
+    .unwrap();
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    ParquetWriter::new(std::fs::File::create(path).unwrap())
+        .finish(&mut df)
+        .unwrap();
+}
+
+fn sppd_cli(args: &[&str], data_dir: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_sppd-cli"))
+        .args(args)
+        .arg("--data-dir")
+        .arg(data_dir)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn diff_counts_new_removed_and_changed_contracts() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let parquet_dir = tmp.path().join("parquet/pt");
+    write_fixture(
+        &parquet_dir.join("202401.parquet"),
+        &[
+            (Some("C-1"), "2024-01-02", Some("100")),
+            // Only the latest row of a contract is compared.
+            (Some("C-2"), "2024-01-01", Some("250")),
+            (Some("C-2"), "2024-01-05", Some("200")),
+            (Some("C-3"), "2024-01-03", Some("300")),
+            (None, "2024-01-04", Some("999")),
+        ],
+    );
+    write_fixture(
+        &parquet_dir.join("202402/batch_0.parquet"),
+        &[
+            (Some("C-1"), "2024-02-01", Some("100.00")),
+            (Some("C-2"), "2024-02-01", Some("250")),
+        ],
+    );
+    write_fixture(
+        &parquet_dir.join("202402/batch_1.parquet"),
+        &[(Some("C-4"), "2024-02-02", None)],
+    );
+    let diff_path = tmp.path().join("out/diff.parquet");
+
+    let output = sppd_cli(
+        &["diff", "-t", "pt", "--from", "202401", "--to", "202402"],
+        tmp.path(),
+    );
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success(), "diff failed: {output:?}");
+    assert!(stdout.contains("New contracts      1"), "{stdout}");
+    assert!(stdout.contains("Removed contracts  1"), "{stdout}");
+    assert!(stdout.contains("Changed contracts  1"), "{stdout}");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_sppd-cli"))
+        .args([
+            "diff",
+            "--from",
+            "202401",
+            "--to",
+            "202402",
+            "--output-path",
+        ])
+        .arg(&diff_path)
+        .arg("--output-dir")
+        .arg(tmp.path().join("parquet"))
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "diff failed: {output:?}");
+
+    let df = ParquetReader::new(std::fs::File::open(&diff_path).unwrap())
+        .finish()
+        .unwrap();
+    let strings = |name: &str| -> Vec<String> {
+        df.column(name)
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .map(|v| v.unwrap().to_string())
+            .collect()
+    };
+    let amounts = |name: &str| -> Vec<Option<f64>> {
+        df.column(name)
+            .unwrap()
+            .f64()
+            .unwrap()
+            .into_iter()
+            .collect()
+    };
+    assert_eq!(strings("contract_id"), ["C-2", "C-4", "C-3"]);
+    assert_eq!(strings("diff_type"), ["changed", "new", "removed"]);
+    assert_eq!(
+        amounts("from_total_amount"),
+        [Some(200.0), None, Some(300.0)]
+    );
+    assert_eq!(amounts("to_total_amount"), [Some(250.0), None, None]);
+}
+
+#[test]
+fn diff_reports_a_missing_period() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    write_fixture(
+        &tmp.path().join("parquet/pt/202401.parquet"),
+        &[(Some("C-1"), "2024-01-01", Some("1"))],
+    );
+
+    let output = sppd_cli(
+        &["diff", "-t", "pt", "--from", "202401", "--to", "202403"],
+        tmp.path(),
+    );
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("No Parquet output for period 202403"),
+        "{stderr}"
+    );
+}
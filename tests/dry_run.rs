@@ -0,0 +1,61 @@
+//! End-to-end test for `sppd-cli cli --dry-run`.
+//!
+//! Archives come from a local `--source-dir` so no network access is needed.
+
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+
+fn write_period_zip(path: &Path, id: &str) {
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+    zip.start_file("feed.atom", zip::write::FileOptions::default())
+        .unwrap();
+    write!(zip, "<feed><entry><id>{id}</id></entry></feed>").unwrap();
+    zip.finish().unwrap();
+}
+
+#[test]
+fn dry_run_prints_plan_and_leaves_data_dir_untouched() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let source_dir = tmp.path().join("mirror");
+    std::fs::create_dir_all(&source_dir).unwrap();
+    for period in ["202401", "202402"] {
+        write_period_zip(
+            &source_dir.join(format!(
+                "licitacionesPerfilesContratanteCompleto3_{period}.zip"
+            )),
+            &format!("entry-{period}"),
+        );
+    }
+    let data_dir = tmp.path().join("data");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_sppd-cli"))
+        .args(["cli", "-t", "pt", "-s", "202401", "-e", "202402", "--quiet"])
+        .args(["--dry-run", "--concat-batches", "--source-dir"])
+        .arg(&source_dir)
+        .arg("--data-dir")
+        .arg(&data_dir)
+        .current_dir(tmp.path())
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "cli failed: {output:?}");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    // Logs share stdout with the plan.
+    assert!(
+        stdout.contains("Dry run: Public Tenders, 2 periods"),
+        "{stdout}"
+    );
+    let rows: Vec<&str> = stdout
+        .lines()
+        .filter(|line| line.trim_start().starts_with("2024"))
+        .collect();
+    assert_eq!(rows.len(), 2, "{stdout}");
+    for row in rows {
+        assert!(
+            row.contains("yes (0 MB)") && row.ends_with("(new)"),
+            "{row}"
+        );
+    }
+    assert!(!data_dir.exists());
+}
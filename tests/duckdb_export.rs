@@ -0,0 +1,105 @@
+//! Loads parsed periods into a DuckDB database with `duckdb_path` and checks the tables
+//! with SQL.
+#![cfg(feature = "duckdb")]
+
+use duckdb::Connection;
+use sppd_cli::models::ProcurementType;
+use sppd_cli::pipeline::{run, PipelineOptions};
+use std::io::Write;
+use std::path::Path;
+
+const SAMPLE_FEED: &str = r#"<feed>
+<entry><id>e1</id><title>Obras</title><cac-place-ext:ContractFolderStatus>
+  <cbc:ContractFolderID>C-1</cbc:ContractFolderID>
+  <cbc-place-ext:ContractFolderStatusCode>ADJ</cbc-place-ext:ContractFolderStatusCode>
+  <cac:ProcurementProjectLot><cbc:ID schemeName="ID_LOTE">1</cbc:ID></cac:ProcurementProjectLot>
+  <cac:ProcurementProjectLot><cbc:ID schemeName="ID_LOTE">2</cbc:ID></cac:ProcurementProjectLot>
+  <cac:TenderResult><cbc:ResultCode>8</cbc:ResultCode>
+    <cac:WinningParty><cac:PartyName><cbc:Name>Empresa A</cbc:Name></cac:PartyName></cac:WinningParty>
+    <cac:AwardedTenderedProject><cbc:ProcurementProjectLotID>1</cbc:ProcurementProjectLotID></cac:AwardedTenderedProject>
+  </cac:TenderResult>
+</cac-place-ext:ContractFolderStatus></entry>
+<entry><id>e2</id><title>Servicios</title><cac-place-ext:ContractFolderStatus>
+  <cbc:ContractFolderID>C-2</cbc:ContractFolderID>
+  <cbc-place-ext:ContractFolderStatusCode>PUB</cbc-place-ext:ContractFolderStatusCode>
+</cac-place-ext:ContractFolderStatus></entry>
+</feed>"#;
+
+fn write_period_zip(path: &Path, feed: &str) {
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+    zip.start_file("feed.atom", zip::write::FileOptions::default())
+        .unwrap();
+    zip.write_all(feed.as_bytes()).unwrap();
+    zip.finish().unwrap();
+}
+
+fn count(conn: &Connection, sql: &str) -> i64 {
+    conn.query_row(sql, [], |row| row.get(0)).unwrap()
+}
+
+#[tokio::test]
+async fn parsed_periods_are_loaded_into_normalized_tables() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let source_dir = tmp.path().join("mirror");
+    std::fs::create_dir_all(&source_dir).unwrap();
+    for period in ["202401", "202402"] {
+        write_period_zip(
+            &source_dir.join(format!(
+                "licitacionesPerfilesContratanteCompleto3_{period}.zip"
+            )),
+            SAMPLE_FEED,
+        );
+    }
+    let db_path = tmp.path().join("sppd.duckdb");
+
+    let mut options = PipelineOptions::new(ProcurementType::PublicTenders);
+    options.end_period = Some("202401".to_string());
+    options.data_dir = Some(tmp.path().join("data"));
+    options.config.source_dir = Some(source_dir);
+    options.config.duckdb_path = Some(db_path.clone());
+    options.config.progress = false;
+    run(options.clone()).await.unwrap();
+    // Reprocessing a period replaces its rows instead of duplicating them.
+    run(options.clone()).await.unwrap();
+
+    {
+        let conn = Connection::open(&db_path).unwrap();
+        assert_eq!(count(&conn, "SELECT count(*) FROM entries"), 2);
+        assert_eq!(count(&conn, "SELECT count(*) FROM project_lots"), 2);
+        assert_eq!(count(&conn, "SELECT count(*) FROM tender_results"), 1);
+        // Struct fields are flattened and child rows join back to their entry.
+        assert_eq!(
+            count(
+                &conn,
+                "SELECT count(*) FROM project_lots l JOIN entries e USING (entry_id)
+                 WHERE e.contract_id = 'C-1' AND e.status_code = 'ADJ'"
+            ),
+            2
+        );
+        let winner: String = conn
+            .query_row(
+                "SELECT r.result_winning_party FROM tender_results r
+                 JOIN entries e USING (entry_id) WHERE r.period = '202401'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(winner, "Empresa A");
+    }
+
+    options.start_period = Some("202402".to_string());
+    options.end_period = Some("202402".to_string());
+    run(options).await.unwrap();
+
+    let conn = Connection::open(&db_path).unwrap();
+    assert_eq!(
+        count(&conn, "SELECT count(DISTINCT period) FROM entries"),
+        2
+    );
+    assert_eq!(count(&conn, "SELECT count(*) FROM entries"), 4);
+    assert_eq!(
+        count(&conn, "SELECT count(DISTINCT entry_id) FROM entries"),
+        4
+    );
+    assert_eq!(count(&conn, "SELECT count(*) FROM project_lots"), 4);
+}
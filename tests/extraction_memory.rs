@@ -0,0 +1,76 @@
+//! Peak-memory regression test for ZIP extraction.
+//!
+//! A counting global allocator tracks the high-water mark of live heap bytes while a
+//! 5 MB archive is extracted. Streaming extraction should stay well below the entry size;
+//! buffering the entry (e.g. `read_to_end`) would push the peak past it.
+
+use sppd_cli::config::ResolvedConfig;
+use sppd_cli::extractor::extract_all_zips;
+use sppd_cli::models::ProcurementType;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+struct PeakAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for PeakAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let now = ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(now, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: PeakAllocator = PeakAllocator;
+
+const ENTRY_SIZE: usize = 5 * 1024 * 1024;
+
+#[tokio::test]
+async fn extraction_peak_memory_stays_below_twice_the_entry_size() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let zip_path = tmp.path().join("202401.zip");
+    {
+        let content: Vec<u8> = (0..ENTRY_SIZE as u32).map(|i| (i % 251) as u8).collect();
+        let mut writer = zip::ZipWriter::new(std::fs::File::create(&zip_path).unwrap());
+        let options =
+            zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        writer.start_file("feed.atom", options).unwrap();
+        writer.write_all(&content).unwrap();
+        writer.finish().unwrap();
+    }
+
+    let config = ResolvedConfig {
+        download_dir_pt: tmp.path().to_path_buf(),
+        concurrent_extractions: 1,
+        ..Default::default()
+    };
+    let mut links = BTreeMap::new();
+    links.insert("202401".to_string(), String::new());
+
+    let baseline = ALLOCATED.load(Ordering::Relaxed);
+    PEAK.store(baseline, Ordering::Relaxed);
+    let stats = extract_all_zips(&links, &ProcurementType::PublicTenders, &config)
+        .await
+        .unwrap();
+    let peak_delta = PEAK.load(Ordering::Relaxed).saturating_sub(baseline);
+
+    assert_eq!(stats.extracted, 1);
+    assert_eq!(stats.bytes, ENTRY_SIZE as u64);
+    assert!(
+        peak_delta < 2 * ENTRY_SIZE,
+        "extraction peak heap delta {peak_delta} bytes exceeds 2x the {ENTRY_SIZE}-byte entry"
+    );
+}
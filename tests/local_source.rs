@@ -0,0 +1,247 @@
+//! End-to-end tests for `sppd-cli cli --source-dir`.
+//!
+//! Three period ZIPs are written to a local directory; a run restricted to two of them
+//! must copy, extract, and parse exactly those two without any network access.
+
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+
+fn write_period_zip(path: &Path, id: &str) {
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+    zip.start_file("feed.atom", zip::write::FileOptions::default())
+        .unwrap();
+    write!(zip, "<feed><entry><id>{id}</id></entry></feed>").unwrap();
+    zip.finish().unwrap();
+}
+
+#[test]
+fn source_dir_processes_only_selected_periods() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let source_dir = tmp.path().join("mirror");
+    let data_dir = tmp.path().join("data");
+    std::fs::create_dir_all(&source_dir).unwrap();
+    for period in ["202401", "202402", "202403"] {
+        write_period_zip(
+            &source_dir.join(format!(
+                "licitacionesPerfilesContratanteCompleto3_{period}.zip"
+            )),
+            &format!("entry-{period}"),
+        );
+    }
+
+    let output = Command::new(env!("CARGO_BIN_EXE_sppd-cli"))
+        .args(["cli", "-t", "pt", "-s", "202402", "-e", "202403", "--quiet"])
+        .arg("--source-dir")
+        .arg(&source_dir)
+        .arg("--data-dir")
+        .arg(&data_dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "cli failed: {output:?}");
+
+    let parquet_dir = data_dir.join("parquet/pt");
+    assert!(parquet_dir.join("202402").is_dir());
+    assert!(parquet_dir.join("202403").is_dir());
+    assert!(!parquet_dir.join("202401").exists());
+    // Cleanup removes the copies but leaves the mirror alone.
+    assert!(!data_dir.join("tmp/pt/202402.zip").exists());
+    assert_eq!(std::fs::read_dir(&source_dir).unwrap().count(), 3);
+}
+
+#[test]
+fn last_months_selects_most_recent_months_up_to_today() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let source_dir = tmp.path().join("mirror");
+    let data_dir = tmp.path().join("data");
+    std::fs::create_dir_all(&source_dir).unwrap();
+    // 2019 is a yearly archive and 209912 lies in the future; neither is selected.
+    for period in ["2019", "202001", "202002", "202003", "209912"] {
+        write_period_zip(
+            &source_dir.join(format!(
+                "licitacionesPerfilesContratanteCompleto3_{period}.zip"
+            )),
+            &format!("entry-{period}"),
+        );
+    }
+
+    let output = Command::new(env!("CARGO_BIN_EXE_sppd-cli"))
+        .args(["cli", "-t", "pt", "--last-months", "2", "--quiet"])
+        .arg("--source-dir")
+        .arg(&source_dir)
+        .arg("--data-dir")
+        .arg(&data_dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "cli failed: {output:?}");
+
+    let mut periods: Vec<_> = std::fs::read_dir(data_dir.join("parquet/pt"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    periods.sort();
+    assert_eq!(periods, ["202002", "202003"]);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_sppd-cli"))
+        .args(["cli", "-t", "pt", "--last-months", "2", "-s", "202001"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn exclude_drops_periods_from_the_range() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let source_dir = tmp.path().join("mirror");
+    let data_dir = tmp.path().join("data");
+    std::fs::create_dir_all(&source_dir).unwrap();
+    for period in ["202401", "202402", "202403", "202404"] {
+        write_period_zip(
+            &source_dir.join(format!(
+                "licitacionesPerfilesContratanteCompleto3_{period}.zip"
+            )),
+            &format!("entry-{period}"),
+        );
+    }
+
+    // 202412 is outside the range, which is not an error.
+    let output = Command::new(env!("CARGO_BIN_EXE_sppd-cli"))
+        .args(["cli", "-t", "pt", "-s", "202401", "-e", "202404", "--quiet"])
+        .args(["--exclude", "202402,202403", "--exclude", "202412"])
+        .arg("--source-dir")
+        .arg(&source_dir)
+        .arg("--data-dir")
+        .arg(&data_dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "cli failed: {output:?}");
+
+    let mut periods: Vec<_> = std::fs::read_dir(data_dir.join("parquet/pt"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    periods.sort();
+    assert_eq!(periods, ["202401", "202404"]);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_sppd-cli"))
+        .args(["cli", "-t", "pt", "--exclude", "2024-02"])
+        .arg("--source-dir")
+        .arg(&source_dir)
+        .arg("--data-dir")
+        .arg(&data_dir)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn manifest_records_every_selected_period() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let source_dir = tmp.path().join("mirror");
+    let data_dir = tmp.path().join("data");
+    std::fs::create_dir_all(&source_dir).unwrap();
+    for period in ["202401", "202402"] {
+        write_period_zip(
+            &source_dir.join(format!(
+                "licitacionesPerfilesContratanteCompleto3_{period}.zip"
+            )),
+            &format!("entry-{period}"),
+        );
+    }
+
+    let output = Command::new(env!("CARGO_BIN_EXE_sppd-cli"))
+        .args(["cli", "-t", "pt", "-s", "202401", "-e", "202402", "--quiet"])
+        .args(["--manifest", "--concat-batches"])
+        .arg("--source-dir")
+        .arg(&source_dir)
+        .arg("--data-dir")
+        .arg(&data_dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "cli failed: {output:?}");
+
+    let parquet_dir = data_dir.join("parquet/pt");
+    let manifest: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(parquet_dir.join("manifest.json")).unwrap())
+            .unwrap();
+    let periods = manifest["periods"].as_array().unwrap();
+    assert_eq!(periods.len(), 2);
+    for (record, period) in periods.iter().zip(["202401", "202402"]) {
+        assert_eq!(record["period"], period);
+        assert!(record["source_url"]
+            .as_str()
+            .unwrap()
+            .ends_with(&format!("_{period}.zip")));
+        // Cleanup has removed the archive by now, but its size was recorded before.
+        assert!(record["zip_bytes"].as_u64().unwrap() > 0);
+        assert_eq!(record["xml_files"], 1);
+        assert_eq!(record["entries"], 1);
+        assert_eq!(
+            record["output_paths"][0].as_str().unwrap(),
+            parquet_dir
+                .join(format!("{period}.parquet"))
+                .to_str()
+                .unwrap()
+        );
+        assert!(record["parse_elapsed_ms"].is_u64());
+    }
+}
+
+#[test]
+fn keep_going_exits_with_partial_failure_code() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let source_dir = tmp.path().join("mirror");
+    let data_dir = tmp.path().join("data");
+    std::fs::create_dir_all(&source_dir).unwrap();
+    for period in ["202401", "202403"] {
+        write_period_zip(
+            &source_dir.join(format!(
+                "licitacionesPerfilesContratanteCompleto3_{period}.zip"
+            )),
+            &format!("entry-{period}"),
+        );
+    }
+    // Not a ZIP archive, so copying 202402 fails.
+    std::fs::write(
+        source_dir.join("licitacionesPerfilesContratanteCompleto3_202402.zip"),
+        b"<html>",
+    )
+    .unwrap();
+    let run = |keep_going: bool| {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_sppd-cli"));
+        command
+            .args(["cli", "-t", "pt", "-s", "202401", "-e", "202403", "--quiet"])
+            .arg("--source-dir")
+            .arg(&source_dir)
+            .arg("--data-dir")
+            .arg(&data_dir);
+        if keep_going {
+            command.arg("--keep-going");
+        }
+        command.output().unwrap()
+    };
+
+    let output = run(false);
+    assert_eq!(output.status.code(), Some(1), "cli output: {output:?}");
+
+    let output = run(true);
+    assert_eq!(output.status.code(), Some(2), "cli output: {output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Failed periods:"), "{stdout}");
+    assert!(stdout.contains("202402      download"), "{stdout}");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("1 period(s) failed at download, 2 succeeded"),
+        "{stderr}"
+    );
+    let parquet_dir = data_dir.join("parquet/pt");
+    assert!(parquet_dir.join("202401").is_dir());
+    assert!(parquet_dir.join("202403").is_dir());
+    assert!(!parquet_dir.join("202402").exists());
+    // The state stops before the failed period so that --since-last-run retries it.
+    let state = std::fs::read_to_string(data_dir.join(".sppd_state.json")).unwrap();
+    assert!(
+        state.contains("202401") && !state.contains("202403"),
+        "{state}"
+    );
+}
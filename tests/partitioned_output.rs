@@ -0,0 +1,173 @@
+//! End-to-end tests for `sppd-cli cli --partitioned` (Hive-style `year=YYYY/month=MM/` output).
+//!
+//! Archives come from a local `--source-dir` so no network access is needed.
+
+use polars::prelude::*;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Output};
+
+fn write_period_zip(source_dir: &Path, period: &str) {
+    let path = source_dir.join(format!(
+        "licitacionesPerfilesContratanteCompleto3_{period}.zip"
+    ));
+    let mut zip = ::zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+    for file in 0..2 {
+        zip.start_file(
+            format!("feed_{file}.atom"),
+            ::zip::write::FileOptions::default(),
+        )
+        .unwrap();
+        write!(
+            zip,
+            "<feed><entry><id>entry-{period}-{file}</id></entry></feed>"
+        )
+        .unwrap();
+    }
+    zip.finish().unwrap();
+}
+
+fn mirror(root: &Path, periods: &[&str]) -> std::path::PathBuf {
+    let source_dir = root.join("mirror");
+    std::fs::create_dir_all(&source_dir).unwrap();
+    for period in periods {
+        write_period_zip(&source_dir, period);
+    }
+    source_dir
+}
+
+fn run_cli(data_dir: &Path, source_dir: &Path, start: &str, end: &str, extra: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_sppd-cli"))
+        .args(["cli", "-t", "pt", "-s", start, "-e", end, "--quiet"])
+        .args(["--partitioned", "--batch-size", "1"])
+        .args(extra)
+        .arg("--source-dir")
+        .arg(source_dir)
+        .arg("--data-dir")
+        .arg(data_dir)
+        .output()
+        .unwrap()
+}
+
+/// Typed literal: polars 0.40 compares partition statistics against dynamic integer
+/// literals (`lit(2024)`) with a debug assertion that fails.
+fn int(value: i64) -> Expr {
+    Expr::Literal(LiteralValue::Int64(value))
+}
+
+/// Reads every partition through one hive-aware glob and returns the sorted `id`s of the
+/// rows matching `predicate`.
+fn ids_where(parquet_dir: &Path, predicate: Expr) -> Vec<String> {
+    let glob = parquet_dir.join("year=*/month=*/*.parquet");
+    let hive_schema = Schema::from_iter([
+        Field::new("year", DataType::Int64),
+        Field::new("month", DataType::Int64),
+    ]);
+    let mut args = ScanArgsParquet::default();
+    args.hive_options.schema = Some(Arc::new(hive_schema));
+    let df = LazyFrame::scan_parquet(glob.to_str().unwrap(), args)
+        .unwrap()
+        .filter(predicate)
+        .sort(["id"], SortMultipleOptions::default())
+        .collect()
+        .unwrap();
+    df.column("id")
+        .unwrap()
+        .str()
+        .unwrap()
+        .into_iter()
+        .map(|id| id.unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn partitions_support_predicate_filtered_scans() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let source_dir = mirror(tmp.path(), &["2023", "202401", "202402"]);
+    let data_dir = tmp.path().join("data");
+
+    let output = run_cli(&data_dir, &source_dir, "2023", "202402", &[]);
+    assert!(output.status.success(), "cli failed: {output:?}");
+
+    let parquet_dir = data_dir.join("parquet/pt");
+    // Year-only periods go to month=00, so every partition has the same depth.
+    for partition in [
+        "year=2023/month=00",
+        "year=2024/month=01",
+        "year=2024/month=02",
+    ] {
+        for part in ["part-0.parquet", "part-1.parquet"] {
+            assert!(
+                parquet_dir.join(partition).join(part).exists(),
+                "{partition}/{part}"
+            );
+        }
+    }
+
+    assert_eq!(
+        ids_where(
+            &parquet_dir,
+            col("year").eq(int(2024)).and(col("month").eq(int(2)))
+        ),
+        ["entry-202402-0", "entry-202402-1"]
+    );
+    assert_eq!(
+        ids_where(&parquet_dir, col("month").eq(int(0))),
+        ["entry-2023-0", "entry-2023-1"]
+    );
+    assert_eq!(ids_where(&parquet_dir, col("year").eq(int(2024))).len(), 4);
+}
+
+#[test]
+fn concatenated_partition_replaces_only_its_own_directory() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let source_dir = mirror(tmp.path(), &["202401", "202402"]);
+    let data_dir = tmp.path().join("data");
+    let parquet_dir = data_dir.join("parquet/pt");
+
+    let output = run_cli(&data_dir, &source_dir, "202401", "202402", &[]);
+    assert!(output.status.success(), "cli failed: {output:?}");
+    assert!(parquet_dir
+        .join("year=2024/month=02/part-1.parquet")
+        .exists());
+
+    let output = run_cli(
+        &data_dir,
+        &source_dir,
+        "202402",
+        "202402",
+        &["--concat-batches", "--checksums", "yes"],
+    );
+    assert!(output.status.success(), "cli failed: {output:?}");
+
+    // The re-run period now holds a single concatenated part...
+    let february: Vec<_> = std::fs::read_dir(parquet_dir.join("year=2024/month=02"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    assert_eq!(february, ["part-0.parquet"]);
+    assert_eq!(
+        std::fs::read_to_string(parquet_dir.join("year=2024/month=02.sha256"))
+            .unwrap()
+            .lines()
+            .map(|line| line.split_once("  ").unwrap().1)
+            .collect::<Vec<_>>(),
+        ["month=02/part-0.parquet"]
+    );
+    // ...while its sibling partition is untouched.
+    assert!(parquet_dir
+        .join("year=2024/month=01/part-0.parquet")
+        .exists());
+    assert!(parquet_dir
+        .join("year=2024/month=01/part-1.parquet")
+        .exists());
+    assert_eq!(
+        ids_where(&parquet_dir, col("year").eq(int(2024))),
+        [
+            "entry-202401-0",
+            "entry-202401-1",
+            "entry-202402-0",
+            "entry-202402-1"
+        ]
+    );
+}
@@ -0,0 +1,216 @@
+//! Drives the whole pipeline through the library API, without any argv parsing.
+//!
+//! Period ZIPs come from a local `source_dir`, so no network access is needed.
+
+use polars::prelude::{ParquetReader, SerReader};
+use sppd_cli::config::OutputFormat;
+use sppd_cli::models::ProcurementType;
+use sppd_cli::pipeline::{run, PipelineOptions};
+use std::io::Write;
+use std::path::Path;
+
+fn write_period_zip(path: &Path, ids: &[&str]) {
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+    zip.start_file("feed.atom", zip::write::FileOptions::default())
+        .unwrap();
+    write!(zip, "<feed>").unwrap();
+    for id in ids {
+        write!(zip, "<entry><id>{id}</id></entry>").unwrap();
+    }
+    write!(zip, "</feed>").unwrap();
+    zip.finish().unwrap();
+}
+
+#[tokio::test]
+async fn run_processes_selected_periods_into_data_dir() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let source_dir = tmp.path().join("mirror");
+    std::fs::create_dir_all(&source_dir).unwrap();
+    for (period, ids) in [
+        ("202401", &["a1"][..]),
+        ("202402", &["b1", "b2"][..]),
+        ("202403", &["c1"][..]),
+    ] {
+        write_period_zip(
+            &source_dir.join(format!("contratosMenoresPerfilesContratantes_{period}.zip")),
+            ids,
+        );
+    }
+
+    let mut options = PipelineOptions::new(ProcurementType::MinorContracts);
+    options.start_period = Some("202402".to_string());
+    options.end_period = Some("202403".to_string());
+    options.data_dir = Some(tmp.path().join("data"));
+    options.config.source_dir = Some(source_dir);
+    options.config.concat_batches = true;
+    options.config.progress = false;
+    let summary = run(options).await.unwrap();
+
+    assert_eq!(summary.periods, 2);
+    assert!(summary.partial_failure().is_none());
+    assert_eq!(summary.download.unwrap().downloaded, 2);
+    assert_eq!(summary.extraction.unwrap().extracted, 2);
+    let parse = summary.parse.unwrap();
+    assert_eq!(parse.entries, 3);
+    assert_eq!(parse.entries_by_period["202402"], 2);
+
+    let parquet_dir = tmp.path().join("data/parquet/mc");
+    let df = ParquetReader::new(std::fs::File::open(parquet_dir.join("202402.parquet")).unwrap())
+        .finish()
+        .unwrap();
+    assert_eq!(df.height(), 2);
+    assert!(!parquet_dir.join("202401.parquet").exists());
+    // Cleanup is on by default.
+    assert!(!tmp.path().join("data/tmp/mc/202402.zip").exists());
+    assert!(tmp.path().join("data/.sppd_state.json").exists());
+}
+
+#[tokio::test]
+async fn run_honours_output_format_and_output_dir() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let source_dir = tmp.path().join("mirror");
+    std::fs::create_dir_all(&source_dir).unwrap();
+    write_period_zip(
+        &source_dir.join("licitacionesPerfilesContratanteCompleto3_202401.zip"),
+        &["a1"],
+    );
+
+    let mut options = PipelineOptions::new(ProcurementType::PublicTenders);
+    options.cleanup = false;
+    options.output_format = Some(OutputFormat::Jsonl);
+    options.data_dir = Some(tmp.path().join("data"));
+    options.output_dir = Some("out".into());
+    options.config.source_dir = Some(source_dir);
+    options.config.concat_batches = true;
+    options.config.progress = false;
+    run(options).await.unwrap();
+
+    assert!(tmp.path().join("data/out/pt/202401.jsonl").exists());
+    assert!(tmp.path().join("data/tmp/pt/202401.zip").exists());
+}
+
+/// Writes a mirror with one single-entry ZIP per period and returns its directory.
+fn mirror_with_periods(root: &Path, periods: &[&str]) -> std::path::PathBuf {
+    let source_dir = root.join("mirror");
+    std::fs::create_dir_all(&source_dir).unwrap();
+    for period in periods {
+        write_period_zip(
+            &source_dir.join(format!(
+                "licitacionesPerfilesContratanteCompleto3_{period}.zip"
+            )),
+            &[&format!("id-{period}")],
+        );
+    }
+    source_dir
+}
+
+fn overlapped_options(source_dir: &Path, data_dir: &Path) -> PipelineOptions {
+    let mut options = PipelineOptions::new(ProcurementType::PublicTenders);
+    options.data_dir = Some(data_dir.to_path_buf());
+    options.config.source_dir = Some(source_dir.to_path_buf());
+    options.config.concat_batches = true;
+    options.config.progress = false;
+    options
+}
+
+#[tokio::test]
+async fn run_with_periods_in_flight_matches_stage_by_stage_run() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let source_dir = mirror_with_periods(tmp.path(), &["202401", "202402", "202403"]);
+
+    let staged = run(overlapped_options(&source_dir, &tmp.path().join("staged")))
+        .await
+        .unwrap();
+    let data_dir = tmp.path().join("overlapped");
+    let mut options = overlapped_options(&source_dir, &data_dir);
+    options.config.periods_in_flight = Some(2);
+    let overlapped = run(options).await.unwrap();
+
+    for period in ["202401", "202402", "202403"] {
+        assert!(data_dir
+            .join(format!("parquet/pt/{period}.parquet"))
+            .exists());
+        assert!(!data_dir.join(format!("tmp/pt/{period}.zip")).exists());
+        assert!(!data_dir.join(format!("tmp/pt/{period}")).exists());
+    }
+    let (staged_download, overlapped_download) =
+        (staged.download.unwrap(), overlapped.download.unwrap());
+    assert_eq!(overlapped_download.downloaded, staged_download.downloaded);
+    assert_eq!(overlapped_download.bytes, staged_download.bytes);
+    let (staged_extraction, overlapped_extraction) =
+        (staged.extraction.unwrap(), overlapped.extraction.unwrap());
+    assert_eq!(overlapped_extraction.extracted, 3);
+    assert_eq!(overlapped_extraction.bytes, staged_extraction.bytes);
+    let (staged_parse, overlapped_parse) = (staged.parse.unwrap(), overlapped.parse.unwrap());
+    assert_eq!(
+        overlapped_parse.entries_by_period,
+        staged_parse.entries_by_period
+    );
+    assert_eq!(overlapped_parse.parquet_files, staged_parse.parquet_files);
+    assert_eq!(overlapped_parse.xml_files, staged_parse.xml_files);
+}
+
+#[tokio::test]
+async fn run_with_periods_in_flight_keeps_going_past_a_failed_period() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let source_dir = mirror_with_periods(tmp.path(), &["202401", "202403"]);
+    std::fs::write(
+        source_dir.join("licitacionesPerfilesContratanteCompleto3_202402.zip"),
+        "<html>not a zip</html>",
+    )
+    .unwrap();
+    let data_dir = tmp.path().join("data");
+
+    let mut options = overlapped_options(&source_dir, &data_dir);
+    options.config.periods_in_flight = Some(3);
+    assert!(run(options.clone()).await.is_err());
+
+    options.config.keep_going = true;
+    let summary = run(options).await.unwrap();
+
+    assert!(summary.partial_failure().is_some());
+    let download = summary.download.unwrap();
+    assert_eq!(
+        download.failed_periods.keys().collect::<Vec<_>>(),
+        ["202402"]
+    );
+    assert_eq!(summary.parse.unwrap().entries_by_period.len(), 2);
+    assert!(data_dir.join("parquet/pt/202401.parquet").exists());
+    assert!(data_dir.join("parquet/pt/202403.parquet").exists());
+}
+
+#[tokio::test]
+async fn run_writes_aggregated_platforms_under_their_own_dirs() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let source_dir = tmp.path().join("mirror");
+    std::fs::create_dir_all(&source_dir).unwrap();
+    for (period, ids) in [("202401", &["a1", "a2"][..]), ("202402", &["b1"][..])] {
+        write_period_zip(
+            &source_dir.join(format!("PlataformasAgregadasSinMenores_{period}.zip")),
+            ids,
+        );
+    }
+    let data_dir = tmp.path().join("data");
+
+    let mut options = PipelineOptions::new("agregadas".parse().unwrap());
+    assert_eq!(
+        options.procurement_type,
+        ProcurementType::AggregatedPlatforms
+    );
+    options.start_period = Some("202401".to_string());
+    options.end_period = Some("202401".to_string());
+    options.cleanup = false;
+    options.data_dir = Some(data_dir.clone());
+    options.config.source_dir = Some(source_dir);
+    options.config.concat_batches = true;
+    options.config.progress = false;
+    let summary = run(options).await.unwrap();
+
+    assert_eq!(summary.periods, 1);
+    assert_eq!(summary.parse.unwrap().entries_by_period["202401"], 2);
+    assert!(data_dir.join("tmp/agg/202401.zip").exists());
+    assert!(!data_dir.join("tmp/agg/202402.zip").exists());
+    assert!(data_dir.join("parquet/agg/202401.parquet").exists());
+    assert!(!data_dir.join("parquet/mc").exists());
+    assert!(!data_dir.join("parquet/pt").exists());
+}
@@ -0,0 +1,100 @@
+//! End-to-end tests for `sppd-cli cli --no-download`, `--no-extract`, and `--no-parse`.
+//!
+//! Archives come from a local `--source-dir` so no network access is needed.
+
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Output};
+
+fn write_period_zip(path: &Path, id: &str) {
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+    zip.start_file("feed.atom", zip::write::FileOptions::default())
+        .unwrap();
+    write!(zip, "<feed><entry><id>{id}</id></entry></feed>").unwrap();
+    zip.finish().unwrap();
+}
+
+fn mirror_with_one_period(root: &Path) -> std::path::PathBuf {
+    let source_dir = root.join("mirror");
+    std::fs::create_dir_all(&source_dir).unwrap();
+    write_period_zip(
+        &source_dir.join("licitacionesPerfilesContratanteCompleto3_202402.zip"),
+        "entry-202402",
+    );
+    source_dir
+}
+
+fn run_cli(data_dir: &Path, extra_args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_sppd-cli"))
+        .args(["cli", "-t", "pt", "-s", "202402", "-e", "202402", "--quiet"])
+        .args(extra_args)
+        .arg("--data-dir")
+        .arg(data_dir)
+        .output()
+        .unwrap()
+}
+
+fn parquet_files(dir: &Path) -> Vec<std::path::PathBuf> {
+    if !dir.exists() {
+        return Vec::new();
+    }
+    walkdir::WalkDir::new(dir)
+        .into_iter()
+        .map(|entry| entry.unwrap().into_path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "parquet"))
+        .collect()
+}
+
+#[test]
+fn no_parse_keeps_files_and_no_download_resumes_from_them() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let source_dir = mirror_with_one_period(tmp.path());
+    let data_dir = tmp.path().join("data");
+
+    let output = run_cli(
+        &data_dir,
+        &["--no-parse", "--source-dir", source_dir.to_str().unwrap()],
+    );
+    assert!(output.status.success(), "cli failed: {output:?}");
+    assert!(parquet_files(&data_dir.join("parquet")).is_empty());
+    assert!(data_dir.join("tmp/pt/202402.zip").exists());
+    assert!(data_dir.join("tmp/pt/202402").is_dir());
+    assert!(!data_dir.join(".sppd_state.json").exists());
+
+    let output = run_cli(&data_dir, &["--no-download", "--no-extract"]);
+    assert!(output.status.success(), "cli failed: {output:?}");
+    assert_eq!(parquet_files(&data_dir.join("parquet/pt")).len(), 1);
+    assert!(!data_dir.join("tmp/pt/202402").exists());
+}
+
+#[test]
+fn no_extract_does_not_create_period_directories() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let source_dir = mirror_with_one_period(tmp.path());
+    let data_dir = tmp.path().join("data");
+
+    let output = run_cli(
+        &data_dir,
+        &[
+            "--no-extract",
+            "--no-cleanup",
+            "--source-dir",
+            source_dir.to_str().unwrap(),
+        ],
+    );
+    assert!(output.status.success(), "cli failed: {output:?}");
+    assert!(data_dir.join("tmp/pt/202402.zip").exists());
+    assert!(!data_dir.join("tmp/pt/202402").exists());
+    assert!(parquet_files(&data_dir.join("parquet")).is_empty());
+}
+
+#[test]
+fn skipping_every_step_is_rejected() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let output = run_cli(
+        &tmp.path().join("data"),
+        &["--no-download", "--no-extract", "--no-parse"],
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("nothing to do"));
+}